@@ -137,6 +137,59 @@ async fn client_enabled_server_enabled_multi_encoding(encoding: CompressionEncod
     }
 }
 
+util::parametrized_tests! {
+    request_encoding_is_exposed_to_handlers,
+    zstd: CompressionEncoding::Zstd,
+    gzip: CompressionEncoding::Gzip,
+}
+
+#[allow(dead_code)]
+async fn request_encoding_is_exposed_to_handlers(encoding: CompressionEncoding) {
+    let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
+
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
+            .await
+            .unwrap();
+    });
+
+    let channel = mock_io_channel(client).await;
+    let data = || SomeData {
+        data: [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec(),
+    };
+
+    let mut client = test_client::TestClient::new(channel.clone()).send_compressed(encoding);
+    let res = client.compress_input_unary(data()).await.unwrap();
+    assert_eq!(
+        res.metadata().get("request-encoding").unwrap(),
+        &encoding.to_string()
+    );
+
+    let res = client
+        .compress_input_client_stream(tokio_stream::iter(vec![data(), data()]))
+        .await
+        .unwrap();
+    assert_eq!(
+        res.metadata().get("request-encoding").unwrap(),
+        &encoding.to_string()
+    );
+
+    // Uncompressed requests report no encoding, whether or not they declare `identity`.
+    let mut client = test_client::TestClient::new(channel.clone());
+    let res = client.compress_input_unary(data()).await.unwrap();
+    assert_eq!(res.metadata().get("request-encoding").unwrap(), "identity");
+
+    let mut req = Request::new(data());
+    req.metadata_mut()
+        .insert("grpc-encoding", "identity".parse().unwrap());
+    let res = client.compress_input_unary(req).await.unwrap();
+    assert_eq!(res.metadata().get("request-encoding").unwrap(), "identity");
+}
+
 parametrized_tests! {
     client_enabled_server_disabled,
     zstd: CompressionEncoding::Zstd,
@@ -44,6 +44,19 @@ impl Svc {
     }
 }
 
+/// Echoes the encoding the server decoded the request with in the response metadata.
+fn echo_request_encoding<T, U>(req: &Request<T>, mut res: Response<U>) -> Response<U> {
+    let encoding = req
+        .extensions()
+        .get::<tonic::codec::RequestEncoding>()
+        .expect("request encoding extension")
+        .encoding()
+        .map_or_else(|| "identity".to_owned(), |encoding| encoding.to_string());
+    res.metadata_mut()
+        .insert("request-encoding", encoding.parse().unwrap());
+    res
+}
+
 #[tonic::async_trait]
 impl test_server::Test for Svc {
     async fn compress_output_unary(&self, _req: Request<()>) -> Result<Response<SomeData>, Status> {
@@ -55,8 +68,9 @@ impl test_server::Test for Svc {
     }
 
     async fn compress_input_unary(&self, req: Request<SomeData>) -> Result<Response<()>, Status> {
+        let res = echo_request_encoding(&req, Response::new(()));
         assert_eq!(req.into_inner().data.len(), UNCOMPRESSED_MIN_BODY_SIZE);
-        Ok(Response::new(()))
+        Ok(res)
     }
 
     type CompressOutputServerStreamStream =
@@ -77,11 +91,12 @@ impl test_server::Test for Svc {
         &self,
         req: Request<Streaming<SomeData>>,
     ) -> Result<Response<()>, Status> {
+        let res = echo_request_encoding(&req, Response::new(()));
         let mut stream = req.into_inner();
         while let Some(item) = stream.next().await {
             item.unwrap();
         }
-        Ok(self.prepare_response(Response::new(())))
+        Ok(self.prepare_response(res))
     }
 
     async fn compress_output_client_stream(
@@ -1,4 +1,19 @@
 fn main() {
     tonic_build::compile_protos("proto/test.proto").unwrap();
     tonic_build::compile_protos("proto/stream.proto").unwrap();
+
+    let echo = tonic_build::manual::Service::builder()
+        .name("Echo")
+        .package("json")
+        .method(
+            tonic_build::manual::Method::builder()
+                .name("echo")
+                .route_name("Echo")
+                .input_type("crate::json::Message")
+                .output_type("crate::json::Message")
+                .codec_path("crate::json::JsonCodec")
+                .build(),
+        )
+        .build();
+    tonic_build::manual::Builder::new().compile(&[echo]);
 }
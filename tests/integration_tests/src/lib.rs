@@ -3,6 +3,86 @@ pub mod pb {
     tonic::include_proto!("stream");
 }
 
+pub mod json {
+    use bytes::{Buf, BufMut};
+    use serde::{Deserialize, Serialize};
+    use std::marker::PhantomData;
+    use tonic::{
+        codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
+        Status,
+    };
+
+    include!(concat!(env!("OUT_DIR"), "/json.Echo.rs"));
+
+    #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+    pub struct Message {
+        pub text: String,
+    }
+
+    /// A [`Codec`] for `application/grpc+json`.
+    #[derive(Debug, Clone)]
+    pub struct JsonCodec<T, U>(PhantomData<(T, U)>);
+
+    impl<T, U> Default for JsonCodec<T, U> {
+        fn default() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    impl<T, U> Codec for JsonCodec<T, U>
+    where
+        T: Serialize + Send + 'static,
+        U: serde::de::DeserializeOwned + Send + 'static,
+    {
+        type Encode = T;
+        type Decode = U;
+        type Encoder = JsonEncoder<T>;
+        type Decoder = JsonDecoder<U>;
+
+        fn encoder(&mut self) -> Self::Encoder {
+            JsonEncoder(PhantomData)
+        }
+
+        fn decoder(&mut self) -> Self::Decoder {
+            JsonDecoder(PhantomData)
+        }
+
+        fn content_subtype(&self) -> Option<&'static str> {
+            Some("json")
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct JsonEncoder<T>(PhantomData<T>);
+
+    impl<T: Serialize> Encoder for JsonEncoder<T> {
+        type Item = T;
+        type Error = Status;
+
+        fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+            serde_json::to_writer(buf.writer(), &item).map_err(|e| Status::internal(e.to_string()))
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct JsonDecoder<U>(PhantomData<U>);
+
+    impl<U: serde::de::DeserializeOwned> Decoder for JsonDecoder<U> {
+        type Item = U;
+        type Error = Status;
+
+        fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+            if !buf.has_remaining() {
+                return Ok(None);
+            }
+
+            serde_json::from_reader(buf.reader())
+                .map(Some)
+                .map_err(|e| Status::internal(e.to_string()))
+        }
+    }
+}
+
 pub mod mock {
     use std::{
         io::IoSlice,
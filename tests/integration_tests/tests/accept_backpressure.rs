@@ -0,0 +1,116 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+use tower::{layer::layer_fn, Service};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// Keeps the services it wraps not ready until it is opened.
+#[derive(Clone, Default)]
+struct Gate(Arc<Mutex<(bool, Vec<Waker>)>>);
+
+impl Gate {
+    fn open(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.0 = true;
+        state.1.drain(..).for_each(Waker::wake);
+    }
+}
+
+#[derive(Clone)]
+struct Gated<S> {
+    inner: S,
+    gate: Gate,
+}
+
+impl<S, R> Service<R> for Gated<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        {
+            let mut state = self.gate.0.lock().unwrap();
+            if !state.0 {
+                state.1.push(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+#[tokio::test]
+async fn connections_are_not_accepted_while_not_ready() {
+    let (tx, rx) = oneshot::channel();
+    let gate = Gate::default();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let builder = Server::builder();
+    let stats = builder.stats();
+    let router = builder
+        .layer(layer_fn({
+            let gate = gate.clone();
+            move |inner| Gated {
+                inner,
+                gate: gate.clone(),
+            }
+        }))
+        .add_service(test_server::TestServer::new(Svc));
+
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    // The connection is established by the operating system, but the server does not accept it.
+    let queued = TcpStream::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(stats.snapshot().active_connections(), 0);
+
+    gate.open();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(stats.snapshot().active_connections(), 1);
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    client.unary_call(Input {}).await.unwrap();
+
+    drop(queued);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
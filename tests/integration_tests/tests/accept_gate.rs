@@ -0,0 +1,63 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn closed_gate_pauses_accepts() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let open = Arc::new(AtomicBool::new(false));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let mut builder = Server::builder().accept_gate({
+        let open = open.clone();
+        move || open.load(Ordering::Relaxed)
+    });
+    let stats = builder.stats();
+    let router = builder.add_service(test_server::TestServer::new(Svc));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let call = tokio::spawn(async move {
+        let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        client.unary_call(Input {}).await.unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!call.is_finished());
+    assert_eq!(stats.snapshot().total_connections(), 0);
+
+    open.store(true, Ordering::Relaxed);
+    call.await.unwrap();
+    assert_eq!(stats.snapshot().total_connections(), 1);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,84 @@
+#![cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+fn free_addr() -> SocketAddr {
+    StdTcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn workers_accept_concurrently() {
+    let listeners = Arc::new(AtomicUsize::new(0));
+
+    let (tx, rx) = oneshot::channel();
+    let addr = free_addr();
+
+    let mut builder = Server::builder().accept_workers(2).configure_listener({
+        let listeners = listeners.clone();
+        move |_| {
+            listeners.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    });
+    let stats = builder.stats();
+    let server = builder.add_service(test_server::TestServer::new(Svc));
+
+    let jh = tokio::spawn(async move {
+        server
+            .serve_with_shutdown(addr, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // One listening socket per worker, all bound to the same address.
+    assert_eq!(listeners.load(Ordering::SeqCst), 2);
+
+    let calls = (0..16)
+        .map(|_| {
+            tokio::spawn(async move {
+                let channel = Endpoint::from_shared(format!("http://{addr}"))
+                    .unwrap()
+                    .connect()
+                    .await
+                    .unwrap();
+                TestClient::new(channel)
+                    .unary_call(Request::new(Input {}))
+                    .await
+                    .unwrap();
+            })
+        })
+        .collect::<Vec<_>>();
+    for call in calls {
+        call.await.unwrap();
+    }
+
+    assert_eq!(stats.snapshot().total_connections(), 16);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,154 @@
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use std::{
+    io::Write,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    transport::{
+        server::{AccessLogFormat, AccessLogLayer, TcpIncoming},
+        Endpoint, Server,
+    },
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1 {
+            buf: req.into_inner().buf,
+        }))
+    }
+
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let output = Output1 {
+            buf: b"pong".to_vec(),
+        };
+        let stream = tokio_stream::iter([
+            Ok(output.clone()),
+            Ok(output.clone()),
+            Ok(output),
+            Err(Status::not_found("end of stream")),
+        ]);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[derive(Clone, Default)]
+struct Records(Arc<Mutex<Vec<u8>>>);
+
+impl Records {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+impl Write for Records {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn field<'a>(record: &'a str, key: &str) -> &'a str {
+    record
+        .split(' ')
+        .find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+        .unwrap_or_else(|| panic!("no `{key}` in {record:?}"))
+}
+
+#[tokio::test]
+async fn records_completed_calls() {
+    let records = Records::default();
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let layer = AccessLogLayer::new()
+        .format(AccessLogFormat::Logfmt)
+        .writer(records.clone());
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .layer(layer)
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = Test1Client::new(channel);
+
+    client
+        .unary_call(Input1 {
+            buf: b"ping".to_vec(),
+        })
+        .await
+        .unwrap();
+
+    let records_after_unary = records.lines();
+    assert_eq!(records_after_unary.len(), 1);
+    let record = &records_after_unary[0];
+
+    assert!(field(record, "timestamp").parse::<f64>().unwrap() > 0.0);
+    assert_eq!(field(record, "method"), "/test.Test1/UnaryCall");
+    assert_eq!(
+        field(record, "peer")
+            .parse::<std::net::SocketAddr>()
+            .unwrap()
+            .ip(),
+        addr.ip()
+    );
+    assert_eq!(field(record, "status"), "0");
+    assert!(field(record, "duration_ms").parse::<f64>().unwrap() >= 0.0);
+    // 5 bytes of gRPC framing plus the encoded 4 byte message
+    assert_eq!(field(record, "request_bytes"), "11");
+    assert_eq!(field(record, "response_bytes"), "11");
+
+    let mut stream = client
+        .stream_call(Input1 { buf: Vec::new() })
+        .await
+        .unwrap()
+        .into_inner();
+
+    for _ in 0..3 {
+        stream.next().await.unwrap().unwrap();
+    }
+    let status = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    // The stream is recorded once, with its final status.
+    let lines = records.lines();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(field(&lines[1], "method"), "/test.Test1/StreamCall");
+    assert_eq!(field(&lines[1], "status"), "5");
+    assert_eq!(field(&lines[1], "response_bytes"), "33");
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,87 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Endpoint, Server},
+    Baggage, Request, Response, Status,
+};
+
+#[tokio::test]
+async fn forwards_baggage_downstream() {
+    struct Backend;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Backend {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let baggage = req.extensions().get::<Baggage>();
+            match baggage.and_then(|baggage| baggage.get("tenant")) {
+                Some("acme corp") => Ok(Response::new(Output {})),
+                tenant => Err(Status::internal(format!("unexpected tenant: {tenant:?}"))),
+            }
+        }
+    }
+
+    struct Frontend(Channel);
+
+    #[tonic::async_trait]
+    impl test_server::Test for Frontend {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let baggage = req
+                .extensions()
+                .get::<Baggage>()
+                .cloned()
+                .unwrap_or_default();
+            if baggage.get("tenant") != Some("acme corp") {
+                return Err(Status::internal("baggage is missing"));
+            }
+
+            let mut outbound = Request::new(Input {});
+            outbound.set_baggage(&baggage);
+            test_client::TestClient::new(self.0.clone())
+                .unary_call(outbound)
+                .await
+        }
+    }
+
+    let backend = serve(test_server::TestServer::new(Backend)).await;
+    let channel = Endpoint::from_shared(format!("http://{backend}"))
+        .unwrap()
+        .connect_lazy();
+    let frontend = serve(test_server::TestServer::new(Frontend(channel))).await;
+
+    let channel = Endpoint::from_shared(format!("http://{frontend}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    let mut baggage = Baggage::new();
+    baggage.insert("tenant", "acme corp");
+    let mut request = Request::new(Input {});
+    request.set_baggage(&baggage);
+
+    match client.unary_call(request).await {
+        Ok(_) => {}
+        Err(status) => panic!("{}", status.message()),
+    }
+}
+
+async fn serve<S>(svc: test_server::TestServer<S>) -> SocketAddr
+where
+    S: test_server::Test,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap();
+    });
+
+    addr
+}
@@ -0,0 +1,102 @@
+use hyper_util::rt::TokioIo;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, oneshot},
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{
+    transport::{server::BoxedIo, Channel, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        assert_eq!(req.remote_addr(), None);
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// A channel over a single, already established connection.
+async fn connect<IO>(io: IO) -> Channel
+where
+    IO: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin + 'static,
+{
+    let mut io = Some(io);
+    Endpoint::from_static("http://[::]:50051")
+        .connect_with_connector(tower::service_fn(move |_| {
+            let io = io.take();
+            async move {
+                io.map(TokioIo::new)
+                    .ok_or_else(|| std::io::Error::other("the connection was already used"))
+            }
+        }))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn serves_boxed_connections() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let (conn_tx, conn_rx) = mpsc::channel::<Result<BoxedIo, std::io::Error>>(1);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_boxed_io_shutdown(ReceiverStream::new(conn_rx), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    // An in-memory connection.
+    let (client, server) = tokio::io::duplex(1024);
+    conn_tx.send(Ok(BoxedIo::new(server))).await.unwrap();
+    let mut duplex_client = test_client::TestClient::new(connect(client).await);
+    duplex_client
+        .unary_call(Request::new(Input {}))
+        .await
+        .unwrap();
+
+    // Errors are skipped, and other kinds of connections are served by the same stream.
+    conn_tx
+        .send(Err(std::io::Error::other("handshake failed")))
+        .await
+        .unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (server, _) = listener.accept().await.unwrap();
+    conn_tx.send(Ok(BoxedIo::new(server))).await.unwrap();
+    let mut tcp_client = test_client::TestClient::new(connect(client).await);
+    tcp_client.unary_call(Request::new(Input {})).await.unwrap();
+
+    drop(duplex_client);
+    drop(tcp_client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn serve_ends_with_the_stream() {
+    let (client, server) = tokio::io::duplex(1024);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_boxed_io(tokio_stream::once(Ok::<_, std::io::Error>(BoxedIo::new(
+                server,
+            ))))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::new(connect(client).await);
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    drop(client);
+    jh.await.unwrap();
+}
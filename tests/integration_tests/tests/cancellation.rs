@@ -0,0 +1,226 @@
+use integration_tests::pb::{
+    test_client::TestClient, test_server, test_stream_client::TestStreamClient, test_stream_server,
+    Input, InputStream, Output, OutputStream,
+};
+use std::{pin::Pin, time::Duration};
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, oneshot},
+};
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc(mpsc::Sender<()>);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let token = req.extensions().get::<CancellationToken>().unwrap().clone();
+
+        // Report cancellation from a separate task, the handler future itself is
+        // dropped when the client goes away.
+        let cancelled = self.0.clone();
+        tokio::spawn(async move {
+            token.cancelled().await;
+            cancelled.send(()).await.unwrap();
+        });
+
+        std::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn client_disconnect_cancels_token() {
+    let (cancelled_tx, mut cancelled_rx) = mpsc::channel(1);
+    let (tx, rx) = oneshot::channel();
+    let svc = test_server::TestServer::new(Svc(cancelled_tx));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    // Give up on the call, which resets the stream.
+    let res = tokio::time::timeout(Duration::from_millis(100), client.unary_call(Input {})).await;
+    assert!(res.is_err());
+
+    tokio::time::timeout(Duration::from_secs(1), cancelled_rx.recv())
+        .await
+        .expect("token was not cancelled")
+        .unwrap();
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn deadline_cancels_token() {
+    let (cancelled_tx, mut cancelled_rx) = mpsc::channel(1);
+    let (tx, rx) = oneshot::channel();
+    let svc = test_server::TestServer::new(Svc(cancelled_tx));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    let mut req = Request::new(Input {});
+    req.set_timeout(Duration::from_millis(100));
+    let status = client.unary_call(req).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Cancelled);
+
+    tokio::time::timeout(Duration::from_secs(1), cancelled_rx.recv())
+        .await
+        .expect("token was not cancelled")
+        .unwrap();
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+/// Reports when the handler state it is moved into is dropped.
+struct DropSignal(Option<oneshot::Sender<()>>);
+
+impl Drop for DropSignal {
+    fn drop(&mut self) {
+        if let Some(tx) = self.0.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+struct PendingSvc(mpsc::Sender<oneshot::Receiver<()>>);
+
+#[tonic::async_trait]
+impl test_server::Test for PendingSvc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        let (tx, rx) = oneshot::channel();
+        let _signal = DropSignal(Some(tx));
+        self.0.send(rx).await.unwrap();
+
+        std::future::pending().await
+    }
+}
+
+#[tonic::async_trait]
+impl test_stream_server::TestStream for PendingSvc {
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<OutputStream, Status>> + Send>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<InputStream>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let (tx, rx) = oneshot::channel();
+        let signal = DropSignal(Some(tx));
+        self.0.send(rx).await.unwrap();
+
+        let stream = tokio_stream::once(Ok(OutputStream {}))
+            .chain(tokio_stream::pending())
+            .map(move |item| {
+                let _ = &signal;
+                item
+            });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tokio::test]
+async fn client_cancel_drops_handlers_and_records_cancelled() {
+    let (started_tx, mut started_rx) = mpsc::channel(1);
+    let (tx, rx) = oneshot::channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let mut builder = Server::builder();
+    let stats = builder.stats();
+    let router = builder
+        .add_service(test_server::TestServer::new(PendingSvc(started_tx.clone())))
+        .add_service(test_stream_server::TestStreamServer::new(PendingSvc(
+            started_tx,
+        )));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    // Cancel a unary call while its handler runs.
+    let mut client = TestClient::new(channel.clone());
+    let call = tokio::spawn(async move { client.unary_call(Input {}).await });
+    let dropped = started_rx.recv().await.unwrap();
+    call.abort();
+    tokio::time::timeout(Duration::from_secs(1), dropped)
+        .await
+        .expect("unary handler was not dropped")
+        .unwrap();
+
+    // Cancel a server streaming call after its first message.
+    let mut client = TestStreamClient::new(channel);
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    let dropped = started_rx.recv().await.unwrap();
+    stream.message().await.unwrap().unwrap();
+    drop(stream);
+    tokio::time::timeout(Duration::from_secs(1), dropped)
+        .await
+        .expect("response stream was not dropped")
+        .unwrap();
+
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.errors_by_code().get(&Code::Cancelled), Some(&2));
+    assert_eq!(snapshot.active_requests(), 0);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
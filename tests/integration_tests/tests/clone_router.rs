@@ -0,0 +1,79 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn cloned_router_serves_multiple_listeners() {
+    let server = Server::builder();
+    let stats = server.stats();
+    let router = server
+        .timeout(Duration::from_secs(5))
+        .add_service(test_server::TestServer::new(Svc));
+
+    let mut addrs = Vec::<SocketAddr>::new();
+    let mut shutdowns = Vec::new();
+    let mut handles = Vec::new();
+
+    // One listener on this runtime, one on a runtime of its own.
+    for own_runtime in [false, true] {
+        let (tx, rx) = oneshot::channel::<()>();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        addrs.push(listener.local_addr().unwrap());
+        let std_listener = listener.into_std().unwrap();
+
+        let router = router.clone();
+        let serve = async move {
+            let listener = TcpListener::from_std(std_listener).unwrap();
+            router
+                .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+                .await
+                .unwrap();
+        };
+
+        let handle = if own_runtime {
+            let thread = std::thread::spawn(move || {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(serve)
+            });
+            tokio::task::spawn_blocking(move || thread.join().unwrap())
+        } else {
+            tokio::spawn(serve)
+        };
+
+        shutdowns.push(tx);
+        handles.push(handle);
+    }
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    for addr in &addrs {
+        let mut client = TestClient::connect(format!("http://{addr}")).await.unwrap();
+        client.unary_call(Request::new(Input {})).await.unwrap();
+    }
+
+    assert_eq!(stats.snapshot().total_requests(), 2);
+
+    for tx in shutdowns {
+        tx.send(()).unwrap();
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+use hyper_util::rt::TokioIo;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// A channel over a single connection, which fails once that connection is closed.
+async fn connect(stream: TcpStream) -> Channel {
+    let mut stream = Some(stream);
+    Endpoint::from_static("http://[::]:50051")
+        .connect_with_connector(tower::service_fn(move |_| {
+            let stream = stream.take();
+            async move {
+                stream
+                    .map(TokioIo::new)
+                    .ok_or_else(|| std::io::Error::other("the connection was closed"))
+            }
+        }))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn closes_matching_connections() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let mut builder = Server::builder();
+    let connections = builder.connections();
+    let router = builder.add_service(test_server::TestServer::new(Svc));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let abuser = TcpStream::connect(addr).await.unwrap();
+    let abuser_addr = abuser.local_addr().unwrap();
+    let mut abuser = test_client::TestClient::new(connect(abuser).await);
+    let mut other =
+        test_client::TestClient::new(connect(TcpStream::connect(addr).await.unwrap()).await);
+    abuser.unary_call(Request::new(Input {})).await.unwrap();
+    other.unary_call(Request::new(Input {})).await.unwrap();
+
+    assert_eq!(
+        connections.close_connections(|peer| *peer == abuser_addr),
+        1
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    abuser.unary_call(Request::new(Input {})).await.unwrap_err();
+    other.unary_call(Request::new(Input {})).await.unwrap();
+    assert_eq!(
+        connections.close_connections(|peer| *peer == abuser_addr),
+        0
+    );
+
+    drop(abuser);
+    drop(other);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
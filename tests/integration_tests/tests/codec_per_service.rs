@@ -0,0 +1,101 @@
+use http::{header::CONTENT_TYPE, HeaderValue};
+use integration_tests::{
+    json::{self, echo_client::EchoClient, echo_server},
+    pb::{test_client::TestClient, test_server, Input, Output},
+};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+use tower::ServiceBuilder;
+use tower_http::set_header::SetRequestHeaderLayer;
+
+struct ProtoSvc;
+
+#[tonic::async_trait]
+impl test_server::Test for ProtoSvc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+struct JsonSvc;
+
+#[tonic::async_trait]
+impl echo_server::Echo for JsonSvc {
+    async fn echo(
+        &self,
+        request: Request<json::Message>,
+    ) -> Result<Response<json::Message>, Status> {
+        Ok(Response::new(request.into_inner()))
+    }
+}
+
+#[tokio::test]
+async fn services_use_their_own_codec() {
+    let (tx, rx) = oneshot::channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(ProtoSvc))
+            .add_service(echo_server::EchoServer::new(JsonSvc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let response = TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap();
+    assert_eq!(
+        response.metadata().get(CONTENT_TYPE.as_str()).unwrap(),
+        "application/grpc"
+    );
+
+    let message = json::Message {
+        text: "hello".to_owned(),
+    };
+    let response = EchoClient::new(channel.clone())
+        .echo(message.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        response.metadata().get(CONTENT_TYPE.as_str()).unwrap(),
+        "application/grpc+json"
+    );
+    assert_eq!(response.into_inner(), message);
+
+    // A json request claiming to be encoded with another codec is rejected.
+    let mut client = EchoClient::new(
+        ServiceBuilder::new()
+            .layer(SetRequestHeaderLayer::overriding(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/grpc+proto"),
+            ))
+            .service(channel),
+    );
+    let status = client.echo(message).await.unwrap_err();
+    assert_eq!(status.code(), Code::Internal);
+    assert_eq!(
+        status.message(),
+        "no codec registered for content-subtype proto"
+    );
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
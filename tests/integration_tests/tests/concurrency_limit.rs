@@ -0,0 +1,238 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+#[tokio::test]
+async fn requests_beyond_per_connection_limit_are_queued() {
+    const LIMIT: usize = 2;
+    const HANDLER_DURATION: Duration = Duration::from_millis(200);
+
+    #[derive(Default)]
+    struct State {
+        active: AtomicUsize,
+        max_active: AtomicUsize,
+    }
+
+    struct Svc(Arc<State>);
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            let active = self.0.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.0.max_active.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(HANDLER_DURATION).await;
+            self.0.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let state = Arc::new(State::default());
+    let svc = test_server::TestServer::new(Svc(state.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .concurrency_limit_per_connection(LIMIT)
+            .max_concurrent_streams(100)
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let start = Instant::now();
+    let calls = (0..=LIMIT)
+        .map(|_| {
+            let mut client = TestClient::new(channel.clone());
+            tokio::spawn(async move { client.unary_call(Input {}).await })
+        })
+        .collect::<Vec<_>>();
+
+    for call in calls {
+        call.await.unwrap().unwrap();
+    }
+
+    // The request beyond the limit only starts once one of the others completed.
+    assert_eq!(state.max_active.load(Ordering::SeqCst), LIMIT);
+    assert!(start.elapsed() >= HANDLER_DURATION * 2);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn saturated_limit_rejects_requests_with_short_deadlines() {
+    const HANDLER_DURATION: Duration = Duration::from_millis(500);
+
+    struct Svc(Arc<AtomicUsize>);
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(HANDLER_DURATION).await;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let svc = test_server::TestServer::new(Svc(calls.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .concurrency_limit_per_connection(1)
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel.clone());
+
+    // Let the server learn how long requests take.
+    client.unary_call(Input {}).await.unwrap();
+
+    let busy = {
+        let mut client = client.clone();
+        tokio::spawn(async move { client.unary_call(Input {}).await })
+    };
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut request = Request::new(Input {});
+    request.set_timeout(Duration::from_millis(300));
+    let start = Instant::now();
+    let status = client.unary_call(request).await.unwrap_err();
+
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+    assert!(start.elapsed() < Duration::from_millis(200));
+
+    // Requests whose deadline leaves enough time are queued instead.
+    let mut request = Request::new(Input {});
+    request.set_timeout(Duration::from_secs(5));
+    client.unary_call(request).await.unwrap();
+
+    busy.await.unwrap().unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn expensive_requests_take_more_of_the_limit() {
+    const LIMIT: usize = 4;
+    const HANDLER_DURATION: Duration = Duration::from_millis(200);
+
+    #[derive(Default)]
+    struct State {
+        active: AtomicUsize,
+        max_active: AtomicUsize,
+    }
+
+    struct Svc(Arc<State>);
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            let active = self.0.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.0.max_active.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(HANDLER_DURATION).await;
+            self.0.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let state = Arc::new(State::default());
+    let svc = test_server::TestServer::new(Svc(state.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .concurrency_limit_per_connection(LIMIT)
+            .cost_fn(|request| match request.headers().get("x-cost") {
+                Some(cost) => cost.to_str().unwrap().parse().unwrap(),
+                None => 1,
+            })
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let run = |count: usize, cost: Option<&'static str>| {
+        let calls = (0..count)
+            .map(|_| {
+                let mut client = TestClient::new(channel.clone());
+                let mut request = Request::new(Input {});
+                if let Some(cost) = cost {
+                    request
+                        .metadata_mut()
+                        .insert("x-cost", cost.parse().unwrap());
+                }
+                tokio::spawn(async move { client.unary_call(request).await })
+            })
+            .collect::<Vec<_>>();
+        async move {
+            for call in calls {
+                call.await.unwrap().unwrap();
+            }
+        }
+    };
+
+    // Cheap requests fill the limit one slot at a time.
+    run(LIMIT, None).await;
+    assert_eq!(state.max_active.swap(0, Ordering::SeqCst), LIMIT);
+
+    // Each expensive request takes half of the limit.
+    let start = Instant::now();
+    run(3, Some("2")).await;
+    assert_eq!(state.max_active.swap(0, Ordering::SeqCst), 2);
+    assert!(start.elapsed() >= HANDLER_DURATION * 2);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
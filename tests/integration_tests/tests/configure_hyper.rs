@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use integration_tests::pb::{test_server, Input, Output};
+use tonic::transport::{
+    server::{HyperBuilder, TcpIncoming},
+    Server,
+};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn hyper_options_set_through_the_callback_take_effect() {
+    let svc = test_server::TestServer::new(Svc);
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            // Not wrapped by tonic, announced in the SETTINGS frame of the server.
+            .configure_hyper(|builder| {
+                if let HyperBuilder::Http2(http2) = builder {
+                    http2.enable_connect_protocol();
+                }
+            })
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let io = TcpStream::connect(addr).await.unwrap();
+    let (client, connection) = h2::client::handshake(io).await.unwrap();
+    tokio::spawn(connection);
+
+    // The settings of the server are known once it answered a request.
+    let mut client = client.ready().await.unwrap();
+    let request = http::Request::post(format!("http://{addr}/test.Test/UnaryCall"))
+        .header("content-type", "application/grpc")
+        .body(())
+        .unwrap();
+    let (response, _) = client.send_request(request, true).unwrap();
+    response.await.unwrap();
+    assert!(client.is_extended_connect_protocol_enabled());
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
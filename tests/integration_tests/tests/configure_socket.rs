@@ -0,0 +1,169 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::io;
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::{io::AsyncReadExt, net::TcpStream, sync::oneshot};
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+fn free_addr() -> SocketAddr {
+    StdTcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn socket_callbacks_run() {
+    let listeners = Arc::new(AtomicUsize::new(0));
+    let sockets = Arc::new(AtomicUsize::new(0));
+
+    let (tx, rx) = oneshot::channel();
+    let addr = free_addr();
+
+    let server = Server::builder()
+        .configure_listener({
+            let listeners = listeners.clone();
+            move |socket| {
+                listeners.fetch_add(1, Ordering::SeqCst);
+                socket.set_reuse_address(true)
+            }
+        })
+        .configure_socket({
+            let sockets = sockets.clone();
+            move |socket| {
+                sockets.fetch_add(1, Ordering::SeqCst);
+                socket.set_send_buffer_size(64 * 1024)
+            }
+        })
+        .add_service(test_server::TestServer::new(Svc));
+
+    let jh = tokio::spawn(async move {
+        server
+            .serve_with_shutdown(addr, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    assert_eq!(listeners.load(Ordering::SeqCst), 1);
+    assert_eq!(sockets.load(Ordering::SeqCst), 1);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn listener_callback_errors_are_returned() {
+    let err = Server::builder()
+        .configure_listener(|_| Err(io::Error::other("rejected by callback")))
+        .add_service(test_server::TestServer::new(Svc))
+        .serve(free_addr())
+        .await
+        .unwrap_err();
+
+    let source = std::error::Error::source(&err).unwrap();
+    assert_eq!(source.to_string(), "rejected by callback");
+}
+
+#[tokio::test]
+async fn socket_callback_errors_close_the_connection() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let (tx, rx) = oneshot::channel();
+    let addr = free_addr();
+
+    let server = Server::builder()
+        .configure_socket({
+            let attempts = attempts.clone();
+            move |_| {
+                // Reject the first connection only.
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(io::Error::other("rejected by callback")),
+                    _ => Ok(()),
+                }
+            }
+        })
+        .add_service(test_server::TestServer::new(Svc));
+
+    let jh = tokio::spawn(async move {
+        server
+            .serve_with_shutdown(addr, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // The server closes the connection instead of sending its HTTP/2 settings.
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut buf = [0; 16];
+    assert!(matches!(stream.read(&mut buf).await, Ok(0) | Err(_)));
+
+    // The server keeps accepting connections.
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn server_binds_with_custom_backlog() {
+    let (tx, rx) = oneshot::channel();
+    let addr = free_addr();
+
+    let server = Server::builder()
+        .tcp_backlog(4096)
+        .add_service(test_server::TestServer::new(Svc));
+
+    let jh = tokio::spawn(async move {
+        server
+            .serve_with_shutdown(addr, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
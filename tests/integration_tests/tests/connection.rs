@@ -1,11 +1,17 @@
+use hyper_util::rt::TokioIo;
 use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use tokio::{net::TcpListener, sync::oneshot};
+use std::time::{Duration, Instant};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
 use tonic::{
+    body::Body,
     transport::{server::TcpIncoming, Endpoint, Server},
     Code, Request, Response, Status,
 };
+use tower_service::Service;
 
 struct Svc(Arc<Mutex<Option<oneshot::Sender<()>>>>);
 
@@ -26,6 +32,80 @@ async fn connect_returns_err() {
     assert!(res.is_err());
 }
 
+#[tokio::test]
+async fn connect_fails_promptly_against_closed_port() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let start = Instant::now();
+    let res = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect_timeout(Duration::from_secs(5))
+        .connect()
+        .await;
+
+    assert!(res.is_err());
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn connect_times_out_when_connection_setup_stalls() {
+    let start = Instant::now();
+    let res = Endpoint::from_static("http://127.0.0.1:1")
+        .connect_timeout(Duration::from_millis(100))
+        .connect_with_connector(tower::service_fn(|_: http::Uri| {
+            std::future::pending::<Result<TokioIo<TcpStream>, std::io::Error>>()
+        }))
+        .await;
+
+    assert!(res.is_err());
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn connect_returns_ready_channel() {
+    let (tx, rx) = oneshot::channel();
+    let sender = Arc::new(Mutex::new(Some(tx)));
+    let svc = test_server::TestServer::new(Svc(sender));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let mut channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect_timeout(Duration::from_secs(5))
+        .connect()
+        .await
+        .unwrap();
+
+    // The connection is already established, so the channel is ready right away.
+    let ready =
+        std::future::poll_fn(|cx| Service::<http::Request<Body>>::poll_ready(&mut channel, cx));
+    tokio::time::timeout(Duration::from_millis(50), ready)
+        .await
+        .unwrap()
+        .unwrap();
+
+    TestClient::new(channel)
+        .unary_call(Request::new(Input {}))
+        .await
+        .unwrap();
+
+    jh.await.unwrap();
+}
+
 #[tokio::test]
 async fn connect_handles_tls() {
     rustls::crypto::ring::default_provider()
@@ -113,3 +193,134 @@ async fn connect_lazy_reconnects_after_first_failure() {
 
     jh.await.unwrap();
 }
+
+#[tokio::test]
+async fn wait_for_ready_holds_call_until_server_is_up() {
+    let (tx, rx) = oneshot::channel();
+    let sender = Arc::new(Mutex::new(Some(tx)));
+    let svc = test_server::TestServer::new(Svc(sender));
+
+    // Reserve a port, then free it so nothing is listening when the call is issued.
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .wait_for_ready(true)
+        .connect_lazy();
+    let mut client = TestClient::new(channel);
+
+    let call = tokio::spawn(async move { client.unary_call(Request::new(Input {})).await });
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!call.is_finished());
+
+    let listener = TcpListener::bind(addr).await.unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    call.await.unwrap().unwrap();
+
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn wait_for_ready_respects_timeout() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect_lazy();
+    let mut client = TestClient::new(channel);
+
+    let mut request = Request::new(Input {});
+    request.set_wait_for_ready(true);
+    request.set_timeout(Duration::from_millis(300));
+
+    let err = client.unary_call(request).await.unwrap_err();
+    assert_eq!(err.code(), Code::Cancelled);
+}
+
+#[tokio::test]
+async fn calls_succeed_across_server_goaway() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let addr = req.remote_addr().unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let mut res = Response::new(Output {});
+            res.metadata_mut()
+                .insert("peer-port", addr.port().to_string().parse().unwrap());
+            Ok(res)
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let svc = test_server::TestServer::new(Svc);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .max_connection_age(Duration::from_millis(200))
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    // Keep several calls in flight while the server rotates connections, so
+    // that some of them are issued after the server has sent GOAWAY.
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(1);
+    let callers = (0..8)
+        .map(|_| {
+            let mut client = TestClient::new(channel.clone());
+            tokio::spawn(async move {
+                let mut ports = Vec::new();
+                while tokio::time::Instant::now() < deadline {
+                    let res = client.unary_call(Input {}).await.unwrap();
+                    ports.push(res.metadata().get("peer-port").unwrap().clone());
+                }
+                ports
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut ports = std::collections::HashSet::new();
+    for caller in callers {
+        ports.extend(caller.await.unwrap());
+    }
+
+    assert!(
+        ports.len() > 1,
+        "expected calls to use more than one connection"
+    );
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
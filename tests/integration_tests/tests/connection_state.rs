@@ -0,0 +1,95 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{
+        server::{ConnectionState, TcpIncoming},
+        Channel, Endpoint, Server,
+    },
+    Request, Response, Status,
+};
+
+struct Session {
+    id: u64,
+    calls: AtomicU64,
+}
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let session = req
+            .extensions()
+            .get::<ConnectionState<Session>>()
+            .ok_or_else(|| Status::internal("no session"))?;
+        let calls = session.calls.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let mut res = Response::new(Output {});
+        res.metadata_mut()
+            .insert("x-session", session.id.to_string().parse().unwrap());
+        res.metadata_mut()
+            .insert("x-calls", calls.to_string().parse().unwrap());
+        Ok(res)
+    }
+}
+
+async fn call(client: &mut TestClient<Channel>) -> (String, String) {
+    let res = client.unary_call(Input {}).await.unwrap();
+    let value = |key: &str| {
+        res.metadata()
+            .get(key)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned()
+    };
+    (value("x-session"), value("x-calls"))
+}
+
+#[tokio::test]
+async fn connections_have_their_own_state() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let sessions = Arc::new(AtomicU64::new(0));
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .on_connect(move |remote_addr| {
+                assert!(remote_addr.is_some());
+                Session {
+                    id: sessions.fetch_add(1, Ordering::Relaxed),
+                    calls: AtomicU64::new(0),
+                }
+            })
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let endpoint = Endpoint::from_shared(format!("http://{addr}")).unwrap();
+    let mut first = TestClient::new(endpoint.connect().await.unwrap());
+    let mut second = TestClient::new(endpoint.connect().await.unwrap());
+
+    let (first_session, calls) = call(&mut first).await;
+    assert_eq!(calls, "1");
+    assert_eq!(call(&mut first).await, (first_session.clone(), "2".into()));
+
+    // The calls on the first connection don't show in the state of the second one.
+    let (second_session, calls) = call(&mut second).await;
+    assert_ne!(second_session, first_session);
+    assert_eq!(calls, "1");
+    assert_eq!(call(&mut first).await, (first_session, "3".into()));
+
+    drop((first, second));
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
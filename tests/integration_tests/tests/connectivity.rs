@@ -0,0 +1,112 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::{net::SocketAddr, thread, time::Duration};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{channel::ConnectivityState, server::TcpIncoming, Channel, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// Runs a server on its own runtime, which drops its connections without closing them
+/// gracefully, as if the server crashed, once `kill` is sent.
+fn spawn_server() -> (SocketAddr, oneshot::Sender<()>, thread::JoinHandle<()>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (kill, killed) = oneshot::channel::<()>();
+
+    let server = thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async move {
+            let incoming = TcpIncoming::from(TcpListener::from_std(listener).unwrap());
+            tokio::spawn(
+                Server::builder()
+                    .add_service(test_server::TestServer::new(Svc))
+                    .serve_with_incoming(incoming),
+            );
+            let _ = killed.await;
+        });
+    });
+
+    (addr, kill, server)
+}
+
+#[tokio::test]
+async fn connectivity_follows_the_connection() {
+    let (addr, kill, server) = spawn_server();
+
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect_lazy();
+    let mut state = channel.connectivity();
+    assert_eq!(*state.borrow_and_update(), ConnectivityState::Idle);
+
+    let watcher = {
+        let mut state = state.clone();
+        tokio::spawn(async move {
+            let mut seen = Vec::new();
+            while state.changed().await.is_ok() {
+                let current = *state.borrow_and_update();
+                seen.push(current);
+                if current == ConnectivityState::Ready {
+                    return seen;
+                }
+            }
+            seen
+        })
+    };
+
+    let mut client = test_client::TestClient::new(channel.clone());
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(
+        watcher.await.unwrap(),
+        [ConnectivityState::Connecting, ConnectivityState::Ready]
+    );
+    assert_eq!(*state.borrow_and_update(), ConnectivityState::Ready);
+
+    kill.send(()).unwrap();
+    server.join().unwrap();
+
+    let lost = tokio::time::timeout(
+        Duration::from_secs(5),
+        state.wait_for(|state| *state != ConnectivityState::Ready),
+    )
+    .await
+    .unwrap()
+    .map(|state| *state)
+    .unwrap();
+    assert_eq!(lost, ConnectivityState::TransientFailure);
+
+    // Reconnecting fails as the server is gone.
+    client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(*state.borrow(), ConnectivityState::TransientFailure);
+}
+
+#[tokio::test]
+async fn connectivity_of_a_failed_connection() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect_lazy();
+    let mut client = test_client::TestClient::new(channel.clone());
+    client.unary_call(Input {}).await.unwrap_err();
+
+    assert_eq!(
+        *channel.connectivity().borrow(),
+        ConnectivityState::TransientFailure
+    );
+}
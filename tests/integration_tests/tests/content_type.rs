@@ -0,0 +1,69 @@
+use http::{header::CONTENT_TYPE, HeaderValue};
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+use tower::ServiceBuilder;
+use tower_http::set_header::SetRequestHeaderLayer;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn echoes_request_content_subtype() {
+    let (tx, rx) = oneshot::channel();
+    let svc = test_server::TestServer::new(Svc);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let mut client = TestClient::new(channel.clone());
+    let response = client.unary_call(Input {}).await.unwrap();
+    assert_eq!(
+        response.metadata().get(CONTENT_TYPE.as_str()).unwrap(),
+        "application/grpc"
+    );
+
+    let mut client = TestClient::new(
+        ServiceBuilder::new()
+            .layer(SetRequestHeaderLayer::overriding(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/grpc+proto"),
+            ))
+            .service(channel),
+    );
+    let response = client.unary_call(Input {}).await.unwrap();
+    assert_eq!(
+        response.metadata().get(CONTENT_TYPE.as_str()).unwrap(),
+        "application/grpc+proto"
+    );
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
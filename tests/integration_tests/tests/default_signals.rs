@@ -0,0 +1,66 @@
+#![cfg(unix)]
+
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+use std::process::Command;
+use std::time::Duration;
+use tonic::{
+    transport::{server::ShutdownOutcome, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+fn free_addr() -> SocketAddr {
+    StdTcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+// The only test in this file, as the signal is sent to the whole test process.
+#[tokio::test]
+async fn sigterm_drains_the_server() {
+    let addr = free_addr();
+
+    let jh = tokio::spawn(
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_default_signals(addr),
+    );
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    let call = tokio::spawn(async move { client.unary_call(Input {}).await });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let status = Command::new("kill")
+        .args(["-TERM", &std::process::id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // The call in flight completes before the server stops.
+    call.await.unwrap().unwrap();
+    let report = tokio::time::timeout(Duration::from_secs(5), jh)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.outcome(), ShutdownOutcome::Drained);
+    assert_eq!(report.drained(), 1);
+}
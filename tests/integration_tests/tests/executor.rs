@@ -0,0 +1,94 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{net::TcpListener, runtime::Handle, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+/// Spawns tasks on a dedicated runtime, counting them.
+#[derive(Clone)]
+struct Dedicated {
+    handle: Handle,
+    spawned: Arc<AtomicUsize>,
+}
+
+impl<F> hyper::rt::Executor<F> for Dedicated
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, fut: F) {
+        self.spawned.fetch_add(1, Ordering::Relaxed);
+        self.handle.spawn(fut);
+    }
+}
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        let name = std::thread::current().name().map(str::to_owned);
+        let mut res = Response::new(Output {});
+        res.metadata_mut()
+            .insert("thread", name.unwrap_or_default().parse().unwrap());
+        Ok(res)
+    }
+}
+
+#[tokio::test]
+async fn connections_are_served_on_the_executor() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("dedicated")
+        .enable_all()
+        .build()
+        .unwrap();
+    let executor = Dedicated {
+        handle: runtime.handle().clone(),
+        spawned: Arc::default(),
+    };
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let router = Server::builder()
+        .executor(executor.clone())
+        .add_service(test_server::TestServer::new(Svc));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    for _ in 0..3 {
+        let res = client.unary_call(Input {}).await.unwrap();
+        assert_eq!(res.metadata().get("thread").unwrap(), "dedicated");
+    }
+
+    // The connection, and a task per stream.
+    assert!(executor.spawned.load(Ordering::Relaxed) >= 4);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+    runtime.shutdown_background();
+}
@@ -0,0 +1,116 @@
+use std::{convert::Infallible, time::Duration};
+
+use integration_tests::pb::{
+    test1_client, test1_server, test_server, Input, Input1, Output, Output1,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    body::Body,
+    codegen::BoxStream,
+    transport::{server::TcpIncoming, Channel, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+use tower::{service_fn, ServiceExt};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+struct Backend;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Backend {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1 {
+            buf: b"from backend".to_vec(),
+        }))
+    }
+
+    type StreamCallStream = BoxStream<Output1>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        unimplemented!()
+    }
+}
+
+async fn serve(router: tonic::transport::server::Router) -> (Channel, oneshot::Sender<()>) {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    (channel, tx)
+}
+
+#[tokio::test]
+async fn unmatched_method_is_unimplemented_by_default() {
+    let (channel, _tx) =
+        serve(Server::builder().add_service(test_server::TestServer::new(Svc))).await;
+
+    let mut client = test1_client::Test1Client::new(channel);
+    let status = client.unary_call(Input1::default()).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+#[tokio::test]
+async fn unmatched_method_gets_custom_status() {
+    let router = Server::builder()
+        .add_service(test_server::TestServer::new(Svc))
+        .fallback_fn(|req| Status::not_found(format!("{} is not served here", req.uri().path())));
+    let (channel, _tx) = serve(router).await;
+
+    let mut client = test1_client::Test1Client::new(channel);
+    let status = client.unary_call(Input1::default()).await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+    assert_eq!(status.message(), "/test.Test1/UnaryCall is not served here");
+}
+
+#[tokio::test]
+async fn unmatched_method_is_forwarded_to_fallback_service() {
+    let (backend, _backend_tx) =
+        serve(Server::builder().add_service(test1_server::Test1Server::new(Backend))).await;
+
+    let forward = service_fn(move |req: http::Request<Body>| {
+        let backend = backend.clone();
+        async move {
+            let res = match backend.oneshot(req).await {
+                Ok(res) => res,
+                Err(err) => Status::from_error(Box::new(err)).into_http(),
+            };
+            Ok::<_, Infallible>(res)
+        }
+    });
+
+    let router = Server::builder()
+        .add_service(test_server::TestServer::new(Svc))
+        .fallback_service(forward);
+    let (channel, _tx) = serve(router).await;
+
+    let mut client = test1_client::Test1Client::new(channel);
+    let res = client.unary_call(Input1::default()).await.unwrap();
+    assert_eq!(res.into_inner().buf, b"from backend");
+}
@@ -0,0 +1,99 @@
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use std::{pin::Pin, time::Duration};
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Fault, FaultInjector, Server},
+    Request, Response, Status,
+};
+
+const MESSAGES: usize = 10;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1 {
+            buf: req.into_inner().buf,
+        }))
+    }
+
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let stream = async_stream::stream! {
+            for _ in 0..MESSAGES {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                yield Ok(Output1 { buf: vec![0; 1024] });
+            }
+        };
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tokio::test]
+async fn client_reconnects_after_mid_stream_disconnect() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let faults = FaultInjector::new();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).map({
+        let faults = faults.clone();
+        move |io| io.map(|io| faults.wrap(io))
+    });
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = Test1Client::new(channel);
+
+    let mut stream = client
+        .stream_call(Input1 { buf: Vec::new() })
+        .await
+        .unwrap()
+        .into_inner();
+    stream.message().await.unwrap().unwrap();
+
+    // Drop the connection a few messages into the response.
+    faults.inject(Fault::DisconnectAfter(3 * 1024));
+    let mut received = 1;
+    let err = loop {
+        match stream.message().await {
+            Ok(Some(_)) => received += 1,
+            Ok(None) => panic!("stream completed despite the dropped connection"),
+            Err(status) => break status,
+        }
+    };
+    assert!(received < MESSAGES);
+    assert_ne!(err.code(), tonic::Code::Ok);
+
+    // The channel reconnects for the next call.
+    let mut stream = client
+        .stream_call(Input1 { buf: Vec::new() })
+        .await
+        .unwrap()
+        .into_inner();
+    let mut received = 0;
+    while stream.message().await.unwrap().is_some() {
+        received += 1;
+    }
+    assert_eq!(received, MESSAGES);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,104 @@
+use integration_tests::pb::{test1_client, test1_server, Input1, Output1};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::StreamExt;
+use tonic::{
+    transport::{
+        server::{FlowControlStall, FlowControlStallLayer, TcpIncoming},
+        Endpoint, Server,
+    },
+    Request, Response, Status,
+};
+
+type Stream<T> = std::pin::Pin<
+    Box<dyn tokio_stream::Stream<Item = std::result::Result<T, Status>> + Send + 'static>,
+>;
+
+const MESSAGES: usize = 256;
+const MESSAGE_SIZE: usize = 1024;
+const WINDOW_SIZE: u32 = 64 * 1024;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        unimplemented!()
+    }
+
+    type StreamCallStream = Stream<Output1>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        // Several times the window, so the response cannot be sent without the client reading.
+        let stream = tokio_stream::iter(0..MESSAGES).map(|_| {
+            Ok(Output1 {
+                buf: vec![0; MESSAGE_SIZE],
+            })
+        });
+
+        Ok(Response::new(Box::pin(stream) as Self::StreamCallStream))
+    }
+}
+
+#[tokio::test]
+async fn non_reading_client_stalls_response() {
+    let stalls = Arc::new(Mutex::new(Vec::<FlowControlStall>::new()));
+    let layer = FlowControlStallLayer::new(Duration::from_millis(100)).on_stall({
+        let stalls = stalls.clone();
+        move |stall| stalls.lock().unwrap().push(stall.clone())
+    });
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .layer(layer)
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .initial_stream_window_size(WINDOW_SIZE)
+        .initial_connection_window_size(WINDOW_SIZE)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test1_client::Test1Client::new(channel);
+
+    // Start the call but don't read from the response stream for a while.
+    let mut stream = client
+        .stream_call(Input1::default())
+        .await
+        .unwrap()
+        .into_inner();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(stalls.lock().unwrap().is_empty());
+
+    // The stall is reported once reading lets the response go on.
+    let mut received = 0;
+    while stream.message().await.unwrap().is_some() {
+        received += 1;
+    }
+    assert_eq!(received, MESSAGES);
+
+    let stalls = stalls.lock().unwrap().clone();
+    assert!(!stalls.is_empty());
+    assert_eq!(stalls[0].method(), "/test.Test1/StreamCall");
+    assert!(stalls[0].stalled() >= Duration::from_millis(200));
+    assert!(stalls[0].resumed());
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,76 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, GoAway, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn max_connection_age_fires_goaway_hooks() {
+    let (tx, rx) = oneshot::channel();
+    let sent = Arc::new(Mutex::new(Vec::<GoAway>::new()));
+    let received = Arc::new(Mutex::new(Vec::<GoAway>::new()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn({
+        let sent = sent.clone();
+        async move {
+            Server::builder()
+                .max_connection_age(Duration::from_millis(200))
+                .on_goaway_sent(move |go_away| sent.lock().unwrap().push(go_away.clone()))
+                .add_service(test_server::TestServer::new(Svc))
+                .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+                .await
+                .unwrap();
+        }
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .on_goaway_received({
+            let received = received.clone();
+            move |go_away| received.lock().unwrap().push(go_away.clone())
+        })
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    assert!(sent.lock().unwrap().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+
+    let sent = sent.lock().unwrap().clone();
+    assert!(!sent.is_empty());
+    assert!(sent.iter().all(|go_away| go_away.code() == 0));
+    // The last one names the last stream that was processed, the one of the request.
+    assert_eq!(sent.last().unwrap().last_stream_id(), 1);
+
+    // The client may close the connection before the second one arrives.
+    let received = received.lock().unwrap().clone();
+    assert!(!received.is_empty());
+    assert!(received.iter().all(GoAway::is_graceful));
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,133 @@
+#![cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc {
+    instance: &'static str,
+    delay: Duration,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        tokio::time::sleep(self.delay).await;
+        let mut response = Response::new(Output {});
+        response
+            .metadata_mut()
+            .insert("instance", self.instance.parse().unwrap());
+        Ok(response)
+    }
+}
+
+fn free_addr() -> SocketAddr {
+    StdTcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+async fn call(addr: SocketAddr) -> Result<Response<Output>, Status> {
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    TestClient::new(channel)
+        .unary_call(Request::new(Input {}))
+        .await
+}
+
+#[tokio::test]
+async fn old_instance_drains_while_new_one_takes_over() {
+    let addr = free_addr();
+
+    let (old_tx, old_rx) = oneshot::channel::<()>();
+    let mut old = Server::builder().reuse_port(true);
+    let old_ready = old.ready();
+    let old_stats = old.stats();
+    let old = tokio::spawn(
+        old.add_service(test_server::TestServer::new(Svc {
+            instance: "old",
+            delay: Duration::from_millis(300),
+        }))
+        .serve_with_shutdown(addr, async { drop(old_rx.await) }),
+    );
+    old_ready.await;
+
+    // A request in flight on the old instance while it hands off.
+    let in_flight = tokio::spawn(call(addr));
+    while old_stats.snapshot().active_requests() == 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let (new_tx, new_rx) = oneshot::channel::<()>();
+    let mut new = Server::builder().reuse_port(true);
+    let new_ready = new.ready();
+    let new = tokio::spawn(
+        new.add_service(test_server::TestServer::new(Svc {
+            instance: "new",
+            delay: Duration::ZERO,
+        }))
+        .serve_with_shutdown(addr, async { drop(new_rx.await) }),
+    );
+    new_ready.await;
+
+    old_tx.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // The old instance stopped listening, so new connections reach the new one.
+    for _ in 0..8 {
+        let response = call(addr).await.unwrap();
+        assert_eq!(response.metadata().get("instance").unwrap(), "new");
+    }
+
+    let response = in_flight.await.unwrap().unwrap();
+    assert_eq!(response.metadata().get("instance").unwrap(), "old");
+
+    tokio::time::timeout(Duration::from_secs(5), old)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(old_stats.snapshot().active_connections(), 0);
+
+    new_tx.send(()).unwrap();
+    new.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn binding_a_used_port_fails_without_reuse_port() {
+    let addr = free_addr();
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let mut first = Server::builder().reuse_port(true);
+    let ready = first.ready();
+    let first = tokio::spawn(
+        first
+            .add_service(test_server::TestServer::new(Svc {
+                instance: "first",
+                delay: Duration::ZERO,
+            }))
+            .serve_with_shutdown(addr, async { drop(rx.await) }),
+    );
+    ready.await;
+
+    let second = Server::builder()
+        .add_service(test_server::TestServer::new(Svc {
+            instance: "second",
+            delay: Duration::ZERO,
+        }))
+        .serve(addr)
+        .await;
+    assert!(second.is_err());
+
+    tx.send(()).unwrap();
+    first.await.unwrap().unwrap();
+}
@@ -0,0 +1,91 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{
+        server::{ShutdownOutcome, ShutdownReport, TcpIncoming},
+        Server,
+    },
+    Request, Response, Status,
+};
+
+struct Svc {
+    delay: Duration,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        tokio::time::sleep(self.delay).await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// Shuts down a server with a request in flight, returning the report returned by the server,
+/// the notified report and the outcome of the request.
+async fn shutdown_during_request(
+    mut builder: Server,
+    delay: Duration,
+) -> (
+    ShutdownReport,
+    ShutdownReport,
+    Result<Response<Output>, Status>,
+) {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let stats = builder.stats();
+    let shutdown_complete = builder.shutdown_complete();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let router = builder.add_service(test_server::TestServer::new(Svc { delay }));
+    let server = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap()
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let call = tokio::spawn(async move { client.unary_call(Input {}).await });
+    while stats.snapshot().active_requests() == 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    tx.send(()).unwrap();
+    let report = server.await.unwrap();
+    let notified = shutdown_complete.await;
+
+    (report, notified, call.await.unwrap())
+}
+
+#[tokio::test]
+async fn drains_in_flight_requests() {
+    let (report, notified, response) =
+        shutdown_during_request(Server::builder(), Duration::from_millis(200)).await;
+
+    response.unwrap();
+    assert_eq!(report.outcome(), ShutdownOutcome::Drained);
+    assert_eq!(report.drained(), 1);
+    assert_eq!(report.aborted(), 0);
+    assert!(report.elapsed() >= Duration::from_millis(100));
+    assert_eq!(notified, report);
+}
+
+#[tokio::test]
+async fn forces_shutdown_after_timeout() {
+    let builder = Server::builder().graceful_shutdown_timeout(Duration::from_millis(100));
+    let (report, notified, response) =
+        shutdown_during_request(builder, Duration::from_secs(30)).await;
+
+    response.unwrap_err();
+    assert_eq!(report.outcome(), ShutdownOutcome::Forced);
+    assert_eq!(report.drained(), 0);
+    assert_eq!(report.aborted(), 1);
+    assert!(report.elapsed() < Duration::from_secs(5));
+    assert_eq!(notified, report);
+}
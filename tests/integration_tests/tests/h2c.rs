@@ -0,0 +1,148 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn serves_prior_knowledge_and_rejects_http1() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .h2c()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    TestClient::new(channel)
+        .unary_call(Request::new(Input {}))
+        .await
+        .unwrap();
+
+    let mut http1 = TcpStream::connect(addr).await.unwrap();
+    http1
+        .write_all(
+            b"POST /test.Test/UnaryCall HTTP/1.1\r\n\
+            host: localhost\r\n\
+            content-type: application/grpc\r\n\
+            content-length: 0\r\n\
+            \r\n",
+        )
+        .await
+        .unwrap();
+    let mut response = String::new();
+    http1.read_to_string(&mut response).await.unwrap();
+    assert!(
+        response.starts_with("HTTP/1.1 505 HTTP Version Not Supported\r\n"),
+        "{response}"
+    );
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn upgrades_http1_connections_to_h2c() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut io = TcpStream::connect(addr).await.unwrap();
+    io.write_all(
+        b"POST /test.Test/UnaryCall HTTP/1.1\r\n\
+        host: localhost\r\n\
+        connection: Upgrade, HTTP2-Settings\r\n\
+        upgrade: h2c\r\n\
+        http2-settings: \r\n\
+        content-type: application/grpc\r\n\
+        te: trailers\r\n\
+        content-length: 5\r\n\
+        \r\n\
+        \0\0\0\0\0",
+    )
+    .await
+    .unwrap();
+
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(io.read_u8().await.unwrap());
+    }
+    let head = String::from_utf8(head).unwrap();
+    assert!(
+        head.starts_with("HTTP/1.1 101 Switching Protocols\r\n"),
+        "{head}"
+    );
+
+    // The connection preface, with an empty `SETTINGS` frame.
+    io.write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+        .await
+        .unwrap();
+
+    // The response to the request that carried the upgrade is sent on stream 1.
+    let mut data = Vec::new();
+    loop {
+        let mut header = [0; 9];
+        io.read_exact(&mut header).await.unwrap();
+        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+        let (kind, flags) = (header[3], header[4]);
+        let stream_id = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+        let mut payload = vec![0; len];
+        io.read_exact(&mut payload).await.unwrap();
+
+        if kind == 0x4 && flags & 0x1 == 0 {
+            io.write_all(&[0, 0, 0, 0x4, 0x1, 0, 0, 0, 0])
+                .await
+                .unwrap();
+        }
+        if stream_id == 1 && kind == 0x0 {
+            data.extend_from_slice(&payload);
+        }
+        if stream_id == 1 && kind == 0x1 && flags & 0x1 != 0 {
+            break;
+        }
+    }
+    assert_eq!(data, [0, 0, 0, 0, 0]);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
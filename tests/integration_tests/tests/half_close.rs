@@ -0,0 +1,91 @@
+use integration_tests::pb::{
+    test_bidi_stream_client::TestBidiStreamClient, test_bidi_stream_server, InputStream,
+    OutputStream,
+};
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::Stream;
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status, Streaming,
+};
+
+/// Reads all requests, then sends five responses after the client half-closed.
+struct Svc(Arc<AtomicUsize>);
+
+#[tonic::async_trait]
+impl test_bidi_stream_server::TestBidiStream for Svc {
+    type BidiStreamCallStream = Pin<Box<dyn Stream<Item = Result<OutputStream, Status>> + Send>>;
+
+    async fn bidi_stream_call(
+        &self,
+        req: Request<Streaming<InputStream>>,
+    ) -> Result<Response<Self::BidiStreamCallStream>, Status> {
+        let mut requests = req.into_inner();
+        let received = self.0.clone();
+
+        let stream = async_stream::try_stream! {
+            while requests.message().await?.is_some() {
+                received.fetch_add(1, Ordering::Relaxed);
+            }
+
+            for _ in 0..5 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                yield OutputStream {};
+            }
+        };
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tokio::test]
+async fn responses_continue_after_client_half_close() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let received = Arc::new(AtomicUsize::new(0));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn({
+        let svc = Svc(received.clone());
+        async move {
+            Server::builder()
+                .add_service(test_bidi_stream_server::TestBidiStreamServer::new(svc))
+                .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+                .await
+                .unwrap();
+        }
+    });
+
+    let mut client = TestBidiStreamClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    // The request stream ends after three messages, which half-closes the call.
+    let requests = tokio_stream::iter(vec![InputStream {}; 3]);
+    let mut responses = client
+        .bidi_stream_call(requests)
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut count = 0;
+    while responses.message().await.unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 5);
+    assert_eq!(received.load(Ordering::Relaxed), 3);
+    assert!(responses.trailers().await.unwrap().is_some());
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,109 @@
+use integration_tests::pb::{test_server, Input, Output};
+use std::{
+    error::Error as _,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tonic::transport::{server::TcpIncoming, Error, Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+const SERVER_CERT: &str = include_str!("../../../examples/data/tls/server.pem");
+const SERVER_KEY: &str = include_str!("../../../examples/data/tls/server.key");
+
+/// The remote address of every failed handshake, and whether it failed with a TLS error.
+type Failures = Arc<Mutex<Vec<(Option<SocketAddr>, bool)>>>;
+
+async fn serve(server: Server) -> (SocketAddr, Failures) {
+    let failures = Failures::default();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let router = server
+        .on_handshake_error({
+            let failures = failures.clone();
+            move |remote_addr, error: &Error| {
+                let tls_error = error
+                    .source()
+                    .and_then(|source| source.downcast_ref::<std::io::Error>())
+                    .and_then(|source| source.get_ref())
+                    .is_some_and(|source| source.is::<rustls::Error>());
+                failures.lock().unwrap().push((remote_addr, tls_error));
+            }
+        })
+        .add_service(test_server::TestServer::new(Svc));
+    tokio::spawn(router.serve_with_incoming(TcpIncoming::from(listener)));
+
+    (addr, failures)
+}
+
+/// Sends `garbage` to `addr` and waits for the server to close the connection.
+async fn send_garbage(addr: SocketAddr, garbage: &[u8]) -> SocketAddr {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let local_addr = stream.local_addr().unwrap();
+    stream.write_all(garbage).await.unwrap();
+
+    let mut buf = Vec::new();
+    let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    local_addr
+}
+
+#[tokio::test]
+async fn plaintext_on_tls_port() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let tls = ServerTlsConfig::new().identity(Identity::from_pem(SERVER_CERT, SERVER_KEY));
+    let (addr, failures) = serve(Server::builder().tls_config(tls).unwrap()).await;
+
+    let client_addr = send_garbage(addr, b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let failures = failures.lock().unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, Some(client_addr));
+    assert!(failures[0].1, "not a TLS error");
+}
+
+#[tokio::test]
+async fn invalid_http2_preface() {
+    let (addr, failures) = serve(Server::builder()).await;
+
+    let client_addr = send_garbage(addr, b"not an HTTP/2 preface at all\r\n\r\n").await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let failures = failures.lock().unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, Some(client_addr));
+    assert!(!failures[0].1);
+}
+
+#[tokio::test]
+async fn successful_handshakes_are_not_reported() {
+    let (addr, failures) = serve(Server::builder()).await;
+
+    let channel = tonic::transport::Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = integration_tests::pb::test_client::TestClient::new(channel.clone());
+    client.unary_call(Input {}).await.unwrap();
+    drop(client);
+    drop(channel);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(failures.lock().unwrap().is_empty());
+}
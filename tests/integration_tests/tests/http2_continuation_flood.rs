@@ -0,0 +1,97 @@
+use integration_tests::pb::{test_server, Input, Output};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+const HEADERS: u8 = 0x1;
+const SETTINGS: u8 = 0x4;
+const GOAWAY: u8 = 0x7;
+const CONTINUATION: u8 = 0x9;
+const ENHANCE_YOUR_CALM: u32 = 0xb;
+
+fn frame(kind: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut frame = (payload.len() as u32).to_be_bytes()[1..].to_vec();
+    frame.extend_from_slice(&[kind, 0]);
+    frame.extend_from_slice(&stream_id.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Reads frames until a GOAWAY, returning its error code.
+async fn read_goaway(mut io: impl AsyncRead + Unpin) -> u32 {
+    loop {
+        let mut head = [0; 9];
+        io.read_exact(&mut head).await.unwrap();
+        let len = u32::from_be_bytes([0, head[0], head[1], head[2]]) as usize;
+        let mut payload = vec![0; len];
+        io.read_exact(&mut payload).await.unwrap();
+        if head[3] == GOAWAY {
+            return u32::from_be_bytes(payload[4..8].try_into().unwrap());
+        }
+    }
+}
+
+#[tokio::test]
+async fn continuation_flood_closes_connection() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut io = TcpStream::connect(addr).await.unwrap();
+    io.write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n")
+        .await
+        .unwrap();
+    io.write_all(&frame(SETTINGS, 0, &[])).await.unwrap();
+    // `:method: POST`, `:scheme: http` and `:path: /`, without END_HEADERS.
+    io.write_all(&frame(HEADERS, 1, &[0x83, 0x86, 0x84]))
+        .await
+        .unwrap();
+
+    // A header block that never ends, one small header per CONTINUATION frame.
+    let mut header = vec![0x00, 7];
+    header.extend_from_slice(b"x-flood");
+    header.push(100);
+    header.extend_from_slice(&[b'a'; 100]);
+    let continuation = frame(CONTINUATION, 1, &header);
+    let (read, mut write) = io.into_split();
+    let flood = tokio::spawn(async move {
+        let mut sent = 0;
+        while sent < 10_000 && write.write_all(&continuation).await.is_ok() {
+            sent += 1;
+        }
+        sent
+    });
+
+    assert_eq!(read_goaway(read).await, ENHANCE_YOUR_CALM);
+    assert!(flood.await.unwrap() < 10_000);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,48 @@
+use integration_tests::pb::{test_client, Input};
+use tokio::net::TcpListener;
+use tonic::{transport::Http2Error, Code};
+
+/// Accepts one connection and resets every stream on it with `reason`.
+async fn reset_streams(listener: TcpListener, reason: h2::Reason) {
+    let (io, _) = listener.accept().await.unwrap();
+    let mut connection = h2::server::handshake(io).await.unwrap();
+    while let Some(Ok((_, mut respond))) = connection.accept().await {
+        respond.send_reset(reason);
+    }
+}
+
+#[tokio::test]
+async fn refused_streams_are_retryable() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(reset_streams(listener, h2::Reason::REFUSED_STREAM));
+
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+
+    let err = Http2Error::find(&status).expect("an HTTP/2 error");
+    assert_eq!(err.code(), u32::from(h2::Reason::REFUSED_STREAM));
+    assert!(err.is_remote());
+    assert!(!err.is_go_away());
+    assert!(err.is_retryable());
+}
+
+#[tokio::test]
+async fn other_resets_are_not_retryable() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(reset_streams(listener, h2::Reason::INTERNAL_ERROR));
+
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Internal);
+
+    let err = Http2Error::find(&status).expect("an HTTP/2 error");
+    assert_eq!(err.code(), u32::from(h2::Reason::INTERNAL_ERROR));
+    assert!(!err.is_retryable());
+}
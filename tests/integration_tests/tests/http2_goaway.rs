@@ -0,0 +1,190 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use integration_tests::pb::{test_server, Input, Output};
+use tonic::transport::{server::TcpIncoming, GoAway, GoAwayReason, Server};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn reset_flood_is_answered_with_enhance_your_calm() {
+    let svc = test_server::TestServer::new(Svc);
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .http2_max_pending_accept_reset_streams(Some(5))
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let io = TcpStream::connect(addr).await.unwrap();
+    let (mut client, connection) = h2::client::handshake(io).await.unwrap();
+    let connection = tokio::spawn(connection);
+
+    // Open streams and reset them right away, before the server gets to accept them.
+    for _ in 0..100 {
+        let Ok(ready) = client.ready().await else {
+            break;
+        };
+        client = ready;
+
+        let request = http::Request::post(format!("http://{addr}/test.Test/UnaryCall"))
+            .header("content-type", "application/grpc")
+            .body(())
+            .unwrap();
+        let Ok((_, mut stream)) = client.send_request(request, false) else {
+            break;
+        };
+        stream.send_reset(h2::Reason::CANCEL);
+    }
+
+    let err = tokio::time::timeout(Duration::from_secs(5), connection)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap_err();
+    assert!(err.is_go_away());
+    assert_eq!(err.reason(), Some(h2::Reason::ENHANCE_YOUR_CALM));
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn goaway_of_a_policy_carries_the_configured_code_and_debug_data() {
+    let svc = test_server::TestServer::new(Svc);
+    let (tx, rx) = oneshot::channel::<()>();
+    let sent = Arc::new(Mutex::new(Vec::new()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn({
+        let sent = sent.clone();
+        async move {
+            Server::builder()
+                .max_connection_age(Duration::from_millis(100))
+                .http2_goaway(|reason, go_away| match reason {
+                    GoAwayReason::MaxConnectionAge => go_away
+                        .with_code(GoAway::ENHANCE_YOUR_CALM)
+                        .with_debug_data("max_connection_age"),
+                    _ => go_away,
+                })
+                .on_goaway_sent(move |go_away| sent.lock().unwrap().push(go_away.clone()))
+                .add_service(svc)
+                .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+                .await
+                .unwrap();
+        }
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let io = TcpStream::connect(addr).await.unwrap();
+    let (_client, connection) = h2::client::handshake(io).await.unwrap();
+
+    let err = tokio::time::timeout(Duration::from_secs(5), connection)
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(err.is_go_away());
+    assert_eq!(err.reason(), Some(h2::Reason::ENHANCE_YOUR_CALM));
+
+    let sent = sent.lock().unwrap().clone();
+    assert!(!sent.is_empty());
+    for go_away in sent {
+        assert_eq!(go_away.code(), GoAway::ENHANCE_YOUR_CALM);
+        assert_eq!(&go_away.debug_data()[..], b"max_connection_age");
+    }
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn goaway_of_a_reset_flood_can_carry_custom_debug_data() {
+    let svc = test_server::TestServer::new(Svc);
+    let (tx, rx) = oneshot::channel::<()>();
+    let sent = Arc::new(Mutex::new(Vec::new()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn({
+        let sent = sent.clone();
+        async move {
+            Server::builder()
+                .http2_max_pending_accept_reset_streams(Some(5))
+                .http2_goaway(|reason, go_away| match reason {
+                    GoAwayReason::Error => go_away.with_debug_data("reset flood"),
+                    _ => go_away,
+                })
+                .on_goaway_sent(move |go_away| sent.lock().unwrap().push(go_away.clone()))
+                .add_service(svc)
+                .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+                .await
+                .unwrap();
+        }
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let io = TcpStream::connect(addr).await.unwrap();
+    let (mut client, connection) = h2::client::handshake(io).await.unwrap();
+    let connection = tokio::spawn(connection);
+
+    for _ in 0..100 {
+        let Ok(ready) = client.ready().await else {
+            break;
+        };
+        client = ready;
+
+        let request = http::Request::post(format!("http://{addr}/test.Test/UnaryCall"))
+            .header("content-type", "application/grpc")
+            .body(())
+            .unwrap();
+        let Ok((_, mut stream)) = client.send_request(request, false) else {
+            break;
+        };
+        stream.send_reset(h2::Reason::CANCEL);
+    }
+
+    let err = tokio::time::timeout(Duration::from_secs(5), connection)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err.reason(), Some(h2::Reason::ENHANCE_YOUR_CALM));
+
+    let sent = sent.lock().unwrap().clone();
+    assert!(!sent.is_empty());
+    for go_away in sent {
+        assert_eq!(go_away.code(), GoAway::ENHANCE_YOUR_CALM);
+        assert_eq!(&go_away.debug_data()[..], b"reset flood");
+    }
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
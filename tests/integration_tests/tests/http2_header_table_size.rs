@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::sync::oneshot;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+/// Calls carrying repeated metadata succeed whether the server shrinks the
+/// header table, disabling the dynamic table entirely, or grows it.
+#[tokio::test]
+async fn test_http2_header_table_size() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            assert_eq!(req.metadata().get("x-key-49").unwrap(), "value-49");
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    for size in [0, 64 * 1024] {
+        let svc = test_server::TestServer::new(Svc);
+
+        let (tx, rx) = oneshot::channel::<()>();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+
+        let jh = tokio::spawn(async move {
+            let listener = tonic::transport::server::TcpIncoming::from(listener);
+            Server::builder()
+                .http2_header_table_size(size)
+                .add_service(svc)
+                .serve_with_incoming_shutdown(listener, async { drop(rx.await) })
+                .await
+                .unwrap();
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let channel = Endpoint::from_shared(addr)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = test_client::TestClient::new(channel);
+
+        for _ in 0..3 {
+            let mut req = Request::new(Input {});
+            for i in 0..50 {
+                req.metadata_mut().insert(
+                    format!("x-key-{i}")
+                        .parse::<tonic::metadata::AsciiMetadataKey>()
+                        .unwrap(),
+                    format!("value-{i}").parse().unwrap(),
+                );
+            }
+            client.unary_call(req).await.unwrap();
+        }
+
+        tx.send(()).unwrap();
+        jh.await.unwrap();
+    }
+}
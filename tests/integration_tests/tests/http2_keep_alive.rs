@@ -1,6 +1,10 @@
-use std::time::Duration;
+use std::{net::SocketAddr, time::Duration};
 
-use tokio::{net::TcpListener, sync::oneshot};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
 
 use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
 use tonic::transport::{server::TcpIncoming, Channel, Server};
@@ -80,3 +84,82 @@ async fn http2_keepalive_does_not_cause_panics_on_client_side() {
     tx.send(()).unwrap();
     jh.await.unwrap();
 }
+
+/// Forwards connections to `upstream`, dropping them once no bytes went either way for `idle`,
+/// like a NAT or load balancer.
+async fn idle_timeout_proxy(upstream: SocketAddr, idle: Duration) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let mut server = TcpStream::connect(upstream).await.unwrap();
+            tokio::spawn(async move {
+                let (mut client_rx, mut client_tx) = client.split();
+                let (mut server_rx, mut server_tx) = server.split();
+                let (mut up, mut down) = ([0; 4096], [0; 4096]);
+
+                loop {
+                    let forwarded = tokio::time::timeout(idle, async {
+                        let (n, buf, write) = tokio::select! {
+                            n = client_rx.read(&mut up) => (n?, &up, &mut server_tx),
+                            n = server_rx.read(&mut down) => (n?, &down, &mut client_tx),
+                        };
+                        write.write_all(&buf[..n]).await?;
+                        Ok::<_, std::io::Error>(n > 0)
+                    })
+                    .await;
+
+                    if !matches!(forwarded, Ok(Ok(true))) {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn http2_keepalive_while_idle_keeps_connection_open() {
+    let svc = test_server::TestServer::new(Svc {});
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let mut builder = Server::builder();
+    let stats = builder.stats();
+    let jh = tokio::spawn(async move {
+        builder
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let proxy = idle_timeout_proxy(addr, Duration::from_millis(300)).await;
+
+    let channel = Channel::from_shared(format!("http://{proxy}"))
+        .unwrap()
+        .http2_keep_alive_interval(Duration::from_millis(100))
+        .keep_alive_while_idle(true)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    // Idle for longer than the proxy allows, kept open by the pings.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    assert_eq!(stats.snapshot().total_connections(), 1);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,141 @@
+use integration_tests::pb::{test_server, Input, Output};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        // Keep the streams open while the client sends its trailers.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+const DATA: u8 = 0x0;
+const HEADERS: u8 = 0x1;
+const RST_STREAM: u8 = 0x3;
+const SETTINGS: u8 = 0x4;
+const GOAWAY: u8 = 0x7;
+const END_STREAM: u8 = 0x1;
+const END_HEADERS: u8 = 0x4;
+const PROTOCOL_ERROR: u32 = 0x1;
+
+fn frame(kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut frame = (payload.len() as u32).to_be_bytes()[1..].to_vec();
+    frame.extend_from_slice(&[kind, flags]);
+    frame.extend_from_slice(&stream_id.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// A header field without indexing, with a literal name.
+fn literal(block: &mut Vec<u8>, name: &[u8], value: &[u8]) {
+    block.push(0x00);
+    block.push(name.len() as u8);
+    block.extend_from_slice(name);
+    block.push(value.len() as u8);
+    block.extend_from_slice(value);
+}
+
+/// The header block of a `test.Test/UnaryCall` request.
+fn request_headers() -> Vec<u8> {
+    // `:method: POST` and `:scheme: http`.
+    let mut block = vec![0x83, 0x86];
+    // `:path`, with the name from the static table.
+    let path = b"/test.Test/UnaryCall";
+    block.extend_from_slice(&[0x04, path.len() as u8]);
+    block.extend_from_slice(path);
+    literal(&mut block, b"content-type", b"application/grpc");
+    literal(&mut block, b"te", b"trailers");
+    block
+}
+
+/// Reads frames until the server answered both streams, returning the error code stream 1 was
+/// reset with, and whether stream 3 completed.
+async fn read_outcome(mut io: impl AsyncRead + Unpin) -> (Option<u32>, bool) {
+    let mut reset = None;
+    let mut completed = false;
+    while reset.is_none() || !completed {
+        let mut head = [0; 9];
+        io.read_exact(&mut head).await.unwrap();
+        let len = u32::from_be_bytes([0, head[0], head[1], head[2]]) as usize;
+        let stream_id = u32::from_be_bytes(head[5..9].try_into().unwrap());
+        let mut payload = vec![0; len];
+        io.read_exact(&mut payload).await.unwrap();
+
+        match (head[3], stream_id) {
+            (GOAWAY, _) => panic!("connection was closed"),
+            (RST_STREAM, 1) => reset = Some(u32::from_be_bytes(payload[..4].try_into().unwrap())),
+            (RST_STREAM, 3) => panic!("sibling stream was reset"),
+            (HEADERS, 3) if head[4] & END_STREAM != 0 => completed = true,
+            _ => {}
+        }
+    }
+    (reset, completed)
+}
+
+#[tokio::test]
+async fn excessive_trailers_reset_only_their_stream() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .http2_max_header_frames_per_stream(2)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut io = TcpStream::connect(addr).await.unwrap();
+    io.write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n")
+        .await
+        .unwrap();
+    io.write_all(&frame(SETTINGS, 0, 0, &[])).await.unwrap();
+
+    let headers = request_headers();
+    // An empty, uncompressed message.
+    let message = [0, 0, 0, 0, 0];
+    let mut trailers = Vec::new();
+    literal(&mut trailers, b"x-trailer", b"value");
+
+    // Stream 1 sends its trailers three times, stream 3 is a regular request.
+    io.write_all(&frame(HEADERS, END_HEADERS, 1, &headers))
+        .await
+        .unwrap();
+    io.write_all(&frame(DATA, 0, 1, &message)).await.unwrap();
+    io.write_all(&frame(HEADERS, END_HEADERS, 3, &headers))
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        io.write_all(&frame(HEADERS, END_HEADERS | END_STREAM, 1, &trailers))
+            .await
+            .unwrap();
+    }
+    io.write_all(&frame(DATA, END_STREAM, 3, &message))
+        .await
+        .unwrap();
+
+    let (reset, completed) = read_outcome(&mut io).await;
+    assert_eq!(reset, Some(PROTOCOL_ERROR));
+    assert!(completed);
+
+    drop(io);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
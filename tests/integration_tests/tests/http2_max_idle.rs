@@ -0,0 +1,62 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn idle_connections_are_closed() {
+    let (tx, rx) = oneshot::channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let mut builder = Server::builder();
+    let stats = builder.stats();
+
+    let jh = tokio::spawn(async move {
+        builder
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .http2_max_idle(Duration::from_millis(200))
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    assert_eq!(stats.snapshot().active_connections(), 1);
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(stats.snapshot().active_connections(), 0);
+
+    // The next request opens a new connection.
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.total_connections(), 2);
+    assert_eq!(snapshot.active_connections(), 1);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    service::Routes,
+    transport::{
+        server::{Http2Settings, TcpIncoming},
+        Server,
+    },
+};
+
+const SETTINGS: u8 = 0x4;
+const WINDOW_UPDATE: u8 = 0x8;
+
+const HEADER_TABLE_SIZE: u16 = 0x1;
+const MAX_CONCURRENT_STREAMS: u16 = 0x3;
+const INITIAL_WINDOW_SIZE: u16 = 0x4;
+const MAX_FRAME_SIZE: u16 = 0x5;
+const MAX_HEADER_LIST_SIZE: u16 = 0x6;
+const ENABLE_CONNECT_PROTOCOL: u16 = 0x8;
+
+#[tokio::test]
+async fn server_advertises_the_configured_settings() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let settings = Http2Settings::new()
+        .header_table_size(8192)
+        .max_concurrent_streams(100)
+        .initial_window_size(1024 * 1024)
+        .initial_connection_window_size(2 * 1024 * 1024)
+        .max_frame_size(32 * 1024)
+        .max_header_list_size(32 * 1024)
+        .enable_connect_protocol(true);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            // Overridden by the settings.
+            .max_concurrent_streams(10)
+            .http2_adaptive_window(Some(true))
+            .http2_settings(settings)
+            .add_routes(Routes::default())
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut io = TcpStream::connect(addr).await.unwrap();
+    io.write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n")
+        .await
+        .unwrap();
+    io.write_all(&[0, 0, 0, SETTINGS, 0, 0, 0, 0, 0])
+        .await
+        .unwrap();
+
+    // The server starts with its settings, followed by the update of the connection window.
+    let mut advertised = BTreeMap::new();
+    let mut window_increment = None;
+    while window_increment.is_none() {
+        let mut head = [0; 9];
+        io.read_exact(&mut head).await.unwrap();
+        let len = u32::from_be_bytes([0, head[0], head[1], head[2]]) as usize;
+        let mut payload = vec![0; len];
+        io.read_exact(&mut payload).await.unwrap();
+
+        match head[3] {
+            // Not an acknowledgement of the client's settings.
+            SETTINGS if head[4] == 0 => {
+                for setting in payload.chunks(6) {
+                    let id = u16::from_be_bytes([setting[0], setting[1]]);
+                    let value = u32::from_be_bytes(setting[2..].try_into().unwrap());
+                    advertised.insert(id, value);
+                }
+            }
+            WINDOW_UPDATE => {
+                window_increment = Some(u32::from_be_bytes(payload[..4].try_into().unwrap()));
+            }
+            _ => {}
+        }
+    }
+
+    assert_eq!(
+        advertised,
+        BTreeMap::from([
+            (HEADER_TABLE_SIZE, 8192),
+            (MAX_CONCURRENT_STREAMS, 100),
+            (INITIAL_WINDOW_SIZE, 1024 * 1024),
+            (MAX_FRAME_SIZE, 32 * 1024),
+            (MAX_HEADER_LIST_SIZE, 32 * 1024),
+            (ENABLE_CONNECT_PROTOCOL, 1),
+        ])
+    );
+    assert_eq!(window_increment, Some(2 * 1024 * 1024 - 65_535));
+
+    drop(io);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
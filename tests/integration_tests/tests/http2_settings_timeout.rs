@@ -0,0 +1,89 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// Reads from `stream` until the server closes the connection, returning whether it did within
+/// `wait`.
+async fn closed_within(stream: &mut TcpStream, wait: Duration) -> bool {
+    let mut buf = Vec::new();
+    tokio::time::timeout(wait, stream.read_to_end(&mut buf))
+        .await
+        .is_ok()
+}
+
+#[tokio::test]
+async fn stalled_handshakes_are_closed() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .http2_settings_timeout(Duration::from_millis(200))
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    // A client that never sends the preface.
+    let mut silent = TcpStream::connect(addr).await.unwrap();
+    // A client that stops in the middle of the preface.
+    let mut partial = TcpStream::connect(addr).await.unwrap();
+    partial.write_all(b"PRI * HTTP/2.0\r\n").await.unwrap();
+
+    assert!(closed_within(&mut silent, Duration::from_secs(2)).await);
+    assert!(closed_within(&mut partial, Duration::from_secs(2)).await);
+
+    // Clients completing the handshake are served past the timeout.
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn timeout_can_be_disabled() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .http2_settings_timeout(None)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap();
+    });
+
+    let mut silent = TcpStream::connect(addr).await.unwrap();
+    assert!(!closed_within(&mut silent, Duration::from_millis(300)).await);
+
+    jh.abort();
+}
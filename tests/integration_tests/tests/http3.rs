@@ -0,0 +1,153 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use integration_tests::{
+    pb::{test_client::TestClient, test_server, Input, Output},
+    BoxFuture,
+};
+use quinn::crypto::rustls::{QuicClientConfig, QuicServerConfig};
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use std::{
+    future::poll_fn,
+    net::SocketAddr,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::oneshot;
+use tonic::{body::Body, transport::Server, Request, Response, Status};
+use tower_service::Service;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        assert!(req.remote_addr().is_some());
+        if req.metadata().contains_key("fail") {
+            return Err(Status::invalid_argument("asked to fail"));
+        }
+        Ok(Response::new(Output {}))
+    }
+}
+
+const CA: &[u8] = include_bytes!("../../../examples/data/tls/ca.pem");
+const SERVER_CERT: &[u8] = include_bytes!("../../../examples/data/tls/server.pem");
+const SERVER_KEY: &[u8] = include_bytes!("../../../examples/data/tls/server.key");
+
+#[tokio::test]
+async fn unary_over_quic() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let server_endpoint = quinn::Endpoint::server(server_config(), localhost()).unwrap();
+    let addr = server_endpoint.local_addr().unwrap();
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_http3_with_shutdown(server_endpoint, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let mut client_endpoint = quinn::Endpoint::client(localhost()).unwrap();
+    client_endpoint.set_default_client_config(client_config());
+    let connection = client_endpoint
+        .connect(addr, "example.com")
+        .unwrap()
+        .await
+        .unwrap();
+    let (mut driver, send_request) = h3::client::new(h3_quinn::Connection::new(connection))
+        .await
+        .unwrap();
+    let driver = tokio::spawn(async move { poll_fn(|cx| driver.poll_close(cx)).await });
+
+    let mut client = TestClient::with_origin(
+        H3Channel(send_request),
+        "https://example.com".parse().unwrap(),
+    );
+
+    client.unary_call(Input {}).await.unwrap();
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut().insert("fail", "1".parse().unwrap());
+    let status = client.unary_call(req).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    assert_eq!(status.message(), "asked to fail");
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+    driver.abort();
+}
+
+fn localhost() -> SocketAddr {
+    "127.0.0.1:0".parse().unwrap()
+}
+
+fn server_config() -> quinn::ServerConfig {
+    let certs = CertificateDer::pem_slice_iter(SERVER_CERT)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_slice(SERVER_KEY).unwrap();
+    let mut tls = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .unwrap();
+    tls.alpn_protocols = vec![b"h3".to_vec()];
+
+    quinn::ServerConfig::with_crypto(Arc::new(QuicServerConfig::try_from(tls).unwrap()))
+}
+
+fn client_config() -> quinn::ClientConfig {
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in CertificateDer::pem_slice_iter(CA) {
+        roots.add(cert.unwrap()).unwrap();
+    }
+    let mut tls = rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    tls.alpn_protocols = vec![b"h3".to_vec()];
+
+    quinn::ClientConfig::new(Arc::new(QuicClientConfig::try_from(tls).unwrap()))
+}
+
+/// A minimal HTTP/3 client transport, buffering the request and response bodies.
+#[derive(Clone)]
+struct H3Channel(h3::client::SendRequest<h3_quinn::OpenStreams, Bytes>);
+
+impl Service<http::Request<Body>> for H3Channel {
+    type Response = http::Response<Body>;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let mut send_request = self.0.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = body.collect().await?.to_bytes();
+
+            let mut stream = send_request
+                .send_request(http::Request::from_parts(parts, ()))
+                .await?;
+            stream.send_data(body).await?;
+            stream.finish().await?;
+
+            let response = stream.recv_response().await?;
+            let mut data = BytesMut::new();
+            while let Some(mut chunk) = stream.recv_data().await? {
+                data.put(chunk.copy_to_bytes(chunk.remaining()));
+            }
+            let mut frames = vec![Ok::<_, Status>(Frame::data(data.freeze()))];
+            if let Some(trailers) = stream.recv_trailers().await? {
+                frames.push(Ok(Frame::trailers(trailers)));
+            }
+
+            Ok(response.map(|()| Body::new(StreamBody::new(tokio_stream::iter(frames)))))
+        })
+    }
+}
@@ -0,0 +1,81 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    service::IdempotencyLayer,
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc(Arc<AtomicUsize>);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+fn request(key: &'static str) -> Request<Input> {
+    let mut request = Request::new(Input {});
+    request
+        .metadata_mut()
+        .insert("idempotency-key", key.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn duplicates_are_executed_once() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let svc = test_server::TestServer::new(Svc(calls.clone()));
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .layer(IdempotencyLayer::new(Duration::from_secs(60)))
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    // A retry after the first call completed.
+    client.unary_call(request("a")).await.unwrap();
+    client.unary_call(request("a")).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Concurrent duplicates wait for the first one.
+    let concurrent = [(); 2].map(|_| {
+        let mut client = client.clone();
+        tokio::spawn(async move { client.unary_call(request("b")).await })
+    });
+    for call in concurrent {
+        call.await.unwrap().unwrap();
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Requests without a key are always executed.
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,63 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn sniffed_connections_are_taken_over() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    // Answers connections starting with `PING` with `PONG`, instead of serving gRPC.
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .intercept_connections(|mut io| async move {
+                if io.peek(4).await.ok()? != b"PING" {
+                    return Some(io);
+                }
+                tokio::spawn(async move {
+                    let mut ping = [0; 4];
+                    io.read_exact(&mut ping).await.unwrap();
+                    io.write_all(b"PONG").await.unwrap();
+                });
+                None
+            })
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let mut raw = TcpStream::connect(addr).await.unwrap();
+    raw.write_all(b"PING").await.unwrap();
+    let mut pong = [0; 4];
+    raw.read_exact(&mut pong).await.unwrap();
+    assert_eq!(&pong, b"PONG");
+
+    // The HTTP/2 preface of gRPC connections is read again by the server.
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+    client.unary_call(Input {}).await.unwrap();
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
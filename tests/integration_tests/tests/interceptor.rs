@@ -1,10 +1,24 @@
 use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
-use std::time::Duration;
-use tokio::{net::TcpListener, sync::oneshot};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    net::TcpListener,
+    sync::{oneshot, OnceCell},
+};
 use tonic::{
+    service::AsyncInterceptorLayer,
     transport::{server::TcpIncoming, Endpoint, Server},
-    GrpcMethod, Request, Response, Status,
+    Code, GrpcMethod, Request, Response, Status,
 };
+use tower_service::Service;
 
 #[tokio::test]
 async fn interceptor_retrieves_grpc_method() {
@@ -57,3 +71,115 @@ async fn interceptor_retrieves_grpc_method() {
     tx.send(()).unwrap();
     jh.await.unwrap();
 }
+
+#[tokio::test]
+async fn async_interceptor_authenticates_requests() {
+    use test_server::Test;
+
+    #[derive(Clone)]
+    struct User(String);
+
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let user = req.extensions().get::<User>().unwrap();
+            assert_eq!(user.0, "alice");
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    /// Fetches the signing key once, like a JWKS endpoint would be queried.
+    #[derive(Default)]
+    struct KeyStore {
+        key: OnceCell<String>,
+        fetches: AtomicUsize,
+    }
+
+    impl KeyStore {
+        async fn key(&self) -> &str {
+            self.key
+                .get_or_init(|| async {
+                    self.fetches.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    "secret".to_string()
+                })
+                .await
+        }
+    }
+
+    #[derive(Clone)]
+    struct Auth(Arc<KeyStore>);
+
+    impl Service<Request<()>> for Auth {
+        type Response = Request<()>;
+        type Error = Status;
+        type Future = Pin<Box<dyn Future<Output = Result<Request<()>, Status>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Status>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, mut req: Request<()>) -> Self::Future {
+            let keys = self.0.clone();
+            Box::pin(async move {
+                let expected = format!("Bearer {}", keys.key().await);
+                match req.metadata().get("authorization") {
+                    Some(token) if token == expected.as_str() => {
+                        req.extensions_mut().insert(User("alice".to_string()));
+                        Ok(req)
+                    }
+                    _ => Err(Status::unauthenticated("invalid token")),
+                }
+            })
+        }
+    }
+
+    let keys = Arc::new(KeyStore::default());
+    let svc = test_server::TestServer::new(Svc);
+
+    let (tx, rx) = oneshot::channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let auth = Auth(keys.clone());
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .layer(AsyncInterceptorLayer::new(auth))
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        .insert("authorization", "Bearer secret".parse().unwrap());
+    client.unary_call(req).await.unwrap();
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        .insert("authorization", "Bearer wrong".parse().unwrap());
+    let status = client.unary_call(req).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let status = client.unary_call(Request::new(Input {})).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    assert_eq!(keys.fetches.load(Ordering::SeqCst), 1);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
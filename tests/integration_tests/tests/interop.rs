@@ -0,0 +1,260 @@
+//! Drives the server with request bodies in the byte-level framing reference clients produce.
+//!
+//! Every case sends a fixture from `fixtures/interop` over a raw HTTP/2 connection, with the
+//! headers of the respective client, and compares the response body with its fixture byte by
+//! byte. grpc-go writes every message in a single DATA frame, while grpc-java writes the
+//! five-byte message prefix and the payload separately, so both paths of the decoder are covered.
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue};
+use integration_tests::pb::{test1_server, Input1, Output1};
+use std::{future, pin::Pin, time::Duration};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tokio_stream::Stream;
+use tonic::{
+    codec::CompressionEncoding,
+    transport::{server::TcpIncoming, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let buf = req.into_inner().buf;
+        if buf == b"fail" {
+            return Err(Status::with_details(
+                Code::InvalidArgument,
+                "invalid café: 100%",
+                Bytes::from_static(b"\x08\x03"),
+            ));
+        }
+        Ok(Response::new(Output1 { buf }))
+    }
+
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send>>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let messages = req.into_inner().buf.into_iter().map(|byte| match byte {
+            b'!' => Err(Status::aborted("stream aborted")),
+            byte => Ok(Output1 { buf: vec![byte] }),
+        });
+        Ok(Response::new(Box::pin(tokio_stream::iter(messages))))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Client {
+    Go,
+    Java,
+}
+
+impl Client {
+    fn headers(self, path: &str, addr: &str) -> http::Request<()> {
+        let builder = http::Request::post(format!("http://{addr}{path}"))
+            .header("content-type", "application/grpc")
+            .header("te", "trailers");
+        match self {
+            Client::Go => builder
+                .header("user-agent", "grpc-go/1.64.0")
+                .header("grpc-timeout", "4999871u"),
+            Client::Java => builder
+                .header("user-agent", "grpc-java-netty/1.64.0")
+                .header("grpc-accept-encoding", "gzip"),
+        }
+        .body(())
+        .unwrap()
+    }
+
+    /// Splits a request body into the DATA frames the client sends it in.
+    fn chunks(self, body: &'static [u8]) -> Vec<Bytes> {
+        match self {
+            Client::Go => vec![Bytes::from_static(body)],
+            Client::Java => {
+                let (prefix, payload) = body.split_at(5);
+                vec![Bytes::from_static(prefix), Bytes::from_static(payload)]
+            }
+        }
+    }
+}
+
+struct Exchange {
+    headers: HeaderMap,
+    body: Vec<u8>,
+    /// `None` for a trailers-only response.
+    trailers: Option<HeaderMap>,
+}
+
+async fn exchange(
+    client: Client,
+    path: &str,
+    extra_headers: &[(&'static str, &'static str)],
+    body: &'static [u8],
+) -> Exchange {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let svc = test1_server::Test1Server::new(Svc).accept_compressed(CompressionEncoding::Gzip);
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let io = TcpStream::connect(addr).await.unwrap();
+    let (h2, connection) = h2::client::handshake(io).await.unwrap();
+    tokio::spawn(connection);
+    let mut h2 = h2.ready().await.unwrap();
+
+    let mut request = client.headers(path, &addr.to_string());
+    for (name, value) in extra_headers {
+        request
+            .headers_mut()
+            .insert(*name, HeaderValue::from_static(value));
+    }
+
+    let (response, mut send) = h2.send_request(request, false).unwrap();
+    let chunks = client.chunks(body);
+    let last = chunks.len() - 1;
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        send.send_data(chunk, i == last).unwrap();
+    }
+
+    let response = tokio::time::timeout(Duration::from_secs(5), response)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let (parts, mut recv) = response.into_parts();
+
+    let trailers_only = recv.is_end_stream();
+    let mut body = Vec::new();
+    while let Some(data) = future::poll_fn(|cx| recv.poll_data(cx)).await {
+        let data = data.unwrap();
+        recv.flow_control().release_capacity(data.len()).unwrap();
+        body.extend_from_slice(&data);
+    }
+    let trailers = recv.trailers().await.unwrap();
+    assert_eq!(trailers_only, trailers.is_none());
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+
+    Exchange {
+        headers: parts.headers,
+        body,
+        trailers,
+    }
+}
+
+macro_rules! fixture {
+    ($name:literal) => {
+        include_bytes!(concat!("../fixtures/interop/", $name, ".bin")).as_slice()
+    };
+}
+
+fn assert_ok(exchange: &Exchange) {
+    assert_eq!(exchange.headers["content-type"], "application/grpc");
+    let trailers = exchange.trailers.as_ref().expect("missing trailers");
+    assert_eq!(trailers["grpc-status"], "0");
+    assert!(!trailers.contains_key("grpc-message"));
+}
+
+#[tokio::test]
+async fn unary() {
+    for client in [Client::Go, Client::Java] {
+        let exchange = exchange(
+            client,
+            "/test.Test1/UnaryCall",
+            &[],
+            fixture!("unary.request"),
+        )
+        .await;
+        assert_ok(&exchange);
+        assert_eq!(exchange.body, fixture!("unary.response"));
+    }
+}
+
+#[tokio::test]
+async fn unary_compressed() {
+    for client in [Client::Go, Client::Java] {
+        let exchange = exchange(
+            client,
+            "/test.Test1/UnaryCall",
+            &[("grpc-encoding", "gzip")],
+            fixture!("unary_gzip.request"),
+        )
+        .await;
+        assert_ok(&exchange);
+        // The response is not compressed, since the server does not send compressed messages.
+        assert!(!exchange.headers.contains_key("grpc-encoding"));
+        assert_eq!(exchange.body, fixture!("unary_gzip.response"));
+    }
+}
+
+#[tokio::test]
+async fn server_streaming() {
+    for client in [Client::Go, Client::Java] {
+        let exchange = exchange(
+            client,
+            "/test.Test1/StreamCall",
+            &[],
+            fixture!("server_streaming.request"),
+        )
+        .await;
+        assert_ok(&exchange);
+        assert_eq!(exchange.body, fixture!("server_streaming.response"));
+    }
+}
+
+#[tokio::test]
+async fn server_streaming_error() {
+    for client in [Client::Go, Client::Java] {
+        let exchange = exchange(
+            client,
+            "/test.Test1/StreamCall",
+            &[],
+            fixture!("server_streaming_error.request"),
+        )
+        .await;
+        assert_eq!(exchange.body, fixture!("server_streaming_error.response"));
+
+        let trailers = exchange.trailers.expect("missing trailers");
+        assert_eq!(trailers["grpc-status"], "10");
+        assert_eq!(trailers["grpc-message"], "stream%20aborted");
+    }
+}
+
+#[tokio::test]
+async fn trailers_only() {
+    for client in [Client::Go, Client::Java] {
+        let exchange = exchange(
+            client,
+            "/test.Test1/UnaryCall",
+            &[],
+            fixture!("trailers_only.request"),
+        )
+        .await;
+        assert!(exchange.body.is_empty());
+        assert!(exchange.trailers.is_none());
+
+        let headers = exchange.headers;
+        assert_eq!(headers["content-type"], "application/grpc");
+        assert_eq!(headers["grpc-status"], "3");
+        assert_eq!(headers["grpc-message"], "invalid%20caf%C3%A9:%20100%25");
+        // Binary headers are base64 encoded without padding.
+        assert_eq!(headers["grpc-status-details-bin"], "CAM");
+    }
+}
@@ -0,0 +1,110 @@
+use integration_tests::pb::{test1_client, test1_server, Input1, Output1};
+use std::{net::SocketAddr, time::Duration};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Endpoint, Server},
+    LoadReport, Request, Response, Status,
+};
+
+type Stream<T> = std::pin::Pin<
+    Box<dyn tokio_stream::Stream<Item = std::result::Result<T, Status>> + Send + 'static>,
+>;
+
+/// Answers with its name, reporting a fixed CPU utilization.
+struct Svc {
+    name: &'static str,
+    utilization: f64,
+}
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let mut report = LoadReport::new();
+        report.set_cpu_utilization(self.utilization);
+
+        let mut response = Response::new(Output1 {
+            buf: self.name.as_bytes().to_vec(),
+        });
+        response.set_load_report(report);
+        Ok(response)
+    }
+
+    type StreamCallStream = Stream<Output1>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        unimplemented!()
+    }
+}
+
+async fn run(svc: Svc) -> (SocketAddr, oneshot::Sender<()>) {
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    (addr, tx)
+}
+
+#[tokio::test]
+async fn client_reads_the_reported_load() {
+    let (addr, _tx) = run(Svc {
+        name: "busy",
+        utilization: 0.9,
+    })
+    .await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test1_client::Test1Client::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let response = client.unary_call(Input1::default()).await.unwrap();
+
+    let report = LoadReport::from_metadata(response.metadata()).unwrap();
+    assert_eq!(report.cpu_utilization(), 0.9);
+}
+
+#[tokio::test]
+async fn balancer_prefers_the_less_loaded_endpoint() {
+    let (busy, _busy_tx) = run(Svc {
+        name: "busy",
+        utilization: 0.9,
+    })
+    .await;
+    let (idle, _idle_tx) = run(Svc {
+        name: "idle",
+        utilization: 0.1,
+    })
+    .await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let endpoints = [busy, idle]
+        .into_iter()
+        .map(|addr| Endpoint::from_shared(format!("http://{addr}")).unwrap());
+    let mut client = test1_client::Test1Client::new(Channel::balance_list(endpoints));
+
+    // Until both endpoints reported their load, they count as idle and are picked at random.
+    let mut seen = Vec::new();
+    while seen.len() < 2 {
+        let response = client.unary_call(Input1::default()).await.unwrap();
+        let name = response.into_inner().buf;
+        if !seen.contains(&name) {
+            seen.push(name);
+        }
+    }
+
+    // With two endpoints, the balancer compares both on every call.
+    for _ in 0..20 {
+        let response = client.unary_call(Input1::default()).await.unwrap();
+        assert_eq!(response.into_inner().buf, b"idle");
+    }
+}
@@ -0,0 +1,99 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{net::SocketAddr, time::Duration};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+    task::JoinHandle,
+};
+use tonic::transport::{
+    server::TcpIncoming, Certificate, Channel, ClientTlsConfig, Identity, Server, ServerTlsConfig,
+};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+const CA: &str = include_str!("../../../examples/data/tls/ca.pem");
+const SERVER_CERT: &str = include_str!("../../../examples/data/tls/server.pem");
+const SERVER_KEY: &str = include_str!("../../../examples/data/tls/server.key");
+
+async fn serve(
+    max_concurrent_handshakes: Option<usize>,
+) -> (SocketAddr, oneshot::Sender<()>, JoinHandle<()>) {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let router = Server::builder()
+        .tls_config(ServerTlsConfig::new().identity(Identity::from_pem(SERVER_CERT, SERVER_KEY)))
+        .unwrap()
+        .max_concurrent_handshakes(max_concurrent_handshakes)
+        .add_service(test_server::TestServer::new(Svc));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    (addr, tx, jh)
+}
+
+/// Connects and completes a call, returning whether that happened within `wait`.
+async fn call_within(addr: SocketAddr, wait: Duration) -> bool {
+    let call = async {
+        let tls = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(CA))
+            .domain_name("example.com");
+        let channel = Channel::from_shared(format!("https://{addr}"))
+            .unwrap()
+            .tls_config(tls)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        TestClient::new(channel)
+            .unary_call(Request::new(Input {}))
+            .await
+            .unwrap();
+    };
+    tokio::time::timeout(wait, call).await.is_ok()
+}
+
+#[tokio::test]
+async fn handshakes_beyond_the_limit_wait() {
+    let (addr, tx, jh) = serve(Some(1)).await;
+
+    // A client that never sends its hello holds the only handshake slot.
+    let stalled = TcpStream::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!call_within(addr, Duration::from_millis(300)).await);
+
+    // Once its handshake fails, the next one proceeds.
+    drop(stalled);
+    assert!(call_within(addr, Duration::from_secs(2)).await);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn handshakes_are_concurrent_by_default() {
+    let (addr, tx, jh) = serve(None).await;
+
+    let stalled = TcpStream::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(call_within(addr, Duration::from_secs(2)).await);
+
+    drop(stalled);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,93 @@
+use integration_tests::pb::{test_server, Input, Output};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        unimplemented!()
+    }
+}
+
+/// Sends the client preface and returns the settings of the server's first `SETTINGS` frame.
+async fn server_settings(stream: &mut TcpStream) -> Vec<(u16, u32)> {
+    stream.write_all(PREFACE).await.unwrap();
+    stream
+        .write_all(&[0, 0, 0, 0x4, 0, 0, 0, 0, 0])
+        .await
+        .unwrap();
+
+    let mut header = [0; 9];
+    stream.read_exact(&mut header).await.unwrap();
+    assert_eq!(header[3], 0x4, "expected a SETTINGS frame");
+    let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+
+    let mut payload = vec![0; len];
+    stream.read_exact(&mut payload).await.unwrap();
+    payload
+        .chunks(6)
+        .map(|setting| {
+            let id = u16::from_be_bytes([setting[0], setting[1]]);
+            let value = u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]);
+            (id, value)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn server_advertises_max_frame_size() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .max_frame_size(1 << 20)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let settings = server_settings(&mut stream).await;
+    assert!(
+        settings.contains(&(SETTINGS_MAX_FRAME_SIZE, 1 << 20)),
+        "{settings:?}"
+    );
+
+    drop(stream);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_max_frame_size_is_an_error() {
+    for size in [1024, 1 << 24] {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let incoming = TcpIncoming::from(listener);
+
+        let err = Server::builder()
+            .max_frame_size(size)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap_err();
+        let source = std::error::Error::source(&err).unwrap().to_string();
+        assert!(source.contains("max frame size"), "{source}");
+    }
+}
@@ -1,4 +1,4 @@
-use std::pin::Pin;
+use std::{pin::Pin, time::Duration};
 
 use hyper_util::rt::TokioIo;
 use integration_tests::{
@@ -10,6 +10,7 @@ use tonic::{
     transport::{Endpoint, Server},
     Code, Request, Response, Status,
 };
+use tower::ServiceExt;
 
 #[test]
 fn max_message_recv_size() {
@@ -189,6 +190,60 @@ async fn response_stream_limit() {
     while let Some(_b) = stream.message().await.unwrap() {}
 }
 
+#[tokio::test]
+async fn oversized_content_length_rejected_before_body() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test1_server::Test1 for Svc {
+        async fn unary_call(&self, _req: Request<Input1>) -> Result<Response<Output1>, Status> {
+            unreachable!("request should be rejected before reaching the handler")
+        }
+
+        type StreamCallStream =
+            Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+        async fn stream_call(
+            &self,
+            _req: Request<Input1>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            unimplemented!()
+        }
+    }
+
+    // A body that never yields, so the request can only complete if it is
+    // rejected without reading it.
+    struct PendingBody;
+
+    impl http_body::Body for PendingBody {
+        type Data = bytes::Bytes;
+        type Error = Status;
+
+        fn poll_frame(
+            self: Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+            std::task::Poll::Pending
+        }
+    }
+
+    let svc = test1_server::Test1Server::new(Svc).max_decoding_message_size(1024);
+
+    let request = http::Request::post("/test.Test1/UnaryCall")
+        .header("content-type", "application/grpc")
+        .header("content-length", 1024 + 5 + 1)
+        .body(PendingBody)
+        .unwrap();
+
+    let response = tokio::time::timeout(Duration::from_secs(1), svc.oneshot(request))
+        .await
+        .expect("request was not rejected immediately")
+        .unwrap();
+
+    let status = Status::from_header_map(response.headers()).unwrap();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+}
+
 // Track caller doesn't work on async fn so we extract the async part
 // into a sync version and assert the response there using track track_caller
 // so that when this does panic it tells us which line in the test failed not
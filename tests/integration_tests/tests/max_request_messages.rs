@@ -0,0 +1,82 @@
+use integration_tests::pb::{
+    test_client_stream_client::TestClientStreamClient, test_client_stream_server, InputStream,
+    OutputStream,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Code, Request, Response, Status, Streaming,
+};
+
+#[tokio::test]
+async fn client_streaming_past_the_limit_is_rejected() {
+    const LIMIT: usize = 3;
+
+    struct Svc(Arc<AtomicUsize>);
+
+    #[tonic::async_trait]
+    impl test_client_stream_server::TestClientStream for Svc {
+        async fn client_stream_call(
+            &self,
+            req: Request<Streaming<InputStream>>,
+        ) -> Result<Response<OutputStream>, Status> {
+            let mut stream = req.into_inner();
+            let result = loop {
+                match stream.message().await {
+                    Ok(Some(_)) => {
+                        self.0.fetch_add(1, Ordering::SeqCst);
+                    }
+                    Ok(None) => break Ok(Response::new(OutputStream {})),
+                    Err(status) => break Err(status),
+                }
+            };
+
+            // The stream ends after yielding the error.
+            assert!(matches!(stream.message().await, Ok(None)));
+            result
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let received = Arc::new(AtomicUsize::new(0));
+    let svc = test_client_stream_server::TestClientStreamServer::new(Svc(received.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .max_request_messages(LIMIT)
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClientStreamClient::new(channel);
+
+    let within_limit = tokio_stream::iter(vec![InputStream {}; LIMIT]);
+    client.client_stream_call(within_limit).await.unwrap();
+    assert_eq!(received.swap(0, Ordering::SeqCst), LIMIT);
+
+    let past_limit = tokio_stream::iter(vec![InputStream {}; LIMIT * 3]);
+    let status = client.client_stream_call(past_limit).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert_eq!(received.load(Ordering::SeqCst), LIMIT);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,109 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        // Keep the request in flight while the server starts closing the connection.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn connections_are_recycled_after_max_requests() {
+    let (tx, rx) = oneshot::channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let mut builder = Server::builder().max_requests_per_connection(2);
+    let stats = builder.stats();
+
+    let jh = tokio::spawn(async move {
+        builder
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    // The client reconnects on its own once a connection is closed.
+    for _ in 0..5 {
+        client.unary_call(Request::new(Input {})).await.unwrap();
+    }
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.total_requests(), 5);
+    assert_eq!(snapshot.total_connections(), 3);
+    assert_eq!(snapshot.recycled_connections(), 2);
+    assert_eq!(snapshot.active_connections(), 1);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn connections_are_recycled_before_stream_ids_run_out() {
+    let (tx, rx) = oneshot::channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    // Lowered from its default, so a few requests exhaust the "stream IDs".
+    let mut builder = Server::builder()
+        .max_requests_per_connection(10)
+        .http2_stream_id_threshold(3);
+    let stats = builder.stats();
+
+    let jh = tokio::spawn(async move {
+        builder
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    for _ in 0..7 {
+        client.unary_call(Request::new(Input {})).await.unwrap();
+    }
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.total_requests(), 7);
+    assert_eq!(snapshot.total_connections(), 3);
+    assert_eq!(snapshot.recycled_connections(), 2);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,88 @@
+use integration_tests::pb::{
+    test_stream_client::TestStreamClient, test_stream_server, InputStream, OutputStream,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::StreamExt;
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+type Stream<T> = std::pin::Pin<
+    Box<dyn tokio_stream::Stream<Item = std::result::Result<T, Status>> + Send + 'static>,
+>;
+
+#[tokio::test]
+async fn server_streaming_past_the_limit_is_terminated() {
+    const LIMIT: usize = 3;
+
+    struct Svc(Arc<AtomicUsize>);
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Svc {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            // An endless stream that counts how many messages were pulled from it.
+            let produced = self.0.clone();
+            let stream = tokio_stream::iter(0..).map(move |_| {
+                produced.fetch_add(1, Ordering::SeqCst);
+                Ok(OutputStream {})
+            });
+
+            Ok(Response::new(Box::pin(stream) as Self::StreamCallStream))
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let produced = Arc::new(AtomicUsize::new(0));
+    let svc = test_stream_server::TestStreamServer::new(Svc(produced.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .max_response_messages(LIMIT)
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestStreamClient::new(channel);
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    for _ in 0..LIMIT {
+        stream.message().await.unwrap().unwrap();
+    }
+    let status = stream.message().await.unwrap_err();
+    assert_eq!(status.code(), Code::Internal);
+
+    // The handler's stream was dropped after the message past the limit.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(produced.load(Ordering::SeqCst), LIMIT + 1);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,63 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    metadata::MetadataKey,
+    transport::{server::TcpIncoming, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+fn request(entries: usize) -> Request<Input> {
+    let mut req = Request::new(Input {});
+    for i in 0..entries {
+        let key = MetadataKey::from_bytes(format!("x-entry-{}", i).as_bytes()).unwrap();
+        req.metadata_mut().insert(key, "value".parse().unwrap());
+    }
+    req
+}
+
+#[tokio::test]
+async fn rejects_excessive_metadata() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .max_metadata_entries(8)
+            .max_metadata_size(1024)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    client.unary_call(request(8)).await.unwrap();
+
+    let status = client.unary_call(request(9)).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        .insert("x-large", "a".repeat(2048).parse().unwrap());
+    let status = client.unary_call(req).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,107 @@
+use integration_tests::pb::{
+    test1_client, test1_server, test_client, test_server, Input, Input1, Output, Output1,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    net::TcpListener,
+    sync::{oneshot, Semaphore},
+};
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Server},
+    Code, Request, Response, Status,
+};
+
+const EXPENSIVE: &str = "/test.Test/UnaryCall";
+
+/// Blocks every call until the test adds a permit for it.
+struct Expensive(Arc<Semaphore>);
+
+#[tonic::async_trait]
+impl test_server::Test for Expensive {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.0.acquire().await.unwrap().forget();
+        Ok(Response::new(Output {}))
+    }
+}
+
+struct Cheap;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Cheap {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1::default()))
+    }
+
+    type StreamCallStream = tokio_stream::Empty<Result<Output1, Status>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        Ok(Response::new(tokio_stream::empty()))
+    }
+}
+
+#[tokio::test]
+async fn limit_applies_to_one_method_only() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let release = Arc::new(Semaphore::new(0));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let mut builder = Server::builder().method_concurrency(EXPENSIVE, 2);
+    let stats = builder.stats();
+    let router = builder
+        .add_service(test_server::TestServer::new(Expensive(release.clone())))
+        .add_service(test1_server::Test1Server::new(Cheap));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let in_flight = (0..2)
+        .map(|_| {
+            let mut client = test_client::TestClient::new(channel.clone());
+            tokio::spawn(async move { client.unary_call(Input {}).await })
+        })
+        .collect::<Vec<_>>();
+    while stats.snapshot().active_requests_per_method().get(EXPENSIVE) != Some(&2) {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let mut expensive = test_client::TestClient::new(channel.clone());
+    let status = expensive.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    // Other methods are not throttled.
+    let mut cheap = test1_client::Test1Client::new(channel.clone());
+    for _ in 0..8 {
+        cheap.unary_call(Input1::default()).await.unwrap();
+    }
+
+    release.add_permits(2);
+    for call in in_flight {
+        call.await.unwrap().unwrap();
+    }
+    assert_eq!(
+        stats.snapshot().active_requests_per_method().get(EXPENSIVE),
+        Some(&0)
+    );
+
+    release.add_permits(1);
+    expensive.unary_call(Input {}).await.unwrap();
+
+    drop((channel, expensive, cheap));
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,88 @@
+use integration_tests::pb::{test1_client, test1_server, Input1, Output1};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::StreamExt;
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+type Stream<T> = std::pin::Pin<
+    Box<dyn tokio_stream::Stream<Item = std::result::Result<T, Status>> + Send + 'static>,
+>;
+
+/// Answers with the `grpc-timeout` the call was sent with, if any.
+struct Svc;
+
+fn timeout_of<T>(req: &Request<T>) -> Output1 {
+    let buf = req
+        .metadata()
+        .get("grpc-timeout")
+        .map(|value| value.as_bytes().to_vec())
+        .unwrap_or_default();
+    Output1 { buf }
+}
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(timeout_of(&req)))
+    }
+
+    type StreamCallStream = Stream<Output1>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let stream = tokio_stream::once(Ok(timeout_of(&req)));
+        Ok(Response::new(Box::pin(stream) as Self::StreamCallStream))
+    }
+}
+
+#[tokio::test]
+async fn configured_methods_get_their_default_timeout() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .method_timeouts([("/test.Test1/UnaryCall", Duration::from_secs(5))])
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test1_client::Test1Client::new(channel);
+
+    let res = client.unary_call(Input1::default()).await.unwrap();
+    assert_eq!(res.get_ref().buf, b"5000000u");
+
+    // The timeout of the call wins over the default.
+    let mut req = Request::new(Input1::default());
+    req.set_timeout(Duration::from_secs(30));
+    let res = client.unary_call(req).await.unwrap();
+    assert_eq!(res.get_ref().buf, b"30000000u");
+
+    // Methods without a default are sent without a timeout.
+    let mut stream = client
+        .stream_call(Input1::default())
+        .await
+        .unwrap()
+        .into_inner();
+    let msg = stream.next().await.unwrap().unwrap();
+    assert!(msg.buf.is_empty());
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,135 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    service::InterceptorLayer,
+    transport::{
+        server::{PeerIdentity, TcpIncoming},
+        Certificate, Channel, ClientTlsConfig, Identity, Server, ServerTlsConfig,
+    },
+    Code, Request, Response, Status,
+};
+
+const CA: &str = include_str!("../../../examples/data/tls/ca.pem");
+const SERVER_CERT: &str = include_str!("../../../examples/data/tls/server.pem");
+const SERVER_KEY: &str = include_str!("../../../examples/data/tls/server.key");
+
+/// The client authorized by the interceptor.
+#[derive(Clone)]
+struct Authorized(String);
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let Authorized(client) = req
+            .extensions()
+            .get::<Authorized>()
+            .cloned()
+            .ok_or_else(|| Status::internal("handler ran before the interceptor"))?;
+        let mut res = Response::new(Output {});
+        res.metadata_mut().insert("client", client.parse().unwrap());
+        Ok(res)
+    }
+}
+
+fn authorize(mut req: Request<()>) -> Result<Request<()>, Status> {
+    let identity = req
+        .extensions()
+        .get::<PeerIdentity>()
+        .ok_or_else(|| Status::unauthenticated("no client certificate"))?;
+    let client = match identity.common_name() {
+        Some(client @ "client1") => client.to_owned(),
+        _ => return Err(Status::permission_denied("client not allowed")),
+    };
+    req.extensions_mut().insert(Authorized(client));
+    Ok(req)
+}
+
+/// Issues client certificates.
+struct ClientCa {
+    cert: rcgen::Certificate,
+    key: KeyPair,
+}
+
+impl ClientCa {
+    fn new() -> Self {
+        let mut params = CertificateParams::default();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let key = KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+        Self { cert, key }
+    }
+
+    fn issue(&self, common_name: &str) -> Identity {
+        let mut params = CertificateParams::default();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, common_name);
+        let key = KeyPair::generate().unwrap();
+        let cert = params.signed_by(&key, &self.cert, &self.key).unwrap();
+        Identity::from_pem(cert.pem(), key.serialize_pem())
+    }
+}
+
+#[tokio::test]
+async fn interceptor_authorizes_client_certificates() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let client_ca = ClientCa::new();
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let tls = ServerTlsConfig::new()
+        .identity(Identity::from_pem(SERVER_CERT, SERVER_KEY))
+        .client_ca_root(Certificate::from_pem(client_ca.cert.pem()))
+        .client_auth_optional(true);
+    let router = Server::builder()
+        .tls_config(tls)
+        .unwrap()
+        .layer(InterceptorLayer::new(authorize))
+        .add_service(test_server::TestServer::new(Svc));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let call = |identity: Option<Identity>| async move {
+        let mut tls = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(CA))
+            .domain_name("example.com");
+        if let Some(identity) = identity {
+            tls = tls.identity(identity);
+        }
+        let channel = Channel::from_shared(format!("https://{addr}"))
+            .unwrap()
+            .tls_config(tls)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        TestClient::new(channel)
+            .unary_call(Request::new(Input {}))
+            .await
+    };
+
+    let res = call(Some(client_ca.issue("client1"))).await.unwrap();
+    assert_eq!(res.metadata().get("client").unwrap(), "client1");
+
+    let status = call(Some(client_ca.issue("client2"))).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    let status = call(None).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,84 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, oneshot, watch},
+};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc {
+    started: mpsc::UnboundedSender<()>,
+    released: watch::Receiver<bool>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.started.send(()).unwrap();
+        self.released
+            .clone()
+            .wait_for(|released| *released)
+            .await
+            .unwrap();
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn paused_server_serves_accepted_connections_only() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let (started_tx, mut started) = mpsc::unbounded_channel();
+    let (release, released) = watch::channel(false);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let mut builder = Server::builder();
+    let stats = builder.stats();
+    let connections = builder.connections();
+    let router = builder.add_service(test_server::TestServer::new(Svc {
+        started: started_tx,
+        released,
+    }));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let call = |addr| async move {
+        let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        client.unary_call(Input {}).await.unwrap();
+    };
+
+    // A request in flight on an accepted connection.
+    let in_flight = tokio::spawn(call(addr));
+    started.recv().await.unwrap();
+
+    connections.pause_accepting();
+    assert!(connections.is_accepting_paused());
+
+    let new_connection = tokio::spawn(call(addr));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!new_connection.is_finished());
+    assert_eq!(stats.snapshot().total_connections(), 1);
+
+    // The request in flight completes while paused.
+    release.send_replace(true);
+    in_flight.await.unwrap();
+    assert!(!new_connection.is_finished());
+
+    connections.resume_accepting();
+    new_connection.await.unwrap();
+    assert_eq!(stats.snapshot().total_connections(), 2);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
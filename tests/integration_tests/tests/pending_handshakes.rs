@@ -0,0 +1,68 @@
+#![cfg(target_os = "linux")]
+
+use integration_tests::pb::{test_server, Input, Output};
+use std::{net::SocketAddr, time::Duration};
+use tokio::{
+    io::AsyncReadExt,
+    net::{TcpListener, TcpSocket, TcpStream},
+};
+use tonic::transport::{server::TcpIncoming, Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+const SERVER_CERT: &str = include_str!("../../../examples/data/tls/server.pem");
+const SERVER_KEY: &str = include_str!("../../../examples/data/tls/server.key");
+
+/// Connects to `addr` from `ip`, any address of the loopback network on Linux.
+async fn connect_from(ip: &str, addr: SocketAddr) -> TcpStream {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(format!("{}:0", ip).parse().unwrap()).unwrap();
+    socket.connect(addr).await.unwrap()
+}
+
+/// Whether the server closed `stream`, which never starts its handshake.
+async fn is_closed(stream: &mut TcpStream) -> bool {
+    let mut buf = [0; 1];
+    let read = tokio::time::timeout(Duration::from_millis(200), stream.read(&mut buf)).await;
+    matches!(read, Ok(Ok(0) | Err(_)))
+}
+
+#[tokio::test]
+async fn pending_handshakes_are_capped_per_ip() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let tls = ServerTlsConfig::new().identity(Identity::from_pem(SERVER_CERT, SERVER_KEY));
+    let router = Server::builder()
+        .tls_config(tls)
+        .unwrap()
+        .max_pending_handshakes_per_ip(2)
+        .add_service(test_server::TestServer::new(Svc));
+    tokio::spawn(router.serve_with_incoming(TcpIncoming::from(listener)));
+
+    let mut first = connect_from("127.0.0.1", addr).await;
+    let mut second = connect_from("127.0.0.1", addr).await;
+    let mut excess = connect_from("127.0.0.1", addr).await;
+    let mut other_ip = connect_from("127.0.0.2", addr).await;
+
+    assert!(is_closed(&mut excess).await);
+    assert!(!is_closed(&mut first).await);
+    assert!(!is_closed(&mut second).await);
+    assert!(!is_closed(&mut other_ip).await);
+
+    // A handshake that ends frees its slot.
+    drop(first);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut next = connect_from("127.0.0.1", addr).await;
+    assert!(!is_closed(&mut next).await);
+}
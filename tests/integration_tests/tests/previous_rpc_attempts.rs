@@ -0,0 +1,71 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::sync::{Arc, Mutex};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Code, PreviousRpcAttempts, Request, Response, Status,
+};
+
+/// Fails the first attempt of every call, recording the attempts it saw.
+#[derive(Clone, Default)]
+struct Svc(Arc<Mutex<Vec<Option<u32>>>>);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let attempts = req
+            .extensions()
+            .get::<PreviousRpcAttempts>()
+            .map(PreviousRpcAttempts::get);
+        self.0.lock().unwrap().push(attempts);
+
+        match attempts {
+            None => Err(Status::unavailable("try again")),
+            Some(_) => Ok(Response::new(Output {})),
+        }
+    }
+}
+
+#[tokio::test]
+async fn retry_carries_previous_attempts() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let svc = Svc::default();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn({
+        let svc = svc.clone();
+        async move {
+            Server::builder()
+                .add_service(test_server::TestServer::new(svc))
+                .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+                .await
+                .unwrap();
+        }
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let mut attempts = 0;
+    loop {
+        let mut request = Request::new(Input {});
+        if attempts > 0 {
+            request.set_previous_rpc_attempts(attempts);
+        }
+        match client.unary_call(request).await {
+            Ok(_) => break,
+            Err(status) if status.code() == Code::Unavailable && attempts < 3 => attempts += 1,
+            Err(status) => panic!("call failed: {status:?}"),
+        }
+    }
+
+    assert_eq!(*svc.0.lock().unwrap(), [None, Some(1)]);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
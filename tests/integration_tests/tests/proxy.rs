@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use integration_tests::pb::{
+    test1_client, test1_server, test_server, Input, Input1, Output, Output1,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::StreamExt;
+use tonic::{
+    codegen::BoxStream,
+    service::Routes,
+    transport::{server::TcpIncoming, Channel, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+struct Backend;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Backend {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let request_id = req.metadata().get("x-request-id").unwrap().clone();
+        let mut res = Response::new(Output1 {
+            buf: req.into_inner().buf,
+        });
+        res.metadata_mut().insert("x-request-id", request_id);
+        Ok(res)
+    }
+
+    type StreamCallStream = BoxStream<Output1>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let buf = req.into_inner().buf;
+        let stream = tokio_stream::iter(0..3u8)
+            .map(move |i| {
+                Ok(Output1 {
+                    buf: [buf.as_slice(), &[i]].concat(),
+                })
+            })
+            .chain(tokio_stream::once(Err(Status::data_loss(
+                "end of the stream",
+            ))));
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+async fn serve(router: tonic::transport::server::Router) -> (Channel, oneshot::Sender<()>) {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    (channel, tx)
+}
+
+async fn proxy() -> (test1_client::Test1Client<Channel>, [oneshot::Sender<()>; 2]) {
+    let (backend, backend_tx) =
+        serve(Server::builder().add_service(test1_server::Test1Server::new(Backend))).await;
+    let (channel, proxy_tx) = serve(
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .proxy_all(backend),
+    )
+    .await;
+
+    (
+        test1_client::Test1Client::new(channel),
+        [backend_tx, proxy_tx],
+    )
+}
+
+#[tokio::test]
+async fn proxies_unary_calls() {
+    let (mut client, _tx) = proxy().await;
+
+    let mut req = Request::new(Input1 {
+        buf: b"hello".to_vec(),
+    });
+    req.metadata_mut()
+        .insert("x-request-id", "42".parse().unwrap());
+    let res = client.unary_call(req).await.unwrap();
+
+    assert_eq!(res.metadata().get("x-request-id").unwrap(), "42");
+    assert_eq!(res.into_inner().buf, b"hello");
+}
+
+#[tokio::test]
+async fn proxies_server_streaming_calls() {
+    let (mut client, _tx) = proxy().await;
+
+    let mut stream = client
+        .stream_call(Input1 {
+            buf: b"chunk".to_vec(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    for i in 0..3u8 {
+        let message = stream.message().await.unwrap().unwrap();
+        assert_eq!(message.buf, [b"chunk".as_slice(), &[i]].concat());
+    }
+
+    // The status in the trailers of the backend reaches the client.
+    let status = stream.message().await.unwrap_err();
+    assert_eq!(status.code(), Code::DataLoss);
+    assert_eq!(status.message(), "end of the stream");
+}
+
+#[tokio::test]
+async fn unreachable_backend_is_unavailable() {
+    let backend = Endpoint::from_static("http://127.0.0.1:1").connect_lazy();
+    let (channel, _tx) =
+        serve(Server::builder().add_routes(Routes::default().proxy_all(backend))).await;
+
+    let mut client = test1_client::Test1Client::new(channel);
+    let status = client.unary_call(Input1::default()).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+}
@@ -0,0 +1,124 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+use tower::{Layer, Service};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// Keeps the services from becoming ready until it is opened.
+#[derive(Clone, Default)]
+struct Gate(Arc<Mutex<(bool, Option<Waker>)>>);
+
+impl Gate {
+    fn open(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.0 = true;
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<S> Layer<S> for Gate {
+    type Service = Gated<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Gated {
+            inner,
+            gate: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Gated<S> {
+    inner: S,
+    gate: Gate,
+}
+
+impl<S, R> Service<R> for Gated<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        {
+            let mut state = self.gate.0.lock().unwrap();
+            if !state.0 {
+                state.1 = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+async fn resolves_within<F: std::future::Future>(future: F, wait: Duration) -> bool {
+    tokio::time::timeout(wait, future).await.is_ok()
+}
+
+#[tokio::test]
+async fn ready_once_bound_and_services_are_ready() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let gate = Gate::default();
+
+    let builder = Server::builder().layer(gate.clone());
+    let ready = builder.ready();
+    tokio::pin!(ready);
+
+    // Not ready before serving.
+    assert!(!resolves_within(&mut ready, Duration::from_millis(100)).await);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let mut builder = builder;
+    let router = builder.add_service(test_server::TestServer::new(Svc));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    // Bound, but the services aren't ready yet.
+    assert!(!resolves_within(&mut ready, Duration::from_millis(100)).await);
+
+    gate.open();
+    assert!(resolves_within(&mut ready, Duration::from_secs(1)).await);
+
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+
+    // Not ready anymore once shut down.
+    assert!(!resolves_within(builder.ready(), Duration::from_millis(100)).await);
+}
@@ -0,0 +1,128 @@
+use integration_tests::pb::{test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    service::MethodFilterLayer,
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        let mut res = Response::new(Output {});
+        res.metadata_mut()
+            .insert("x-served-by", "test.Test".parse().unwrap());
+        Ok(res)
+    }
+}
+
+#[tokio::test]
+async fn deprecated_paths_are_served_by_the_new_method() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            // `test.v1.Test` was renamed to `test.Test`.
+            .resolve_path(|path| {
+                path.strip_prefix("/test.v1.Test/")
+                    .map(|method| format!("/test.Test/{method}"))
+            })
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = Grpc::new(channel);
+
+    for path in ["/test.v1.Test/UnaryCall", "/test.Test/UnaryCall"] {
+        client.ready().await.unwrap();
+        let res = client
+            .unary(
+                Request::new(Input {}),
+                path.parse().unwrap(),
+                ProstCodec::<Input, Output>::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            res.metadata().get("x-served-by").unwrap(),
+            "test.Test",
+            "{path}"
+        );
+    }
+
+    // Paths the rewrite leaves unchanged are routed as they are.
+    client.ready().await.unwrap();
+    let status = client
+        .unary(
+            Request::new(Input {}),
+            "/test.v2.Test/UnaryCall".parse().unwrap(),
+            ProstCodec::<Input, Output>::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn layers_see_the_resolved_path() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .layer(MethodFilterLayer::deny(["/test.Test/UnaryCall"]))
+            .add_service(test_server::TestServer::new(Svc))
+            .resolve_path(|path| path.strip_prefix("/v2").map(str::to_owned))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = Grpc::new(channel);
+
+    // The denied method can't be reached through an alias.
+    for path in ["/test.Test/UnaryCall", "/v2/test.Test/UnaryCall"] {
+        client.ready().await.unwrap();
+        let status = client
+            .unary(
+                Request::new(Input {}),
+                path.parse().unwrap(),
+                ProstCodec::<Input, Output>::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied, "{path}");
+    }
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
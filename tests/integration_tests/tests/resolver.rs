@@ -0,0 +1,84 @@
+use http::uri::Authority;
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{channel::ResolveFuture, server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc(Arc<AtomicUsize>);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn run_server(calls: Arc<AtomicUsize>) -> (SocketAddr, oneshot::Sender<()>) {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc(calls)))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    (addr, tx)
+}
+
+#[tokio::test]
+async fn custom_resolver_balances_across_addresses() {
+    let calls = [Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0))];
+    let (addr1, tx1) = run_server(calls[0].clone()).await;
+    let (addr2, tx2) = run_server(calls[1].clone()).await;
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let resolved = Arc::new(AtomicUsize::new(0));
+    let resolver = {
+        let resolved = resolved.clone();
+        move |authority: &Authority| -> ResolveFuture {
+            assert_eq!(authority.as_str(), "backend.test:50051");
+            resolved.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move { Ok(vec![addr1, addr2]) })
+        }
+    };
+
+    let channel = Endpoint::from_static("http://backend.test:50051")
+        .resolver(resolver)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    for _ in 0..50 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    assert_eq!(resolved.load(Ordering::SeqCst), 1);
+    assert!(calls[0].load(Ordering::SeqCst) > 0);
+    assert!(calls[1].load(Ordering::SeqCst) > 0);
+    assert_eq!(
+        calls[0].load(Ordering::SeqCst) + calls[1].load(Ordering::SeqCst),
+        50
+    );
+
+    tx1.send(()).unwrap();
+    tx2.send(()).unwrap();
+}
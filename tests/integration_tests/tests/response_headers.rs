@@ -0,0 +1,87 @@
+use http::HeaderValue;
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    metadata::MetadataMap,
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        let mut response = Response::new(Output {});
+        response
+            .metadata_mut()
+            .insert("server", "handler".parse().unwrap());
+        Ok(response)
+    }
+}
+
+async fn response_metadata(mut server: Server) -> MetadataMap {
+    let (tx, rx) = oneshot::channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    let metadata = client
+        .unary_call(Input {})
+        .await
+        .unwrap()
+        .metadata()
+        .clone();
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+
+    metadata
+}
+
+#[tokio::test]
+async fn default_headers() {
+    let metadata = response_metadata(Server::builder()).await;
+    assert_eq!(metadata.get("server").unwrap(), "handler");
+    assert!(metadata.get("date").is_some());
+}
+
+#[tokio::test]
+async fn custom_server_header() {
+    let server = Server::builder().server_header(Some(HeaderValue::from_static("acme")));
+    let metadata = response_metadata(server).await;
+    assert_eq!(metadata.get("server").unwrap(), "acme");
+}
+
+#[tokio::test]
+async fn suppressed_headers() {
+    let server = Server::builder().server_header(None).date_header(false);
+    let metadata = response_metadata(server).await;
+    assert!(metadata.get("server").is_none());
+    assert!(metadata.get("date").is_none());
+}
+
+#[tokio::test]
+async fn suppressed_date_header_with_http1_enabled() {
+    let server = Server::builder().accept_http1(true).date_header(false);
+    let metadata = response_metadata(server).await;
+    assert!(metadata.get("date").is_none());
+}
@@ -0,0 +1,70 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    service::TrailersInterceptorLayer,
+    transport::{server::TcpIncoming, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        match req.metadata().get("fail") {
+            Some(_) => Err(Status::internal("connection to db-7 refused")),
+            None => Ok(Response::new(Output {})),
+        }
+    }
+}
+
+#[tokio::test]
+async fn trailers_are_intercepted() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let layer = TrailersInterceptorLayer::new(|trailers| {
+        trailers
+            .metadata_mut()
+            .insert("x-server-version", "1.2.3".parse().unwrap());
+        if trailers.code() == Code::Internal {
+            trailers.set_message("internal error");
+        }
+    });
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .layer(layer)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let response = client.unary_call(Request::new(Input {})).await.unwrap();
+    assert_eq!(
+        response.metadata().get("x-server-version").unwrap(),
+        "1.2.3"
+    );
+
+    let mut request = Request::new(Input {});
+    request.metadata_mut().insert("fail", "1".parse().unwrap());
+    let status = client.unary_call(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::Internal);
+    assert_eq!(status.message(), "internal error");
+    assert_eq!(status.metadata().get("x-server-version").unwrap(), "1.2.3");
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
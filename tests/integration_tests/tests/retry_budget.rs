@@ -0,0 +1,75 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+/// Fails every call, counting the attempts it saw.
+#[derive(Clone, Default)]
+struct Svc(Arc<AtomicUsize>);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        Err(Status::unavailable("overloaded"))
+    }
+}
+
+#[tokio::test]
+async fn retries_are_capped_by_the_budget() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let svc = Svc::default();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn({
+        let svc = svc.clone();
+        async move {
+            Server::builder()
+                .add_service(test_server::TestServer::new(svc))
+                .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+                .await
+                .unwrap();
+        }
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .retry_budget(0.1, 1)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    // Every call fails and is retried once.
+    let mut throttled = 0;
+    for _ in 0..100 {
+        let status = client.unary_call(Input {}).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+
+        let mut retry = Request::new(Input {});
+        retry.set_previous_rpc_attempts(1);
+        match client.unary_call(retry).await.unwrap_err().code() {
+            Code::Unavailable => {}
+            Code::ResourceExhausted => throttled += 1,
+            code => panic!("unexpected code {code:?}"),
+        }
+    }
+
+    // A tenth of the calls are retried, plus the reserve of 1 retry per second over 10 seconds,
+    // rather than doubling the traffic.
+    let attempts = svc.0.load(Ordering::Relaxed);
+    assert!(attempts <= 120, "{attempts} attempts");
+    assert_eq!(attempts + throttled, 200);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,60 @@
+use integration_tests::pb::{test_client, Input, Output};
+use std::{cell::Cell, convert::Infallible, rc::Rc};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    body::Body,
+    codec::ProstCodec,
+    server::Grpc,
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+#[tokio::test(flavor = "current_thread")]
+async fn non_send_handler_serves_unary_calls() {
+    // State that can't be sent across threads.
+    let calls = Rc::new(Cell::new(0));
+
+    let svc = tower::service_fn({
+        let calls = calls.clone();
+        move |req: http::Request<Body>| {
+            let calls = calls.clone();
+            async move {
+                let unary_call = tower::service_fn(move |_: Request<Input>| {
+                    calls.set(calls.get() + 1);
+                    async { Ok::<_, Status>(Response::new(Output {})) }
+                });
+                let mut grpc = Grpc::new(ProstCodec::<Output, Input>::default());
+                Ok::<_, Infallible>(grpc.unary(unary_call, req).await)
+            }
+        }
+    });
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = Server::builder().serve_local_with_incoming_shutdown(
+        svc,
+        TcpIncoming::from(listener),
+        async { drop(rx.await) },
+    );
+
+    let client = async move {
+        let channel = Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = test_client::TestClient::new(channel);
+
+        client.unary_call(Input {}).await.unwrap();
+        client.unary_call(Input {}).await.unwrap();
+
+        drop(client);
+        tx.send(()).unwrap();
+    };
+
+    let (served, ()) = tokio::join!(server, client);
+    served.unwrap();
+    assert_eq!(calls.get(), 2);
+}
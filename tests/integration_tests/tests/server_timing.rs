@@ -0,0 +1,72 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn server_timing(client: &mut TestClient<Channel>, opt_in: bool) -> Option<String> {
+    let mut request = Request::new(Input {});
+    if opt_in {
+        request
+            .metadata_mut()
+            .insert("x-server-timing", "1".parse().unwrap());
+    }
+    let response = client.unary_call(request).await.unwrap();
+    let value = response.metadata().get("server-timing")?;
+    Some(value.to_str().unwrap().to_owned())
+}
+
+async fn serve(server_timing: bool) -> (TestClient<Channel>, oneshot::Sender<()>) {
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .server_timing(server_timing)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let client = TestClient::connect(format!("http://{addr}")).await.unwrap();
+    (client, tx)
+}
+
+#[tokio::test]
+async fn timing_trailer_is_sent_on_opt_in() {
+    let (mut client, _shutdown) = serve(true).await;
+
+    let timing = server_timing(&mut client, true).await.unwrap();
+    let phases: Vec<_> = timing
+        .split(", ")
+        .map(|phase| phase.split_once(";dur=").unwrap())
+        .collect();
+    let names: Vec<_> = phases.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["queue", "decode", "handler", "encode"]);
+
+    let handler: f64 = phases[2].1.parse().unwrap();
+    assert!(handler >= 20.0, "{timing}");
+
+    assert_eq!(server_timing(&mut client, false).await, None);
+}
+
+#[tokio::test]
+async fn timing_trailer_needs_the_server_flag() {
+    let (mut client, _shutdown) = serve(false).await;
+
+    assert_eq!(server_timing(&mut client, true).await, None);
+}
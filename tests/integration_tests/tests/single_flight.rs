@@ -0,0 +1,73 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    service::SingleFlightLayer,
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+struct Svc(Arc<AtomicUsize>);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut response = Response::new(Output {});
+        response
+            .metadata_mut()
+            .insert("x-handled", "1".parse().unwrap());
+        Ok(response)
+    }
+}
+
+#[tokio::test]
+async fn identical_concurrent_requests_are_coalesced() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let svc = test_server::TestServer::new(Svc(calls.clone()));
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .layer(SingleFlightLayer::new(|parts: &http::request::Parts| {
+                (parts.uri.path() == "/test.Test/UnaryCall").then_some(())
+            }))
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let calls_in_flight = [(); 2].map(|_| {
+        let mut client = client.clone();
+        tokio::spawn(async move { client.unary_call(Request::new(Input {})).await })
+    });
+    for call in calls_in_flight {
+        let response = call.await.unwrap().unwrap();
+        assert_eq!(response.metadata().get("x-handled").unwrap(), "1");
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Once the call completed, the response isn't shared anymore.
+    let mut client = client;
+    client.unary_call(Request::new(Input {})).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,44 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tonic::{
+    transport::{server::ShutdownOutcome, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn serves_until_shutdown() {
+    let server = Server::builder()
+        .add_service(test_server::TestServer::new(Svc))
+        .spawn("127.0.0.1:0".parse().unwrap())
+        .unwrap();
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", server.local_addr()))
+        .await
+        .unwrap();
+    client.unary_call(Input {}).await.unwrap();
+    assert!(!server.is_finished());
+
+    drop(client);
+    server.shutdown();
+    let report = server.await_shutdown().await.unwrap();
+    assert_eq!(report.outcome(), ShutdownOutcome::Drained);
+}
+
+#[tokio::test]
+async fn returns_bind_errors() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let result = Server::builder()
+        .add_service(test_server::TestServer::new(Svc))
+        .spawn(listener.local_addr().unwrap());
+
+    assert!(result.is_err());
+}
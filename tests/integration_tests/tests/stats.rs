@@ -0,0 +1,240 @@
+use integration_tests::pb::{
+    test_client::TestClient, test_server, test_stream_client::TestStreamClient, test_stream_server,
+    Input, InputStream, Output, OutputStream,
+};
+use std::pin::Pin;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::{
+    net::TcpListener,
+    sync::{oneshot, Semaphore},
+};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    transport::{
+        server::{StatsSnapshot, TcpIncoming},
+        Endpoint, Server,
+    },
+    Code, Request, Response, Status,
+};
+
+#[tokio::test]
+async fn snapshot_reflects_issued_calls() {
+    struct Svc(AtomicUsize);
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            if self.0.fetch_add(1, Ordering::SeqCst) < 2 {
+                Ok(Response::new(Output {}))
+            } else {
+                Err(Status::internal("third call fails"))
+            }
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let svc = test_server::TestServer::new(Svc(AtomicUsize::new(0)));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let mut builder = Server::builder();
+    let stats = builder.stats();
+
+    let jh = tokio::spawn(async move {
+        builder
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Internal);
+
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.active_connections(), 1);
+    assert_eq!(snapshot.total_requests(), 3);
+    assert_eq!(
+        snapshot.requests_per_method().get("/test.Test/UnaryCall"),
+        Some(&3)
+    );
+    assert_eq!(snapshot.errors_by_code().len(), 1);
+    assert_eq!(snapshot.errors_by_code().get(&Code::Internal), Some(&1));
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+
+    assert_eq!(stats.snapshot().active_connections(), 0);
+}
+
+#[tokio::test]
+async fn active_requests_tracks_streaming_calls() {
+    /// Sends one message per permit added to the semaphore, then ends the stream.
+    struct Svc(Arc<Semaphore>);
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Svc {
+        type StreamCallStream =
+            Pin<Box<dyn Stream<Item = Result<OutputStream, Status>> + Send + 'static>>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let permits = self.0.clone();
+            let stream = async_stream::try_stream! {
+                permits.acquire().await.unwrap().forget();
+                yield OutputStream {};
+            };
+            Ok(Response::new(Box::pin(stream)))
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let permits = Arc::new(Semaphore::new(0));
+    let svc = test_stream_server::TestStreamServer::new(Svc(permits.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let mut builder = Server::builder();
+    let stats = builder.stats();
+
+    let jh = tokio::spawn(async move {
+        builder
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestStreamClient::new(channel);
+    assert_eq!(stats.active_requests(), 0);
+
+    let mut first = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    let mut second = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(stats.active_requests(), 2);
+    assert_eq!(stats.snapshot().active_requests(), 2);
+
+    permits.add_permits(1);
+    first.next().await.unwrap().unwrap();
+    assert!(first.next().await.is_none());
+    assert_eq!(stats.active_requests(), 1);
+
+    permits.add_permits(1);
+    second.next().await.unwrap().unwrap();
+    assert!(second.next().await.is_none());
+    assert_eq!(stats.active_requests(), 0);
+    assert_eq!(stats.snapshot().total_requests(), 2);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn unknown_paths_share_one_entry() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let (tx, rx) = oneshot::channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let mut builder = Server::builder();
+    let stats = builder.stats();
+
+    let jh = tokio::spawn(async move {
+        builder
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = Grpc::new(channel);
+
+    for path in [
+        "/test.Test/UnaryCall",
+        "/test.Test/Random1",
+        "/random.Service/Random2",
+        "/random.Service/Random3",
+    ] {
+        client.ready().await.unwrap();
+        let _ = client
+            .unary(
+                Request::new(Input {}),
+                path.parse().unwrap(),
+                ProstCodec::<Input, Output>::default(),
+            )
+            .await;
+    }
+
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.total_requests(), 4);
+    assert_eq!(snapshot.requests_per_method().len(), 2);
+    assert_eq!(
+        snapshot.requests_per_method().get("/test.Test/UnaryCall"),
+        Some(&1)
+    );
+    assert_eq!(
+        snapshot
+            .requests_per_method()
+            .get(StatsSnapshot::UNKNOWN_METHOD),
+        Some(&3)
+    );
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
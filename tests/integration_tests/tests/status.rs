@@ -65,6 +65,53 @@ async fn status_with_details() {
     jh.await.unwrap();
 }
 
+#[tokio::test]
+async fn status_from_io_error() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            std::fs::read("/nonexistent/config.toml")?;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let svc = test_server::TestServer::new(Svc);
+
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut channel = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let err = channel
+        .unary_call(Request::new(Input {}))
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.code(), Code::NotFound);
+    assert_eq!(i32::from(err.code()), 5);
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
+
 #[tokio::test]
 async fn status_with_metadata() {
     const MESSAGE: &str = "Internal error, see metadata for details";
@@ -0,0 +1,94 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Code, Request, Response, Status,
+};
+use tonic_types::{ErrorDetails, StatusExt};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        let mut details = ErrorDetails::with_retry_info(Some(Duration::from_secs(5)));
+        details.add_quota_failure_violation("clientip:127.0.0.1", "too many requests");
+        Err(Status::with_error_details(
+            Code::ResourceExhausted,
+            "quota exceeded",
+            details,
+        ))
+    }
+}
+
+#[tokio::test]
+async fn client_reads_status_details() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert_eq!(status.message(), "quota exceeded");
+
+    let retry_info = status.get_details_retry_info().expect("retry info");
+    assert_eq!(retry_info.retry_delay, Some(Duration::from_secs(5)));
+    let quota_failure = status.get_details_quota_failure().expect("quota failure");
+    assert_eq!(quota_failure.violations.len(), 1);
+    assert_eq!(quota_failure.violations[0].subject, "clientip:127.0.0.1");
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_status_details_are_dropped() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // Answers every call with details that aren't valid base64.
+    tokio::spawn(async move {
+        let (io, _) = listener.accept().await.unwrap();
+        let mut connection = h2::server::handshake(io).await.unwrap();
+        tokio::spawn(async move {
+            while let Some(Ok((_, mut respond))) = connection.accept().await {
+                let response = http::Response::builder()
+                    .header("content-type", "application/grpc")
+                    .body(())
+                    .unwrap();
+                let mut send = respond.send_response(response, false).unwrap();
+
+                let mut trailers = http::HeaderMap::new();
+                trailers.insert("grpc-status", "14".parse().unwrap());
+                trailers.insert("grpc-message", "overloaded".parse().unwrap());
+                trailers.insert("grpc-status-details-bin", "not base64!".parse().unwrap());
+                send.send_trailers(trailers).unwrap();
+            }
+        });
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(status.message(), "overloaded");
+    assert!(status.details().is_empty());
+}
@@ -0,0 +1,73 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::sync::Arc;
+use tokio::{net::TcpListener, sync::oneshot, sync::Notify};
+use tonic::{
+    transport::{server::TcpIncoming, Http2Error, Server},
+    Code, Request, Response, Status,
+};
+
+/// Holds every call until it is released.
+#[derive(Clone, Default)]
+struct Svc {
+    started: Arc<Notify>,
+    release: Arc<Notify>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.started.notify_one();
+        self.release.notified().await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn refuses_new_streams_while_in_flight_ones_complete() {
+    let (tx, rx) = oneshot::channel::<()>();
+    let svc = Svc::default();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let mut builder = Server::builder();
+    let admission = builder.stream_admission();
+    let stats = builder.stats();
+    let router = builder.add_service(test_server::TestServer::new(svc.clone()));
+    let jh = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let in_flight = tokio::spawn({
+        let mut client = client.clone();
+        async move { client.unary_call(Input {}).await }
+    });
+    svc.started.notified().await;
+
+    admission.refuse_streams();
+    let status = client.clone().unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    let err = Http2Error::find(&status).expect("an HTTP/2 error");
+    assert!(err.is_retryable());
+
+    svc.release.notify_one();
+    in_flight.await.unwrap().unwrap();
+
+    admission.admit_streams();
+    svc.release.notify_one();
+    client.clone().unary_call(Input {}).await.unwrap();
+
+    assert_eq!(stats.snapshot().total_connections(), 1);
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
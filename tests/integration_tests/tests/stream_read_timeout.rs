@@ -0,0 +1,76 @@
+use integration_tests::pb::{
+    test_client_stream_client::TestClientStreamClient, test_client_stream_server, InputStream,
+    OutputStream,
+};
+use std::time::{Duration, Instant};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Code, Request, Response, Status, Streaming,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_client_stream_server::TestClientStream for Svc {
+    async fn client_stream_call(
+        &self,
+        req: Request<Streaming<InputStream>>,
+    ) -> Result<Response<OutputStream>, Status> {
+        let mut stream = req.into_inner();
+        while stream.message().await?.is_some() {}
+        Ok(Response::new(OutputStream {}))
+    }
+}
+
+#[tokio::test]
+async fn stalled_client_stream_is_aborted() {
+    const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+    let (tx, rx) = oneshot::channel();
+    let svc = test_client_stream_server::TestClientStreamServer::new(Svc);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .stream_read_timeout(READ_TIMEOUT)
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClientStreamClient::new(channel);
+
+    // Pauses shorter than the timeout are fine, even if the whole call takes longer.
+    let steady = async_stream::stream! {
+        for _ in 0..4 {
+            yield InputStream {};
+            tokio::time::sleep(READ_TIMEOUT / 2).await;
+        }
+    };
+    client.client_stream_call(steady).await.unwrap();
+
+    let stalled = async_stream::stream! {
+        yield InputStream {};
+        tokio::time::sleep(READ_TIMEOUT * 10).await;
+        yield InputStream {};
+    };
+    let start = Instant::now();
+    let status = client.client_stream_call(stalled).await.unwrap_err();
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+    assert!(start.elapsed() < READ_TIMEOUT * 5);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
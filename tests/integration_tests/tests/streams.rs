@@ -1,6 +1,17 @@
-use integration_tests::pb::{test_stream_server, InputStream, OutputStream};
-use tokio::sync::oneshot;
-use tonic::{transport::Server, Request, Response, Status};
+use integration_tests::pb::{
+    test1_client, test1_server, test_stream_server, Input1, InputStream, Output1, OutputStream,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::StreamExt;
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
 
 type Stream<T> = std::pin::Pin<
     Box<dyn tokio_stream::Stream<Item = std::result::Result<T, Status>> + Send + 'static>,
@@ -41,6 +52,166 @@ async fn status_from_server_stream_with_source() {
     jh.await.unwrap();
 }
 
+#[tokio::test]
+async fn slow_reader_backpressures_server_stream() {
+    const MESSAGE_SIZE: usize = 1024;
+    const WINDOW_SIZE: u32 = 64 * 1024;
+
+    struct Svc(Arc<AtomicUsize>);
+
+    #[tonic::async_trait]
+    impl test1_server::Test1 for Svc {
+        async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+            unimplemented!()
+        }
+
+        type StreamCallStream = Stream<Output1>;
+
+        async fn stream_call(
+            &self,
+            _: Request<Input1>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            // An endless stream that counts how many messages were pulled from it.
+            let produced = self.0.clone();
+            let stream = tokio_stream::iter(0..).map(move |_| {
+                produced.fetch_add(1, Ordering::SeqCst);
+                Ok(Output1 {
+                    buf: vec![0; MESSAGE_SIZE],
+                })
+            });
+
+            Ok(Response::new(Box::pin(stream) as Self::StreamCallStream))
+        }
+    }
+
+    let produced = Arc::new(AtomicUsize::new(0));
+    let svc = test1_server::Test1Server::new(Svc(produced.clone()));
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .initial_stream_window_size(WINDOW_SIZE)
+        .initial_connection_window_size(WINDOW_SIZE)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test1_client::Test1Client::new(channel);
+
+    // Start the call but never read from the response stream.
+    let mut stream = client
+        .stream_call(Input1::default())
+        .await
+        .unwrap()
+        .into_inner();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let stalled_at = produced.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // The handler's stream stops being polled once the flow control window is
+    // exhausted, bounding what the server holds to the window plus a single
+    // encoded chunk.
+    assert_eq!(produced.load(Ordering::SeqCst), stalled_at);
+    assert!(
+        stalled_at * MESSAGE_SIZE < 4 * WINDOW_SIZE as usize,
+        "server produced {stalled_at} messages without the client reading"
+    );
+
+    // Reading makes progress again.
+    for _ in 0..stalled_at + 100 {
+        stream.message().await.unwrap().unwrap();
+    }
+    assert!(produced.load(Ordering::SeqCst) > stalled_at);
+
+    drop(stream);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn initial_metadata_sent_before_first_message() {
+    const FIRST_MESSAGE_DELAY: Duration = Duration::from_millis(500);
+
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test1_server::Test1 for Svc {
+        async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+            unimplemented!()
+        }
+
+        type StreamCallStream = Stream<Output1>;
+
+        async fn stream_call(
+            &self,
+            _: Request<Input1>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let stream = async_stream::stream! {
+                tokio::time::sleep(FIRST_MESSAGE_DELAY).await;
+                yield Ok(Output1::default());
+            };
+
+            let mut response = Response::new(Box::pin(stream) as Self::StreamCallStream);
+            response
+                .metadata_mut()
+                .insert("resume-token", "abc".parse().unwrap());
+            Ok(response)
+        }
+    }
+
+    let svc = test1_server::Test1Server::new(Svc);
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test1_client::Test1Client::new(channel);
+
+    let start = std::time::Instant::now();
+    let response = client.stream_call(Input1::default()).await.unwrap();
+
+    // The headers are flushed as soon as the handler returns, without waiting for a message.
+    assert!(start.elapsed() < FIRST_MESSAGE_DELAY);
+    assert_eq!(response.metadata().get("resume-token").unwrap(), "abc");
+
+    let mut stream = response.into_inner();
+    stream.message().await.unwrap().unwrap();
+    assert!(start.elapsed() >= FIRST_MESSAGE_DELAY);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
+
 #[allow(dead_code)]
 struct Unsync(*mut ());
 
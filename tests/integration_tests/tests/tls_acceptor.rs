@@ -0,0 +1,87 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::transport::{
+    server::TcpIncoming, Certificate, Channel, ClientTlsConfig, Identity, Server, ServerTlsConfig,
+};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+const CA: &str = include_str!("../../../examples/data/tls/ca.pem");
+const SERVER_CERT: &str = include_str!("../../../examples/data/tls/server.pem");
+const SERVER_KEY: &str = include_str!("../../../examples/data/tls/server.key");
+
+#[tokio::test]
+async fn acceptor_is_shared_between_servers() {
+    rustls::crypto::ring::default_provider()
+        .install_default()
+        .unwrap();
+
+    let acceptor = ServerTlsConfig::new()
+        .identity(Identity::from_pem(SERVER_CERT, SERVER_KEY))
+        .tls_acceptor()
+        .unwrap();
+
+    let mut addrs = Vec::new();
+    let mut shutdowns = Vec::new();
+    let mut handles = Vec::new();
+
+    // A public and an admin listener, both terminating TLS with the same acceptor.
+    for _ in 0..2 {
+        let (tx, rx) = oneshot::channel::<()>();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        addrs.push(listener.local_addr().unwrap());
+
+        let router = Server::builder()
+            .tls_acceptor(acceptor.clone())
+            .add_service(test_server::TestServer::new(Svc));
+        handles.push(tokio::spawn(async move {
+            router
+                .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+                .await
+                .unwrap();
+        }));
+        shutdowns.push(tx);
+    }
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    for addr in &addrs {
+        let tls = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(CA))
+            .domain_name("example.com");
+        let channel = Channel::from_shared(format!("https://{addr}"))
+            .unwrap()
+            .tls_config(tls)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        TestClient::new(channel)
+            .unary_call(Request::new(Input {}))
+            .await
+            .unwrap();
+    }
+
+    for tx in shutdowns {
+        tx.send(()).unwrap();
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+}
+
+#[test]
+fn acceptor_requires_an_identity() {
+    let err = ServerTlsConfig::new().tls_acceptor().unwrap_err();
+    let source = std::error::Error::source(&err).unwrap();
+    assert_eq!(source.to_string(), "No server identity set.");
+}
@@ -0,0 +1,85 @@
+use integration_tests::pb::{test1_client, test1_server, Input1, Output1};
+use std::pin::Pin;
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status, TrailingMetadata,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        unimplemented!()
+    }
+
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send>>;
+
+    /// Streams as many messages as the request buffer is long, counting them in the trailers.
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let trailers = TrailingMetadata::new();
+        trailers.update(|metadata| metadata.insert("item-count", 0.into()));
+
+        let items = req.into_inner().buf.len();
+        let stream = tokio_stream::iter(0..items).map({
+            let trailers = trailers.clone();
+            move |i| {
+                trailers.update(|metadata| metadata.insert("item-count", (i + 1).into()));
+                Ok(Output1::default())
+            }
+        });
+
+        let mut response = Response::new(Box::pin(stream) as Self::StreamCallStream);
+        response.set_trailing_metadata(trailers);
+        Ok(response)
+    }
+}
+
+#[tokio::test]
+async fn handler_sets_trailing_metadata() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let mut client = test1_client::Test1Client::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    for items in [3, 0] {
+        let mut stream = client
+            .stream_call(Input1 {
+                buf: vec![0; items],
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        let mut received = 0;
+        while stream.message().await.unwrap().is_some() {
+            received += 1;
+        }
+        assert_eq!(received, items);
+
+        let trailers = stream.trailers().await.unwrap().expect("trailers");
+        assert_eq!(trailers.get("item-count").unwrap(), &items.to_string());
+    }
+
+    drop(client);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,53 @@
+use integration_tests::pb::{test_server, Input, Output};
+use std::error::Error as _;
+use tokio::net::TcpListener;
+use tonic::transport::{Identity, Server, ServerTlsConfig, TransportError};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+const SERVER_KEY: &str = include_str!("../../../examples/data/tls/server.key");
+
+#[tokio::test]
+async fn address_in_use_is_a_bind_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let err = Server::builder()
+        .add_service(test_server::TestServer::new(Svc))
+        .serve(addr)
+        .await
+        .unwrap_err();
+
+    match TransportError::from(err) {
+        TransportError::Bind(err) => {
+            assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+        }
+        err => panic!("expected a bind error, got {err:?}"),
+    }
+}
+
+#[tokio::test]
+async fn invalid_certificate_is_a_tls_error() {
+    rustls::crypto::ring::default_provider()
+        .install_default()
+        .ok();
+
+    let err = Server::builder()
+        .tls_config(
+            ServerTlsConfig::new().identity(Identity::from_pem("not a certificate", SERVER_KEY)),
+        )
+        .unwrap_err();
+
+    let err = TransportError::from(err);
+    assert!(matches!(err, TransportError::Tls(_)), "{err:?}");
+    // The error of the TLS library is kept as the source.
+    assert!(err.source().is_some());
+}
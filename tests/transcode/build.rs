@@ -0,0 +1,15 @@
+use std::{env, path::PathBuf};
+
+fn main() {
+    let protos = &["proto/test.proto"];
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("test_descriptor.bin"))
+        .compile_protos(protos, &["proto", "../../examples/proto/googleapis"])
+        .unwrap();
+
+    protos
+        .iter()
+        .for_each(|file| println!("cargo:rerun-if-changed={}", file));
+}
@@ -0,0 +1,40 @@
+use tonic::{Request, Response, Status};
+
+use pb::{items_server::Items, CreateItemRequest, GetItemRequest, Item};
+
+pub mod pb {
+    tonic::include_proto!("test");
+
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("test_descriptor");
+}
+
+pub struct Svc;
+
+#[tonic::async_trait]
+impl Items for Svc {
+    async fn get_item(&self, req: Request<GetItemRequest>) -> Result<Response<Item>, Status> {
+        let req = req.into_inner();
+
+        if req.id == 0 {
+            return Err(Status::not_found("no item 0"));
+        }
+        Ok(Response::new(Item {
+            id: req.id,
+            name: format!("item {}", req.id),
+            shelf: String::new(),
+            tags: vec![req.view],
+        }))
+    }
+
+    async fn create_item(&self, req: Request<CreateItemRequest>) -> Result<Response<Item>, Status> {
+        let req = req.into_inner();
+        let item = req
+            .item
+            .ok_or_else(|| Status::invalid_argument("missing item"))?;
+
+        Ok(Response::new(Item {
+            shelf: req.shelf,
+            ..item
+        }))
+    }
+}
@@ -0,0 +1,124 @@
+use std::net::SocketAddr;
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::{Method, Request, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+
+use test_transcode::pb::{
+    items_client::ItemsClient, items_server::ItemsServer, GetItemRequest, FILE_DESCRIPTOR_SET,
+};
+use test_transcode::Svc;
+use tonic_transcode::TranscodeLayer;
+
+#[tokio::test]
+async fn get_with_path_variable() {
+    let addr = spawn().await;
+
+    let (status, body) = call(addr, Method::GET, "/v1/items/42", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "id": "42", "name": "item 42", "tags": [""] }));
+}
+
+#[tokio::test]
+async fn get_with_query_parameters() {
+    let addr = spawn().await;
+
+    let (status, body) = call(addr, Method::GET, "/v1/items/7?view=full", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({ "id": "7", "name": "item 7", "tags": ["full"] })
+    );
+}
+
+#[tokio::test]
+async fn post_with_body_field() {
+    let addr = spawn().await;
+
+    let item = json!({ "id": 3, "name": "three", "tags": ["a", "b"] });
+    let (status, body) = call(addr, Method::POST, "/v1/shelves/top/items", Some(item)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({ "id": "3", "name": "three", "shelf": "top", "tags": ["a", "b"] })
+    );
+}
+
+#[tokio::test]
+async fn errors_map_to_http_statuses() {
+    let addr = spawn().await;
+
+    let (status, body) = call(addr, Method::GET, "/v1/items/0", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, json!({ "code": 5, "message": "no item 0" }));
+
+    let (status, body) = call(addr, Method::GET, "/v1/items/abc", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], json!(3));
+
+    let (status, body) = call(addr, Method::POST, "/v1/shelves/top/items", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, json!({ "code": 3, "message": "missing item" }));
+}
+
+#[tokio::test]
+async fn grpc_passes_through() {
+    let addr = spawn().await;
+
+    let mut client = ItemsClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let item = client
+        .get_item(GetItemRequest {
+            id: 5,
+            view: "basic".to_owned(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(item.name, "item 5");
+}
+
+async fn spawn() -> SocketAddr {
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = Server::builder()
+        .accept_http1(true)
+        .layer(TranscodeLayer::new(FILE_DESCRIPTOR_SET).unwrap())
+        .add_service(ItemsServer::new(Svc))
+        .serve_with_incoming(TcpListenerStream::new(listener));
+    tokio::spawn(server);
+
+    addr
+}
+
+async fn call(
+    addr: SocketAddr,
+    method: Method,
+    path: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let req = Request::builder()
+        .method(method)
+        .uri(format!("http://{addr}{path}"))
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(body)))
+        .unwrap();
+
+    let res = client.request(req).await.unwrap();
+    let status = res.status();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
@@ -14,10 +14,10 @@ use tokio_stream::wrappers::TcpListenerStream;
 use tonic::body::Body;
 use tonic::transport::Server;
 
-use test_web::pb::{test_server::TestServer, Input, Output};
+use test_web::pb::{test_client::TestClient, test_server::TestServer, Input, Output};
 use test_web::Svc;
-use tonic::Status;
-use tonic_web::GrpcWebLayer;
+use tonic::{Code, Status};
+use tonic_web::{GrpcWebLayer, HttpStatus};
 
 #[tokio::test]
 async fn binary_request() {
@@ -65,6 +65,55 @@ async fn text_request() {
     assert_eq!(&trailers[..], b"grpc-status:0\r\n");
 }
 
+#[tokio::test]
+async fn error_with_http_status() {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let server_url = format!("http://{}", listener.local_addr().unwrap());
+    let listener_stream = TcpListenerStream::new(listener);
+
+    // Answers grpc-web requests failing with `InvalidArgument` with `400 Bad Request`.
+    let http_status = tower::util::MapResponseLayer::new(|mut res: hyper::Response<Body>| {
+        if res.headers().get("grpc-status").is_some_and(|s| s == "3") {
+            res.extensions_mut()
+                .insert(HttpStatus(StatusCode::BAD_REQUEST));
+        }
+        res
+    });
+
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .layer(GrpcWebLayer::new())
+            .layer(http_status)
+            .add_service(TestServer::new(Svc))
+            .serve_with_incoming(listener_stream)
+            .await
+            .unwrap()
+    }));
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    let mut req = build_request(server_url.clone(), "grpc-web", "grpc-web");
+    *req.body_mut() =
+        Body::new(Full::new(encode_input("boom")).map_err(|err| Status::internal(err.to_string())));
+
+    let res = client.request(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.headers()["grpc-status"], "3");
+
+    // Native gRPC clients still see a regular error response.
+    let mut client = TestClient::connect(server_url).await.unwrap();
+    let status = client
+        .unary_call(Input {
+            id: 1,
+            desc: "boom".to_owned(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "invalid boom");
+}
+
 async fn spawn() -> String {
     let addr = SocketAddr::from(([127, 0, 0, 1], 0));
     let listener = TcpListener::bind(addr).await.expect("listener");
@@ -85,9 +134,13 @@ async fn spawn() -> String {
 }
 
 fn encode_body() -> Bytes {
+    encode_input("one")
+}
+
+fn encode_input(desc: &str) -> Bytes {
     let input = Input {
         id: 1,
-        desc: "one".to_owned(),
+        desc: desc.to_owned(),
     };
 
     let mut buf = BytesMut::with_capacity(1024);
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use prost::Message as _;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+use tonic::transport::Server;
+
+use test_web::pb::{test_server::TestServer, Input, Output};
+use test_web::Svc;
+use tonic_web::GrpcWebSocketLayer;
+
+const HEADERS: u8 = 0;
+const DATA: u8 = 1;
+const END: u8 = 2;
+const TRAILERS: u8 = 3;
+
+#[derive(Default)]
+struct Call {
+    headers: HashMap<String, String>,
+    data: BytesMut,
+    trailers: Option<HashMap<String, String>>,
+}
+
+impl Call {
+    fn status(&self) -> &str {
+        self.headers
+            .get("grpc-status")
+            .or_else(|| self.trailers.as_ref()?.get("grpc-status"))
+            .expect("grpc-status")
+    }
+
+    fn messages(&mut self) -> Vec<Output> {
+        let mut messages = Vec::new();
+        while self.data.has_remaining() {
+            assert_eq!(self.data.get_u8(), 0);
+            let len = self.data.get_u32() as usize;
+            messages.push(Output::decode(self.data.split_to(len)).unwrap());
+        }
+        messages
+    }
+}
+
+#[tokio::test]
+async fn multiplexed_round_trip() {
+    let mut ws = connect().await;
+
+    start(&mut ws, 1, "/test.Test/UnaryCall", &input(1, "one")).await;
+    start(&mut ws, 3, "/test.Test/ServerStream", &input(2, "two")).await;
+
+    let mut calls = receive(&mut ws, 2).await;
+
+    let unary = calls.get_mut(&1).unwrap();
+    assert_eq!(unary.headers["content-type"], "application/grpc");
+    assert_eq!(unary.status(), "0");
+    assert_eq!(
+        unary.messages(),
+        [Output {
+            id: 1,
+            desc: "one".to_owned()
+        }]
+    );
+
+    let stream = calls.get_mut(&3).unwrap();
+    assert_eq!(stream.status(), "0");
+    assert_eq!(
+        stream.messages(),
+        [
+            Output {
+                id: 2,
+                desc: "1-two".to_owned()
+            },
+            Output {
+                id: 2,
+                desc: "2-two".to_owned()
+            }
+        ]
+    );
+}
+
+#[tokio::test]
+async fn error_status() {
+    let mut ws = connect().await;
+
+    start(&mut ws, 7, "/test.Test/UnaryCall", &input(1, "boom")).await;
+
+    let mut calls = receive(&mut ws, 1).await;
+    let call = calls.get_mut(&7).unwrap();
+    assert_eq!(call.status(), "3");
+    assert!(call.messages().is_empty());
+}
+
+async fn connect() -> WebSocketStream<TcpStream> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let addr = listener.local_addr().unwrap();
+    let listener_stream = TcpListenerStream::new(listener);
+
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .layer(GrpcWebSocketLayer::new("/grpc-ws"))
+            .add_service(TestServer::new(Svc))
+            .serve_with_incoming(listener_stream)
+            .await
+            .unwrap()
+    }));
+
+    let tcp = TcpStream::connect(addr).await.unwrap();
+    let (ws, response) = tokio_tungstenite::client_async(format!("ws://{addr}/grpc-ws"), tcp)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 101);
+    ws
+}
+
+fn input(id: i32, desc: &str) -> Bytes {
+    let input = Input {
+        id,
+        desc: desc.to_owned(),
+    };
+
+    let mut buf = BytesMut::new();
+    buf.put_u8(0);
+    buf.put_u32(input.encoded_len() as u32);
+    input.encode(&mut buf).unwrap();
+    buf.freeze()
+}
+
+fn frame(id: u32, kind: u8, payload: &[u8]) -> Message {
+    let mut frame = BytesMut::new();
+    frame.put_u32(id);
+    frame.put_u8(kind);
+    frame.put_slice(payload);
+    Message::Binary(frame.freeze())
+}
+
+async fn start(ws: &mut WebSocketStream<TcpStream>, id: u32, path: &str, body: &[u8]) {
+    let headers = format!(":path: {path}\r\ncontent-type: application/grpc\r\nte: trailers\r\n");
+    ws.send(frame(id, HEADERS, headers.as_bytes()))
+        .await
+        .unwrap();
+
+    // Split the body to exercise reassembly across frames.
+    let (first, second) = body.split_at(3);
+    ws.send(frame(id, DATA, first)).await.unwrap();
+    ws.send(frame(id, DATA, second)).await.unwrap();
+    ws.send(frame(id, END, &[])).await.unwrap();
+}
+
+async fn receive(ws: &mut WebSocketStream<TcpStream>, count: usize) -> HashMap<u32, Call> {
+    let mut calls = HashMap::<u32, Call>::new();
+
+    while calls
+        .values()
+        .filter(|call| call.trailers.is_some())
+        .count()
+        < count
+    {
+        let mut frame = match ws.next().await.unwrap().unwrap() {
+            Message::Binary(frame) => frame,
+            message => panic!("unexpected message {message:?}"),
+        };
+        let id = frame.get_u32();
+        let kind = frame.get_u8();
+        let call = calls.entry(id).or_default();
+
+        match kind {
+            HEADERS => call.headers = parse_headers(&frame),
+            DATA => call.data.put(frame),
+            TRAILERS => call.trailers = Some(parse_headers(&frame)),
+            kind => panic!("unexpected frame kind {kind}"),
+        }
+    }
+
+    calls
+}
+
+fn parse_headers(payload: &[u8]) -> HashMap<String, String> {
+    std::str::from_utf8(payload)
+        .unwrap()
+        .split("\r\n")
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (name, value) = line.split_once(": ").unwrap();
+            (name.to_owned(), value.to_owned())
+        })
+        .collect()
+}
@@ -52,7 +52,7 @@ pub(crate) fn generate_internal<T: Service>(
         generate_doc_comments(service.comment())
     };
 
-    let named = generate_named(&server_service, &service_name);
+    let named = generate_named(service, &server_service, &service_name);
     let mod_attributes = attributes.for_mod(package);
     let struct_attributes = attributes.for_struct(&service_name);
 
@@ -352,9 +352,14 @@ fn generate_trait_methods<T: Service>(
     stream
 }
 
-fn generate_named(server_service: &syn::Ident, service_name: &str) -> TokenStream {
+fn generate_named<T: Service>(
+    service: &T,
+    server_service: &syn::Ident,
+    service_name: &str,
+) -> TokenStream {
     let service_name = syn::LitStr::new(service_name, proc_macro2::Span::call_site());
     let name_doc = generate_doc_comment(" Generated gRPC service name");
+    let methods = service.methods().iter().map(|method| method.identifier());
 
     quote! {
         #name_doc
@@ -362,6 +367,7 @@ fn generate_named(server_service: &syn::Ident, service_name: &str) -> TokenStrea
 
         impl<T> tonic::server::NamedService for #server_service<T> {
             const NAME: &'static str = SERVICE_NAME;
+            const METHODS: &'static [&'static str] = &[#(#methods),*];
         }
     }
 }
@@ -455,5 +455,6 @@ pub mod health_server {
     pub const SERVICE_NAME: &str = "grpc.health.v1.Health";
     impl<T> tonic::server::NamedService for HealthServer<T> {
         const NAME: &'static str = SERVICE_NAME;
+        const METHODS: &'static [&'static str] = &["Check", "Watch"];
     }
 }
@@ -4,12 +4,16 @@ use crate::pb::health_server::{Health, HealthServer};
 use crate::pb::{HealthCheckRequest, HealthCheckResponse};
 use crate::ServingStatus;
 use std::collections::HashMap;
+use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{watch, RwLock};
 use tokio_stream::Stream;
 #[cfg(feature = "transport")]
 use tonic::server::NamedService;
+#[cfg(feature = "transport")]
+use tonic::transport::server::{Router, Server};
 use tonic::{Request, Response, Status};
 
 /// Creates a `HealthReporter` and a linked `HealthServer` pair. Together,
@@ -28,6 +32,56 @@ pub fn health_reporter() -> (HealthReporter, HealthServer<impl Health>) {
     (reporter, server)
 }
 
+/// Mounts the gRPC Health Checking service on a server.
+///
+/// Implemented for [`Server`] and [`Router`], so the service can be added like any other one,
+/// conditionally if needed:
+///
+/// ```rust,no_run
+/// use tonic::transport::Server;
+/// use tonic_health::server::HealthExt;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let (router, mut reporter) = Server::builder().with_health();
+/// # let _ = &mut reporter;
+/// router.serve("[::1]:50051".parse()?).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Like all services of the router, the service is wrapped in the layers of the [`Server`], so
+/// authentication and other interceptors added with [`Server::layer`] apply to health checks as
+/// well.
+#[cfg(feature = "transport")]
+pub trait HealthExt {
+    /// The router the service is added to.
+    type Router;
+
+    /// Adds the gRPC Health Checking service, returning the [`HealthReporter`] used to update the
+    /// statuses it serves.
+    fn with_health(self) -> (Self::Router, HealthReporter);
+}
+
+#[cfg(feature = "transport")]
+impl<L: Clone> HealthExt for Server<L> {
+    type Router = Router<L>;
+
+    fn with_health(mut self) -> (Self::Router, HealthReporter) {
+        let (reporter, service) = health_reporter();
+        (self.add_service(service), reporter)
+    }
+}
+
+#[cfg(feature = "transport")]
+impl<L> HealthExt for Router<L> {
+    type Router = Self;
+
+    fn with_health(self) -> (Self::Router, HealthReporter) {
+        let (reporter, service) = health_reporter();
+        (self.add_service(service), reporter)
+    }
+}
+
 type StatusPair = (watch::Sender<ServingStatus>, watch::Receiver<ServingStatus>);
 
 /// A handle providing methods to update the health status of gRPC services. A
@@ -99,6 +153,39 @@ impl HealthReporter {
         let mut writer = self.statuses.write().await;
         let _ = writer.remove(service_name);
     }
+
+    /// Sets the status of every registered service, including the overall server health
+    /// (the empty service name), to `NotServing`. This notifies any watchers.
+    pub async fn set_all_not_serving(&mut self) {
+        let reader = self.statuses.read().await;
+        for (tx, _) in reader.values() {
+            tx.send_replace(ServingStatus::NotServing);
+        }
+    }
+
+    /// Wraps a shutdown `signal` so that health checks fail before the server starts draining.
+    ///
+    /// The returned future can be passed to [`Router::serve_with_shutdown`]. Once `signal`
+    /// completes, all services are set to `NotServing`, then the future waits for
+    /// `pre_shutdown_delay` before completing. This gives load balancers time to observe the
+    /// change and stop routing new requests to this server before connections are drained.
+    ///
+    /// [`Router::serve_with_shutdown`]: https://docs.rs/tonic/latest/tonic/transport/server/struct.Router.html#method.serve_with_shutdown
+    pub fn shutdown_signal<F>(
+        &self,
+        signal: F,
+        pre_shutdown_delay: Duration,
+    ) -> impl Future<Output = ()> + Send + 'static
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut reporter = self.clone();
+        async move {
+            signal.await;
+            reporter.set_all_not_serving().await;
+            tokio::time::sleep(pre_shutdown_delay).await;
+        }
+    }
 }
 
 /// A service providing implementations of gRPC health checking protocol.
@@ -170,7 +257,8 @@ mod tests {
     use crate::pb::HealthCheckRequest;
     use crate::server::{HealthReporter, HealthService};
     use crate::ServingStatus;
-    use tokio::sync::watch;
+    use std::time::Duration;
+    use tokio::sync::{oneshot, watch};
     use tokio_stream::StreamExt;
     use tonic::{Code, Request, Status};
 
@@ -320,4 +408,45 @@ mod tests {
         let item = resp.next().await;
         assert!(item.is_none());
     }
+
+    #[tokio::test]
+    async fn test_shutdown_signal_reports_not_serving_before_drain() {
+        let (reporter, service) = make_test_service().await;
+
+        let mut resp = service
+            .watch(Request::new(HealthCheckRequest {
+                service: "".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::Serving);
+
+        let (tx, rx) = oneshot::channel::<()>();
+        let signal = reporter.shutdown_signal(
+            async {
+                let _ = rx.await;
+            },
+            Duration::from_secs(60),
+        );
+        let drain = tokio::spawn(signal);
+
+        tx.send(()).unwrap();
+
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::NotServing);
+        assert!(!drain.is_finished());
+
+        let resp = service
+            .check(Request::new(HealthCheckRequest {
+                service: "TestService".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_serving_status(resp.status, ServingStatus::NotServing);
+
+        drain.abort();
+    }
 }
@@ -0,0 +1,97 @@
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    metadata::MetadataValue,
+    service::{Interceptor, InterceptorLayer},
+    transport::{Endpoint, Server},
+    Code, Request, Status,
+};
+use tonic_health::{
+    pb::{health_check_response, health_client::HealthClient, HealthCheckRequest},
+    server::HealthExt,
+    ServingStatus,
+};
+
+#[tokio::test]
+async fn test_with_health() {
+    let (router, mut reporter) = Server::builder().with_health();
+    let (incoming, local_addr) = listen().await;
+    tokio::spawn(router.serve_with_incoming(incoming));
+
+    let mut client = HealthClient::new(Endpoint::new(local_addr).unwrap().connect().await.unwrap());
+    let response = client
+        .check(HealthCheckRequest {
+            service: "".to_string(),
+        })
+        .await
+        .expect("health check")
+        .into_inner();
+    assert_eq!(
+        response.status(),
+        health_check_response::ServingStatus::Serving
+    );
+
+    reporter
+        .set_service_status("", ServingStatus::NotServing)
+        .await;
+    let response = client
+        .check(HealthCheckRequest {
+            service: "".to_string(),
+        })
+        .await
+        .expect("health check")
+        .into_inner();
+    assert_eq!(
+        response.status(),
+        health_check_response::ServingStatus::NotServing
+    );
+}
+
+#[tokio::test]
+async fn test_with_health_behind_interceptor() {
+    let (router, _reporter) = Server::builder()
+        .layer(InterceptorLayer::new(CheckAuth))
+        .with_health();
+    let (incoming, local_addr) = listen().await;
+    tokio::spawn(router.serve_with_incoming(incoming));
+
+    let mut client = HealthClient::new(Endpoint::new(local_addr).unwrap().connect().await.unwrap());
+    let status = client
+        .check(HealthCheckRequest {
+            service: "".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut request = Request::new(HealthCheckRequest {
+        service: "".to_string(),
+    });
+    request
+        .metadata_mut()
+        .insert("authorization", MetadataValue::from_static("Bearer secret"));
+    let response = client.check(request).await.expect("health check");
+    assert_eq!(
+        response.into_inner().status(),
+        health_check_response::ServingStatus::Serving
+    );
+}
+
+#[derive(Clone)]
+struct CheckAuth;
+
+impl Interceptor for CheckAuth {
+    fn call(&mut self, req: Request<()>) -> Result<Request<()>, Status> {
+        match req.metadata().get("authorization") {
+            Some(token) if token == "Bearer secret" => Ok(req),
+            _ => Err(Status::unauthenticated("missing token")),
+        }
+    }
+}
+
+async fn listen() -> (TcpListenerStream, String) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let local_addr = format!("http://{}", listener.local_addr().expect("local address"));
+    (TcpListenerStream::new(listener), local_addr)
+}
@@ -457,5 +457,6 @@ pub mod server_reflection_server {
     pub const SERVICE_NAME: &str = "grpc.reflection.v1.ServerReflection";
     impl<T> tonic::server::NamedService for ServerReflectionServer<T> {
         const NAME: &'static str = SERVICE_NAME;
+        const METHODS: &'static [&'static str] = &["ServerReflectionInfo"];
     }
 }
@@ -7,6 +7,8 @@ use prost_types::{
     DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
     FileDescriptorSet,
 };
+#[cfg(feature = "transport")]
+use tonic::transport::server::{Router, Server};
 use tonic::Status;
 
 /// v1 interface for the gRPC Reflection Service server.
@@ -111,6 +113,94 @@ impl<'b> Builder<'b> {
     }
 }
 
+/// Mounts the gRPC Reflection Service on a server, in both its v1 and v1alpha versions.
+///
+/// Implemented for [`Server`] and [`Router`]. The service is only added if a file descriptor
+/// set is given, which allows enabling reflection depending on the environment:
+///
+/// ```rust,no_run
+/// # mod proto { pub const FILE_DESCRIPTOR_SET: &[u8] = &[]; }
+/// use tonic::transport::Server;
+/// use tonic_reflection::server::ReflectionExt;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let production = std::env::var("ENV").as_deref() == Ok("production");
+/// Server::builder()
+///     .with_reflection((!production).then_some(proto::FILE_DESCRIPTOR_SET))?
+///     .serve("[::1]:50051".parse()?)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Like all services of the router, the service is wrapped in the layers of the [`Server`], so
+/// authentication and other interceptors added with [`Server::layer`] apply to reflection
+/// requests as well.
+#[cfg(feature = "transport")]
+pub trait ReflectionExt {
+    /// The router the service is added to.
+    type Router;
+
+    /// Adds the gRPC Reflection Service for `encoded_file_descriptor_set`, if given.
+    ///
+    /// Like [`Router::add_optional_service`], requests to the service are answered with
+    /// `Unimplemented` if it isn't added.
+    fn with_reflection<'b>(
+        self,
+        encoded_file_descriptor_set: impl Into<Option<&'b [u8]>>,
+    ) -> Result<Self::Router, Error>;
+}
+
+#[cfg(feature = "transport")]
+impl<L: Clone> ReflectionExt for Server<L> {
+    type Router = Router<L>;
+
+    fn with_reflection<'b>(
+        mut self,
+        encoded_file_descriptor_set: impl Into<Option<&'b [u8]>>,
+    ) -> Result<Self::Router, Error> {
+        let (v1, v1alpha) = build_reflection(encoded_file_descriptor_set.into())?.unzip();
+        Ok(self.add_optional_service(v1).add_optional_service(v1alpha))
+    }
+}
+
+#[cfg(feature = "transport")]
+impl<L> ReflectionExt for Router<L> {
+    type Router = Self;
+
+    fn with_reflection<'b>(
+        self,
+        encoded_file_descriptor_set: impl Into<Option<&'b [u8]>>,
+    ) -> Result<Self::Router, Error> {
+        let (v1, v1alpha) = build_reflection(encoded_file_descriptor_set.into())?.unzip();
+        Ok(self.add_optional_service(v1).add_optional_service(v1alpha))
+    }
+}
+
+#[cfg(feature = "transport")]
+#[allow(clippy::type_complexity)]
+fn build_reflection(
+    encoded_file_descriptor_set: Option<&[u8]>,
+) -> Result<
+    Option<(
+        v1::ServerReflectionServer<impl v1::ServerReflection>,
+        v1alpha::ServerReflectionServer<impl v1alpha::ServerReflection>,
+    )>,
+    Error,
+> {
+    let Some(encoded_file_descriptor_set) = encoded_file_descriptor_set else {
+        return Ok(None);
+    };
+
+    let v1 = Builder::configure()
+        .register_encoded_file_descriptor_set(encoded_file_descriptor_set)
+        .build_v1()?;
+    let v1alpha = Builder::configure()
+        .register_encoded_file_descriptor_set(encoded_file_descriptor_set)
+        .build_v1alpha()?;
+    Ok(Some((v1, v1alpha)))
+}
+
 #[derive(Debug)]
 struct ReflectionServiceState {
     service_names: Vec<String>,
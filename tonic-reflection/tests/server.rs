@@ -2,14 +2,17 @@ use prost::Message;
 use std::net::SocketAddr;
 use tokio::sync::oneshot;
 use tokio_stream::{wrappers::TcpListenerStream, StreamExt};
-use tonic::{transport::Server, Request};
+use tonic::{
+    transport::{server::Router, Endpoint, Server},
+    Code, Request,
+};
 use tonic_reflection::{
     pb::v1::{
         server_reflection_client::ServerReflectionClient,
         server_reflection_request::MessageRequest, server_reflection_response::MessageResponse,
         ServerReflectionRequest, ServiceResponse, FILE_DESCRIPTOR_SET,
     },
-    server::Builder,
+    server::{Builder, ReflectionExt},
 };
 
 pub(crate) fn get_encoded_reflection_service_fd() -> Vec<u8> {
@@ -147,3 +150,56 @@ async fn make_test_reflection_request(request: ServerReflectionRequest) -> Messa
 
     response
 }
+
+#[tokio::test]
+async fn test_with_reflection() {
+    let local_addr = serve(
+        Server::builder()
+            .with_reflection(FILE_DESCRIPTOR_SET)
+            .unwrap(),
+    )
+    .await;
+
+    let mut client =
+        ServerReflectionClient::new(Endpoint::new(local_addr).unwrap().connect().await.unwrap());
+    let request = Request::new(tokio_stream::once(ServerReflectionRequest {
+        host: "".to_string(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    }));
+    let response = client
+        .server_reflection_info(request)
+        .await
+        .expect("request")
+        .into_inner()
+        .next()
+        .await
+        .expect("streamed response")
+        .expect("successful response");
+    assert!(matches!(
+        response.message_response,
+        Some(MessageResponse::ListServicesResponse(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_with_reflection_disabled() {
+    let local_addr = serve(Server::builder().with_reflection(None).unwrap()).await;
+
+    let mut client =
+        ServerReflectionClient::new(Endpoint::new(local_addr).unwrap().connect().await.unwrap());
+    let request = Request::new(tokio_stream::once(ServerReflectionRequest {
+        host: "".to_string(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    }));
+    let status = client.server_reflection_info(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+async fn serve(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let local_addr = format!("http://{}", listener.local_addr().expect("local address"));
+    tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+    local_addr
+}
@@ -0,0 +1,284 @@
+//! Conversion between JSON and protobuf messages, following the proto3 JSON mapping.
+//!
+//! Well-known types like `google.protobuf.Timestamp` are mapped like any other message, without
+//! their special JSON representation.
+
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE},
+    Engine as _,
+};
+use prost_reflect::{
+    DynamicMessage, EnumDescriptor, FieldDescriptor, Kind, MapKey, MessageDescriptor,
+    ReflectMessage, Value,
+};
+use serde_json::{Number, Value as Json};
+use std::collections::HashMap;
+
+/// Encodes a message as a JSON object, leaving out fields with their default value.
+pub(crate) fn to_json(message: &DynamicMessage) -> Json {
+    let object = message
+        .fields()
+        .map(|(field, value)| {
+            (
+                field.json_name().to_owned(),
+                value_to_json(&field.kind(), value),
+            )
+        })
+        .collect();
+    Json::Object(object)
+}
+
+fn value_to_json(kind: &Kind, value: &Value) -> Json {
+    match value {
+        Value::Bool(value) => Json::Bool(*value),
+        Value::I32(value) => Json::from(*value),
+        Value::U32(value) => Json::from(*value),
+        // 64-bit integers are strings, since JSON numbers can't safely hold them.
+        Value::I64(value) => Json::String(value.to_string()),
+        Value::U64(value) => Json::String(value.to_string()),
+        Value::F32(value) => float_to_json(f64::from(*value)),
+        Value::F64(value) => float_to_json(*value),
+        Value::String(value) => Json::String(value.clone()),
+        Value::Bytes(value) => Json::String(STANDARD.encode(value)),
+        Value::EnumNumber(number) => match kind {
+            Kind::Enum(desc) => match desc.get_value(*number) {
+                Some(value) => Json::String(value.name().to_owned()),
+                None => Json::from(*number),
+            },
+            _ => Json::from(*number),
+        },
+        Value::Message(message) => to_json(message),
+        Value::List(values) => Json::Array(values.iter().map(|v| value_to_json(kind, v)).collect()),
+        Value::Map(entries) => {
+            let value_kind = match kind {
+                Kind::Message(entry) => entry.map_entry_value_field().kind(),
+                _ => kind.clone(),
+            };
+            let object = entries
+                .iter()
+                .map(|(key, value)| (map_key_to_string(key), value_to_json(&value_kind, value)))
+                .collect();
+            Json::Object(object)
+        }
+    }
+}
+
+fn float_to_json(value: f64) -> Json {
+    match Number::from_f64(value) {
+        Some(number) => Json::Number(number),
+        None if value.is_nan() => Json::String("NaN".to_owned()),
+        None if value > 0.0 => Json::String("Infinity".to_owned()),
+        None => Json::String("-Infinity".to_owned()),
+    }
+}
+
+fn map_key_to_string(key: &MapKey) -> String {
+    match key {
+        MapKey::Bool(key) => key.to_string(),
+        MapKey::I32(key) => key.to_string(),
+        MapKey::I64(key) => key.to_string(),
+        MapKey::U32(key) => key.to_string(),
+        MapKey::U64(key) => key.to_string(),
+        MapKey::String(key) => key.clone(),
+    }
+}
+
+/// Merges a JSON object into `message`.
+pub(crate) fn merge_json(message: &mut DynamicMessage, json: Json) -> Result<(), String> {
+    let Json::Object(object) = json else {
+        return Err(format!(
+            "expected an object for `{}`",
+            message.descriptor().full_name()
+        ));
+    };
+
+    let desc = message.descriptor();
+    for (name, value) in object {
+        let field = desc
+            .get_field_by_json_name(&name)
+            .or_else(|| desc.get_field_by_name(&name))
+            .ok_or_else(|| format!("unknown field `{name}` in `{}`", desc.full_name()))?;
+
+        if value.is_null() {
+            continue;
+        }
+        let value = field_from_json(&field, value)?;
+        message.set_field(&field, value);
+    }
+
+    Ok(())
+}
+
+/// Converts the JSON value of a field, which may be repeated or a map.
+pub(crate) fn field_from_json(field: &FieldDescriptor, json: Json) -> Result<Value, String> {
+    let err = |expected: &str| format!("expected {expected} for field `{}`", field.name());
+
+    if field.is_map() {
+        let Kind::Message(entry) = field.kind() else {
+            unreachable!("map fields have a message kind");
+        };
+        let (key_field, value_field) = (entry.map_entry_key_field(), entry.map_entry_value_field());
+
+        let Json::Object(object) = json else {
+            return Err(err("an object"));
+        };
+        let entries = object
+            .into_iter()
+            .map(|(key, value)| {
+                let key = match value_from_str(&key_field.kind(), &key)? {
+                    Value::Bool(key) => MapKey::Bool(key),
+                    Value::I32(key) => MapKey::I32(key),
+                    Value::I64(key) => MapKey::I64(key),
+                    Value::U32(key) => MapKey::U32(key),
+                    Value::U64(key) => MapKey::U64(key),
+                    Value::String(key) => MapKey::String(key),
+                    _ => return Err(err("a valid map key")),
+                };
+                Ok((key, value_from_json(&value_field.kind(), value)?))
+            })
+            .collect::<Result<HashMap<_, _>, String>>()?;
+        Ok(Value::Map(entries))
+    } else if field.is_list() {
+        let Json::Array(values) = json else {
+            return Err(err("an array"));
+        };
+        let kind = field.kind();
+        let values = values
+            .into_iter()
+            .map(|value| value_from_json(&kind, value))
+            .collect::<Result<_, _>>()?;
+        Ok(Value::List(values))
+    } else {
+        value_from_json(&field.kind(), json)
+    }
+}
+
+/// Converts a single JSON value of the given kind.
+fn value_from_json(kind: &Kind, json: Json) -> Result<Value, String> {
+    match (kind, json) {
+        (Kind::Message(desc), json) => {
+            let mut message = DynamicMessage::new(desc.clone());
+            merge_json(&mut message, json)?;
+            Ok(Value::Message(message))
+        }
+        (Kind::Bool, Json::Bool(value)) => Ok(Value::Bool(value)),
+        (Kind::Enum(_), Json::Number(number)) => number
+            .as_i64()
+            .and_then(|number| i32::try_from(number).ok())
+            .map(Value::EnumNumber)
+            .ok_or_else(|| invalid(kind, number)),
+        (Kind::Enum(desc), Json::String(name)) => enum_from_name(desc, &name),
+        (Kind::String | Kind::Bytes, Json::String(value)) => value_from_str(kind, &value),
+        (_, Json::Number(number)) if is_numeric(kind) => value_from_str(kind, &number.to_string()),
+        (_, Json::String(value)) if is_numeric(kind) => value_from_str(kind, &value),
+        (kind, json) => Err(invalid(kind, json)),
+    }
+}
+
+/// Converts a value from a path or query parameter, or a map key.
+pub(crate) fn value_from_str(kind: &Kind, value: &str) -> Result<Value, String> {
+    let parsed = match kind {
+        Kind::Double => parse_float(value).map(Value::F64),
+        Kind::Float => parse_float(value).map(|value| Value::F32(value as f32)),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => parse_int(value).map(Value::I32),
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => parse_int(value).map(Value::I64),
+        Kind::Uint32 | Kind::Fixed32 => parse_int(value).map(Value::U32),
+        Kind::Uint64 | Kind::Fixed64 => parse_int(value).map(Value::U64),
+        Kind::Bool => value.parse().ok().map(Value::Bool),
+        Kind::String => Some(Value::String(value.to_owned())),
+        Kind::Bytes => STANDARD
+            .decode(value)
+            .or_else(|_| URL_SAFE.decode(value))
+            .ok()
+            .map(|bytes| Value::Bytes(bytes.into())),
+        Kind::Enum(desc) => return enum_from_name(desc, value),
+        Kind::Message(_) => None,
+    };
+    parsed.ok_or_else(|| invalid(kind, value))
+}
+
+fn enum_from_name(desc: &EnumDescriptor, name: &str) -> Result<Value, String> {
+    match desc.get_value_by_name(name) {
+        Some(value) => Ok(Value::EnumNumber(value.number())),
+        None => name
+            .parse()
+            .map(Value::EnumNumber)
+            .map_err(|_| format!("unknown value `{name}` of enum `{}`", desc.full_name())),
+    }
+}
+
+fn is_numeric(kind: &Kind) -> bool {
+    !matches!(
+        kind,
+        Kind::Bool | Kind::String | Kind::Bytes | Kind::Enum(_) | Kind::Message(_)
+    )
+}
+
+fn parse_float(value: &str) -> Option<f64> {
+    match value {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        value => value.parse().ok(),
+    }
+}
+
+/// Parses an integer, also accepting an integral number in exponent notation like `1e3`.
+fn parse_int<T>(value: &str) -> Option<T>
+where
+    T: std::str::FromStr + TryFrom<i128>,
+{
+    value.parse().ok().or_else(|| {
+        let float: f64 = value.parse().ok()?;
+        if float.fract() != 0.0 || !float.is_finite() {
+            return None;
+        }
+        T::try_from(float as i128).ok()
+    })
+}
+
+fn invalid(kind: &Kind, value: impl std::fmt::Display) -> String {
+    let kind = match kind {
+        Kind::Message(desc) => desc.full_name().to_owned(),
+        Kind::Enum(desc) => desc.full_name().to_owned(),
+        kind => format!("{kind:?}").to_lowercase(),
+    };
+    format!("invalid {kind} value `{value}`")
+}
+
+/// Sets the field at `path`, like `item.id`, creating the messages along the way.
+///
+/// Values of repeated fields are appended.
+pub(crate) fn set_path(
+    message: &mut DynamicMessage,
+    path: &[String],
+    value: &str,
+) -> Result<(), String> {
+    let desc: MessageDescriptor = message.descriptor();
+    let (name, rest) = path.split_first().expect("field paths are not empty");
+    let field = desc
+        .get_field_by_name(name)
+        .or_else(|| desc.get_field_by_json_name(name))
+        .ok_or_else(|| format!("unknown field `{name}` in `{}`", desc.full_name()))?;
+
+    if !rest.is_empty() {
+        return match message.get_field_mut(&field) {
+            Value::Message(message) if !field.is_list() => set_path(message, rest, value),
+            _ => Err(format!("field `{name}` is not a message")),
+        };
+    }
+
+    if field.is_map() {
+        return Err(format!("map field `{name}` can't be set from a parameter"));
+    }
+
+    let parsed = value_from_str(&field.kind(), value)?;
+    if field.is_list() {
+        if let Value::List(values) = message.get_field_mut(&field) {
+            values.push(parsed);
+        }
+    } else {
+        message.set_field(&field, parsed);
+    }
+    Ok(())
+}
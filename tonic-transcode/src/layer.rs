@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use prost_reflect::DescriptorPool;
+use tower_layer::Layer;
+
+use crate::rules::{routes, Route};
+use crate::{Error, TranscodeService};
+
+/// Layer transcoding HTTP/JSON requests to gRPC methods, see the [crate] documentation.
+#[derive(Debug, Clone)]
+pub struct TranscodeLayer {
+    routes: Arc<[Route]>,
+}
+
+impl TranscodeLayer {
+    /// Create a transcoding layer for the `google.api.http` rules in an encoded
+    /// `FileDescriptorSet`.
+    ///
+    /// Returns an error if the descriptor set can't be decoded, does not include
+    /// `google/api/http.proto`, or has an invalid rule.
+    pub fn new(file_descriptor_set: &[u8]) -> Result<TranscodeLayer, Error> {
+        let pool = DescriptorPool::decode(file_descriptor_set)
+            .map_err(|err| Error::new(format!("invalid file descriptor set: {err}")))?;
+        Ok(TranscodeLayer {
+            routes: routes(&pool)?.into(),
+        })
+    }
+}
+
+impl<S> Layer<S> for TranscodeLayer {
+    type Service = TranscodeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TranscodeService::new(inner, self.routes.clone())
+    }
+}
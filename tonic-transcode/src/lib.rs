@@ -0,0 +1,100 @@
+//! HTTP/JSON to gRPC transcoding for [`tonic`] services.
+//!
+//! [`tonic_transcode`] lets a tonic server answer REST clients next to gRPC clients, like
+//! [grpc-gateway] or Envoy's gRPC-JSON transcoder do as a proxy. Methods are mapped to HTTP
+//! endpoints with [`google.api.http`] annotations in the proto files:
+//!
+//! ```protobuf
+//! import "google/api/annotations.proto";
+//!
+//! service Items {
+//!   rpc GetItem(GetItemRequest) returns (Item) {
+//!     option (google.api.http) = { get: "/v1/items/{id}" };
+//!   }
+//!   rpc CreateItem(CreateItemRequest) returns (Item) {
+//!     option (google.api.http) = { post: "/v1/items" body: "item" };
+//!   }
+//! }
+//! ```
+//!
+//! The annotations are read from a file descriptor set, which `tonic-build` writes with
+//! `file_descriptor_set_path`. The descriptor set must include `google/api/annotations.proto`
+//! and `google/api/http.proto`.
+//!
+//! ## Enabling tonic services
+//!
+//! ```ignore
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let addr = "[::1]:50051".parse().unwrap();
+//!     let items = ItemsServer::new(MyItems::default());
+//!
+//!     Server::builder()
+//!        .accept_http1(true)
+//!        // This will apply the transcoding layer
+//!        .layer(TranscodeLayer::new(FILE_DESCRIPTOR_SET)?)
+//!        .add_service(items)
+//!        .serve(addr)
+//!        .await?;
+//!
+//!    Ok(())
+//! }
+//! ```
+//!
+//! A request is transcoded when it matches the HTTP method and path template of a rule. The
+//! request message is built from the path variables, the JSON body as selected by the rule's
+//! `body`, and the query parameters for the remaining fields. The response message is sent as
+//! JSON with `200 OK`, and errors as a JSON object with the `code` and `message` of the status
+//! and the HTTP status for the code. gRPC requests and all requests that match no rule are passed
+//! through to the inner service unchanged.
+//!
+//! ## Limitations
+//!
+//! * Only unary methods are transcoded, rules on streaming methods are ignored.
+//! * `custom` rules and `response_body` are not supported.
+//! * Well-known types like `google.protobuf.Timestamp` are mapped like other messages, without
+//!   their special JSON representation.
+//!
+//! [`tonic`]: https://github.com/hyperium/tonic
+//! [`tonic_transcode`]: https://github.com/hyperium/tonic
+//! [grpc-gateway]: https://github.com/grpc-ecosystem/grpc-gateway
+//! [`google.api.http`]: https://github.com/googleapis/googleapis/blob/master/google/api/http.proto
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+#![doc(html_root_url = "https://docs.rs/tonic-transcode/0.13.0")]
+#![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
+
+pub use layer::TranscodeLayer;
+pub use service::{ResponseFuture, TranscodeService};
+
+mod json;
+mod layer;
+mod rules;
+mod service;
+mod template;
+
+use std::fmt;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Error building a [`TranscodeLayer`] from a file descriptor set.
+#[derive(Debug)]
+pub struct Error(String);
+
+impl Error {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        Error(message.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,210 @@
+use crate::{
+    json::{field_from_json, merge_json, set_path},
+    template::PathTemplate,
+    Error,
+};
+use bytes::Bytes;
+use http::Method;
+use percent_encoding::percent_decode_str;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, ExtensionDescriptor, FieldDescriptor, MessageDescriptor,
+    MethodDescriptor, Value,
+};
+
+const HTTP_RULE_EXTENSION: &str = "google.api.http";
+
+/// Where the fields of the request message are taken from besides the path.
+#[derive(Debug)]
+enum RequestBody {
+    /// The request body is ignored, fields are set with query parameters.
+    None,
+    /// The request body is the whole message, `body: "*"`.
+    Message,
+    /// The request body is a single field, fields besides it are set with query parameters.
+    Field(FieldDescriptor),
+}
+
+/// A REST endpoint mapped to a gRPC method by a `google.api.http` rule.
+#[derive(Debug)]
+pub(crate) struct Route {
+    http_method: Method,
+    template: PathTemplate,
+    body: RequestBody,
+    pub(crate) grpc_path: String,
+    input: MessageDescriptor,
+    pub(crate) output: MessageDescriptor,
+}
+
+impl Route {
+    /// Matches a request, returning the field paths and values of the path variables.
+    pub(crate) fn matches(
+        &self,
+        method: &Method,
+        path: &str,
+    ) -> Option<Vec<(Vec<String>, String)>> {
+        if self.http_method != method {
+            return None;
+        }
+        let values = self.template.matches(path)?;
+        Some(
+            values
+                .into_iter()
+                .map(|(field_path, value)| (field_path.to_vec(), value))
+                .collect(),
+        )
+    }
+
+    /// Builds the request message from the path variables, query and body.
+    pub(crate) fn request_message(
+        &self,
+        variables: Vec<(Vec<String>, String)>,
+        query: Option<&str>,
+        body: Bytes,
+    ) -> Result<DynamicMessage, String> {
+        let mut message = DynamicMessage::new(self.input.clone());
+
+        match &self.body {
+            RequestBody::None => {}
+            RequestBody::Message => {
+                if !body.is_empty() {
+                    merge_json(&mut message, parse_json(&body)?)?;
+                }
+            }
+            RequestBody::Field(field) => {
+                if !body.is_empty() {
+                    let value = field_from_json(field, parse_json(&body)?)?;
+                    message.set_field(field, value);
+                }
+            }
+        }
+
+        for (field_path, value) in variables {
+            set_path(&mut message, &field_path, &value)?;
+        }
+
+        // All fields are taken from the body with `body: "*"`, so there are no query parameters.
+        if !matches!(self.body, RequestBody::Message) {
+            for (name, value) in query.into_iter().flat_map(query_pairs) {
+                let field_path: Vec<String> = name.split('.').map(str::to_owned).collect();
+                set_path(&mut message, &field_path, &value)?;
+            }
+        }
+
+        Ok(message)
+    }
+}
+
+fn parse_json(body: &[u8]) -> Result<serde_json::Value, String> {
+    serde_json::from_slice(body).map_err(|err| format!("invalid JSON body: {err}"))
+}
+
+fn query_pairs(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    let decode = |s: &str| {
+        percent_decode_str(&s.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(move |pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+}
+
+/// Collects the routes of all methods annotated with `google.api.http` in `pool`.
+pub(crate) fn routes(pool: &DescriptorPool) -> Result<Vec<Route>, Error> {
+    let extension = pool
+        .get_extension_by_name(HTTP_RULE_EXTENSION)
+        .ok_or_else(|| {
+            Error::new(format!(
+                "the descriptor set does not define `{HTTP_RULE_EXTENSION}`, \
+             it must include `google/api/annotations.proto`"
+            ))
+        })?;
+
+    let mut routes = Vec::new();
+    for service in pool.services() {
+        for method in service.methods() {
+            method_routes(&method, &extension, &mut routes)?;
+        }
+    }
+    Ok(routes)
+}
+
+fn method_routes(
+    method: &MethodDescriptor,
+    extension: &ExtensionDescriptor,
+    routes: &mut Vec<Route>,
+) -> Result<(), Error> {
+    let options = method.options();
+    if !options.has_extension(extension) {
+        return Ok(());
+    }
+
+    if method.is_client_streaming() || method.is_server_streaming() {
+        tracing::debug!(
+            method = method.full_name(),
+            "ignoring the HTTP rule of a streaming method"
+        );
+        return Ok(());
+    }
+
+    let Value::Message(rule) = &*options.get_extension(extension) else {
+        return Ok(());
+    };
+
+    routes.push(route(method, rule)?);
+    if let Some(bindings) = rule.get_field_by_name("additional_bindings") {
+        if let Value::List(bindings) = &*bindings {
+            for binding in bindings.iter().filter_map(Value::as_message) {
+                routes.push(route(method, binding)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn route(method: &MethodDescriptor, rule: &DynamicMessage) -> Result<Route, Error> {
+    let err = |reason: String| Error::new(format!("`{}`: {reason}", method.full_name()));
+    let string_field = |name: &str| {
+        rule.get_field_by_name(name)
+            .and_then(|value| value.as_str().map(str::to_owned))
+            .unwrap_or_default()
+    };
+
+    let (http_method, template) = [
+        ("get", Method::GET),
+        ("put", Method::PUT),
+        ("post", Method::POST),
+        ("delete", Method::DELETE),
+        ("patch", Method::PATCH),
+    ]
+    .into_iter()
+    .find(|(name, _)| rule.has_field_by_name(name))
+    .map(|(name, http_method)| (http_method, string_field(name)))
+    .ok_or_else(|| err("the HTTP rule has no supported method".to_owned()))?;
+
+    let template = PathTemplate::parse(&template).map_err(|e| err(e.to_string()))?;
+
+    let input = method.input();
+    let body = match string_field("body").as_str() {
+        "" => RequestBody::None,
+        "*" => RequestBody::Message,
+        name => RequestBody::Field(
+            input
+                .get_field_by_name(name)
+                .ok_or_else(|| err(format!("unknown body field `{name}`")))?,
+        ),
+    };
+
+    Ok(Route {
+        http_method,
+        template,
+        body,
+        grpc_path: format!("/{}/{}", method.parent_service().full_name(), method.name()),
+        input,
+        output: method.output(),
+    })
+}
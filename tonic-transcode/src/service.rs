@@ -0,0 +1,298 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use http::{header, HeaderValue, Method, Request, Response, StatusCode, Uri, Version};
+use http_body_util::{BodyExt, Full};
+use pin_project::pin_project;
+use prost::Message as _;
+use prost_reflect::DynamicMessage;
+use tonic::metadata::GRPC_CONTENT_TYPE;
+use tonic::{body::Body, server::NamedService, Code, Status};
+use tower_service::Service;
+use tracing::{debug, trace};
+
+use crate::json::to_json;
+use crate::rules::Route;
+use crate::BoxError;
+
+const JSON_CONTENT_TYPE: HeaderValue = HeaderValue::from_static("application/json");
+
+/// Service transcoding HTTP/JSON requests to the gRPC methods of the inner service.
+///
+/// See [`TranscodeLayer`](crate::TranscodeLayer).
+#[derive(Clone)]
+pub struct TranscodeService<S> {
+    inner: S,
+    routes: Arc<[Route]>,
+}
+
+impl<S> TranscodeService<S> {
+    pub(crate) fn new(inner: S, routes: Arc<[Route]>) -> Self {
+        TranscodeService { inner, routes }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for TranscodeService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranscodeService")
+            .field("inner", &self.inner)
+            .field("routes", &self.routes.len())
+            .finish()
+    }
+}
+
+impl<S, B> Service<Request<B>> for TranscodeService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let is_grpc = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/grpc"));
+
+        let matched = (!is_grpc)
+            .then(|| {
+                self.routes.iter().enumerate().find_map(|(index, route)| {
+                    route
+                        .matches(req.method(), req.uri().path())
+                        .map(|variables| (index, variables))
+                })
+            })
+            .flatten();
+
+        let Some((index, variables)) = matched else {
+            return ResponseFuture {
+                case: Case::Other {
+                    future: self.inner.call(req.map(Body::new)),
+                },
+            };
+        };
+
+        let route = RouteRef {
+            routes: self.routes.clone(),
+            index,
+        };
+        trace!(path = ?req.uri().path(), method = route.get().grpc_path, "transcoding");
+
+        // The inner service was driven to readiness, so take it and leave the clone behind.
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+
+        ResponseFuture {
+            case: Case::Transcode {
+                future: Box::pin(transcode(inner, route, variables, req)),
+            },
+        }
+    }
+}
+
+impl<S: NamedService> NamedService for TranscodeService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+/// A route, shared with the service it was matched by.
+struct RouteRef {
+    routes: Arc<[Route]>,
+    index: usize,
+}
+
+impl RouteRef {
+    fn get(&self) -> &Route {
+        &self.routes[self.index]
+    }
+}
+
+async fn transcode<S, B>(
+    mut inner: S,
+    route: RouteRef,
+    variables: Vec<(Vec<String>, String)>,
+    req: Request<B>,
+) -> Result<Response<Body>, S::Error>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    let route = route.get();
+    let (mut parts, body) = req.into_parts();
+
+    let body = match body.collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(err) => {
+            let err = err.into();
+            debug!(error = %err, "failed to read the request body");
+            return Ok(error_response(&Status::invalid_argument(format!(
+                "failed to read the request body: {err}"
+            ))));
+        }
+    };
+
+    let message = match route.request_message(variables, parts.uri.query(), body) {
+        Ok(message) => message,
+        Err(err) => return Ok(error_response(&Status::invalid_argument(err))),
+    };
+
+    parts.method = Method::POST;
+    parts.uri = Uri::try_from(route.grpc_path.as_str()).expect("method paths are valid URIs");
+    parts.version = Version::HTTP_2;
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.remove(header::ACCEPT_ENCODING);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+    parts
+        .headers
+        .insert(header::TE, HeaderValue::from_static("trailers"));
+
+    let req = Request::from_parts(parts, Body::new(Full::new(encode_frame(&message))));
+    let res = inner.call(req).await?;
+    Ok(coerce_response(route, res).await)
+}
+
+/// Prefixes the encoded message with the uncompressed flag and its length.
+fn encode_frame(message: &DynamicMessage) -> Bytes {
+    let len = message.encoded_len();
+    let mut buf = BytesMut::with_capacity(5 + len);
+    buf.put_u8(0);
+    buf.put_u32(len as u32);
+    message
+        .encode(&mut buf)
+        .expect("the buffer has enough capacity");
+    buf.freeze()
+}
+
+async fn coerce_response(route: &Route, res: Response<Body>) -> Response<Body> {
+    let (parts, body) = res.into_parts();
+
+    // A trailers-only response.
+    if let Some(status) = Status::from_header_map(&parts.headers) {
+        if status.code() != Code::Ok {
+            return error_response(&status);
+        }
+    }
+
+    let body = match body.collect().await {
+        Ok(body) => body,
+        Err(status) => return error_response(&status),
+    };
+    if let Some(status) = body.trailers().and_then(Status::from_header_map) {
+        if status.code() != Code::Ok {
+            return error_response(&status);
+        }
+    }
+
+    let data = body.to_bytes();
+    let message = match decode_frame(&data) {
+        Ok(message) => DynamicMessage::decode(route.output.clone(), message)
+            .map_err(|err| Status::internal(format!("failed to decode the response: {err}"))),
+        Err(status) => Err(status),
+    };
+
+    match message {
+        Ok(message) => json_response(StatusCode::OK, &to_json(&message)),
+        Err(status) => error_response(&status),
+    }
+}
+
+fn decode_frame(data: &[u8]) -> Result<&[u8], Status> {
+    let (&compressed, rest) = data
+        .split_first()
+        .ok_or_else(|| Status::internal("the response has no message"))?;
+    if compressed != 0 {
+        return Err(Status::internal("compressed responses are not supported"));
+    }
+    if rest.len() < 4 {
+        return Err(Status::internal("the response message is truncated"));
+    }
+    let (len, message) = rest.split_at(4);
+    let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+    message
+        .get(..len)
+        .ok_or_else(|| Status::internal("the response message is truncated"))
+}
+
+fn error_response(status: &Status) -> Response<Body> {
+    let body = serde_json::json!({
+        "code": status.code().to_i32(),
+        "message": status.message(),
+    });
+    json_response(status.code().to_http_status(), &body)
+}
+
+fn json_response(status: StatusCode, body: &serde_json::Value) -> Response<Body> {
+    let mut res = Response::new(Body::new(Full::new(Bytes::from(body.to_string()))));
+    *res.status_mut() = status;
+    res.headers_mut()
+        .insert(header::CONTENT_TYPE, JSON_CONTENT_TYPE);
+    res
+}
+
+type TranscodeFuture<E> = Pin<Box<dyn Future<Output = Result<Response<Body>, E>> + Send>>;
+
+/// Response future for the [`TranscodeService`].
+#[pin_project]
+pub struct ResponseFuture<F, E> {
+    #[pin]
+    case: Case<F, E>,
+}
+
+#[pin_project(project = CaseProj)]
+enum Case<F, E> {
+    Transcode {
+        future: TranscodeFuture<E>,
+    },
+    Other {
+        #[pin]
+        future: F,
+    },
+}
+
+impl<F, E> Future for ResponseFuture<F, E>
+where
+    F: Future<Output = Result<Response<Body>, E>>,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().case.project() {
+            CaseProj::Transcode { future } => future.as_mut().poll(cx),
+            CaseProj::Other { future } => future.poll(cx),
+        }
+    }
+}
+
+impl<F, E> fmt::Debug for ResponseFuture<F, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames() {
+        assert_eq!(decode_frame(b"\0\0\0\0\x02ab\0").unwrap(), b"ab");
+        assert!(decode_frame(b"").is_err());
+        assert!(decode_frame(b"\0\0\0").is_err());
+        assert!(decode_frame(b"\0\0\0\0\x03ab").is_err());
+        assert!(decode_frame(b"\x01\0\0\0\x02ab").is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Path templates of `google.api.http` rules.
+//!
+//! The grammar, from `google/api/http.proto`:
+//!
+//! ```text
+//! Template = "/" Segments [ Verb ] ;
+//! Segments = Segment { "/" Segment } ;
+//! Segment  = "*" | "**" | LITERAL | Variable ;
+//! Variable = "{" FieldPath [ "=" Segments ] "}" ;
+//! FieldPath = IDENT { "." IDENT } ;
+//! Verb     = ":" LITERAL ;
+//! ```
+
+use percent_encoding::percent_decode_str;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    /// `*`, matching a single segment.
+    Single,
+    /// `**`, matching the rest of the path.
+    Rest,
+}
+
+/// A variable capturing the segments `start..end` of the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Variable {
+    field_path: Vec<String>,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PathTemplate {
+    segments: Vec<Segment>,
+    variables: Vec<Variable>,
+    verb: Option<String>,
+}
+
+/// An error parsing a path template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TemplateError(String);
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl PathTemplate {
+    pub(crate) fn parse(template: &str) -> Result<Self, TemplateError> {
+        let err =
+            |reason: &str| TemplateError(format!("invalid path template `{template}`: {reason}"));
+
+        let path = template
+            .strip_prefix('/')
+            .ok_or_else(|| err("must start with `/`"))?;
+
+        // The verb follows the last `:` outside of a variable.
+        let (path, verb) = match path.rfind(':') {
+            Some(pos) if !path[pos..].contains('}') => {
+                (&path[..pos], Some(path[pos + 1..].to_owned()))
+            }
+            _ => (path, None),
+        };
+
+        let mut segments = Vec::new();
+        let mut variables = Vec::new();
+        let mut rest = path;
+
+        while !rest.is_empty() {
+            let (segment, tail) = if let Some(variable) = rest.strip_prefix('{') {
+                let end = variable
+                    .find('}')
+                    .ok_or_else(|| err("unterminated variable"))?;
+                let (field_path, pattern) = match variable[..end].split_once('=') {
+                    Some((field_path, pattern)) => (field_path, pattern),
+                    None => (&variable[..end], "*"),
+                };
+
+                let field_path: Vec<String> = field_path.split('.').map(str::to_owned).collect();
+                if field_path.iter().any(String::is_empty) {
+                    return Err(err("empty field path"));
+                }
+
+                let start = segments.len();
+                for segment in pattern.split('/') {
+                    segments.push(parse_segment(segment).ok_or_else(|| err("invalid segment"))?);
+                }
+                variables.push(Variable {
+                    field_path,
+                    start,
+                    end: segments.len(),
+                });
+
+                (None, &variable[end + 1..])
+            } else {
+                let end = rest.find('/').unwrap_or(rest.len());
+                (Some(&rest[..end]), &rest[end..])
+            };
+
+            if let Some(segment) = segment {
+                segments.push(parse_segment(segment).ok_or_else(|| err("invalid segment"))?);
+            }
+
+            rest = match tail.strip_prefix('/') {
+                Some("") => return Err(err("trailing `/`")),
+                Some(tail) => tail,
+                None if tail.is_empty() => tail,
+                None => return Err(err("expected `/` after a variable")),
+            };
+        }
+
+        if segments.is_empty() {
+            return Err(err("no segments"));
+        }
+        if let Some(pos) = segments.iter().position(|s| *s == Segment::Rest) {
+            if pos != segments.len() - 1 {
+                return Err(err("`**` must be the last segment"));
+            }
+        }
+
+        Ok(Self {
+            segments,
+            variables,
+            verb,
+        })
+    }
+
+    /// Matches a request path, returning the field paths and decoded values of the variables.
+    pub(crate) fn matches(&self, path: &str) -> Option<Vec<(&[String], String)>> {
+        let path = path.strip_prefix('/')?;
+        let path = match &self.verb {
+            Some(verb) => path.strip_suffix(verb.as_str())?.strip_suffix(':')?,
+            None => path,
+        };
+
+        let parts: Vec<&str> = path.split('/').collect();
+        let ends_with_rest = self.segments.last() == Some(&Segment::Rest);
+        let fixed = self.segments.len() - usize::from(ends_with_rest);
+        if parts.len() < fixed || (!ends_with_rest && parts.len() != fixed) {
+            return None;
+        }
+
+        for (segment, part) in self.segments.iter().zip(&parts) {
+            match segment {
+                Segment::Literal(literal) if literal != part => return None,
+                Segment::Single if part.is_empty() => return None,
+                _ => {}
+            }
+        }
+
+        let values = self
+            .variables
+            .iter()
+            .map(|variable| {
+                let end = if variable.end == self.segments.len() && ends_with_rest {
+                    parts.len()
+                } else {
+                    variable.end
+                };
+                let value = parts[variable.start..end]
+                    .iter()
+                    .map(|part| percent_decode_str(part).decode_utf8_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                (variable.field_path.as_slice(), value)
+            })
+            .collect();
+
+        Some(values)
+    }
+}
+
+fn parse_segment(segment: &str) -> Option<Segment> {
+    match segment {
+        "" => None,
+        "*" => Some(Segment::Single),
+        "**" => Some(Segment::Rest),
+        literal if literal.contains(['*', '{', '}', '=']) => None,
+        literal => Some(Segment::Literal(literal.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(template: &str, path: &str) -> Option<Vec<(String, String)>> {
+        PathTemplate::parse(template)
+            .unwrap()
+            .matches(path)
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|(field_path, value)| (field_path.join("."), value))
+                    .collect()
+            })
+    }
+
+    fn vars(values: &[(&str, &str)]) -> Option<Vec<(String, String)>> {
+        Some(
+            values
+                .iter()
+                .map(|(field, value)| (field.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn literal_segments() {
+        assert_eq!(matches("/v1/items", "/v1/items"), vars(&[]));
+        assert_eq!(matches("/v1/items", "/v1/items/1"), None);
+        assert_eq!(matches("/v1/items", "/v2/items"), None);
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            matches("/v1/items/{id}", "/v1/items/42"),
+            vars(&[("id", "42")])
+        );
+        assert_eq!(
+            matches(
+                "/v1/items/{item.id}/parts/{part}",
+                "/v1/items/a%20b/parts/x"
+            ),
+            vars(&[("item.id", "a b"), ("part", "x")])
+        );
+        assert_eq!(matches("/v1/items/{id}", "/v1/items/"), None);
+        assert_eq!(matches("/v1/items/{id}", "/v1/items/1/2"), None);
+    }
+
+    #[test]
+    fn variables_with_patterns() {
+        assert_eq!(
+            matches("/v1/{name=shelves/*/books/*}", "/v1/shelves/1/books/2"),
+            vars(&[("name", "shelves/1/books/2")])
+        );
+        assert_eq!(matches("/v1/{name=shelves/*}", "/v1/books/1"), None);
+        assert_eq!(
+            matches("/v1/files/{path=**}", "/v1/files/a/b/c"),
+            vars(&[("path", "a/b/c")])
+        );
+        assert_eq!(matches("/v1/*/items", "/v1/any/items"), vars(&[]));
+    }
+
+    #[test]
+    fn verbs() {
+        assert_eq!(
+            matches("/v1/items/{id}:cancel", "/v1/items/1:cancel"),
+            vars(&[("id", "1")])
+        );
+        assert_eq!(matches("/v1/items/{id}:cancel", "/v1/items/1"), None);
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for template in [
+            "v1/items",
+            "/v1/items/",
+            "/v1/{id",
+            "/v1/{}",
+            "/v1/**/items",
+            "/v1/{id}items",
+            "/",
+        ] {
+            assert!(PathTemplate::parse(template).is_err(), "{template}");
+        }
+    }
+}
@@ -61,7 +61,8 @@
 //! * Currently, grpc-web clients can only perform `unary` and `server-streaming` calls. These
 //!   are the only requests this crate is designed to handle. Support for client and bi-directional
 //!   streaming will be officially supported when clients do.
-//! * There is no support for web socket transports.
+//! * WebSocket transports are only supported through the bespoke framing of the
+//!   [`websocket`](crate::websocket) module, behind the `websocket` feature.
 //!
 //!
 //! [`tonic`]: https://github.com/hyperium/tonic
@@ -80,12 +81,16 @@
 pub use call::GrpcWebCall;
 pub use client::{GrpcWebClientLayer, GrpcWebClientService};
 pub use layer::GrpcWebLayer;
-pub use service::{GrpcWebService, ResponseFuture};
+pub use service::{GrpcWebService, HttpStatus, ResponseFuture};
+#[cfg(feature = "websocket")]
+pub use websocket::{GrpcWebSocketLayer, GrpcWebSocketService, WebSocketResponseFuture};
 
 mod call;
 mod client;
 mod layer;
 mod service;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
 type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
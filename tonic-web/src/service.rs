@@ -13,6 +13,29 @@ use tracing::{debug, trace};
 use crate::call::content_types::is_grpc_web;
 use crate::call::{Encoding, GrpcWebCall};
 
+/// Overrides the HTTP status of a grpc-web response.
+///
+/// gRPC responses always have the HTTP status `200 OK`, with the outcome of the call in the
+/// `grpc-status` header or trailer. Some proxies and browser tooling only look at the HTTP status
+/// though. A handler, or a layer between the [`GrpcWebService`] and the handlers, can insert this
+/// into the extensions of a response to answer grpc-web requests with a different HTTP status.
+///
+/// The `grpc-status` is sent unchanged, and responses to native gRPC requests keep `200 OK`.
+///
+/// # Example
+///
+/// ```
+/// # use http::StatusCode;
+/// # use tonic::Response;
+/// # use tonic_web::HttpStatus;
+/// let mut response = Response::new(());
+/// response
+///     .extensions_mut()
+///     .insert(HttpStatus(StatusCode::ACCEPTED));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpStatus(pub StatusCode);
+
 /// Service implementing the grpc-web protocol.
 #[derive(Debug, Clone)]
 pub struct GrpcWebService<S> {
@@ -167,7 +190,12 @@ where
 
                 Poll::Ready(Ok(coerce_response(res, *accept)))
             }
-            CaseProj::Other { future } => future.poll(cx),
+            CaseProj::Other { future } => {
+                let mut res = ready!(future.poll(cx))?;
+                res.extensions_mut().remove::<HttpStatus>();
+
+                Poll::Ready(Ok(res))
+            }
             CaseProj::ImmediateResponse { res } => {
                 let res = Response::from_parts(res.take().unwrap(), Body::empty());
                 Poll::Ready(Ok(res))
@@ -233,6 +261,10 @@ where
         .map(|b| GrpcWebCall::response(b, encoding))
         .map(Body::new);
 
+    if let Some(HttpStatus(status)) = res.extensions_mut().remove() {
+        *res.status_mut() = status;
+    }
+
     res.headers_mut().insert(
         header::CONTENT_TYPE,
         HeaderValue::from_static(encoding.to_content_type()),
@@ -273,6 +305,29 @@ mod tests {
         const NAME: &'static str = "test";
     }
 
+    /// Answers every request with a custom HTTP status.
+    #[derive(Debug, Clone)]
+    struct StatusSvc;
+
+    impl tower_service::Service<Request<Body>> for StatusSvc {
+        type Response = Response<Body>;
+        type Error = String;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Request<Body>) -> Self::Future {
+            Box::pin(async {
+                let mut res = Response::new(Body::default());
+                res.extensions_mut()
+                    .insert(HttpStatus(StatusCode::SERVICE_UNAVAILABLE));
+                Ok(res)
+            })
+        }
+    }
+
     fn enable<S>(service: S) -> tower_http::cors::Cors<GrpcWebService<S>>
     where
         S: Service<http::Request<Body>, Response = http::Response<Body>>,
@@ -350,6 +405,15 @@ mod tests {
             }
         }
 
+        #[tokio::test]
+        async fn http_status_override() {
+            let mut svc = enable(StatusSvc);
+            let res = svc.call(request()).await.unwrap();
+
+            assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert!(res.extensions().get::<HttpStatus>().is_none());
+        }
+
         #[tokio::test]
         async fn grpc_web_content_types() {
             let mut svc = enable(Svc);
@@ -409,6 +473,15 @@ mod tests {
             assert_eq!(res.status(), StatusCode::OK)
         }
 
+        #[tokio::test]
+        async fn http_status_override_is_ignored() {
+            let mut svc = enable(StatusSvc);
+            let res = svc.call(request()).await.unwrap();
+
+            assert_eq!(res.status(), StatusCode::OK);
+            assert!(res.extensions().get::<HttpStatus>().is_none());
+        }
+
         #[tokio::test]
         async fn h1_is_err() {
             let mut svc = enable(Svc);
@@ -0,0 +1,509 @@
+//! gRPC tunneled over WebSocket connections.
+//!
+//! A single WebSocket connection multiplexes any number of calls. Every binary WebSocket message
+//! carries one frame:
+//!
+//! ```text
+//! +------------------------+-----------+---------+
+//! | stream id (u32, big    | kind (u8) | payload |
+//! | endian)                |           |         |
+//! +------------------------+-----------+---------+
+//! ```
+//!
+//! Stream ids are chosen by the client and must not be reused while a call is in progress.
+//! Frames of the following kinds are sent by the client:
+//!
+//! * `HEADERS` (`0`): starts a call. The payload holds the request headers as `name: value`
+//!   lines separated by `\r\n`, including a `:path` line with the method path, e.g.
+//!   `:path: /helloworld.Greeter/SayHello`.
+//! * `DATA` (`1`): a chunk of the gRPC encoded request messages, i.e. length-prefixed messages
+//!   exactly as they would be sent in an HTTP/2 request body.
+//! * `END` (`2`): the client has sent all its messages.
+//! * `CANCEL` (`4`): the client cancels the call.
+//!
+//! And the server answers with:
+//!
+//! * `HEADERS` (`0`): the response headers, encoded like the request headers.
+//! * `DATA` (`1`): a chunk of the gRPC encoded response messages.
+//! * `TRAILERS` (`3`): the response trailers, including `grpc-status`, which complete the call.
+//!   A response that fails before sending messages may carry its `grpc-status` in the `HEADERS`
+//!   frame instead, followed by empty trailers.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fmt,
+    future::{poll_fn, Future},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use http::{
+    header::{self, HeaderName, HeaderValue},
+    Extensions, HeaderMap, Method, Request, Response, StatusCode, Uri, Version,
+};
+use http_body::{Body as _, Frame};
+use hyper_util::rt::TokioIo;
+use pin_project::pin_project;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::mpsc,
+    task::JoinHandle,
+};
+use tokio_tungstenite::{
+    tungstenite::{handshake::derive_accept_key, protocol::Role, Message},
+    WebSocketStream,
+};
+use tonic::{body::Body, Status};
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::debug;
+
+use crate::BoxError;
+
+const HEADERS: u8 = 0;
+const DATA: u8 = 1;
+const END: u8 = 2;
+const TRAILERS: u8 = 3;
+const CANCEL: u8 = 4;
+
+const FRAME_HEADER_LEN: usize = 5;
+const OUTGOING_BUFFER: usize = 64;
+
+/// Layer tunneling gRPC over WebSocket connections accepted on a given path.
+///
+/// WebSocket upgrade requests for `path` are answered with a handshake, after which the calls
+/// multiplexed over the connection are dispatched to the wrapped service. Every other request is
+/// passed through unchanged. See the [module documentation](self) for the framing.
+///
+/// Upgrades require HTTP/1.1, so the server must [accept http1] connections.
+///
+/// [accept http1]: tonic::transport::Server::accept_http1
+#[derive(Debug, Clone)]
+pub struct GrpcWebSocketLayer {
+    path: Arc<str>,
+}
+
+impl GrpcWebSocketLayer {
+    /// Create a new layer accepting WebSocket upgrades on `path`.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into().into(),
+        }
+    }
+}
+
+impl<S> Layer<S> for GrpcWebSocketLayer {
+    type Service = GrpcWebSocketService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcWebSocketService {
+            inner,
+            path: self.path.clone(),
+        }
+    }
+}
+
+/// Service tunneling gRPC over WebSocket connections, see [`GrpcWebSocketLayer`].
+#[derive(Debug, Clone)]
+pub struct GrpcWebSocketService<S> {
+    inner: S,
+    path: Arc<str>,
+}
+
+impl<S> Service<Request<Body>> for GrpcWebSocketService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError> + Send,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = WebSocketResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if req.uri().path() != &*self.path || !is_upgrade(&req) {
+            return WebSocketResponseFuture {
+                case: Case::Inner {
+                    future: self.inner.call(req),
+                },
+            };
+        }
+
+        WebSocketResponseFuture {
+            case: Case::Upgrade {
+                response: Some(upgrade(req, self.inner.clone())),
+            },
+        }
+    }
+}
+
+/// Response future for the [`GrpcWebSocketService`].
+#[pin_project]
+pub struct WebSocketResponseFuture<F> {
+    #[pin]
+    case: Case<F>,
+}
+
+#[pin_project(project = CaseProj)]
+enum Case<F> {
+    Inner {
+        #[pin]
+        future: F,
+    },
+    Upgrade {
+        response: Option<Response<Body>>,
+    },
+}
+
+impl<F, E> Future for WebSocketResponseFuture<F>
+where
+    F: Future<Output = Result<Response<Body>, E>>,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().case.project() {
+            CaseProj::Inner { future } => future.poll(cx),
+            CaseProj::Upgrade { response } => {
+                Poll::Ready(Ok(response.take().expect("polled after completion")))
+            }
+        }
+    }
+}
+
+impl<F> fmt::Debug for WebSocketResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketResponseFuture").finish()
+    }
+}
+
+fn is_upgrade<B>(req: &Request<B>) -> bool {
+    let has_token = |name, token: &str| {
+        req.headers().get_all(name).iter().any(|value| {
+            value.to_str().is_ok_and(|value| {
+                value
+                    .split(',')
+                    .any(|t| t.trim().eq_ignore_ascii_case(token))
+            })
+        })
+    };
+
+    req.method() == Method::GET
+        && req.version() <= Version::HTTP_11
+        && has_token(header::CONNECTION, "upgrade")
+        && has_token(header::UPGRADE, "websocket")
+        && req.headers().contains_key(header::SEC_WEBSOCKET_KEY)
+}
+
+fn upgrade<S>(mut req: Request<Body>, svc: S) -> Response<Body>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError> + Send,
+{
+    if req
+        .headers()
+        .get(header::SEC_WEBSOCKET_VERSION)
+        .is_none_or(|version| version != "13")
+    {
+        let mut response = Response::new(Body::default());
+        *response.status_mut() = StatusCode::UPGRADE_REQUIRED;
+        response.headers_mut().insert(
+            header::SEC_WEBSOCKET_VERSION,
+            HeaderValue::from_static("13"),
+        );
+        return response;
+    }
+
+    let accept = derive_accept_key(req.headers()[header::SEC_WEBSOCKET_KEY].as_bytes());
+    let on_upgrade = hyper::upgrade::on(&mut req);
+    let extensions = req.extensions().clone();
+
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => {
+                let ws =
+                    WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None)
+                        .await;
+                serve(ws, svc, extensions).await;
+            }
+            Err(err) => debug!("websocket upgrade failed: {}", err),
+        }
+    });
+
+    let mut response = Response::new(Body::default());
+    *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+    let headers = response.headers_mut();
+    headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+    headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+    headers.insert(
+        header::SEC_WEBSOCKET_ACCEPT,
+        HeaderValue::from_str(&accept).expect("accept key is valid base64"),
+    );
+    response
+}
+
+struct Call {
+    body: Option<mpsc::UnboundedSender<Bytes>>,
+    task: JoinHandle<()>,
+}
+
+/// Serve the calls multiplexed over `ws` until the connection is closed.
+///
+/// Every call is dispatched to `svc` with a copy of `extensions`.
+pub(crate) async fn serve<IO, S>(ws: WebSocketStream<IO>, svc: S, extensions: Extensions)
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError> + Send,
+{
+    let (mut sink, mut source) = ws.split();
+    let (tx, mut rx) = mpsc::channel::<Bytes>(OUTGOING_BUFFER);
+    let mut calls = HashMap::<u32, Call>::new();
+
+    loop {
+        tokio::select! {
+            message = source.next() => match message {
+                Some(Ok(Message::Binary(frame))) => {
+                    if let Err(err) = handle_frame(frame, &mut calls, &svc, &extensions, &tx) {
+                        debug!("invalid websocket frame: {}", err);
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    debug!("websocket connection error: {}", err);
+                    break;
+                }
+            },
+            Some(frame) = rx.recv() => {
+                if frame[FRAME_HEADER_LEN - 1] == TRAILERS {
+                    calls.remove(&(&frame[..]).get_u32());
+                }
+                if let Err(err) = sink.send(Message::Binary(frame)).await {
+                    debug!("websocket connection error: {}", err);
+                    break;
+                }
+            }
+        }
+    }
+
+    for call in calls.into_values() {
+        call.task.abort();
+    }
+}
+
+fn handle_frame<S>(
+    mut frame: Bytes,
+    calls: &mut HashMap<u32, Call>,
+    svc: &S,
+    extensions: &Extensions,
+    tx: &mpsc::Sender<Bytes>,
+) -> Result<(), BoxError>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError> + Send,
+{
+    if frame.len() < FRAME_HEADER_LEN {
+        return Err("frame too short".into());
+    }
+    let id = frame.get_u32();
+    let kind = frame.get_u8();
+
+    match kind {
+        HEADERS => {
+            if calls.contains_key(&id) {
+                return Err(format!("stream {id} is already in use").into());
+            }
+
+            let (body_tx, body_rx) = mpsc::unbounded_channel();
+            let mut req = build_request(&frame)?.map(|()| Body::new(RequestBody(body_rx)));
+            *req.extensions_mut() = extensions.clone();
+
+            let task = tokio::spawn(call(svc.clone(), req, id, tx.clone()));
+            calls.insert(
+                id,
+                Call {
+                    body: Some(body_tx),
+                    task,
+                },
+            );
+        }
+        DATA => {
+            if let Some(body) = calls.get(&id).and_then(|call| call.body.as_ref()) {
+                // The call may have completed without reading its whole request.
+                let _ = body.send(frame);
+            }
+        }
+        END => {
+            if let Some(call) = calls.get_mut(&id) {
+                call.body = None;
+            }
+        }
+        CANCEL => {
+            if let Some(call) = calls.remove(&id) {
+                call.task.abort();
+            }
+        }
+        kind => return Err(format!("unknown frame kind {kind}").into()),
+    }
+
+    Ok(())
+}
+
+fn build_request(payload: &[u8]) -> Result<Request<()>, BoxError> {
+    let mut path = None;
+    let mut headers = HeaderMap::new();
+
+    for line in std::str::from_utf8(payload)?.split("\r\n") {
+        if line.is_empty() {
+            continue;
+        }
+
+        // Pseudo headers start with a colon, the separator is the next one.
+        let separator = line
+            .get(1..)
+            .and_then(|rest| rest.find(':'))
+            .ok_or("malformed header line")?
+            + 1;
+        let (name, value) = (&line[..separator], line[separator + 1..].trim());
+
+        if name == ":path" {
+            path = Some(value.parse::<Uri>()?);
+        } else {
+            headers.append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+        }
+    }
+
+    let mut req = Request::new(());
+    *req.method_mut() = Method::POST;
+    *req.version_mut() = Version::HTTP_2;
+    *req.uri_mut() = path.ok_or("missing :path")?;
+    *req.headers_mut() = headers;
+    Ok(req)
+}
+
+async fn call<S>(mut svc: S, req: Request<Body>, id: u32, tx: mpsc::Sender<Bytes>)
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Error: Into<BoxError>,
+{
+    let result = match poll_fn(|cx| svc.poll_ready(cx)).await {
+        Ok(()) => svc.call(req).await,
+        Err(err) => Err(err),
+    };
+    let response = result.unwrap_or_else(|err| Status::from_error(err.into()).into_http());
+
+    let (parts, mut body) = response.into_parts();
+    if tx.send(encode(id, HEADERS, &parts.headers)).await.is_err() {
+        return;
+    }
+
+    let trailers = loop {
+        match poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+            Some(Ok(frame)) => match frame.into_data() {
+                Ok(data) => {
+                    let mut frame = frame_header(id, DATA, data.len());
+                    frame.put(data);
+                    if tx.send(frame.freeze()).await.is_err() {
+                        return;
+                    }
+                }
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        break trailers;
+                    }
+                }
+            },
+            Some(Err(err)) => {
+                let mut trailers = HeaderMap::new();
+                let _ = Status::from_error(err.into()).add_header(&mut trailers);
+                break trailers;
+            }
+            None => break HeaderMap::new(),
+        }
+    };
+
+    let _ = tx.send(encode(id, TRAILERS, &trailers)).await;
+}
+
+fn frame_header(id: u32, kind: u8, payload_len: usize) -> BytesMut {
+    let mut frame = BytesMut::with_capacity(FRAME_HEADER_LEN + payload_len);
+    frame.put_u32(id);
+    frame.put_u8(kind);
+    frame
+}
+
+fn encode(id: u32, kind: u8, headers: &HeaderMap) -> Bytes {
+    let mut frame = frame_header(id, kind, 64);
+    for (name, value) in headers {
+        frame.put_slice(name.as_ref());
+        frame.put_slice(b": ");
+        frame.put_slice(value.as_ref());
+        frame.put_slice(b"\r\n");
+    }
+    frame.freeze()
+}
+
+struct RequestBody(mpsc::UnboundedReceiver<Bytes>);
+
+impl http_body::Body for RequestBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.0
+            .poll_recv(cx)
+            .map(|data| data.map(|data| Ok(Frame::data(data))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_from_headers() {
+        let req = build_request(
+            b":path: /test.Test/UnaryCall\r\ncontent-type: application/grpc\r\nx-id: a:b\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(req.method(), Method::POST);
+        assert_eq!(req.uri().path(), "/test.Test/UnaryCall");
+        assert_eq!(req.headers()[header::CONTENT_TYPE], "application/grpc");
+        assert_eq!(req.headers()["x-id"], "a:b");
+    }
+
+    #[test]
+    fn request_without_path() {
+        assert!(build_request(b"content-type: application/grpc\r\n").is_err());
+        assert!(build_request(b"no separator\r\n").is_err());
+        assert!(build_request("é\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn headers_round_trip() {
+        let mut headers = HeaderMap::new();
+        headers.insert("grpc-status", HeaderValue::from_static("0"));
+
+        let mut frame = encode(9, TRAILERS, &headers);
+        assert_eq!(frame.get_u32(), 9);
+        assert_eq!(frame.get_u8(), TRAILERS);
+        assert_eq!(&frame[..], b"grpc-status: 0\r\n");
+    }
+}
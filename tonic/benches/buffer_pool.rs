@@ -0,0 +1,167 @@
+//! Compares the allocations per call of the default buffer pool with a reusing one.
+
+use bencher::{benchmark_group, Bencher};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http_body_util::BodyExt;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+use tonic::{
+    codec::{BufferPool, BufferSettings, DecodeBuf, Decoder, EncodeBody, EncodeBuf, Encoder},
+    Status, Streaming,
+};
+
+/// Counts the allocations of the process.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Keeps the buffers given back for the next calls.
+struct ReusingPool(Mutex<Vec<BytesMut>>);
+
+impl BufferPool for ReusingPool {
+    fn get(&self, capacity: usize) -> BytesMut {
+        let mut buf = self.0.lock().unwrap().pop().unwrap_or_default();
+        buf.reserve(capacity);
+        buf
+    }
+
+    fn put(&self, mut buf: BytesMut) {
+        buf.clear();
+        self.0.lock().unwrap().push(buf);
+    }
+}
+
+static POOL: ReusingPool = ReusingPool(Mutex::new(Vec::new()));
+
+/// Copies messages of `Bytes` in and out of the buffers.
+#[derive(Debug, Clone)]
+struct Codec(BufferSettings);
+
+impl Encoder for Codec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        buf.put_slice(&item);
+        Ok(())
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.0
+    }
+}
+
+impl Decoder for Codec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        Ok(Some(buf.copy_to_bytes(buf.remaining())))
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.0
+    }
+}
+
+/// Encodes and decodes the message of a unary call.
+async fn call(settings: BufferSettings, payload: &Bytes) {
+    let source = tokio_stream::once(Ok(payload.clone()));
+    let body = EncodeBody::new_client(Codec(settings), source, None, None);
+    let mut stream = Streaming::new_request(Codec(settings), body.boxed_unsync(), None, None);
+
+    let message = stream.message().await.unwrap().unwrap();
+    assert_eq!(message.len(), payload.len());
+}
+
+fn settings(pooled: bool) -> BufferSettings {
+    if pooled {
+        BufferSettings::default().with_pool(&POOL)
+    } else {
+        BufferSettings::default()
+    }
+}
+
+macro_rules! bench {
+    ($name:ident, $pooled:expr, $message_size:expr) => {
+        fn $name(b: &mut Bencher) {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("runtime");
+
+            let payload = Bytes::from(vec![97u8; $message_size]);
+            b.bytes = $message_size as u64;
+
+            b.iter(|| rt.block_on(call(settings($pooled), &payload)))
+        }
+    };
+}
+
+bench!(allocate_1k, false, 1024);
+bench!(pool_1k, true, 1024);
+bench!(allocate_64k, false, 64 * 1024);
+bench!(pool_64k, true, 64 * 1024);
+
+benchmark_group!(unary, allocate_1k, pool_1k, allocate_64k, pool_64k);
+
+/// Prints the allocations per call with and without pooling.
+fn allocations() {
+    const CALLS: usize = 1000;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("runtime");
+
+    for message_size in [1024, 64 * 1024] {
+        let payload = Bytes::from(vec![97u8; message_size]);
+        for pooled in [false, true] {
+            // Warm up the pool.
+            rt.block_on(call(settings(pooled), &payload));
+
+            let before = ALLOCATIONS.load(Ordering::Relaxed);
+            for _ in 0..CALLS {
+                rt.block_on(call(settings(pooled), &payload));
+            }
+            let per_call = (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / CALLS as f64;
+
+            println!(
+                "{message_size} bytes, {}: {per_call:.1} allocations per call",
+                if pooled { "pooled" } else { "allocated" },
+            );
+        }
+    }
+}
+
+fn main() {
+    allocations();
+
+    let mut test_opts = bencher::TestOpts::default();
+    if let Some(arg) = std::env::args().skip(1).find(|arg| *arg != "--bench") {
+        test_opts.filter = Some(arg);
+    }
+    bencher::run_tests_console(&test_opts, unary()).unwrap();
+}
@@ -0,0 +1,102 @@
+use bencher::{benchmark_group, benchmark_main, Bencher};
+use bytes::{Buf, BufMut, Bytes};
+use http_body_util::BodyExt;
+use tonic::{
+    codec::{DecodeBuf, Decoder, EncodeBody, EncodeBuf, Encoder},
+    Status, Streaming,
+};
+
+macro_rules! bench {
+    ($name:ident, $encoder:expr, $message_size:expr, $message_count:expr) => {
+        fn $name(b: &mut Bencher) {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("runtime");
+
+            let payload = Bytes::from(vec![97u8; $message_size]);
+            b.bytes = ($message_size * $message_count) as u64;
+
+            b.iter(|| {
+                rt.block_on(async {
+                    let messages = std::iter::repeat(Ok(payload.clone())).take($message_count);
+                    let body = EncodeBody::new_client(
+                        $encoder,
+                        tokio_stream::iter(messages),
+                        None,
+                        Some(usize::MAX),
+                    );
+                    let mut stream = Streaming::new_request(
+                        MockDecoder,
+                        body.boxed_unsync(),
+                        None,
+                        Some(usize::MAX),
+                    );
+
+                    let mut count = 0;
+                    while let Some(msg) = stream.message().await.unwrap() {
+                        assert_eq!($message_size, msg.len());
+                        count += 1;
+                    }
+
+                    assert_eq!(count, $message_count);
+                })
+            })
+        }
+    };
+}
+
+/// Puts messages into the buffer as `Bytes`, which are passed on without being copied.
+#[derive(Debug, Clone)]
+struct BytesEncoder;
+
+impl Encoder for BytesEncoder {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        buf.put(item);
+        Ok(())
+    }
+}
+
+/// Copies messages into the buffer.
+#[derive(Debug, Clone)]
+struct SliceEncoder;
+
+impl Encoder for SliceEncoder {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        buf.put_slice(&item);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockDecoder;
+
+impl Decoder for MockDecoder {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        Ok(Some(buf.copy_to_bytes(buf.remaining())))
+    }
+}
+
+// an echo of large payloads, with and without copying them into the encode buffer
+bench!(echo_bytes_64k, BytesEncoder, 64 * 1024, 16);
+bench!(echo_slice_64k, SliceEncoder, 64 * 1024, 16);
+bench!(echo_bytes_1m, BytesEncoder, 1024 * 1024, 4);
+bench!(echo_slice_1m, SliceEncoder, 1024 * 1024, 4);
+
+benchmark_group!(
+    large_payload,
+    echo_bytes_64k,
+    echo_slice_64k,
+    echo_bytes_1m,
+    echo_slice_1m
+);
+
+benchmark_main!(large_payload);
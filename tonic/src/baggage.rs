@@ -0,0 +1,285 @@
+use http::{HeaderMap, HeaderValue};
+use std::fmt::Write as _;
+
+/// The header carrying baggage, see the [W3C Baggage] specification.
+///
+/// [W3C Baggage]: https://www.w3.org/TR/baggage/
+pub(crate) const BAGGAGE_HEADER: &str = "baggage";
+
+/// Request-scoped key-value pairs propagated along a call chain, from the `baggage` header.
+///
+/// Baggage carries context like a tenant or a correlation ID from service to service, next to
+/// the `traceparent` of the trace the calls belong to. The server inserts it into the extensions
+/// of every request that has the header, and [`Request::set_baggage`] sets it on an outbound
+/// request, so a handler forwards it to the services it calls by passing it on:
+///
+/// ```
+/// # use tonic::{Baggage, Request};
+/// # fn handler<T>(request: &Request<T>) {
+/// let baggage = request
+///     .extensions()
+///     .get::<Baggage>()
+///     .cloned()
+///     .unwrap_or_default();
+/// println!("called for tenant {:?}", baggage.get("tenant"));
+///
+/// let mut outbound = Request::new(());
+/// outbound.set_baggage(&baggage);
+/// # }
+/// ```
+///
+/// The format follows the [W3C Baggage] specification, including its limits: at most
+/// [`Baggage::MAX_ENTRIES`] entries and [`Baggage::MAX_SIZE`] bytes in total. Entries beyond
+/// those limits are dropped, both when receiving and when sending baggage, as are malformed
+/// ones. Properties of received entries are kept and forwarded as they are.
+///
+/// [`Request::set_baggage`]: crate::Request::set_baggage
+/// [W3C Baggage]: https://www.w3.org/TR/baggage/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baggage {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    key: String,
+    value: String,
+    /// The properties following the value, e.g. `;ttl=30`, kept as they were received.
+    properties: String,
+}
+
+impl Baggage {
+    /// The maximum number of entries sent or received.
+    pub const MAX_ENTRIES: usize = 64;
+
+    /// The maximum size of the `baggage` header sent or received, in bytes.
+    pub const MAX_SIZE: usize = 8192;
+
+    /// Create empty baggage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value of `key`, if any.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.value.as_str())
+    }
+
+    /// Set `key` to `value`, replacing its previous value.
+    ///
+    /// Keys must be tokens as defined by HTTP, e.g. `tenant` or `x-correlation-id`. Entries with
+    /// other keys are not sent. Values may contain any characters, they are percent-encoded.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let value = value.into();
+        match self.entries.iter_mut().find(|entry| entry.key == key) {
+            Some(entry) => {
+                entry.value = value;
+                entry.properties.clear();
+            }
+            None => self.entries.push(Entry {
+                key,
+                value,
+                properties: String::new(),
+            }),
+        }
+    }
+
+    /// Remove `key`, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let index = self.entries.iter().position(|entry| entry.key == key)?;
+        Some(self.entries.remove(index).value)
+    }
+
+    /// The entries as key-value pairs, in the order they were received or inserted.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.key.as_str(), entry.value.as_str()))
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Parse the `baggage` headers of `headers`, if any.
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let mut baggage = Self::default();
+        let mut size = 0;
+        let members = headers
+            .get_all(BAGGAGE_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for member in members {
+            let member = member.trim_matches(|c| c == ' ' || c == '\t');
+            if member.is_empty() {
+                continue;
+            }
+            size += member.len() + usize::from(size > 0);
+            if size > Self::MAX_SIZE || baggage.entries.len() == Self::MAX_ENTRIES {
+                break;
+            }
+            if let Some(entry) = Entry::parse(member) {
+                baggage.entries.retain(|other| other.key != entry.key);
+                baggage.entries.push(entry);
+            }
+        }
+
+        (!baggage.is_empty()).then_some(baggage)
+    }
+
+    /// Encode the entries as a `baggage` header, if there are any within the limits.
+    pub(crate) fn to_header(&self) -> Option<HeaderValue> {
+        let mut header = String::new();
+        let mut entries = 0;
+        for entry in &self.entries {
+            if entries == Self::MAX_ENTRIES {
+                break;
+            }
+            let Some(member) = entry.encode() else {
+                continue;
+            };
+            let separator = usize::from(!header.is_empty());
+            if header.len() + separator + member.len() > Self::MAX_SIZE {
+                continue;
+            }
+            if separator > 0 {
+                header.push(',');
+            }
+            header.push_str(&member);
+            entries += 1;
+        }
+
+        // The members only contain visible ASCII characters.
+        (!header.is_empty()).then(|| HeaderValue::from_str(&header).unwrap())
+    }
+}
+
+impl Entry {
+    fn parse(member: &str) -> Option<Self> {
+        let (pair, properties) = match member.find(';') {
+            Some(index) => member.split_at(index),
+            None => (member, ""),
+        };
+        let (key, value) = pair.split_once('=')?;
+        let key = key.trim_matches(|c| c == ' ' || c == '\t');
+        let value = value.trim_matches(|c| c == ' ' || c == '\t');
+        if !is_token(key) || !value.bytes().all(is_baggage_octet) {
+            return None;
+        }
+
+        Some(Self {
+            key: key.to_owned(),
+            value: percent_decode(value),
+            properties: properties.to_owned(),
+        })
+    }
+
+    fn encode(&self) -> Option<String> {
+        if !is_token(&self.key) {
+            return None;
+        }
+
+        let mut member = format!("{}=", self.key);
+        for byte in self.value.bytes() {
+            if is_baggage_octet(byte) && byte != b'%' {
+                member.push(char::from(byte));
+            } else {
+                write!(member, "%{byte:02X}").unwrap();
+            }
+        }
+        member.push_str(&self.properties);
+        Some(member)
+    }
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte))
+}
+
+/// The characters allowed in values without percent-encoding: visible ASCII characters except
+/// `"`, `,`, `;` and `\`.
+fn is_baggage_octet(byte: u8) -> bool {
+    matches!(byte, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(values: &[&str]) -> Option<Baggage> {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(BAGGAGE_HEADER, HeaderValue::from_str(value).unwrap());
+        }
+        Baggage::from_headers(&headers)
+    }
+
+    #[test]
+    fn parses_and_encodes_entries() {
+        let baggage = parse(&[
+            "tenant=acme, user=J%C3%BCrgen%20K;ttl=30",
+            "malformed,tenant=other,region=eu",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            baggage.iter().collect::<Vec<_>>(),
+            [("user", "Jürgen K"), ("tenant", "other"), ("region", "eu")]
+        );
+        assert_eq!(
+            baggage.to_header().unwrap(),
+            "user=J%C3%BCrgen%20K;ttl=30,tenant=other,region=eu"
+        );
+        assert!(parse(&["", " , "]).is_none());
+    }
+
+    #[test]
+    fn drops_entries_beyond_the_limits() {
+        let members: Vec<_> = (0..100).map(|i| format!("key{i}=value")).collect();
+        let baggage = parse(&[&members.join(",")]).unwrap();
+        assert_eq!(baggage.len(), Baggage::MAX_ENTRIES);
+
+        let mut baggage = Baggage::new();
+        baggage.insert("large", "x".repeat(Baggage::MAX_SIZE));
+        baggage.insert("small", "value");
+        baggage.insert("invalid key", "value");
+        assert_eq!(baggage.to_header().unwrap(), "small=value");
+    }
+}
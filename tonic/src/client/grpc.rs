@@ -1,6 +1,6 @@
 use crate::codec::compression::{CompressionEncoding, EnabledCompressionEncodings};
 use crate::codec::EncodeBody;
-use crate::metadata::GRPC_CONTENT_TYPE;
+use crate::metadata::grpc_content_type;
 use crate::{
     body::Body,
     client::GrpcService,
@@ -305,7 +305,8 @@ impl<T> Grpc<T> {
             })
             .map(Body::new);
 
-        let request = self.config.prepare_request(request, path);
+        let content_type = grpc_content_type(codec.content_subtype());
+        let request = self.config.prepare_request(request, path, content_type);
 
         let response = self
             .inner
@@ -369,7 +370,12 @@ impl<T> Grpc<T> {
 }
 
 impl GrpcConfig {
-    fn prepare_request(&self, request: Request<Body>, path: PathAndQuery) -> http::Request<Body> {
+    fn prepare_request(
+        &self,
+        request: Request<Body>,
+        path: PathAndQuery,
+        content_type: HeaderValue,
+    ) -> http::Request<Body> {
         let mut parts = self.origin.clone().into_parts();
 
         match &parts.path_and_query {
@@ -400,9 +406,7 @@ impl GrpcConfig {
             .insert(TE, HeaderValue::from_static("trailers"));
 
         // Set the content type
-        request
-            .headers_mut()
-            .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+        request.headers_mut().insert(CONTENT_TYPE, content_type);
 
         #[cfg(any(feature = "gzip", feature = "zstd"))]
         if let Some(encoding) = self.send_compression_encodings {
@@ -8,10 +8,19 @@ pub struct DecodeBuf<'a> {
     len: usize,
 }
 
+/// Buffers of at least this size put into an [`EncodeBuf`] are passed on without being copied,
+/// if the buffer allows it.
+pub(crate) const ZERO_COPY_THRESHOLD: usize = 8 * 1024;
+
 /// A specialized buffer to encode gRPC messages into.
+///
+/// Large [`Bytes`] put into it, like the `bytes` fields of prost messages generated with
+/// `Builder::bytes`, are sent as they are instead of being copied into the buffer.
 #[derive(Debug)]
 pub struct EncodeBuf<'a> {
     buf: &'a mut BytesMut,
+    /// The buffers to be sent in between the bytes of `buf`, along with their position in it.
+    splices: Option<&'a mut Vec<(usize, Bytes)>>,
 }
 
 impl<'a> DecodeBuf<'a> {
@@ -48,13 +57,25 @@ impl Buf for DecodeBuf<'_> {
     fn copy_to_bytes(&mut self, len: usize) -> Bytes {
         assert!(len <= self.len);
         self.len -= len;
+        // Splits the bytes off the buffer, so the message shares its memory.
         self.buf.copy_to_bytes(len)
     }
 }
 
 impl<'a> EncodeBuf<'a> {
     pub(crate) fn new(buf: &'a mut BytesMut) -> Self {
-        EncodeBuf { buf }
+        EncodeBuf { buf, splices: None }
+    }
+
+    /// Create a buffer recording large buffers in `splices` instead of copying them.
+    pub(crate) fn with_splices(
+        buf: &'a mut BytesMut,
+        splices: &'a mut Vec<(usize, Bytes)>,
+    ) -> Self {
+        EncodeBuf {
+            buf,
+            splices: Some(splices),
+        }
     }
 }
 
@@ -73,6 +94,7 @@ impl EncodeBuf<'_> {
 unsafe impl BufMut for EncodeBuf<'_> {
     #[inline]
     fn remaining_mut(&self) -> usize {
+        // The spliced buffers don't take up any of the buffer's capacity.
         self.buf.remaining_mut()
     }
 
@@ -87,11 +109,18 @@ unsafe impl BufMut for EncodeBuf<'_> {
     }
 
     #[inline]
-    fn put<T: Buf>(&mut self, src: T)
+    fn put<T: Buf>(&mut self, mut src: T)
     where
         Self: Sized,
     {
-        self.buf.put(src)
+        match &mut self.splices {
+            // `copy_to_bytes` doesn't copy `Bytes`, and copies other buffers only once.
+            Some(splices) if src.remaining() >= ZERO_COPY_THRESHOLD => {
+                let bytes = src.copy_to_bytes(src.remaining());
+                splices.push((self.buf.len(), bytes));
+            }
+            _ => self.buf.put(src),
+        }
     }
 
     #[inline]
@@ -143,4 +172,32 @@ mod tests {
         buf.put_u8(b'a');
         assert_eq!(buf.remaining_mut(), initial - 20 - 1);
     }
+
+    #[test]
+    fn decode_buf_shares_memory() {
+        let mut payload = BytesMut::from(&[1u8; 100][..]);
+        let start = payload.as_ptr();
+        let mut buf = DecodeBuf::new(&mut payload, 50);
+
+        buf.advance(10);
+        let bytes = buf.copy_to_bytes(40);
+        assert_eq!(bytes.as_ptr(), start.wrapping_add(10));
+    }
+
+    #[test]
+    fn encode_buf_splices_large_bytes() {
+        let mut bytes = BytesMut::new();
+        let mut splices = Vec::new();
+        let mut buf = EncodeBuf::with_splices(&mut bytes, &mut splices);
+
+        let large = Bytes::from(vec![1u8; ZERO_COPY_THRESHOLD]);
+        buf.put_u8(b'a');
+        buf.put(large.clone());
+        buf.put(Bytes::from_static(b"small"));
+
+        assert_eq!(&bytes[..], b"asmall");
+        assert_eq!(splices.len(), 1);
+        assert_eq!(splices[0].0, 1);
+        assert_eq!(splices[0].1.as_ptr(), large.as_ptr());
+    }
 }
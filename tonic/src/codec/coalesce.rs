@@ -0,0 +1,182 @@
+use pin_project::pin_project;
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep, Instant, Sleep};
+use tokio_stream::{adapters::Fuse, Stream, StreamExt};
+
+/// Holds back the messages of a stream to send them in batches.
+///
+/// Tonic encodes the messages that are ready to send into one buffer, written at once, but
+/// streams that produce many small messages one at a time, e.g. from a channel fed by another
+/// task, end up with one write per message, where the framing overhead and syscalls dominate.
+/// A `Coalesce` buffers up to `max_messages` messages, or the messages produced within
+/// `max_delay` of the first one, and then releases them together so they share a write. The
+/// write is still bounded by the [yield threshold] of the encoder, and split into HTTP/2 frames
+/// of the negotiated maximum frame size.
+///
+/// This trades latency for throughput: a message may be held back for up to `max_delay`, so it
+/// is opt-in.
+///
+/// # Example
+///
+/// ```rust
+/// # use tonic::{codec::Coalesce, codegen::BoxStream, Response, Status};
+/// # use std::time::Duration;
+/// # type Tick = Vec<u8>;
+/// fn ticks(
+///     ticks: impl tokio_stream::Stream<Item = Result<Tick, Status>> + Send + 'static,
+/// ) -> Response<BoxStream<Tick>> {
+///     let batched = Coalesce::new(ticks, 64, Duration::from_millis(5));
+///     Response::new(Box::pin(batched))
+/// }
+/// ```
+///
+/// [yield threshold]: super::BufferSettings
+#[pin_project]
+pub struct Coalesce<S: Stream> {
+    #[pin]
+    inner: Fuse<S>,
+    max_messages: usize,
+    max_delay: Duration,
+    batch: VecDeque<S::Item>,
+    flushing: bool,
+    deadline: Pin<Box<Sleep>>,
+}
+
+impl<S: Stream> Coalesce<S> {
+    /// Batch the messages of `stream`, releasing them once `max_messages` are buffered or
+    /// `max_delay` passed since the first one was.
+    pub fn new(stream: S, max_messages: usize, max_delay: Duration) -> Self {
+        Self {
+            inner: stream.fuse(),
+            max_messages: max_messages.max(1),
+            max_delay,
+            batch: VecDeque::new(),
+            flushing: false,
+            deadline: Box::pin(sleep(max_delay)),
+        }
+    }
+}
+
+impl<S: Stream> Stream for Coalesce<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.flushing {
+                if let Some(item) = this.batch.pop_front() {
+                    return Poll::Ready(Some(item));
+                }
+                *this.flushing = false;
+            }
+
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if this.batch.is_empty() {
+                        this.deadline
+                            .as_mut()
+                            .reset(Instant::now() + *this.max_delay);
+                    }
+                    this.batch.push_back(item);
+                    *this.flushing = this.batch.len() >= *this.max_messages;
+                }
+                Poll::Ready(None) if this.batch.is_empty() => return Poll::Ready(None),
+                Poll::Ready(None) => *this.flushing = true,
+                Poll::Pending if this.batch.is_empty() => return Poll::Pending,
+                Poll::Pending => match this.deadline.as_mut().poll(cx) {
+                    Poll::Ready(()) => *this.flushing = true,
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+    }
+}
+
+impl<S: Stream> fmt::Debug for Coalesce<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Coalesce")
+            .field("max_messages", &self.max_messages)
+            .field("max_delay", &self.max_delay)
+            .field("buffered", &self.batch.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codec::{EncodeBody, EncodeBuf, Encoder},
+        Status,
+    };
+    use bytes::BufMut;
+    use http_body_util::BodyExt;
+
+    #[derive(Debug, Clone)]
+    struct BytesEncoder;
+
+    impl Encoder for BytesEncoder {
+        type Item = Vec<u8>;
+        type Error = Status;
+
+        fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+            buf.put_slice(&item);
+            Ok(())
+        }
+    }
+
+    /// Small messages that each become ready after the task yielded once.
+    fn trickle(messages: u8) -> impl Stream<Item = Result<Vec<u8>, Status>> {
+        tokio_stream::iter(0..messages).then(|i| async move {
+            tokio::task::yield_now().await;
+            Ok(vec![i; 8])
+        })
+    }
+
+    /// Counts the data frames of the encoded `source`, i.e. its writes.
+    async fn writes<S>(source: S) -> (usize, Vec<u8>)
+    where
+        S: Stream<Item = Result<Vec<u8>, Status>>,
+    {
+        let mut body = std::pin::pin!(EncodeBody::new_client(BytesEncoder, source, None, None));
+        let (mut writes, mut bytes) = (0, Vec::new());
+        while let Some(frame) = body.frame().await {
+            if let Ok(data) = frame.unwrap().into_data() {
+                writes += 1;
+                bytes.extend_from_slice(&data);
+            }
+        }
+        (writes, bytes)
+    }
+
+    #[tokio::test]
+    async fn batches_small_messages_into_fewer_writes() {
+        let (unbatched, expected) = writes(trickle(100)).await;
+        assert_eq!(unbatched, 100);
+
+        let (batched, bytes) =
+            writes(Coalesce::new(trickle(100), 32, Duration::from_secs(1))).await;
+        // Three full batches and the rest once the stream ends.
+        assert_eq!(batched, 4);
+        assert_eq!(bytes, expected);
+    }
+
+    #[tokio::test]
+    async fn releases_messages_after_the_delay() {
+        let source = tokio_stream::iter([1, 2]).chain(tokio_stream::pending());
+        let mut stream = std::pin::pin!(Coalesce::new(source, 32, Duration::from_millis(20)));
+
+        let started = Instant::now();
+        assert_eq!(stream.next().await, Some(1));
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(stream.next().await, Some(2));
+    }
+}
@@ -3,6 +3,8 @@ use bytes::{Buf, BufMut, BytesMut};
 #[cfg(feature = "gzip")]
 use flate2::read::{GzDecoder, GzEncoder};
 use std::fmt;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use std::io::Read as _;
 #[cfg(feature = "zstd")]
 use zstd::stream::read::{Decoder, Encoder};
 
@@ -187,6 +189,36 @@ impl fmt::Display for CompressionEncoding {
     }
 }
 
+/// The compression encoding of a request's messages.
+///
+/// A server inserts this into the extensions of every request it decodes, so handlers can tell
+/// whether the request arrived compressed, e.g. to log it or to compress the response alike.
+/// Requests without a `grpc-encoding` header and requests declaring `identity` are both
+/// uncompressed, so both report [`None`].
+///
+/// # Example
+///
+/// ```rust
+/// # use tonic::{codec::RequestEncoding, Request};
+/// fn is_compressed<T>(request: &Request<T>) -> bool {
+///     request
+///         .extensions()
+///         .get::<RequestEncoding>()
+///         .and_then(RequestEncoding::encoding)
+///         .is_some()
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestEncoding(pub(crate) Option<CompressionEncoding>);
+
+impl RequestEncoding {
+    /// The encoding the request's messages are compressed with, or [`None`] if they are not
+    /// compressed.
+    pub fn encoding(&self) -> Option<CompressionEncoding> {
+        self.0
+    }
+}
+
 fn split_by_comma(s: &str) -> impl Iterator<Item = &str> {
     s.split(',').map(|s| s.trim())
 }
@@ -235,31 +267,38 @@ pub(crate) fn compress(
 
 /// Decompress `len` bytes from `compressed_buf` into `out_buf`.
 #[allow(unused_variables, unreachable_code)]
+/// Decompresses the `len` bytes at the start of `compressed_buf` into `out_buf`.
+///
+/// At most `max_len + 1` bytes are decompressed, so a message that decompresses to more than
+/// `max_len` bytes is detected by the length of `out_buf` without inflating it completely.
 pub(crate) fn decompress(
     settings: CompressionSettings,
     compressed_buf: &mut BytesMut,
     out_buf: &mut BytesMut,
     len: usize,
+    max_len: usize,
 ) -> Result<(), std::io::Error> {
     let buffer_growth_interval = settings.buffer_growth_interval;
-    let estimate_decompressed_len = len * 2;
+    let estimate_decompressed_len = (len * 2).min(max_len.saturating_add(1));
     let capacity =
         ((estimate_decompressed_len / buffer_growth_interval) + 1) * buffer_growth_interval;
     out_buf.reserve(capacity);
 
     #[cfg(any(feature = "gzip", feature = "zstd"))]
     let mut out_writer = out_buf.writer();
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    let limit = (max_len as u64).saturating_add(1);
 
     match settings.encoding {
         #[cfg(feature = "gzip")]
         CompressionEncoding::Gzip => {
-            let mut gzip_decoder = GzDecoder::new(&compressed_buf[0..len]);
-            std::io::copy(&mut gzip_decoder, &mut out_writer)?;
+            let gzip_decoder = GzDecoder::new(&compressed_buf[0..len]);
+            std::io::copy(&mut gzip_decoder.take(limit), &mut out_writer)?;
         }
         #[cfg(feature = "zstd")]
         CompressionEncoding::Zstd => {
-            let mut zstd_decoder = Decoder::new(&compressed_buf[0..len])?;
-            std::io::copy(&mut zstd_decoder, &mut out_writer)?;
+            let zstd_decoder = Decoder::new(&compressed_buf[0..len])?;
+            std::io::copy(&mut zstd_decoder.take(limit), &mut out_writer)?;
         }
     }
 
@@ -357,4 +396,27 @@ mod tests {
             HeaderValue::from_static("zstd,gzip,identity"),
         );
     }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn decompress_stops_after_the_limit() {
+        let msg = vec![0u8; 1024 * 1024];
+        let mut compressed = Vec::new();
+        Encoder::new(&msg[..], 0)
+            .unwrap()
+            .read_to_end(&mut compressed)
+            .unwrap();
+
+        let settings = CompressionSettings {
+            encoding: CompressionEncoding::Zstd,
+            buffer_growth_interval: 8 * 1024,
+        };
+        let len = compressed.len();
+        let mut compressed = BytesMut::from(&compressed[..]);
+        let mut out = BytesMut::new();
+        decompress(settings, &mut compressed, &mut out, len, 4096).unwrap();
+
+        assert_eq!(out.len(), 4097);
+        assert!(compressed.is_empty());
+    }
 }
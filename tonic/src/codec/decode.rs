@@ -1,6 +1,6 @@
 use super::compression::{decompress, CompressionEncoding, CompressionSettings};
 use super::{BufferSettings, DecodeBuf, Decoder, DEFAULT_MAX_RECV_MESSAGE_SIZE, HEADER_SIZE};
-use crate::{body::Body, metadata::MetadataMap, Code, Status};
+use crate::{body::Body, metadata::MetadataMap, server::timing::ServerTiming, Code, Status};
 use bytes::{Buf, BufMut, BytesMut};
 use http::{HeaderMap, StatusCode};
 use http_body::Body as HttpBody;
@@ -10,6 +10,7 @@ use std::{
     pin::Pin,
     task::ready,
     task::{Context, Poll},
+    time::Instant,
 };
 use tokio_stream::Stream;
 use tracing::{debug, trace};
@@ -20,9 +21,14 @@ use tracing::{debug, trace};
 /// to fetch the message stream and trailing metadata
 pub struct Streaming<T> {
     decoder: Box<dyn Decoder<Item = T, Error = Status> + Send + 'static>,
+    inspector: Option<Inspector<T>>,
+    timing: Option<ServerTiming>,
     inner: StreamingInner,
 }
 
+/// Checks a decoded message, rejecting it with an error.
+pub(crate) type Inspector<T> = Box<dyn Fn(&T) -> Result<(), Status> + Send + 'static>;
+
 struct StreamingInner {
     body: Body,
     state: State,
@@ -32,6 +38,9 @@ struct StreamingInner {
     decompress_buf: BytesMut,
     encoding: Option<CompressionEncoding>,
     max_message_size: Option<usize>,
+    max_message_count: Option<usize>,
+    sequence: u64,
+    buffer_settings: BufferSettings,
 }
 
 impl<T> Unpin for Streaming<T> {}
@@ -121,9 +130,12 @@ impl<T> Streaming<T> {
         B::Error: Into<crate::BoxError>,
         D: Decoder<Item = T, Error = Status> + Send + 'static,
     {
-        let buffer_size = decoder.buffer_settings().buffer_size;
+        let buffer_settings = decoder.buffer_settings();
+        let buffer_size = buffer_settings.buffer_size;
         Self {
             decoder: Box::new(decoder),
+            inspector: None,
+            timing: None,
             inner: StreamingInner {
                 body: Body::new(
                     body.map_frame(|frame| {
@@ -133,14 +145,49 @@ impl<T> Streaming<T> {
                 ),
                 state: State::ReadHeader,
                 direction,
-                buf: BytesMut::with_capacity(buffer_size),
+                buf: buffer_settings.pool.get(buffer_size),
                 trailers: None,
-                decompress_buf: BytesMut::new(),
+                decompress_buf: if encoding.is_some() {
+                    buffer_settings.pool.get(buffer_size)
+                } else {
+                    BytesMut::new()
+                },
                 encoding,
                 max_message_size,
+                max_message_count: None,
+                sequence: 0,
+                buffer_settings,
             },
         }
     }
+
+    /// Fail the stream once it carries more than `limit` messages.
+    pub(crate) fn with_max_message_count(mut self, limit: Option<usize>) -> Self {
+        self.inner.max_message_count = limit;
+        self
+    }
+
+    /// Run `inspector` on every decoded message, failing the stream when it rejects one.
+    pub(crate) fn with_inspector(mut self, inspector: Option<Inspector<T>>) -> Self {
+        self.inspector = inspector;
+        self
+    }
+
+    /// Add the time spent decoding messages to the decode phase of `timing`.
+    pub(crate) fn with_timing(mut self, timing: Option<ServerTiming>) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl Drop for StreamingInner {
+    fn drop(&mut self) {
+        let pool = self.buffer_settings.pool;
+        pool.put(std::mem::take(&mut self.buf));
+        if self.encoding.is_some() {
+            pool.put(std::mem::take(&mut self.decompress_buf));
+        }
+    }
 }
 
 impl StreamingInner {
@@ -153,6 +200,17 @@ impl StreamingInner {
                 return Ok(None);
             }
 
+            if let Some(limit) = self.max_message_count {
+                if self.sequence >= limit as u64 {
+                    // Yield the error once, then end the stream.
+                    self.state = State::Error(None);
+                    return Err(Status::resource_exhausted(format!(
+                        "Error, too many messages: the limit is {} messages",
+                        limit
+                    )));
+                }
+            }
+
             let compression_encoding = match self.buf.get_u8() {
                 0 => None,
                 1 => {
@@ -213,6 +271,9 @@ impl StreamingInner {
             let decode_buf = if let Some(encoding) = compression {
                 self.decompress_buf.clear();
 
+                let limit = self
+                    .max_message_size
+                    .unwrap_or(DEFAULT_MAX_RECV_MESSAGE_SIZE);
                 if let Err(err) = decompress(
                     CompressionSettings {
                         encoding,
@@ -221,6 +282,7 @@ impl StreamingInner {
                     &mut self.buf,
                     &mut self.decompress_buf,
                     len,
+                    limit,
                 ) {
                     let message = if let Direction::Response(status) = self.direction {
                         format!(
@@ -233,6 +295,16 @@ impl StreamingInner {
                     return Err(Status::internal(message));
                 }
                 let decompressed_len = self.decompress_buf.len();
+                if decompressed_len > limit {
+                    // Only the first bytes beyond the limit were decompressed. Yield the error
+                    // once, then end the stream.
+                    self.decompress_buf.clear();
+                    self.state = State::Error(None);
+                    return Err(Status::resource_exhausted(format!(
+                        "Error, decompressed message length too large: the limit is {} bytes",
+                        limit
+                    )));
+                }
                 DecodeBuf::new(&mut self.decompress_buf, decompressed_len)
             } else {
                 DecodeBuf::new(&mut self.buf, len)
@@ -373,7 +445,23 @@ impl<T> Streaming<T> {
         match self.inner.decode_chunk(self.decoder.buffer_settings())? {
             Some(mut decode_buf) => match self.decoder.decode(&mut decode_buf)? {
                 Some(msg) => {
+                    if let State::ReadBody { len, compression } = self.inner.state {
+                        debug!(
+                            sequence = self.inner.sequence,
+                            encoded_size = len,
+                            compressed = compression.is_some(),
+                            "decoded message"
+                        );
+                    }
+                    self.inner.sequence += 1;
                     self.inner.state = State::ReadHeader;
+                    if let Some(inspector) = &self.inspector {
+                        if let Err(status) = inspector(&msg) {
+                            // Yield the error once, then end the stream.
+                            self.inner.state = State::Error(None);
+                            return Err(status);
+                        }
+                    }
                     Ok(Some(msg))
                 }
                 None => Ok(None),
@@ -395,7 +483,12 @@ impl<T> Stream for Streaming<T> {
                 return Poll::Ready(status.take().map(Err));
             }
 
-            if let Some(item) = self.decode_chunk()? {
+            let started_at = self.timing.is_some().then(Instant::now);
+            let item = self.decode_chunk();
+            if let (Some(timing), Some(started_at)) = (&self.timing, started_at) {
+                timing.add_decode(started_at.elapsed());
+            }
+            if let Some(item) = item? {
                 return Poll::Ready(Some(Ok(item)));
             }
 
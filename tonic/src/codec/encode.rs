@@ -2,33 +2,71 @@ use super::compression::{
     compress, CompressionEncoding, CompressionSettings, SingleMessageCompressionOverride,
 };
 use super::{BufferSettings, EncodeBuf, Encoder, DEFAULT_MAX_SEND_MESSAGE_SIZE, HEADER_SIZE};
-use crate::Status;
+use crate::{
+    server::timing::{ServerTiming, SERVER_TIMING_TRAILER},
+    MessageCompression, Status, TrailingMetadata,
+};
 use bytes::{BufMut, Bytes, BytesMut};
 use http::HeaderMap;
 use http_body::{Body, Frame};
-use pin_project::pin_project;
+use pin_project::{pin_project, pinned_drop};
 use std::{
+    collections::VecDeque,
     pin::Pin,
     task::{ready, Context, Poll},
+    time::Instant,
 };
 use tokio_stream::{adapters::Fuse, Stream, StreamExt};
+use tracing::debug;
+
+/// When the encoded messages of a response stream are handed to the connection to be written.
+///
+/// Set through [`Server::streaming_flush_mode`].
+///
+/// [`Server::streaming_flush_mode`]: crate::transport::Server::streaming_flush_mode
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StreamingFlushMode {
+    /// Encode all the messages the stream has ready into one buffer, written once the stream
+    /// has no message ready or the buffer reaches the yield threshold of the [`BufferSettings`].
+    ///
+    /// This is the default. Messages are never held back waiting for more, but a stream
+    /// producing messages faster than they are written coalesces them into fewer, larger writes.
+    #[default]
+    Buffered,
+    /// Hand every message to the connection on its own, as soon as it is encoded.
+    ///
+    /// This gets each message on its way before the next one is produced, at the cost of one
+    /// write, and HTTP/2 `DATA` frame, per message.
+    Immediate,
+}
 
 /// Combinator for efficient encoding of messages into reasonably sized buffers.
 /// EncodedBytes encodes ready messages from its delegate stream into a BytesMut,
 /// splitting off and yielding a buffer when either:
 ///  * The delegate stream polls as not ready, or
 ///  * The encoded buffer surpasses YIELD_THRESHOLD.
-#[pin_project(project = EncodedBytesProj)]
+///
+/// Large buffers spliced into uncompressed messages by the encoder are yielded as they are, in
+/// between the bytes encoded before and after them.
+#[pin_project(PinnedDrop, project = EncodedBytesProj)]
 #[derive(Debug)]
 struct EncodedBytes<T, U> {
     #[pin]
     source: Fuse<U>,
     encoder: T,
     compression_encoding: Option<CompressionEncoding>,
+    message_compression: Option<MessageCompression>,
     max_message_size: Option<usize>,
     buf: BytesMut,
     uncompression_buf: BytesMut,
+    splices: Vec<(usize, Bytes)>,
+    pending: VecDeque<Bytes>,
     error: Option<Status>,
+    sequence: u64,
+    buffer_settings: BufferSettings,
+    flush_mode: StreamingFlushMode,
+    max_message_count: Option<usize>,
+    timing: Option<ServerTiming>,
 }
 
 impl<T: Encoder, U: Stream> EncodedBytes<T, U> {
@@ -40,7 +78,7 @@ impl<T: Encoder, U: Stream> EncodedBytes<T, U> {
         max_message_size: Option<usize>,
     ) -> Self {
         let buffer_settings = encoder.buffer_settings();
-        let buf = BytesMut::with_capacity(buffer_settings.buffer_size);
+        let buf = buffer_settings.pool.get(buffer_settings.buffer_size);
 
         let compression_encoding =
             if compression_override == SingleMessageCompressionOverride::Disable {
@@ -50,7 +88,7 @@ impl<T: Encoder, U: Stream> EncodedBytes<T, U> {
             };
 
         let uncompression_buf = if compression_encoding.is_some() {
-            BytesMut::with_capacity(buffer_settings.buffer_size)
+            buffer_settings.pool.get(buffer_settings.buffer_size)
         } else {
             BytesMut::new()
         };
@@ -59,10 +97,30 @@ impl<T: Encoder, U: Stream> EncodedBytes<T, U> {
             source: source.fuse(),
             encoder,
             compression_encoding,
+            message_compression: None,
             max_message_size,
             buf,
             uncompression_buf,
+            splices: Vec::new(),
+            pending: VecDeque::new(),
             error: None,
+            sequence: 0,
+            buffer_settings,
+            flush_mode: StreamingFlushMode::default(),
+            max_message_count: None,
+            timing: None,
+        }
+    }
+}
+
+#[pinned_drop]
+impl<T, U> PinnedDrop for EncodedBytes<T, U> {
+    fn drop(self: Pin<&mut Self>) {
+        let this = self.project();
+        let pool = this.buffer_settings.pool;
+        pool.put(std::mem::take(this.buf));
+        if this.compression_encoding.is_some() {
+            pool.put(std::mem::take(this.uncompression_buf));
         }
     }
 }
@@ -79,19 +137,40 @@ where
             mut source,
             encoder,
             compression_encoding,
+            message_compression,
             max_message_size,
             buf,
             uncompression_buf,
+            splices,
+            pending,
             error,
+            sequence,
+            buffer_settings,
+            flush_mode,
+            max_message_count,
+            timing,
         } = self.project();
-        let buffer_settings = encoder.buffer_settings();
-
-        if let Some(status) = error.take() {
-            return Poll::Ready(Some(Err(status)));
-        }
+        let buffer_settings = *buffer_settings;
 
         loop {
-            match source.as_mut().poll_next(cx) {
+            if let Some(bytes) = pending.pop_front() {
+                return Poll::Ready(Some(Ok(bytes)));
+            }
+            if let Some(status) = error.take() {
+                return Poll::Ready(Some(Err(status)));
+            }
+
+            let polled = match (source.as_mut().poll_next(cx), *max_message_count) {
+                (Poll::Ready(Some(Ok(_))), Some(limit)) if *sequence >= limit as u64 => {
+                    // Drop the message, the stream ends with the error.
+                    Poll::Ready(Some(Err(Status::internal(format!(
+                        "Error, too many messages: the limit is {} messages",
+                        limit
+                    )))))
+                }
+                (polled, _) => polled,
+            };
+            match polled {
                 Poll::Pending if buf.is_empty() => {
                     return Poll::Pending;
                 }
@@ -102,19 +181,57 @@ where
                     return Poll::Ready(Some(Ok(buf.split_to(buf.len()).freeze())));
                 }
                 Poll::Ready(Some(Ok(item))) => {
-                    if let Err(status) = encode_item(
+                    let compression_encoding = match message_compression {
+                        Some(compression) if !compression.is_enabled() => None,
+                        _ => *compression_encoding,
+                    };
+                    let started_at = timing.is_some().then(Instant::now);
+                    let encoded = encode_item(
                         encoder,
                         buf,
                         uncompression_buf,
-                        *compression_encoding,
+                        splices,
+                        compression_encoding,
                         *max_message_size,
                         buffer_settings,
                         item,
-                    ) {
-                        return Poll::Ready(Some(Err(status)));
+                    );
+                    if let (Some(timing), Some(started_at)) = (timing.as_ref(), started_at) {
+                        timing.add_encode(started_at.elapsed());
                     }
+                    let encoded_size = match encoded {
+                        Ok(encoded_size) => encoded_size,
+                        Err(status) => {
+                            splices.clear();
+                            return Poll::Ready(Some(Err(status)));
+                        }
+                    };
 
-                    if buf.len() >= buffer_settings.yield_threshold {
+                    debug!(
+                        sequence = *sequence,
+                        encoded_size,
+                        compressed = compression_encoding.is_some(),
+                        "encoded message"
+                    );
+                    *sequence += 1;
+
+                    if !splices.is_empty() {
+                        // Queue the bytes encoded so far and the spliced buffers in order, the
+                        // rest of `buf` follows them.
+                        let mut start = 0;
+                        for (position, bytes) in splices.drain(..) {
+                            if position > start {
+                                pending.push_back(buf.split_to(position - start).freeze());
+                                start = position;
+                            }
+                            pending.push_back(bytes);
+                        }
+                        continue;
+                    }
+
+                    if *flush_mode == StreamingFlushMode::Immediate
+                        || buf.len() >= buffer_settings.yield_threshold
+                    {
                         return Poll::Ready(Some(Ok(buf.split_to(buf.len()).freeze())));
                     }
                 }
@@ -130,15 +247,20 @@ where
     }
 }
 
+/// Encodes `item` into `buf`, returning the size of the encoded message.
+///
+/// Uncompressed messages may record buffers to be sent after a position of `buf` in `splices`.
+#[allow(clippy::too_many_arguments)]
 fn encode_item<T>(
     encoder: &mut T,
     buf: &mut BytesMut,
     uncompression_buf: &mut BytesMut,
+    splices: &mut Vec<(usize, Bytes)>,
     compression_encoding: Option<CompressionEncoding>,
     max_message_size: Option<usize>,
     buffer_settings: BufferSettings,
     item: T::Item,
-) -> Result<(), Status>
+) -> Result<usize, Status>
 where
     T: Encoder<Error = Status>,
 {
@@ -170,20 +292,27 @@ where
         .map_err(|err| Status::internal(format!("Error compressing: {}", err)))?;
     } else {
         encoder
-            .encode(item, &mut EncodeBuf::new(buf))
+            .encode(item, &mut EncodeBuf::with_splices(buf, splices))
             .map_err(|err| Status::internal(format!("Error encoding: {}", err)))?;
     }
+    let spliced_len = splices.iter().map(|(_, bytes)| bytes.len()).sum();
 
     // now that we know length, we can write the header
-    finish_encoding(compression_encoding, max_message_size, &mut buf[offset..])
+    finish_encoding(
+        compression_encoding,
+        max_message_size,
+        &mut buf[offset..],
+        spliced_len,
+    )
 }
 
 fn finish_encoding(
     compression_encoding: Option<CompressionEncoding>,
     max_message_size: Option<usize>,
     buf: &mut [u8],
-) -> Result<(), Status> {
-    let len = buf.len() - HEADER_SIZE;
+    spliced_len: usize,
+) -> Result<usize, Status> {
+    let len = buf.len() - HEADER_SIZE + spliced_len;
     let limit = max_message_size.unwrap_or(DEFAULT_MAX_SEND_MESSAGE_SIZE);
     if len > limit {
         return Err(Status::out_of_range(format!(
@@ -203,7 +332,7 @@ fn finish_encoding(
         buf.put_u32(len as u32);
     }
 
-    Ok(())
+    Ok(len)
 }
 
 #[derive(Debug)]
@@ -226,6 +355,8 @@ struct EncodeState {
     error: Option<Status>,
     role: Role,
     is_end_stream: bool,
+    trailing_metadata: Option<TrailingMetadata>,
+    timing: Option<ServerTiming>,
 }
 
 impl<T: Encoder, U: Stream> EncodeBody<T, U> {
@@ -249,6 +380,8 @@ impl<T: Encoder, U: Stream> EncodeBody<T, U> {
                 error: None,
                 role: Role::Client,
                 is_end_stream: false,
+                trailing_metadata: None,
+                timing: None,
             },
         }
     }
@@ -274,9 +407,49 @@ impl<T: Encoder, U: Stream> EncodeBody<T, U> {
                 error: None,
                 role: Role::Server,
                 is_end_stream: false,
+                trailing_metadata: None,
+                timing: None,
             },
         }
     }
+
+    /// Send the metadata set through `trailing_metadata` in the trailers of a server response.
+    pub(crate) fn with_trailing_metadata(
+        mut self,
+        trailing_metadata: Option<TrailingMetadata>,
+    ) -> Self {
+        self.state.trailing_metadata = trailing_metadata;
+        self
+    }
+
+    /// Add the time spent encoding messages to the encode phase of `timing`, and send the phases
+    /// in the trailers of a server response.
+    pub(crate) fn with_timing(mut self, timing: Option<ServerTiming>) -> Self {
+        self.inner.timing = timing.clone();
+        self.state.timing = timing;
+        self
+    }
+
+    /// Hand the encoded messages to the connection according to `flush_mode`.
+    pub(crate) fn with_flush_mode(mut self, flush_mode: StreamingFlushMode) -> Self {
+        self.inner.flush_mode = flush_mode;
+        self
+    }
+
+    /// Fail the stream once it yields more than `limit` messages.
+    pub(crate) fn with_max_message_count(mut self, limit: Option<usize>) -> Self {
+        self.inner.max_message_count = limit;
+        self
+    }
+
+    /// Decide per message whether to compress it through `message_compression`.
+    pub(crate) fn with_message_compression(
+        mut self,
+        message_compression: Option<MessageCompression>,
+    ) -> Self {
+        self.inner.message_compression = message_compression;
+        self
+    }
 }
 
 impl EncodeState {
@@ -288,16 +461,28 @@ impl EncodeState {
                     return None;
                 }
 
-                self.is_end_stream = true;
                 let status = if let Some(status) = self.error.take() {
                     status
                 } else {
                     Status::ok("")
                 };
-                Some(status.to_header_map())
+                Some(self.finish(status))
             }
         }
     }
+
+    /// Ends a server response with the trailers for `status`.
+    fn finish(&mut self, status: Status) -> Result<HeaderMap, Status> {
+        self.is_end_stream = true;
+        let mut trailers = status.to_header_map()?;
+        if let Some(trailing_metadata) = self.trailing_metadata.take() {
+            trailers.extend(trailing_metadata.take().into_sanitized_headers());
+        }
+        if let Some(timing) = self.timing.take() {
+            trailers.insert(SERVER_TIMING_TRAILER, timing.to_header_value());
+        }
+        Ok(trailers)
+    }
 }
 
 impl<T, U> Body for EncodeBody<T, U>
@@ -321,10 +506,7 @@ where
             Some(Ok(d)) => Some(Ok(Frame::data(d))).into(),
             Some(Err(status)) => match self_proj.state.role {
                 Role::Client => Some(Err(status)).into(),
-                Role::Server => {
-                    self_proj.state.is_end_stream = true;
-                    Some(Ok(Frame::trailers(status.to_header_map()?))).into()
-                }
+                Role::Server => Some(Ok(Frame::trailers(self_proj.state.finish(status)?))).into(),
             },
             None => self_proj
                 .state
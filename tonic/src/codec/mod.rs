@@ -4,21 +4,31 @@
 //! and a protobuf codec based on prost.
 
 mod buffer;
+#[cfg(any(feature = "server", feature = "channel"))]
+mod coalesce;
 pub(crate) mod compression;
-mod decode;
+pub(crate) mod decode;
 mod encode;
+mod pool;
 #[cfg(feature = "prost")]
 mod prost;
+#[cfg(feature = "spool")]
+mod spool;
 
 use crate::Status;
 use std::io;
 
 pub use self::buffer::{DecodeBuf, EncodeBuf};
-pub use self::compression::{CompressionEncoding, EnabledCompressionEncodings};
+#[cfg(any(feature = "server", feature = "channel"))]
+pub use self::coalesce::Coalesce;
+pub use self::compression::{CompressionEncoding, EnabledCompressionEncodings, RequestEncoding};
 pub use self::decode::Streaming;
-pub use self::encode::EncodeBody;
+pub use self::encode::{EncodeBody, StreamingFlushMode};
+pub use self::pool::{AllocatingPool, BufferPool};
 #[cfg(feature = "prost")]
 pub use self::prost::ProstCodec;
+#[cfg(feature = "spool")]
+pub use self::spool::{Spool, Spooled};
 
 /// Unless overridden, this is the buffer size used for encoding requests.
 /// This is spent per-rpc, so you may wish to adjust it. The default is
@@ -64,10 +74,14 @@ const DEFAULT_YIELD_THRESHOLD: usize = 32 * 1024;
 /// not affect the responsiveness of your streaming rpc (for reasonable
 /// sizes of yield threshold).
 /// Yield threshold defaults to 32 KiB.
-#[derive(Clone, Copy, Debug)]
+///
+/// The buffers come from a [`BufferPool`], which allocates them by default. See
+/// [`BufferSettings::with_pool`] to reuse them instead.
+#[derive(Clone, Copy)]
 pub struct BufferSettings {
     buffer_size: usize,
     yield_threshold: usize,
+    pool: &'static dyn BufferPool,
 }
 
 impl BufferSettings {
@@ -76,28 +90,43 @@ impl BufferSettings {
         Self {
             buffer_size,
             yield_threshold,
+            pool: &AllocatingPool,
         }
     }
+
+    /// Take the buffers from `pool`, e.g. to reuse them across calls.
+    ///
+    /// Pools are typically shared by all calls for the lifetime of the process, a pool created at
+    /// runtime can be leaked with [`Box::leak`].
+    pub fn with_pool(self, pool: &'static dyn BufferPool) -> Self {
+        Self { pool, ..self }
+    }
 }
 
 impl Default for BufferSettings {
     fn default() -> Self {
-        Self {
-            buffer_size: DEFAULT_CODEC_BUFFER_SIZE,
-            yield_threshold: DEFAULT_YIELD_THRESHOLD,
-        }
+        Self::new(DEFAULT_CODEC_BUFFER_SIZE, DEFAULT_YIELD_THRESHOLD)
+    }
+}
+
+impl std::fmt::Debug for BufferSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferSettings")
+            .field("buffer_size", &self.buffer_size)
+            .field("yield_threshold", &self.yield_threshold)
+            .finish_non_exhaustive()
     }
 }
 
 // 5 bytes
-const HEADER_SIZE: usize =
+pub(crate) const HEADER_SIZE: usize =
     // compression flag
     std::mem::size_of::<u8>() +
     // data length
     std::mem::size_of::<u32>();
 
 // The default maximum uncompressed size in bytes for a message. Defaults to 4MB.
-const DEFAULT_MAX_RECV_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+pub(crate) const DEFAULT_MAX_RECV_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
 const DEFAULT_MAX_SEND_MESSAGE_SIZE: usize = usize::MAX;
 
 /// Trait that knows how to encode and decode gRPC messages.
@@ -116,6 +145,17 @@ pub trait Codec {
     fn encoder(&mut self) -> Self::Encoder;
     /// Fetch the decoder.
     fn decoder(&mut self) -> Self::Decoder;
+
+    /// The gRPC content-subtype of this codec, for example `"proto"` or `"json"`.
+    ///
+    /// Clients send it as part of the `content-type` header, i.e. `application/grpc+{subtype}`.
+    /// Servers echo the content type of the request and only fall back to this value when the
+    /// request did not carry a gRPC content type. Servers reject requests naming a different
+    /// content-subtype, so services using different codecs can share a server. Defaults to
+    /// `None`, which uses the bare `application/grpc` content type and accepts any request.
+    fn content_subtype(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 /// Encodes gRPC message types
@@ -0,0 +1,140 @@
+use bytes::BytesMut;
+
+/// A source of the buffers messages are encoded into and decoded from.
+///
+/// Every call takes a buffer from the pool for its messages, and for the uncompressed messages
+/// when compression is enabled, and gives them back once the call completes. A pool reusing the
+/// buffers saves these allocations under high request rates. The buffers handed back may still
+/// share their memory with messages or frames that are alive, [`BytesMut::reserve`] reclaims it
+/// once they are gone.
+///
+/// Pools are set per codec through [`BufferSettings::with_pool`], the default
+/// [`AllocatingPool`] allocates a new buffer for every call.
+///
+/// [`BufferSettings::with_pool`]: super::BufferSettings::with_pool
+pub trait BufferPool: Send + Sync {
+    /// Take a buffer with room for at least `capacity` bytes.
+    fn get(&self, capacity: usize) -> BytesMut;
+
+    /// Give back a buffer taken with [`BufferPool::get`], which may hold data.
+    ///
+    /// Drops the buffer by default.
+    fn put(&self, buf: BytesMut) {
+        drop(buf);
+    }
+}
+
+/// The default [`BufferPool`], allocating a new buffer every time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AllocatingPool;
+
+impl BufferPool for AllocatingPool {
+    fn get(&self, capacity: usize) -> BytesMut {
+        BytesMut::with_capacity(capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{
+        BufferSettings, DecodeBuf, Decoder, EncodeBody, EncodeBuf, Encoder, Streaming,
+    };
+    use crate::Status;
+    use bytes::{Buf, BufMut, Bytes};
+    use http_body_util::BodyExt;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    /// Reuses buffers, counting how many were allocated.
+    struct ReusingPool {
+        free: Mutex<Vec<BytesMut>>,
+        allocated: AtomicUsize,
+    }
+
+    impl BufferPool for ReusingPool {
+        fn get(&self, capacity: usize) -> BytesMut {
+            let mut buf = self.free.lock().unwrap().pop().unwrap_or_else(|| {
+                self.allocated.fetch_add(1, Ordering::Relaxed);
+                BytesMut::new()
+            });
+            buf.reserve(capacity);
+            buf
+        }
+
+        fn put(&self, mut buf: BytesMut) {
+            buf.clear();
+            self.free.lock().unwrap().push(buf);
+        }
+    }
+
+    static POOL: ReusingPool = ReusingPool {
+        free: Mutex::new(Vec::new()),
+        allocated: AtomicUsize::new(0),
+    };
+
+    fn settings() -> BufferSettings {
+        BufferSettings::default().with_pool(&POOL)
+    }
+
+    struct PooledCodec;
+
+    impl Encoder for PooledCodec {
+        type Item = Vec<u8>;
+        type Error = Status;
+
+        fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+            dst.put_slice(&item);
+            Ok(())
+        }
+
+        fn buffer_settings(&self) -> BufferSettings {
+            settings()
+        }
+    }
+
+    impl Decoder for PooledCodec {
+        type Item = Vec<u8>;
+        type Error = Status;
+
+        fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Vec<u8>>, Status> {
+            Ok(Some(src.copy_to_bytes(src.remaining()).to_vec()))
+        }
+
+        fn buffer_settings(&self) -> BufferSettings {
+            settings()
+        }
+    }
+
+    async fn echo(messages: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        let source = tokio_stream::iter(messages.to_vec().into_iter().map(Ok));
+        let body = EncodeBody::new_client(PooledCodec, source, None, None);
+        let body: Bytes = body.collect().await.unwrap().to_bytes();
+
+        let mut stream =
+            Streaming::new_request(PooledCodec, http_body_util::Full::new(body), None, None);
+        let mut echoed = Vec::new();
+        while let Some(message) = stream.message().await.unwrap() {
+            echoed.push(message);
+        }
+        echoed
+    }
+
+    #[tokio::test]
+    async fn roundtrip_with_reused_buffers() {
+        let messages = (0..64u8)
+            .map(|i| vec![i; 1024 * usize::from(i % 4)])
+            .collect::<Vec<_>>();
+
+        for _ in 0..16 {
+            assert_eq!(echo(&messages).await, messages);
+        }
+
+        // Every buffer was given back and reused by the following calls.
+        let allocated = POOL.allocated.load(Ordering::Relaxed);
+        assert!(allocated <= 2, "{allocated} buffers allocated");
+        assert_eq!(POOL.free.lock().unwrap().len(), allocated);
+    }
+}
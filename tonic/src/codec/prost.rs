@@ -154,10 +154,12 @@ mod tests {
         DecodeBuf, Decoder, EncodeBody, EncodeBuf, Encoder, Streaming, HEADER_SIZE,
     };
     use crate::Status;
-    use bytes::{Buf, BufMut, BytesMut};
+    use bytes::{Buf, BufMut, Bytes, BytesMut};
     use http_body::Body;
     use http_body_util::BodyExt as _;
     use std::pin::pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     const LEN: usize = 10000;
     // The maximum uncompressed size in bytes for a message. Set to 2MB.
@@ -219,6 +221,46 @@ mod tests {
         assert_eq!(actual.message(), expected.message());
     }
 
+    #[tokio::test]
+    #[cfg(feature = "gzip")]
+    async fn decode_decompressed_message_size_exceeded() {
+        use crate::codec::CompressionEncoding;
+        use flate2::{read::GzEncoder, Compression};
+        use std::io::Read as _;
+
+        // Zeros compress to a few kilobytes, far below the limit.
+        let msg = vec![0u8; 8 * MAX_MESSAGE_SIZE];
+        let mut compressed = Vec::new();
+        GzEncoder::new(&msg[..], Compression::best())
+            .read_to_end(&mut compressed)
+            .unwrap();
+        assert!(compressed.len() < MAX_MESSAGE_SIZE / 64);
+
+        let mut buf = BytesMut::new();
+        buf.put_u8(1);
+        buf.put_u32(compressed.len() as u32);
+        buf.put(&compressed[..]);
+
+        let body = body::MockBody::new(&buf[..], buf.len(), 0);
+        let mut stream = Streaming::new_request(
+            MockDecoder::default(),
+            body,
+            Some(CompressionEncoding::Gzip),
+            Some(MAX_MESSAGE_SIZE),
+        );
+
+        let actual = stream.message().await.unwrap_err();
+        assert_eq!(actual.code(), crate::Code::ResourceExhausted);
+        assert_eq!(
+            actual.message(),
+            format!(
+                "Error, decompressed message length too large: the limit is {} bytes",
+                MAX_MESSAGE_SIZE
+            )
+        );
+        assert!(stream.message().await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn encode() {
         let encoder = MockEncoder::default();
@@ -241,6 +283,117 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    #[cfg(feature = "gzip")]
+    async fn encode_with_message_compression_disabled() {
+        use crate::codec::CompressionEncoding;
+        use crate::MessageCompression;
+        use tokio_stream::StreamExt;
+
+        let compression = MessageCompression::new();
+        let source = tokio_stream::iter(0..3).map({
+            let compression = compression.clone();
+            move |i| {
+                compression.set_enabled(i != 1);
+                Ok::<_, Status>(Bytes::from(vec![i; 1024]))
+            }
+        });
+
+        let body = EncodeBody::new_server(
+            BytesEncoder,
+            source,
+            Some(CompressionEncoding::Gzip),
+            SingleMessageCompressionOverride::default(),
+            None,
+        )
+        .with_message_compression(Some(compression));
+        let mut buf = body.collect().await.unwrap().to_bytes();
+
+        let mut flags = Vec::new();
+        while buf.has_remaining() {
+            flags.push(buf.get_u8());
+            let len = buf.get_u32() as usize;
+            let message = buf.split_to(len);
+            if flags.len() == 2 {
+                assert_eq!(&message[..], &[1; 1024][..]);
+            } else {
+                assert!(len < 1024);
+            }
+        }
+        assert_eq!(flags, [1, 0, 1]);
+    }
+
+    #[tokio::test]
+    async fn encode_with_immediate_flush_mode() {
+        use crate::codec::StreamingFlushMode;
+        use tokio_stream::StreamExt;
+
+        async fn first_frame(flush_mode: StreamingFlushMode) -> (Bytes, usize) {
+            let produced = Arc::new(AtomicUsize::new(0));
+            let source = tokio_stream::iter(0..3).map({
+                let produced = produced.clone();
+                move |i| {
+                    produced.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, Status>(Bytes::from(vec![i; 8]))
+                }
+            });
+
+            let mut body = pin!(EncodeBody::new_server(
+                BytesEncoder,
+                source,
+                None,
+                SingleMessageCompressionOverride::default(),
+                None,
+            )
+            .with_flush_mode(flush_mode));
+            let frame = body.frame().await.unwrap().unwrap().into_data().unwrap();
+            (frame, produced.load(Ordering::SeqCst))
+        }
+
+        // The first message is on its way before the stream produced the second one.
+        let (frame, produced) = first_frame(StreamingFlushMode::Immediate).await;
+        assert_eq!(frame.len(), HEADER_SIZE + 8);
+        assert_eq!(produced, 1);
+
+        let (frame, produced) = first_frame(StreamingFlushMode::Buffered).await;
+        assert_eq!(frame.len(), 3 * (HEADER_SIZE + 8));
+        assert_eq!(produced, 3);
+    }
+
+    #[tokio::test]
+    async fn encode_and_decode_large_bytes_without_copying() {
+        let msg = Bytes::from(vec![7u8; 64 * 1024]);
+        let source = tokio_stream::iter([Ok::<_, Status>(msg.clone())]);
+
+        let mut body = pin!(EncodeBody::new_server(
+            BytesEncoder,
+            source,
+            None,
+            SingleMessageCompressionOverride::default(),
+            None,
+        ));
+
+        let mut frames = Vec::new();
+        while let Some(frame) = body.frame().await {
+            if let Ok(data) = frame.unwrap().into_data() {
+                frames.push(data);
+            }
+        }
+        assert_eq!(frames.len(), 2);
+        assert_eq!(&frames[0][..], &[0, 0, 1, 0, 0]);
+        assert_eq!(frames[1].as_ptr(), msg.as_ptr());
+
+        let mut buf = BytesMut::new();
+        for frame in &frames {
+            buf.put(&frame[..]);
+        }
+        let body = body::MockBody::new(&buf[..], buf.len(), 0);
+        let mut stream = Streaming::new_request(BytesDecoder, body, None, None);
+
+        let decoded = stream.message().await.unwrap().unwrap();
+        assert_eq!(decoded, msg);
+    }
+
     #[tokio::test]
     async fn encode_max_message_size_exceeded() {
         let encoder = MockEncoder::default();
@@ -311,6 +464,65 @@ mod tests {
         assert!(body.is_end_stream());
     }
 
+    #[tokio::test]
+    async fn encode_and_decode_emit_event_per_message() {
+        let encoded = Arc::new(AtomicUsize::new(0));
+        let decoded = Arc::new(AtomicUsize::new(0));
+        let _guard = tracing::subscriber::set_default(MessageEventCounter {
+            encoded: encoded.clone(),
+            decoded: decoded.clone(),
+        });
+
+        let encoder = MockEncoder::default();
+        let msg = vec![0u8; LEN];
+        let messages = std::iter::repeat_n(Ok::<_, Status>(msg), 3);
+        let source = tokio_stream::iter(messages);
+
+        let mut body = pin!(EncodeBody::new_client(encoder, source, None, None));
+
+        let mut buf = BytesMut::new();
+        while let Some(frame) = body.frame().await {
+            buf.put(frame.unwrap().into_data().unwrap());
+        }
+        assert_eq!(encoded.load(Ordering::SeqCst), 3);
+
+        let body = body::MockBody::new(&buf[..], buf.len(), 0);
+        let mut stream = Streaming::new_request(MockDecoder::default(), body, None, None);
+        while stream.message().await.unwrap().is_some() {}
+        assert_eq!(decoded.load(Ordering::SeqCst), 3);
+    }
+
+    struct MessageEventCounter {
+        encoded: Arc<AtomicUsize>,
+        decoded: Arc<AtomicUsize>,
+    }
+
+    impl tracing::Subscriber for MessageEventCounter {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            match event.metadata().target() {
+                "tonic::codec::encode" => self.encoded.fetch_add(1, Ordering::SeqCst),
+                "tonic::codec::decode" => self.decoded.fetch_add(1, Ordering::SeqCst),
+                _ => 0,
+            };
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
     #[derive(Debug, Clone, Default)]
     struct MockEncoder {}
 
@@ -331,6 +543,39 @@ mod tests {
     #[derive(Debug, Clone, Default)]
     struct MockDecoder {}
 
+    #[derive(Debug, Clone)]
+    struct BytesEncoder;
+
+    impl Encoder for BytesEncoder {
+        type Item = Bytes;
+        type Error = Status;
+
+        fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+            buf.put(item);
+            Ok(())
+        }
+
+        fn buffer_settings(&self) -> crate::codec::BufferSettings {
+            Default::default()
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct BytesDecoder;
+
+    impl Decoder for BytesDecoder {
+        type Item = Bytes;
+        type Error = Status;
+
+        fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+            Ok(Some(buf.copy_to_bytes(buf.remaining())))
+        }
+
+        fn buffer_settings(&self) -> crate::codec::BufferSettings {
+            Default::default()
+        }
+    }
+
     impl Decoder for MockDecoder {
         type Item = Vec<u8>;
         type Error = Status;
@@ -0,0 +1,334 @@
+use super::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use crate::Status;
+use bytes::{Buf, BufMut, BytesMut};
+use std::{fmt, io, path::PathBuf};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+use tokio_stream::{Stream, StreamExt};
+
+/// The default number of bytes a [`Spool`] keeps in memory. Defaults to 8MB.
+const DEFAULT_SPOOL_THRESHOLD: usize = 8 * 1024 * 1024;
+
+/// The length prefix of every message in the spool.
+const LEN_SIZE: usize = std::mem::size_of::<u32>();
+
+/// Buffers a stream of messages, spilling them to a temporary file once they grow too large.
+///
+/// Handlers of client-streaming calls that need all of their input before they can start, like
+/// media ingestion, would otherwise have to hold the whole upload in memory. A `Spool` encodes the
+/// messages with the given [`Codec`] and keeps them in memory until they take up more than the
+/// [threshold](Spool::threshold), then moves them to an anonymous temporary file that is removed
+/// once the returned [`Spooled`] is dropped.
+///
+/// # Example
+///
+/// ```rust
+/// # use tonic::{codec::{ProstCodec, Spool}, Request, Response, Status, Streaming};
+/// # type Chunk = Vec<u8>;
+/// async fn upload(request: Request<Streaming<Chunk>>) -> Result<Response<()>, Status> {
+///     let mut chunks = Spool::new(ProstCodec::<Chunk, Chunk>::default())
+///         .threshold(64 * 1024 * 1024)
+///         .spool(request.into_inner())
+///         .await?;
+///
+///     while let Some(chunk) = chunks.message().await? {
+///         // ...
+///     }
+///
+///     Ok(Response::new(()))
+/// }
+/// ```
+pub struct Spool<C> {
+    codec: C,
+    threshold: usize,
+    temp_dir: Option<PathBuf>,
+}
+
+impl<C, T> Spool<C>
+where
+    C: Codec<Encode = T, Decode = T>,
+{
+    /// Create a new spool that encodes messages with `codec`.
+    pub fn new(codec: C) -> Self {
+        Self {
+            codec,
+            threshold: DEFAULT_SPOOL_THRESHOLD,
+            temp_dir: None,
+        }
+    }
+
+    /// Sets how many bytes of encoded messages are kept in memory before spilling them to disk.
+    ///
+    /// Default is 8MB.
+    #[must_use]
+    pub fn threshold(self, threshold: usize) -> Self {
+        Self { threshold, ..self }
+    }
+
+    /// Sets the directory the temporary file is created in.
+    ///
+    /// Default is [`std::env::temp_dir`].
+    #[must_use]
+    pub fn temp_dir(self, temp_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            temp_dir: Some(temp_dir.into()),
+            ..self
+        }
+    }
+
+    /// Drain `stream` into the spool.
+    ///
+    /// Returns the first error yielded by `stream`, or an [`Code::Internal`] error if spooling
+    /// to disk failed.
+    ///
+    /// [`Code::Internal`]: crate::Code::Internal
+    pub async fn spool<S>(mut self, stream: S) -> Result<Spooled<T>, Status>
+    where
+        S: Stream<Item = Result<T, Status>>,
+    {
+        let mut encoder = self.codec.encoder();
+        let mut stream = std::pin::pin!(stream);
+
+        let mut buf = BytesMut::new();
+        let mut scratch = BytesMut::new();
+        let mut file = None;
+        let mut messages = 0;
+
+        while let Some(message) = stream.next().await {
+            scratch.clear();
+            let message = message?;
+            encoder.encode(message, &mut EncodeBuf::new(&mut scratch))?;
+            let len = u32::try_from(scratch.len())
+                .map_err(|_| Status::resource_exhausted("message too large to spool"))?;
+
+            buf.reserve(LEN_SIZE + scratch.len());
+            buf.put_u32(len);
+            buf.put_slice(&scratch);
+            messages += 1;
+
+            if buf.len() > self.threshold {
+                let file = match &mut file {
+                    Some(file) => file,
+                    None => file.insert(self.temp_file().map_err(spool_error)?),
+                };
+                file.write_all(&buf).await.map_err(spool_error)?;
+                buf.clear();
+            }
+        }
+
+        if let Some(file) = &mut file {
+            file.write_all(&buf).await.map_err(spool_error)?;
+            file.flush().await.map_err(spool_error)?;
+            file.rewind().await.map_err(spool_error)?;
+            buf.clear();
+        }
+
+        Ok(Spooled {
+            decoder: Box::new(self.codec.decoder()),
+            file,
+            buf,
+            messages,
+        })
+    }
+
+    fn temp_file(&self) -> io::Result<File> {
+        let file = match &self.temp_dir {
+            Some(dir) => tempfile::tempfile_in(dir)?,
+            None => tempfile::tempfile()?,
+        };
+        Ok(File::from_std(file))
+    }
+}
+
+impl<C> fmt::Debug for Spool<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spool")
+            .field("threshold", &self.threshold)
+            .field("temp_dir", &self.temp_dir)
+            .finish()
+    }
+}
+
+/// The messages buffered by a [`Spool`].
+pub struct Spooled<T> {
+    decoder: Box<dyn Decoder<Item = T, Error = Status> + Send + 'static>,
+    file: Option<File>,
+    buf: BytesMut,
+    messages: usize,
+}
+
+impl<T> Spooled<T> {
+    /// Returns `true` if the messages were spilled to a temporary file.
+    pub fn is_spilled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// The number of messages that have not been read yet.
+    pub fn remaining(&self) -> usize {
+        self.messages
+    }
+
+    /// Read the next message, in the order they were spooled.
+    pub async fn message(&mut self) -> Result<Option<T>, Status> {
+        if !self.fill(LEN_SIZE).await? {
+            return if self.buf.is_empty() {
+                Ok(None)
+            } else {
+                Err(Status::internal("spooled message was truncated"))
+            };
+        }
+
+        let len = (&self.buf[..LEN_SIZE]).get_u32() as usize;
+        if !self.fill(LEN_SIZE + len).await? {
+            return Err(Status::internal("spooled message was truncated"));
+        }
+
+        self.buf.advance(LEN_SIZE);
+        let mut message = self.buf.split_to(len);
+        self.messages -= 1;
+
+        self.decoder
+            .decode(&mut DecodeBuf::new(&mut message, len))?
+            .map(Some)
+            .ok_or_else(|| Status::internal("spooled message was truncated"))
+    }
+
+    /// Read from the temporary file until at least `len` bytes are buffered.
+    async fn fill(&mut self, len: usize) -> Result<bool, Status> {
+        while self.buf.len() < len {
+            let Some(file) = &mut self.file else {
+                break;
+            };
+
+            self.buf.reserve(len - self.buf.len());
+            if file.read_buf(&mut self.buf).await.map_err(spool_error)? == 0 {
+                break;
+            }
+        }
+
+        Ok(self.buf.len() >= len)
+    }
+}
+
+impl<T> fmt::Debug for Spooled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spooled")
+            .field("spilled", &self.is_spilled())
+            .field("remaining", &self.messages)
+            .finish()
+    }
+}
+
+fn spool_error(err: io::Error) -> Status {
+    Status::internal(format!("failed to spool messages: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::BufferSettings;
+
+    #[derive(Debug, Clone, Default)]
+    struct BytesCodec;
+
+    impl Codec for BytesCodec {
+        type Encode = Vec<u8>;
+        type Decode = Vec<u8>;
+        type Encoder = BytesCodec;
+        type Decoder = BytesCodec;
+
+        fn encoder(&mut self) -> Self::Encoder {
+            BytesCodec
+        }
+
+        fn decoder(&mut self) -> Self::Decoder {
+            BytesCodec
+        }
+    }
+
+    impl Encoder for BytesCodec {
+        type Item = Vec<u8>;
+        type Error = Status;
+
+        fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+            buf.put_slice(&item);
+            Ok(())
+        }
+
+        fn buffer_settings(&self) -> BufferSettings {
+            Default::default()
+        }
+    }
+
+    impl Decoder for BytesCodec {
+        type Item = Vec<u8>;
+        type Error = Status;
+
+        fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+            Ok(Some(buf.copy_to_bytes(buf.remaining()).to_vec()))
+        }
+
+        fn buffer_settings(&self) -> BufferSettings {
+            Default::default()
+        }
+    }
+
+    fn messages() -> Vec<Vec<u8>> {
+        (0..64u8).map(|i| vec![i; 1000 + i as usize]).collect()
+    }
+
+    async fn read_all(mut spooled: Spooled<Vec<u8>>) -> Vec<Vec<u8>> {
+        let mut out = Vec::new();
+        while let Some(message) = spooled.message().await.unwrap() {
+            out.push(message);
+        }
+        assert_eq!(spooled.remaining(), 0);
+        out
+    }
+
+    #[tokio::test]
+    async fn small_input_stays_in_memory() {
+        let spooled = Spool::new(BytesCodec)
+            .spool(tokio_stream::iter(messages().into_iter().map(Ok)))
+            .await
+            .unwrap();
+
+        assert!(!spooled.is_spilled());
+        assert_eq!(spooled.remaining(), 64);
+        assert_eq!(read_all(spooled).await, messages());
+    }
+
+    #[tokio::test]
+    async fn large_input_spills_to_disk() {
+        let spooled = Spool::new(BytesCodec)
+            .threshold(10 * 1024)
+            .temp_dir(std::env::temp_dir())
+            .spool(tokio_stream::iter(messages().into_iter().map(Ok)))
+            .await
+            .unwrap();
+
+        assert!(spooled.is_spilled());
+        assert!(spooled.buf.is_empty());
+        assert_eq!(read_all(spooled).await, messages());
+    }
+
+    #[tokio::test]
+    async fn stream_errors_are_returned() {
+        let input = tokio_stream::iter(vec![Ok(vec![1]), Err(Status::cancelled("gone"))]);
+        let err = Spool::new(BytesCodec).spool(input).await.unwrap_err();
+        assert_eq!(err.code(), crate::Code::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn missing_temp_dir_is_an_error() {
+        let err = Spool::new(BytesCodec)
+            .threshold(0)
+            .temp_dir("/nonexistent/tonic-spool")
+            .spool(tokio_stream::iter(vec![Ok(vec![1])]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), crate::Code::Internal);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::Status;
+use std::{any::Any, sync::Arc};
+
 /// A gRPC Method info extension.
 #[derive(Debug, Clone)]
 pub struct GrpcMethod<'a> {
@@ -21,3 +24,110 @@ impl<'a> GrpcMethod<'a> {
         self.method
     }
 }
+
+/// The `user-agent` a client sent, e.g. `my-app/1.2 grpc-rust-tonic/0.13.0`.
+///
+/// The server inserts it into the extensions of every request that has one, to identify client
+/// versions, see [`Endpoint::user_agent`] for setting it on the client.
+///
+/// ```
+/// # use tonic::{ClientUserAgent, Request};
+/// # fn handler<T>(request: &Request<T>) {
+/// if let Some(user_agent) = request.extensions().get::<ClientUserAgent>() {
+///     println!("called by {:?}", user_agent.to_str());
+/// }
+/// # }
+/// ```
+///
+/// [`Endpoint::user_agent`]: crate::transport::Endpoint::user_agent
+#[derive(Debug, Clone)]
+pub struct ClientUserAgent(http::HeaderValue);
+
+impl ClientUserAgent {
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub(crate) fn new(value: http::HeaderValue) -> Self {
+        Self(value)
+    }
+
+    /// The user-agent as a string, if it only contains visible ASCII characters.
+    pub fn to_str(&self) -> Option<&str> {
+        self.0.to_str().ok()
+    }
+
+    /// The raw header value.
+    pub fn as_header_value(&self) -> &http::HeaderValue {
+        &self.0
+    }
+}
+
+/// How many times a client tried a call before this request, from the
+/// `grpc-previous-rpc-attempts` header.
+///
+/// The server inserts it into the extensions of every request that has the header, so handlers
+/// and logs can tell retries apart from first attempts, see
+/// [`Request::set_previous_rpc_attempts`](crate::Request::set_previous_rpc_attempts) for
+/// setting it on the client.
+///
+/// ```
+/// # use tonic::{PreviousRpcAttempts, Request};
+/// # fn handler<T>(request: &Request<T>) {
+/// if let Some(attempts) = request.extensions().get::<PreviousRpcAttempts>() {
+///     println!("retried after {} attempts", attempts.get());
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviousRpcAttempts(u32);
+
+impl PreviousRpcAttempts {
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub(crate) fn from_header(value: &http::HeaderValue) -> Option<Self> {
+        value.to_str().ok()?.parse().ok().map(Self)
+    }
+
+    /// The number of previous attempts, at least 1 for a retry.
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Whether a request should wait for the channel to become ready instead of failing fast.
+///
+/// Set through [`Request::set_wait_for_ready`](crate::Request::set_wait_for_ready).
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "channel"), allow(dead_code))]
+pub(crate) struct WaitForReady(pub(crate) bool);
+
+/// The maximum number of messages a streaming request may carry.
+///
+/// Set through [`Server::max_request_messages`](crate::transport::Server::max_request_messages).
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub(crate) struct MaxRequestMessages(pub(crate) usize);
+
+/// The maximum number of messages a streaming response may carry.
+///
+/// Set through [`Server::max_response_messages`](crate::transport::Server::max_response_messages).
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub(crate) struct MaxResponseMessages(pub(crate) usize);
+
+/// An inspector of decoded request messages, rejecting a message by returning an error.
+pub(crate) type Inspector = Arc<dyn Fn(&dyn Any) -> Result<(), Status> + Send + Sync>;
+
+/// The inspectors of decoded request messages, from the outermost to the innermost.
+///
+/// Added by [`InspectMessageLayer`](crate::service::InspectMessageLayer)s.
+#[derive(Clone, Default)]
+pub(crate) struct MessageInspectors(Vec<Inspector>);
+
+impl MessageInspectors {
+    pub(crate) fn push(&mut self, inspector: Inspector) {
+        self.0.push(inspector);
+    }
+
+    /// Run the inspectors on `message`, stopping at the first one rejecting it.
+    pub(crate) fn inspect(&self, message: &dyn Any) -> Result<(), Status> {
+        self.0.iter().try_for_each(|inspector| inspector(message))
+    }
+}
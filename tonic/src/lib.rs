@@ -37,6 +37,19 @@
 //!   Not enabled by default.
 //! - `zstd`: Enables compressing requests, responses, and streams. Depends on [`zstd`].
 //!   Not enabled by default.
+//! - `spool`: Enables `codec::Spool`, which spills large message streams to a temporary
+//!   file. Not enabled by default.
+//! - `http3`: Enables serving gRPC over QUIC through `Router::serve_http3`, using [`h3`] and
+//!   [`quinn`]. Not enabled by default.
+//! - `otel-trace`: Enables `transport::server::OtelTraceLayer`, which creates OpenTelemetry
+//!   server spans following the gRPC semantic conventions. Depends on [`opentelemetry`].
+//!   Not enabled by default.
+//! - `audit`: Enables `transport::server::AuditLayer`, which emits an audit event with
+//!   SHA-256 digests of the request and response bodies per RPC. Depends on [`ring`].
+//!   Not enabled by default.
+//! - `test-util`: Enables `transport::FaultInjector`, which injects faults like dropped
+//!   connections into the connections of a `Server` or `Channel` for resilience tests.
+//!   Not enabled by default.
 //!
 //! # Structure
 //!
@@ -78,6 +91,9 @@
 //! [`Channel`]: transport/struct.Channel.html
 //! [`Server`]: transport/struct.Server.html
 //! [`rustls`]: https://docs.rs/rustls
+//! [`h3`]: https://docs.rs/h3
+//! [`quinn`]: https://docs.rs/quinn
+//! [`opentelemetry`]: https://docs.rs/opentelemetry
 //! [`client`]: client/index.html
 //! [`transport`]: transport/index.html
 //! [`rustls-native-certs`]: https://docs.rs/rustls-native-certs
@@ -111,7 +127,9 @@ pub mod service;
 #[cfg(any(feature = "server", feature = "channel"))]
 pub mod transport;
 
+mod baggage;
 mod extensions;
+mod load_report;
 mod macros;
 mod request;
 mod response;
@@ -122,12 +140,14 @@ mod util;
 #[cfg(feature = "codegen")]
 pub use async_trait::async_trait;
 
+pub use baggage::Baggage;
 #[doc(inline)]
 pub use codec::Streaming;
-pub use extensions::GrpcMethod;
+pub use extensions::{ClientUserAgent, GrpcMethod, PreviousRpcAttempts};
 pub use http::Extensions;
+pub use load_report::LoadReport;
 pub use request::{IntoRequest, IntoStreamingRequest, Request};
-pub use response::Response;
+pub use response::{MessageCompression, Response, TrailingMetadata};
 pub use status::{Code, ConnectError, Status, TimeoutExpired};
 
 pub(crate) type BoxError = Box<dyn std::error::Error + Send + Sync>;
@@ -0,0 +1,378 @@
+use crate::metadata::{MetadataMap, MetadataValue};
+use base64::Engine as _;
+use http::HeaderMap;
+use std::collections::BTreeMap;
+
+/// The trailer carrying a load report, see [`LoadReport`].
+pub(crate) const LOAD_REPORT_HEADER: &str = "endpoint-load-metrics-bin";
+
+// Field numbers of `xds.data.orca.v3.OrcaLoadReport`.
+const CPU_UTILIZATION: u64 = 1;
+const MEM_UTILIZATION: u64 = 2;
+const UTILIZATION: u64 = 5;
+const RPS_FRACTIONAL: u64 = 6;
+const EPS: u64 = 7;
+const NAMED_METRICS: u64 = 8;
+const APPLICATION_UTILIZATION: u64 = 9;
+
+// Protobuf wire types.
+const VARINT: u64 = 0;
+const I64: u64 = 1;
+const LEN: u64 = 2;
+const I32: u64 = 5;
+
+/// The load of a backend, reported to clients in the trailers of a response for load-aware
+/// balancing.
+///
+/// This is the per-call load report of [ORCA], sent in the `endpoint-load-metrics-bin` trailer
+/// as an `xds.data.orca.v3.OrcaLoadReport` message, which other gRPC implementations and proxies
+/// understand as well. A handler attaches it to its response with
+/// [`Response::set_load_report`]:
+///
+/// ```
+/// # use tonic::{LoadReport, Response};
+/// let mut report = LoadReport::new();
+/// report.set_cpu_utilization(0.42);
+/// report.set_utilization("queue", 0.1);
+///
+/// let mut response = Response::new(());
+/// response.set_load_report(report);
+/// ```
+///
+/// Channels balancing over several endpoints, e.g. [`Channel::balance_list`], send requests to
+/// the endpoint with the lower load of two picked at random. An endpoint's load is the
+/// application utilization of the last report it sent, or its CPU utilization if it didn't
+/// report an application utilization. Endpoints that never reported a load count as idle.
+///
+/// [ORCA]: https://github.com/grpc/proposal/blob/master/A51-custom-backend-metrics.md
+/// [`Response::set_load_report`]: crate::Response::set_load_report
+/// [`Channel::balance_list`]: crate::transport::Channel::balance_list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadReport {
+    cpu_utilization: f64,
+    mem_utilization: f64,
+    application_utilization: f64,
+    qps: f64,
+    eps: f64,
+    utilization: BTreeMap<String, f64>,
+    named_metrics: BTreeMap<String, f64>,
+}
+
+impl LoadReport {
+    /// Create an empty load report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The CPU utilization of the backend, usually between `0.0` and `1.0`.
+    pub fn cpu_utilization(&self) -> f64 {
+        self.cpu_utilization
+    }
+
+    /// Set the CPU utilization of the backend, usually between `0.0` and `1.0`.
+    pub fn set_cpu_utilization(&mut self, utilization: f64) {
+        self.cpu_utilization = utilization;
+    }
+
+    /// The memory utilization of the backend, between `0.0` and `1.0`.
+    pub fn mem_utilization(&self) -> f64 {
+        self.mem_utilization
+    }
+
+    /// Set the memory utilization of the backend, between `0.0` and `1.0`.
+    pub fn set_mem_utilization(&mut self, utilization: f64) {
+        self.mem_utilization = utilization;
+    }
+
+    /// The utilization of the backend as defined by the application.
+    pub fn application_utilization(&self) -> f64 {
+        self.application_utilization
+    }
+
+    /// Set the utilization of the backend as defined by the application, e.g. from its queue
+    /// depth. Balancers prefer it over the CPU utilization when it is set.
+    pub fn set_application_utilization(&mut self, utilization: f64) {
+        self.application_utilization = utilization;
+    }
+
+    /// The queries per second the backend serves.
+    pub fn qps(&self) -> f64 {
+        self.qps
+    }
+
+    /// Set the queries per second the backend serves.
+    pub fn set_qps(&mut self, qps: f64) {
+        self.qps = qps;
+    }
+
+    /// The errors per second of the backend.
+    pub fn eps(&self) -> f64 {
+        self.eps
+    }
+
+    /// Set the errors per second of the backend.
+    pub fn set_eps(&mut self, eps: f64) {
+        self.eps = eps;
+    }
+
+    /// The utilization of the resource `name`, if reported.
+    pub fn utilization(&self, name: &str) -> Option<f64> {
+        self.utilization.get(name).copied()
+    }
+
+    /// Set the utilization of the resource `name`, between `0.0` and `1.0`.
+    pub fn set_utilization(&mut self, name: impl Into<String>, utilization: f64) {
+        self.utilization.insert(name.into(), utilization);
+    }
+
+    /// The application-specific metric `name`, if reported.
+    pub fn named_metric(&self, name: &str) -> Option<f64> {
+        self.named_metrics.get(name).copied()
+    }
+
+    /// Set the application-specific metric `name`.
+    pub fn set_named_metric(&mut self, name: impl Into<String>, value: f64) {
+        self.named_metrics.insert(name.into(), value);
+    }
+
+    /// The load report in `metadata`, e.g. the metadata of a [`Response`](crate::Response),
+    /// which includes its trailers once they were received.
+    ///
+    /// Returns `None` if there is no report, or if it is malformed.
+    pub fn from_metadata(metadata: &MetadataMap) -> Option<Self> {
+        let value = metadata.get_bin(LOAD_REPORT_HEADER)?;
+        Self::decode(&value.to_bytes().ok()?)
+    }
+
+    /// The load report in `headers`, see [`LoadReport::from_metadata`].
+    #[cfg_attr(not(feature = "channel"), allow(dead_code))]
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = headers.get(LOAD_REPORT_HEADER)?;
+        let bytes = crate::util::base64::STANDARD
+            .decode(value.as_bytes())
+            .ok()?;
+        Self::decode(&bytes)
+    }
+
+    /// Add the load report to `metadata`, replacing any previous one.
+    pub(crate) fn insert_into(&self, metadata: &mut MetadataMap) {
+        metadata.insert_bin(
+            LOAD_REPORT_HEADER,
+            MetadataValue::from_bytes(&self.encode()),
+        );
+    }
+
+    /// The load of the backend used for balancing, `0.0` for values that can't be compared.
+    #[cfg_attr(not(feature = "channel"), allow(dead_code))]
+    pub(crate) fn load(&self) -> f64 {
+        let load = if self.application_utilization > 0.0 {
+            self.application_utilization
+        } else {
+            self.cpu_utilization
+        };
+        if load.is_finite() && load > 0.0 {
+            load
+        } else {
+            0.0
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let doubles = [
+            (CPU_UTILIZATION, self.cpu_utilization),
+            (MEM_UTILIZATION, self.mem_utilization),
+            (RPS_FRACTIONAL, self.qps),
+            (EPS, self.eps),
+            (APPLICATION_UTILIZATION, self.application_utilization),
+        ];
+        for (field, value) in doubles {
+            // Like protobuf, leave out the default value.
+            if value != 0.0 {
+                encode_double(field, value, &mut buf);
+            }
+        }
+
+        for (field, map) in [
+            (UTILIZATION, &self.utilization),
+            (NAMED_METRICS, &self.named_metrics),
+        ] {
+            for (key, value) in map {
+                let mut entry = Vec::new();
+                encode_varint(1 << 3 | LEN, &mut entry);
+                encode_varint(key.len() as u64, &mut entry);
+                entry.extend_from_slice(key.as_bytes());
+                encode_double(2, *value, &mut entry);
+
+                encode_varint(field << 3 | LEN, &mut buf);
+                encode_varint(entry.len() as u64, &mut buf);
+                buf.extend_from_slice(&entry);
+            }
+        }
+
+        buf
+    }
+
+    fn decode(mut buf: &[u8]) -> Option<Self> {
+        let mut report = Self::new();
+        while !buf.is_empty() {
+            let (field, value) = decode_field(&mut buf)?;
+            match (field, value) {
+                (CPU_UTILIZATION, Value::I64(bits)) => {
+                    report.cpu_utilization = f64::from_bits(bits)
+                }
+                (MEM_UTILIZATION, Value::I64(bits)) => {
+                    report.mem_utilization = f64::from_bits(bits)
+                }
+                (RPS_FRACTIONAL, Value::I64(bits)) => report.qps = f64::from_bits(bits),
+                (EPS, Value::I64(bits)) => report.eps = f64::from_bits(bits),
+                (APPLICATION_UTILIZATION, Value::I64(bits)) => {
+                    report.application_utilization = f64::from_bits(bits)
+                }
+                (UTILIZATION, Value::Len(entry)) => {
+                    let (key, value) = decode_entry(entry)?;
+                    report.utilization.insert(key, value);
+                }
+                (NAMED_METRICS, Value::Len(entry)) => {
+                    let (key, value) = decode_entry(entry)?;
+                    report.named_metrics.insert(key, value);
+                }
+                // Fields this version doesn't know, e.g. `request_cost`.
+                _ => {}
+            }
+        }
+        Some(report)
+    }
+}
+
+enum Value<'a> {
+    Varint,
+    I64(u64),
+    Len(&'a [u8]),
+    I32,
+}
+
+fn encode_varint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn encode_double(field: u64, value: f64, buf: &mut Vec<u8>) {
+    encode_varint(field << 3 | I64, buf);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn decode_varint(buf: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first()?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn decode_bytes<'a>(buf: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if buf.len() < len {
+        return None;
+    }
+    let (bytes, rest) = buf.split_at(len);
+    *buf = rest;
+    Some(bytes)
+}
+
+fn decode_field<'a>(buf: &mut &'a [u8]) -> Option<(u64, Value<'a>)> {
+    let key = decode_varint(buf)?;
+    let value = match key & 0x7 {
+        VARINT => decode_varint(buf).map(|_| Value::Varint)?,
+        I64 => Value::I64(u64::from_le_bytes(decode_bytes(buf, 8)?.try_into().ok()?)),
+        LEN => {
+            let len = decode_varint(buf)?;
+            Value::Len(decode_bytes(buf, usize::try_from(len).ok()?)?)
+        }
+        I32 => decode_bytes(buf, 4).map(|_| Value::I32)?,
+        _ => return None,
+    };
+    Some((key >> 3, value))
+}
+
+/// Decodes an entry of a `map<string, double>`.
+fn decode_entry(mut buf: &[u8]) -> Option<(String, f64)> {
+    let mut key = String::new();
+    let mut value = 0.0;
+    while !buf.is_empty() {
+        match decode_field(&mut buf)? {
+            (1, Value::Len(bytes)) => key = String::from_utf8(bytes.to_vec()).ok()?,
+            (2, Value::I64(bits)) => value = f64::from_bits(bits),
+            _ => {}
+        }
+    }
+    Some((key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> LoadReport {
+        let mut report = LoadReport::new();
+        report.set_cpu_utilization(0.5);
+        report.set_mem_utilization(0.25);
+        report.set_qps(1200.0);
+        report.set_utilization("queue", 0.75);
+        report.set_named_metric("cache-hit-ratio", 0.9);
+        report
+    }
+
+    #[test]
+    fn round_trips_through_metadata() {
+        let mut metadata = MetadataMap::new();
+        report().insert_into(&mut metadata);
+        assert_eq!(LoadReport::from_metadata(&metadata), Some(report()));
+
+        let headers = metadata.into_sanitized_headers();
+        assert_eq!(LoadReport::from_headers(&headers), Some(report()));
+    }
+
+    #[test]
+    fn decodes_the_orca_wire_format() {
+        // `cpu_utilization: 0.5, rps: 7, utilization { key: "queue" value: 0.75 }`, encoded by
+        // protobuf, including the deprecated integer `rps` unknown to `LoadReport`.
+        let mut buf = vec![0x09];
+        buf.extend_from_slice(&0.5f64.to_le_bytes());
+        buf.extend_from_slice(&[0x18, 0x07]);
+        buf.extend_from_slice(&[0x2a, 0x10, 0x0a, 0x05]);
+        buf.extend_from_slice(b"queue");
+        buf.push(0x11);
+        buf.extend_from_slice(&0.75f64.to_le_bytes());
+
+        let report = LoadReport::decode(&buf).unwrap();
+        assert_eq!(report.cpu_utilization(), 0.5);
+        assert_eq!(report.utilization("queue"), Some(0.75));
+        assert_eq!(report.qps(), 0.0);
+    }
+
+    #[test]
+    fn rejects_truncated_reports() {
+        let buf = report().encode();
+        assert_eq!(LoadReport::decode(&buf[..buf.len() - 1]), None);
+    }
+
+    #[test]
+    fn prefers_application_utilization() {
+        let mut report = report();
+        assert_eq!(report.load(), 0.5);
+        report.set_application_utilization(0.1);
+        assert_eq!(report.load(), 0.1);
+
+        report.set_application_utilization(f64::NAN);
+        report.set_cpu_utilization(-1.0);
+        assert_eq!(report.load(), 0.0);
+    }
+}
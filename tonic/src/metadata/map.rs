@@ -197,6 +197,7 @@ pub struct OccupiedEntry<'a, VE: ValueEncoding> {
 }
 
 pub(crate) const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+pub(crate) const GRPC_PREVIOUS_RPC_ATTEMPTS_HEADER: &str = "grpc-previous-rpc-attempts";
 
 // ===== impl MetadataMap =====
 
@@ -251,6 +252,11 @@ impl MetadataMap {
         self.headers
     }
 
+    #[cfg(feature = "channel")]
+    pub(crate) fn as_headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
     pub(crate) fn into_sanitized_headers(mut self) -> http::HeaderMap {
         for r in &Self::GRPC_RESERVED_HEADERS {
             self.headers.remove(r);
@@ -32,11 +32,39 @@ pub use self::value::BinaryMetadataValue;
 pub use self::value::MetadataValue;
 use http::HeaderValue;
 
-pub(crate) use self::map::GRPC_TIMEOUT_HEADER;
+pub(crate) use self::map::{GRPC_PREVIOUS_RPC_ATTEMPTS_HEADER, GRPC_TIMEOUT_HEADER};
 
 /// HTTP Header `content-type` value for gRPC calls.
 pub const GRPC_CONTENT_TYPE: HeaderValue = HeaderValue::from_static("application/grpc");
 
+/// Build the `content-type` header value for the given gRPC content-subtype.
+///
+/// Falls back to [`GRPC_CONTENT_TYPE`] when no subtype is given or the subtype is not a valid
+/// header value.
+pub(crate) fn grpc_content_type(subtype: Option<&str>) -> HeaderValue {
+    subtype
+        .and_then(|subtype| HeaderValue::try_from(format!("application/grpc+{subtype}")).ok())
+        .unwrap_or(GRPC_CONTENT_TYPE)
+}
+
+/// Returns `true` if `value` is `application/grpc`, optionally followed by a `+subtype` or
+/// parameters.
+pub(crate) fn is_grpc_content_type(value: &HeaderValue) -> bool {
+    let value = value.as_bytes();
+    match value.strip_prefix(b"application/grpc") {
+        Some(rest) => rest.is_empty() || rest.starts_with(b"+") || rest.starts_with(b";"),
+        None => false,
+    }
+}
+
+/// Returns the content-subtype of a gRPC content type, e.g. `json` for
+/// `application/grpc+json; charset=utf-8`.
+pub(crate) fn grpc_content_subtype(value: &HeaderValue) -> Option<&str> {
+    let subtype = value.to_str().ok()?.strip_prefix("application/grpc+")?;
+    let subtype = subtype.split(';').next().unwrap_or_default().trim();
+    (!subtype.is_empty()).then_some(subtype)
+}
+
 /// The metadata::errors module contains types for errors that can occur
 /// while handling gRPC custom metadata.
 pub mod errors {
@@ -1,8 +1,8 @@
+use crate::extensions::WaitForReady;
 use crate::metadata::{MetadataMap, MetadataValue};
 #[cfg(feature = "server")]
-use crate::transport::server::TcpConnectInfo;
-#[cfg(all(feature = "server", feature = "_tls-any"))]
-use crate::transport::server::TlsConnectInfo;
+use crate::transport::server::{PeerInfo, TcpConnectInfo};
+use crate::Baggage;
 use http::Extensions;
 #[cfg(feature = "server")]
 use std::net::SocketAddr;
@@ -213,19 +213,14 @@ impl<T> Request<T> {
     /// This currently only works on the server side.
     #[cfg(feature = "server")]
     pub fn local_addr(&self) -> Option<SocketAddr> {
-        let addr = self
-            .extensions()
-            .get::<TcpConnectInfo>()
-            .and_then(|i| i.local_addr());
-
-        #[cfg(feature = "_tls-any")]
-        let addr = addr.or_else(|| {
-            self.extensions()
-                .get::<TlsConnectInfo<TcpConnectInfo>>()
-                .and_then(|i| i.get_ref().local_addr())
-        });
-
-        addr
+        self.extensions()
+            .get::<PeerInfo>()
+            .and_then(|i| i.local_addr)
+            .or_else(|| {
+                self.extensions()
+                    .get::<TcpConnectInfo>()
+                    .and_then(|i| i.local_addr())
+            })
     }
 
     /// Get the remote address of this connection.
@@ -235,19 +230,14 @@ impl<T> Request<T> {
     /// This currently only works on the server side.
     #[cfg(feature = "server")]
     pub fn remote_addr(&self) -> Option<SocketAddr> {
-        let addr = self
-            .extensions()
-            .get::<TcpConnectInfo>()
-            .and_then(|i| i.remote_addr());
-
-        #[cfg(feature = "_tls-any")]
-        let addr = addr.or_else(|| {
-            self.extensions()
-                .get::<TlsConnectInfo<TcpConnectInfo>>()
-                .and_then(|i| i.get_ref().remote_addr())
-        });
-
-        addr
+        self.extensions()
+            .get::<PeerInfo>()
+            .and_then(|i| i.remote_addr)
+            .or_else(|| {
+                self.extensions()
+                    .get::<TcpConnectInfo>()
+                    .and_then(|i| i.remote_addr())
+            })
     }
 
     /// Get the peer certificates of the connected client.
@@ -255,12 +245,13 @@ impl<T> Request<T> {
     /// This is used to fetch the certificates from the TLS session
     /// and is mostly used for mTLS. This currently only returns
     /// `Some` on the server side of the `transport` server with
-    /// TLS enabled connections.
+    /// TLS enabled connections. They are available to interceptors as well
+    /// as to handlers.
     #[cfg(all(feature = "server", feature = "_tls-any"))]
     pub fn peer_certs(&self) -> Option<Arc<Vec<CertificateDer<'static>>>> {
         self.extensions()
-            .get::<TlsConnectInfo<TcpConnectInfo>>()
-            .and_then(|i| i.peer_certs())
+            .get::<PeerInfo>()
+            .and_then(|i| i.peer_certs.clone())
     }
 
     /// Set the max duration the request is allowed to take.
@@ -296,6 +287,76 @@ impl<T> Request<T> {
             .insert(crate::metadata::GRPC_TIMEOUT_HEADER, value);
     }
 
+    /// Mark this request as a retry of a call that was already tried `attempts` times.
+    ///
+    /// This sets the `grpc-previous-rpc-attempts` metadata, which tells the server, and its
+    /// logs, that the call is a retry. Tonic servers expose it to handlers as the
+    /// [`PreviousRpcAttempts`](crate::PreviousRpcAttempts) extension. Tonic doesn't retry calls
+    /// by itself, so retry logic should set this on every attempt but the first.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use tonic::Request;
+    ///
+    /// let mut request = Request::new(());
+    ///
+    /// // the first retry
+    /// request.set_previous_rpc_attempts(1);
+    ///
+    /// let value = request.metadata().get("grpc-previous-rpc-attempts").unwrap();
+    /// assert_eq!(value, "1");
+    /// ```
+    pub fn set_previous_rpc_attempts(&mut self, attempts: u32) {
+        self.metadata_mut().insert(
+            crate::metadata::GRPC_PREVIOUS_RPC_ATTEMPTS_HEADER,
+            MetadataValue::from(attempts),
+        );
+    }
+
+    /// Set the `baggage` header of this request to `baggage`, e.g. to forward the [`Baggage`]
+    /// of an inbound request to the services a handler calls.
+    ///
+    /// Entries beyond the limits of [`Baggage`] are dropped. Empty baggage removes the header.
+    ///
+    /// ```
+    /// use tonic::{Baggage, Request};
+    ///
+    /// let mut baggage = Baggage::new();
+    /// baggage.insert("tenant", "acme");
+    ///
+    /// let mut request = Request::new(());
+    /// request.set_baggage(&baggage);
+    ///
+    /// let value = request.metadata().get("baggage").unwrap();
+    /// assert_eq!(value, "tenant=acme");
+    /// ```
+    pub fn set_baggage(&mut self, baggage: &Baggage) {
+        match baggage.to_header() {
+            Some(value) => {
+                self.metadata_mut().insert(
+                    crate::baggage::BAGGAGE_HEADER,
+                    MetadataValue::unchecked_from_header_value(value),
+                );
+            }
+            None => {
+                self.metadata_mut().remove(crate::baggage::BAGGAGE_HEADER);
+            }
+        }
+    }
+
+    /// Set whether this request should wait for the channel to be ready.
+    ///
+    /// By default, requests fail fast with [`Code::Unavailable`] when the channel
+    /// cannot connect. With wait-for-ready enabled, the request is instead held
+    /// until a connection is established or its `grpc-timeout` expires. This
+    /// overrides the channel's default set with `Endpoint::wait_for_ready`.
+    ///
+    /// [`Code::Unavailable`]: crate::Code::Unavailable
+    pub fn set_wait_for_ready(&mut self, wait_for_ready: bool) {
+        self.extensions_mut().insert(WaitForReady(wait_for_ready));
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &Extensions {
         &self.extensions
@@ -394,7 +455,7 @@ mod sealed {
     pub trait Sealed {}
 }
 
-fn duration_to_grpc_timeout(duration: Duration) -> String {
+pub(crate) fn duration_to_grpc_timeout(duration: Duration) -> String {
     fn try_format<T: Into<u128>>(
         duration: Duration,
         unit: char,
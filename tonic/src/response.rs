@@ -1,8 +1,36 @@
 use http::Extensions;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
 
 use crate::metadata::MetadataMap;
 
 /// A gRPC response and metadata from an RPC call.
+///
+/// # Initial metadata
+///
+/// On the server, the [metadata](Response::metadata_mut) of the response is sent as the initial
+/// HEADERS frame as soon as the handler returns, before any message is encoded. A streaming
+/// handler that needs to send headers, e.g. a resumption token, long before its first message
+/// should return right away with a stream that does the remaining work lazily:
+///
+/// ```rust
+/// # use tonic::{Response, Status};
+/// # use tokio_stream::{Stream, StreamExt};
+/// # struct Item;
+/// # async fn next_item() -> Item { Item }
+/// fn stream_call() -> Response<impl Stream<Item = Result<Item, Status>>> {
+///     // `next_item` only runs once the headers have been flushed to the client.
+///     let stream = tokio_stream::once(()).then(|_| async { Ok(next_item().await) });
+///
+///     let mut response = Response::new(stream);
+///     response
+///         .metadata_mut()
+///         .insert("resume-token", "abc".parse().unwrap());
+///     response
+/// }
+/// ```
 #[derive(Debug)]
 pub struct Response<T> {
     metadata: MetadataMap,
@@ -112,6 +140,48 @@ impl<T> Response<T> {
         &mut self.extensions
     }
 
+    /// Send the metadata set through `trailers` in the trailers of this response.
+    ///
+    /// The metadata is sent alongside `grpc-status` once the response stream ends, also when it
+    /// yields no message at all, so a streaming handler can move a clone of `trailers` into its
+    /// stream to set values that are only known after the last message:
+    ///
+    /// ```rust
+    /// # use tonic::{Response, Status, TrailingMetadata};
+    /// # use tokio_stream::{Stream, StreamExt};
+    /// fn stream_call() -> Response<impl Stream<Item = Result<u32, Status>>> {
+    ///     let trailers = TrailingMetadata::new();
+    ///
+    ///     let stream = tokio_stream::iter(0..3).map({
+    ///         let trailers = trailers.clone();
+    ///         move |i| {
+    ///             trailers.update(|metadata| metadata.insert("item-count", (i + 1).into()));
+    ///             Ok(i)
+    ///         }
+    ///     });
+    ///
+    ///     let mut response = Response::new(stream);
+    ///     response.set_trailing_metadata(trailers);
+    ///     response
+    /// }
+    /// ```
+    ///
+    /// The trailers are read once the stream ends, so everything set before the stream yields
+    /// `None` is sent. A [`Status`](crate::Status) ending the stream early is sent with both its
+    /// own metadata and this one.
+    pub fn set_trailing_metadata(&mut self, trailers: TrailingMetadata) {
+        self.extensions.insert(trailers);
+    }
+
+    /// Report the load of the backend to the client in the trailers of this response, see
+    /// [`LoadReport`](crate::LoadReport).
+    ///
+    /// The report is sent next to any metadata set through
+    /// [`set_trailing_metadata`](Self::set_trailing_metadata), replacing a load report set there.
+    pub fn set_load_report(&mut self, report: crate::LoadReport) {
+        self.extensions.insert(report);
+    }
+
     /// Disable compression of the response body.
     ///
     /// This disables compression of the body of this response, even if compression is enabled on
@@ -119,12 +189,111 @@ impl<T> Response<T> {
     ///
     /// **Note**: This only has effect on responses to unary requests and responses to client to
     /// server streams. Response streams (server to client stream and bidirectional streams) will
-    /// still be compressed according to the configuration of the server.
+    /// still be compressed according to the configuration of the server, use
+    /// [`set_message_compression`](Self::set_message_compression) to skip compressing some of
+    /// their messages.
     #[cfg(feature = "gzip")]
     pub fn disable_compression(&mut self) {
         self.extensions_mut()
             .insert(crate::codec::compression::SingleMessageCompressionOverride::Disable);
     }
+
+    /// Decide per message whether to compress the messages of this response through
+    /// `compression`.
+    ///
+    /// Messages are encoded as soon as the response stream yields them, so a streaming handler
+    /// can move a clone of `compression` into its stream and disable compression right before
+    /// yielding a message that doesn't benefit from it, e.g. an already compressed blob:
+    ///
+    /// ```rust
+    /// # use tonic::{MessageCompression, Response, Status};
+    /// # use tokio_stream::{Stream, StreamExt};
+    /// # struct Chunk { data: Vec<u8>, compressed: bool }
+    /// fn stream_call(
+    ///     chunks: impl Stream<Item = Chunk>,
+    /// ) -> Response<impl Stream<Item = Result<Vec<u8>, Status>>> {
+    ///     let compression = MessageCompression::new();
+    ///
+    ///     let stream = chunks.map({
+    ///         let compression = compression.clone();
+    ///         move |chunk| {
+    ///             compression.set_enabled(!chunk.compressed);
+    ///             Ok(chunk.data)
+    ///         }
+    ///     });
+    ///
+    ///     let mut response = Response::new(stream);
+    ///     response.set_message_compression(compression);
+    ///     response
+    /// }
+    /// ```
+    ///
+    /// The `grpc-encoding` of the response stays the one negotiated with the client, messages
+    /// encoded while compression is disabled are sent with the compressed flag unset. This has no
+    /// effect when the response isn't compressed in the first place.
+    pub fn set_message_compression(&mut self, compression: MessageCompression) {
+        self.extensions.insert(compression);
+    }
+}
+
+/// A handle to the trailing metadata of a [`Response`], see
+/// [`Response::set_trailing_metadata`].
+#[derive(Clone, Debug, Default)]
+pub struct TrailingMetadata(Arc<Mutex<MetadataMap>>);
+
+impl TrailingMetadata {
+    /// Create a handle to empty trailing metadata.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the trailing metadata with `f`.
+    pub fn update<R>(&self, f: impl FnOnce(&mut MetadataMap) -> R) -> R {
+        f(&mut self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Take the metadata set so far, leaving it empty.
+    pub(crate) fn take(&self) -> MetadataMap {
+        self.update(std::mem::take)
+    }
+}
+
+/// A switch for compressing the messages of a [`Response`], see
+/// [`Response::set_message_compression`].
+#[derive(Clone, Debug)]
+pub struct MessageCompression(Arc<AtomicBool>);
+
+impl MessageCompression {
+    /// Create a switch with compression enabled.
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+
+    /// Compress the messages encoded from now on, if the response is compressed.
+    pub fn enable(&self) {
+        self.set_enabled(true);
+    }
+
+    /// Send the messages encoded from now on uncompressed.
+    pub fn disable(&self) {
+        self.set_enabled(false);
+    }
+
+    /// Enable or disable compression of the messages encoded from now on.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether the next message is compressed, if the response is.
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for MessageCompression {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
@@ -1,23 +1,30 @@
 use crate::codec::compression::{
-    CompressionEncoding, EnabledCompressionEncodings, SingleMessageCompressionOverride,
+    CompressionEncoding, EnabledCompressionEncodings, RequestEncoding,
+    SingleMessageCompressionOverride,
 };
-use crate::codec::EncodeBody;
-use crate::metadata::GRPC_CONTENT_TYPE;
+use crate::codec::decode::Inspector;
+use crate::codec::{EncodeBody, StreamingFlushMode, DEFAULT_MAX_RECV_MESSAGE_SIZE, HEADER_SIZE};
+use crate::extensions::{MaxRequestMessages, MaxResponseMessages, MessageInspectors};
+use crate::metadata::{grpc_content_subtype, grpc_content_type, is_grpc_content_type};
 use crate::{
     body::Body,
     codec::{Codec, Streaming},
-    server::{ClientStreamingService, ServerStreamingService, StreamingService, UnaryService},
+    server::{
+        timing::ServerTiming, ClientStreamingService, ServerStreamingService, StreamingService,
+        UnaryService,
+    },
     Request, Status,
 };
+use http::{header::CONTENT_LENGTH, HeaderValue};
 use http_body::Body as HttpBody;
-use std::{fmt, pin::pin};
+use std::{fmt, future::Future, pin::pin, time::Instant};
 use tokio_stream::{Stream, StreamExt};
 
 macro_rules! t {
-    ($result:expr) => {
+    ($result:expr, $content_type:expr) => {
         match $result {
             Ok(value) => value,
-            Err(status) => return status.into_http(),
+            Err(status) => return status_into_http(status, $content_type),
         }
     };
 }
@@ -123,6 +130,12 @@ where
 
     /// Limits the maximum size of a decoded message.
     ///
+    /// Unary requests that advertise a `content-length` larger than this limit are rejected with
+    /// [`Code::ResourceExhausted`] before any of the body is read. Compressed messages are
+    /// rejected with the same code as soon as decompressing them exceeds this limit.
+    ///
+    /// [`Code::ResourceExhausted`]: crate::Code::ResourceExhausted
+    ///
     /// # Example
     ///
     /// The most common way of using this is through a server generated by tonic-build:
@@ -230,6 +243,8 @@ where
             req.headers(),
             self.send_compression_encodings,
         );
+        let content_type = self.response_content_type(&req);
+        let timing = req.extensions().get::<ServerTiming>().cloned();
 
         let request = match self.map_request_unary(req).await {
             Ok(r) => r,
@@ -239,12 +254,15 @@ where
                     accept_encoding,
                     SingleMessageCompressionOverride::default(),
                     self.max_encoding_message_size,
+                    content_type,
+                    StreamingFlushMode::default(),
+                    None,
+                    None,
                 );
             }
         };
 
-        let response = service
-            .call(request)
+        let response = handle(timing.as_ref(), service.call(request))
             .await
             .map(|r| r.map(|m| tokio_stream::once(Ok(m))));
 
@@ -255,6 +273,10 @@ where
             accept_encoding,
             compression_override,
             self.max_encoding_message_size,
+            content_type,
+            StreamingFlushMode::default(),
+            None,
+            timing,
         )
     }
 
@@ -274,6 +296,13 @@ where
             req.headers(),
             self.send_compression_encodings,
         );
+        let content_type = self.response_content_type(&req);
+        let timing = req.extensions().get::<ServerTiming>().cloned();
+        let flush_mode = streaming_flush_mode(&req);
+        let max_message_count = req
+            .extensions()
+            .get::<MaxResponseMessages>()
+            .map(|limit| limit.0);
 
         let request = match self.map_request_unary(req).await {
             Ok(r) => r,
@@ -283,19 +312,27 @@ where
                     accept_encoding,
                     SingleMessageCompressionOverride::default(),
                     self.max_encoding_message_size,
+                    content_type,
+                    flush_mode,
+                    max_message_count,
+                    None,
                 );
             }
         };
 
-        let response = service.call(request).await;
+        let response = handle(timing.as_ref(), service.call(request)).await;
 
         self.map_response(
             response,
             accept_encoding,
-            // disabling compression of individual stream items must be done on
-            // the items themselves
+            // disabling compression of individual stream items is done through
+            // the `MessageCompression` of the response
             SingleMessageCompressionOverride::default(),
             self.max_encoding_message_size,
+            content_type,
+            flush_mode,
+            max_message_count,
+            timing,
         )
     }
 
@@ -314,11 +351,12 @@ where
             req.headers(),
             self.send_compression_encodings,
         );
+        let content_type = self.response_content_type(&req);
+        let timing = req.extensions().get::<ServerTiming>().cloned();
 
-        let request = t!(self.map_request_streaming(req));
+        let request = t!(self.map_request_streaming(req), content_type);
 
-        let response = service
-            .call(request)
+        let response = handle(timing.as_ref(), service.call(request))
             .await
             .map(|r| r.map(|m| tokio_stream::once(Ok(m))));
 
@@ -329,6 +367,10 @@ where
             accept_encoding,
             compression_override,
             self.max_encoding_message_size,
+            content_type,
+            StreamingFlushMode::default(),
+            None,
+            timing,
         )
     }
 
@@ -348,16 +390,27 @@ where
             req.headers(),
             self.send_compression_encodings,
         );
+        let content_type = self.response_content_type(&req);
+        let timing = req.extensions().get::<ServerTiming>().cloned();
+        let flush_mode = streaming_flush_mode(&req);
+        let max_message_count = req
+            .extensions()
+            .get::<MaxResponseMessages>()
+            .map(|limit| limit.0);
 
-        let request = t!(self.map_request_streaming(req));
+        let request = t!(self.map_request_streaming(req), content_type);
 
-        let response = service.call(request).await;
+        let response = handle(timing.as_ref(), service.call(request)).await;
 
         self.map_response(
             response,
             accept_encoding,
             SingleMessageCompressionOverride::default(),
             self.max_encoding_message_size,
+            content_type,
+            flush_mode,
+            max_message_count,
+            timing,
         )
     }
 
@@ -369,16 +422,25 @@ where
         B: HttpBody + Send + 'static,
         B::Error: Into<crate::BoxError> + Send,
     {
+        self.check_content_subtype(&request)?;
         let request_compression_encoding = self.request_encoding_if_supported(&request)?;
+        self.check_content_length(&request)?;
 
-        let (parts, body) = request.into_parts();
+        let (mut parts, body) = request.into_parts();
+        parts
+            .extensions
+            .insert(RequestEncoding(request_compression_encoding));
+        let inspector = message_inspector(&parts.extensions);
+        let timing = parts.extensions.get::<ServerTiming>().cloned();
 
         let mut stream = pin!(Streaming::new_request(
             self.codec.decoder(),
             body,
             request_compression_encoding,
             self.max_decoding_message_size,
-        ));
+        )
+        .with_inspector(inspector)
+        .with_timing(timing));
 
         let message = stream
             .try_next()
@@ -396,13 +458,22 @@ where
 
     fn map_request_streaming<B>(
         &mut self,
-        request: http::Request<B>,
+        mut request: http::Request<B>,
     ) -> Result<Request<Streaming<T::Decode>>, Status>
     where
         B: HttpBody + Send + 'static,
         B::Error: Into<crate::BoxError> + Send,
     {
+        self.check_content_subtype(&request)?;
         let encoding = self.request_encoding_if_supported(&request)?;
+        request.extensions_mut().insert(RequestEncoding(encoding));
+
+        let max_message_count = request
+            .extensions()
+            .get::<MaxRequestMessages>()
+            .map(|limit| limit.0);
+        let inspector = message_inspector(request.extensions());
+        let timing = request.extensions().get::<ServerTiming>().cloned();
 
         let request = request.map(|body| {
             Streaming::new_request(
@@ -411,29 +482,44 @@ where
                 encoding,
                 self.max_decoding_message_size,
             )
+            .with_max_message_count(max_message_count)
+            .with_inspector(inspector)
+            .with_timing(timing)
         });
 
         Ok(Request::from_http(request))
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn map_response<B>(
         &mut self,
         response: Result<crate::Response<B>, Status>,
         accept_encoding: Option<CompressionEncoding>,
         compression_override: SingleMessageCompressionOverride,
         max_message_size: Option<usize>,
+        content_type: HeaderValue,
+        flush_mode: StreamingFlushMode,
+        max_message_count: Option<usize>,
+        timing: Option<ServerTiming>,
     ) -> http::Response<Body>
     where
         B: Stream<Item = Result<T::Encode, Status>> + Send + 'static,
     {
-        let response = t!(response);
+        let response = t!(response, content_type);
 
         let (mut parts, body) = response.into_http().into_parts();
+        let mut trailing_metadata = parts.extensions.remove::<crate::TrailingMetadata>();
+        if let Some(report) = parts.extensions.remove::<crate::LoadReport>() {
+            trailing_metadata
+                .get_or_insert_with(crate::TrailingMetadata::new)
+                .update(|metadata| report.insert_into(metadata));
+        }
+        let message_compression = parts.extensions.remove::<crate::MessageCompression>();
 
         // Set the content type
         parts
             .headers
-            .insert(http::header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+            .insert(http::header::CONTENT_TYPE, content_type);
 
         #[cfg(any(feature = "gzip", feature = "zstd"))]
         if let Some(encoding) = accept_encoding {
@@ -450,11 +536,69 @@ where
             accept_encoding,
             compression_override,
             max_message_size,
-        );
+        )
+        .with_trailing_metadata(trailing_metadata)
+        .with_message_compression(message_compression)
+        .with_flush_mode(flush_mode)
+        .with_max_message_count(max_message_count)
+        .with_timing(timing);
 
         http::Response::from_parts(parts, Body::new(body))
     }
 
+    /// Echo the gRPC content type of the request, falling back to the codec's content-subtype.
+    fn response_content_type<B>(&self, request: &http::Request<B>) -> HeaderValue {
+        request
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .filter(|value| is_grpc_content_type(value))
+            .cloned()
+            .unwrap_or_else(|| grpc_content_type(self.codec.content_subtype()))
+    }
+
+    /// Reject a request whose content-subtype names a different codec than the one of this
+    /// service. Requests without a content-subtype, and codecs without one, are always accepted.
+    fn check_content_subtype<B>(&self, request: &http::Request<B>) -> Result<(), Status> {
+        let requested = request
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(grpc_content_subtype);
+
+        match (requested, self.codec.content_subtype()) {
+            (Some(requested), Some(supported)) if !requested.eq_ignore_ascii_case(supported) => {
+                Err(Status::internal(format!(
+                    "no codec registered for content-subtype {requested}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject a unary request up front if its advertised `content-length` cannot fit within the
+    /// decoding limit. HTTP/2 clients usually omit the header, in which case the limit is still
+    /// enforced by the decoder once the message header is read.
+    fn check_content_length<B>(&self, request: &http::Request<B>) -> Result<(), Status> {
+        let content_length = request
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<usize>().ok());
+
+        if let Some(content_length) = content_length {
+            let limit = self
+                .max_decoding_message_size
+                .unwrap_or(DEFAULT_MAX_RECV_MESSAGE_SIZE);
+            if content_length > limit.saturating_add(HEADER_SIZE) {
+                return Err(Status::resource_exhausted(format!(
+                    "Error, request content-length too large: found {} bytes, the limit is: {} bytes",
+                    content_length, limit
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     fn request_encoding_if_supported<B>(
         &self,
         request: &http::Request<B>,
@@ -482,6 +626,24 @@ impl<T: fmt::Debug> fmt::Debug for Grpc<T> {
     }
 }
 
+fn status_into_http(status: Status, content_type: HeaderValue) -> http::Response<Body> {
+    let mut response = status.into_http();
+    response
+        .headers_mut()
+        .insert(http::header::CONTENT_TYPE, content_type);
+    response
+}
+
+/// The flush mode of the response to `request`, see
+/// [`Server::streaming_flush_mode`](crate::transport::Server::streaming_flush_mode).
+fn streaming_flush_mode<B>(request: &http::Request<B>) -> StreamingFlushMode {
+    request
+        .extensions()
+        .get::<StreamingFlushMode>()
+        .copied()
+        .unwrap_or_default()
+}
+
 fn compression_override_from_response<B, E>(
     res: &Result<crate::Response<B>, E>,
 ) -> SingleMessageCompressionOverride {
@@ -495,3 +657,73 @@ fn compression_override_from_response<B, E>(
         })
         .unwrap_or_default()
 }
+
+/// Awaits the response of a handler, recording how long it took in `timing`.
+async fn handle<F: Future>(timing: Option<&ServerTiming>, response: F) -> F::Output {
+    let started_at = Instant::now();
+    let response = response.await;
+    if let Some(timing) = timing {
+        timing.handled(started_at.elapsed());
+    }
+    response
+}
+
+/// The inspector running the [`MessageInspectors`] of a request on its decoded messages, if any.
+fn message_inspector<M: 'static>(extensions: &http::Extensions) -> Option<Inspector<M>> {
+    let inspectors = extensions.get::<MessageInspectors>()?.clone();
+    Some(Box::new(move |message: &M| inspectors.inspect(message)))
+}
+
+#[cfg(all(test, feature = "prost", feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::{codec::ProstCodec, transport::server::TcpConnectInfo, Response, TrailingMetadata};
+    use bytes::{BufMut, Bytes, BytesMut};
+    use http_body_util::{BodyExt, Full};
+    use prost::Message;
+
+    #[tokio::test]
+    async fn unary_handler_round_trips_typed_request_and_response() {
+        let remote_addr = "127.0.0.1:50051".parse().unwrap();
+
+        let message = "ping".to_owned().encode_to_vec();
+        let mut body = BytesMut::new();
+        body.put_u8(0);
+        body.put_u32(message.len() as u32);
+        body.put_slice(&message);
+        let mut req = http::Request::post("/test.Test/Echo")
+            .header("content-type", "application/grpc")
+            .header("x-greeting", "hello")
+            .body(Full::new(body.freeze()))
+            .unwrap();
+        req.extensions_mut().insert(TcpConnectInfo {
+            local_addr: None,
+            remote_addr: Some(remote_addr),
+        });
+
+        let svc = tower::service_fn(move |req: Request<String>| async move {
+            assert_eq!(req.metadata().get("x-greeting").unwrap(), "hello");
+            assert_eq!(req.remote_addr(), Some(remote_addr));
+
+            let mut res = Response::new(format!("{}, pong", req.into_inner()));
+            res.metadata_mut()
+                .insert("x-reply", "header".parse().unwrap());
+            let trailers = TrailingMetadata::new();
+            trailers.update(|metadata| metadata.insert("x-reply", "trailer".parse().unwrap()));
+            res.set_trailing_metadata(trailers);
+            Ok::<_, Status>(res)
+        });
+        let res = Grpc::new(ProstCodec::<String, String>::default())
+            .unary(svc, req)
+            .await;
+
+        assert_eq!(res.headers()["x-reply"], "header");
+        let body = res.into_body().collect().await.unwrap();
+        let trailers = body.trailers().unwrap().clone();
+        let body: Bytes = body.to_bytes();
+        assert_eq!(body[0], 0);
+        assert_eq!(String::decode(&body[HEADER_SIZE..]).unwrap(), "ping, pong");
+        assert_eq!(trailers["grpc-status"], "0");
+        assert_eq!(trailers["x-reply"], "trailer");
+    }
+}
@@ -10,6 +10,7 @@
 
 mod grpc;
 mod service;
+pub(crate) mod timing;
 
 pub use self::grpc::Grpc;
 pub use self::service::{
@@ -23,4 +24,11 @@ pub trait NamedService {
     ///
     /// [here]: https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md#requests
     const NAME: &'static str;
+
+    /// The names of the methods of the service, e.g. `SayHello`, see [`Routes::services`].
+    ///
+    /// Services generated by `tonic-build` list their methods, others may leave this empty.
+    ///
+    /// [`Routes::services`]: crate::service::Routes::services
+    const METHODS: &'static [&'static str] = &[];
 }
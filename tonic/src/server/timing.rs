@@ -0,0 +1,112 @@
+use http::HeaderValue;
+use std::{
+    fmt::Write as _,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// The request header a client sends to get the timing of its call in the trailers.
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub(crate) const SERVER_TIMING_REQUEST_HEADER: &str = "x-server-timing";
+/// The trailer carrying the timing of a call, in the format of the HTTP `Server-Timing` header.
+pub(crate) const SERVER_TIMING_TRAILER: &str = "server-timing";
+
+/// The time a call spent in each phase of the server, sent in the trailers of its response, see
+/// [`Server::server_timing`](crate::transport::Server::server_timing).
+#[derive(Clone, Debug)]
+pub(crate) struct ServerTiming(Arc<Phases>);
+
+#[derive(Debug)]
+struct Phases {
+    received_at: Instant,
+    queue: OnceLock<Duration>,
+    handler: OnceLock<Duration>,
+    /// Nanoseconds spent decoding request messages.
+    decode: AtomicU64,
+    /// Nanoseconds spent encoding response messages.
+    encode: AtomicU64,
+}
+
+impl ServerTiming {
+    /// Starts timing a call received now.
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(Phases {
+            received_at: Instant::now(),
+            queue: OnceLock::new(),
+            handler: OnceLock::new(),
+            decode: AtomicU64::new(0),
+            encode: AtomicU64::new(0),
+        }))
+    }
+
+    /// Ends the queue phase, as the handler of the call starts now.
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub(crate) fn dequeued(&self) {
+        let _ = self.0.queue.set(self.0.received_at.elapsed());
+    }
+
+    /// Records the time the handler took to return the response.
+    pub(crate) fn handled(&self, handler: Duration) {
+        let _ = self.0.handler.set(handler);
+    }
+
+    pub(crate) fn add_decode(&self, decode: Duration) {
+        add(&self.0.decode, decode);
+    }
+
+    pub(crate) fn add_encode(&self, encode: Duration) {
+        add(&self.0.encode, encode);
+    }
+
+    /// The phases as a `Server-Timing` header value, in milliseconds.
+    pub(crate) fn to_header_value(&self) -> HeaderValue {
+        let phases = [
+            ("queue", self.0.queue.get().copied().unwrap_or_default()),
+            ("decode", total(&self.0.decode)),
+            ("handler", self.0.handler.get().copied().unwrap_or_default()),
+            ("encode", total(&self.0.encode)),
+        ];
+
+        let mut value = String::new();
+        for (name, duration) in phases {
+            if !value.is_empty() {
+                value.push_str(", ");
+            }
+            let _ = write!(value, "{};dur={:.3}", name, duration.as_secs_f64() * 1000.0);
+        }
+        HeaderValue::try_from(value).expect("server-timing should be a valid header value")
+    }
+}
+
+fn add(total: &AtomicU64, duration: Duration) {
+    let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+    total.fetch_add(nanos, Ordering::Relaxed);
+}
+
+fn total(total: &AtomicU64) -> Duration {
+    Duration::from_nanos(total.load(Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_the_phases_in_milliseconds() {
+        let timing = ServerTiming::new();
+        timing.add_decode(Duration::from_micros(250));
+        timing.add_decode(Duration::from_micros(250));
+        timing.handled(Duration::from_millis(12));
+        timing.add_encode(Duration::from_micros(1500));
+
+        // The call was never dequeued.
+        assert_eq!(
+            timing.to_header_value(),
+            "queue;dur=0.000, decode;dur=0.500, handler;dur=12.000, encode;dur=1.500"
+        );
+    }
+}
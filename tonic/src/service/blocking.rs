@@ -0,0 +1,130 @@
+//! Adapter running synchronous unary handlers on the blocking thread pool.
+
+use crate::{
+    body::Body,
+    codec::ProstCodec,
+    server::{Grpc, UnaryService},
+    Request, Response, Status,
+};
+use std::{convert::Infallible, future::Future, pin::Pin, sync::Arc};
+use tokio_util::sync::CancellationToken;
+use tower::util::BoxCloneService;
+
+/// A unary method implemented by a synchronous function, see [`Routes::blocking_unary`].
+///
+/// [`Routes::blocking_unary`]: crate::service::Routes::blocking_unary
+struct BlockingUnary<F> {
+    f: Arc<F>,
+}
+
+impl<F, Req, Res> UnaryService<Req> for BlockingUnary<F>
+where
+    F: Fn(Request<Req>) -> Result<Response<Res>, Status> + Send + Sync + 'static,
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    type Response = Res;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<Res>, Status>> + Send + 'static>>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        let f = self.f.clone();
+        Box::pin(async move {
+            let cancelled = request
+                .extensions()
+                .get::<CancellationToken>()
+                .is_some_and(CancellationToken::is_cancelled);
+            if cancelled {
+                return Err(Status::cancelled(
+                    "call was cancelled before it was handled",
+                ));
+            }
+
+            match tokio::task::spawn_blocking(move || f(request).map_err(Box::new)).await {
+                Ok(result) => result.map_err(|status| *status),
+                Err(err) if err.is_panic() => Err(Status::internal("handler panicked")),
+                Err(_) => Err(Status::unavailable("runtime is shutting down")),
+            }
+        })
+    }
+}
+
+/// The service answering a unary method with `f`, run on the blocking thread pool.
+pub(crate) fn service<F, Req, Res>(
+    f: F,
+) -> BoxCloneService<http::Request<Body>, http::Response<Body>, Infallible>
+where
+    F: Fn(Request<Req>) -> Result<Response<Res>, Status> + Send + Sync + 'static,
+    Req: prost::Message + Default + Send + 'static,
+    Res: prost::Message + Send + 'static,
+{
+    let f = Arc::new(f);
+    BoxCloneService::new(tower::service_fn(move |req: http::Request<Body>| {
+        let method = BlockingUnary { f: f.clone() };
+        async move {
+            let mut grpc = Grpc::new(ProstCodec::<Res, Req>::default());
+            Ok(grpc.unary(method, req).await)
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{body::Body, service::Routes, Response, Status};
+    use http::HeaderValue;
+    use std::time::{Duration, Instant};
+    use tower::ServiceExt;
+
+    const DELAY: Duration = Duration::from_millis(200);
+
+    async fn call(routes: Routes, path: &str) -> http::Response<Body> {
+        let req = http::Request::post(path)
+            .header("content-type", "application/grpc")
+            .body(Body::new(http_body_util::Full::new(
+                bytes::Bytes::from_static(&[0, 0, 0, 0, 0]),
+            )))
+            .unwrap();
+        routes.oneshot(req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn blocking_handlers_do_not_stall_the_runtime() {
+        let routes =
+            Routes::default().blocking_unary("/test.Test/Sleep", |_: crate::Request<()>| {
+                std::thread::sleep(DELAY);
+                Ok(Response::new(()))
+            });
+
+        // A single-threaded runtime runs all tasks on one thread, which handlers blocking it
+        // would serialize.
+        let start = Instant::now();
+        let calls: Vec<_> = (0..8)
+            .map(|_| tokio::spawn(call(routes.clone(), "/test.Test/Sleep")))
+            .collect();
+        let mut ticks = 0;
+        let mut interval = tokio::time::interval(DELAY / 10);
+        while calls.iter().any(|call| !call.is_finished()) {
+            interval.tick().await;
+            ticks += 1;
+        }
+
+        assert!(start.elapsed() < DELAY * 4, "{:?}", start.elapsed());
+        assert!(ticks >= 5, "{ticks}");
+        for call in calls {
+            assert_eq!(call.await.unwrap().status(), http::StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn maps_panics_to_internal() {
+        let routes = Routes::default().blocking_unary(
+            "/test.Test/Panic",
+            |_: crate::Request<()>| -> Result<Response<()>, Status> { panic!("handler failed") },
+        );
+
+        let res = call(routes, "/test.Test/Panic").await;
+        assert_eq!(
+            res.headers().get(Status::GRPC_STATUS),
+            Some(&HeaderValue::from_static("13"))
+        );
+    }
+}
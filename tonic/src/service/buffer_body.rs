@@ -0,0 +1,298 @@
+//! Middleware buffering unary request bodies for interceptors.
+
+use crate::{body::Body, BoxError, Status};
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use std::{
+    collections::HashSet,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A layer buffering the request bodies of some unary methods, so interceptors can read them.
+///
+/// The whole body of a call to one of the methods is read before the call goes on, and made
+/// available to the inner services, like interceptors, as a [`BufferedBody`] request extension.
+/// The handler then receives the same body as usual. This allows e.g. verifying a signature over
+/// the request payload. Bodies larger than `max_size` are rejected with
+/// [`Code::ResourceExhausted`].
+///
+/// Only list unary methods: the body of a streaming call ends with the call, so it would wait
+/// for the client to finish sending before the handler starts.
+///
+/// ```
+/// # use tonic::service::{BufferBodyLayer, BufferedBody, InterceptorLayer};
+/// # use tonic::{Request, Status};
+/// let buffer = BufferBodyLayer::new(["/payments.Payments/Transfer"], 64 * 1024);
+/// let verify = InterceptorLayer::new(|request: Request<()>| {
+///     if let Some(body) = request.extensions().get::<BufferedBody>() {
+///         // Check the signature of `body.bytes()` against the request metadata.
+///     }
+///     Ok::<_, Status>(request)
+/// });
+///
+/// // Apply both to all services of a server, the buffer first so it wraps the interceptor,
+/// // through `Server::builder().layer(buffer).layer(verify)`.
+/// ```
+///
+/// [`Code::ResourceExhausted`]: crate::Code::ResourceExhausted
+#[derive(Clone)]
+pub struct BufferBodyLayer {
+    methods: Arc<HashSet<String>>,
+    max_size: usize,
+}
+
+impl BufferBodyLayer {
+    /// Create a layer buffering the request bodies of `methods`, up to `max_size` bytes.
+    ///
+    /// Methods are identified by their full path, like `/payments.Payments/Transfer`.
+    pub fn new<I, P>(methods: I, max_size: usize) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        Self {
+            methods: Arc::new(methods.into_iter().map(Into::into).collect()),
+            max_size,
+        }
+    }
+}
+
+impl fmt::Debug for BufferBodyLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferBodyLayer")
+            .field("methods", &self.methods)
+            .field("max_size", &self.max_size)
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for BufferBodyLayer {
+    type Service = BufferBody<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BufferBody {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The request body of a call, buffered by [`BufferBodyLayer`].
+///
+/// The bytes are the body as received, i.e. the length-prefixed gRPC message, which may be
+/// compressed if the request's `grpc-encoding` says so.
+#[derive(Debug, Clone)]
+pub struct BufferedBody(Bytes);
+
+impl BufferedBody {
+    /// The bytes of the request body.
+    pub fn bytes(&self) -> &Bytes {
+        &self.0
+    }
+}
+
+/// A service buffering request bodies, see [`BufferBodyLayer`].
+#[derive(Clone)]
+pub struct BufferBody<S> {
+    inner: S,
+    layer: BufferBodyLayer,
+}
+
+impl<S: fmt::Debug> fmt::Debug for BufferBody<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferBody")
+            .field("inner", &self.inner)
+            .field("layer", &self.layer)
+            .finish()
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for BufferBody<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BufferBodyFuture<S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // The inner service was driven to readiness, so take it and leave the clone behind.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if !self.layer.methods.contains(req.uri().path()) {
+            let future = inner.call(req);
+            return BufferBodyFuture {
+                inner: Box::pin(async move { Ok(future.await?.map(Body::new)) }),
+            };
+        }
+
+        let max_size = self.layer.max_size;
+        BufferBodyFuture {
+            inner: Box::pin(async move {
+                let (mut parts, body) = req.into_parts();
+                let bytes = match Limited::new(body, max_size).collect().await {
+                    Ok(body) => body.to_bytes(),
+                    Err(err) if err.is::<LengthLimitError>() => {
+                        return Ok(Status::resource_exhausted(format!(
+                            "request body is larger than the limit of {max_size} bytes"
+                        ))
+                        .into_http());
+                    }
+                    Err(err) => return Ok(Status::from_error(err).into_http()),
+                };
+
+                parts.extensions.insert(BufferedBody(bytes.clone()));
+                let req = Request::from_parts(parts, Body::new(Full::new(bytes)));
+                Ok(inner.call(req).await?.map(Body::new))
+            }),
+        }
+    }
+}
+
+// required to use `BufferBody` with `Router`
+impl<S> crate::server::NamedService for BufferBody<S>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+    const METHODS: &'static [&'static str] = S::METHODS;
+}
+
+/// Response future for [`BufferBody`].
+pub struct BufferBodyFuture<E> {
+    inner: Pin<Box<dyn Future<Output = Result<Response<Body>, E>> + Send>>,
+}
+
+impl<E> fmt::Debug for BufferBodyFuture<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferBodyFuture").finish()
+    }
+}
+
+impl<E> Future for BufferBodyFuture<E> {
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        metadata::MetadataValue, request::SanitizeHeaders, service::InterceptorLayer, Code,
+    };
+    use ring::hmac;
+    use std::convert::Infallible;
+    use tower::{ServiceBuilder, ServiceExt};
+
+    const KEY: &[u8] = b"shared secret";
+
+    /// Echoes the request body, after checking the HMAC in the `x-signature-bin` metadata.
+    fn service(
+        max_size: usize,
+    ) -> impl Service<Request<Body>, Response = Response<Body>, Error = Infallible> {
+        let verify = |request: crate::Request<()>| {
+            let body = request
+                .extensions()
+                .get::<BufferedBody>()
+                .ok_or_else(|| Status::internal("body not buffered"))?;
+            let signature = request
+                .metadata()
+                .get_bin("x-signature-bin")
+                .and_then(|value| value.to_bytes().ok())
+                .ok_or_else(|| Status::unauthenticated("missing signature"))?;
+            hmac::verify(
+                &hmac::Key::new(hmac::HMAC_SHA256, KEY),
+                body.bytes(),
+                &signature,
+            )
+            .map_err(|_| Status::unauthenticated("invalid signature"))?;
+            Ok(request)
+        };
+
+        ServiceBuilder::new()
+            .layer(BufferBodyLayer::new(["/test.Test/Unary"], max_size))
+            .layer(InterceptorLayer::new(verify))
+            .service_fn(|request: Request<Body>| async move {
+                let body = request.into_body().collect().await.unwrap().to_bytes();
+                Ok::<_, Infallible>(Response::new(Body::new(Full::new(body))))
+            })
+    }
+
+    fn request(path: &str, body: &'static [u8], signed: &[u8]) -> Request<Body> {
+        let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, KEY), signed);
+        let mut request = crate::Request::new(());
+        request
+            .metadata_mut()
+            .insert_bin("x-signature-bin", MetadataValue::from_bytes(tag.as_ref()));
+        request
+            .into_http(
+                path.parse().unwrap(),
+                http::Method::POST,
+                http::Version::HTTP_2,
+                SanitizeHeaders::No,
+            )
+            .map(|()| Body::new(Full::new(Bytes::from_static(body))))
+    }
+
+    #[tokio::test]
+    async fn interceptor_verifies_body_before_handler() {
+        let body = b"\0\0\0\0\x05hello";
+
+        let response = service(1024)
+            .oneshot(request("/test.Test/Unary", body, body))
+            .await
+            .unwrap();
+        assert!(Status::from_header_map(response.headers()).is_none());
+        let echoed = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(echoed, &body[..]);
+
+        let response = service(1024)
+            .oneshot(request("/test.Test/Unary", body, b"something else"))
+            .await
+            .unwrap();
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_bodies() {
+        let body = b"\0\0\0\0\x05hello";
+
+        let response = service(4)
+            .oneshot(request("/test.Test/Unary", body, body))
+            .await
+            .unwrap();
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+    }
+
+    #[tokio::test]
+    async fn passes_other_methods_through() {
+        let body = b"\0\0\0\0\x05hello";
+
+        let response = service(4)
+            .oneshot(request("/test.Test/Other", body, body))
+            .await
+            .unwrap();
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.message(), "body not buffered");
+    }
+}
@@ -0,0 +1,377 @@
+//! Middleware deduplicating requests through idempotency keys.
+
+use crate::{body::Body, BoxError, Code, Status};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, Request, Response};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::sync::watch;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The metadata key clients send the idempotency key of a request in.
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+type InFlight = Arc<Mutex<HashMap<String, watch::Receiver<Option<IdempotentResponse>>>>>;
+
+/// A completed response, cached for its idempotency key.
+#[derive(Clone, Debug)]
+pub struct IdempotentResponse {
+    headers: HeaderMap,
+    body: Bytes,
+    trailers: Option<HeaderMap>,
+}
+
+impl IdempotentResponse {
+    /// Create a response, e.g. when loading it from an external store.
+    pub fn new(headers: HeaderMap, body: Bytes, trailers: Option<HeaderMap>) -> Self {
+        Self {
+            headers,
+            body,
+            trailers,
+        }
+    }
+
+    /// The response headers.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The encoded response messages.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The response trailers, or `None` for a trailers-only response.
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.trailers.as_ref()
+    }
+
+    fn code(&self) -> Code {
+        self.trailers
+            .as_ref()
+            .and_then(Status::from_header_map)
+            .or_else(|| Status::from_header_map(&self.headers))
+            .map_or(Code::Unknown, |status| status.code())
+    }
+
+    fn to_response(&self) -> Response<Body> {
+        let mut frames = vec![Ok::<_, std::convert::Infallible>(Frame::data(
+            self.body.clone(),
+        ))];
+        if let Some(trailers) = &self.trailers {
+            frames.push(Ok(Frame::trailers(trailers.clone())));
+        }
+        let mut response = Response::new(Body::new(StreamBody::new(tokio_stream::iter(frames))));
+        *response.headers_mut() = self.headers.clone();
+        response
+    }
+}
+
+/// Stores the responses of an [`IdempotencyLayer`].
+///
+/// Both methods are called while holding a lock shared by all requests, so they must not
+/// block. The default is an [`InMemoryIdempotencyStore`].
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Return the response stored for `key`, unless it expired.
+    fn get(&self, key: &str) -> Option<IdempotentResponse>;
+
+    /// Store `response` for `key` for at least `ttl`.
+    fn put(&self, key: String, response: IdempotentResponse, ttl: Duration);
+}
+
+/// An [`IdempotencyStore`] keeping the responses in memory, removing expired ones as new ones
+/// are added.
+#[derive(Debug, Default)]
+pub struct InMemoryIdempotencyStore {
+    responses: Mutex<HashMap<String, (Instant, IdempotentResponse)>>,
+}
+
+impl InMemoryIdempotencyStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    fn get(&self, key: &str) -> Option<IdempotentResponse> {
+        let responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        responses
+            .get(key)
+            .filter(|(expires, _)| *expires > Instant::now())
+            .map(|(_, response)| response.clone())
+    }
+
+    fn put(&self, key: String, response: IdempotentResponse, ttl: Duration) {
+        let mut responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        responses.retain(|_, (expires, _)| *expires > now);
+        responses.insert(key, (now + ttl, response));
+    }
+}
+
+/// A layer executing the requests carrying the same [`IDEMPOTENCY_KEY`] at most once, for
+/// at-most-once semantics on mutating RPCs.
+///
+/// The response of the first request with a key is stored for a TTL, and the requests with the
+/// same key on the same method receive that response instead of being executed. Requests
+/// arriving while the first one is in flight wait for it to complete. Requests without a key are
+/// passed through.
+///
+/// Responses with a status the client may retry, i.e. `CANCELLED`, `DEADLINE_EXCEEDED`,
+/// `RESOURCE_EXHAUSTED`, `ABORTED` and `UNAVAILABLE`, aren't stored, so the next request with
+/// the key is executed. Responses are buffered, so this is only meant for unary methods.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tonic::service::IdempotencyLayer;
+/// let layer = IdempotencyLayer::new(Duration::from_secs(24 * 60 * 60));
+///
+/// // Apply it to all services of a server through `Server::builder().layer(layer)`.
+/// ```
+#[derive(Clone)]
+pub struct IdempotencyLayer {
+    ttl: Duration,
+    store: Arc<dyn IdempotencyStore>,
+    in_flight: InFlight,
+}
+
+impl IdempotencyLayer {
+    /// Create a layer storing responses for `ttl` in an [`InMemoryIdempotencyStore`].
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            store: Arc::new(InMemoryIdempotencyStore::new()),
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// Store the responses in `store` instead.
+    pub fn store(self, store: impl IdempotencyStore) -> Self {
+        Self {
+            store: Arc::new(store),
+            ..self
+        }
+    }
+}
+
+impl fmt::Debug for IdempotencyLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdempotencyLayer")
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for IdempotencyLayer {
+    type Service = Idempotent<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Idempotent {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// A service executing requests with the same idempotency key at most once, see
+/// [`IdempotencyLayer`].
+#[derive(Clone)]
+pub struct Idempotent<S> {
+    inner: S,
+    layer: IdempotencyLayer,
+}
+
+impl<S: fmt::Debug> fmt::Debug for Idempotent<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Idempotent")
+            .field("inner", &self.inner)
+            .field("layer", &self.layer)
+            .finish()
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for Idempotent<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = IdempotentFuture<S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let key = req
+            .headers()
+            .get(IDEMPOTENCY_KEY)
+            .and_then(|key| key.to_str().ok())
+            .map(|key| format!("{}:{}", req.uri().path(), key));
+
+        // The inner service was driven to readiness, so take it and leave the clone behind.
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+
+        let future: Pin<Box<dyn Future<Output = _> + Send>> = match key {
+            Some(key) => Box::pin(deduplicate(inner, self.layer.clone(), key, req)),
+            None => Box::pin(passthrough(inner, req)),
+        };
+        IdempotentFuture { inner: future }
+    }
+}
+
+// required to use `Idempotent` with `Router`
+impl<S> crate::server::NamedService for Idempotent<S>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+}
+
+/// Removes an in-flight request once it completes or is dropped.
+struct Landing {
+    in_flight: InFlight,
+    key: String,
+}
+
+impl Drop for Landing {
+    fn drop(&mut self) {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.key);
+    }
+}
+
+async fn passthrough<S, ResBody>(
+    mut inner: S,
+    req: Request<Body>,
+) -> Result<Response<Body>, S::Error>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    Ok(inner.call(req).await?.map(Body::new))
+}
+
+async fn deduplicate<S, ResBody>(
+    mut inner: S,
+    layer: IdempotencyLayer,
+    key: String,
+    req: Request<Body>,
+) -> Result<Response<Body>, S::Error>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    // The store is checked under the lock, and responses are stored before their in-flight entry
+    // is removed, so a duplicate either waits for the first request or finds its response.
+    let tx = {
+        let mut in_flight = layer.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(rx) = in_flight.get(&key) {
+            Err(rx.clone())
+        } else if let Some(response) = layer.store.get(&key) {
+            return Ok(response.to_response());
+        } else {
+            let (tx, rx) = watch::channel(None);
+            in_flight.insert(key.clone(), rx);
+            Ok(tx)
+        }
+    };
+
+    let tx = match tx {
+        Ok(tx) => tx,
+        Err(mut rx) => {
+            if let Ok(response) = rx.wait_for(Option::is_some).await {
+                if let Some(response) = &*response {
+                    return Ok(response.to_response());
+                }
+            }
+            // The first request failed or was dropped, so execute this one.
+            return passthrough(inner, req).await;
+        }
+    };
+
+    let _landing = Landing {
+        in_flight: layer.in_flight.clone(),
+        key: key.clone(),
+    };
+
+    let (parts, body) = inner.call(req).await?.into_parts();
+    let body = match body.collect().await {
+        Ok(body) => body,
+        Err(err) => return Ok(Status::from_error(err.into()).into_http()),
+    };
+    let trailers = body.trailers().cloned();
+    let response = IdempotentResponse::new(parts.headers, body.to_bytes(), trailers);
+
+    let retryable = matches!(
+        response.code(),
+        Code::Cancelled
+            | Code::DeadlineExceeded
+            | Code::ResourceExhausted
+            | Code::Aborted
+            | Code::Unavailable
+    );
+    if !retryable {
+        layer.store.put(key, response.clone(), layer.ttl);
+        tx.send_replace(Some(response.clone()));
+    }
+
+    Ok(response.to_response())
+}
+
+/// Response future for [`Idempotent`].
+pub struct IdempotentFuture<E> {
+    inner: Pin<Box<dyn Future<Output = Result<Response<Body>, E>> + Send>>,
+}
+
+impl<E> fmt::Debug for IdempotentFuture<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdempotentFuture").finish()
+    }
+}
+
+impl<E> Future for IdempotentFuture<E> {
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_store_expires_responses() {
+        let store = InMemoryIdempotencyStore::new();
+        let response = IdempotentResponse::new(HeaderMap::new(), Bytes::new(), None);
+
+        store.put("a".to_owned(), response.clone(), Duration::from_secs(60));
+        store.put("b".to_owned(), response, Duration::ZERO);
+
+        assert!(store.get("a").is_some());
+        assert!(store.get("b").is_none());
+        assert!(store.get("c").is_none());
+    }
+}
@@ -0,0 +1,235 @@
+//! Middleware inspecting decoded request messages before they reach the handler.
+
+use crate::{
+    extensions::{Inspector, MessageInspectors},
+    Status,
+};
+use std::{
+    any::Any,
+    fmt,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A layer inspecting every decoded request message of type `M` before the handler sees it.
+///
+/// The inspector runs once the server decoded a message, and an error rejects it with the
+/// returned [`Status`]. For unary and server streaming calls the handler is then not called at
+/// all. For client and bidirectional streaming calls every message is inspected, and the stream
+/// given to the handler yields the error in place of the rejected message, then ends.
+///
+/// Messages of other types pass through, so one layer applied to all services of a server only
+/// affects the methods taking an `M`. Multiple layers can be stacked, their inspectors run from
+/// the outermost to the innermost.
+///
+/// ```
+/// # use tonic::{service::InspectMessageLayer, Status};
+/// # struct CreateUserRequest { name: String }
+/// let layer = InspectMessageLayer::new(|req: &CreateUserRequest| {
+///     if req.name.is_empty() {
+///         return Err(Status::invalid_argument("name must not be empty"));
+///     }
+///     Ok(())
+/// });
+///
+/// // Apply it to all services of a server through `Server::builder().layer(layer)`.
+/// ```
+#[derive(Clone)]
+pub struct InspectMessageLayer {
+    inspector: Inspector,
+}
+
+impl InspectMessageLayer {
+    /// Create a layer inspecting messages of type `M` with `f`.
+    pub fn new<M, F>(f: F) -> Self
+    where
+        M: 'static,
+        F: Fn(&M) -> Result<(), Status> + Send + Sync + 'static,
+    {
+        Self {
+            inspector: Arc::new(move |message: &dyn Any| match message.downcast_ref::<M>() {
+                Some(message) => f(message),
+                None => Ok(()),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for InspectMessageLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectMessageLayer").finish()
+    }
+}
+
+impl<S> Layer<S> for InspectMessageLayer {
+    type Service = InspectMessage<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InspectMessage {
+            inner,
+            inspector: self.inspector.clone(),
+        }
+    }
+}
+
+/// Middleware inspecting decoded request messages, see [`InspectMessageLayer`].
+#[derive(Clone)]
+pub struct InspectMessage<S> {
+    inner: S,
+    inspector: Inspector,
+}
+
+impl<S> fmt::Debug for InspectMessage<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectMessage")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for InspectMessage<S>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        // The server decodes the messages, so hand it the inspector through the extensions.
+        let mut inspectors = req
+            .extensions_mut()
+            .remove::<MessageInspectors>()
+            .unwrap_or_default();
+        inspectors.push(self.inspector.clone());
+        req.extensions_mut().insert(inspectors);
+
+        self.inner.call(req)
+    }
+}
+
+// required to use `InspectMessage` with `Router`
+impl<S> crate::server::NamedService for InspectMessage<S>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(all(test, feature = "prost"))]
+mod tests {
+    use super::*;
+    use crate::{body::Body, codec::ProstCodec, server::Grpc, Code, Request, Response, Streaming};
+    use bytes::{BufMut, Bytes, BytesMut};
+    use http_body_util::BodyExt;
+    use prost::Message;
+    use std::convert::Infallible;
+    use tokio_stream::StreamExt;
+    use tower::ServiceExt;
+
+    fn reject_empty() -> InspectMessageLayer {
+        InspectMessageLayer::new(|message: &String| {
+            if message.is_empty() {
+                return Err(Status::invalid_argument("empty message"));
+            }
+            Ok(())
+        })
+    }
+
+    fn frames(messages: &[&str]) -> Bytes {
+        let mut frames = BytesMut::new();
+        for message in messages {
+            let message = message.to_string().encode_to_vec();
+            frames.put_u8(0);
+            frames.put_u32(message.len() as u32);
+            frames.put_slice(&message);
+        }
+        frames.freeze()
+    }
+
+    fn request(messages: &[&str]) -> http::Request<Body> {
+        http::Request::post("/test.Test/Call")
+            .header(http::header::CONTENT_TYPE, "application/grpc")
+            .body(Body::new(http_body_util::Full::new(frames(messages))))
+            .unwrap()
+    }
+
+    /// Returns the status of the call and the messages the handler got.
+    async fn status(res: http::Response<Body>) -> (Code, Bytes) {
+        if let Some(status) = Status::from_header_map(res.headers()) {
+            return (status.code(), Bytes::new());
+        }
+        let body = res.into_body().collect().await.unwrap();
+        let trailers = body.trailers().cloned().unwrap();
+        let status = Status::from_header_map(&trailers).unwrap();
+        (status.code(), body.to_bytes())
+    }
+
+    #[tokio::test]
+    async fn rejects_unary_message() {
+        let svc = tower::service_fn(|req: http::Request<Body>| async move {
+            let handler = tower::service_fn(|req: Request<String>| async move {
+                assert!(!req.get_ref().is_empty(), "handler got a rejected message");
+                Ok::<_, Status>(Response::new(req.into_inner()))
+            });
+            let mut grpc = Grpc::new(ProstCodec::<String, String>::default());
+            Ok::<_, Infallible>(grpc.unary(handler, req).await)
+        });
+        let svc = reject_empty().layer(svc);
+
+        let res = svc.clone().oneshot(request(&["hello"])).await.unwrap();
+        assert_eq!(status(res).await.0, Code::Ok);
+
+        let res = svc.oneshot(request(&[""])).await.unwrap();
+        assert_eq!(status(res).await.0, Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn inspects_each_streamed_message() {
+        let svc = tower::service_fn(|req: http::Request<Body>| async move {
+            let handler = tower::service_fn(|req: Request<Streaming<String>>| async move {
+                // Echo the messages until the first error.
+                let mut stream = req.into_inner();
+                let mut messages = Vec::new();
+                while let Some(message) = stream.next().await {
+                    messages.push(message);
+                }
+                Ok::<_, Status>(Response::new(tokio_stream::iter(messages)))
+            });
+            let mut grpc = Grpc::new(ProstCodec::<String, String>::default());
+            Ok::<_, Infallible>(grpc.streaming(handler, req).await)
+        });
+        let svc = reject_empty().layer(svc);
+
+        let res = svc.oneshot(request(&["a", "b", "", "c"])).await.unwrap();
+        let (code, body) = status(res).await;
+        assert_eq!(code, Code::InvalidArgument);
+
+        assert_eq!(body, frames(&["a", "b"]));
+    }
+
+    #[tokio::test]
+    async fn ignores_other_message_types() {
+        let svc = tower::service_fn(|req: http::Request<Body>| async move {
+            let handler = tower::service_fn(|req: Request<Vec<u8>>| async move {
+                Ok::<_, Status>(Response::new(req.into_inner()))
+            });
+            let mut grpc = Grpc::new(ProstCodec::<Vec<u8>, Vec<u8>>::default());
+            Ok::<_, Infallible>(grpc.unary(handler, req).await)
+        });
+        let svc = reject_empty().layer(svc);
+
+        let res = svc.oneshot(request(&[""])).await.unwrap();
+        assert_eq!(status(res).await.0, Code::Ok);
+    }
+}
@@ -1,12 +1,13 @@
 //! gRPC interceptors which are a kind of middleware.
 //!
-//! See [`Interceptor`] for more details.
+//! See [`Interceptor`] and [`AsyncInterceptorLayer`] for more details.
 
 use crate::{request::SanitizeHeaders, Status};
 use pin_project::pin_project;
 use std::{
     fmt,
     future::Future,
+    mem,
     pin::Pin,
     task::{Context, Poll},
 };
@@ -161,7 +162,9 @@ where
     const NAME: &'static str = S::NAME;
 }
 
-/// Response future for [`InterceptedService`].
+/// Response future for [`InterceptedService`] and [`MethodFilter`].
+///
+/// [`MethodFilter`]: crate::service::MethodFilter
 #[pin_project]
 #[derive(Debug)]
 pub struct ResponseFuture<F> {
@@ -170,13 +173,13 @@ pub struct ResponseFuture<F> {
 }
 
 impl<F> ResponseFuture<F> {
-    fn future(future: F) -> Self {
+    pub(crate) fn future(future: F) -> Self {
         Self {
             kind: Kind::Future(future),
         }
     }
 
-    fn status(status: Status) -> Self {
+    pub(crate) fn status(status: Status) -> Self {
         Self {
             kind: Kind::Status(Some(status)),
         }
@@ -199,16 +202,251 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match self.project().kind.project() {
             KindProj::Future(future) => future.poll(cx).map_ok(|res| res.map(ResponseBody::wrap)),
-            KindProj::Status(status) => {
-                let (parts, ()) = status.take().unwrap().into_http::<()>().into_parts();
-                let response = http::Response::from_parts(parts, ResponseBody::<B>::empty());
-                Poll::Ready(Ok(response))
-            }
+            KindProj::Status(status) => Poll::Ready(Ok(status_response(status.take().unwrap()))),
+        }
+    }
+}
+
+/// An asynchronous gRPC interceptor that can be used as a [`Layer`].
+///
+/// Unlike an [`Interceptor`], which is a synchronous function, the interceptor is a [`Service`]
+/// that takes the request without its message and returns it, possibly modified, or rejects it
+/// with a [`Status`]. This allows interceptors that need to do IO, for example authentication
+/// that fetches signing keys or calls an introspection endpoint, and attaches the resulting
+/// identity to the request [extensions](crate::Request::extensions).
+///
+/// The interceptor is cloned for every request and only called once it is ready. Errors
+/// returned by [`Service::poll_ready`] are sent to the client like rejections.
+///
+/// # Example
+///
+/// ```
+/// # use tonic::{service::AsyncInterceptorLayer, Request, Status};
+/// #[derive(Clone)]
+/// struct User(String);
+///
+/// async fn authenticate(mut request: Request<()>) -> Result<Request<()>, Status> {
+///     let user = match request.metadata().get("authorization") {
+///         // Validate the token against keys that are fetched lazily...
+///         Some(token) if token == "Bearer some-secret-token" => User("alice".to_string()),
+///         _ => return Err(Status::unauthenticated("invalid token")),
+///     };
+///
+///     request.extensions_mut().insert(user);
+///     Ok(request)
+/// }
+///
+/// // Apply it to all services of a server through `Server::builder().layer(layer)`.
+/// let layer = AsyncInterceptorLayer::new(tower::service_fn(authenticate));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncInterceptorLayer<A> {
+    interceptor: A,
+}
+
+impl<A> AsyncInterceptorLayer<A> {
+    /// Create a new asynchronous interceptor layer.
+    ///
+    /// See [`AsyncInterceptorLayer`] for more details.
+    pub fn new(interceptor: A) -> Self {
+        Self { interceptor }
+    }
+}
+
+impl<S, A> Layer<S> for AsyncInterceptorLayer<A>
+where
+    A: Clone,
+{
+    type Service = AsyncInterceptedService<S, A>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        AsyncInterceptedService::new(service, self.interceptor.clone())
+    }
+}
+
+/// A service wrapped in an asynchronous interceptor middleware.
+///
+/// See [`AsyncInterceptorLayer`] for more details.
+#[derive(Clone, Copy)]
+pub struct AsyncInterceptedService<S, A> {
+    inner: S,
+    interceptor: A,
+}
+
+impl<S, A> AsyncInterceptedService<S, A> {
+    /// Create a new `AsyncInterceptedService` that wraps `S` and intercepts each request with the
+    /// service `A`.
+    pub fn new(service: S, interceptor: A) -> Self {
+        Self {
+            inner: service,
+            interceptor,
+        }
+    }
+}
+
+impl<S, A> fmt::Debug for AsyncInterceptedService<S, A>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncInterceptedService")
+            .field("inner", &self.inner)
+            .field(
+                "interceptor",
+                &format_args!("{}", std::any::type_name::<A>()),
+            )
+            .finish()
+    }
+}
+
+impl<S, A, ReqBody, ResBody> Service<http::Request<ReqBody>> for AsyncInterceptedService<S, A>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone,
+    A: Service<crate::Request<()>, Response = crate::Request<()>, Error = Status> + Clone,
+{
+    type Response = http::Response<ResponseBody<ResBody>>;
+    type Error = S::Error;
+    type Future = AsyncResponseFuture<S, A, ReqBody>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        // See `InterceptedService::call` for why the message is kept away from the interceptor.
+        let uri = req.uri().clone();
+        let method = req.method().clone();
+        let version = req.version();
+        let req = crate::Request::from_http(req);
+        let (metadata, extensions, msg) = req.into_parts();
+
+        // The inner service is ready, so take it and leave a clone in its place.
+        let clone = self.inner.clone();
+        let inner = mem::replace(&mut self.inner, clone);
+
+        AsyncResponseFuture {
+            state: AsyncState::Ready {
+                interceptor: self.interceptor.clone(),
+                request: Some(crate::Request::from_parts(metadata, extensions, ())),
+            },
+            pending: Some(PendingRequest {
+                inner,
+                uri,
+                method,
+                version,
+                msg,
+            }),
         }
     }
 }
 
-/// Response body for [`InterceptedService`].
+// required to use `AsyncInterceptedService` with `Router`
+impl<S, A> crate::server::NamedService for AsyncInterceptedService<S, A>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+}
+
+struct PendingRequest<S, ReqBody> {
+    inner: S,
+    uri: http::Uri,
+    method: http::Method,
+    version: http::Version,
+    msg: ReqBody,
+}
+
+/// Response future for [`AsyncInterceptedService`].
+#[pin_project]
+pub struct AsyncResponseFuture<S, A, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+    A: Service<crate::Request<()>>,
+{
+    #[pin]
+    state: AsyncState<A, A::Future, S::Future>,
+    pending: Option<PendingRequest<S, ReqBody>>,
+}
+
+#[pin_project(project = AsyncStateProj)]
+enum AsyncState<A, I, F> {
+    Ready {
+        interceptor: A,
+        request: Option<crate::Request<()>>,
+    },
+    Intercepting(#[pin] I),
+    Future(#[pin] F),
+}
+
+impl<S, A, ReqBody, ResBody> Future for AsyncResponseFuture<S, A, ReqBody>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    A: Service<crate::Request<()>, Response = crate::Request<()>, Error = Status>,
+{
+    type Output = Result<http::Response<ResponseBody<ResBody>>, S::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        loop {
+            let next = match this.state.as_mut().project() {
+                AsyncStateProj::Ready {
+                    interceptor,
+                    request,
+                } => match interceptor.poll_ready(cx) {
+                    Poll::Ready(Ok(())) => {
+                        AsyncState::Intercepting(interceptor.call(request.take().unwrap()))
+                    }
+                    Poll::Ready(Err(status)) => return Poll::Ready(Ok(status_response(status))),
+                    Poll::Pending => return Poll::Pending,
+                },
+                AsyncStateProj::Intercepting(future) => match future.poll(cx) {
+                    Poll::Ready(Ok(req)) => {
+                        let PendingRequest {
+                            mut inner,
+                            uri,
+                            method,
+                            version,
+                            msg,
+                        } = this.pending.take().unwrap();
+
+                        let (metadata, extensions, _) = req.into_parts();
+                        let req = crate::Request::from_parts(metadata, extensions, msg);
+                        let req = req.into_http(uri, method, version, SanitizeHeaders::No);
+                        AsyncState::Future(inner.call(req))
+                    }
+                    Poll::Ready(Err(status)) => return Poll::Ready(Ok(status_response(status))),
+                    Poll::Pending => return Poll::Pending,
+                },
+                AsyncStateProj::Future(future) => {
+                    return future.poll(cx).map_ok(|res| res.map(ResponseBody::wrap))
+                }
+            };
+
+            this.state.set(next);
+        }
+    }
+}
+
+impl<S, A, ReqBody> fmt::Debug for AsyncResponseFuture<S, A, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+    A: Service<crate::Request<()>>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncResponseFuture").finish()
+    }
+}
+
+fn status_response<B>(status: Status) -> http::Response<ResponseBody<B>> {
+    let (parts, ()) = status.into_http::<()>().into_parts();
+    http::Response::from_parts(parts, ResponseBody::empty())
+}
+
+/// Response body for [`InterceptedService`] and [`MethodFilter`].
+///
+/// [`MethodFilter`]: crate::service::MethodFilter
 #[pin_project]
 #[derive(Debug)]
 pub struct ResponseBody<B> {
@@ -326,6 +564,52 @@ mod tests {
         assert_eq!(expected.headers(), response.headers());
     }
 
+    #[tokio::test]
+    async fn async_interceptor_modifies_request() {
+        let svc = tower::service_fn(|request: http::Request<&'static str>| async move {
+            assert_eq!(request.headers().get("x-user").unwrap(), "alice");
+            assert_eq!(*request.body(), "message");
+
+            Ok::<_, Status>(http::Response::new(()))
+        });
+
+        let svc = AsyncInterceptedService::new(
+            svc,
+            tower::service_fn(|mut request: crate::Request<()>| async move {
+                tokio::task::yield_now().await;
+                request
+                    .metadata_mut()
+                    .insert("x-user", "alice".parse().unwrap());
+                Ok(request)
+            }),
+        );
+
+        let request = http::Request::builder().body("message").unwrap();
+        svc.oneshot(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_interceptor_handles_status_as_response() {
+        let expected = Status::unauthenticated("invalid token").into_http::<()>();
+
+        let svc = tower::service_fn(|_: http::Request<()>| async {
+            Ok::<_, Status>(http::Response::new(()))
+        });
+
+        let svc = AsyncInterceptedService::new(
+            svc,
+            tower::service_fn(|_: crate::Request<()>| async {
+                Err::<crate::Request<()>, _>(Status::unauthenticated("invalid token"))
+            }),
+        );
+
+        let request = http::Request::builder().body(()).unwrap();
+        let response = svc.oneshot(request).await.unwrap();
+
+        assert_eq!(expected.status(), response.status());
+        assert_eq!(expected.headers(), response.headers());
+    }
+
     #[tokio::test]
     async fn doesnt_change_http_method() {
         let svc = tower::service_fn(|request: http::Request<()>| async move {
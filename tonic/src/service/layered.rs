@@ -17,6 +17,7 @@ pub struct Layered<S, T> {
 
 impl<S, T: NamedService> NamedService for Layered<S, T> {
     const NAME: &'static str = T::NAME;
+    const METHODS: &'static [&'static str] = T::METHODS;
 }
 
 impl<Req, S, T> Service<Req> for Layered<S, T>
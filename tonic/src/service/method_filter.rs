@@ -0,0 +1,255 @@
+//! Middleware blocking gRPC methods by their path.
+
+use super::interceptor::{ResponseBody, ResponseFuture};
+use crate::Status;
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+#[derive(Debug)]
+enum Rules {
+    Allow(HashSet<String>),
+    Deny(HashSet<String>),
+}
+
+impl Rules {
+    fn check(&self, path: &str) -> Result<(), Status> {
+        match self {
+            Rules::Allow(methods) if !methods.contains(path) => Err(Status::unimplemented(
+                format!("method `{path}` is not enabled"),
+            )),
+            Rules::Deny(methods) if methods.contains(path) => Err(Status::permission_denied(
+                format!("method `{path}` is disabled"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn collect<I, P>(methods: I) -> HashSet<String>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<String>,
+{
+    methods.into_iter().map(Into::into).collect()
+}
+
+/// A layer rejecting calls to some gRPC methods before they reach their handler.
+///
+/// Methods are identified by their full path, like `/admin.AdminService/DeleteAll`. The filter
+/// either works with a deny list, rejecting the listed methods with [`Code::PermissionDenied`], or
+/// with an allow list, rejecting all other methods with [`Code::Unimplemented`] as if they did
+/// not exist.
+///
+/// The lists can be replaced while the server is running through a [`MethodFilterHandle`].
+///
+/// ```
+/// # use tonic::service::MethodFilterLayer;
+/// let filter = MethodFilterLayer::deny(["/admin.AdminService/DeleteAll"]);
+/// let handle = filter.handle();
+///
+/// // Apply it to all services of a server through `Server::builder().layer(filter)`.
+///
+/// // Later, e.g. when the configuration changes.
+/// handle.deny(Vec::<String>::new());
+/// ```
+///
+/// [`Code::PermissionDenied`]: crate::Code::PermissionDenied
+/// [`Code::Unimplemented`]: crate::Code::Unimplemented
+#[derive(Clone)]
+pub struct MethodFilterLayer {
+    rules: Arc<RwLock<Rules>>,
+}
+
+impl MethodFilterLayer {
+    /// Create a filter that only lets calls to `methods` through.
+    pub fn allow<I, P>(methods: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        Self {
+            rules: Arc::new(RwLock::new(Rules::Allow(collect(methods)))),
+        }
+    }
+
+    /// Create a filter that rejects calls to `methods`.
+    pub fn deny<I, P>(methods: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        Self {
+            rules: Arc::new(RwLock::new(Rules::Deny(collect(methods)))),
+        }
+    }
+
+    /// Returns a handle to change the filtered methods later on.
+    pub fn handle(&self) -> MethodFilterHandle {
+        MethodFilterHandle {
+            rules: self.rules.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for MethodFilterLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MethodFilterLayer")
+            .field("rules", &self.rules.read().unwrap())
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for MethodFilterLayer {
+    type Service = MethodFilter<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MethodFilter {
+            inner,
+            rules: self.rules.clone(),
+        }
+    }
+}
+
+/// A handle to replace the methods filtered by a [`MethodFilterLayer`].
+///
+/// Changes apply to all calls that start afterwards, on every service the layer was applied to.
+#[derive(Clone)]
+pub struct MethodFilterHandle {
+    rules: Arc<RwLock<Rules>>,
+}
+
+impl MethodFilterHandle {
+    /// Only let calls to `methods` through from now on.
+    pub fn allow<I, P>(&self, methods: I)
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        *self.rules.write().unwrap() = Rules::Allow(collect(methods));
+    }
+
+    /// Reject calls to `methods` from now on, and let all others through.
+    pub fn deny<I, P>(&self, methods: I)
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        *self.rules.write().unwrap() = Rules::Deny(collect(methods));
+    }
+}
+
+impl fmt::Debug for MethodFilterHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MethodFilterHandle").finish()
+    }
+}
+
+/// Middleware rejecting calls to filtered methods, see [`MethodFilterLayer`].
+#[derive(Clone)]
+pub struct MethodFilter<S> {
+    inner: S,
+    rules: Arc<RwLock<Rules>>,
+}
+
+impl<S> fmt::Debug for MethodFilter<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MethodFilter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for MethodFilter<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Response = http::Response<ResponseBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let checked = self.rules.read().unwrap().check(req.uri().path());
+        match checked {
+            Ok(()) => ResponseFuture::future(self.inner.call(req)),
+            Err(status) => ResponseFuture::status(status),
+        }
+    }
+}
+
+// required to use `MethodFilter` with `Router`
+impl<S> crate::server::NamedService for MethodFilter<S>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+    const METHODS: &'static [&'static str] = S::METHODS;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Code;
+    use tower::ServiceExt;
+
+    async fn call(layer: &MethodFilterLayer, path: &str) -> Option<Code> {
+        let svc = tower::service_fn(|_: http::Request<()>| async {
+            Ok::<_, Status>(http::Response::new(()))
+        });
+        let request = http::Request::builder().uri(path).body(()).unwrap();
+        let response = layer.layer(svc).oneshot(request).await.unwrap();
+        Status::from_header_map(response.headers()).map(|status| status.code())
+    }
+
+    #[tokio::test]
+    async fn deny_list() {
+        let layer = MethodFilterLayer::deny(["/admin.Admin/DeleteAll"]);
+        assert_eq!(
+            call(&layer, "/admin.Admin/DeleteAll").await,
+            Some(Code::PermissionDenied)
+        );
+        assert_eq!(call(&layer, "/admin.Admin/List").await, None);
+    }
+
+    #[tokio::test]
+    async fn allow_list() {
+        let layer = MethodFilterLayer::allow(["/admin.Admin/List"]);
+        assert_eq!(call(&layer, "/admin.Admin/List").await, None);
+        assert_eq!(
+            call(&layer, "/admin.Admin/DeleteAll").await,
+            Some(Code::Unimplemented)
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_replaces_the_rules() {
+        let layer = MethodFilterLayer::deny(Vec::<String>::new());
+        assert_eq!(call(&layer, "/admin.Admin/DeleteAll").await, None);
+
+        layer.handle().deny(["/admin.Admin/DeleteAll"]);
+        assert_eq!(
+            call(&layer, "/admin.Admin/DeleteAll").await,
+            Some(Code::PermissionDenied)
+        );
+
+        layer.handle().allow(["/admin.Admin/DeleteAll"]);
+        assert_eq!(call(&layer, "/admin.Admin/DeleteAll").await, None);
+        assert_eq!(
+            call(&layer, "/admin.Admin/List").await,
+            Some(Code::Unimplemented)
+        );
+    }
+}
@@ -1,15 +1,44 @@
 //! Utilities for using Tower services with Tonic.
 
+#[cfg(all(feature = "server", feature = "prost"))]
+pub(crate) mod blocking;
+pub(crate) mod buffer_body;
+#[cfg(feature = "server")]
+pub(crate) mod idempotency;
+pub(crate) mod inspect_message;
 pub mod interceptor;
 pub(crate) mod layered;
+pub(crate) mod method_filter;
 #[cfg(feature = "router")]
 pub(crate) mod router;
+#[cfg(feature = "server")]
+pub(crate) mod single_flight;
+pub(crate) mod status_interceptor;
+pub(crate) mod trailers;
 
+pub use self::buffer_body::{BufferBody, BufferBodyFuture, BufferBodyLayer, BufferedBody};
+#[cfg(feature = "server")]
+pub use self::idempotency::{
+    IdempotencyLayer, IdempotencyStore, Idempotent, IdempotentFuture, IdempotentResponse,
+    InMemoryIdempotencyStore, IDEMPOTENCY_KEY,
+};
+pub use self::inspect_message::{InspectMessage, InspectMessageLayer};
 #[doc(inline)]
-pub use self::interceptor::{Interceptor, InterceptorLayer};
+pub use self::interceptor::{AsyncInterceptorLayer, Interceptor, InterceptorLayer};
 pub use self::layered::{LayerExt, Layered};
+pub use self::method_filter::{MethodFilter, MethodFilterHandle, MethodFilterLayer};
 #[doc(inline)]
 #[cfg(feature = "router")]
-pub use self::router::{Routes, RoutesBuilder};
+pub use self::router::{BoxedService, Routes, RoutesBuilder, ServiceInfo};
+#[cfg(feature = "server")]
+pub use self::single_flight::{SingleFlight, SingleFlightFuture, SingleFlightLayer};
+pub use self::status_interceptor::{
+    StatusInterceptedService, StatusInterceptor, StatusInterceptorLayer, StatusResponseBody,
+    StatusResponseFuture,
+};
+pub use self::trailers::{
+    Trailers, TrailersInterceptedService, TrailersInterceptorLayer, TrailersResponseBody,
+    TrailersResponseFuture,
+};
 #[cfg(feature = "router")]
 pub use axum::{body::Body as AxumBody, Router as AxumRouter};
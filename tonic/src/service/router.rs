@@ -1,18 +1,60 @@
 use crate::{body::Body, metadata::GRPC_CONTENT_TYPE, server::NamedService, Status};
-use http::{HeaderValue, Request, Response};
+use http::{uri::PathAndQuery, HeaderValue, Request, Response, Uri};
 use std::{
+    collections::HashSet,
     convert::Infallible,
     fmt,
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{ready, Context, Poll},
 };
-use tower::{Service, ServiceExt};
+use tower::{util::BoxCloneService, Service, ServiceExt};
+
+pub(crate) type ResolvePath = Arc<dyn Fn(&str) -> Option<String> + Send + Sync + 'static>;
+type UnknownMethod = Arc<dyn Fn(&Request<Body>) -> Status + Send + Sync + 'static>;
 
 /// A [`Service`] router.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Routes {
     router: axum::Router,
+    resolve_path: Option<ResolvePath>,
+    unknown_method: Option<UnknownMethod>,
+    services: Vec<ServiceInfo>,
+}
+
+/// The name and methods of a service added to [`Routes`], see [`Routes::services`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceInfo {
+    name: &'static str,
+    methods: &'static [&'static str],
+}
+
+impl ServiceInfo {
+    fn of<S: NamedService>() -> Self {
+        Self {
+            name: S::NAME,
+            methods: S::METHODS,
+        }
+    }
+
+    /// The name of the service, e.g. `helloworld.Greeter`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The names of the methods of the service, e.g. `SayHello`, see
+    /// [`NamedService::METHODS`].
+    pub fn methods(&self) -> &'static [&'static str] {
+        self.methods
+    }
+
+    /// The paths of the methods of the service, e.g. `/helloworld.Greeter/SayHello`.
+    pub fn method_paths(&self) -> impl Iterator<Item = String> + '_ {
+        self.methods
+            .iter()
+            .map(|method| format!("/{}/{}", self.name, method))
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -44,6 +86,9 @@ impl Default for Routes {
     fn default() -> Self {
         Self {
             router: axum::Router::new().fallback(unimplemented),
+            resolve_path: None,
+            unknown_method: None,
+            services: Vec::new(),
         }
     }
 }
@@ -75,19 +120,236 @@ impl Routes {
             &format!("/{}/*rest", S::NAME),
             svc.map_request(|req: Request<axum::body::Body>| req.map(Body::new)),
         );
+        self.services.push(ServiceInfo::of::<S>());
+        self
+    }
+
+    /// Add a type-erased service, see [`BoxedService`].
+    fn add_boxed_service(mut self, svc: BoxedService) -> Self {
+        self.router = self.router.route_service(
+            &format!("/{}/*rest", svc.info.name),
+            svc.svc
+                .map_request(|req: Request<axum::body::Body>| req.map(Body::new)),
+        );
+        self.services.push(svc.info);
+        self
+    }
+
+    /// Answer the unary method at `path`, e.g. `/helloworld.Greeter/SayHello`, with the
+    /// synchronous function `f`.
+    ///
+    /// This serves handlers that call blocking code, like a synchronous database client or
+    /// C library, without stalling the runtime: every call runs `f` on the blocking thread pool
+    /// through [`tokio::task::spawn_blocking`], decoding and encoding its messages with
+    /// [`ProstCodec`]. A panic in `f` answers the call with an `Internal` status.
+    ///
+    /// Blocking code can't be interrupted, so `f` runs to completion even if the client cancels
+    /// the call, and its response is dropped. Long running handlers can check the
+    /// [`CancellationToken`] in the request extensions to stop early, see [`Server`].
+    ///
+    /// The method isn't listed by [`Routes::services`].
+    ///
+    /// ```
+    /// # use tonic::{service::Routes, Request, Response, Status};
+    /// fn count_users(_: Request<()>) -> Result<Response<u64>, Status> {
+    ///     // e.g. a query with a synchronous database client
+    ///     Ok(Response::new(42))
+    /// }
+    ///
+    /// let routes = Routes::default().blocking_unary("/users.Users/Count", count_users);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` doesn't start with `/` or another method was added with the same path.
+    ///
+    /// [`ProstCodec`]: crate::codec::ProstCodec
+    /// [`CancellationToken`]: tokio_util::sync::CancellationToken
+    /// [`Server`]: crate::transport::Server
+    #[cfg(all(feature = "server", feature = "prost"))]
+    pub fn blocking_unary<F, Req, Res>(mut self, path: &str, f: F) -> Self
+    where
+        F: Fn(crate::Request<Req>) -> Result<crate::Response<Res>, Status> + Send + Sync + 'static,
+        Req: prost::Message + Default + Send + 'static,
+        Res: prost::Message + Send + 'static,
+    {
+        self.router = self.router.route_service(
+            path,
+            super::blocking::service(f)
+                .map_request(|req: Request<axum::body::Body>| req.map(Body::new)),
+        );
         self
     }
 
+    /// The services added to these routes, in the order they were added.
+    ///
+    /// This lists the names of the services and their methods, e.g. to log them at startup or
+    /// show them in an admin page, without the descriptors reflection needs. Services added to
+    /// the [`axum::Router`] directly aren't listed.
+    ///
+    /// ```
+    /// # use tonic::service::Routes;
+    /// fn log_services(routes: &Routes) {
+    ///     for service in routes.services() {
+    ///         println!("{}: {:?}", service.name(), service.methods());
+    ///     }
+    /// }
+    /// ```
+    pub fn services(&self) -> &[ServiceInfo] {
+        &self.services
+    }
+
+    /// Set the service that handles requests which don't match any added service.
+    ///
+    /// By default such requests are answered with an `Unimplemented` status. A fallback service
+    /// can instead forward them elsewhere, e.g. to a remote backend when acting as a gateway.
+    pub fn fallback_service<S>(mut self, svc: S) -> Self
+    where
+        S: Service<Request<Body>, Error = Infallible> + Clone + Send + 'static,
+        S::Response: axum::response::IntoResponse,
+        S::Future: Send + 'static,
+    {
+        self.router = self
+            .router
+            .fallback_service(svc.map_request(|req: Request<axum::body::Body>| req.map(Body::new)));
+        self
+    }
+
+    /// Answer requests which don't match any added service with the [`Status`] returned by `f`.
+    ///
+    /// See [`Routes::fallback_service`] for forwarding these requests instead.
+    pub fn fallback_fn<F>(self, f: F) -> Self
+    where
+        F: Fn(&Request<Body>) -> Status + Clone + Send + 'static,
+    {
+        self.fallback_service(tower::service_fn(move |req: Request<Body>| {
+            let status = f(&req);
+            async move { Ok::<_, Infallible>(status.into_http::<Body>()) }
+        }))
+    }
+
+    /// Answer requests for a method that an added service doesn't have with the [`Status`]
+    /// returned by `f`.
+    ///
+    /// Requests for an unknown service are handled by the fallback, see
+    /// [`Routes::fallback_fn`], while services answer their unknown methods with an
+    /// `Unimplemented` status by default, as the gRPC spec asks for both. This allows telling
+    /// both cases apart, e.g. to answer with `NotFound` when transcoding REST requests. `f` gets
+    /// the request, so the status may depend on its headers.
+    ///
+    /// The methods of a service are the ones it lists in [`NamedService::METHODS`], which
+    /// services generated by `tonic-build` do. Services that don't list them still answer their
+    /// unknown methods themselves.
+    ///
+    /// ```
+    /// # use tonic::{service::Routes, Status};
+    /// let routes = Routes::default()
+    ///     .fallback_fn(|req| Status::not_found(format!("unknown service: {}", req.uri().path())))
+    ///     .unknown_method_fn(|req| Status::not_found(format!("unknown method: {}", req.uri().path())));
+    /// ```
+    pub fn unknown_method_fn<F>(self, f: F) -> Self
+    where
+        F: Fn(&Request<Body>) -> Status + Send + Sync + 'static,
+    {
+        Self {
+            unknown_method: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Whether `path` is a method that an added service listing its methods doesn't have.
+    fn is_unknown_method(&self, path: &str) -> bool {
+        let Some((name, method)) = path.strip_prefix('/').and_then(|path| path.split_once('/'))
+        else {
+            return false;
+        };
+        self.services.iter().any(|service| {
+            service.name == name
+                && !service.methods.is_empty()
+                && !service.methods.contains(&method)
+        })
+    }
+
+    /// Forward requests which don't match any added service to `channel`, e.g. to build a
+    /// gRPC proxy or gateway.
+    ///
+    /// Requests are forwarded as they are, without decoding their messages: the method path and
+    /// metadata are passed on, and the messages and trailers of both directions are streamed
+    /// through, so streaming methods of any kind work. The channel sets the scheme and authority
+    /// of its endpoint. Requests the channel fails to send are answered with the status of the
+    /// error, e.g. `Unavailable` when the backend can't be reached.
+    ///
+    /// This replaces any fallback set before, see [`Routes::fallback_service`].
+    ///
+    /// ```
+    /// # use tonic::{service::Routes, transport::Channel};
+    /// fn gateway(backend: Channel) -> Routes {
+    ///     Routes::default().proxy_all(backend)
+    /// }
+    /// ```
+    #[cfg(feature = "channel")]
+    pub fn proxy_all(self, channel: crate::transport::Channel) -> Self {
+        self.fallback_service(tower::service_fn(move |req: Request<Body>| {
+            let channel = channel.clone();
+            async move {
+                let response = match channel.oneshot(forwarded(req)).await {
+                    Ok(response) => response,
+                    Err(err) => Status::from_error(Box::new(err)).into_http(),
+                };
+                Ok::<_, Infallible>(response)
+            }
+        }))
+    }
+
+    /// Resolve the path of every request with `f` before routing it.
+    ///
+    /// `f` gets the request path, e.g. `/v2/helloworld.Greeter/SayHello`, and returns the path of
+    /// the method to call instead, e.g. `/helloworld.Greeter/SayHello`, or `None` to route the
+    /// request by its own path. This allows serving versioned paths or aliases of a method. By
+    /// default, requests are routed by their own path.
+    ///
+    /// When the routes are served by a [`Server`], the path is resolved before any layer of the
+    /// server, so the layers, e.g. a [`MethodFilterLayer`], and the service all see the resolved
+    /// path.
+    ///
+    /// ```
+    /// # use tonic::service::Routes;
+    /// let routes = Routes::default().resolve_path(|path| {
+    ///     path.strip_prefix("/v2").map(str::to_owned)
+    /// });
+    /// ```
+    ///
+    /// [`Server`]: crate::transport::Server
+    /// [`MethodFilterLayer`]: crate::service::MethodFilterLayer
+    pub fn resolve_path<F>(self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            resolve_path: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Takes the resolver set by [`Routes::resolve_path`], for the server to apply it before its
+    /// layers.
+    pub(crate) fn take_resolve_path(&mut self) -> Option<ResolvePath> {
+        self.resolve_path.take()
+    }
+
     /// This makes axum perform update some internals of the router that improves perf.
     ///
     /// See <https://docs.rs/axum/latest/axum/routing/struct.Router.html#a-note-about-performance>
     pub fn prepare(self) -> Self {
         Self {
             router: self.router.with_state(()),
+            ..self
         }
     }
 
     /// Convert this `Routes` into an [`axum::Router`].
+    ///
+    /// The router does not apply the resolver set by [`Routes::resolve_path`].
     pub fn into_axum_router(self) -> axum::Router {
         self.router
     }
@@ -98,6 +360,77 @@ impl Routes {
     }
 }
 
+/// A request to forward to a backend, keeping its head but none of the extensions the server
+/// added, which could change how the channel sends it.
+#[cfg(feature = "channel")]
+fn forwarded(req: Request<Body>) -> Request<Body> {
+    let (parts, body) = req.into_parts();
+    let mut forwarded = Request::new(body);
+    *forwarded.method_mut() = parts.method;
+    *forwarded.uri_mut() = parts.uri;
+    *forwarded.version_mut() = http::Version::HTTP_2;
+    *forwarded.headers_mut() = parts.headers;
+    forwarded
+}
+
+/// Builds the routes of a list of services at once.
+///
+/// # Panics
+///
+/// Panics if two services share the same [`NamedService::NAME`], as their paths would conflict.
+impl FromIterator<BoxedService> for Routes {
+    fn from_iter<I: IntoIterator<Item = BoxedService>>(iter: I) -> Self {
+        let mut names = HashSet::new();
+        iter.into_iter().fold(Self::default(), |routes, svc| {
+            assert!(
+                names.insert(svc.info.name),
+                "service `{}` is added more than once",
+                svc.info.name
+            );
+            routes.add_boxed_service(svc)
+        })
+    }
+}
+
+/// A type-erased service that remembers its [`NamedService::NAME`].
+///
+/// Services of different types can be put in a single collection, from which [`Routes`] are built
+/// with [`FromIterator`], e.g. when a server registers many services.
+pub struct BoxedService {
+    info: ServiceInfo,
+    svc: BoxCloneService<Request<Body>, axum::response::Response, Infallible>,
+}
+
+impl BoxedService {
+    /// Box `svc`.
+    pub fn new<S>(svc: S) -> Self
+    where
+        S: Service<Request<Body>, Error = Infallible> + NamedService + Clone + Send + 'static,
+        S::Response: axum::response::IntoResponse,
+        S::Future: Send + 'static,
+    {
+        Self {
+            info: ServiceInfo::of::<S>(),
+            svc: BoxCloneService::new(
+                svc.map_response(axum::response::IntoResponse::into_response),
+            ),
+        }
+    }
+
+    /// The name of the service, which is the prefix of the paths it serves.
+    pub fn name(&self) -> &'static str {
+        self.info.name
+    }
+}
+
+impl fmt::Debug for BoxedService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedService")
+            .field("name", &self.info.name)
+            .finish()
+    }
+}
+
 impl From<Routes> for RoutesBuilder {
     fn from(routes: Routes) -> Self {
         Self {
@@ -116,7 +449,45 @@ impl From<axum::Router> for RoutesBuilder {
 
 impl From<axum::Router> for Routes {
     fn from(router: axum::Router) -> Self {
-        Self { router }
+        Self {
+            router,
+            resolve_path: None,
+            unknown_method: None,
+            services: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Routes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Routes")
+            .field("router", &self.router)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Replace the path of `uri` with the one `resolve_path` returns for it, see
+/// [`Routes::resolve_path`].
+pub(crate) fn resolve_path(resolve_path: &ResolvePath, uri: &mut Uri) {
+    if let Some(path) = resolve_path(uri.path()) {
+        set_path(uri, &path);
+    }
+}
+
+/// Replace the path of `uri`, keeping its query. Invalid paths leave it unchanged.
+fn set_path(uri: &mut Uri, path: &str) {
+    let path_and_query = match uri.query() {
+        Some(query) => PathAndQuery::try_from(format!("{path}?{query}")),
+        None => PathAndQuery::try_from(path),
+    };
+    let Ok(path_and_query) = path_and_query else {
+        return;
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query);
+    if let Ok(resolved) = Uri::from_parts(parts) {
+        *uri = resolved;
     }
 }
 
@@ -143,12 +514,31 @@ where
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, req: Request<B>) -> Self::Future {
-        RoutesFuture(self.router.call(req.map(axum::body::Body::new)))
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        if let Some(resolver) = &self.resolve_path {
+            resolve_path(resolver, req.uri_mut());
+        }
+
+        if let Some(unknown_method) = &self.unknown_method {
+            if self.is_unknown_method(req.uri().path()) {
+                let req = req.map(Body::new);
+                let response = unknown_method(&req).into_http();
+                return RoutesFuture(Inner::Ready(Some(response)));
+            }
+        }
+
+        RoutesFuture(Inner::Route(
+            self.router.call(req.map(axum::body::Body::new)),
+        ))
     }
 }
 
-pub struct RoutesFuture(axum::routing::future::RouteFuture<Infallible>);
+pub struct RoutesFuture(Inner);
+
+enum Inner {
+    Route(axum::routing::future::RouteFuture<Infallible>),
+    Ready(Option<Response<Body>>),
+}
 
 impl fmt::Debug for RoutesFuture {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -160,7 +550,13 @@ impl Future for RoutesFuture {
     type Output = Result<Response<Body>, crate::BoxError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match ready!(Pin::new(&mut self.as_mut().0).poll(cx)) {
+        let route = match &mut self.0 {
+            Inner::Route(route) => route,
+            Inner::Ready(response) => {
+                return Poll::Ready(Ok(response.take().expect("polled after completion")));
+            }
+        };
+        match ready!(Pin::new(route).poll(cx)) {
             Ok(res) => Ok(res.map(Body::new)).into(),
             // NOTE: This pattern is not needed from Rust 1.82.
             // See https://github.com/rust-lang/rust/pull/122792.
@@ -169,3 +565,149 @@ impl Future for RoutesFuture {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::StatusCode;
+
+    #[derive(Clone)]
+    struct Named<const N: usize>;
+
+    impl NamedService for Named<0> {
+        const NAME: &'static str = "test.Zero";
+    }
+
+    impl NamedService for Named<1> {
+        const NAME: &'static str = "test.One";
+        const METHODS: &'static [&'static str] = &["A", "B"];
+    }
+
+    impl NamedService for Named<2> {
+        const NAME: &'static str = "test.Two";
+        const METHODS: &'static [&'static str] = &["C"];
+    }
+
+    impl<const N: usize> Service<Request<Body>> for Named<N> {
+        type Response = Response<Body>;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Request<Body>) -> Self::Future {
+            let mut res = Response::new(Body::empty());
+            res.headers_mut()
+                .insert("x-service", HeaderValue::from(N as u64));
+            std::future::ready(Ok(res))
+        }
+    }
+
+    async fn call(routes: &mut Routes, path: &str) -> Response<Body> {
+        let req = Request::post(path).body(Body::empty()).unwrap();
+        ServiceExt::<Request<Body>>::ready(routes)
+            .await
+            .unwrap()
+            .call(req)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn from_iter_routes_each_service() {
+        let services = vec![
+            BoxedService::new(Named::<0>),
+            BoxedService::new(Named::<1>),
+            BoxedService::new(Named::<2>),
+        ];
+        let mut routes: Routes = services.into_iter().collect();
+
+        for (n, path) in ["/test.Zero/A", "/test.One/B", "/test.Two/C"]
+            .into_iter()
+            .enumerate()
+        {
+            let res = call(&mut routes, path).await;
+            assert_eq!(res.headers()["x-service"], n.to_string().as_str());
+        }
+
+        let res = call(&mut routes, "/test.Three/D").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[Status::GRPC_STATUS], "12");
+    }
+
+    #[tokio::test]
+    async fn resolve_path_aliases_methods() {
+        let mut routes = Routes::new(Named::<1>).resolve_path(|path| match path {
+            "/test.OneAlias/A" => Some("/test.One/A".to_owned()),
+            _ => path.strip_prefix("/v2").map(str::to_owned),
+        });
+
+        for path in ["/test.One/A", "/test.OneAlias/A", "/v2/test.One/A"] {
+            let res = call(&mut routes, path).await;
+            assert_eq!(res.headers()["x-service"], "1", "{path}");
+        }
+
+        let res = call(&mut routes, "/v3/test.One/A").await;
+        assert_eq!(res.headers()[Status::GRPC_STATUS], "12");
+    }
+
+    #[test]
+    #[should_panic(expected = "service `test.One` is added more than once")]
+    fn from_iter_rejects_duplicate_services() {
+        let _: Routes = [BoxedService::new(Named::<1>), BoxedService::new(Named::<1>)]
+            .into_iter()
+            .collect();
+    }
+
+    #[tokio::test]
+    async fn unknown_methods_and_services_get_their_configured_status() {
+        let mut routes = Routes::new(Named::<0>)
+            .add_service(Named::<1>)
+            .fallback_fn(|_| Status::not_found("service"))
+            .unknown_method_fn(|_| Status::not_found("method"));
+
+        let res = call(&mut routes, "/test.Three/A").await;
+        assert_eq!(res.headers()[Status::GRPC_STATUS], "5");
+        assert_eq!(res.headers()[Status::GRPC_MESSAGE], "service");
+
+        let res = call(&mut routes, "/test.One/C").await;
+        assert_eq!(res.headers()[Status::GRPC_STATUS], "5");
+        assert_eq!(res.headers()[Status::GRPC_MESSAGE], "method");
+
+        let res = call(&mut routes, "/test.One/B").await;
+        assert_eq!(res.headers()["x-service"], "1");
+
+        // Services that don't list their methods answer all of them.
+        let res = call(&mut routes, "/test.Zero/C").await;
+        assert_eq!(res.headers()["x-service"], "0");
+    }
+
+    #[tokio::test]
+    async fn services_answer_unknown_methods_by_default() {
+        let mut routes = Routes::new(Named::<1>);
+
+        let res = call(&mut routes, "/test.One/C").await;
+        assert_eq!(res.headers()["x-service"], "1");
+    }
+
+    #[test]
+    fn services_lists_added_services_and_methods() {
+        let routes = Routes::new(Named::<1>).add_service(Named::<2>);
+
+        let services = routes.services();
+        assert_eq!(services.len(), 2);
+        assert_eq!(services[0].name(), "test.One");
+        assert_eq!(services[0].methods(), ["A", "B"]);
+        assert_eq!(
+            services[0].method_paths().collect::<Vec<_>>(),
+            ["/test.One/A", "/test.One/B"]
+        );
+        assert_eq!(services[1].name(), "test.Two");
+        assert_eq!(
+            services[1].method_paths().collect::<Vec<_>>(),
+            ["/test.Two/C"]
+        );
+    }
+}
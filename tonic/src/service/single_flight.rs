@@ -0,0 +1,391 @@
+//! Middleware coalescing identical concurrent requests into one call.
+
+use crate::{body::Body, BoxError, Status};
+use bytes::Bytes;
+use http::{request, HeaderMap, Request, Response};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    hash::Hash,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::sync::watch;
+use tower_layer::Layer;
+use tower_service::Service;
+
+type Flights<K> = Arc<Mutex<HashMap<FlightKey<K>, watch::Receiver<Option<Arc<Shared>>>>>>;
+
+/// A layer coalescing identical concurrent requests into a single call of the inner service,
+/// sharing its response, e.g. for a caching gateway in front of idempotent reads.
+///
+/// The key function decides which requests can be coalesced from their method path and
+/// headers, returning `None` to pass a request through. Requests are identical when their path,
+/// key and encoded request message are equal. While a call is in flight, identical requests wait
+/// for it and receive a copy of its response, and once it completes, the next request makes a
+/// new call, so responses are never shared with later requests. This applies to error statuses
+/// too. If the call fails without a response, or is dropped, the waiting requests make their own
+/// calls.
+///
+/// Only unary methods can be coalesced: the request and response bodies are buffered, so the key
+/// function must return `None` for streaming methods. Responses are shared between callers, so
+/// put everything the response depends on into the key, like the caller's credentials.
+///
+/// ```
+/// # use tonic::service::SingleFlightLayer;
+/// let layer = SingleFlightLayer::new(|parts: &http::request::Parts| {
+///     if parts.uri.path().starts_with("/catalog.Catalog/Get") {
+///         Some(parts.headers.get("authorization").cloned())
+///     } else {
+///         None
+///     }
+/// });
+///
+/// // Apply it to all services of a server through `Server::builder().layer(layer)`.
+/// ```
+pub struct SingleFlightLayer<F, K> {
+    key: Arc<F>,
+    flights: Flights<K>,
+}
+
+impl<F, K> SingleFlightLayer<F, K>
+where
+    F: Fn(&request::Parts) -> Option<K>,
+    K: Hash + Eq,
+{
+    /// Create a layer coalescing the requests `key` returns a key for.
+    pub fn new(key: F) -> Self {
+        Self {
+            key: Arc::new(key),
+            flights: Arc::default(),
+        }
+    }
+}
+
+impl<F, K> Clone for SingleFlightLayer<F, K> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            flights: self.flights.clone(),
+        }
+    }
+}
+
+impl<F, K> fmt::Debug for SingleFlightLayer<F, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SingleFlightLayer")
+            .field("key", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+impl<S, F, K> Layer<S> for SingleFlightLayer<F, K> {
+    type Service = SingleFlight<S, F, K>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SingleFlight {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// A service coalescing identical concurrent requests, see [`SingleFlightLayer`].
+pub struct SingleFlight<S, F, K> {
+    inner: S,
+    layer: SingleFlightLayer<F, K>,
+}
+
+impl<S: Clone, F, K> Clone for SingleFlight<S, F, K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+impl<S: fmt::Debug, F, K> fmt::Debug for SingleFlight<S, F, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SingleFlight")
+            .field("inner", &self.inner)
+            .field("layer", &self.layer)
+            .finish()
+    }
+}
+
+impl<S, F, K, ResBody> Service<Request<Body>> for SingleFlight<S, F, K>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send,
+    F: Fn(&request::Parts) -> Option<K>,
+    K: Hash + Eq + Clone + Send + 'static,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = SingleFlightFuture<S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let (parts, body) = req.into_parts();
+        let key = (self.layer.key)(&parts);
+
+        // The inner service was driven to readiness, so take it and leave the clone behind.
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        let req = Request::from_parts(parts, body);
+
+        let future: Pin<Box<dyn Future<Output = _> + Send>> = match key {
+            Some(key) => Box::pin(coalesce(inner, self.layer.flights.clone(), key, req)),
+            None => Box::pin(passthrough(inner, req)),
+        };
+        SingleFlightFuture { inner: future }
+    }
+}
+
+// required to use `SingleFlight` with `Router`
+impl<S, F, K> crate::server::NamedService for SingleFlight<S, F, K>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FlightKey<K> {
+    path: String,
+    key: K,
+    message: Bytes,
+}
+
+/// The buffered response of a call, shared with the identical requests.
+struct Shared {
+    parts: http::response::Parts,
+    data: Bytes,
+    trailers: Option<HeaderMap>,
+}
+
+impl Shared {
+    fn to_response(&self) -> Response<Body> {
+        let mut frames = vec![Ok::<_, std::convert::Infallible>(Frame::data(
+            self.data.clone(),
+        ))];
+        if let Some(trailers) = &self.trailers {
+            frames.push(Ok(Frame::trailers(trailers.clone())));
+        }
+        let body = Body::new(StreamBody::new(tokio_stream::iter(frames)));
+        Response::from_parts(self.parts.clone(), body)
+    }
+}
+
+/// Removes a flight once its call completes or is dropped.
+struct Landing<K: Hash + Eq> {
+    flights: Flights<K>,
+    key: Option<FlightKey<K>>,
+}
+
+impl<K: Hash + Eq> Drop for Landing<K> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.flights.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+async fn passthrough<S, ResBody>(
+    mut inner: S,
+    req: Request<Body>,
+) -> Result<Response<Body>, S::Error>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    Ok(inner.call(req).await?.map(Body::new))
+}
+
+async fn coalesce<S, K, ResBody>(
+    inner: S,
+    flights: Flights<K>,
+    key: K,
+    req: Request<Body>,
+) -> Result<Response<Body>, S::Error>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    K: Hash + Eq + Clone,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    let (parts, body) = req.into_parts();
+    let message = match body.collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(status) => return Ok(status.into_http()),
+    };
+    let key = FlightKey {
+        path: parts.uri.path().to_owned(),
+        key,
+        message,
+    };
+    let req = Request::from_parts(
+        parts,
+        Body::new(http_body_util::Full::new(key.message.clone())),
+    );
+
+    let waiting = {
+        let mut flights = flights.lock().unwrap();
+        match flights.get(&key) {
+            Some(rx) => Err(rx.clone()),
+            None => {
+                let (tx, rx) = watch::channel(None);
+                flights.insert(key.clone(), rx);
+                Ok(tx)
+            }
+        }
+    };
+
+    match waiting {
+        Ok(tx) => {
+            let _landing = Landing {
+                flights,
+                key: Some(key),
+            };
+            let shared = match call(inner, req).await? {
+                Ok(shared) => Arc::new(shared),
+                Err(response) => return Ok(response),
+            };
+            tx.send_replace(Some(shared.clone()));
+            Ok(shared.to_response())
+        }
+        Err(mut rx) => {
+            if let Ok(shared) = rx.wait_for(Option::is_some).await {
+                if let Some(shared) = &*shared {
+                    return Ok(shared.to_response());
+                }
+            }
+            // The call failed or was dropped, so make our own.
+            passthrough(inner, req).await
+        }
+    }
+}
+
+/// Calls `inner`, buffering its response, or returning the error response if the body fails.
+async fn call<S, ResBody>(
+    mut inner: S,
+    req: Request<Body>,
+) -> Result<Result<Shared, Response<Body>>, S::Error>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    let (parts, body) = inner.call(req).await?.into_parts();
+    match body.collect().await {
+        Ok(body) => {
+            let trailers = body.trailers().cloned();
+            Ok(Ok(Shared {
+                parts,
+                data: body.to_bytes(),
+                trailers,
+            }))
+        }
+        Err(err) => Ok(Err(Status::from_error(err.into()).into_http())),
+    }
+}
+
+/// Response future for [`SingleFlight`].
+pub struct SingleFlightFuture<E> {
+    inner: Pin<Box<dyn Future<Output = Result<Response<Body>, E>> + Send>>,
+}
+
+impl<E> fmt::Debug for SingleFlightFuture<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SingleFlightFuture").finish()
+    }
+}
+
+impl<E> Future for SingleFlightFuture<E> {
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+    use tower::ServiceExt;
+
+    fn request(message: &'static [u8]) -> Request<Body> {
+        Request::builder()
+            .uri("/test.Test/UnaryCall")
+            .body(Body::new(http_body_util::Full::new(Bytes::from_static(
+                message,
+            ))))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_calls_are_not_shared() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = tower::service_fn({
+            let calls = calls.clone();
+            move |_: Request<Body>| {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    if call == 0 {
+                        Err(Status::unavailable("connection reset"))
+                    } else {
+                        Ok(Response::new(Body::empty()))
+                    }
+                }
+            }
+        });
+        let svc = SingleFlightLayer::new(|_: &request::Parts| Some(())).layer(svc);
+
+        let first = tokio::spawn(svc.clone().oneshot(request(b"a")));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let second = tokio::spawn(svc.oneshot(request(b"a")));
+
+        assert!(first.await.unwrap().is_err());
+        assert!(second.await.unwrap().is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn different_messages_are_not_coalesced() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = tower::service_fn({
+            let calls = calls.clone();
+            move |_: Request<Body>| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Ok::<_, Status>(Response::new(Body::empty()))
+                }
+            }
+        });
+        let svc = SingleFlightLayer::new(|_: &request::Parts| Some(())).layer(svc);
+
+        let first = tokio::spawn(svc.clone().oneshot(request(b"a")));
+        let second = tokio::spawn(svc.oneshot(request(b"b")));
+        first.await.unwrap().unwrap();
+        second.await.unwrap().unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,441 @@
+//! Interceptors observing the final status of the calls they let through.
+
+use crate::{request::SanitizeHeaders, Status};
+use http_body::Frame;
+use pin_project::{pin_project, pinned_drop};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A gRPC interceptor that also observes the status each intercepted call ends with.
+///
+/// Like an [`Interceptor`], it is called with the request without its message, and returns it,
+/// possibly modified, or rejects it with a [`Status`]. Along with the request it returns an
+/// observer, which is called once with the final status of the call, e.g. to count errors per
+/// method. The status of streaming responses is only known once the stream ends, so the observer
+/// runs when the trailers are sent. Calls that fail or are cancelled before that, e.g. because
+/// the client went away, are observed with [`Code::Unknown`], [`Code::Internal`] or
+/// [`Code::Cancelled`].
+///
+/// Requests rejected by the interceptor itself aren't observed.
+///
+/// Any function that satisfies the bound
+/// `FnMut(&str, Request<()>) -> Result<(Request<()>, O), Status>`, where `O: FnOnce(&Status)`,
+/// can be used as a `StatusInterceptor`. The first argument is the path of the request, e.g.
+/// `/helloworld.Greeter/SayHello`.
+///
+/// [`Interceptor`]: crate::service::Interceptor
+/// [`Code::Unknown`]: crate::Code::Unknown
+/// [`Code::Internal`]: crate::Code::Internal
+/// [`Code::Cancelled`]: crate::Code::Cancelled
+pub trait StatusInterceptor {
+    /// Observes the final status of an intercepted call.
+    type Observer: FnOnce(&Status);
+
+    /// Intercept a request to `method`, optionally cancelling it, and return the observer of its
+    /// status.
+    fn call(
+        &mut self,
+        method: &str,
+        request: crate::Request<()>,
+    ) -> Result<(crate::Request<()>, Self::Observer), Status>;
+}
+
+impl<F, O> StatusInterceptor for F
+where
+    F: FnMut(&str, crate::Request<()>) -> Result<(crate::Request<()>, O), Status>,
+    O: FnOnce(&Status),
+{
+    type Observer = O;
+
+    fn call(
+        &mut self,
+        method: &str,
+        request: crate::Request<()>,
+    ) -> Result<(crate::Request<()>, O), Status> {
+        self(method, request)
+    }
+}
+
+/// A [`StatusInterceptor`] that can be used as a [`Layer`].
+///
+/// ```
+/// # use tonic::{service::StatusInterceptorLayer, Code, Request, Status};
+/// # use std::{collections::HashMap, sync::{Arc, Mutex}};
+/// let errors = Arc::new(Mutex::new(HashMap::<String, u64>::new()));
+///
+/// let layer = StatusInterceptorLayer::new(move |method: &str, request: Request<()>| {
+///     let (errors, method) = (errors.clone(), method.to_owned());
+///     let observe = move |status: &Status| {
+///         if status.code() != Code::Ok {
+///             *errors.lock().unwrap().entry(method).or_default() += 1;
+///         }
+///     };
+///     Ok::<_, Status>((request, observe))
+/// });
+///
+/// // Apply it to all services of a server through `Server::builder().layer(layer)`.
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct StatusInterceptorLayer<I> {
+    interceptor: I,
+}
+
+impl<I> StatusInterceptorLayer<I> {
+    /// Create a new status interceptor layer.
+    ///
+    /// See [`StatusInterceptor`] for more details.
+    pub fn new(interceptor: I) -> Self {
+        Self { interceptor }
+    }
+}
+
+impl<S, I> Layer<S> for StatusInterceptorLayer<I>
+where
+    I: Clone,
+{
+    type Service = StatusInterceptedService<S, I>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        StatusInterceptedService::new(service, self.interceptor.clone())
+    }
+}
+
+/// A service wrapped in a status interceptor middleware.
+///
+/// See [`StatusInterceptor`] for more details.
+#[derive(Clone, Copy)]
+pub struct StatusInterceptedService<S, I> {
+    inner: S,
+    interceptor: I,
+}
+
+impl<S, I> StatusInterceptedService<S, I> {
+    /// Create a new `StatusInterceptedService` that wraps `S` and intercepts each request with
+    /// `I`.
+    pub fn new(service: S, interceptor: I) -> Self {
+        Self {
+            inner: service,
+            interceptor,
+        }
+    }
+}
+
+impl<S, I> fmt::Debug for StatusInterceptedService<S, I>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatusInterceptedService")
+            .field("inner", &self.inner)
+            .field(
+                "interceptor",
+                &format_args!("{}", std::any::type_name::<I>()),
+            )
+            .finish()
+    }
+}
+
+impl<S, I, ReqBody, ResBody> Service<http::Request<ReqBody>> for StatusInterceptedService<S, I>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    I: StatusInterceptor,
+{
+    type Response = http::Response<StatusResponseBody<ResBody, I::Observer>>;
+    type Error = S::Error;
+    type Future = StatusResponseFuture<S::Future, I::Observer>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        // See `InterceptedService::call` for why the message is kept away from the interceptor.
+        let uri = req.uri().clone();
+        let method = req.method().clone();
+        let version = req.version();
+        let req = crate::Request::from_http(req);
+        let (metadata, extensions, msg) = req.into_parts();
+
+        match self.interceptor.call(
+            uri.path(),
+            crate::Request::from_parts(metadata, extensions, ()),
+        ) {
+            Ok((req, observer)) => {
+                let (metadata, extensions, _) = req.into_parts();
+                let req = crate::Request::from_parts(metadata, extensions, msg);
+                let req = req.into_http(uri, method, version, SanitizeHeaders::No);
+                StatusResponseFuture::new(self.inner.call(req), observer)
+            }
+            Err(status) => StatusResponseFuture {
+                kind: Kind::Status(Some(status)),
+                observer: None,
+            },
+        }
+    }
+}
+
+// required to use `StatusInterceptedService` with `Router`
+impl<S, I> crate::server::NamedService for StatusInterceptedService<S, I>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+    const METHODS: &'static [&'static str] = S::METHODS;
+}
+
+/// An observer of the final status of a call, for the middleware of the server to name their
+/// futures and bodies.
+#[cfg(feature = "server")]
+pub(crate) type BoxObserver = Box<dyn FnOnce(&Status) + Send>;
+
+/// Response future for [`StatusInterceptedService`].
+#[pin_project(PinnedDrop)]
+pub struct StatusResponseFuture<F, O: FnOnce(&Status)> {
+    #[pin]
+    kind: Kind<F>,
+    observer: Option<O>,
+}
+
+#[pin_project(project = KindProj)]
+enum Kind<F> {
+    Future(#[pin] F),
+    Status(Option<Status>),
+}
+
+impl<F, O: FnOnce(&Status)> StatusResponseFuture<F, O> {
+    /// Observe the final status of the response `future` resolves to.
+    pub(crate) fn new(future: F, observer: O) -> Self {
+        Self {
+            kind: Kind::Future(future),
+            observer: Some(observer),
+        }
+    }
+}
+
+impl<F, O: FnOnce(&Status)> fmt::Debug for StatusResponseFuture<F, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatusResponseFuture").finish()
+    }
+}
+
+impl<F, O, B, E> Future for StatusResponseFuture<F, O>
+where
+    F: Future<Output = Result<http::Response<B>, E>>,
+    O: FnOnce(&Status),
+{
+    type Output = Result<http::Response<StatusResponseBody<B, O>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let future = match this.kind.project() {
+            KindProj::Future(future) => future,
+            KindProj::Status(status) => {
+                let (parts, ()) = status.take().unwrap().into_http::<()>().into_parts();
+                let body = StatusResponseBody {
+                    inner: None,
+                    observer: None,
+                };
+                return Poll::Ready(Ok(http::Response::from_parts(parts, body)));
+            }
+        };
+
+        let result = ready!(future.poll(cx));
+        let observer = this.observer.take().expect("polled after completion");
+
+        match result {
+            Ok(response) => {
+                // Trailers-only responses carry their status in the headers.
+                let observer = match Status::from_header_map(response.headers()) {
+                    Some(status) => {
+                        observer(&status);
+                        None
+                    }
+                    None => Some(observer),
+                };
+
+                Poll::Ready(Ok(response.map(|inner| StatusResponseBody {
+                    inner: Some(inner),
+                    observer,
+                })))
+            }
+            Err(err) => {
+                observer(&Status::unknown("the service failed to respond"));
+                Poll::Ready(Err(err))
+            }
+        }
+    }
+}
+
+#[pinned_drop]
+impl<F, O: FnOnce(&Status)> PinnedDrop for StatusResponseFuture<F, O> {
+    fn drop(self: Pin<&mut Self>) {
+        if let Some(observer) = self.project().observer.take() {
+            observer(&Status::cancelled("the call was cancelled"));
+        }
+    }
+}
+
+/// Response body for [`StatusInterceptedService`], observing the status in its trailers.
+#[pin_project(PinnedDrop)]
+pub struct StatusResponseBody<B, O: FnOnce(&Status)> {
+    #[pin]
+    inner: Option<B>,
+    observer: Option<O>,
+}
+
+impl<B, O: FnOnce(&Status)> fmt::Debug for StatusResponseBody<B, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatusResponseBody").finish()
+    }
+}
+
+impl<B, O> http_body::Body for StatusResponseBody<B, O>
+where
+    B: http_body::Body,
+    O: FnOnce(&Status),
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let Some(inner) = this.inner.as_pin_mut() else {
+            return Poll::Ready(None);
+        };
+        let frame = ready!(inner.poll_frame(cx));
+
+        if let Some(observer) = this.observer.take() {
+            let status = match &frame {
+                Some(Ok(frame)) => frame.trailers_ref().map(|trailers| {
+                    Status::from_header_map(trailers)
+                        .unwrap_or_else(|| Status::unknown("grpc-status missing from trailers"))
+                }),
+                Some(Err(_)) => Some(Status::internal("failed to send the response")),
+                None => Some(Status::unknown("the response ended without grpc-status")),
+            };
+
+            match status {
+                Some(status) => observer(&status),
+                None => *this.observer = Some(observer),
+            }
+        }
+
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.inner {
+            Some(inner) => inner.is_end_stream(),
+            None => true,
+        }
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        match &self.inner {
+            Some(inner) => inner.size_hint(),
+            None => http_body::SizeHint::with_exact(0),
+        }
+    }
+}
+
+#[pinned_drop]
+impl<B, O: FnOnce(&Status)> PinnedDrop for StatusResponseBody<B, O> {
+    fn drop(self: Pin<&mut Self>) {
+        if let Some(observer) = self.project().observer.take() {
+            observer(&Status::cancelled("the call was cancelled"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{body::Body, Code};
+    use http::HeaderMap;
+    use http_body_util::BodyExt;
+    use std::sync::{Arc, Mutex};
+    use tower::ServiceExt;
+
+    type Observed = Arc<Mutex<Vec<(String, Code)>>>;
+
+    fn layer(observed: Observed) -> StatusInterceptorLayer<impl StatusInterceptor + Clone> {
+        StatusInterceptorLayer::new(move |method: &str, request: crate::Request<()>| {
+            let (observed, method) = (observed.clone(), method.to_owned());
+            let observe = move |status: &Status| {
+                observed.lock().unwrap().push((method, status.code()));
+            };
+            Ok::<_, Status>((request, observe))
+        })
+    }
+
+    #[tokio::test]
+    async fn observes_success_and_error_statuses() {
+        let svc = tower::service_fn(|req: http::Request<()>| async move {
+            if req.uri().path() == "/test.Test/Fail" {
+                return Ok::<_, Status>(Status::not_found("missing").into_http::<Body>());
+            }
+
+            let mut trailers = HeaderMap::new();
+            Status::ok("").add_header(&mut trailers).unwrap();
+            let body = Body::new(http_body_util::StreamBody::new(tokio_stream::iter([
+                Ok::<_, Status>(Frame::data(bytes::Bytes::from_static(b"data"))),
+                Ok(Frame::trailers(trailers)),
+            ])));
+            Ok(http::Response::new(body))
+        });
+        let observed = Observed::default();
+        let svc = layer(observed.clone()).layer(svc);
+
+        let req = http::Request::post("/test.Test/Succeed").body(()).unwrap();
+        let body = svc.clone().oneshot(req).await.unwrap().into_body();
+        // The status of a streaming response is only known once its trailers are sent.
+        assert!(observed.lock().unwrap().is_empty());
+        body.collect().await.unwrap();
+
+        let req = http::Request::post("/test.Test/Fail").body(()).unwrap();
+        svc.oneshot(req).await.unwrap();
+
+        assert_eq!(
+            *observed.lock().unwrap(),
+            [
+                ("/test.Test/Succeed".to_owned(), Code::Ok),
+                ("/test.Test/Fail".to_owned(), Code::NotFound),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn observes_dropped_responses_as_cancelled() {
+        let svc = tower::service_fn(|_: http::Request<()>| async {
+            Ok::<_, Status>(http::Response::new(Body::new(
+                http_body_util::StreamBody::new(tokio_stream::pending::<
+                    Result<Frame<bytes::Bytes>, Status>,
+                >()),
+            )))
+        });
+        let observed = Observed::default();
+
+        let req = http::Request::post("/test.Test/Stream").body(()).unwrap();
+        let response = layer(observed.clone())
+            .layer(svc)
+            .oneshot(req)
+            .await
+            .unwrap();
+        drop(response);
+
+        assert_eq!(
+            *observed.lock().unwrap(),
+            [("/test.Test/Stream".to_owned(), Code::Cancelled)]
+        );
+    }
+}
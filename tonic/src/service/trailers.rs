@@ -0,0 +1,371 @@
+//! Middleware observing and modifying the trailers of gRPC responses.
+
+use crate::{metadata::MetadataMap, Code, Status};
+use bytes::Bytes;
+use http::HeaderMap;
+use http_body::Frame;
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The trailers of a gRPC response, passed to the callback of a [`TrailersInterceptorLayer`].
+///
+/// The status code and message can be changed, and custom trailers added or removed through the
+/// [`metadata`](Self::metadata_mut), but the `grpc-status` is always sent.
+#[derive(Debug)]
+pub struct Trailers {
+    code: Code,
+    message: String,
+    details: Bytes,
+    metadata: MetadataMap,
+}
+
+impl Trailers {
+    /// The status code of the response.
+    pub fn code(&self) -> Code {
+        self.code
+    }
+
+    /// Change the status code of the response.
+    pub fn set_code(&mut self, code: Code) {
+        self.code = code;
+    }
+
+    /// The status message of the response.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Change the status message of the response.
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = message.into();
+    }
+
+    /// The custom trailers of the response.
+    ///
+    /// For trailers-only responses, which carry the status in the response headers, e.g. most
+    /// error responses, this holds the other response headers as well.
+    pub fn metadata(&self) -> &MetadataMap {
+        &self.metadata
+    }
+
+    /// A mutable reference to the custom trailers of the response.
+    ///
+    /// The `grpc-status`, `grpc-message` and `grpc-status-details-bin` entries are set from the
+    /// status after the callback returns, so they can't be changed or removed here.
+    pub fn metadata_mut(&mut self) -> &mut MetadataMap {
+        &mut self.metadata
+    }
+}
+
+/// Runs `f` on the status carried by `headers`, if any, and writes the result back.
+fn intercept<F>(f: &F, headers: &mut HeaderMap)
+where
+    F: Fn(&mut Trailers),
+{
+    let Some(status) = Status::from_header_map(headers) else {
+        return;
+    };
+
+    let mut trailers = Trailers {
+        code: status.code(),
+        message: status.message().to_owned(),
+        details: Bytes::copy_from_slice(status.details()),
+        metadata: status.metadata().clone(),
+    };
+    f(&mut trailers);
+
+    let status = Status::with_details(trailers.code, trailers.message, trailers.details);
+    match status.to_header_map() {
+        Ok(status_headers) => {
+            *headers = trailers.metadata.into_headers();
+            headers.extend(status_headers);
+        }
+        Err(err) => {
+            tracing::debug!("failed to encode intercepted trailers: {}", err);
+        }
+    }
+}
+
+/// A layer passing the trailers of every gRPC response to a callback, after the handler but
+/// before they are sent.
+///
+/// This is the response-side counterpart of an [`Interceptor`], e.g. to normalize error
+/// messages or to add a trailer to every response. The callback runs for the trailers sent at
+/// the end of the response body, and for trailers-only responses, which carry the status in the
+/// response headers. See [`Trailers`] for what can be changed.
+///
+/// ```
+/// # use tonic::service::TrailersInterceptorLayer;
+/// let layer = TrailersInterceptorLayer::new(|trailers| {
+///     trailers
+///         .metadata_mut()
+///         .insert("x-server-version", "1.2.3".parse().unwrap());
+/// });
+///
+/// // Apply it to all services of a server through `Server::builder().layer(layer)`.
+/// ```
+///
+/// [`Interceptor`]: crate::service::Interceptor
+pub struct TrailersInterceptorLayer<F> {
+    f: Arc<F>,
+}
+
+impl<F> TrailersInterceptorLayer<F>
+where
+    F: Fn(&mut Trailers),
+{
+    /// Create a layer passing the trailers of every response to `f`.
+    pub fn new(f: F) -> Self {
+        Self { f: Arc::new(f) }
+    }
+}
+
+impl<F> Clone for TrailersInterceptorLayer<F> {
+    fn clone(&self) -> Self {
+        Self { f: self.f.clone() }
+    }
+}
+
+impl<F> fmt::Debug for TrailersInterceptorLayer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrailersInterceptorLayer")
+            .field("f", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+impl<S, F> Layer<S> for TrailersInterceptorLayer<F> {
+    type Service = TrailersInterceptedService<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TrailersInterceptedService {
+            inner,
+            f: self.f.clone(),
+        }
+    }
+}
+
+/// A service whose response trailers are intercepted, see [`TrailersInterceptorLayer`].
+pub struct TrailersInterceptedService<S, F> {
+    inner: S,
+    f: Arc<F>,
+}
+
+impl<S: Clone, F> Clone for TrailersInterceptedService<S, F> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            f: self.f.clone(),
+        }
+    }
+}
+
+impl<S, F> fmt::Debug for TrailersInterceptedService<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrailersInterceptedService")
+            .field("inner", &self.inner)
+            .field("f", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+impl<S, F, ReqBody, ResBody> Service<http::Request<ReqBody>> for TrailersInterceptedService<S, F>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    F: Fn(&mut Trailers),
+{
+    type Response = http::Response<TrailersResponseBody<ResBody, F>>;
+    type Error = S::Error;
+    type Future = TrailersResponseFuture<S::Future, F>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        TrailersResponseFuture {
+            inner: self.inner.call(req),
+            f: Some(self.f.clone()),
+        }
+    }
+}
+
+// required to use `TrailersInterceptedService` with `Router`
+impl<S, F> crate::server::NamedService for TrailersInterceptedService<S, F>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+}
+
+/// Response future for [`TrailersInterceptedService`].
+#[pin_project]
+pub struct TrailersResponseFuture<T, F> {
+    #[pin]
+    inner: T,
+    f: Option<Arc<F>>,
+}
+
+impl<T, F> fmt::Debug for TrailersResponseFuture<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrailersResponseFuture").finish()
+    }
+}
+
+impl<T, F, B, E> Future for TrailersResponseFuture<T, F>
+where
+    T: Future<Output = Result<http::Response<B>, E>>,
+    F: Fn(&mut Trailers),
+{
+    type Output = Result<http::Response<TrailersResponseBody<B, F>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut response = ready!(this.inner.poll(cx))?;
+        let f = this.f.take().expect("polled after completion");
+
+        // Trailers-only responses carry their status in the headers, and have no trailers.
+        let f = if response.headers().contains_key(Status::GRPC_STATUS) {
+            intercept(&*f, response.headers_mut());
+            None
+        } else {
+            Some(f)
+        };
+
+        Poll::Ready(Ok(response.map(|inner| TrailersResponseBody { inner, f })))
+    }
+}
+
+/// Response body for [`TrailersInterceptedService`].
+#[pin_project]
+pub struct TrailersResponseBody<B, F> {
+    #[pin]
+    inner: B,
+    f: Option<Arc<F>>,
+}
+
+impl<B, F> fmt::Debug for TrailersResponseBody<B, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrailersResponseBody").finish()
+    }
+}
+
+impl<B, F> http_body::Body for TrailersResponseBody<B, F>
+where
+    B: http_body::Body,
+    F: Fn(&mut Trailers),
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+
+        let frame = match frame {
+            Some(Ok(frame)) if frame.is_trailers() => {
+                match (this.f.take(), frame.into_trailers()) {
+                    (Some(f), Ok(mut trailers)) => {
+                        intercept(&*f, &mut trailers);
+                        Some(Ok(Frame::trailers(trailers)))
+                    }
+                    (_, Ok(trailers)) => Some(Ok(Frame::trailers(trailers))),
+                    (_, Err(frame)) => Some(Ok(frame)),
+                }
+            }
+            frame => frame,
+        };
+
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    fn layer() -> TrailersInterceptorLayer<impl Fn(&mut Trailers)> {
+        TrailersInterceptorLayer::new(|trailers: &mut Trailers| {
+            trailers
+                .metadata_mut()
+                .insert("x-server-version", "1.2.3".parse().unwrap());
+            trailers.metadata_mut().remove("grpc-status");
+            if trailers.code() == Code::Internal {
+                trailers.set_message("internal error");
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn intercepts_body_trailers() {
+        let svc = tower::service_fn(|_: http::Request<()>| async {
+            let mut trailers = HeaderMap::new();
+            Status::ok("").add_header(&mut trailers).unwrap();
+            let body = Body::new(http_body_util::StreamBody::new(tokio_stream::iter([
+                Ok::<_, Status>(Frame::data(bytes::Bytes::from_static(b"data"))),
+                Ok(Frame::trailers(trailers)),
+            ])));
+            Ok::<_, Status>(http::Response::new(body))
+        });
+
+        let response = layer()
+            .layer(svc)
+            .oneshot(http::Request::new(()))
+            .await
+            .unwrap();
+        let trailers = response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .trailers()
+            .cloned()
+            .unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+        assert_eq!(trailers["x-server-version"], "1.2.3");
+    }
+
+    #[tokio::test]
+    async fn intercepts_trailers_only_responses() {
+        let svc = tower::service_fn(|_: http::Request<()>| async {
+            Ok::<_, Status>(Status::internal("stack trace").into_http::<Body>())
+        });
+
+        let response = layer()
+            .layer(svc)
+            .oneshot(http::Request::new(()))
+            .await
+            .unwrap();
+        let headers = response.headers();
+        assert_eq!(headers["grpc-status"], "13");
+        assert_eq!(headers["grpc-message"], "internal%20error");
+        assert_eq!(headers["x-server-version"], "1.2.3");
+        assert_eq!(headers["content-type"], "application/grpc");
+    }
+}
@@ -14,6 +14,7 @@ const ENCODING_SET: &AsciiSet = &CONTROLS
     .add(b' ')
     .add(b'"')
     .add(b'#')
+    .add(b'%')
     .add(b'<')
     .add(b'>')
     .add(b'`')
@@ -304,6 +305,13 @@ impl Status {
         Status::new(Code::Unauthenticated, message)
     }
 
+    /// A status with `code`, the message of `err` and `err` as its source.
+    fn with_source(code: Code, err: impl Error + Send + Sync + 'static) -> Status {
+        let mut status = Status::new(code, err.to_string());
+        status.source = Some(Arc::new(err));
+        status
+    }
+
     pub(crate) fn from_error_generic(
         err: impl Into<Box<dyn Error + Send + Sync + 'static>>,
     ) -> Status {
@@ -365,10 +373,15 @@ impl Status {
         status
     }
 
-    #[cfg(feature = "server")]
+    #[cfg(any(feature = "server", feature = "channel"))]
     fn code_from_h2(err: &h2::Error) -> Code {
+        Self::code_from_h2_reason(err.reason())
+    }
+
+    #[cfg(any(feature = "server", feature = "channel"))]
+    fn code_from_h2_reason(reason: Option<h2::Reason>) -> Code {
         // See https://github.com/grpc/grpc/blob/3977c30/doc/PROTOCOL-HTTP2.md#errors
-        match err.reason() {
+        match reason {
             Some(h2::Reason::NO_ERROR)
             | Some(h2::Reason::PROTOCOL_ERROR)
             | Some(h2::Reason::INTERNAL_ERROR)
@@ -417,7 +430,7 @@ impl Status {
             return Some(Status::cancelled(err.to_string()));
         }
 
-        #[cfg(feature = "server")]
+        #[cfg(any(feature = "server", feature = "channel"))]
         if let Some(h2_err) = err.source().and_then(|e| e.downcast_ref::<h2::Error>()) {
             let code = Status::code_from_h2(h2_err);
             let status = Self::new(code, format!("h2 protocol error: {}", err));
@@ -447,11 +460,15 @@ impl Status {
             None => Ok(String::new()),
         };
 
+        // Details that can't be decoded are dropped, the status is still meaningful without them.
         let details = match header_map.get(Self::GRPC_STATUS_DETAILS) {
-            Some(header) => crate::util::base64::STANDARD
-                .decode(header.as_bytes())
-                .expect("Invalid status header, expected base64 encoded value")
-                .into(),
+            Some(header) => match crate::util::base64::STANDARD.decode(header.as_bytes()) {
+                Ok(details) => details.into(),
+                Err(e) => {
+                    warn!("Error deserializing status details header: {e}");
+                    Bytes::new()
+                }
+            },
             None => Bytes::new(),
         };
 
@@ -492,6 +509,12 @@ impl Status {
     }
 
     /// Get the opaque error details of this `Status`.
+    ///
+    /// These are usually an encoded `google.rpc.Status` message, which the `StatusExt` trait of
+    /// the [`tonic-types`] crate decodes into the standard error details, e.g. a `RetryInfo`.
+    /// Details a client receives that aren't valid base64 are dropped.
+    ///
+    /// [`tonic-types`]: https://docs.rs/tonic-types
     pub fn details(&self) -> &[u8] {
         &self.details
     }
@@ -620,6 +643,12 @@ fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
             return Some(Status::unavailable(connect.to_string()));
         }
 
+        #[cfg(any(feature = "server", feature = "channel"))]
+        if let Some(h2) = err.downcast_ref::<crate::transport::Http2Error>() {
+            let code = Status::code_from_h2_reason(Some(h2.reason()));
+            return Some(Status::new(code, format!("h2 protocol error: {}", h2)));
+        }
+
         #[cfg(any(feature = "server", feature = "channel"))]
         if let Some(hyper) = err
             .downcast_ref::<hyper::Error>()
@@ -681,9 +710,46 @@ impl From<Status> for h2::Error {
     }
 }
 
+/// Maps an IO error to a status by its [kind](std::io::ErrorKind), so handlers can use `?` on
+/// IO operations:
+///
+/// | Kind | Code |
+/// |------|------|
+/// | `NotFound` | `NotFound` |
+/// | `PermissionDenied` | `PermissionDenied` |
+/// | `AlreadyExists` | `AlreadyExists` |
+/// | `InvalidInput` | `InvalidArgument` |
+/// | `InvalidData` | `DataLoss` |
+/// | `TimedOut` | `DeadlineExceeded` |
+/// | `UnexpectedEof` | `OutOfRange` |
+/// | `ConnectionAborted` | `Aborted` |
+/// | `ConnectionRefused`, `ConnectionReset`, `NotConnected`, `AddrInUse`, `AddrNotAvailable` | `Unavailable` |
+/// | `BrokenPipe`, `WouldBlock`, `WriteZero`, `Interrupted` | `Internal` |
+/// | others | `Unknown` |
+///
+/// An IO error wrapping a `Status`, e.g. returned by a body or stream, is converted to that
+/// status instead. The message is the error's, which may reveal details like file paths, so
+/// to use a different code or message, map the error before `?`:
+///
+/// ```
+/// # use tonic::Status;
+/// fn read_config() -> Result<Vec<u8>, Status> {
+///     let config = std::fs::read("/etc/app.toml")
+///         .map_err(|_| Status::failed_precondition("no configuration"))?;
+///     Ok(config)
+/// }
+/// ```
 impl From<std::io::Error> for Status {
     fn from(err: std::io::Error) -> Self {
         use std::io::ErrorKind;
+
+        if err.get_ref().is_some_and(|inner| inner.is::<Status>()) {
+            let inner = err.into_inner().expect("the error has an inner error");
+            return *inner
+                .downcast::<Status>()
+                .expect("the inner error is a status");
+        }
+
         let code = match err.kind() {
             ErrorKind::BrokenPipe
             | ErrorKind::WouldBlock
@@ -704,7 +770,45 @@ impl From<std::io::Error> for Status {
             ErrorKind::UnexpectedEof => Code::OutOfRange,
             _ => Code::Unknown,
         };
-        Status::new(code, err.to_string())
+        Status::with_source(code, err)
+    }
+}
+
+/// Maps an elapsed [`tokio::time::timeout`] to `DeadlineExceeded`.
+#[cfg(any(feature = "server", feature = "channel"))]
+impl From<tokio::time::error::Elapsed> for Status {
+    fn from(err: tokio::time::error::Elapsed) -> Self {
+        Status::with_source(Code::DeadlineExceeded, err)
+    }
+}
+
+/// Maps invalid UTF-8 to `InvalidArgument`, as it usually comes from request input.
+impl From<std::str::Utf8Error> for Status {
+    fn from(err: std::str::Utf8Error) -> Self {
+        Status::with_source(Code::InvalidArgument, err)
+    }
+}
+
+/// Maps invalid UTF-8 to `InvalidArgument`, as it usually comes from request input.
+impl From<std::string::FromUtf8Error> for Status {
+    fn from(err: std::string::FromUtf8Error) -> Self {
+        Status::with_source(Code::InvalidArgument, err)
+    }
+}
+
+/// Maps a failure to parse an integer to `InvalidArgument`, as it usually comes from request
+/// input.
+impl From<std::num::ParseIntError> for Status {
+    fn from(err: std::num::ParseIntError) -> Self {
+        Status::with_source(Code::InvalidArgument, err)
+    }
+}
+
+/// Maps a failure to parse a float to `InvalidArgument`, as it usually comes from request
+/// input.
+impl From<std::num::ParseFloatError> for Status {
+    fn from(err: std::num::ParseFloatError) -> Self {
+        Status::with_source(Code::InvalidArgument, err)
     }
 }
 
@@ -782,6 +886,40 @@ impl Code {
         Code::from(i)
     }
 
+    /// Get the integer value of the `Code`, as sent in the `grpc-status` header.
+    pub const fn to_i32(self) -> i32 {
+        self as i32
+    }
+
+    /// Get the HTTP status that corresponds to the `Code`, for answering HTTP/JSON clients.
+    ///
+    /// This follows the mapping documented in [`google/rpc/code.proto`], which is also used by
+    /// gRPC-HTTP gateways. It is not the inverse of the mapping gRPC clients apply to responses
+    /// from non-gRPC servers.
+    ///
+    /// [`google/rpc/code.proto`]: https://github.com/googleapis/googleapis/blob/master/google/rpc/code.proto
+    pub fn to_http_status(self) -> http::StatusCode {
+        match self {
+            Code::Ok => http::StatusCode::OK,
+            // Client Closed Request, a non-standard status from nginx.
+            Code::Cancelled => http::StatusCode::from_u16(499).unwrap(),
+            Code::Unknown | Code::Internal | Code::DataLoss => {
+                http::StatusCode::INTERNAL_SERVER_ERROR
+            }
+            Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+                http::StatusCode::BAD_REQUEST
+            }
+            Code::DeadlineExceeded => http::StatusCode::GATEWAY_TIMEOUT,
+            Code::NotFound => http::StatusCode::NOT_FOUND,
+            Code::AlreadyExists | Code::Aborted => http::StatusCode::CONFLICT,
+            Code::PermissionDenied => http::StatusCode::FORBIDDEN,
+            Code::ResourceExhausted => http::StatusCode::TOO_MANY_REQUESTS,
+            Code::Unimplemented => http::StatusCode::NOT_IMPLEMENTED,
+            Code::Unavailable => http::StatusCode::SERVICE_UNAVAILABLE,
+            Code::Unauthenticated => http::StatusCode::UNAUTHORIZED,
+        }
+    }
+
     /// Convert the string representation of a `Code` (as stored, for example, in the `grpc-status`
     /// header in a response) into a `Code`. Returns `Code::Unknown` if the code string is not a
     /// valid gRPC status code.
@@ -964,6 +1102,62 @@ mod tests {
         assert_eq!(Code::from(-1), Code::Unknown);
     }
 
+    #[test]
+    fn from_io_error() {
+        let status = Status::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(status.code(), Code::NotFound);
+        assert!(status.source().is_some());
+
+        let status = Status::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+
+        let wrapped = std::io::Error::other(Status::resource_exhausted("quota"));
+        let status = Status::from(wrapped);
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.message(), "quota");
+    }
+
+    #[test]
+    fn from_parse_errors() {
+        let status = Status::from("x".parse::<u32>().unwrap_err());
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let status = Status::from(String::from_utf8(vec![0xff]).unwrap_err());
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[test]
+    fn code_conversions() {
+        let table = [
+            (Code::Ok, 0, 200),
+            (Code::Cancelled, 1, 499),
+            (Code::Unknown, 2, 500),
+            (Code::InvalidArgument, 3, 400),
+            (Code::DeadlineExceeded, 4, 504),
+            (Code::NotFound, 5, 404),
+            (Code::AlreadyExists, 6, 409),
+            (Code::PermissionDenied, 7, 403),
+            (Code::ResourceExhausted, 8, 429),
+            (Code::FailedPrecondition, 9, 400),
+            (Code::Aborted, 10, 409),
+            (Code::OutOfRange, 11, 400),
+            (Code::Unimplemented, 12, 501),
+            (Code::Internal, 13, 500),
+            (Code::Unavailable, 14, 503),
+            (Code::DataLoss, 15, 500),
+            (Code::Unauthenticated, 16, 401),
+        ];
+
+        for (code, i, http_status) in table {
+            assert_eq!(code.to_i32(), i);
+            assert_eq!(Code::from_i32(i), code);
+            assert_eq!(code.to_http_status().as_u16(), http_status, "{code:?}");
+        }
+
+        assert_eq!(Code::from_i32(17), Code::Unknown);
+        assert_eq!(Code::from_i32(i32::MIN), Code::Unknown);
+    }
+
     #[test]
     fn constructors() {
         assert_eq!(Status::ok("").code(), Code::Ok);
@@ -1009,6 +1203,38 @@ mod tests {
 
         assert_eq!(status.details(), DETAILS);
     }
+
+    #[test]
+    fn invalid_details() {
+        let mut header_map = HeaderMap::new();
+        header_map.insert(Status::GRPC_STATUS, HeaderValue::from_static("14"));
+        header_map.insert(Status::GRPC_MESSAGE, HeaderValue::from_static("try again"));
+        header_map.insert(
+            Status::GRPC_STATUS_DETAILS,
+            HeaderValue::from_static("not base64!"),
+        );
+
+        let status = Status::from_header_map(&header_map).unwrap();
+
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(status.message(), "try again");
+        assert!(status.details().is_empty());
+        assert!(status.metadata().is_empty());
+    }
+
+    #[test]
+    fn message_percent_encoding() {
+        let status = Status::internal("50%25 off: café");
+
+        let header_map = status.to_header_map().unwrap();
+        assert_eq!(
+            header_map[Status::GRPC_MESSAGE],
+            "50%2525%20off:%20caf%C3%A9"
+        );
+
+        let status = Status::from_header_map(&header_map).unwrap();
+        assert_eq!(status.message(), "50%25 off: café");
+    }
 }
 
 /// Error returned if a request didn't complete within the configured timeout.
@@ -1,15 +1,19 @@
+use super::resolve::{Resolve, SharedResolver};
 #[cfg(feature = "_tls-any")]
 use super::service::TlsConnector;
-use super::service::{self, Executor, SharedExec};
+use super::service::{self, Executor, MethodTimeouts, SharedExec};
 use super::Channel;
 #[cfg(feature = "_tls-any")]
 use super::ClientTlsConfig;
-use crate::transport::Error;
+use crate::transport::{
+    go_away::{GoAway, GoAwayHook},
+    Error,
+};
 use bytes::Bytes;
 use http::{uri::Uri, HeaderValue};
 use hyper::rt;
 use hyper_util::client::legacy::connect::HttpConnector;
-use std::{fmt, future::Future, pin::Pin, str::FromStr, time::Duration};
+use std::{fmt, future::Future, pin::Pin, str::FromStr, sync::Arc, time::Duration};
 use tower_service::Service;
 
 /// Channel builder.
@@ -34,9 +38,15 @@ pub struct Endpoint {
     pub(crate) http2_keep_alive_timeout: Option<Duration>,
     pub(crate) http2_keep_alive_while_idle: Option<bool>,
     pub(crate) http2_max_header_list_size: Option<u32>,
+    pub(crate) http2_max_idle: Option<Duration>,
+    pub(crate) on_goaway_received: Option<GoAwayHook>,
     pub(crate) connect_timeout: Option<Duration>,
     pub(crate) http2_adaptive_window: Option<bool>,
+    pub(crate) wait_for_ready: bool,
+    pub(crate) retry_budget: Option<(f32, u32)>,
+    pub(crate) method_timeouts: Option<MethodTimeouts>,
     pub(crate) executor: SharedExec,
+    pub(crate) resolver: Option<SharedResolver>,
 }
 
 impl Endpoint {
@@ -85,14 +95,15 @@ impl Endpoint {
 
     /// Set a custom user-agent header.
     ///
-    /// `user_agent` will be prepended to Tonic's default user-agent string (`tonic/x.x.x`).
+    /// `user_agent` will be prepended to Tonic's default user-agent string
+    /// (`grpc-rust-tonic/x.x.x`).
     /// It must be a value that can be converted into a valid  `http::HeaderValue` or building
     /// the endpoint will fail.
     /// ```
     /// # use tonic::transport::Endpoint;
     /// # let mut builder = Endpoint::from_static("https://example.com");
     /// builder.user_agent("Greeter").expect("Greeter should be a valid header value");
-    /// // user-agent: "Greeter tonic/x.x.x"
+    /// // user-agent: "Greeter grpc-rust-tonic/x.x.x"
     /// ```
     pub fn user_agent<T>(self, user_agent: T) -> Result<Self, Error>
     where
@@ -151,6 +162,10 @@ impl Endpoint {
 
     /// Apply a timeout to connecting to the uri.
     ///
+    /// For [`Endpoint::connect`] the timeout covers the whole connection setup, including the
+    /// TLS and HTTP/2 handshakes. Reconnects, and the first connection of a channel created
+    /// with [`Endpoint::connect_lazy`], only apply it to establishing the TCP connection.
+    ///
     /// Defaults to no timeout.
     ///
     /// ```
@@ -250,7 +265,7 @@ impl Endpoint {
             tls: Some(
                 tls_config
                     .into_tls_connector(&self.uri)
-                    .map_err(Error::from_source)?,
+                    .map_err(Error::new_tls)?,
             ),
             ..self
         })
@@ -265,6 +280,11 @@ impl Endpoint {
     }
 
     /// Set http2 KEEP_ALIVE_INTERVAL. Uses `hyper`'s default otherwise.
+    ///
+    /// The client then sends an HTTP/2 `PING` frame at this interval while requests are in
+    /// flight, and closes the connection if one isn't acknowledged within
+    /// [`Endpoint::keep_alive_timeout`]. See [`Endpoint::keep_alive_while_idle`] to also ping
+    /// idle connections.
     pub fn http2_keep_alive_interval(self, interval: Duration) -> Self {
         Endpoint {
             http2_keep_alive_interval: Some(interval),
@@ -281,6 +301,24 @@ impl Endpoint {
     }
 
     /// Set http2 KEEP_ALIVE_WHILE_IDLE. Uses `hyper`'s default otherwise.
+    ///
+    /// If enabled, the keepalive pings of [`Endpoint::http2_keep_alive_interval`] are sent even
+    /// when the connection has no requests in flight. This keeps idle connections open through
+    /// NATs and load balancers that drop connections without traffic, which would otherwise fail
+    /// the first request after a quiet period. Does nothing without a keepalive interval.
+    ///
+    /// Servers may limit how often clients may ping, so the interval should be agreed with the
+    /// operator of the server. A tonic `Server` answers every ping.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # use std::time::Duration;
+    /// // Pass a load balancer that drops connections idle for more than 60 seconds.
+    /// let endpoint = Endpoint::from_static("http://[::1]:50051")
+    ///     .http2_keep_alive_interval(Duration::from_secs(30))
+    ///     .keep_alive_timeout(Duration::from_secs(10))
+    ///     .keep_alive_while_idle(true);
+    /// ```
     pub fn keep_alive_while_idle(self, enabled: bool) -> Self {
         Endpoint {
             http2_keep_alive_while_idle: Some(enabled),
@@ -288,6 +326,49 @@ impl Endpoint {
         }
     }
 
+    /// Close connections that had no requests in flight for `duration`.
+    ///
+    /// An idle connection holds a socket and the resources of the server it is connected to.
+    /// Once closed, the channel reconnects on the next request, resolving the address of the
+    /// endpoint again, which also picks up changes to its DNS records. A request counts as in
+    /// flight until its response body is dropped, so streaming calls keep the connection open.
+    ///
+    /// Connections are kept open for as long as the channel is used by default.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # use std::time::Duration;
+    /// let endpoint = Endpoint::from_static("http://[::1]:50051")
+    ///     .http2_max_idle(Duration::from_secs(300));
+    /// ```
+    pub fn http2_max_idle(self, duration: Duration) -> Self {
+        Endpoint {
+            http2_max_idle: Some(duration),
+            ..self
+        }
+    }
+
+    /// Call `f` with every `GOAWAY` frame received from the server, announcing that it closes
+    /// the connection.
+    ///
+    /// Servers send these when they recycle connections, e.g. during rollouts, on shutdown, or
+    /// when overloaded, so logging them helps to explain reconnects and failed requests. The
+    /// channel reconnects on its own. `f` is called on the task driving the connection, so it
+    /// must not block.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// let endpoint = Endpoint::from_static("http://[::1]:50051").on_goaway_received(|go_away| {
+    ///     tracing::info!(code = go_away.code(), "server is closing the connection");
+    /// });
+    /// ```
+    pub fn on_goaway_received(self, f: impl Fn(&GoAway) + Send + Sync + 'static) -> Self {
+        Endpoint {
+            on_goaway_received: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
     /// Sets whether to use an adaptive flow control. Uses `hyper`'s default otherwise.
     pub fn http2_adaptive_window(self, enabled: bool) -> Self {
         Endpoint {
@@ -306,6 +387,90 @@ impl Endpoint {
         }
     }
 
+    /// Sets the default wait-for-ready behavior of requests sent on the channel.
+    ///
+    /// When enabled, requests are held until a connection is established instead of
+    /// failing fast with `Unavailable`, which is useful when the client may start before
+    /// the server. Combine with [`Endpoint::connect_lazy`] so that creating the channel
+    /// does not fail either. Individual requests can override this with
+    /// [`Request::set_wait_for_ready`](crate::Request::set_wait_for_ready).
+    ///
+    /// Default is `false`.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.wait_for_ready(true);
+    /// ```
+    pub fn wait_for_ready(self, enabled: bool) -> Self {
+        Endpoint {
+            wait_for_ready: enabled,
+            ..self
+        }
+    }
+
+    /// Throttles the retries sent on the channel, to avoid amplifying the load on a struggling
+    /// server.
+    ///
+    /// Each request that is not a retry adds `ratio` retries to a budget, for example `0.1` allows
+    /// one retry every ten requests, and every retry sent takes one out, on top of a reserve of
+    /// `min_per_sec` retries per second for channels that send few requests. Requests count for
+    /// 10 seconds. Retries are told apart by their `grpc-previous-rpc-attempts` header, see
+    /// [`Request::set_previous_rpc_attempts`](crate::Request::set_previous_rpc_attempts), and
+    /// those exceeding the budget fail with `ResourceExhausted` without being sent.
+    ///
+    /// This follows the retry throttling of gRPC, see [gRFC A6], so that when failures spike,
+    /// retries are capped at a fraction of the traffic instead of doubling it. The `ratio` is
+    /// capped at `1000`.
+    ///
+    /// Default is no budget, every retry is sent.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.retry_budget(0.1, 10);
+    /// ```
+    ///
+    /// [gRFC A6]: https://github.com/grpc/proposal/blob/master/A6-client-retries.md#retry-throttling
+    pub fn retry_budget(self, ratio: f32, min_per_sec: u32) -> Self {
+        Endpoint {
+            retry_budget: Some((ratio, min_per_sec)),
+            ..self
+        }
+    }
+
+    /// Sets default timeouts for calls to the given methods, keyed by their path such as
+    /// `/helloworld.Greeter/SayHello`.
+    ///
+    /// A call to one of these methods without a timeout of its own, see
+    /// [`Request::set_timeout`](crate::Request::set_timeout), is sent with the method's default
+    /// as its `grpc-timeout` header. Like the method config of the gRPC service config, this
+    /// keeps deadlines in one place instead of at every call site. The channel enforces them the
+    /// same way as timeouts set on the request, together with [`Endpoint::timeout`].
+    ///
+    /// Default is no timeouts. Calling this again replaces the previous timeouts.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # use std::time::Duration;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.method_timeouts([
+    ///     ("/helloworld.Greeter/SayHello", Duration::from_millis(200)),
+    ///     ("/helloworld.Greeter/SayGoodbye", Duration::from_secs(1)),
+    /// ]);
+    /// ```
+    pub fn method_timeouts<I, K>(self, timeouts: I) -> Self
+    where
+        I: IntoIterator<Item = (K, Duration)>,
+        K: Into<String>,
+    {
+        let timeouts = timeouts.into_iter().map(|(path, dur)| (path.into(), dur));
+        Endpoint {
+            method_timeouts: Some(MethodTimeouts::new(timeouts)),
+            ..self
+        }
+    }
+
     /// Sets the executor used to spawn async tasks.
     ///
     /// Uses `tokio::spawn` by default.
@@ -317,6 +482,30 @@ impl Endpoint {
         self
     }
 
+    /// Sets a custom resolver for the authority of this endpoint.
+    ///
+    /// The resulting channel connects to every address returned by the resolver and balances
+    /// requests across them, while still addressing the endpoint's original authority. The
+    /// authority is resolved again every 30 seconds to pick up changes.
+    ///
+    /// By default, the authority is resolved through the system DNS resolver and a single
+    /// connection is used.
+    ///
+    /// ```
+    /// # use tonic::transport::{channel::ResolveFuture, Endpoint};
+    /// # use http::uri::Authority;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.resolver(|_: &Authority| -> ResolveFuture {
+    ///     Box::pin(async { Ok(vec!["127.0.0.1:50051".parse()?]) })
+    /// });
+    /// ```
+    pub fn resolver(self, resolver: impl Resolve) -> Self {
+        Endpoint {
+            resolver: Some(SharedResolver::new(resolver)),
+            ..self
+        }
+    }
+
     pub(crate) fn connector<C>(&self, c: C) -> service::Connector<C> {
         service::Connector::new(
             c,
@@ -326,7 +515,16 @@ impl Endpoint {
     }
 
     /// Create a channel from this config.
+    ///
+    /// Unlike [`Endpoint::connect_lazy`], this establishes the connection right away and only
+    /// returns once the channel is ready to send requests, so the first request does not pay for
+    /// connecting. Fails if the server cannot be reached within the
+    /// [connect timeout](Endpoint::connect_timeout).
     pub async fn connect(&self) -> Result<Channel, Error> {
+        if let Some(resolver) = &self.resolver {
+            return Channel::resolve(resolver.clone(), self.clone()).await;
+        }
+
         let mut http = HttpConnector::new();
         http.enforce_http(false);
         http.set_nodelay(self.tcp_nodelay);
@@ -342,7 +540,16 @@ impl Endpoint {
     ///
     /// The channel returned by this method does not attempt to connect to the endpoint until first
     /// use.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`resolver`](Endpoint::resolver) is set and the endpoint's URI has no
+    /// authority.
     pub fn connect_lazy(&self) -> Channel {
+        if let Some(resolver) = &self.resolver {
+            return Channel::resolve_lazy(resolver.clone(), self.clone());
+        }
+
         let mut http = HttpConnector::new();
         http.enforce_http(false);
         http.set_nodelay(self.tcp_nodelay);
@@ -456,9 +663,15 @@ impl From<Uri> for Endpoint {
             http2_keep_alive_timeout: None,
             http2_keep_alive_while_idle: None,
             http2_max_header_list_size: None,
+            http2_max_idle: None,
+            on_goaway_received: None,
             connect_timeout: None,
             http2_adaptive_window: None,
+            wait_for_ready: false,
+            retry_budget: None,
+            method_timeouts: None,
             executor: SharedExec::tokio(),
+            resolver: None,
         }
     }
 }
@@ -0,0 +1,222 @@
+use crate::{transport::service::grpc_timeout::try_parse_grpc_timeout, Request, Status};
+use std::{
+    fmt,
+    future::{poll_fn, Future},
+    pin::Pin,
+    task::Poll,
+    time::Duration,
+};
+use tokio::time::{sleep_until, Instant};
+
+/// Runs calls to several backends in parallel within the deadline of an inbound request.
+///
+/// A handler fanning out to other services should give up on them once its own caller gave up.
+/// `FanOut` takes the deadline of the inbound request, from its `grpc-timeout`, and passes the
+/// time that remains of it on to every outbound request built with [`FanOut::request`], so the
+/// backends stop working on calls nobody waits for. [`FanOut::join`] then drives the calls
+/// concurrently, and once the deadline expires, cancels the ones still in flight, which resets
+/// their streams on the [`Channel`](super::Channel).
+///
+/// ```
+/// # use tonic::{transport::channel::FanOut, Request, Response, Status};
+/// # async fn lookup(request: Request<String>) -> Result<Response<u32>, Status> { unimplemented!() }
+/// async fn handler(request: Request<Vec<String>>) -> Result<Response<Vec<u32>>, Status> {
+///     let fan_out = FanOut::from_request(&request);
+///     let calls = request
+///         .into_inner()
+///         .into_iter()
+///         .map(|key| lookup(fan_out.request(key)));
+///
+///     let mut values = Vec::new();
+///     for result in fan_out.join(calls).await {
+///         values.push(result?.into_inner());
+///     }
+///     Ok(Response::new(values))
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct FanOut {
+    deadline: Option<Instant>,
+}
+
+impl FanOut {
+    /// Fan out within the deadline of `request`.
+    ///
+    /// The deadline is the `grpc-timeout` of the request, counted from when the server received
+    /// it if the request carries [`RequestTiming`], or from now otherwise. Requests without a
+    /// valid `grpc-timeout` have no deadline.
+    ///
+    /// [`RequestTiming`]: crate::transport::server::RequestTiming
+    pub fn from_request<T>(request: &Request<T>) -> Self {
+        let timeout = try_parse_grpc_timeout(request.metadata().as_headers()).unwrap_or(None);
+        #[cfg(feature = "server")]
+        let received_at = request
+            .extensions()
+            .get::<crate::transport::server::RequestTiming>()
+            .map(|timing| Instant::from_std(timing.received_at()));
+        #[cfg(not(feature = "server"))]
+        let received_at = None;
+
+        Self {
+            deadline: timeout.map(|timeout| received_at.unwrap_or_else(Instant::now) + timeout),
+        }
+    }
+
+    /// Fan out within `deadline`.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+        }
+    }
+
+    /// The deadline of the calls, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The time that remains until the deadline, if any.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Create an outbound request for `message`, with its timeout set to the time remaining until
+    /// the deadline, see [`Request::set_timeout`].
+    pub fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        if let Some(remaining) = self.remaining() {
+            request.set_timeout(remaining);
+        }
+        request
+    }
+
+    /// Drive `calls` concurrently and return their results, in the order of `calls`.
+    ///
+    /// Calls still in flight once the deadline expires are dropped, cancelling them, and result
+    /// in [`Code::DeadlineExceeded`](crate::Code::DeadlineExceeded). The results of calls that
+    /// completed before are kept.
+    pub async fn join<I, F, T>(&self, calls: I) -> Vec<Result<T, Status>>
+    where
+        I: IntoIterator<Item = F>,
+        F: Future<Output = Result<T, Status>>,
+    {
+        let mut calls: Vec<_> = calls.into_iter().map(|call| Some(Box::pin(call))).collect();
+        let mut results: Vec<Option<Result<T, Status>>> = calls.iter().map(|_| None).collect();
+        let mut expired = self
+            .deadline
+            .map(|deadline| Box::pin(sleep_until(deadline)));
+
+        poll_fn(|cx| {
+            let mut pending = false;
+            for (call, result) in calls.iter_mut().zip(&mut results) {
+                if let Some(future) = call {
+                    match future.as_mut().poll(cx) {
+                        Poll::Ready(output) => {
+                            *result = Some(output);
+                            *call = None;
+                        }
+                        Poll::Pending => pending = true,
+                    }
+                }
+            }
+
+            if !pending {
+                return Poll::Ready(());
+            }
+            match &mut expired {
+                Some(expired) => Pin::new(expired).poll(cx),
+                None => Poll::Pending,
+            }
+        })
+        .await;
+
+        // Cancel the calls still in flight.
+        drop(calls);
+        let mut joined = Vec::with_capacity(results.len());
+        for result in results {
+            joined.push(match result {
+                Some(result) => result,
+                None => Err(Status::deadline_exceeded(
+                    "deadline expired before the call completed",
+                )),
+            });
+        }
+        joined
+    }
+}
+
+impl fmt::Debug for FanOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FanOut")
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Code;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Counts the calls that were dropped before they completed.
+    struct Cancelled(Arc<AtomicUsize>);
+
+    impl Drop for Cancelled {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn propagates_the_inbound_deadline() {
+        let mut inbound = Request::new(());
+        inbound.set_timeout(Duration::from_secs(10));
+        let fan_out = FanOut::from_request(&inbound);
+
+        let outbound = fan_out.request(());
+        let timeout = try_parse_grpc_timeout(outbound.metadata().as_headers())
+            .unwrap()
+            .unwrap();
+        assert!(timeout <= Duration::from_secs(10));
+        assert!(timeout > Duration::from_secs(9));
+
+        let unbounded = FanOut::from_request(&Request::new(()));
+        assert!(unbounded.deadline().is_none());
+        assert!(unbounded
+            .request(())
+            .metadata()
+            .get("grpc-timeout")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn cancels_calls_in_flight_once_the_deadline_expires() {
+        let fan_out = FanOut::with_deadline(Instant::now() + Duration::from_millis(50));
+        let cancelled = Arc::new(AtomicUsize::new(0));
+
+        let calls = (0..3u64).map(|i| {
+            let guard = Cancelled(cancelled.clone());
+            async move {
+                // The first call completes in time, the others would hang forever.
+                if i > 0 {
+                    std::future::pending::<()>().await;
+                }
+                std::mem::forget(guard);
+                Ok(i)
+            }
+        });
+
+        let start = Instant::now();
+        let results = fan_out.join(calls).await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(results[0].as_ref().unwrap(), &0);
+        for result in &results[1..] {
+            assert_eq!(result.as_ref().unwrap_err().code(), Code::DeadlineExceeded);
+        }
+        assert_eq!(cancelled.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,17 +1,26 @@
 //! Client implementation and builder.
 
 mod endpoint;
+mod fan_out;
+mod resolve;
 pub(crate) mod service;
 #[cfg(feature = "_tls-any")]
 mod tls;
 
-pub use self::service::Change;
+pub use self::service::{Change, ConnectivityState};
 pub use endpoint::Endpoint;
+pub use fan_out::FanOut;
+pub use resolve::{Resolve, ResolveFuture};
 #[cfg(feature = "_tls-any")]
 pub use tls::ClientTlsConfig;
 
-use self::service::{Connection, DynamicServiceStream, Executor, SharedExec};
-use crate::body::Body;
+use self::resolve::{ResolveTask, SharedResolver};
+use self::service::{
+    Connection, ConnectivityTracker, DynamicServiceStream, Executor, MethodTimeouts,
+    ReadinessProbe, RequestNotSent, RetryBudget, SharedExec,
+};
+use super::service::grpc_timeout::try_parse_grpc_timeout;
+use crate::{body::Body, extensions::WaitForReady, TimeoutExpired};
 use bytes::Bytes;
 use http::{
     uri::{InvalidUri, Uri},
@@ -21,10 +30,16 @@ use std::{
     fmt,
     future::Future,
     hash::Hash,
+    net::SocketAddr,
     pin::Pin,
-    task::{Context, Poll},
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::sync::{
+    mpsc::{channel, Sender},
+    watch,
 };
-use tokio::sync::mpsc::{channel, Sender};
+use tokio::time::Instant;
 
 use hyper::rt;
 use tower::balance::p2c::Balance;
@@ -32,13 +47,22 @@ use tower::{
     buffer::{future::ResponseFuture as BufferResponseFuture, Buffer},
     discover::Discover,
     util::BoxService,
-    Service,
+    Service, ServiceExt,
 };
 
 type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 const DEFAULT_BUFFER_SIZE: usize = 1024;
 
+const WAIT_FOR_READY_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const WAIT_FOR_READY_MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The number of times a request is sent again after connections closed before writing it.
+const MAX_RESENDS: u32 = 3;
+
+type BufferedService =
+    Buffer<Request<Body>, BoxFuture<'static, Result<Response<Body>, crate::BoxError>>>;
+
 /// A default batteries included `transport` channel.
 ///
 /// This provides a fully featured http2 gRPC client based on `hyper`
@@ -64,14 +88,28 @@ const DEFAULT_BUFFER_SIZE: usize = 1024;
 /// cloning the `Channel` type is cheap and encouraged.
 #[derive(Clone)]
 pub struct Channel {
-    svc: Buffer<Request<Body>, BoxFuture<'static, Result<Response<Body>, crate::BoxError>>>,
+    svc: BufferedService,
+    wait_for_ready: bool,
+    retry_budget: Option<RetryBudget>,
+    method_timeouts: Option<MethodTimeouts>,
+    timeout: Option<Duration>,
+    connectivity: ConnectivityTracker,
 }
 
 /// A future that resolves to an HTTP response.
 ///
 /// This is returned by the `Service::call` on [`Channel`].
 pub struct ResponseFuture {
-    inner: BufferResponseFuture<BoxFuture<'static, Result<Response<Body>, crate::BoxError>>>,
+    inner: ResponseFutureInner,
+}
+
+enum ResponseFutureInner {
+    Buffered {
+        future: BufferResponseFuture<BoxFuture<'static, Result<Response<Body>, crate::BoxError>>>,
+        svc: BufferedService,
+        deadline: Option<Instant>,
+    },
+    Boxed(BoxFuture<'static, Result<Response<Body>, crate::BoxError>>),
 }
 
 impl Channel {
@@ -140,8 +178,12 @@ impl Channel {
         E: Executor<Pin<Box<dyn Future<Output = ()> + Send>>> + Send + Sync + 'static,
     {
         let (tx, rx) = channel(capacity);
-        let list = DynamicServiceStream::new(rx);
-        (Self::balance(list, DEFAULT_BUFFER_SIZE, executor), tx)
+        let connectivity = ConnectivityTracker::new();
+        let list = DynamicServiceStream::new(rx, connectivity.clone());
+        (
+            Self::balance(list, DEFAULT_BUFFER_SIZE, executor, connectivity),
+            tx,
+        )
     }
 
     /// Create a new [`Channel`] using a custom connector to the provided [Endpoint].
@@ -156,18 +198,33 @@ impl Channel {
     {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
         let executor = endpoint.executor.clone();
+        let wait_for_ready = endpoint.wait_for_ready;
+        let retry_budget = endpoint.retry_budget.map(RetryBudget::new);
+        let method_timeouts = endpoint.method_timeouts.clone();
+        let timeout = endpoint.timeout;
 
-        let svc = Connection::lazy(connector, endpoint);
+        let connectivity = ConnectivityTracker::new();
+        let svc = Connection::lazy(connector, endpoint, &connectivity);
         let (svc, worker) = Buffer::pair(svc, buffer_size);
 
         executor.execute(worker);
 
-        Channel { svc }
+        Channel {
+            svc,
+            wait_for_ready,
+            retry_budget,
+            method_timeouts,
+            timeout,
+            connectivity,
+        }
     }
 
     /// Connect to the provided [`Endpoint`] using the provided connector, and return a new [`Channel`].
     ///
     /// This is a lower level API, prefer to use [`Endpoint::connect`] if you are not using a custom connector.
+    ///
+    /// Like [`Endpoint::connect`], this only returns once the connection is ready to be used, and
+    /// fails if that takes longer than the [connect timeout](Endpoint::connect_timeout).
     pub async fn connect<C>(connector: C, endpoint: Endpoint) -> Result<Self, super::Error>
     where
         C: Service<Uri> + Send + 'static,
@@ -177,17 +234,112 @@ impl Channel {
     {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
         let executor = endpoint.executor.clone();
-
-        let svc = Connection::connect(connector, endpoint)
-            .await
-            .map_err(super::Error::from_source)?;
+        let wait_for_ready = endpoint.wait_for_ready;
+        let retry_budget = endpoint.retry_budget.map(RetryBudget::new);
+        let method_timeouts = endpoint.method_timeouts.clone();
+        let timeout = endpoint.timeout;
+
+        let connect_timeout = endpoint.connect_timeout;
+
+        let connectivity = ConnectivityTracker::new();
+        let connect = Connection::connect(connector, endpoint, &connectivity);
+        let svc = match connect_timeout {
+            Some(connect_timeout) => tokio::time::timeout(connect_timeout, connect)
+                .await
+                .map_err(|_| super::Error::from_source(TimeoutExpired(())))?,
+            None => connect.await,
+        }
+        .map_err(super::Error::from_source)?;
         let (svc, worker) = Buffer::pair(svc, buffer_size);
         executor.execute(worker);
 
-        Ok(Channel { svc })
+        Ok(Channel {
+            svc,
+            wait_for_ready,
+            retry_budget,
+            method_timeouts,
+            timeout,
+            connectivity,
+        })
+    }
+
+    /// Watch the connectivity state of this channel, e.g. to show its health on a dashboard or
+    /// to hold requests back until it is [`Ready`](ConnectivityState::Ready).
+    ///
+    /// The state is shared by all clones of the channel. A channel created by
+    /// [`Endpoint::connect_lazy`] is `Idle` until its first request, which makes it `Connecting`,
+    /// then `Ready` once connected. A failed connection attempt, or a connection lost without
+    /// the server closing it gracefully, makes it `TransientFailure` until the next request
+    /// reconnects. A connection closed gracefully, e.g. by [`Endpoint::http2_max_idle`], makes it
+    /// `Idle` again. See [`ConnectivityState`] for the state of channels balancing requests
+    /// across several endpoints.
+    ///
+    /// ```
+    /// # use tonic::transport::{channel::ConnectivityState, Channel};
+    /// async fn wait_until_ready(channel: &Channel) {
+    ///     let mut state = channel.connectivity();
+    ///     let _ = state
+    ///         .wait_for(|state| *state == ConnectivityState::Ready)
+    ///         .await;
+    /// }
+    /// ```
+    pub fn connectivity(&self) -> watch::Receiver<ConnectivityState> {
+        self.connectivity.subscribe()
+    }
+
+    pub(crate) async fn resolve(
+        resolver: SharedResolver,
+        endpoint: Endpoint,
+    ) -> Result<Self, super::Error> {
+        let (channel, mut task) =
+            Self::resolve_task(resolver, endpoint.clone()).map_err(super::Error::from_source)?;
+
+        task.refresh().await.map_err(super::Error::from_source)?;
+        endpoint.executor.execute(Box::pin(task.run(true)));
+
+        Ok(channel)
+    }
+
+    pub(crate) fn resolve_lazy(resolver: SharedResolver, endpoint: Endpoint) -> Self {
+        let (channel, task) = Self::resolve_task(resolver, endpoint.clone())
+            .expect("endpoint with a resolver must have an authority");
+        endpoint.executor.execute(Box::pin(task.run(false)));
+
+        channel
+    }
+
+    fn resolve_task(
+        resolver: SharedResolver,
+        endpoint: Endpoint,
+    ) -> Result<(Self, ResolveTask), crate::BoxError> {
+        let (tx, rx) = channel::<Change<SocketAddr, Endpoint>>(DEFAULT_BUFFER_SIZE);
+        let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
+        let wait_for_ready = endpoint.wait_for_ready;
+        let retry_budget = endpoint.retry_budget.map(RetryBudget::new);
+        let method_timeouts = endpoint.method_timeouts.clone();
+        let timeout = endpoint.timeout;
+        let executor = endpoint.executor.clone();
+        let connectivity = ConnectivityTracker::new();
+
+        let task = ResolveTask::new(resolver, endpoint, tx)?;
+        let discover = DynamicServiceStream::new(rx, connectivity.clone());
+        let channel = Channel {
+            wait_for_ready,
+            retry_budget,
+            method_timeouts,
+            timeout,
+            ..Self::balance(discover, buffer_size, executor, connectivity)
+        };
+
+        Ok((channel, task))
     }
 
-    pub(crate) fn balance<D, E>(discover: D, buffer_size: usize, executor: E) -> Self
+    pub(crate) fn balance<D, E>(
+        discover: D,
+        buffer_size: usize,
+        executor: E,
+        connectivity: ConnectivityTracker,
+    ) -> Self
     where
         D: Discover<Service = Connection> + Unpin + Send + 'static,
         D::Error: Into<crate::BoxError>,
@@ -200,7 +352,14 @@ impl Channel {
         let (svc, worker) = Buffer::pair(svc, buffer_size);
         executor.execute(Box::pin(worker));
 
-        Channel { svc }
+        Channel {
+            svc,
+            wait_for_ready: false,
+            retry_budget: None,
+            method_timeouts: None,
+            timeout: None,
+            connectivity,
+        }
     }
 }
 
@@ -213,20 +372,174 @@ impl Service<http::Request<Body>> for Channel {
         Service::poll_ready(&mut self.svc, cx).map_err(super::Error::from_source)
     }
 
-    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
-        let inner = Service::call(&mut self.svc, request);
+    fn call(&mut self, mut request: http::Request<Body>) -> Self::Future {
+        if let Some(method_timeouts) = &self.method_timeouts {
+            method_timeouts.apply(&mut request);
+        }
+
+        if let Some(retry_budget) = &self.retry_budget {
+            if !retry_budget.withdraw(request.headers()) {
+                let status = RetryBudget::exhausted();
+                let inner = ResponseFutureInner::Boxed(Box::pin(async move { Err(status.into()) }));
+                return ResponseFuture { inner };
+            }
+        }
+
+        // Requests are timed out on each connection, see `Connection`, so resending one takes
+        // the time already spent into account.
+        let deadline = try_parse_grpc_timeout(request.headers())
+            .unwrap_or(None)
+            .into_iter()
+            .chain(self.timeout)
+            .min()
+            .map(|timeout| Instant::now() + timeout);
+
+        let wait_for_ready = request
+            .extensions()
+            .get::<WaitForReady>()
+            .map_or(self.wait_for_ready, |w| w.0);
+
+        let inner = if wait_for_ready {
+            // The buffer handed out a slot in `poll_ready`, use it for the first
+            // probe and wait for readiness on a clone.
+            let probe = Service::call(&mut self.svc, readiness_probe());
+            let svc = self.svc.clone();
+            ResponseFutureInner::Boxed(Box::pin(wait_for_ready_then_call(
+                svc, probe, request, deadline,
+            )))
+        } else {
+            ResponseFutureInner::Buffered {
+                future: Service::call(&mut self.svc, request),
+                svc: self.svc.clone(),
+                deadline,
+            }
+        };
 
         ResponseFuture { inner }
     }
 }
 
+fn readiness_probe() -> Request<Body> {
+    let mut probe = Request::new(Body::empty());
+    probe.extensions_mut().insert(ReadinessProbe);
+    probe
+}
+
+/// Holds `request` until the connection is established, then sends it.
+///
+/// Readiness is checked by sending probes through the buffer, which drive the
+/// connection attempts but are never sent to the server. Waiting is bounded by
+/// the request's `grpc-timeout`, if any.
+async fn wait_for_ready_then_call<F>(
+    mut svc: BufferedService,
+    probe: F,
+    request: Request<Body>,
+    deadline: Option<Instant>,
+) -> Result<Response<Body>, crate::BoxError>
+where
+    F: Future<Output = Result<Response<Body>, crate::BoxError>>,
+{
+    let timeout = try_parse_grpc_timeout(request.headers()).unwrap_or(None);
+
+    let wait = async {
+        let mut backoff = WAIT_FOR_READY_INITIAL_BACKOFF;
+        let mut result = probe.await;
+
+        while let Err(err) = result {
+            tracing::debug!("channel not ready, retrying in {:?}: {}", backoff, err);
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(WAIT_FOR_READY_MAX_BACKOFF);
+
+            result = svc.ready().await?.call(readiness_probe()).await;
+        }
+
+        Ok::<_, crate::BoxError>(())
+    };
+
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| TimeoutExpired(()))??,
+        None => wait.await?,
+    }
+
+    resend_until_written(svc, request, 0, deadline).await
+}
+
+/// Sends `request`, sending it again whenever the connection it was dispatched
+/// to closed before writing it, e.g. because the server sent GOAWAY while the
+/// request was queued. The buffer reconnects before handing out the next slot,
+/// so the retry goes out on a fresh connection.
+///
+/// `not_sent` is the number of times the request was already sent without being
+/// written. It is sent again at most [`MAX_RESENDS`] times and not past its
+/// `deadline`, so a server that keeps closing connections fails it instead of
+/// holding it forever.
+async fn resend_until_written(
+    mut svc: BufferedService,
+    mut request: Request<Body>,
+    mut not_sent: u32,
+    deadline: Option<Instant>,
+) -> Result<Response<Body>, crate::BoxError> {
+    let resend = async {
+        loop {
+            match svc.ready().await?.call(request).await {
+                Err(err) => match err.downcast::<RequestNotSent>() {
+                    Ok(err) if not_sent < MAX_RESENDS => {
+                        tracing::debug!("{}, sending it again", err);
+                        not_sent += 1;
+                        request = err.into_request();
+                    }
+                    Ok(err) => return Err(err as crate::BoxError),
+                    Err(err) => return Err(err),
+                },
+                res => return res,
+            }
+        }
+    };
+
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, resend)
+            .await
+            .map_err(|_| TimeoutExpired(()))?,
+        None => resend.await,
+    }
+}
+
 impl Future for ResponseFuture {
     type Output = Result<Response<Body>, super::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Pin::new(&mut self.inner)
-            .poll(cx)
-            .map_err(super::Error::from_source)
+        loop {
+            let result = match &mut self.inner {
+                ResponseFutureInner::Buffered {
+                    future,
+                    svc,
+                    deadline,
+                } => match ready!(Pin::new(future).poll(cx)) {
+                    Err(err) => match err.downcast::<RequestNotSent>() {
+                        Ok(not_sent) => {
+                            tracing::debug!("{}, sending it again", not_sent);
+                            let resend = resend_until_written(
+                                svc.clone(),
+                                not_sent.into_request(),
+                                1,
+                                *deadline,
+                            );
+                            self.inner = ResponseFutureInner::Boxed(Box::pin(resend));
+                            continue;
+                        }
+                        Err(err) => Err(err),
+                    },
+                    res => res,
+                },
+                ResponseFutureInner::Boxed(inner) => ready!(inner.as_mut().poll(cx)),
+            };
+
+            return Poll::Ready(
+                result.map_err(|err| super::Error::from_source(super::Http2Error::wrap(err))),
+            );
+        }
     }
 }
 
@@ -241,3 +554,54 @@ impl fmt::Debug for ResponseFuture {
         f.debug_struct("ResponseFuture").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    /// A service whose connections all close before writing the requests, like a server that
+    /// keeps closing connections, counting the requests sent to it.
+    fn closing_connections(delay: Duration) -> (BufferedService, Arc<AtomicU32>) {
+        let sent = Arc::new(AtomicU32::new(0));
+        let svc = tower::service_fn({
+            let sent = sent.clone();
+            move |request: Request<Body>| {
+                sent.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    tokio::time::sleep(delay).await;
+                    Err(RequestNotSent::new(request, "connection closed").into())
+                }) as BoxFuture<'static, _>
+            }
+        });
+        let (svc, worker) = Buffer::pair(svc, 1);
+        tokio::spawn(worker);
+        (svc, sent)
+    }
+
+    #[tokio::test]
+    async fn resends_a_bounded_number_of_times() {
+        let (svc, sent) = closing_connections(Duration::ZERO);
+
+        let err = resend_until_written(svc, Request::new(Body::empty()), 0, None)
+            .await
+            .unwrap_err();
+        assert!(err.is::<RequestNotSent>());
+        assert_eq!(sent.load(Ordering::SeqCst), MAX_RESENDS + 1);
+    }
+
+    #[tokio::test]
+    async fn stops_resending_at_the_deadline() {
+        let (svc, sent) = closing_connections(Duration::from_millis(100));
+
+        let deadline = Instant::now() + Duration::from_millis(150);
+        let err = resend_until_written(svc, Request::new(Body::empty()), 0, Some(deadline))
+            .await
+            .unwrap_err();
+        assert!(err.is::<TimeoutExpired>());
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+}
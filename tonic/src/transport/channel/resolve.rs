@@ -0,0 +1,141 @@
+use super::{Change, Endpoint};
+use http::uri::{Authority, Uri};
+use std::{
+    collections::HashSet, fmt, future::Future, net::SocketAddr, pin::Pin, sync::Arc, time::Duration,
+};
+use tokio::sync::mpsc::Sender;
+
+const RESOLVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The future returned by [`Resolve::resolve`].
+pub type ResolveFuture =
+    Pin<Box<dyn Future<Output = Result<Vec<SocketAddr>, crate::BoxError>> + Send>>;
+
+/// Resolves the authority of an [`Endpoint`] to the addresses of its backends.
+///
+/// Set through [`Endpoint::resolver`] to plug in a custom service discovery mechanism, such as
+/// SRV records or a control-plane API. The channel balances requests across all returned
+/// addresses and re-resolves periodically, connecting to new addresses and dropping the ones
+/// that are no longer returned.
+pub trait Resolve: Send + Sync + 'static {
+    /// Resolve `authority` to a set of addresses.
+    fn resolve(&self, authority: &Authority) -> ResolveFuture;
+}
+
+impl<F> Resolve for F
+where
+    F: Fn(&Authority) -> ResolveFuture + Send + Sync + 'static,
+{
+    fn resolve(&self, authority: &Authority) -> ResolveFuture {
+        self(authority)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct SharedResolver(Arc<dyn Resolve>);
+
+impl SharedResolver {
+    pub(crate) fn new(resolver: impl Resolve) -> Self {
+        Self(Arc::new(resolver))
+    }
+}
+
+impl fmt::Debug for SharedResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedResolver").finish()
+    }
+}
+
+/// Feeds the addresses returned by a [`Resolve`] into a balanced channel.
+pub(crate) struct ResolveTask {
+    resolver: SharedResolver,
+    endpoint: Endpoint,
+    authority: Authority,
+    addrs: HashSet<SocketAddr>,
+    tx: Sender<Change<SocketAddr, Endpoint>>,
+}
+
+impl ResolveTask {
+    pub(crate) fn new(
+        resolver: SharedResolver,
+        mut endpoint: Endpoint,
+        tx: Sender<Change<SocketAddr, Endpoint>>,
+    ) -> Result<Self, crate::BoxError> {
+        let authority = endpoint
+            .uri
+            .authority()
+            .cloned()
+            .ok_or_else(super::super::Error::new_invalid_uri)?;
+
+        // Requests keep addressing the original authority, whatever backend they are sent to.
+        endpoint.origin.get_or_insert_with(|| endpoint.uri.clone());
+        endpoint.resolver = None;
+
+        Ok(Self {
+            resolver,
+            endpoint,
+            authority,
+            addrs: HashSet::new(),
+            tx,
+        })
+    }
+
+    /// Resolve the authority once and apply the difference to the channel.
+    pub(crate) async fn refresh(&mut self) -> Result<(), crate::BoxError> {
+        let addrs = self
+            .resolver
+            .0
+            .resolve(&self.authority)
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        for addr in self.addrs.difference(&addrs) {
+            self.tx.send(Change::Remove(*addr)).await?;
+        }
+
+        for addr in addrs.difference(&self.addrs) {
+            let endpoint = self.endpoint_for(*addr)?;
+            self.tx.send(Change::Insert(*addr, endpoint)).await?;
+        }
+
+        self.addrs = addrs;
+        Ok(())
+    }
+
+    /// Keep the channel up to date until it is dropped.
+    pub(crate) async fn run(mut self, resolved: bool) {
+        if !resolved {
+            if let Err(err) = self.refresh().await {
+                tracing::debug!("failed to resolve {}: {}", self.authority, err);
+            }
+        }
+
+        loop {
+            if tokio::time::timeout(RESOLVE_INTERVAL, self.tx.closed())
+                .await
+                .is_ok()
+            {
+                return;
+            }
+
+            if let Err(err) = self.refresh().await {
+                if self.tx.is_closed() {
+                    return;
+                }
+                tracing::debug!("failed to resolve {}: {}", self.authority, err);
+            }
+        }
+    }
+
+    fn endpoint_for(&self, addr: SocketAddr) -> Result<Endpoint, crate::BoxError> {
+        let mut parts = self.endpoint.uri.clone().into_parts();
+        parts.authority = Some(Authority::try_from(addr.to_string())?);
+        let uri = Uri::from_parts(parts)?;
+
+        Ok(Endpoint {
+            uri,
+            ..self.endpoint.clone()
+        })
+    }
+}
@@ -1,15 +1,26 @@
-use super::{AddOrigin, Reconnect, SharedExec, UserAgent};
+use super::{
+    ActiveBody, AddOrigin, ConnectionConnectivity, ConnectivityState, ConnectivityTracker,
+    IdleTracker, LoadTracker, Reconnect, SharedExec, UserAgent,
+};
 use crate::{
     body::Body,
-    transport::{channel::BoxFuture, service::GrpcTimeout, Endpoint},
+    transport::{
+        channel::BoxFuture,
+        go_away::{GoAwayHook, GoAwayIo},
+        service::GrpcTimeout,
+        Endpoint,
+    },
 };
 use http::{Request, Response, Uri};
 use hyper::rt;
 use hyper::{client::conn::http2::Builder, rt::Executor};
-use hyper_util::rt::TokioTimer;
+use hyper_util::rt::{TokioIo, TokioTimer};
 use std::{
+    error::Error as StdError,
     fmt,
+    sync::Mutex,
     task::{Context, Poll},
+    time::Duration,
 };
 use tower::load::Load;
 use tower::{
@@ -22,10 +33,16 @@ use tower_service::Service;
 
 pub(crate) struct Connection {
     inner: BoxService<Request<Body>, Response<Body>, crate::BoxError>,
+    load: LoadTracker,
 }
 
 impl Connection {
-    fn new<C>(connector: C, endpoint: Endpoint, is_lazy: bool) -> Self
+    fn new<C>(
+        connector: C,
+        endpoint: Endpoint,
+        is_lazy: bool,
+        connectivity: &ConnectivityTracker,
+    ) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::BoxError> + Send,
@@ -62,24 +79,35 @@ impl Connection {
                 AddOrigin::new(s, origin)
             })
             .layer_fn(|s| UserAgent::new(s, endpoint.user_agent.clone()))
-            .layer_fn(|s| GrpcTimeout::new(s, endpoint.timeout))
+            .layer_fn(|s| GrpcTimeout::new(s, endpoint.timeout, None))
             .option_layer(endpoint.concurrency_limit.map(ConcurrencyLimitLayer::new))
             .option_layer(endpoint.rate_limit.map(|(l, d)| RateLimitLayer::new(l, d)))
             .into_inner();
 
-        let make_service =
-            MakeSendRequestService::new(connector, endpoint.executor.clone(), settings);
+        let load = LoadTracker::default();
+        let connectivity = connectivity.connection();
+        let make_service = MakeSendRequestService::new(
+            connector,
+            endpoint.executor.clone(),
+            settings,
+            endpoint.http2_max_idle,
+            endpoint.on_goaway_received.clone(),
+            load.clone(),
+            connectivity.clone(),
+        );
 
-        let conn = Reconnect::new(make_service, endpoint.uri.clone(), is_lazy);
+        let conn = Reconnect::new(make_service, endpoint.uri.clone(), is_lazy, connectivity);
 
         Self {
             inner: BoxService::new(stack.layer(conn)),
+            load,
         }
     }
 
     pub(crate) async fn connect<C>(
         connector: C,
         endpoint: Endpoint,
+        connectivity: &ConnectivityTracker,
     ) -> Result<Self, crate::BoxError>
     where
         C: Service<Uri> + Send + 'static,
@@ -87,17 +115,23 @@ impl Connection {
         C::Future: Unpin + Send,
         C::Response: rt::Read + rt::Write + Unpin + Send + 'static,
     {
-        Self::new(connector, endpoint, false).ready_oneshot().await
+        Self::new(connector, endpoint, false, connectivity)
+            .ready_oneshot()
+            .await
     }
 
-    pub(crate) fn lazy<C>(connector: C, endpoint: Endpoint) -> Self
+    pub(crate) fn lazy<C>(
+        connector: C,
+        endpoint: Endpoint,
+        connectivity: &ConnectivityTracker,
+    ) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::BoxError> + Send,
         C::Future: Send,
         C::Response: rt::Read + rt::Write + Unpin + Send + 'static,
     {
-        Self::new(connector, endpoint, true)
+        Self::new(connector, endpoint, true, connectivity)
     }
 }
 
@@ -115,11 +149,13 @@ impl Service<Request<Body>> for Connection {
     }
 }
 
+/// The load of the endpoint, from the load reports in the trailers of its responses, see
+/// [`LoadReport`](crate::LoadReport).
 impl Load for Connection {
-    type Metric = usize;
+    type Metric = f64;
 
     fn load(&self) -> Self::Metric {
-        0
+        self.load.load()
     }
 }
 
@@ -131,11 +167,17 @@ impl fmt::Debug for Connection {
 
 struct SendRequest {
     inner: hyper::client::conn::http2::SendRequest<Body>,
+    idle: Option<IdleTracker>,
+    load: LoadTracker,
 }
 
-impl From<hyper::client::conn::http2::SendRequest<Body>> for SendRequest {
-    fn from(inner: hyper::client::conn::http2::SendRequest<Body>) -> Self {
-        Self { inner }
+impl SendRequest {
+    fn new(
+        inner: hyper::client::conn::http2::SendRequest<Body>,
+        idle: Option<IdleTracker>,
+        load: LoadTracker,
+    ) -> Self {
+        Self { inner, idle, load }
     }
 }
 
@@ -149,9 +191,63 @@ impl tower::Service<Request<Body>> for SendRequest {
     }
 
     fn call(&mut self, req: Request<Body>) -> Self::Future {
-        let fut = self.inner.send_request(req);
+        let fut = self.inner.try_send_request(req);
+        let guard = self.idle.as_ref().map(IdleTracker::start);
+        let load = self.load.clone();
+
+        Box::pin(async move {
+            match fut.await {
+                Ok(res) => Ok(load.track(match guard {
+                    Some(guard) => res.map(|body| Body::new(ActiveBody::new(body, guard))),
+                    None => res.map(Body::new),
+                })),
+                Err(mut err) => match err.take_message() {
+                    Some(request) => Err(RequestNotSent::new(request, err.into_error()).into()),
+                    None => Err(err.into_error().into()),
+                },
+            }
+        })
+    }
+}
+
+/// Error returned when the connection closed before the request was written to it, e.g.
+/// because the server sent a GOAWAY frame. The request can safely be sent again on a new
+/// connection.
+pub(crate) struct RequestNotSent {
+    request: Mutex<Request<Body>>,
+    source: crate::BoxError,
+}
+
+impl RequestNotSent {
+    pub(crate) fn new(request: Request<Body>, source: impl Into<crate::BoxError>) -> Self {
+        Self {
+            request: Mutex::new(request),
+            source: source.into(),
+        }
+    }
+
+    pub(crate) fn into_request(self) -> Request<Body> {
+        self.request.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
 
-        Box::pin(async move { fut.await.map_err(Into::into).map(|res| res.map(Body::new)) })
+impl fmt::Debug for RequestNotSent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestNotSent")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl fmt::Display for RequestNotSent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("connection closed before the request was sent")
+    }
+}
+
+impl StdError for RequestNotSent {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.source)
     }
 }
 
@@ -159,14 +255,30 @@ struct MakeSendRequestService<C> {
     connector: C,
     executor: SharedExec,
     settings: Builder<SharedExec>,
+    max_idle: Option<Duration>,
+    on_goaway: Option<GoAwayHook>,
+    load: LoadTracker,
+    connectivity: ConnectionConnectivity,
 }
 
 impl<C> MakeSendRequestService<C> {
-    fn new(connector: C, executor: SharedExec, settings: Builder<SharedExec>) -> Self {
+    fn new(
+        connector: C,
+        executor: SharedExec,
+        settings: Builder<SharedExec>,
+        max_idle: Option<Duration>,
+        on_goaway: Option<GoAwayHook>,
+        load: LoadTracker,
+        connectivity: ConnectionConnectivity,
+    ) -> Self {
         Self {
             connector,
             executor,
             settings,
+            max_idle,
+            on_goaway,
+            load,
+            connectivity,
         }
     }
 }
@@ -190,21 +302,42 @@ where
         let fut = self.connector.call(req);
         let builder = self.settings.clone();
         let executor = self.executor.clone();
+        let idle = self.max_idle.map(IdleTracker::new);
+        let on_goaway = self.on_goaway.clone();
+        let load = self.load.clone();
+        let connectivity = self.connectivity.clone();
 
         Box::pin(async move {
             let io = fut.await.map_err(Into::into)?;
+            let io = TokioIo::new(GoAwayIo::received(TokioIo::new(io), on_goaway));
             let (send_request, conn) = builder.handshake(io).await?;
 
+            let tracker = idle.clone();
             Executor::<BoxFuture<'static, ()>>::execute(
                 &executor,
                 Box::pin(async move {
-                    if let Err(e) = conn.await {
-                        tracing::debug!("connection task error: {:?}", e);
+                    let result = match tracker {
+                        Some(tracker) => match tracker.run(conn).await {
+                            Some(result) => result,
+                            None => {
+                                tracing::debug!("closing idle connection");
+                                connectivity.set(ConnectivityState::Idle);
+                                return;
+                            }
+                        },
+                        None => conn.await,
+                    };
+                    match result {
+                        Ok(()) => connectivity.set(ConnectivityState::Idle),
+                        Err(e) => {
+                            tracing::debug!("connection task error: {:?}", e);
+                            connectivity.set(ConnectivityState::TransientFailure);
+                        }
                     }
                 }) as _,
             );
 
-            Ok(SendRequest::from(send_request))
+            Ok(SendRequest::new(send_request, idle, load))
         })
     }
 }
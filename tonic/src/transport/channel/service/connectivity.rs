@@ -0,0 +1,153 @@
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// The connectivity state of a [`Channel`], mirroring the [gRPC connectivity states].
+///
+/// A channel balancing requests across several endpoints is `Ready` if any of its connections
+/// is, otherwise `Connecting` if any of them is, otherwise `TransientFailure` if any of them
+/// failed, and `Idle` if none of them is any of these.
+///
+/// See [`Channel::connectivity`].
+///
+/// [`Channel`]: crate::transport::Channel
+/// [`Channel::connectivity`]: crate::transport::Channel::connectivity
+/// [gRPC connectivity states]: https://github.com/grpc/grpc/blob/master/doc/connectivity-semantics-and-api.md
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConnectivityState {
+    /// There is no connection, one will be opened by the next request.
+    Idle,
+    /// A connection is being opened.
+    Connecting,
+    /// The channel is connected and can send requests right away.
+    Ready,
+    /// The last connection attempt failed, or the connection was lost. The next request opens a
+    /// new one.
+    TransientFailure,
+}
+
+impl ConnectivityState {
+    const ALL: [Self; 4] = [
+        Self::Ready,
+        Self::Connecting,
+        Self::TransientFailure,
+        Self::Idle,
+    ];
+
+    fn index(self) -> usize {
+        match self {
+            Self::Ready => 0,
+            Self::Connecting => 1,
+            Self::TransientFailure => 2,
+            Self::Idle => 3,
+        }
+    }
+}
+
+/// The connectivity state of a channel, aggregated over its connections.
+#[derive(Clone, Debug)]
+pub(crate) struct ConnectivityTracker(Arc<Shared>);
+
+#[derive(Debug)]
+struct Shared {
+    /// The number of connections in each state, by [`ConnectivityState::index`].
+    connections: Mutex<[usize; 4]>,
+    state: watch::Sender<ConnectivityState>,
+}
+
+impl ConnectivityTracker {
+    pub(crate) fn new() -> Self {
+        let (state, _) = watch::channel(ConnectivityState::Idle);
+        Self(Arc::new(Shared {
+            connections: Mutex::new([0; 4]),
+            state,
+        }))
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<ConnectivityState> {
+        self.0.state.subscribe()
+    }
+
+    /// Tracks the state of a new connection, `Idle` until it connects.
+    pub(crate) fn connection(&self) -> ConnectionConnectivity {
+        self.update(None, Some(ConnectivityState::Idle));
+        ConnectionConnectivity(Arc::new(Connection {
+            tracker: self.clone(),
+            state: Mutex::new(ConnectivityState::Idle),
+        }))
+    }
+
+    fn update(&self, from: Option<ConnectivityState>, to: Option<ConnectivityState>) {
+        let mut connections = self.0.connections.lock().unwrap();
+        if let Some(from) = from {
+            connections[from.index()] -= 1;
+        }
+        if let Some(to) = to {
+            connections[to.index()] += 1;
+        }
+
+        let state = ConnectivityState::ALL
+            .into_iter()
+            .find(|state| connections[state.index()] > 0)
+            .unwrap_or(ConnectivityState::Idle);
+        self.0.state.send_if_modified(|current| {
+            let modified = *current != state;
+            *current = state;
+            modified
+        });
+    }
+}
+
+/// The connectivity state of a single connection of a channel.
+#[derive(Clone, Debug)]
+pub(crate) struct ConnectionConnectivity(Arc<Connection>);
+
+#[derive(Debug)]
+struct Connection {
+    tracker: ConnectivityTracker,
+    state: Mutex<ConnectivityState>,
+}
+
+impl ConnectionConnectivity {
+    pub(crate) fn set(&self, state: ConnectivityState) {
+        let mut current = self.0.state.lock().unwrap();
+        if *current != state {
+            self.0.tracker.update(Some(*current), Some(state));
+            *current = state;
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let state = *self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        self.tracker.update(Some(state), None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_the_states_of_connections() {
+        let tracker = ConnectivityTracker::new();
+        let state = tracker.subscribe();
+
+        let first = tracker.connection();
+        let second = tracker.connection();
+        assert_eq!(*state.borrow(), ConnectivityState::Idle);
+
+        first.set(ConnectivityState::TransientFailure);
+        assert_eq!(*state.borrow(), ConnectivityState::TransientFailure);
+        second.set(ConnectivityState::Connecting);
+        assert_eq!(*state.borrow(), ConnectivityState::Connecting);
+        second.set(ConnectivityState::Ready);
+        assert_eq!(*state.borrow(), ConnectivityState::Ready);
+
+        drop(second);
+        assert_eq!(*state.borrow(), ConnectivityState::TransientFailure);
+        drop(first);
+        assert_eq!(*state.borrow(), ConnectivityState::Idle);
+    }
+}
@@ -1,4 +1,5 @@
 use super::super::{Connection, Endpoint};
+use super::ConnectivityTracker;
 
 use hyper_util::client::legacy::connect::HttpConnector;
 use std::{
@@ -21,11 +22,18 @@ pub enum Change<K, V> {
 
 pub(crate) struct DynamicServiceStream<K: Hash + Eq + Clone> {
     changes: Receiver<Change<K, Endpoint>>,
+    connectivity: ConnectivityTracker,
 }
 
 impl<K: Hash + Eq + Clone> DynamicServiceStream<K> {
-    pub(crate) fn new(changes: Receiver<Change<K, Endpoint>>) -> Self {
-        Self { changes }
+    pub(crate) fn new(
+        changes: Receiver<Change<K, Endpoint>>,
+        connectivity: ConnectivityTracker,
+    ) -> Self {
+        Self {
+            changes,
+            connectivity,
+        }
     }
 }
 
@@ -33,8 +41,8 @@ impl<K: Hash + Eq + Clone> Stream for DynamicServiceStream<K> {
     type Item = Result<TowerChange<K, Connection>, crate::BoxError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let c = &mut self.changes;
-        match Pin::new(&mut *c).poll_recv(cx) {
+        let this = &mut *self;
+        match Pin::new(&mut this.changes).poll_recv(cx) {
             Poll::Pending | Poll::Ready(None) => Poll::Pending,
             Poll::Ready(Some(change)) => match change {
                 Change::Insert(k, endpoint) => {
@@ -44,7 +52,8 @@ impl<K: Hash + Eq + Clone> Stream for DynamicServiceStream<K> {
                     http.set_connect_timeout(endpoint.connect_timeout);
                     http.enforce_http(false);
 
-                    let connection = Connection::lazy(endpoint.connector(http), endpoint);
+                    let connection =
+                        Connection::lazy(endpoint.connector(http), endpoint, &this.connectivity);
                     let change = Ok(TowerChange::Insert(k, connection));
                     Poll::Ready(Some(change))
                 }
@@ -0,0 +1,156 @@
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    future::{poll_fn, Future},
+    pin::{pin, Pin},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep, Instant};
+
+/// Closes a connection once it had no requests in flight for a while, see
+/// [`Endpoint::http2_max_idle`].
+///
+/// [`Endpoint::http2_max_idle`]: crate::transport::Endpoint::http2_max_idle
+#[derive(Clone, Debug)]
+pub(crate) struct IdleTracker {
+    max_idle: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    in_flight: usize,
+    last_active: Instant,
+}
+
+impl IdleTracker {
+    pub(crate) fn new(max_idle: Duration) -> Self {
+        Self {
+            max_idle,
+            state: Arc::new(Mutex::new(State {
+                in_flight: 0,
+                last_active: Instant::now(),
+            })),
+        }
+    }
+
+    /// Marks a request as in flight until the returned guard is dropped.
+    pub(crate) fn start(&self) -> ActiveGuard {
+        self.state.lock().unwrap().in_flight += 1;
+        ActiveGuard(self.state.clone())
+    }
+
+    /// Drives the connection `conn`, returning `None` if it was dropped, closing it, because it
+    /// was idle for too long.
+    pub(crate) async fn run<F: Future>(self, conn: F) -> Option<F::Output> {
+        let mut conn = pin!(conn);
+        let mut expired = pin!(sleep(self.max_idle));
+
+        poll_fn(|cx| {
+            if let Poll::Ready(output) = conn.as_mut().poll(cx) {
+                return Poll::Ready(Some(output));
+            }
+
+            while expired.as_mut().poll(cx).is_ready() {
+                let state = self.state.lock().unwrap();
+                let next = if state.in_flight > 0 {
+                    Instant::now() + self.max_idle
+                } else if state.last_active.elapsed() >= self.max_idle {
+                    return Poll::Ready(None);
+                } else {
+                    state.last_active + self.max_idle
+                };
+                expired.as_mut().reset(next);
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// Keeps a request in flight, see [`IdleTracker::start`].
+#[derive(Debug)]
+pub(crate) struct ActiveGuard(Arc<Mutex<State>>);
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap();
+        state.in_flight -= 1;
+        state.last_active = Instant::now();
+    }
+}
+
+/// Response body keeping its request in flight until it is dropped.
+#[pin_project]
+pub(crate) struct ActiveBody<B> {
+    #[pin]
+    inner: B,
+    _guard: ActiveGuard,
+}
+
+impl<B> ActiveBody<B> {
+    pub(crate) fn new(inner: B, guard: ActiveGuard) -> Self {
+        Self {
+            inner,
+            _guard: guard,
+        }
+    }
+}
+
+impl<B> Body for ActiveBody<B>
+where
+    B: Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn closes_the_connection_once_idle() {
+        let tracker = IdleTracker::new(Duration::from_millis(50));
+
+        let start = Instant::now();
+        let closed = tracker.run(std::future::pending::<()>()).await;
+        assert!(closed.is_none());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn keeps_the_connection_while_requests_are_in_flight() {
+        let tracker = IdleTracker::new(Duration::from_millis(50));
+        let guard = tracker.start();
+
+        let start = Instant::now();
+        let run = tracker.clone().run(std::future::pending::<()>());
+        let release = async {
+            sleep(Duration::from_millis(120)).await;
+            drop(guard);
+        };
+        let (closed, ()) = tokio::join!(run, release);
+        assert!(closed.is_none());
+        // Counted from the end of the request, not the start of the connection.
+        assert!(start.elapsed() >= Duration::from_millis(170));
+    }
+}
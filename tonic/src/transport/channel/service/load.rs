@@ -0,0 +1,117 @@
+use crate::{body::Body, LoadReport};
+use bytes::Bytes;
+use http::{HeaderMap, Response};
+use http_body::{Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+};
+
+/// The load of an endpoint, from the last [`LoadReport`] in the trailers of its responses.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LoadTracker(Arc<AtomicU64>);
+
+impl LoadTracker {
+    /// The load of the endpoint, `0.0` until it reported one.
+    pub(crate) fn load(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn observe(&self, headers: &HeaderMap) {
+        if let Some(report) = LoadReport::from_headers(headers) {
+            self.0.store(report.load().to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Updates the load from the trailers of `response` once they are received.
+    pub(crate) fn track(&self, response: Response<Body>) -> Response<Body> {
+        // Trailers-only responses carry the report in their headers.
+        self.observe(response.headers());
+
+        let tracker = self.clone();
+        response.map(|inner| Body::new(LoadReportBody { inner, tracker }))
+    }
+}
+
+/// Response body updating a [`LoadTracker`] from its trailers.
+#[pin_project]
+struct LoadReportBody {
+    #[pin]
+    inner: Body,
+    tracker: LoadTracker,
+}
+
+impl http_body::Body for LoadReportBody {
+    type Data = Bytes;
+    type Error = crate::Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+        if let Some(trailers) = frame
+            .as_ref()
+            .and_then(|frame| frame.as_ref().ok()?.trailers_ref())
+        {
+            this.tracker.observe(trailers);
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::MetadataMap;
+    use http_body_util::{BodyExt, StreamBody};
+
+    fn report(utilization: f64) -> HeaderMap {
+        let mut report = LoadReport::new();
+        report.set_cpu_utilization(utilization);
+        let mut metadata = MetadataMap::new();
+        report.insert_into(&mut metadata);
+        metadata.into_sanitized_headers()
+    }
+
+    #[tokio::test]
+    async fn reads_the_load_from_trailers() {
+        let tracker = LoadTracker::default();
+        assert_eq!(tracker.load(), 0.0);
+
+        let frames = [
+            Ok::<_, crate::Status>(Frame::data(Bytes::from_static(b"message"))),
+            Ok(Frame::trailers(report(0.8))),
+        ];
+        let body = Body::new(StreamBody::new(tokio_stream::iter(frames)));
+        let response = tracker.track(Response::new(body));
+        assert_eq!(tracker.load(), 0.0);
+
+        response.into_body().collect().await.unwrap();
+        assert_eq!(tracker.load(), 0.8);
+    }
+
+    #[test]
+    fn reads_the_load_from_trailers_only_responses() {
+        let tracker = LoadTracker::default();
+
+        let mut response = Response::new(Body::empty());
+        *response.headers_mut() = report(0.3);
+        let _ = tracker.track(response);
+        assert_eq!(tracker.load(), 0.3);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{metadata::GRPC_TIMEOUT_HEADER, request::duration_to_grpc_timeout};
+use http::{HeaderValue, Request};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+/// The default timeouts of the methods called on a channel, see [`Endpoint::method_timeouts`].
+///
+/// [`Endpoint::method_timeouts`]: crate::transport::Endpoint::method_timeouts
+#[derive(Clone, Debug)]
+pub(crate) struct MethodTimeouts(Arc<HashMap<String, HeaderValue>>);
+
+impl MethodTimeouts {
+    pub(crate) fn new(timeouts: impl IntoIterator<Item = (String, Duration)>) -> Self {
+        let timeouts = timeouts
+            .into_iter()
+            .map(|(path, timeout)| {
+                let value = HeaderValue::try_from(duration_to_grpc_timeout(timeout))
+                    .expect("grpc-timeout should be a valid header value");
+                (path, value)
+            })
+            .collect();
+        Self(Arc::new(timeouts))
+    }
+
+    /// Sets the `grpc-timeout` header of a call to a method with a default timeout, unless the
+    /// call has its own, see [`Request::set_timeout`](crate::Request::set_timeout).
+    pub(crate) fn apply<B>(&self, request: &mut Request<B>) {
+        if request.headers().contains_key(GRPC_TIMEOUT_HEADER) {
+            return;
+        }
+
+        if let Some(value) = self.0.get(request.uri().path()) {
+            request
+                .headers_mut()
+                .insert(GRPC_TIMEOUT_HEADER, value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeouts() -> MethodTimeouts {
+        MethodTimeouts::new([(
+            "/test.Test/UnaryCall".to_owned(),
+            Duration::from_millis(250),
+        )])
+    }
+
+    fn request(path: &str) -> Request<()> {
+        Request::post(format!("http://[::1]:50051{path}"))
+            .body(())
+            .unwrap()
+    }
+
+    #[test]
+    fn sets_the_timeout_of_configured_methods() {
+        let mut req = request("/test.Test/UnaryCall");
+        timeouts().apply(&mut req);
+        assert_eq!(req.headers()[GRPC_TIMEOUT_HEADER], "250000u");
+
+        let mut req = request("/test.Test/StreamCall");
+        timeouts().apply(&mut req);
+        assert!(req.headers().is_empty());
+    }
+
+    #[test]
+    fn keeps_the_timeout_of_the_call() {
+        let mut req = request("/test.Test/UnaryCall");
+        req.headers_mut()
+            .insert(GRPC_TIMEOUT_HEADER, HeaderValue::from_static("5S"));
+        timeouts().apply(&mut req);
+        assert_eq!(req.headers()[GRPC_TIMEOUT_HEADER], "5S");
+    }
+}
@@ -5,23 +5,40 @@ mod user_agent;
 use self::user_agent::UserAgent;
 
 mod reconnect;
+pub(crate) use self::reconnect::ReadinessProbe;
 use self::reconnect::Reconnect;
 
+mod connectivity;
+use self::connectivity::ConnectionConnectivity;
+pub use self::connectivity::ConnectivityState;
+pub(super) use self::connectivity::ConnectivityTracker;
+
 mod connection;
-pub(super) use self::connection::Connection;
+pub(super) use self::connection::{Connection, RequestNotSent};
 
 mod discover;
 pub use self::discover::Change;
 pub(super) use self::discover::DynamicServiceStream;
 
+mod idle;
+use self::idle::{ActiveBody, IdleTracker};
+
+mod load;
+use self::load::LoadTracker;
+
 mod io;
 use self::io::BoxedIo;
 
 mod connector;
 pub(crate) use self::connector::Connector;
 
-mod executor;
-pub(super) use self::executor::{Executor, SharedExec};
+mod retry_budget;
+pub(super) use self::retry_budget::RetryBudget;
+
+mod method_timeouts;
+pub(crate) use self::method_timeouts::MethodTimeouts;
+
+pub(super) use crate::transport::service::{Executor, SharedExec};
 
 #[cfg(feature = "_tls-any")]
 mod tls;
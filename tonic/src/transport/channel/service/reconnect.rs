@@ -1,3 +1,4 @@
+use super::{ConnectionConnectivity, ConnectivityState};
 use pin_project::pin_project;
 use std::fmt;
 use std::{
@@ -9,6 +10,14 @@ use tower::make::MakeService;
 use tower_service::Service;
 use tracing::trace;
 
+/// Marks a request that only checks whether the connection is established.
+///
+/// Probes are answered by [`Reconnect`] itself and never sent to the server. They are
+/// used by the channel to implement wait-for-ready: the probe drives the connection
+/// attempt and fails with the connection error if it could not be established.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReadinessProbe;
+
 pub(crate) struct Reconnect<M, Target>
 where
     M: Service<Target>,
@@ -20,6 +29,7 @@ where
     error: Option<crate::BoxError>,
     has_been_connected: bool,
     is_lazy: bool,
+    connectivity: ConnectionConnectivity,
 }
 
 #[derive(Debug)]
@@ -34,7 +44,12 @@ where
     M: Service<Target>,
     M::Error: Into<crate::BoxError>,
 {
-    pub(crate) fn new(mk_service: M, target: Target, is_lazy: bool) -> Self {
+    pub(crate) fn new(
+        mk_service: M,
+        target: Target,
+        is_lazy: bool,
+        connectivity: ConnectionConnectivity,
+    ) -> Self {
         Reconnect {
             mk_service,
             state: State::Idle,
@@ -42,14 +57,16 @@ where
             error: None,
             has_been_connected: false,
             is_lazy,
+            connectivity,
         }
     }
 }
 
-impl<M, Target, S, Request> Service<Request> for Reconnect<M, Target>
+impl<M, Target, S, B> Service<http::Request<B>> for Reconnect<M, Target>
 where
     M: Service<Target, Response = S>,
-    S: Service<Request>,
+    S: Service<http::Request<B>>,
+    S::Response: Default,
     M::Future: Unpin,
     crate::BoxError: From<M::Error> + From<S::Error>,
     Target: Clone,
@@ -79,6 +96,7 @@ where
                     }
 
                     let fut = self.mk_service.make_service(self.target.clone());
+                    self.connectivity.set(ConnectivityState::Connecting);
                     self.state = State::Connecting(fut);
                     continue;
                 }
@@ -86,6 +104,7 @@ where
                     trace!("poll_ready; connecting");
                     match Pin::new(f).poll(cx) {
                         Poll::Ready(Ok(service)) => {
+                            self.connectivity.set(ConnectivityState::Ready);
                             state = State::Connected(service);
                         }
                         Poll::Pending => {
@@ -95,6 +114,7 @@ where
                         Poll::Ready(Err(e)) => {
                             trace!("poll_ready; error");
 
+                            self.connectivity.set(ConnectivityState::TransientFailure);
                             state = State::Idle;
 
                             if !(self.has_been_connected || self.is_lazy) {
@@ -137,7 +157,7 @@ where
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, request: Request) -> Self::Future {
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
         tracing::trace!("Reconnect::call");
         if let Some(error) = self.error.take() {
             tracing::debug!("error: {}", error);
@@ -148,6 +168,10 @@ where
             panic!("service not ready; poll_ready must be called first");
         };
 
+        if request.extensions().get::<ReadinessProbe>().is_some() {
+            return ResponseFuture::probe();
+        }
+
         let fut = service.call(request);
         ResponseFuture::new(fut)
     }
@@ -183,6 +207,7 @@ pub(crate) struct ResponseFuture<F> {
 enum Inner<F> {
     Future(#[pin] F),
     Error(Option<crate::BoxError>),
+    Probe,
 }
 
 impl<F> ResponseFuture<F> {
@@ -197,11 +222,18 @@ impl<F> ResponseFuture<F> {
             inner: Inner::Error(Some(error)),
         }
     }
+
+    pub(crate) fn probe() -> Self {
+        ResponseFuture {
+            inner: Inner::Probe,
+        }
+    }
 }
 
 impl<F, T, E> Future for ResponseFuture<F>
 where
     F: Future<Output = Result<T, E>>,
+    T: Default,
     E: Into<crate::BoxError>,
 {
     type Output = Result<T, crate::BoxError>;
@@ -215,6 +247,7 @@ where
                 let e = e.take().expect("Polled after ready.");
                 Poll::Ready(Err(e))
             }
+            InnerProj::Probe => Poll::Ready(Ok(T::default())),
         }
     }
 }
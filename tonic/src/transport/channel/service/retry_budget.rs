@@ -0,0 +1,80 @@
+use crate::{metadata::GRPC_PREVIOUS_RPC_ATTEMPTS_HEADER, Status};
+use http::HeaderMap;
+use std::{sync::Arc, time::Duration};
+use tower::retry::budget::{Budget, TpsBudget};
+
+/// How long a request that is not a retry counts towards the budget.
+const TTL: Duration = Duration::from_secs(10);
+
+/// Throttles the retries sent on a channel, see [`Endpoint::retry_budget`].
+///
+/// [`Endpoint::retry_budget`]: crate::transport::Endpoint::retry_budget
+#[derive(Clone, Debug)]
+pub(crate) struct RetryBudget(Arc<TpsBudget>);
+
+impl RetryBudget {
+    pub(crate) fn new((ratio, min_per_sec): (f32, u32)) -> Self {
+        // `TpsBudget` panics outside of these bounds, `!(ratio > 0.0)` catches NaN as well.
+        let ratio = if ratio > 0.0 { ratio.min(1000.0) } else { 0.0 };
+        let min_per_sec = min_per_sec.min(i32::MAX as u32 - 1);
+        Self(Arc::new(TpsBudget::new(TTL, min_per_sec, ratio)))
+    }
+
+    /// Records a request about to be sent, returning whether it may be sent, which is `false`
+    /// for retries once the budget is spent.
+    ///
+    /// Retries are told apart by their `grpc-previous-rpc-attempts` header, see
+    /// [`Request::set_previous_rpc_attempts`](crate::Request::set_previous_rpc_attempts).
+    pub(crate) fn withdraw(&self, headers: &HeaderMap) -> bool {
+        if headers.contains_key(GRPC_PREVIOUS_RPC_ATTEMPTS_HEADER) {
+            self.0.withdraw()
+        } else {
+            self.0.deposit();
+            true
+        }
+    }
+
+    /// The error of retries exceeding the budget.
+    pub(crate) fn exhausted() -> Status {
+        Status::resource_exhausted("retry budget of the channel exhausted, not sending the retry")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn retry() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            GRPC_PREVIOUS_RPC_ATTEMPTS_HEADER,
+            HeaderValue::from_static("1"),
+        );
+        headers
+    }
+
+    #[test]
+    fn caps_retries_under_sustained_failures() {
+        let budget = RetryBudget::new((0.1, 1));
+
+        // Every call fails and is retried once.
+        let mut retries = 0;
+        for _ in 0..1000 {
+            assert!(budget.withdraw(&HeaderMap::new()));
+            if budget.withdraw(&retry()) {
+                retries += 1;
+            }
+        }
+
+        // A tenth of the calls, plus the reserve of `min_per_sec` over the TTL, instead of all.
+        assert!((100..=110).contains(&retries), "{retries} retries");
+    }
+
+    #[test]
+    fn allows_min_per_sec_without_requests() {
+        let budget = RetryBudget::new((0.0, 2));
+        let retries = (0..100).filter(|_| budget.withdraw(&retry())).count();
+        assert_eq!(retries, 2 * TTL.as_secs() as usize);
+    }
+}
@@ -2,7 +2,9 @@ use http::{header::USER_AGENT, HeaderValue, Request};
 use std::task::{Context, Poll};
 use tower_service::Service;
 
-const TONIC_USER_AGENT: &str = concat!("tonic/", env!("CARGO_PKG_VERSION"));
+/// The default user-agent, following the `grpc-<language>-<implementation>/<version>` convention
+/// of the other gRPC implementations.
+const TONIC_USER_AGENT: &str = concat!("grpc-rust-tonic/", env!("CARGO_PKG_VERSION"));
 
 #[derive(Debug)]
 pub(crate) struct UserAgent<T> {
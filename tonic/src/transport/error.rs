@@ -19,6 +19,12 @@ pub(crate) enum Kind {
     InvalidUri,
     #[cfg(feature = "channel")]
     InvalidUserAgent,
+    #[cfg(feature = "server")]
+    Bind,
+    #[cfg(feature = "_tls-any")]
+    Tls,
+    #[cfg(feature = "server")]
+    Serve,
 }
 
 impl Error {
@@ -47,13 +53,124 @@ impl Error {
         Error::new(Kind::InvalidUserAgent)
     }
 
+    #[cfg(feature = "server")]
+    pub(crate) fn new_bind(source: std::io::Error) -> Self {
+        Error::new(Kind::Bind).with(source)
+    }
+
+    #[cfg(feature = "_tls-any")]
+    pub(crate) fn new_tls(source: impl Into<crate::BoxError>) -> Self {
+        Error::new(Kind::Tls).with(source)
+    }
+
+    #[cfg(feature = "server")]
+    pub(crate) fn new_serve(source: impl Into<crate::BoxError>) -> Self {
+        Error::new(Kind::Serve).with(source)
+    }
+
     fn description(&self) -> &str {
-        match &self.inner.kind {
+        self.inner.kind.description()
+    }
+}
+
+impl Kind {
+    fn description(&self) -> &'static str {
+        match self {
             Kind::Transport => "transport error",
             #[cfg(feature = "channel")]
             Kind::InvalidUri => "invalid URI",
             #[cfg(feature = "channel")]
             Kind::InvalidUserAgent => "user agent is not a valid header value",
+            #[cfg(feature = "server")]
+            Kind::Bind => "failed to bind the address",
+            #[cfg(feature = "_tls-any")]
+            Kind::Tls => "invalid TLS configuration",
+            #[cfg(feature = "server")]
+            Kind::Serve => "failed serving",
+        }
+    }
+}
+
+/// The stage a transport [`Error`] failed in, so callers can react to each, e.g. retry binding
+/// an address that is in use, but not loading an invalid certificate.
+///
+/// ```no_run
+/// # use tonic::transport::{Server, TransportError};
+/// # async fn run(routes: tonic::service::Routes) -> Result<(), tonic::transport::Error> {
+/// let addr = "[::1]:50051".parse().unwrap();
+/// loop {
+///     match Server::builder().add_routes(routes.clone()).serve(addr).await {
+///         Err(err) => match TransportError::from(err) {
+///             TransportError::Bind(err) => eprintln!("retrying to bind {addr}: {err}"),
+///             TransportError::Other(err) => return Err(err),
+///             err => panic!("{err}"),
+///         },
+///         Ok(()) => return Ok(()),
+///     }
+///     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TransportError {
+    /// Binding the address to listen on failed, e.g. as it is in use already.
+    #[cfg(feature = "server")]
+    Bind(std::io::Error),
+    /// The TLS configuration is invalid, e.g. as a certificate or key can't be parsed.
+    #[cfg(feature = "_tls-any")]
+    Tls(Error),
+    /// Serving failed after the server started, e.g. as its service failed.
+    #[cfg(feature = "server")]
+    Serve(Error),
+    /// Any other error, e.g. an invalid setting.
+    Other(Error),
+}
+
+impl From<Error> for TransportError {
+    fn from(err: Error) -> Self {
+        match err.inner.kind {
+            #[cfg(feature = "server")]
+            Kind::Bind => match err.inner.source {
+                Some(source) => match source.downcast::<std::io::Error>() {
+                    Ok(source) => TransportError::Bind(*source),
+                    Err(source) => TransportError::Other(Error::new(Kind::Bind).with(source)),
+                },
+                None => TransportError::Other(Error::new(Kind::Bind)),
+            },
+            #[cfg(feature = "_tls-any")]
+            Kind::Tls => TransportError::Tls(err),
+            #[cfg(feature = "server")]
+            Kind::Serve => TransportError::Serve(err),
+            _ => TransportError::Other(err),
+        }
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "server")]
+            TransportError::Bind(_) => f.write_str(Kind::Bind.description()),
+            #[cfg(feature = "_tls-any")]
+            TransportError::Tls(err) => fmt::Display::fmt(err, f),
+            #[cfg(feature = "server")]
+            TransportError::Serve(err) => fmt::Display::fmt(err, f),
+            TransportError::Other(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl StdError for TransportError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            #[cfg(feature = "server")]
+            TransportError::Bind(err) => Some(err),
+            #[cfg(feature = "_tls-any")]
+            TransportError::Tls(err) => err.source(),
+            #[cfg(feature = "server")]
+            TransportError::Serve(err) => err.source(),
+            TransportError::Other(err) => err.source(),
         }
     }
 }
@@ -78,6 +195,113 @@ impl fmt::Display for Error {
     }
 }
 
+/// An HTTP/2 error that failed a request, keeping its error code.
+///
+/// Errors of requests sent through a [`Channel`](super::Channel) that were caused by an HTTP/2
+/// stream reset or connection error carry this error in their source chain, e.g. the one of the
+/// resulting [`Status`](crate::Status), see [`Http2Error::find`]. Use it to decide whether a
+/// request can be retried with [`Http2Error::is_retryable`].
+#[cfg(any(feature = "server", feature = "channel"))]
+pub struct Http2Error {
+    reason: h2::Reason,
+    go_away: bool,
+    remote: bool,
+    source: Source,
+}
+
+#[cfg(any(feature = "server", feature = "channel"))]
+impl Http2Error {
+    /// Wraps `err` if its source chain holds an HTTP/2 error with an error code.
+    #[cfg_attr(not(feature = "channel"), allow(dead_code))]
+    pub(crate) fn wrap(err: Source) -> Source {
+        let mut source = Some(&*err as &(dyn StdError + 'static));
+        while let Some(inner) = source {
+            if let Some(h2) = inner.downcast_ref::<h2::Error>() {
+                if let Some(reason) = h2.reason() {
+                    let (go_away, remote) = (h2.is_go_away(), h2.is_remote());
+                    return Box::new(Http2Error {
+                        reason,
+                        go_away,
+                        remote,
+                        source: err,
+                    });
+                }
+            }
+            source = inner.source();
+        }
+        err
+    }
+
+    /// Find the HTTP/2 error in the source chain of `err`, starting with `err` itself.
+    pub fn find<'a>(err: &'a (dyn StdError + 'static)) -> Option<&'a Http2Error> {
+        let mut source = Some(err);
+        while let Some(err) = source {
+            if let Some(h2) = err.downcast_ref::<Http2Error>() {
+                return Some(h2);
+            }
+            source = err.source();
+        }
+        None
+    }
+
+    /// The HTTP/2 error code, e.g. `0x7` for `REFUSED_STREAM`, see [RFC 9113, section 7].
+    ///
+    /// [RFC 9113, section 7]: https://www.rfc-editor.org/rfc/rfc9113#section-7
+    pub fn code(&self) -> u32 {
+        self.reason.into()
+    }
+
+    /// Whether the whole connection failed with a `GOAWAY` frame, rather than a single stream
+    /// being reset.
+    pub fn is_go_away(&self) -> bool {
+        self.go_away
+    }
+
+    /// Whether the error was sent by the peer, rather than detected locally.
+    pub fn is_remote(&self) -> bool {
+        self.remote
+    }
+
+    /// Whether the request can be retried safely, as the server didn't process it.
+    ///
+    /// This is the case for `REFUSED_STREAM`, which a server sends for streams it didn't start
+    /// any work for.
+    pub fn is_retryable(&self) -> bool {
+        self.reason == h2::Reason::REFUSED_STREAM
+    }
+
+    pub(crate) fn reason(&self) -> h2::Reason {
+        self.reason
+    }
+}
+
+#[cfg(any(feature = "server", feature = "channel"))]
+impl fmt::Debug for Http2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Http2Error")
+            .field("reason", &self.reason)
+            .field("go_away", &self.go_away)
+            .field("remote", &self.remote)
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "server", feature = "channel"))]
+impl fmt::Display for Http2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scope = if self.go_away { "connection" } else { "stream" };
+        write!(f, "HTTP/2 {} error: {}", scope, self.reason)
+    }
+}
+
+#[cfg(any(feature = "server", feature = "channel"))]
+impl StdError for Http2Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.source)
+    }
+}
+
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         self.inner
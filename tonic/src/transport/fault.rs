@@ -0,0 +1,458 @@
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    time::{sleep, Sleep},
+};
+
+const FRAME_HEADER_LEN: usize = 9;
+const RST_STREAM: u8 = 0x3;
+const RST_STREAM_FRAME_LEN: usize = FRAME_HEADER_LEN + 4;
+/// The connection preface an HTTP/2 client starts with.
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// A fault injected into the connections wrapped by a [`FaultInjector`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// Close a connection once this many more bytes were read from or written to it.
+    ///
+    /// The first connection to transfer the bytes is closed: it reads an end of file, and
+    /// writing to it fails with [`io::ErrorKind::BrokenPipe`]. Connections opened afterwards are
+    /// not affected.
+    DisconnectAfter(usize),
+    /// Delay every read from and write to the connections by this long, until the fault is
+    /// cleared with [`FaultInjector::clear`].
+    Delay(Duration),
+    /// Write an HTTP/2 `RST_STREAM` frame resetting the stream with this ID, with the given
+    /// HTTP/2 error code, e.g. `0x8` for `CANCEL`.
+    ///
+    /// The frame is written by the first connection writing after the fault was injected, in
+    /// between the frames it writes, so it reaches the peer as if this side reset the stream.
+    ResetStream {
+        /// The ID of the stream, e.g. `1` for the first request of a connection.
+        stream_id: u32,
+        /// The HTTP/2 error code.
+        code: u32,
+    },
+}
+
+/// Injects faults into connections, to test how clients and servers handle them.
+///
+/// Connections wrapped with [`FaultInjector::wrap`] behave like the wrapped IO until a
+/// [`Fault`] is injected, which makes them drop, stall or reset streams on demand. This makes
+/// retry, reconnect and cancellation logic testable without relying on timing or a flaky
+/// network. The wrapped connections can be served through
+/// [`Router::serve_with_incoming`](crate::transport::server::Router::serve_with_incoming), and
+/// used by a [`Channel`](crate::transport::Channel) through
+/// [`Endpoint::connect_with_connector`](crate::transport::Endpoint::connect_with_connector).
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tokio::net::TcpListener;
+/// # use tokio_stream::StreamExt;
+/// # use tonic::transport::{server::TcpIncoming, Fault, FaultInjector, Server};
+/// # async fn run(router: tonic::service::Routes) -> Result<(), Box<dyn std::error::Error>> {
+/// let faults = FaultInjector::new();
+/// let listener = TcpListener::bind("127.0.0.1:0").await?;
+/// let incoming = TcpIncoming::from(listener).map({
+///     let faults = faults.clone();
+///     move |io| io.map(|io| faults.wrap(io))
+/// });
+///
+/// tokio::spawn(Server::builder().add_routes(router).serve_with_incoming(incoming));
+///
+/// // Drop the connection in the middle of the next response.
+/// faults.inject(Fault::DisconnectAfter(64));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// This is only meant for tests, and requires the `test-util` feature.
+#[derive(Clone, Default)]
+pub struct FaultInjector {
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    /// The bytes until a connection is closed.
+    disconnect_after: Option<usize>,
+    delay: Option<Duration>,
+    resets: Vec<[u8; RST_STREAM_FRAME_LEN]>,
+}
+
+impl FaultInjector {
+    /// Create an injector without faults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap `io`, injecting the faults of this injector into it.
+    pub fn wrap<IO>(&self, io: IO) -> FaultyIo<IO> {
+        FaultyIo {
+            inner: io,
+            shared: self.shared.clone(),
+            closed: false,
+            read_delay: Delay::default(),
+            write_delay: Delay::default(),
+            writer: Writer::default(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Inject `fault` into the wrapped connections.
+    pub fn inject(&self, fault: Fault) {
+        let mut shared = self.lock();
+        match fault {
+            Fault::DisconnectAfter(bytes) => shared.disconnect_after = Some(bytes),
+            Fault::Delay(delay) => shared.delay = Some(delay),
+            Fault::ResetStream { stream_id, code } => {
+                let mut frame = [0; RST_STREAM_FRAME_LEN];
+                frame[2] = 4;
+                frame[3] = RST_STREAM;
+                frame[5..9].copy_from_slice(&(stream_id & !(1 << 31)).to_be_bytes());
+                frame[9..].copy_from_slice(&code.to_be_bytes());
+                shared.resets.push(frame);
+            }
+        }
+    }
+
+    /// Remove the faults not triggered yet, and stop delaying.
+    pub fn clear(&self) {
+        *self.lock() = Shared::default();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        self.shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for FaultInjector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FaultInjector")
+            .field("faults", &*self.lock())
+            .finish()
+    }
+}
+
+/// A connection wrapped by a [`FaultInjector`].
+#[pin_project]
+pub struct FaultyIo<IO> {
+    #[pin]
+    inner: IO,
+    shared: Arc<Mutex<Shared>>,
+    closed: bool,
+    read_delay: Delay,
+    write_delay: Delay,
+    writer: Writer,
+    /// The part of an `RST_STREAM` frame still to be written.
+    pending: Vec<u8>,
+}
+
+impl<IO> FaultyIo<IO> {
+    /// A reference to the wrapped IO.
+    pub fn get_ref(&self) -> &IO {
+        &self.inner
+    }
+
+    /// Consume the wrapper, returning the wrapped IO.
+    pub fn into_inner(self) -> IO {
+        self.inner
+    }
+}
+
+impl<IO: AsyncWrite> FaultyIo<IO> {
+    /// Writes the requested `RST_STREAM` frames, if the connection is in between frames.
+    fn poll_write_resets(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+        if this.pending.is_empty() && this.writer.at_boundary() {
+            let mut shared = this.shared.lock().unwrap_or_else(|e| e.into_inner());
+            for frame in shared.resets.drain(..) {
+                this.pending.extend_from_slice(&frame);
+            }
+        }
+
+        while !this.pending.is_empty() {
+            let n = ready!(this.inner.as_mut().poll_write(cx, this.pending))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            this.pending.drain(..n);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Takes `len` bytes from the budget of [`Fault::DisconnectAfter`], returning how many may be
+/// transferred, and whether the connection must be closed after them.
+fn take_budget(shared: &Mutex<Shared>, len: usize) -> (usize, bool) {
+    let mut shared = shared.lock().unwrap_or_else(|e| e.into_inner());
+    match shared.disconnect_after {
+        Some(remaining) if remaining <= len => {
+            shared.disconnect_after = None;
+            (remaining, true)
+        }
+        Some(remaining) => {
+            shared.disconnect_after = Some(remaining - len);
+            (len, false)
+        }
+        None => (len, false),
+    }
+}
+
+fn current_delay(shared: &Mutex<Shared>) -> Option<Duration> {
+    shared.lock().unwrap_or_else(|e| e.into_inner()).delay
+}
+
+impl<IO: AsyncRead> AsyncRead for FaultyIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        if *this.closed {
+            return Poll::Ready(Ok(()));
+        }
+        ready!(this.read_delay.poll(cx, current_delay(this.shared)));
+
+        let filled = buf.filled().len();
+        ready!(this.inner.poll_read(cx, buf))?;
+        this.read_delay.reset();
+
+        let (allowed, close) = take_budget(this.shared, buf.filled().len() - filled);
+        buf.set_filled(filled + allowed);
+        *this.closed = close;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for FaultyIo<IO> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.closed {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        let delay = current_delay(&self.shared);
+        ready!(self.as_mut().project().write_delay.poll(cx, delay));
+        ready!(self.as_mut().poll_write_resets(cx))?;
+
+        let this = self.project();
+        let budget = this
+            .shared
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .disconnect_after;
+        let allowed = budget.unwrap_or(usize::MAX).min(buf.len());
+        if allowed == 0 && !buf.is_empty() {
+            take_budget(this.shared, 0);
+            *this.closed = true;
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+
+        let n = ready!(this.inner.poll_write(cx, &buf[..allowed]))?;
+        this.write_delay.reset();
+        this.writer.feed(&buf[..n]);
+        let (_, close) = take_budget(this.shared, n);
+        *this.closed = close;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "server")]
+impl<IO: crate::transport::server::Connected> crate::transport::server::Connected for FaultyIo<IO> {
+    type ConnectInfo = IO::ConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.inner.connect_info()
+    }
+
+    fn peer_info(&self) -> crate::transport::server::PeerInfo {
+        self.inner.peer_info()
+    }
+}
+
+impl<IO> fmt::Debug for FaultyIo<IO> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FaultyIo")
+            .field("closed", &self.closed)
+            .finish()
+    }
+}
+
+/// The delay of the next read or write, see [`Fault::Delay`].
+#[derive(Default)]
+struct Delay {
+    sleep: Option<Pin<Box<Sleep>>>,
+    /// The delay of the current operation passed, it is waiting for the IO itself.
+    elapsed: bool,
+}
+
+impl Delay {
+    fn poll(&mut self, cx: &mut Context<'_>, delay: Option<Duration>) -> Poll<()> {
+        if self.elapsed {
+            return Poll::Ready(());
+        }
+        let Some(delay) = delay else {
+            self.sleep = None;
+            return Poll::Ready(());
+        };
+
+        let sleep = self.sleep.get_or_insert_with(|| Box::pin(sleep(delay)));
+        ready!(sleep.as_mut().poll(cx));
+        self.sleep = None;
+        self.elapsed = true;
+        Poll::Ready(())
+    }
+
+    /// The operation completed, the next one is delayed again.
+    fn reset(&mut self) {
+        self.elapsed = false;
+    }
+}
+
+/// Tracks the frames written to a connection, to tell when a frame can be inserted.
+#[derive(Debug, Default)]
+struct Writer {
+    /// The connection wrote its first frame, after the connection preface of a client.
+    started: bool,
+    preface_len: usize,
+    header: [u8; FRAME_HEADER_LEN],
+    header_len: usize,
+    /// The bytes of the payload of the current frame still to be written.
+    remaining: usize,
+}
+
+impl Writer {
+    fn at_boundary(&self) -> bool {
+        self.started && self.header_len == 0 && self.remaining == 0
+    }
+
+    fn feed(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if !self.started && self.header_len == 0 && self.preface_len < PREFACE.len() {
+                // A frame header never starts with the preface's `P`, the length it would
+                // encode is far beyond the maximum frame size.
+                if self.preface_len > 0 || data[0] == PREFACE[0] {
+                    let n = (PREFACE.len() - self.preface_len).min(data.len());
+                    self.preface_len += n;
+                    data = &data[n..];
+                    continue;
+                }
+            }
+
+            if self.remaining > 0 {
+                let n = self.remaining.min(data.len());
+                self.remaining -= n;
+                data = &data[n..];
+                continue;
+            }
+
+            let n = (FRAME_HEADER_LEN - self.header_len).min(data.len());
+            self.header[self.header_len..self.header_len + n].copy_from_slice(&data[..n]);
+            self.header_len += n;
+            data = &data[n..];
+            if self.header_len == FRAME_HEADER_LEN {
+                self.started = true;
+                self.header_len = 0;
+                self.remaining =
+                    u32::from_be_bytes([0, self.header[0], self.header[1], self.header[2]])
+                        as usize;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const SETTINGS: [u8; FRAME_HEADER_LEN] = [0, 0, 0, 0x4, 0, 0, 0, 0, 0];
+
+    #[tokio::test]
+    async fn disconnects_after_the_given_bytes() {
+        let faults = FaultInjector::new();
+        let (client, mut server) = tokio::io::duplex(1024);
+        let mut client = faults.wrap(client);
+
+        server.write_all(b"hello").await.unwrap();
+        let mut buf = [0; 16];
+        assert_eq!(client.read(&mut buf).await.unwrap(), 5);
+
+        faults.inject(Fault::DisconnectAfter(3));
+        server.write_all(b"world").await.unwrap();
+        assert_eq!(client.read(&mut buf).await.unwrap(), 3);
+        assert_eq!(&buf[..3], b"wor");
+        assert_eq!(client.read(&mut buf).await.unwrap(), 0);
+        let err = client.write_all(b"again").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+        // Only the first connection is closed.
+        let (other, mut server) = tokio::io::duplex(1024);
+        let mut other = faults.wrap(other);
+        server.write_all(b"hello").await.unwrap();
+        assert_eq!(other.read(&mut buf).await.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn resets_streams_in_between_frames() {
+        let faults = FaultInjector::new();
+        let (client, mut server) = tokio::io::duplex(1024);
+        let mut client = faults.wrap(client);
+
+        client.write_all(PREFACE).await.unwrap();
+        client.write_all(&SETTINGS).await.unwrap();
+        faults.inject(Fault::ResetStream {
+            stream_id: 1,
+            code: 0x8,
+        });
+        client.write_all(&SETTINGS).await.unwrap();
+
+        let mut written = vec![0; PREFACE.len() + 2 * SETTINGS.len() + RST_STREAM_FRAME_LEN];
+        server.read_exact(&mut written).await.unwrap();
+        let reset = &written[PREFACE.len() + SETTINGS.len()..][..RST_STREAM_FRAME_LEN];
+        assert_eq!(reset, [0, 0, 4, RST_STREAM, 0, 0, 0, 0, 1, 0, 0, 0, 0x8]);
+        assert_eq!(&written[written.len() - SETTINGS.len()..], SETTINGS);
+    }
+
+    #[tokio::test]
+    async fn delays_reads_and_writes() {
+        let faults = FaultInjector::new();
+        let (client, mut server) = tokio::io::duplex(1024);
+        let mut client = faults.wrap(client);
+        faults.inject(Fault::Delay(Duration::from_millis(50)));
+
+        let start = tokio::time::Instant::now();
+        client.write_all(b"hello").await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        faults.clear();
+        let mut buf = [0; 5];
+        server.read_exact(&mut buf).await.unwrap();
+        let start = tokio::time::Instant::now();
+        server.write_all(b"world").await.unwrap();
+        client.read_exact(&mut buf).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+}
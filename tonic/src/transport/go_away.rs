@@ -0,0 +1,370 @@
+use bytes::Bytes;
+use pin_project::pin_project;
+use std::{
+    fmt, io,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+const FRAME_HEADER_LEN: usize = 9;
+const SETTINGS: u8 = 0x4;
+const GOAWAY: u8 = 0x7;
+/// The length of the last stream ID and error code that start the payload of a `GOAWAY` frame.
+const GOAWAY_FIELDS_LEN: usize = 8;
+/// The debug data of a `GOAWAY` frame is cut off after this many bytes.
+const MAX_DEBUG_DATA_LEN: usize = 1024;
+
+/// A hook called with the `GOAWAY` frames of a connection.
+pub(crate) type GoAwayHook = Arc<dyn Fn(&GoAway) + Send + Sync + 'static>;
+/// A hook returning the `GOAWAY` frame a server sends instead of the one passed to it.
+#[cfg(feature = "server")]
+pub(crate) type GoAwayRewrite = Arc<dyn Fn(GoAway) -> GoAway + Send + Sync + 'static>;
+
+/// An HTTP/2 `GOAWAY` frame, announcing that a connection is being closed.
+///
+/// Passed to the hooks of [`Server::on_goaway_sent`] and [`Endpoint::on_goaway_received`], to
+/// log and alert on connections being closed, e.g. by [`Server::max_connection_age`] during
+/// rollouts, or on shutdown.
+///
+/// A graceful close sends two `GOAWAY` frames: the first one with the maximum stream ID, which
+/// tells the client to stop opening streams, and once the client had time to notice, a second
+/// one with the ID of the last stream that will be processed.
+///
+/// [`Server::on_goaway_sent`]: crate::transport::Server::on_goaway_sent
+/// [`Server::max_connection_age`]: crate::transport::Server::max_connection_age
+/// [`Endpoint::on_goaway_received`]: crate::transport::Endpoint::on_goaway_received
+#[derive(Clone, Debug)]
+pub struct GoAway {
+    code: u32,
+    last_stream_id: u32,
+    debug_data: Bytes,
+}
+
+impl GoAway {
+    /// The error code of connections closed gracefully.
+    pub const NO_ERROR: u32 = 0x0;
+    /// The error code of connections closed because the peer behaves in a way that may be
+    /// abusive, e.g. resets too many streams.
+    pub const ENHANCE_YOUR_CALM: u32 = 0xb;
+
+    /// The length of the payload kept of a `GOAWAY` frame, whose debug data is cut off after
+    /// [`MAX_DEBUG_DATA_LEN`].
+    pub(crate) const MAX_PAYLOAD_LEN: usize = GOAWAY_FIELDS_LEN + MAX_DEBUG_DATA_LEN;
+
+    /// Parses the payload of a `GOAWAY` frame, returning `None` if it is malformed.
+    pub(crate) fn parse(payload: &[u8]) -> Option<Self> {
+        if payload.len() < GOAWAY_FIELDS_LEN {
+            return None;
+        }
+
+        let last_stream_id =
+            u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) & !(1 << 31);
+        let code = u32::from_be_bytes([payload[4], payload[5], payload[6], payload[7]]);
+        Some(GoAway {
+            code,
+            last_stream_id,
+            debug_data: Bytes::copy_from_slice(&payload[GOAWAY_FIELDS_LEN..]),
+        })
+    }
+
+    /// The HTTP/2 error code, e.g. `0x0` for `NO_ERROR` when the connection is closed
+    /// gracefully, see [RFC 9113, section 7].
+    ///
+    /// [RFC 9113, section 7]: https://www.rfc-editor.org/rfc/rfc9113#section-7
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Whether the connection is closed gracefully, with the error code `NO_ERROR`.
+    pub fn is_graceful(&self) -> bool {
+        self.code == Self::NO_ERROR
+    }
+
+    /// The ID of the last stream that was or will be processed.
+    pub fn last_stream_id(&self) -> u32 {
+        self.last_stream_id
+    }
+
+    /// The opaque debug data of the frame, usually a reason for closing the connection, cut off
+    /// after 1 KiB.
+    pub fn debug_data(&self) -> &Bytes {
+        &self.debug_data
+    }
+
+    /// Replace the error code, see [`Server::http2_goaway`].
+    ///
+    /// [`Server::http2_goaway`]: crate::transport::Server::http2_goaway
+    pub fn with_code(self, code: u32) -> Self {
+        GoAway { code, ..self }
+    }
+
+    /// Replace the debug data, e.g. with a reason for clients and proxies to log, see
+    /// [`Server::http2_goaway`]. It is cut off after 1 KiB.
+    ///
+    /// [`Server::http2_goaway`]: crate::transport::Server::http2_goaway
+    pub fn with_debug_data(self, debug_data: impl Into<Bytes>) -> Self {
+        let mut debug_data = debug_data.into();
+        debug_data.truncate(MAX_DEBUG_DATA_LEN);
+        GoAway { debug_data, ..self }
+    }
+
+    /// Encodes the frame, as sent on the connection.
+    #[cfg(feature = "server")]
+    pub(crate) fn encode(&self) -> Bytes {
+        let len = GOAWAY_FIELDS_LEN + self.debug_data.len();
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + len);
+        frame.extend_from_slice(&(len as u32).to_be_bytes()[1..]);
+        frame.extend_from_slice(&[GOAWAY, 0, 0, 0, 0, 0]);
+        frame.extend_from_slice(&self.last_stream_id.to_be_bytes());
+        frame.extend_from_slice(&self.code.to_be_bytes());
+        frame.extend_from_slice(&self.debug_data);
+        frame.into()
+    }
+}
+
+/// Why a server sends a `GOAWAY` frame, passed to [`Server::http2_goaway`].
+///
+/// [`Server::http2_goaway`]: crate::transport::Server::http2_goaway
+#[cfg(feature = "server")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GoAwayReason {
+    /// The server is shutting down.
+    Shutdown,
+    /// The connection reached [`Server::max_connection_age`].
+    ///
+    /// [`Server::max_connection_age`]: crate::transport::Server::max_connection_age
+    MaxConnectionAge,
+    /// The connection reached its request limit, see [`Server::max_requests_per_connection`]
+    /// and [`Server::http2_stream_id_threshold`].
+    ///
+    /// [`Server::max_requests_per_connection`]: crate::transport::Server::max_requests_per_connection
+    /// [`Server::http2_stream_id_threshold`]: crate::transport::Server::http2_stream_id_threshold
+    RequestLimit,
+    /// The connection was closed through [`ConnectionControl::close_connections`].
+    ///
+    /// [`ConnectionControl::close_connections`]: crate::transport::server::ConnectionControl::close_connections
+    Closed,
+    /// The connection closes itself, e.g. because the client violated the protocol, or reset
+    /// too many streams, see [`Server::http2_max_pending_accept_reset_streams`]. These frames
+    /// usually carry an error code other than [`GoAway::NO_ERROR`], like
+    /// [`GoAway::ENHANCE_YOUR_CALM`].
+    ///
+    /// [`Server::http2_max_pending_accept_reset_streams`]: crate::transport::Server::http2_max_pending_accept_reset_streams
+    Error,
+}
+
+/// Watches the frames read from a client connection for `GOAWAY` frames, passing them to a
+/// [`GoAwayHook`].
+///
+/// The frames start with the server's `SETTINGS` frame, connections that start with anything
+/// else, like HTTP/1 ones, are not watched. The `GOAWAY` frames sent by a server are watched by
+/// its `FramesIo`.
+#[pin_project]
+pub(crate) struct GoAwayIo<IO> {
+    #[pin]
+    inner: IO,
+    hook: Option<GoAwayHook>,
+    parser: Parser,
+}
+
+impl<IO> GoAwayIo<IO> {
+    /// Watch the frames read from `inner`.
+    #[cfg_attr(not(feature = "channel"), allow(dead_code))]
+    pub(crate) fn received(inner: IO, hook: Option<GoAwayHook>) -> Self {
+        Self {
+            inner,
+            hook,
+            parser: Parser::default(),
+        }
+    }
+}
+
+impl<IO: AsyncRead> AsyncRead for GoAwayIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let filled = buf.filled().len();
+        ready!(this.inner.poll_read(cx, buf))?;
+
+        if let Some(hook) = this.hook {
+            this.parser.feed(&buf.filled()[filled..], &**hook);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for GoAwayIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+impl<IO> fmt::Debug for GoAwayIo<IO> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GoAwayIo").finish()
+    }
+}
+
+/// Parses a stream of HTTP/2 frames, looking for `GOAWAY` frames.
+#[derive(Debug, Default)]
+struct Parser {
+    /// The first frame was read, the `SETTINGS` frame that must come first.
+    started: bool,
+    header: [u8; FRAME_HEADER_LEN],
+    header_len: usize,
+    /// The bytes of the payload of the current frame still to be parsed.
+    remaining: usize,
+    /// The payload of the current frame, if it is a `GOAWAY` frame.
+    go_away: Option<Vec<u8>>,
+    /// The stream doesn't consist of HTTP/2 frames.
+    done: bool,
+}
+
+impl Parser {
+    fn feed(&mut self, mut data: &[u8], hook: &dyn Fn(&GoAway)) {
+        while !data.is_empty() && !self.done {
+            if self.remaining > 0 {
+                let n = self.remaining.min(data.len());
+                if let Some(payload) = &mut self.go_away {
+                    let keep = GoAway::MAX_PAYLOAD_LEN.saturating_sub(payload.len());
+                    payload.extend_from_slice(&data[..n.min(keep)]);
+                }
+                self.remaining -= n;
+                data = &data[n..];
+                if self.remaining == 0 {
+                    self.finish_frame(hook);
+                }
+                continue;
+            }
+
+            let n = (FRAME_HEADER_LEN - self.header_len).min(data.len());
+            self.header[self.header_len..self.header_len + n].copy_from_slice(&data[..n]);
+            self.header_len += n;
+            data = &data[n..];
+            if self.header_len < FRAME_HEADER_LEN {
+                continue;
+            }
+
+            self.header_len = 0;
+            let kind = self.header[3];
+            if !self.started {
+                self.started = true;
+                if kind != SETTINGS {
+                    self.done = true;
+                    return;
+                }
+            }
+            self.remaining =
+                u32::from_be_bytes([0, self.header[0], self.header[1], self.header[2]]) as usize;
+            self.go_away = (kind == GOAWAY).then(Vec::new);
+            if self.remaining == 0 {
+                self.finish_frame(hook);
+            }
+        }
+    }
+
+    fn finish_frame(&mut self, hook: &dyn Fn(&GoAway)) {
+        // Malformed frames fail the connection anyway.
+        if let Some(go_away) = self
+            .go_away
+            .take()
+            .and_then(|payload| GoAway::parse(&payload))
+        {
+            hook(&go_away);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const SETTINGS_FRAME: [u8; FRAME_HEADER_LEN] = [0, 0, 0, SETTINGS, 0, 0, 0, 0, 0];
+
+    fn go_away_frame(last_stream_id: u32, code: u32, debug_data: &[u8]) -> Vec<u8> {
+        let len = (GOAWAY_FIELDS_LEN + debug_data.len()) as u32;
+        let mut frame = len.to_be_bytes()[1..].to_vec();
+        frame.extend_from_slice(&[GOAWAY, 0, 0, 0, 0, 0]);
+        frame.extend_from_slice(&last_stream_id.to_be_bytes());
+        frame.extend_from_slice(&code.to_be_bytes());
+        frame.extend_from_slice(debug_data);
+        frame
+    }
+
+    fn parse(chunks: &[&[u8]]) -> Vec<GoAway> {
+        let seen = Mutex::new(Vec::new());
+        let hook = |go_away: &GoAway| seen.lock().unwrap().push(go_away.clone());
+        let mut parser = Parser::default();
+        for chunk in chunks {
+            parser.feed(chunk, &hook);
+        }
+        seen.into_inner().unwrap()
+    }
+
+    #[test]
+    fn finds_go_away_frames_split_across_chunks() {
+        let ping = [0, 0, 8, 0x6, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let go_away = go_away_frame(5, 0x2, b"overloaded");
+        let (head, tail) = go_away.split_at(11);
+
+        let seen = parse(&[&SETTINGS_FRAME, &ping, head, tail]);
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].code(), 0x2);
+        assert!(!seen[0].is_graceful());
+        assert_eq!(seen[0].last_stream_id(), 5);
+        assert_eq!(&seen[0].debug_data()[..], b"overloaded");
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn encodes_rewritten_frames() {
+        let go_away = GoAway::parse(&go_away_frame(7, 0, b"")[FRAME_HEADER_LEN..])
+            .unwrap()
+            .with_code(GoAway::ENHANCE_YOUR_CALM)
+            .with_debug_data(vec![b'a'; 2 * MAX_DEBUG_DATA_LEN]);
+        let frame = go_away.encode();
+        assert_eq!(
+            frame,
+            go_away_frame(7, GoAway::ENHANCE_YOUR_CALM, &[b'a'; MAX_DEBUG_DATA_LEN])
+        );
+    }
+
+    #[test]
+    fn ignores_connections_not_starting_with_settings() {
+        let response = b"HTTP/1.1 200 OK\r\n\r\n";
+        let go_away = go_away_frame(0, 0, b"");
+        assert!(parse(&[response, &go_away]).is_empty());
+    }
+}
@@ -94,6 +94,10 @@ pub mod channel;
 pub mod server;
 
 mod error;
+#[cfg(all(feature = "test-util", any(feature = "server", feature = "channel")))]
+mod fault;
+#[cfg(any(feature = "server", feature = "channel"))]
+mod go_away;
 mod service;
 #[cfg(feature = "_tls-any")]
 mod tls;
@@ -101,7 +105,15 @@ mod tls;
 #[doc(inline)]
 #[cfg(feature = "channel")]
 pub use self::channel::{Channel, Endpoint};
-pub use self::error::Error;
+#[cfg(any(feature = "server", feature = "channel"))]
+pub use self::error::Http2Error;
+pub use self::error::{Error, TransportError};
+#[cfg(all(feature = "test-util", any(feature = "server", feature = "channel")))]
+pub use self::fault::{Fault, FaultInjector, FaultyIo};
+#[cfg(any(feature = "server", feature = "channel"))]
+pub use self::go_away::GoAway;
+#[cfg(feature = "server")]
+pub use self::go_away::GoAwayReason;
 #[doc(inline)]
 #[cfg(feature = "server")]
 pub use self::server::Server;
@@ -111,6 +123,8 @@ pub use self::tls::Certificate;
 pub use hyper::{body::Body, Uri};
 #[cfg(feature = "_tls-any")]
 pub use tokio_rustls::rustls::pki_types::CertificateDer;
+#[cfg(all(feature = "server", feature = "_tls-any"))]
+pub use tokio_rustls::rustls::{CipherSuite, ProtocolVersion};
 
 #[cfg(all(feature = "channel", feature = "_tls-any"))]
 pub use self::channel::ClientTlsConfig;
@@ -0,0 +1,124 @@
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep_until, Instant, Sleep};
+use tokio_stream::Stream;
+
+/// The rate at which connections are accepted, see [`Server::connection_rate_limit`].
+///
+/// [`Server::connection_rate_limit`]: super::Server::connection_rate_limit
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AcceptRate {
+    interval: Duration,
+    burst: u32,
+}
+
+impl AcceptRate {
+    pub(crate) fn new(per_sec: u32, burst: u32) -> Self {
+        assert!(per_sec > 0, "connection rate limit must be positive");
+        Self {
+            interval: Duration::from_secs(1) / per_sec,
+            burst: burst.max(1),
+        }
+    }
+}
+
+/// Delays taking the next connection from `inner` until the [`AcceptRate`] allows it.
+///
+/// Until then the connection stays in the listen queue, before any TLS handshake.
+#[pin_project]
+pub(crate) struct RateLimitedIncoming<S> {
+    #[pin]
+    inner: S,
+    rate: Option<AcceptRate>,
+    /// When the bucket is full again, every accepted connection pushes it back by the interval.
+    full_at: Instant,
+    acquired: bool,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl<S> RateLimitedIncoming<S> {
+    pub(crate) fn new(inner: S, rate: Option<AcceptRate>) -> Self {
+        Self {
+            inner,
+            rate,
+            full_at: Instant::now(),
+            acquired: false,
+            delay: None,
+        }
+    }
+}
+
+impl<S: Stream> Stream for RateLimitedIncoming<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if let Some(rate) = this.rate.filter(|_| !*this.acquired) {
+            loop {
+                if let Some(delay) = this.delay {
+                    ready!(delay.as_mut().poll(cx));
+                    *this.delay = None;
+                }
+
+                let now = Instant::now();
+                let full_at = (*this.full_at).max(now) + rate.interval;
+                match full_at.checked_sub(rate.interval * rate.burst) {
+                    Some(allowed_at) if allowed_at > now => {
+                        *this.delay = Some(Box::pin(sleep_until(allowed_at)));
+                    }
+                    _ => {
+                        *this.full_at = full_at;
+                        *this.acquired = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let item = ready!(this.inner.poll_next(cx));
+        *this.acquired = false;
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::pin;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn delays_connections_beyond_the_burst() {
+        let mut incoming = pin!(RateLimitedIncoming::new(
+            tokio_stream::iter(0..4),
+            Some(AcceptRate::new(20, 2))
+        ));
+
+        let start = Instant::now();
+        assert_eq!(incoming.next().await, Some(0));
+        assert_eq!(incoming.next().await, Some(1));
+        assert!(start.elapsed() < Duration::from_millis(40));
+
+        assert_eq!(incoming.next().await, Some(2));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(incoming.next().await, Some(3));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        assert_eq!(incoming.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn unlimited_without_rate() {
+        let incoming = RateLimitedIncoming::new(tokio_stream::iter(0..100), None);
+
+        let start = Instant::now();
+        assert_eq!(incoming.collect::<Vec<_>>().await.len(), 100);
+        assert!(start.elapsed() < Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,391 @@
+use super::PeerInfo;
+use crate::{
+    body::Body,
+    service::status_interceptor::{BoxObserver, StatusResponseBody, StatusResponseFuture},
+    Code,
+};
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body::{Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    fmt::{self, Write as _},
+    future::Future,
+    io::Write,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{ready, Context, Poll},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The format of the records emitted by an [`AccessLogLayer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AccessLogFormat {
+    /// Space separated `key=value` pairs.
+    #[default]
+    Logfmt,
+    /// One JSON object per record.
+    Json,
+}
+
+#[derive(Clone)]
+enum Sink {
+    Tracing,
+    Writer(Arc<Mutex<dyn Write + Send>>),
+}
+
+/// A layer emitting one structured access log record per completed RPC.
+///
+/// Each record holds the time the request was received, the method path, the peer address, the
+/// final `grpc-status` code, the duration of the call and the number of request and response body
+/// bytes. Records are emitted once the response body completes, so streaming RPCs are logged when
+/// the stream terminates. Requests dropped before their response completes, e.g. because the client
+/// went away, are logged with [`Code::Cancelled`].
+///
+/// By default, records are emitted in [logfmt](AccessLogFormat::Logfmt) as `INFO` events with the
+/// `tonic::access_log` target.
+///
+/// ```
+/// # use tonic::transport::{server::{AccessLogFormat, AccessLogLayer}, Server};
+/// Server::builder().layer(
+///     AccessLogLayer::new()
+///         .format(AccessLogFormat::Json)
+///         .writer(std::io::stdout()),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct AccessLogLayer {
+    format: AccessLogFormat,
+    sink: Sink,
+}
+
+impl AccessLogLayer {
+    /// Create a new access log layer.
+    pub fn new() -> Self {
+        Self {
+            format: AccessLogFormat::default(),
+            sink: Sink::Tracing,
+        }
+    }
+
+    /// Set the format of the emitted records.
+    pub fn format(self, format: AccessLogFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Write records to `writer`, one per line, instead of emitting `tracing` events.
+    pub fn writer<W>(self, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Self {
+            sink: Sink::Writer(Arc::new(Mutex::new(writer))),
+            ..self
+        }
+    }
+}
+
+impl Default for AccessLogLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for AccessLogLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessLogLayer")
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for AccessLogLayer {
+    type Service = AccessLog<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessLog {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Middleware emitting access log records, see [`AccessLogLayer`].
+#[derive(Clone)]
+pub struct AccessLog<S> {
+    inner: S,
+    layer: AccessLogLayer,
+}
+
+impl<S> fmt::Debug for AccessLog<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessLog").finish()
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for AccessLog<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+{
+    type Response = Response<AccessLogBody<ResBody>>;
+    type Error = S::Error;
+    type Future = AccessLogFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let request_bytes = Arc::new(AtomicU64::new(0));
+        let response_bytes = Arc::new(AtomicU64::new(0));
+        let record = Record {
+            layer: self.layer.clone(),
+            timestamp: SystemTime::now(),
+            start: Instant::now(),
+            method: req.uri().path().to_owned(),
+            peer: req
+                .extensions()
+                .get::<PeerInfo>()
+                .and_then(|info| info.remote_addr),
+            request_bytes: request_bytes.clone(),
+            response_bytes: response_bytes.clone(),
+        };
+
+        let req = req.map(|body| {
+            Body::new(CountingBody {
+                inner: body,
+                bytes: request_bytes,
+            })
+        });
+
+        let observer: BoxObserver = Box::new(move |status| record.emit(status.code()));
+
+        AccessLogFuture {
+            inner: StatusResponseFuture::new(self.inner.call(req), observer),
+            bytes: Some(response_bytes),
+        }
+    }
+}
+
+/// Response future for [`AccessLog`].
+#[pin_project]
+pub struct AccessLogFuture<F> {
+    #[pin]
+    inner: StatusResponseFuture<F, BoxObserver>,
+    bytes: Option<Arc<AtomicU64>>,
+}
+
+impl<F> fmt::Debug for AccessLogFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessLogFuture").finish()
+    }
+}
+
+impl<F, ResBody, E> Future for AccessLogFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<AccessLogBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = ready!(this.inner.poll(cx))?;
+        let bytes = this.bytes.take().expect("polled after completion");
+
+        Poll::Ready(Ok(response.map(|inner| AccessLogBody {
+            inner: CountingBody { inner, bytes },
+        })))
+    }
+}
+
+/// Response body for [`AccessLog`], emitting the record once it completes.
+#[pin_project]
+pub struct AccessLogBody<B> {
+    #[pin]
+    inner: CountingBody<StatusResponseBody<B, BoxObserver>>,
+}
+
+impl<B> fmt::Debug for AccessLogBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessLogBody").finish()
+    }
+}
+
+impl<B> http_body::Body for AccessLogBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Counts the bytes of the data frames of a body as they stream through.
+#[pin_project]
+struct CountingBody<B> {
+    #[pin]
+    inner: B,
+    bytes: Arc<AtomicU64>,
+}
+
+impl<B> http_body::Body for CountingBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+
+        if let Some(Ok(frame)) = &frame {
+            if let Some(data) = frame.data_ref() {
+                this.bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+            }
+        }
+
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+struct Record {
+    layer: AccessLogLayer,
+    timestamp: SystemTime,
+    start: Instant,
+    method: String,
+    peer: Option<SocketAddr>,
+    request_bytes: Arc<AtomicU64>,
+    response_bytes: Arc<AtomicU64>,
+}
+
+impl Record {
+    fn emit(self, code: Code) {
+        let line = self.format(code, self.start.elapsed());
+
+        match &self.layer.sink {
+            Sink::Tracing => tracing::info!(target: "tonic::access_log", "{}", line),
+            Sink::Writer(writer) => {
+                let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(err) = writeln!(writer, "{line}") {
+                    tracing::debug!("failed to write access log record: {}", err);
+                }
+            }
+        }
+    }
+
+    fn format(&self, code: Code, duration: Duration) -> String {
+        let timestamp = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let duration_ms = duration.as_secs_f64() * 1000.0;
+        let request_bytes = self.request_bytes.load(Ordering::Relaxed);
+        let response_bytes = self.response_bytes.load(Ordering::Relaxed);
+        let code = code.to_i32();
+
+        let mut line = String::new();
+        match self.layer.format {
+            AccessLogFormat::Logfmt => {
+                let _ = write!(line, "timestamp={timestamp:.3} method={} ", self.method);
+                match self.peer {
+                    Some(peer) => {
+                        let _ = write!(line, "peer={peer} ");
+                    }
+                    None => line.push_str("peer=- "),
+                }
+                let _ = write!(
+                    line,
+                    "status={code} duration_ms={duration_ms:.3} request_bytes={request_bytes} response_bytes={response_bytes}"
+                );
+            }
+            AccessLogFormat::Json => {
+                let _ = write!(
+                    line,
+                    r#"{{"timestamp":{timestamp:.3},"method":"{}","#,
+                    self.method.escape_default()
+                );
+                match self.peer {
+                    Some(peer) => {
+                        let _ = write!(line, r#""peer":"{peer}","#);
+                    }
+                    None => line.push_str(r#""peer":null,"#),
+                }
+                let _ = write!(
+                    line,
+                    r#""status":{code},"duration_ms":{duration_ms:.3},"request_bytes":{request_bytes},"response_bytes":{response_bytes}}}"#
+                );
+            }
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(format: AccessLogFormat, peer: Option<SocketAddr>) -> Record {
+        Record {
+            layer: AccessLogLayer::new().format(format),
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+            start: Instant::now(),
+            method: "/test.Test/UnaryCall".to_owned(),
+            peer,
+            request_bytes: Arc::new(AtomicU64::new(5)),
+            response_bytes: Arc::new(AtomicU64::new(7)),
+        }
+    }
+
+    #[test]
+    fn logfmt() {
+        let record = record(AccessLogFormat::Logfmt, "127.0.0.1:1234".parse().ok());
+        assert_eq!(
+            record.format(Code::NotFound, Duration::from_micros(1500)),
+            "timestamp=1700000000.123 method=/test.Test/UnaryCall peer=127.0.0.1:1234 \
+             status=5 duration_ms=1.500 request_bytes=5 response_bytes=7"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let record = record(AccessLogFormat::Json, None);
+        assert_eq!(
+            record.format(Code::Ok, Duration::from_millis(2)),
+            r#"{"timestamp":1700000000.123,"method":"/test.Test/UnaryCall","peer":null,"status":0,"duration_ms":2.000,"request_bytes":5,"response_bytes":7}"#
+        );
+    }
+}
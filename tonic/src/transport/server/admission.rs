@@ -0,0 +1,49 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A shared handle to refuse new streams on the connections of a [`Server`] at runtime.
+///
+/// Obtained through [`Server::stream_admission`]. While streams are refused, every new HTTP/2
+/// stream is reset with `REFUSED_STREAM`, which tells clients the request wasn't processed and
+/// can be retried, e.g. on another server. Connections, the listener and the requests already in
+/// flight are left alone, so this is meant for shedding load during a partial overload rather
+/// than for shutting down.
+///
+/// ```
+/// # use tonic::transport::Server;
+/// let builder = Server::builder();
+/// let admission = builder.stream_admission();
+///
+/// // ... serve from `builder` ...
+///
+/// admission.refuse_streams();
+/// assert!(!admission.is_admitting());
+///
+/// admission.admit_streams();
+/// ```
+///
+/// [`Server`]: super::Server
+/// [`Server::stream_admission`]: super::Server::stream_admission
+#[derive(Clone, Debug, Default)]
+pub struct StreamAdmission {
+    refusing: Arc<AtomicBool>,
+}
+
+impl StreamAdmission {
+    /// Refuse new streams until [`StreamAdmission::admit_streams`] is called.
+    pub fn refuse_streams(&self) {
+        self.refusing.store(true, Ordering::Relaxed);
+    }
+
+    /// Accept new streams again.
+    pub fn admit_streams(&self) {
+        self.refusing.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether new streams are accepted.
+    pub fn is_admitting(&self) -> bool {
+        !self.refusing.load(Ordering::Relaxed)
+    }
+}
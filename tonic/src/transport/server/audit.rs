@@ -0,0 +1,455 @@
+use crate::{
+    body::Body,
+    service::status_interceptor::{BoxObserver, StatusResponseBody, StatusResponseFuture},
+    Code,
+};
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body::{Frame, SizeHint};
+use pin_project::pin_project;
+use ring::digest::{Context as DigestContext, SHA256, SHA256_OUTPUT_LEN};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+type Digest = [u8; SHA256_OUTPUT_LEN];
+
+/// Receives the events of an [`AuditLayer`], e.g. to write them to an audit log.
+///
+/// Events are emitted from the tasks serving the RPCs, so sinks should hand them off, e.g.
+/// through a channel, rather than block. Closures taking an [`AuditEvent`] are sinks.
+pub trait AuditSink: Send + Sync + 'static {
+    /// Receive the event of a completed RPC.
+    fn emit(&self, event: AuditEvent);
+}
+
+impl<F> AuditSink for F
+where
+    F: Fn(AuditEvent) + Send + Sync + 'static,
+{
+    fn emit(&self, event: AuditEvent) {
+        self(event)
+    }
+}
+
+/// The audit record of a single RPC, see [`AuditLayer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    method: String,
+    identity: Option<String>,
+    request_digest: Digest,
+    response_digest: Digest,
+    code: Code,
+}
+
+impl AuditEvent {
+    /// The method path, e.g. `/helloworld.Greeter/SayHello`.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The authenticated identity of the caller, see [`AuditIdentity`].
+    pub fn identity(&self) -> Option<&str> {
+        self.identity.as_deref()
+    }
+
+    /// The SHA-256 digest of the request body.
+    pub fn request_digest(&self) -> &Digest {
+        &self.request_digest
+    }
+
+    /// The SHA-256 digest of the response body.
+    pub fn response_digest(&self) -> &Digest {
+        &self.response_digest
+    }
+
+    /// The final status code of the call.
+    pub fn code(&self) -> Code {
+        self.code
+    }
+}
+
+/// The authenticated identity of the caller, recorded in [`AuditEvent`]s.
+///
+/// An [`AuditLayer`] inserts an `AuditIdentity` into the extensions of every request, which
+/// authentication interceptors set once they verified the caller, e.g. from a bearer token.
+/// It is shared by all the clones of the request, so interceptors running inside the layer can
+/// set it:
+///
+/// ```
+/// # use tonic::{transport::server::AuditIdentity, Request, Status};
+/// fn authenticate(request: Request<()>) -> Result<Request<()>, Status> {
+///     let user = "alice"; // e.g. verified from the `authorization` metadata
+///     if let Some(identity) = request.extensions().get::<AuditIdentity>() {
+///         identity.set(user);
+///     }
+///     Ok(request)
+/// }
+/// ```
+///
+/// If no interceptor sets it, the identity of TLS clients is taken from their certificate, see
+/// `PeerIdentity`: the first URI of its Subject Alternative Names, like a SPIFFE ID, or else its
+/// Common Name.
+#[derive(Debug, Clone, Default)]
+pub struct AuditIdentity(Arc<Mutex<Option<String>>>);
+
+impl AuditIdentity {
+    /// Set the identity of the caller.
+    pub fn set(&self, identity: impl Into<String>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(identity.into());
+    }
+
+    /// The identity of the caller, if set.
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// A layer emitting one [`AuditEvent`] per RPC to an [`AuditSink`], for compliance auditing.
+///
+/// Each event holds the method, the authenticated identity of the caller, see
+/// [`AuditIdentity`], SHA-256 digests of the request and response bodies, and the final status
+/// code. The bodies are hashed frame by frame as they stream through, without buffering them,
+/// so the digests cover the bodies as sent on the wire: the length-prefixed, possibly
+/// compressed, messages. The request digest covers what the handler read of the request.
+///
+/// Events are emitted once the response body completes, so streaming RPCs are audited when the
+/// stream terminates. Requests dropped before their response completes, e.g. because the client
+/// went away, are audited with [`Code::Cancelled`].
+///
+/// ```
+/// # use tonic::transport::{server::{AuditEvent, AuditLayer}, Server};
+/// Server::builder().layer(AuditLayer::new(|event: AuditEvent| {
+///     println!(
+///         "{} by {:?}: {:?}",
+///         event.method(),
+///         event.identity(),
+///         event.code()
+///     );
+/// }));
+/// ```
+#[derive(Clone)]
+pub struct AuditLayer {
+    sink: Arc<dyn AuditSink>,
+}
+
+impl AuditLayer {
+    /// Create a new audit layer emitting its events to `sink`.
+    pub fn new(sink: impl AuditSink) -> Self {
+        Self {
+            sink: Arc::new(sink),
+        }
+    }
+}
+
+impl fmt::Debug for AuditLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditLayer").finish()
+    }
+}
+
+impl<S> Layer<S> for AuditLayer {
+    type Service = Audit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Audit {
+            inner,
+            sink: self.sink.clone(),
+        }
+    }
+}
+
+/// Middleware emitting audit events, see [`AuditLayer`].
+#[derive(Clone)]
+pub struct Audit<S> {
+    inner: S,
+    sink: Arc<dyn AuditSink>,
+}
+
+impl<S> fmt::Debug for Audit<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Audit").finish()
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for Audit<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+{
+    type Response = Response<AuditBody<ResBody>>;
+    type Error = S::Error;
+    type Future = AuditFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let identity = AuditIdentity::default();
+        #[cfg(feature = "_tls-any")]
+        if let Some(peer) = req.extensions().get::<super::PeerIdentity>() {
+            if let Some(name) = peer
+                .uris()
+                .first()
+                .map(String::as_str)
+                .or(peer.common_name())
+            {
+                identity.set(name);
+            }
+        }
+        req.extensions_mut().insert(identity.clone());
+
+        let request_digest = Arc::new(Mutex::new(DigestContext::new(&SHA256)));
+        let response_digest = Arc::new(Mutex::new(DigestContext::new(&SHA256)));
+        let record = Record {
+            sink: self.sink.clone(),
+            method: req.uri().path().to_owned(),
+            identity,
+            request_digest: request_digest.clone(),
+            response_digest: response_digest.clone(),
+        };
+
+        let req = req.map(|body| {
+            Body::new(DigestBody {
+                inner: body,
+                digest: request_digest,
+            })
+        });
+        let observer: BoxObserver = Box::new(move |status| record.emit(status.code()));
+
+        AuditFuture {
+            inner: StatusResponseFuture::new(self.inner.call(req), observer),
+            digest: Some(response_digest),
+        }
+    }
+}
+
+/// Response future for [`Audit`].
+#[pin_project]
+pub struct AuditFuture<F> {
+    #[pin]
+    inner: StatusResponseFuture<F, BoxObserver>,
+    digest: Option<Arc<Mutex<DigestContext>>>,
+}
+
+impl<F> fmt::Debug for AuditFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditFuture").finish()
+    }
+}
+
+impl<F, ResBody, E> Future for AuditFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<AuditBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = ready!(this.inner.poll(cx))?;
+        let digest = this.digest.take().expect("polled after completion");
+
+        Poll::Ready(Ok(response.map(|inner| AuditBody {
+            inner: DigestBody { inner, digest },
+        })))
+    }
+}
+
+/// Response body for [`Audit`], hashing its frames and emitting the event once it completes.
+#[pin_project]
+pub struct AuditBody<B> {
+    #[pin]
+    inner: DigestBody<StatusResponseBody<B, BoxObserver>>,
+}
+
+impl<B> fmt::Debug for AuditBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditBody").finish()
+    }
+}
+
+impl<B> http_body::Body for AuditBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Hashes the data frames of a body as they stream through.
+#[pin_project]
+struct DigestBody<B> {
+    #[pin]
+    inner: B,
+    digest: Arc<Mutex<DigestContext>>,
+}
+
+impl<B> http_body::Body for DigestBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+
+        if let Some(data) = frame
+            .as_ref()
+            .and_then(|frame| frame.as_ref().ok()?.data_ref())
+        {
+            this.digest
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .update(data);
+        }
+
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+struct Record {
+    sink: Arc<dyn AuditSink>,
+    method: String,
+    identity: AuditIdentity,
+    request_digest: Arc<Mutex<DigestContext>>,
+    response_digest: Arc<Mutex<DigestContext>>,
+}
+
+impl Record {
+    fn emit(self, code: Code) {
+        self.sink.emit(AuditEvent {
+            method: self.method,
+            identity: self.identity.get(),
+            request_digest: finish(&self.request_digest),
+            response_digest: finish(&self.response_digest),
+            code,
+        });
+    }
+}
+
+fn finish(context: &Mutex<DigestContext>) -> Digest {
+    let context = context.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    context
+        .finish()
+        .as_ref()
+        .try_into()
+        .expect("SHA-256 digests are 32 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Status;
+    use http::HeaderMap;
+    use http_body_util::{BodyExt, Full, StreamBody};
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+
+    const REQUEST: &[u8] = &[0, 0, 0, 0, 3, 8, 150, 1];
+    const RESPONSE: &[u8] = &[0, 0, 0, 0, 2, 8, 1];
+
+    fn sha256(data: &[u8]) -> Digest {
+        ring::digest::digest(&SHA256, data)
+            .as_ref()
+            .try_into()
+            .unwrap()
+    }
+
+    fn audited(
+        events: Arc<Mutex<Vec<AuditEvent>>>,
+    ) -> Audit<impl Service<Request<Body>, Response = Response<Body>, Error = Infallible>> {
+        let svc = tower::service_fn(|req: Request<Body>| async move {
+            // An authentication interceptor running inside the layer.
+            req.extensions()
+                .get::<AuditIdentity>()
+                .unwrap()
+                .set("alice");
+            req.into_body().collect().await.unwrap();
+
+            let mut trailers = HeaderMap::new();
+            trailers.insert(Status::GRPC_STATUS, "0".parse().unwrap());
+            let frames = [
+                Ok::<_, Infallible>(Frame::data(Bytes::from_static(&RESPONSE[..4]))),
+                Ok(Frame::data(Bytes::from_static(&RESPONSE[4..]))),
+                Ok(Frame::trailers(trailers)),
+            ];
+            let body = Body::new(StreamBody::new(tokio_stream::iter(frames)));
+            Ok::<_, Infallible>(Response::new(body))
+        });
+
+        AuditLayer::new(move |event| events.lock().unwrap().push(event)).layer(svc)
+    }
+
+    #[tokio::test]
+    async fn emits_digests_of_unary_call() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let req = Request::post("/test.Test/UnaryCall")
+            .body(Body::new(Full::new(Bytes::from_static(REQUEST))))
+            .unwrap();
+
+        let res = audited(events.clone()).oneshot(req).await.unwrap();
+        assert!(events.lock().unwrap().is_empty());
+        let body = res.into_body().collect().await.unwrap();
+        assert_eq!(body.trailers().unwrap()[Status::GRPC_STATUS], "0");
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            *events,
+            [AuditEvent {
+                method: "/test.Test/UnaryCall".to_owned(),
+                identity: Some("alice".to_owned()),
+                request_digest: sha256(REQUEST),
+                response_digest: sha256(RESPONSE),
+                code: Code::Ok,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn dropped_responses_are_cancelled() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let req = Request::post("/test.Test/UnaryCall")
+            .body(Body::new(Full::new(Bytes::from_static(REQUEST))))
+            .unwrap();
+
+        drop(audited(events.clone()).oneshot(req).await.unwrap());
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].code(), Code::Cancelled);
+    }
+}
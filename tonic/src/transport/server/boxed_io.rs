@@ -0,0 +1,127 @@
+use super::Connected;
+use bytes::{Buf, BytesMut};
+use std::{
+    fmt,
+    future::poll_fn,
+    io::{self, IoSlice},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+trait Io: AsyncRead + AsyncWrite + Send + 'static {}
+
+impl<T> Io for T where T: AsyncRead + AsyncWrite + Send + 'static {}
+
+/// A type-erased connection, served by [`Router::serve_with_boxed_io`], or passed to
+/// [`Server::intercept_connections`].
+///
+/// Any `AsyncRead + AsyncWrite` byte stream can be boxed, e.g. one half of a
+/// [`tokio::io::duplex`] pipe, a connection of a custom transport or crafted IO for fuzzing.
+/// Requests on a boxed connection carry no connect info, so [`Request::remote_addr`] returns
+/// `None`.
+///
+/// [`Router::serve_with_boxed_io`]: super::Router::serve_with_boxed_io
+/// [`Server::intercept_connections`]: super::Server::intercept_connections
+/// [`Request::remote_addr`]: crate::Request::remote_addr
+pub struct BoxedIo {
+    io: Pin<Box<dyn Io>>,
+    /// Bytes read by [`BoxedIo::peek`], returned by the next reads.
+    peeked: BytesMut,
+}
+
+impl BoxedIo {
+    /// Box a connection.
+    pub fn new<T>(io: T) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        BoxedIo {
+            io: Box::pin(io),
+            peeked: BytesMut::new(),
+        }
+    }
+
+    /// Read the first `len` bytes of the connection without consuming them, e.g. to sniff its
+    /// protocol.
+    ///
+    /// The bytes are buffered and returned again by the next reads, so whoever serves the
+    /// connection reads it from its start. This waits until `len` bytes were received, and
+    /// returns fewer only if the connection was closed before. Peeking again returns the same
+    /// bytes, and reads more only if `len` is larger.
+    pub async fn peek(&mut self, len: usize) -> io::Result<&[u8]> {
+        let mut chunk = [0; 512];
+        while self.peeked.len() < len {
+            let want = (len - self.peeked.len()).min(chunk.len());
+            let read = poll_fn(|cx| {
+                let mut buf = ReadBuf::new(&mut chunk[..want]);
+                ready!(self.io.as_mut().poll_read(cx, &mut buf))?;
+                Poll::Ready(Ok::<_, io::Error>(buf.filled().len()))
+            })
+            .await?;
+            if read == 0 {
+                break;
+            }
+            self.peeked.extend_from_slice(&chunk[..read]);
+        }
+        Ok(&self.peeked[..len.min(self.peeked.len())])
+    }
+}
+
+impl fmt::Debug for BoxedIo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedIo").finish()
+    }
+}
+
+impl Connected for BoxedIo {
+    type ConnectInfo = ();
+
+    fn connect_info(&self) -> Self::ConnectInfo {}
+}
+
+impl AsyncRead for BoxedIo {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if !self.peeked.is_empty() {
+            let n = self.peeked.len().min(buf.remaining());
+            buf.put_slice(&self.peeked[..n]);
+            self.peeked.advance(n);
+            return Poll::Ready(Ok(()));
+        }
+        self.io.as_mut().poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for BoxedIo {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.as_mut().poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.io.as_mut().poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.io.as_mut().poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.io.as_mut().poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+}
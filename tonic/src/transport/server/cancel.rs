@@ -0,0 +1,49 @@
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio_util::sync::DropGuard;
+
+/// A response body that cancels the request's `CancellationToken` once it is dropped, which
+/// happens when the response has been sent or the stream was reset by the client.
+#[pin_project]
+pub(crate) struct CancelOnDrop<B> {
+    #[pin]
+    inner: B,
+    _guard: DropGuard,
+}
+
+impl<B> CancelOnDrop<B> {
+    pub(crate) fn new(inner: B, guard: DropGuard) -> Self {
+        Self {
+            inner,
+            _guard: guard,
+        }
+    }
+}
+
+impl<B> Body for CancelOnDrop<B>
+where
+    B: Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
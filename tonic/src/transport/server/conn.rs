@@ -55,6 +55,66 @@ pub trait Connected {
 
     /// Create type holding information about the connection.
     fn connect_info(&self) -> Self::ConnectInfo;
+
+    /// Return the transport independent info about the connection, see [`PeerInfo`].
+    ///
+    /// Defaults to a [`PeerInfo`] without addresses, override it for transports that have
+    /// them.
+    fn peer_info(&self) -> PeerInfo {
+        PeerInfo::default()
+    }
+}
+
+/// Transport independent info about a connection, produced by [`Connected::peer_info`].
+///
+/// The server inserts it into the [request extensions][ext] of every request, alongside the
+/// transport specific [`Connected::ConnectInfo`], and uses it to back [`Request::local_addr`],
+/// [`Request::remote_addr`] and `Request::peer_certs`, so handlers can read them without
+/// knowing whether the connection came in over TCP, a unix domain socket, TLS or a custom
+/// transport.
+///
+/// [ext]: crate::Request::extensions
+/// [`Request::local_addr`]: crate::Request::local_addr
+/// [`Request::remote_addr`]: crate::Request::remote_addr
+#[derive(Debug, Clone, Default)]
+pub struct PeerInfo {
+    pub(crate) local_addr: Option<SocketAddr>,
+    pub(crate) remote_addr: Option<SocketAddr>,
+    #[cfg(feature = "_tls-any")]
+    pub(crate) peer_certs: Option<Arc<Vec<CertificateDer<'static>>>>,
+}
+
+impl PeerInfo {
+    /// Create the info of a connection with the given addresses.
+    pub fn new(local_addr: Option<SocketAddr>, remote_addr: Option<SocketAddr>) -> Self {
+        Self {
+            local_addr,
+            remote_addr,
+            ..Self::default()
+        }
+    }
+
+    /// Set the certificates the peer presented during the TLS handshake.
+    #[cfg(feature = "_tls-any")]
+    pub fn with_peer_certs(self, peer_certs: Option<Arc<Vec<CertificateDer<'static>>>>) -> Self {
+        Self { peer_certs, ..self }
+    }
+
+    /// Return the local address of the connection, if it has one.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Return the remote (peer) address of the connection, if it has one.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// Return the certificates the peer presented during the TLS handshake, if any.
+    #[cfg(feature = "_tls-any")]
+    pub fn peer_certs(&self) -> Option<Arc<Vec<CertificateDer<'static>>>> {
+        self.peer_certs.clone()
+    }
 }
 
 /// Connection info for standard TCP streams.
@@ -94,6 +154,10 @@ impl Connected for TcpStream {
             remote_addr: self.peer_addr().ok(),
         }
     }
+
+    fn peer_info(&self) -> PeerInfo {
+        PeerInfo::new(self.local_addr().ok(), self.peer_addr().ok())
+    }
 }
 
 impl Connected for tokio::io::DuplexStream {
@@ -119,6 +183,15 @@ where
 
         TlsConnectInfo { inner, certs }
     }
+
+    fn peer_info(&self) -> PeerInfo {
+        let (inner, session) = self.get_ref();
+        let certs = session
+            .peer_certificates()
+            .map(|certs| certs.to_owned().into());
+
+        inner.peer_info().with_peer_certs(certs)
+    }
 }
 
 /// Connection info for TLS streams.
@@ -152,3 +225,58 @@ impl<T> TlsConnectInfo<T> {
         self.certs.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tcp_peer_info() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = TcpStream::connect(addr).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        let peer_info = server.peer_info();
+        assert_eq!(peer_info.local_addr(), Some(addr));
+        assert_eq!(peer_info.remote_addr(), client.local_addr().ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn uds_peer_info() {
+        let (server, _client) = tokio::net::UnixStream::pair().unwrap();
+
+        assert!(server.connect_info().peer_cred.is_some());
+
+        let peer_info = server.peer_info();
+        assert!(peer_info.local_addr().is_none());
+        assert!(peer_info.remote_addr().is_none());
+    }
+
+    #[test]
+    fn custom_io_has_no_peer_info() {
+        struct MyIo;
+
+        impl Connected for MyIo {
+            type ConnectInfo = ();
+
+            fn connect_info(&self) -> Self::ConnectInfo {}
+        }
+
+        let peer_info = MyIo.peer_info();
+        assert!(peer_info.local_addr().is_none());
+        assert!(peer_info.remote_addr().is_none());
+    }
+
+    #[cfg(feature = "_tls-any")]
+    #[test]
+    fn tls_peer_info() {
+        let remote_addr = "127.0.0.1:50051".parse().unwrap();
+        let certs = Arc::new(vec![CertificateDer::from(vec![1, 2, 3])]);
+
+        let peer_info = PeerInfo::new(None, Some(remote_addr)).with_peer_certs(Some(certs.clone()));
+        assert_eq!(peer_info.remote_addr(), Some(remote_addr));
+        assert_eq!(peer_info.peer_certs(), Some(certs));
+    }
+}
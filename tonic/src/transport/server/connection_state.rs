@@ -0,0 +1,106 @@
+use http::Extensions;
+use std::{fmt, net::SocketAddr, ops::Deref, sync::Arc};
+
+/// The state of the connection a request arrived on, created by [`Server::on_connect`].
+///
+/// This type is accessible through [request extensions][ext] of every request served by
+/// [`Server`] once [`Server::on_connect`] is set. All requests on a connection share the same
+/// state, unlike the extensions of a request, so handlers of protocols keeping a session or
+/// parameters negotiated per connection can read them there. The state is dropped once the
+/// connection and all its requests are done. Use interior mutability, e.g. a `Mutex`, to change
+/// it.
+///
+/// ```
+/// # use std::sync::atomic::{AtomicU64, Ordering};
+/// # use tonic::{Request, transport::server::ConnectionState};
+/// #[derive(Default)]
+/// struct Session {
+///     calls: AtomicU64,
+/// }
+///
+/// fn record_call(request: &Request<()>) -> u64 {
+///     let session = request.extensions().get::<ConnectionState<Session>>().unwrap();
+///     session.calls.fetch_add(1, Ordering::Relaxed) + 1
+/// }
+/// ```
+///
+/// [`Server`]: super::Server
+/// [`Server::on_connect`]: super::Server::on_connect
+/// [ext]: crate::Request::extensions
+pub struct ConnectionState<T>(Arc<T>);
+
+impl<T> Clone for ConnectionState<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Deref for ConnectionState<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ConnectionState<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ConnectionState").field(&self.0).finish()
+    }
+}
+
+/// Inserts the state of a connection into the extensions of its requests.
+pub(crate) type InsertState = Arc<dyn Fn(&mut Extensions) + Send + Sync + 'static>;
+
+/// Creates the state of every connection, see [`Server::on_connect`](super::Server::on_connect).
+#[derive(Clone)]
+pub(crate) struct OnConnect(Arc<dyn Fn(Option<SocketAddr>) -> InsertState + Send + Sync>);
+
+impl OnConnect {
+    pub(crate) fn new<T, F>(f: F) -> Self
+    where
+        F: Fn(Option<SocketAddr>) -> T + Send + Sync + 'static,
+        T: Send + Sync + 'static,
+    {
+        Self(Arc::new(move |remote_addr| {
+            let state = ConnectionState(Arc::new(f(remote_addr)));
+            Arc::new(move |extensions: &mut Extensions| {
+                extensions.insert(state.clone());
+            })
+        }))
+    }
+
+    /// Creates the state of a new connection from `remote_addr`.
+    pub(crate) fn connect(&self, remote_addr: Option<SocketAddr>) -> InsertState {
+        (self.0)(remote_addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn connections_have_their_own_state() {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let on_connect = OnConnect::new({
+            let connections = connections.clone();
+            move |_| connections.fetch_add(1, Ordering::Relaxed)
+        });
+
+        let first = on_connect.connect(None);
+        let second = on_connect.connect(None);
+        assert_eq!(connections.load(Ordering::Relaxed), 2);
+
+        let state = |insert: &InsertState| {
+            let mut extensions = Extensions::new();
+            insert(&mut extensions);
+            **extensions.get::<ConnectionState<usize>>().unwrap()
+        };
+        // Requests on the same connection share its state.
+        assert_eq!(state(&first), 0);
+        assert_eq!(state(&first), 0);
+        assert_eq!(state(&second), 1);
+    }
+}
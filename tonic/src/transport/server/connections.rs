@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{watch, Notify};
+
+/// A shared handle to the connections of a [`Server`], to close them or pause accepting new
+/// ones at runtime.
+///
+/// Obtained through [`Server::connections`]. This is meant for abuse mitigation, e.g. to drop
+/// the connections of a peer once it was detected, and for maintenance windows and load
+/// control, without restarting the server.
+///
+/// [`Server`]: super::Server
+/// [`Server::connections`]: super::Server::connections
+#[derive(Clone, Default)]
+pub struct ConnectionControl {
+    inner: Arc<Mutex<Registry>>,
+    paused: Arc<watch::Sender<bool>>,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    connections: HashMap<u64, Entry>,
+}
+
+struct Entry {
+    remote_addr: Option<SocketAddr>,
+    close: Arc<Notify>,
+}
+
+impl ConnectionControl {
+    /// Close the connections whose peer address matches `pred`, returning how many matched.
+    ///
+    /// Matching connections are shut down gracefully: HTTP/2 connections are sent a `GOAWAY`
+    /// frame and close once their in-flight requests complete. Connections without a peer
+    /// address, e.g. over unix domain sockets, are never matched.
+    pub fn close_connections<F>(&self, pred: F) -> usize
+    where
+        F: Fn(&SocketAddr) -> bool,
+    {
+        let registry = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        registry
+            .connections
+            .values()
+            .filter(|entry| entry.remote_addr.as_ref().is_some_and(&pred))
+            .inspect(|entry| entry.close.notify_one())
+            .count()
+    }
+
+    /// Pause accepting new connections, until [`ConnectionControl::resume_accepting`] is called.
+    ///
+    /// Unlike a shutdown, the listener stays open: new connections wait in its listen queue,
+    /// see [`Server::tcp_backlog`], and are accepted once resumed. The connections already
+    /// accepted keep being served as usual.
+    ///
+    /// [`Server::tcp_backlog`]: super::Server::tcp_backlog
+    pub fn pause_accepting(&self) {
+        self.paused.send_replace(true);
+    }
+
+    /// Resume accepting new connections after [`ConnectionControl::pause_accepting`].
+    pub fn resume_accepting(&self) {
+        self.paused.send_replace(false);
+    }
+
+    /// Whether accepting new connections is paused, see [`ConnectionControl::pause_accepting`].
+    pub fn is_accepting_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Watches whether accepting new connections is paused.
+    pub(crate) fn watch_paused(&self) -> watch::Receiver<bool> {
+        self.paused.subscribe()
+    }
+
+    /// Track a new connection until the returned handle is dropped.
+    pub(crate) fn register(&self, remote_addr: Option<SocketAddr>) -> ConnectionHandle {
+        let close = Arc::new(Notify::new());
+        let mut registry = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.connections.insert(
+            id,
+            Entry {
+                remote_addr,
+                close: close.clone(),
+            },
+        );
+
+        ConnectionHandle {
+            control: self.clone(),
+            id,
+            close,
+        }
+    }
+}
+
+impl fmt::Debug for ConnectionControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let registry = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("ConnectionControl")
+            .field("connections", &registry.connections.len())
+            .field("paused", &self.is_accepting_paused())
+            .finish()
+    }
+}
+
+/// A connection tracked by a [`ConnectionControl`].
+pub(crate) struct ConnectionHandle {
+    control: ConnectionControl,
+    id: u64,
+    close: Arc<Notify>,
+}
+
+impl ConnectionHandle {
+    /// Resolves once the connection was asked to close.
+    pub(crate) async fn closed(&self) {
+        self.close.notified().await
+    }
+}
+
+impl Drop for ConnectionHandle {
+    fn drop(&mut self) {
+        self.control
+            .inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .connections
+            .remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pausing_is_shared_between_handles() {
+        let control = ConnectionControl::default();
+        let server = control.clone();
+        let paused = server.watch_paused();
+        assert!(!*paused.borrow());
+
+        control.pause_accepting();
+        assert!(server.is_accepting_paused());
+        assert!(*paused.borrow());
+
+        control.resume_accepting();
+        assert!(!*paused.borrow());
+    }
+}
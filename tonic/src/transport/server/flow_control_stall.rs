@@ -0,0 +1,366 @@
+use super::PeerInfo;
+use bytes::{Buf, Bytes};
+use http::{Request, Response};
+use http_body::{Frame, SizeHint};
+use pin_project::{pin_project, pinned_drop};
+use std::{
+    fmt,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+type OnStall = Arc<dyn Fn(&FlowControlStall) + Send + Sync + 'static>;
+
+/// A layer reporting responses blocked on the client's flow control window.
+///
+/// A client that does not read its responses stops granting flow control window, and the server
+/// then cannot send the rest of the response. Responses stalled for longer than a threshold emit
+/// a `WARN` event with the `tonic::flow_control_stall` target, holding the method path, the peer
+/// address and the stalled time. To also feed metrics, register a callback with
+/// [`on_stall`](Self::on_stall).
+///
+/// # Limitations
+///
+/// The HTTP/2 implementation does not expose the window of a stream, so a stall is inferred from
+/// the connection not asking for the next part of the response body after sending one. This
+/// includes waiting for the window of the whole connection, so one slow stream can make the
+/// others of its connection report stalls, and for the socket to accept more data, e.g. on a
+/// congested network. A stall is reported once it ends: when the client opens the window again,
+/// or when the response is dropped, e.g. because the client went away.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tonic::transport::{server::FlowControlStallLayer, Server};
+/// Server::builder().layer(
+///     FlowControlStallLayer::new(Duration::from_secs(5)).on_stall(|stall| {
+///         eprintln!("{} stalled for {:?}", stall.method(), stall.stalled());
+///     }),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct FlowControlStallLayer {
+    threshold: Duration,
+    on_stall: Option<OnStall>,
+}
+
+impl FlowControlStallLayer {
+    /// Create a layer reporting responses stalled for longer than `threshold`.
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            on_stall: None,
+        }
+    }
+
+    /// Call `f` with every stall, in addition to emitting the event.
+    pub fn on_stall<F>(self, f: F) -> Self
+    where
+        F: Fn(&FlowControlStall) + Send + Sync + 'static,
+    {
+        Self {
+            on_stall: Some(Arc::new(f)),
+            ..self
+        }
+    }
+}
+
+impl fmt::Debug for FlowControlStallLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlowControlStallLayer")
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for FlowControlStallLayer {
+    type Service = FlowControlStallDetector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FlowControlStallDetector {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// A stalled response, passed to the callback of [`FlowControlStallLayer::on_stall`].
+#[derive(Clone, Debug)]
+pub struct FlowControlStall {
+    method: Arc<str>,
+    peer: Option<SocketAddr>,
+    stalled: Duration,
+    resumed: bool,
+}
+
+impl FlowControlStall {
+    /// The method path, e.g. `/helloworld.Greeter/SayHello`.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The address of the client, if known.
+    pub fn peer(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+
+    /// How long the response waited for the client.
+    pub fn stalled(&self) -> Duration {
+        self.stalled
+    }
+
+    /// Whether the response went on after the stall, rather than being dropped.
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+}
+
+/// Middleware reporting stalled responses, see [`FlowControlStallLayer`].
+#[derive(Clone)]
+pub struct FlowControlStallDetector<S> {
+    inner: S,
+    layer: FlowControlStallLayer,
+}
+
+impl<S> fmt::Debug for FlowControlStallDetector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlowControlStallDetector")
+            .field("threshold", &self.layer.threshold)
+            .finish()
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for FlowControlStallDetector<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = Response<FlowControlStallBody<ResBody>>;
+    type Error = S::Error;
+    type Future = FlowControlStallFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let watch = Watch {
+            layer: self.layer.clone(),
+            method: req.uri().path().into(),
+            peer: req
+                .extensions()
+                .get::<PeerInfo>()
+                .and_then(|info| info.remote_addr),
+            sent_at: None,
+        };
+
+        FlowControlStallFuture {
+            inner: self.inner.call(req),
+            watch: Some(watch),
+        }
+    }
+}
+
+/// Response future for [`FlowControlStallDetector`].
+#[pin_project]
+pub struct FlowControlStallFuture<F> {
+    #[pin]
+    inner: F,
+    watch: Option<Watch>,
+}
+
+impl<F> fmt::Debug for FlowControlStallFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlowControlStallFuture").finish()
+    }
+}
+
+impl<F, ResBody, E> Future for FlowControlStallFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<FlowControlStallBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = ready!(this.inner.poll(cx))?;
+        let watch = this.watch.take().expect("polled after completion");
+
+        Poll::Ready(Ok(
+            response.map(|inner| FlowControlStallBody { inner, watch })
+        ))
+    }
+}
+
+/// Response body for [`FlowControlStallDetector`], timing how long the connection takes to ask
+/// for more data after each chunk.
+#[pin_project(PinnedDrop)]
+pub struct FlowControlStallBody<B> {
+    #[pin]
+    inner: B,
+    watch: Watch,
+}
+
+impl<B> fmt::Debug for FlowControlStallBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlowControlStallBody").finish()
+    }
+}
+
+impl<B> http_body::Body for FlowControlStallBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        this.watch.check(true);
+
+        let frame = ready!(this.inner.as_mut().poll_frame(cx));
+        // The connection only asks for the next frame once it could send this one.
+        let sent_data = matches!(
+            &frame,
+            Some(Ok(frame)) if frame.data_ref().is_some_and(Buf::has_remaining)
+        );
+        if sent_data && !this.inner.is_end_stream() {
+            this.watch.sent_at = Some(Instant::now());
+        }
+
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[pinned_drop]
+impl<B> PinnedDrop for FlowControlStallBody<B> {
+    fn drop(self: Pin<&mut Self>) {
+        self.project().watch.check(false);
+    }
+}
+
+struct Watch {
+    layer: FlowControlStallLayer,
+    method: Arc<str>,
+    peer: Option<SocketAddr>,
+    sent_at: Option<Instant>,
+}
+
+impl Watch {
+    fn check(&mut self, resumed: bool) {
+        let Some(sent_at) = self.sent_at.take() else {
+            return;
+        };
+        let stalled = sent_at.elapsed();
+        if stalled <= self.layer.threshold {
+            return;
+        }
+
+        tracing::warn!(
+            target: "tonic::flow_control_stall",
+            method = %self.method,
+            peer = ?self.peer,
+            stalled_ms = stalled.as_secs_f64() * 1000.0,
+            resumed,
+            "response stalled on flow control"
+        );
+
+        if let Some(on_stall) = &self.layer.on_stall {
+            on_stall(&FlowControlStall {
+                method: self.method.clone(),
+                peer: self.peer,
+                stalled,
+                resumed,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use http_body_util::BodyExt;
+    use std::{pin::pin, sync::Mutex};
+    use tower::ServiceExt;
+
+    async fn response(
+        samples: &Arc<Mutex<Vec<FlowControlStall>>>,
+    ) -> Response<FlowControlStallBody<Body>> {
+        let layer = FlowControlStallLayer::new(Duration::from_millis(50)).on_stall({
+            let samples = samples.clone();
+            move |sample| samples.lock().unwrap().push(sample.clone())
+        });
+
+        let svc = tower::service_fn(|_: Request<Body>| async {
+            let frames = ["a", "b"].map(|chunk| Ok::<_, crate::Status>(Frame::data(chunk.into())));
+            Ok::<_, std::convert::Infallible>(Response::new(Body::new(
+                http_body_util::StreamBody::new(tokio_stream::iter(frames)),
+            )))
+        });
+        let request = Request::builder()
+            .uri("/test.Test/StreamCall")
+            .body(Body::empty())
+            .unwrap();
+        layer.layer(svc).oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn reports_stalls() {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let mut body = pin!(response(&samples).await.into_body());
+
+        body.frame().await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        body.frame().await.unwrap().unwrap();
+        assert!(body.frame().await.is_none());
+
+        let samples = samples.lock().unwrap().clone();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].method(), "/test.Test/StreamCall");
+        assert!(samples[0].stalled() >= Duration::from_millis(100));
+        assert!(samples[0].resumed());
+    }
+
+    #[tokio::test]
+    async fn reports_stalls_of_dropped_responses() {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let mut body = Box::pin(response(&samples).await.into_body());
+
+        body.frame().await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(body);
+
+        let samples = samples.lock().unwrap().clone();
+        assert_eq!(samples.len(), 1);
+        assert!(!samples[0].resumed());
+    }
+
+    #[tokio::test]
+    async fn ignores_responses_read_in_time() {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        response(&samples)
+            .await
+            .into_body()
+            .collect()
+            .await
+            .unwrap();
+
+        assert!(samples.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1224 @@
+use super::{
+    ping::{ConnectionPing, PAYLOAD, PING_FRAME},
+    preface::{PrefaceDone, PREFACE},
+};
+use crate::transport::go_away::{GoAway, GoAwayHook, GoAwayRewrite};
+use bytes::{Buf, Bytes, BytesMut};
+use pin_project::{pin_project, pinned_drop};
+use std::{
+    collections::HashMap,
+    io,
+    ops::Deref,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_util::either::Either;
+
+const FRAME_HEADER_LEN: usize = 9;
+const DATA: u8 = 0x0;
+const HEADERS: u8 = 0x1;
+const RST_STREAM: u8 = 0x3;
+const SETTINGS: u8 = 0x4;
+const PING: u8 = 0x6;
+const GOAWAY: u8 = 0x7;
+const END_STREAM: u8 = 0x1;
+const ACK: u8 = 0x1;
+/// The number of bytes read from the connection at once.
+const READ_CHUNK_LEN: usize = 8 * 1024;
+
+/// The options of a connection that watch its HTTP/2 frames, see [`FramesIo`].
+#[derive(Default)]
+pub(crate) struct Watch {
+    /// Set once the client sent its connection preface and `SETTINGS` frame, see
+    /// [`Server::http2_settings_timeout`](super::Server::http2_settings_timeout).
+    pub(crate) preface: Option<PrefaceDone>,
+    /// See [`Server::http2_max_header_frames_per_stream`](super::Server::http2_max_header_frames_per_stream).
+    pub(crate) max_header_frames_per_stream: Option<u32>,
+    /// Pings requested by handlers, see [`ConnectionPing`].
+    pub(crate) ping: Option<ConnectionPing>,
+    /// See [`Server::on_goaway_sent`](super::Server::on_goaway_sent).
+    pub(crate) on_goaway_sent: Option<GoAwayHook>,
+    /// See [`Server::http2_goaway`](super::Server::http2_goaway).
+    pub(crate) rewrite_goaway: Option<GoAwayRewrite>,
+}
+
+/// Watches the HTTP/2 frames of a server connection, for the options that need to see them.
+///
+/// The frames read from the connection are parsed once for all the options in [`Watch`]:
+///
+/// - the connection preface and the client's first `SETTINGS` frame, see [`PrefaceDone`],
+/// - the `HEADERS` frames of each stream, whose `END_STREAM` flag is cleared beyond the limit.
+///   This makes them trailers without `END_STREAM`, a malformed message that resets just their
+///   stream with `PROTOCOL_ERROR`, while the header block is still decoded, keeping the header
+///   compression state of the connection intact for the other streams. Streams are counted
+///   until they end, when the client ends them with `END_STREAM` or either side resets them,
+/// - the acks of the pings sent through a [`ConnectionPing`], which are removed, and of the
+///   ones `hyper` sends itself, to measure their round-trip time.
+///
+/// The frames written to the connection are parsed for `GOAWAY`, `PING` and `RST_STREAM`
+/// frames, and to find the frame boundaries where the pings of a [`ConnectionPing`] are
+/// written. While a ping waits, writes are cut at the end of the current frame, as the
+/// connection may write many frames at once. To rewrite `GOAWAY` frames, writes are cut before
+/// them, and they are held back until complete, then written as rewritten.
+///
+/// Bytes are read into a buffer, and returned once the frame headers they belong to were
+/// parsed, so that flags can be changed before the connection sees them. Once no option needs
+/// to see more frames, e.g. after the handshake or because the connection isn't HTTP/2, bytes
+/// are passed through as they are.
+#[pin_project(PinnedDrop)]
+pub(crate) struct FramesIo<IO> {
+    #[pin]
+    inner: IO,
+    reader: Reader,
+    writer: Writer,
+}
+
+impl<IO> FramesIo<IO> {
+    /// Wraps `inner`, unless none of the options in `watch` is set.
+    pub(crate) fn wrap(inner: IO, watch: Watch) -> Either<IO, Self> {
+        let Watch {
+            preface,
+            max_header_frames_per_stream,
+            ping,
+            on_goaway_sent,
+            rewrite_goaway,
+        } = watch;
+        if preface.is_none()
+            && max_header_frames_per_stream.is_none()
+            && ping.is_none()
+            && on_goaway_sent.is_none()
+            && rewrite_goaway.is_none()
+        {
+            return Either::Left(inner);
+        }
+
+        Either::Right(Self {
+            inner,
+            reader: Reader::new(
+                preface,
+                max_header_frames_per_stream.map(HeaderFrames::new),
+                ping.clone(),
+            ),
+            writer: Writer::new(
+                ping,
+                on_goaway_sent,
+                rewrite_goaway,
+                max_header_frames_per_stream.is_some(),
+            ),
+        })
+    }
+}
+
+impl<IO: AsyncWrite> FramesIo<IO> {
+    /// Writes the frame that was held back, or the requested ping, if the connection is in
+    /// between frames.
+    fn poll_write_pending(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+        let writer = &mut *this.writer;
+        if let Some(ping) = &writer.ping {
+            if writer.pending.is_empty()
+                && this.reader.is_http2()
+                && writer.at_boundary()
+                && ping.take_request()
+            {
+                writer.pending = Bytes::from_static(&PING_FRAME);
+            }
+        }
+
+        while !writer.pending.is_empty() {
+            let n = ready!(this.inner.as_mut().poll_write(cx, &writer.pending))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            writer.pending.advance(n);
+            writer.unflushed = true;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// The number of bytes that may be written now: up to the end of the current frame while
+    /// a ping waits for it, so the ping isn't delayed by writes that span several frames.
+    fn write_limit(&self) -> usize {
+        match &self.writer.ping {
+            Some(ping) if self.reader.is_http2() && ping.is_requested() => {
+                self.writer.until_boundary()
+            }
+            _ => usize::MAX,
+        }
+    }
+}
+
+impl<IO: AsyncRead + AsyncWrite> AsyncRead for FramesIo<IO> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if let (Some(ping), false) = (&self.writer.ping, self.reader.done) {
+            ping.set_reader(cx.waker());
+
+            // The connection may be idle and not write anything, so the reading task, which is
+            // always waiting, writes the ping.
+            if let Poll::Ready(Ok(())) = self.as_mut().poll_write_pending(cx) {
+                let this = self.as_mut().project();
+                if this.writer.unflushed {
+                    if let Poll::Ready(Ok(())) = this.inner.poll_flush(cx) {
+                        this.writer.unflushed = false;
+                    }
+                }
+            }
+        }
+
+        let mut this = self.project();
+        let reader = &mut *this.reader;
+        loop {
+            if reader.ready > 0 {
+                let n = reader.ready.min(buf.remaining());
+                buf.put_slice(&reader.buf[..n]);
+                reader.buf.advance(n);
+                reader.ready -= n;
+                return Poll::Ready(Ok(()));
+            }
+            if reader.done && reader.buf.is_empty() {
+                return this.inner.poll_read(cx, buf);
+            }
+
+            let len = reader.buf.len();
+            reader.buf.resize(len + READ_CHUNK_LEN, 0);
+            let mut chunk = ReadBuf::new(&mut reader.buf[len..]);
+            let polled = this.inner.as_mut().poll_read(cx, &mut chunk);
+            let read = chunk.filled().len();
+            reader.buf.truncate(len + read);
+            ready!(polled)?;
+
+            if read == 0 {
+                // The connection is closed, so what is held back is returned as it is.
+                reader.ready = reader.buf.len();
+                if reader.ready == 0 {
+                    return Poll::Ready(Ok(()));
+                }
+            } else {
+                if let (Some(header_frames), Some(resets)) =
+                    (&mut reader.header_frames, &mut this.writer.resets)
+                {
+                    for stream_id in resets.drain(..) {
+                        header_frames.end(stream_id);
+                    }
+                }
+                reader.process();
+            }
+        }
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for FramesIo<IO> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.as_mut().poll_write_pending(cx))?;
+
+        let mut limit = self.write_limit();
+        match self.writer.next_held(&[buf]) {
+            Some(0) => return Poll::Ready(Ok(self.project().writer.hold(buf))),
+            Some(held) => limit = limit.min(held),
+            None => {}
+        }
+
+        let buf = &buf[..buf.len().min(limit)];
+        let this = self.project();
+        let n = ready!(this.inner.poll_write(cx, buf))?;
+        this.writer.feed(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_write_pending(cx))?;
+
+        let this = self.project();
+        ready!(this.inner.poll_flush(cx))?;
+        this.writer.unflushed = false;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_write_pending(cx))?;
+        self.project().inner.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.as_mut().poll_write_pending(cx))?;
+
+        let limit = self.write_limit();
+        if limit != usize::MAX || self.writer.next_held(bufs).is_some() {
+            let buf = bufs
+                .iter()
+                .find(|buf| !buf.is_empty())
+                .map_or(&[][..], |buf| buf);
+            return self.poll_write(cx, &buf[..buf.len().min(limit)]);
+        }
+
+        let this = self.project();
+        let n = ready!(this.inner.poll_write_vectored(cx, bufs))?;
+        let mut written = n;
+        for buf in bufs {
+            let len = buf.len().min(written);
+            this.writer.feed(&buf[..len]);
+            written -= len;
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+#[pinned_drop]
+impl<IO> PinnedDrop for FramesIo<IO> {
+    fn drop(self: Pin<&mut Self>) {
+        if let Some(ping) = &self.writer.ping {
+            ping.close();
+        }
+    }
+}
+
+/// Parses the frames read from the connection.
+#[derive(Debug)]
+struct Reader {
+    /// The bytes read from the connection that weren't returned yet.
+    buf: BytesMut,
+    /// The number of bytes at the start of `buf` that were parsed and can be returned.
+    ready: usize,
+    /// The bytes of the connection preface read so far.
+    preface_len: usize,
+    /// The first frame was read, the `SETTINGS` frame that must come first.
+    started: bool,
+    /// The current frame is the client's first `SETTINGS` frame.
+    in_settings: bool,
+    /// The bytes of the payload of the current frame still to be read.
+    remaining: usize,
+    preface: Option<PrefaceDone>,
+    header_frames: Option<HeaderFrames>,
+    ping: Option<ConnectionPing>,
+    /// No more frames are parsed, because none of the options needs them, or the connection
+    /// doesn't consist of HTTP/2 frames.
+    done: bool,
+}
+
+impl Reader {
+    fn new(
+        preface: Option<PrefaceDone>,
+        header_frames: Option<HeaderFrames>,
+        ping: Option<ConnectionPing>,
+    ) -> Self {
+        Self {
+            buf: BytesMut::new(),
+            ready: 0,
+            preface_len: 0,
+            started: false,
+            in_settings: false,
+            remaining: 0,
+            preface,
+            header_frames,
+            ping,
+            done: false,
+        }
+    }
+
+    /// Whether the client started with the HTTP/2 connection preface.
+    fn is_http2(&self) -> bool {
+        self.preface_len == PREFACE.len() && !self.done
+    }
+
+    /// Parses the bytes of `buf` after the ones that are ready, making ready the ones that
+    /// complete frame headers. Bytes of incomplete frame headers are held back until the rest
+    /// was read.
+    fn process(&mut self) {
+        while !self.done && self.ready < self.buf.len() {
+            let data = &self.buf[self.ready..];
+
+            if self.preface_len < PREFACE.len() {
+                let n = (PREFACE.len() - self.preface_len).min(data.len());
+                if data[..n] != PREFACE[self.preface_len..self.preface_len + n] {
+                    // Not HTTP/2 with prior knowledge, which is left to the connection to handle.
+                    self.finish_preface(false);
+                    self.done = true;
+                    if let Some(ping) = &self.ping {
+                        ping.close();
+                    }
+                    break;
+                }
+                self.preface_len += n;
+                self.ready += n;
+                continue;
+            }
+
+            if self.remaining > 0 {
+                let n = self.remaining.min(data.len());
+                self.remaining -= n;
+                self.ready += n;
+                if self.remaining == 0 && self.in_settings {
+                    self.finish_preface(true);
+                }
+                continue;
+            }
+
+            let Some(header) = data.get(..FRAME_HEADER_LEN) else {
+                break;
+            };
+            let kind = header[3];
+            let flags = header[4];
+            let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+            let stream_id =
+                u32::from_be_bytes([header[5], header[6], header[7], header[8]]) & !(1 << 31);
+
+            if !self.started {
+                self.started = true;
+                if kind == SETTINGS {
+                    self.in_settings = true;
+                } else {
+                    // A protocol error, which is left to the connection to handle.
+                    self.finish_preface(false);
+                }
+            }
+
+            if kind == PING && flags & ACK != 0 && len == PAYLOAD.len() {
+                if let Some(ping) = &self.ping {
+                    let end = self.ready + PING_FRAME.len();
+                    let Some(frame) = self.buf.get(self.ready..end) else {
+                        break;
+                    };
+                    if frame[FRAME_HEADER_LEN..] != PAYLOAD {
+                        ping.connection_ping_acked(&frame[FRAME_HEADER_LEN..]);
+                    } else {
+                        ping.acked();
+                        // `hyper` warns about acks of pings it didn't send, so it never sees
+                        // the ack.
+                        let len = self.buf.len();
+                        self.buf.copy_within(end.., self.ready);
+                        self.buf.truncate(len - PING_FRAME.len());
+                        continue;
+                    }
+                }
+            }
+
+            if let Some(header_frames) = &mut self.header_frames {
+                match kind {
+                    HEADERS if header_frames.record(stream_id) => {
+                        self.buf[self.ready + 4] &= !END_STREAM;
+                    }
+                    HEADERS | DATA if flags & END_STREAM != 0 => header_frames.end(stream_id),
+                    RST_STREAM => header_frames.end(stream_id),
+                    _ => {}
+                }
+            }
+
+            self.ready += FRAME_HEADER_LEN;
+            self.remaining = len;
+            if self.remaining == 0 && self.in_settings {
+                self.finish_preface(true);
+            }
+        }
+
+        if self.done {
+            self.ready = self.buf.len();
+        }
+    }
+
+    /// Records that the client completed its side of the handshake, or failed to.
+    fn finish_preface(&mut self, established: bool) {
+        self.in_settings = false;
+        if let Some(preface) = self.preface.take() {
+            preface.finish(established);
+        }
+        if self.header_frames.is_none() && self.ping.is_none() {
+            self.done = true;
+        }
+    }
+}
+
+/// Counts the `HEADERS` frames of each stream, see
+/// [`Server::http2_max_header_frames_per_stream`](super::Server::http2_max_header_frames_per_stream).
+///
+/// Only the streams that didn't end are tracked, so there are never more than the connection
+/// has open.
+#[derive(Debug)]
+struct HeaderFrames {
+    limit: u32,
+    counts: HashMap<u32, u32>,
+    /// The highest stream opened by the client.
+    last_stream_id: u32,
+}
+
+impl HeaderFrames {
+    fn new(limit: u32) -> Self {
+        Self {
+            limit: limit.max(1),
+            counts: HashMap::new(),
+            last_stream_id: 0,
+        }
+    }
+
+    /// Counts a `HEADERS` frame of `stream_id`, returning whether it is beyond the limit.
+    ///
+    /// Frames of streams that ended are left to the connection, which rejects them, as stream
+    /// identifiers can't be reused.
+    fn record(&mut self, stream_id: u32) -> bool {
+        let count = match self.counts.get_mut(&stream_id) {
+            Some(count) => count,
+            None if stream_id > self.last_stream_id => {
+                self.last_stream_id = stream_id;
+                self.counts.entry(stream_id).or_default()
+            }
+            None => return false,
+        };
+        *count += 1;
+        *count > self.limit
+    }
+
+    /// Stops counting the frames of `stream_id`, which ended.
+    fn end(&mut self, stream_id: u32) {
+        self.counts.remove(&stream_id);
+    }
+}
+
+/// Parses the frames written to the connection.
+struct Writer {
+    /// The connection wrote its first frame, the `SETTINGS` frame that must come first.
+    started: bool,
+    header: [u8; FRAME_HEADER_LEN],
+    header_len: usize,
+    /// The bytes of the payload of the current frame still to be written.
+    remaining: usize,
+    /// The payload of the current frame, if it is a `GOAWAY` or a `PING` frame that is watched.
+    payload: Option<Vec<u8>>,
+    /// The connection doesn't consist of HTTP/2 frames.
+    done: bool,
+    ping: Option<ConnectionPing>,
+    on_goaway_sent: Option<GoAwayHook>,
+    rewrite_goaway: Option<GoAwayRewrite>,
+    /// The streams reset by the connection, for the reader to stop counting their `HEADERS`
+    /// frames, if they are counted.
+    resets: Option<Vec<u32>>,
+    /// The bytes of the frame being written that are held back, a `GOAWAY` frame to rewrite,
+    /// or a frame header cut off by the write, whose frame type isn't known yet.
+    held: Option<BytesMut>,
+    /// The part still to be written of the `PING` frame, or of the frame that was held back.
+    pending: Bytes,
+    /// The `PING` frame was written but not flushed yet.
+    unflushed: bool,
+}
+
+impl Writer {
+    fn new(
+        ping: Option<ConnectionPing>,
+        on_goaway_sent: Option<GoAwayHook>,
+        rewrite_goaway: Option<GoAwayRewrite>,
+        track_resets: bool,
+    ) -> Self {
+        Self {
+            started: false,
+            header: [0; FRAME_HEADER_LEN],
+            header_len: 0,
+            remaining: 0,
+            payload: None,
+            done: false,
+            ping,
+            on_goaway_sent,
+            rewrite_goaway,
+            resets: track_resets.then(Vec::new),
+            held: None,
+            pending: Bytes::new(),
+            unflushed: false,
+        }
+    }
+
+    /// Whether the connection is in between frames, where a frame can be inserted.
+    fn at_boundary(&self) -> bool {
+        self.started && !self.done && self.header_len == 0 && self.remaining == 0
+    }
+
+    /// The number of bytes until the end of the current frame header or payload.
+    fn until_boundary(&self) -> usize {
+        if !self.started || self.done {
+            usize::MAX
+        } else if self.header_len > 0 {
+            FRAME_HEADER_LEN - self.header_len
+        } else if self.remaining > 0 {
+            self.remaining
+        } else {
+            usize::MAX
+        }
+    }
+
+    /// The offset in `bufs`, which are about to be written, of the first frame that is held
+    /// back instead, see [`Writer::hold`].
+    fn next_held<B: Deref<Target = [u8]>>(&self, bufs: &[B]) -> Option<usize> {
+        if self.rewrite_goaway.is_none() || !self.started || self.done {
+            return None;
+        }
+        if self.held.is_some() {
+            return Some(0);
+        }
+
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        let byte = |mut offset: usize| {
+            bufs.iter().find_map(|buf| {
+                let byte = buf.get(offset).copied();
+                offset = offset.saturating_sub(buf.len());
+                byte
+            })
+        };
+
+        // The end of the current frame, where the next one starts.
+        let mut pos = if self.header_len > 0 {
+            let mut header = self.header;
+            for (i, b) in header[self.header_len..].iter_mut().enumerate() {
+                // The write ends in the header of a frame that isn't held back.
+                *b = byte(i)?;
+            }
+            FRAME_HEADER_LEN - self.header_len + payload_len(&header)
+        } else {
+            self.remaining
+        };
+        while pos < len {
+            if pos + FRAME_HEADER_LEN > len || byte(pos + 3) == Some(GOAWAY) {
+                return Some(pos);
+            }
+            let header = [byte(pos)?, byte(pos + 1)?, byte(pos + 2)?];
+            pos += FRAME_HEADER_LEN + payload_len(&header);
+        }
+        None
+    }
+
+    /// Takes the bytes of `buf` that belong to the frame held back, returning how many it took.
+    ///
+    /// Once its header is complete, a frame other than a `GOAWAY` frame is written as it is.
+    /// A `GOAWAY` frame is written once complete, as rewritten.
+    fn hold(&mut self, buf: &[u8]) -> usize {
+        let held = self.held.get_or_insert_with(BytesMut::new);
+        let missing = if held.len() < FRAME_HEADER_LEN {
+            FRAME_HEADER_LEN - held.len()
+        } else {
+            FRAME_HEADER_LEN + payload_len(held) - held.len()
+        };
+        let n = missing.min(buf.len());
+        held.extend_from_slice(&buf[..n]);
+
+        let complete = held.len() >= FRAME_HEADER_LEN
+            && (held[3] != GOAWAY || held.len() == FRAME_HEADER_LEN + payload_len(held));
+        if complete {
+            let held = self.held.take().unwrap_or_default().freeze();
+            let frame = match (&self.rewrite_goaway, held[3]) {
+                (Some(rewrite), GOAWAY) => GoAway::parse(&held[FRAME_HEADER_LEN..])
+                    .map_or(held, |go_away| rewrite(go_away).encode()),
+                _ => held,
+            };
+            self.feed(&frame);
+            self.pending = frame;
+        }
+        n
+    }
+
+    fn feed(&mut self, mut data: &[u8]) {
+        while !data.is_empty() && !self.done {
+            if self.remaining > 0 {
+                let n = self.remaining.min(data.len());
+                if let Some(payload) = &mut self.payload {
+                    let keep = GoAway::MAX_PAYLOAD_LEN.saturating_sub(payload.len());
+                    payload.extend_from_slice(&data[..n.min(keep)]);
+                }
+                self.remaining -= n;
+                data = &data[n..];
+                if self.remaining == 0 {
+                    self.finish_frame();
+                }
+                continue;
+            }
+
+            let n = (FRAME_HEADER_LEN - self.header_len).min(data.len());
+            self.header[self.header_len..self.header_len + n].copy_from_slice(&data[..n]);
+            self.header_len += n;
+            data = &data[n..];
+            if self.header_len < FRAME_HEADER_LEN {
+                continue;
+            }
+
+            self.header_len = 0;
+            let kind = self.header[3];
+            if !self.started {
+                self.started = true;
+                if kind != SETTINGS {
+                    self.done = true;
+                    return;
+                }
+            }
+            self.remaining =
+                u32::from_be_bytes([0, self.header[0], self.header[1], self.header[2]]) as usize;
+            if let (RST_STREAM, Some(resets)) = (kind, &mut self.resets) {
+                let stream_id = &self.header[5..];
+                resets.push(u32::from_be_bytes(stream_id.try_into().unwrap()) & !(1 << 31));
+            }
+            let watched = match kind {
+                GOAWAY => self.on_goaway_sent.is_some(),
+                PING => self.header[4] & ACK == 0 && self.ping.is_some(),
+                _ => false,
+            };
+            self.payload = watched.then(Vec::new);
+            if self.remaining == 0 {
+                self.finish_frame();
+            }
+        }
+    }
+
+    fn finish_frame(&mut self) {
+        let Some(payload) = self.payload.take() else {
+            return;
+        };
+        match self.header[3] {
+            GOAWAY => {
+                if let (Some(hook), Some(go_away)) = (&self.on_goaway_sent, GoAway::parse(&payload))
+                {
+                    hook(&go_away);
+                }
+            }
+            _ => {
+                if let (Some(ping), Ok(payload)) = (&self.ping, payload.try_into()) {
+                    ping.connection_ping_sent(payload);
+                }
+            }
+        }
+    }
+}
+
+/// The payload length in a frame header.
+fn payload_len(header: &[u8]) -> usize {
+    u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+    const SETTINGS_FRAME: [u8; FRAME_HEADER_LEN] = [0, 0, 0, SETTINGS, 0, 0, 0, 0, 0];
+
+    fn frame(kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+        let mut frame = (payload.len() as u32).to_be_bytes()[1..].to_vec();
+        frame.extend_from_slice(&[kind, flags]);
+        frame.extend_from_slice(&stream_id.to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn wrap(io: DuplexStream, watch: Watch) -> FramesIo<DuplexStream> {
+        match FramesIo::wrap(io, watch) {
+            Either::Right(io) => io,
+            Either::Left(_) => panic!("nothing to watch"),
+        }
+    }
+
+    /// Reads all of `input` through a [`FramesIo`], in reads of at most `read_len` bytes.
+    async fn read_through(input: Vec<u8>, watch: Watch, read_len: usize) -> Vec<u8> {
+        // Written in small chunks, splitting frame headers.
+        let (mut client, server) = tokio::io::duplex(7);
+        let write = tokio::spawn(async move { client.write_all(&input).await });
+        let mut io = wrap(server, watch);
+        let mut output = Vec::new();
+        let mut buf = vec![0; read_len];
+        loop {
+            let n = io.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            output.extend_from_slice(&buf[..n]);
+        }
+        write.await.unwrap().unwrap();
+        output
+    }
+
+    #[test]
+    fn wraps_only_if_something_is_watched() {
+        let (io, _) = tokio::io::duplex(1);
+        assert!(matches!(
+            FramesIo::wrap(io, Watch::default()),
+            Either::Left(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn clears_end_stream_of_header_frames_beyond_the_limit() {
+        let headers = frame(HEADERS, 0x4, 1, &[0x83, 0x86, 0x84]);
+        let trailers = frame(HEADERS, 0x4 | END_STREAM, 1, &[0x40, 1, b'a', 1, b'b']);
+        let other = frame(HEADERS, 0x4 | END_STREAM, 3, &[0x83, 0x86, 0x84]);
+        let mut input = PREFACE.to_vec();
+        input.extend_from_slice(&SETTINGS_FRAME);
+        for frame in [&headers, &trailers, &trailers, &other] {
+            input.extend_from_slice(frame);
+        }
+
+        let mut expected = PREFACE.to_vec();
+        expected.extend_from_slice(&SETTINGS_FRAME);
+        expected.extend_from_slice(&headers);
+        // The trailers of stream 1 are beyond the limit, the headers of stream 3 are not.
+        let mut reset = trailers.clone();
+        reset[4] &= !END_STREAM;
+        expected.extend_from_slice(&reset);
+        expected.extend_from_slice(&reset);
+        expected.extend_from_slice(&other);
+
+        // Reads smaller than a frame header don't stop the limit from being enforced.
+        for read_len in [1, 5, 1024] {
+            let watch = Watch {
+                max_header_frames_per_stream: Some(1),
+                ..Watch::default()
+            };
+            let output = read_through(input.clone(), watch, read_len).await;
+            assert_eq!(output, expected, "reads of {read_len} bytes");
+        }
+    }
+
+    #[tokio::test]
+    async fn counts_streams_opened_before_many_others() {
+        let mut input = PREFACE.to_vec();
+        input.extend_from_slice(&SETTINGS_FRAME);
+        input.extend_from_slice(&frame(HEADERS, 0x4, 1, &[0x83, 0x86, 0x84]));
+        // Short calls that end while the first stream stays open.
+        for stream_id in (3..).step_by(2).take(2048) {
+            input.extend_from_slice(&frame(
+                HEADERS,
+                0x4 | END_STREAM,
+                stream_id,
+                &[0x83, 0x86, 0x84],
+            ));
+        }
+        let mut expected = input.clone();
+        let trailers = frame(HEADERS, 0x4 | END_STREAM, 1, &[0x40, 1, b'a', 1, b'b']);
+        input.extend_from_slice(&trailers);
+        let mut reset = trailers;
+        reset[4] &= !END_STREAM;
+        expected.extend_from_slice(&reset);
+
+        let watch = Watch {
+            max_header_frames_per_stream: Some(1),
+            ..Watch::default()
+        };
+        assert_eq!(read_through(input, watch, 1024).await, expected);
+    }
+
+    #[tokio::test]
+    async fn stops_counting_streams_that_ended() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let mut io = wrap(
+            server,
+            Watch {
+                max_header_frames_per_stream: Some(1),
+                ..Watch::default()
+            },
+        );
+        let mut buf = vec![0; 1024];
+
+        let mut input = PREFACE.to_vec();
+        input.extend_from_slice(&SETTINGS_FRAME);
+        input.extend_from_slice(&frame(HEADERS, 0x4, 1, &[0x83, 0x86, 0x84]));
+        input.extend_from_slice(&frame(HEADERS, 0x4, 3, &[0x83, 0x86, 0x84]));
+        client.write_all(&input).await.unwrap();
+        let n = io.read(&mut buf).await.unwrap();
+        assert_eq!(buf[..n], input);
+
+        // The connection resets stream 1, and the client ends stream 3.
+        io.write_all(&SETTINGS_FRAME).await.unwrap();
+        io.write_all(&frame(RST_STREAM, 0, 1, &[0, 0, 0, 1]))
+            .await
+            .unwrap();
+        let mut input = frame(DATA, END_STREAM, 3, b"");
+        // Frames of streams that ended are left to the connection, which rejects them.
+        for stream_id in [1, 3] {
+            input.extend_from_slice(&frame(
+                HEADERS,
+                0x4 | END_STREAM,
+                stream_id,
+                &[0x40, 1, b'a', 1, b'b'],
+            ));
+        }
+        client.write_all(&input).await.unwrap();
+        let n = io.read(&mut buf).await.unwrap();
+        assert_eq!(buf[..n], input);
+    }
+
+    #[tokio::test]
+    async fn passes_other_protocols_through() {
+        let input = b"GET / HTTP/1.1\r\n\r\n".to_vec();
+        let preface = PrefaceDone::default();
+        let watch = Watch {
+            preface: Some(preface.clone()),
+            max_header_frames_per_stream: Some(1),
+            ..Watch::default()
+        };
+
+        assert_eq!(read_through(input.clone(), watch, 3).await, input);
+        assert!(preface.get());
+        assert!(!preface.established());
+    }
+
+    #[test]
+    fn tracks_the_handshake() {
+        let settings = frame(SETTINGS, 0, 0, &[0, 3, 0, 0, 0, 100]);
+        let mut input = PREFACE.to_vec();
+        input.extend_from_slice(&settings);
+
+        let preface = PrefaceDone::default();
+        let mut reader = Reader::new(Some(preface.clone()), None, None);
+        for byte in &input[..input.len() - 1] {
+            reader.buf.extend_from_slice(std::slice::from_ref(byte));
+            reader.process();
+            assert!(!preface.get());
+        }
+        reader.buf.extend_from_slice(&input[input.len() - 1..]);
+        reader.process();
+        assert!(preface.established());
+        // Nothing else is watched, so the rest is passed through.
+        assert!(reader.done);
+        assert_eq!(reader.ready, reader.buf.len());
+    }
+
+    #[test]
+    fn handshake_fails_without_settings() {
+        let mut input = PREFACE.to_vec();
+        input.extend_from_slice(&frame(PING, 0, 0, &[0; 8]));
+
+        let preface = PrefaceDone::default();
+        let mut reader = Reader::new(Some(preface.clone()), None, None);
+        reader.buf.extend_from_slice(&input);
+        reader.process();
+        assert!(preface.get());
+        assert!(!preface.established());
+    }
+
+    #[test]
+    fn finds_go_away_frames_split_across_writes() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook: GoAwayHook = Arc::new({
+            let seen = seen.clone();
+            move |go_away: &GoAway| seen.lock().unwrap().push(go_away.clone())
+        });
+        let mut writer = Writer::new(None, Some(hook), None, false);
+
+        let ping = frame(PING, 0, 0, &[1, 2, 3, 4, 5, 6, 7, 8]);
+        let mut payload = 5u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&2u32.to_be_bytes());
+        payload.extend_from_slice(b"overloaded");
+        let go_away = frame(GOAWAY, 0, 0, &payload);
+        let (head, tail) = go_away.split_at(11);
+        for chunk in [&SETTINGS_FRAME[..], &ping, head, tail] {
+            writer.feed(chunk);
+        }
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].code(), 0x2);
+        assert_eq!(seen[0].last_stream_id(), 5);
+        assert_eq!(&seen[0].debug_data()[..], b"overloaded");
+    }
+
+    #[tokio::test]
+    async fn removes_the_acks_of_its_pings() {
+        let mut ack = PING_FRAME.to_vec();
+        ack[4] = ACK;
+        let other_ack = frame(PING, ACK, 0, &[1, 2, 3, 4, 5, 6, 7, 8]);
+        let headers = frame(HEADERS, 0x4 | END_STREAM, 1, &[0x83, 0x86, 0x84]);
+        let mut input = PREFACE.to_vec();
+        input.extend_from_slice(&SETTINGS_FRAME);
+        for frame in [&ack, &headers, &other_ack, &ack] {
+            input.extend_from_slice(frame);
+        }
+
+        // The acks of the pings `hyper` sends itself are kept.
+        let mut expected = PREFACE.to_vec();
+        expected.extend_from_slice(&SETTINGS_FRAME);
+        expected.extend_from_slice(&headers);
+        expected.extend_from_slice(&other_ack);
+
+        for read_len in [1, 5, 1024] {
+            let watch = Watch {
+                ping: Some(ConnectionPing::default()),
+                ..Watch::default()
+            };
+            let output = read_through(input.clone(), watch, read_len).await;
+            assert_eq!(output, expected, "reads of {read_len} bytes");
+        }
+    }
+
+    #[tokio::test]
+    async fn measures_the_rtt_of_pings_sent_by_the_connection() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let ping = ConnectionPing::default();
+        let mut io = wrap(
+            server,
+            Watch {
+                ping: Some(ping.clone()),
+                ..Watch::default()
+            },
+        );
+        let keepalive = frame(PING, 0, 0, &[1, 2, 3, 4, 5, 6, 7, 8]);
+        io.write_all(&SETTINGS_FRAME).await.unwrap();
+        io.write_all(&keepalive).await.unwrap();
+        assert_eq!(ping.last_rtt(), None);
+
+        let mut input = PREFACE.to_vec();
+        input.extend_from_slice(&SETTINGS_FRAME);
+        input.extend_from_slice(&frame(PING, ACK, 0, &[1, 2, 3, 4, 5, 6, 7, 8]));
+        client.write_all(&input).await.unwrap();
+        drop(client);
+
+        // The ack is still passed on to the connection, which is waiting for it.
+        let mut output = Vec::new();
+        io.read_to_end(&mut output).await.unwrap();
+        assert_eq!(output, input);
+        assert!(ping.last_rtt().is_some());
+    }
+
+    #[test]
+    fn writer_tracks_frame_boundaries() {
+        let mut writer = Writer::new(None, None, None, false);
+        assert!(!writer.at_boundary());
+        writer.feed(&SETTINGS_FRAME);
+        assert!(writer.at_boundary());
+        writer.feed(&[0, 0, 4, 0, 0]);
+        assert!(!writer.at_boundary());
+        writer.feed(&[0, 0, 0, 1, 1, 2]);
+        assert!(!writer.at_boundary());
+        writer.feed(&[3, 4]);
+        assert!(writer.at_boundary());
+    }
+
+    /// Accepts at most `max` bytes per write, splitting the frames written to it.
+    #[derive(Default)]
+    struct Trickle {
+        written: Vec<u8>,
+        max: usize,
+    }
+
+    impl AsyncWrite for Trickle {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.poll_write_vectored(cx, &[io::IoSlice::new(buf)])
+        }
+
+        fn poll_write_vectored(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            bufs: &[io::IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            let mut n = 0;
+            for buf in bufs {
+                let len = buf.len().min(self.max - n);
+                self.written.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+            Poll::Ready(Ok(n))
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            true
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_pings_in_between_frames_split_across_vectored_writes() {
+        let ping = ConnectionPing::default();
+        let mut io = match FramesIo::wrap(
+            Trickle {
+                max: 5,
+                ..Trickle::default()
+            },
+            Watch {
+                ping: Some(ping.clone()),
+                ..Watch::default()
+            },
+        ) {
+            Either::Right(io) => io,
+            Either::Left(_) => panic!("nothing to watch"),
+        };
+        io.reader.preface_len = PREFACE.len();
+        io.write_all(&SETTINGS_FRAME).await.unwrap();
+
+        let headers = frame(HEADERS, 0x4, 1, &[0x83, 0x86, 0x84]);
+        let data = frame(0x0, END_STREAM, 1, b"hello");
+        let frames = [
+            &headers[..FRAME_HEADER_LEN],
+            &headers[FRAME_HEADER_LEN..],
+            &data,
+        ];
+        let mut bufs: Vec<_> = frames.iter().map(|frame| io::IoSlice::new(frame)).collect();
+        let mut bufs = &mut bufs[..];
+
+        // The ping is requested in the middle of the `HEADERS` frame.
+        let n = io.write_vectored(bufs).await.unwrap();
+        io::IoSlice::advance_slices(&mut bufs, n);
+        tokio::spawn({
+            let ping = ping.clone();
+            async move { ping.ping().await }
+        });
+        tokio::task::yield_now().await;
+        while !bufs.is_empty() {
+            let n = io.write_vectored(bufs).await.unwrap();
+            io::IoSlice::advance_slices(&mut bufs, n);
+        }
+
+        let mut expected = SETTINGS_FRAME.to_vec();
+        expected.extend_from_slice(&headers);
+        expected.extend_from_slice(&PING_FRAME);
+        expected.extend_from_slice(&data);
+        assert_eq!(io.inner.written, expected);
+    }
+
+    #[tokio::test]
+    async fn rewrites_go_away_frames_split_across_writes() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut io = match FramesIo::wrap(
+            Trickle {
+                max: 5,
+                ..Trickle::default()
+            },
+            Watch {
+                on_goaway_sent: Some(Arc::new({
+                    let seen = seen.clone();
+                    move |go_away: &GoAway| seen.lock().unwrap().push(go_away.clone())
+                })),
+                rewrite_goaway: Some(Arc::new(|go_away: GoAway| {
+                    go_away
+                        .with_code(GoAway::ENHANCE_YOUR_CALM)
+                        .with_debug_data("slow down")
+                })),
+                ..Watch::default()
+            },
+        ) {
+            Either::Right(io) => io,
+            Either::Left(_) => panic!("nothing to watch"),
+        };
+        io.write_all(&SETTINGS_FRAME).await.unwrap();
+
+        let data = frame(0x0, 0, 1, b"hello");
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&0u32.to_be_bytes());
+        let go_away = frame(GOAWAY, 0, 0, &payload);
+        let ping = frame(PING, 0, 0, &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // Frames written at once, and a `GOAWAY` frame split across vectored writes.
+        let mut frames = data.clone();
+        frames.extend_from_slice(&go_away);
+        io.write_all(&frames).await.unwrap();
+        let (head, tail) = go_away.split_at(6);
+        let slices = [&data[..], head, tail, &ping];
+        let mut bufs: Vec<_> = slices.iter().map(|buf| io::IoSlice::new(buf)).collect();
+        let mut bufs = &mut bufs[..];
+        while !bufs.is_empty() {
+            let n = io.write_vectored(bufs).await.unwrap();
+            io::IoSlice::advance_slices(&mut bufs, n);
+        }
+        io.flush().await.unwrap();
+
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&GoAway::ENHANCE_YOUR_CALM.to_be_bytes());
+        payload.extend_from_slice(b"slow down");
+        let rewritten = frame(GOAWAY, 0, 0, &payload);
+        let mut expected = SETTINGS_FRAME.to_vec();
+        for frame in [&data, &rewritten, &data, &rewritten, &ping] {
+            expected.extend_from_slice(frame);
+        }
+        assert_eq!(io.inner.written, expected);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].code(), GoAway::ENHANCE_YOUR_CALM);
+        assert_eq!(&seen[0].debug_data()[..], b"slow down");
+    }
+
+    /// Serves a [`FramesIo`] watching pings the way a connection would, sending its `SETTINGS`
+    /// and reading it until it closes.
+    fn serve_pings(io: DuplexStream) -> ConnectionPing {
+        let ping = ConnectionPing::default();
+        let mut io = wrap(
+            io,
+            Watch {
+                ping: Some(ping.clone()),
+                ..Watch::default()
+            },
+        );
+        tokio::spawn(async move {
+            io.write_all(&SETTINGS_FRAME).await.unwrap();
+            let mut buf = [0; 1024];
+            while io.read(&mut buf).await.is_ok_and(|n| n > 0) {}
+        });
+        ping
+    }
+
+    #[tokio::test]
+    async fn ping_is_acked_by_a_live_client() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let ping = serve_pings(server);
+        client.write_all(PREFACE).await.unwrap();
+
+        tokio::spawn(async move {
+            let mut settings = [0; SETTINGS_FRAME.len()];
+            client.read_exact(&mut settings).await.unwrap();
+            let mut frame = [0; PING_FRAME.len()];
+            client.read_exact(&mut frame).await.unwrap();
+            assert_eq!(frame, PING_FRAME);
+            frame[4] = ACK;
+            client.write_all(&frame).await.unwrap();
+            // Keep the connection open.
+            std::future::pending::<()>().await;
+        });
+
+        let rtt = tokio::time::timeout(Duration::from_secs(1), ping.ping()).await;
+        assert!(rtt.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn ping_times_out_on_a_stalled_client() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let ping = serve_pings(server);
+        client.write_all(PREFACE).await.unwrap();
+
+        let rtt = tokio::time::timeout(Duration::from_millis(50), ping.ping()).await;
+        assert!(rtt.is_err());
+        drop(client);
+    }
+
+    #[tokio::test]
+    async fn ping_fails_on_http1() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let ping = serve_pings(server);
+        client
+            .write_all(b"POST /test.Test/UnaryCall HTTP/1.1\r\n")
+            .await
+            .unwrap();
+
+        let rtt = tokio::time::timeout(Duration::from_secs(1), ping.ping()).await;
+        assert_eq!(rtt.unwrap(), None);
+    }
+}
@@ -0,0 +1,454 @@
+use super::preface::PREFACE;
+use crate::body::Body;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::{header, HeaderValue, Request, Response, StatusCode, Version};
+use http_body_util::BodyExt;
+use hyper::{
+    body::Incoming,
+    upgrade::{OnUpgrade, Upgraded},
+};
+use hyper_util::rt::TokioIo;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tower_service::Service;
+use tracing::debug;
+
+const FRAME_HEADER_LEN: usize = 9;
+const DATA: u8 = 0x0;
+const HEADERS: u8 = 0x1;
+const SETTINGS: u8 = 0x4;
+const END_STREAM: u8 = 0x1;
+const END_HEADERS: u8 = 0x4;
+/// The smallest `SETTINGS_MAX_FRAME_SIZE` HTTP/2 allows, so frames up to this size are accepted
+/// whatever the server advertises.
+const MAX_FRAME_LEN: usize = 16 * 1024;
+/// The headers of HTTP/1.1 that are specific to the connection, which HTTP/2 doesn't allow.
+const CONNECTION_HEADERS: [&str; 7] = [
+    "connection",
+    "upgrade",
+    "http2-settings",
+    "host",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+];
+
+/// The response to clients of an h2c server that don't speak HTTP/2, see [`Server::h2c`].
+///
+/// [`Server::h2c`]: super::Server::h2c
+const REJECTION: &[u8] = b"HTTP/1.1 505 HTTP Version Not Supported\r\n\
+    connection: close\r\n\
+    content-type: text/plain\r\n\
+    content-length: 53\r\n\
+    \r\n\
+    this server only accepts HTTP/2 with prior knowledge\n";
+
+/// Reads the connection preface a client of an h2c server starts with, see [`Server::h2c`].
+///
+/// Clients that send anything else, like HTTP/1.1 requests, including ones asking to upgrade to
+/// HTTP/2, get a `505 HTTP Version Not Supported` response before the connection is closed, and
+/// `None` is returned. Otherwise the preface is put back for the HTTP/2 connection to read.
+///
+/// [`Server::h2c`]: super::Server::h2c
+pub(crate) async fn accept<IO>(mut io: IO) -> Option<Rewind<IO>>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut head = [0; PREFACE.len()];
+    let mut len = 0;
+    while len < head.len() {
+        match io.read(&mut head[len..]).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => len += n,
+        }
+
+        if head[..len] != PREFACE[..len] {
+            debug!("client is not using HTTP/2 with prior knowledge, rejecting its connection");
+            if io.write_all(REJECTION).await.is_ok() {
+                let _ = io.shutdown().await;
+            }
+            return None;
+        }
+    }
+
+    Some(Rewind {
+        inner: io,
+        pending: Bytes::from_static(PREFACE),
+    })
+}
+
+/// Answers the HTTP/1.1 requests that ask to upgrade their connection to h2c, see
+/// [`Server::accept_http1`].
+///
+/// Such a request gets a `101 Switching Protocols` response, and is kept as the `HEADERS` and
+/// `DATA` frames of stream 1 in the [`PendingUpgrade`] of the connection, which is then served
+/// as HTTP/2, replaying them once the client sent its connection preface, see [`upgraded`].
+/// Requests whose headers or body don't fit in a frame of the smallest size HTTP/2 allows, or
+/// whose body is chunked, are served over HTTP/1.1, like the ones that don't ask to upgrade.
+///
+/// [`Server::accept_http1`]: super::Server::accept_http1
+#[derive(Clone)]
+pub(crate) struct UpgradeH2c<S> {
+    inner: S,
+    pending: Option<PendingUpgrade>,
+}
+
+impl<S> UpgradeH2c<S> {
+    /// Upgrades the requests of the connection of `pending`, if any.
+    pub(crate) fn new(inner: S, pending: Option<PendingUpgrade>) -> Self {
+        Self { inner, pending }
+    }
+}
+
+impl<S> Service<Request<Incoming>> for UpgradeH2c<S>
+where
+    S: Service<Request<Incoming>, Response = Response<Body>>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = UpgradeFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Incoming>) -> Self::Future {
+        let Some((pending, headers)) = self.pending.as_ref().and_then(|pending| {
+            let headers = asks_to_upgrade(&req)
+                .then(|| replay_headers(&req))
+                .flatten()?;
+            Some((pending.clone(), headers))
+        }) else {
+            return UpgradeFuture::Inner(self.inner.call(req));
+        };
+
+        let on_upgrade = hyper::upgrade::on(&mut req);
+        UpgradeFuture::Upgrade(Box::pin(async move {
+            let body = match req.into_body().collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(err) => {
+                    debug!("failed reading the request upgrading to h2c: {}", err);
+                    let mut response = Response::new(Body::empty());
+                    *response.status_mut() = StatusCode::BAD_REQUEST;
+                    return response;
+                }
+            };
+
+            let mut replay = BytesMut::new();
+            if body.is_empty() {
+                put_frame(&mut replay, HEADERS, END_HEADERS | END_STREAM, &headers);
+            } else {
+                put_frame(&mut replay, HEADERS, END_HEADERS, &headers);
+                put_frame(&mut replay, DATA, END_STREAM, &body);
+            }
+            *pending.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(Upgrade {
+                on_upgrade,
+                replay: replay.freeze(),
+            });
+
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+            let headers = response.headers_mut();
+            headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+            headers.insert(header::UPGRADE, HeaderValue::from_static("h2c"));
+            response
+        }))
+    }
+}
+
+/// The future of an [`UpgradeH2c`].
+#[pin_project(project = UpgradeFutureProj)]
+pub(crate) enum UpgradeFuture<F> {
+    Inner(#[pin] F),
+    Upgrade(Pin<Box<dyn Future<Output = Response<Body>> + Send>>),
+}
+
+impl<F, E> Future for UpgradeFuture<F>
+where
+    F: Future<Output = Result<Response<Body>, E>>,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            UpgradeFutureProj::Inner(future) => future.poll(cx),
+            UpgradeFutureProj::Upgrade(future) => future.as_mut().poll(cx).map(Ok),
+        }
+    }
+}
+
+/// The request of a connection that asked to upgrade it to h2c, set by an [`UpgradeH2c`].
+#[derive(Clone, Default)]
+pub(crate) struct PendingUpgrade(Arc<Mutex<Option<Upgrade>>>);
+
+impl PendingUpgrade {
+    /// Takes the upgrade of the connection, once its HTTP/1.1 side is done.
+    pub(crate) fn take(&self) -> Option<Upgrade> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+/// A connection being upgraded to h2c, and the frames replaying the request that asked for it.
+pub(crate) struct Upgrade {
+    on_upgrade: OnUpgrade,
+    replay: Bytes,
+}
+
+/// Takes over a connection upgraded to h2c by an [`UpgradeH2c`].
+///
+/// Reads the client's connection preface and the `SETTINGS` frame that must follow it, and
+/// returns the connection yielding them, followed by the frames replaying the request that
+/// asked for the upgrade. Returns `None` if the client sends anything else.
+pub(crate) async fn upgraded(upgrade: Upgrade) -> Option<Rewind<TokioIo<Upgraded>>> {
+    let mut io = match upgrade.on_upgrade.await {
+        Ok(io) => TokioIo::new(io),
+        Err(err) => {
+            debug!("failed upgrading the connection to h2c: {}", err);
+            return None;
+        }
+    };
+
+    let mut head = BytesMut::zeroed(PREFACE.len() + FRAME_HEADER_LEN);
+    io.read_exact(&mut head).await.ok()?;
+    let header = &head[PREFACE.len()..];
+    let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+    if !head.starts_with(PREFACE) || header[3] != SETTINGS || len > MAX_FRAME_LEN {
+        debug!("client did not send its HTTP/2 connection preface after upgrading to h2c");
+        return None;
+    }
+
+    let settings = head.len();
+    head.resize(settings + len, 0);
+    io.read_exact(&mut head[settings..]).await.ok()?;
+    head.extend_from_slice(&upgrade.replay);
+
+    Some(Rewind {
+        inner: io,
+        pending: head.freeze(),
+    })
+}
+
+/// Whether `req` asks to upgrade its HTTP/1.1 connection to h2c, with a body that fits in a
+/// `DATA` frame, see [RFC 7540, section 3.2].
+///
+/// [RFC 7540, section 3.2]: https://www.rfc-editor.org/rfc/rfc7540#section-3.2
+fn asks_to_upgrade(req: &Request<Incoming>) -> bool {
+    let has_token = |name, token: &str| {
+        req.headers().get_all(name).iter().any(|value| {
+            value.to_str().is_ok_and(|value| {
+                value
+                    .split(',')
+                    .any(|t| t.trim().eq_ignore_ascii_case(token))
+            })
+        })
+    };
+    let body_fits = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .map_or(Some(0), |len| len.to_str().ok()?.parse::<usize>().ok())
+        .is_some_and(|len| len <= MAX_FRAME_LEN);
+
+    req.version() == Version::HTTP_11
+        && has_token(header::UPGRADE, "h2c")
+        && has_token(header::CONNECTION, "upgrade")
+        && has_token(header::CONNECTION, "http2-settings")
+        && req.headers().get_all("http2-settings").iter().count() == 1
+        && !req.headers().contains_key(header::TRANSFER_ENCODING)
+        && body_fits
+}
+
+/// Encodes the headers of `req` as the header block of an HTTP/2 `HEADERS` frame, without the
+/// ones specific to the HTTP/1.1 connection, or `None` if they don't fit in a frame.
+///
+/// The fields are encoded as literals that are not added to the dynamic table, so the header
+/// compression state of the connection is left as it starts.
+fn replay_headers(req: &Request<Incoming>) -> Option<Bytes> {
+    let mut block = BytesMut::new();
+    let path = req.uri().path_and_query().map_or("/", |path| path.as_str());
+    put_field(&mut block, b":method", req.method().as_str().as_bytes());
+    put_field(&mut block, b":scheme", b"http");
+    put_field(&mut block, b":path", path.as_bytes());
+    if let Some(host) = req.headers().get(header::HOST) {
+        put_field(&mut block, b":authority", host.as_bytes());
+    }
+
+    let nominated = req
+        .headers()
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    for (name, value) in req.headers() {
+        let name = name.as_str();
+        if CONNECTION_HEADERS.contains(&name)
+            || nominated.iter().any(|nominated| nominated == name)
+            || (name == "te" && value != "trailers")
+        {
+            continue;
+        }
+        put_field(&mut block, name.as_bytes(), value.as_bytes());
+    }
+
+    (block.len() <= MAX_FRAME_LEN).then(|| block.freeze())
+}
+
+/// Encodes a literal header field without indexing, see [RFC 7541, section 6.2.2].
+///
+/// [RFC 7541, section 6.2.2]: https://www.rfc-editor.org/rfc/rfc7541#section-6.2.2
+fn put_field(block: &mut BytesMut, name: &[u8], value: &[u8]) {
+    block.put_u8(0);
+    for string in [name, value] {
+        // Strings are not Huffman encoded.
+        put_int(block, 7, string.len());
+        block.put_slice(string);
+    }
+}
+
+/// Encodes an integer with a prefix of `prefix_bits`, see [RFC 7541, section 5.1].
+///
+/// [RFC 7541, section 5.1]: https://www.rfc-editor.org/rfc/rfc7541#section-5.1
+fn put_int(block: &mut BytesMut, prefix_bits: u32, mut value: usize) {
+    let max = (1 << prefix_bits) - 1;
+    if value < max {
+        block.put_u8(value as u8);
+        return;
+    }
+
+    block.put_u8(max as u8);
+    value -= max;
+    while value >= 0x80 {
+        block.put_u8((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    block.put_u8(value as u8);
+}
+
+fn put_frame(dst: &mut BytesMut, kind: u8, flags: u8, payload: &[u8]) {
+    dst.put_slice(&(payload.len() as u32).to_be_bytes()[1..]);
+    dst.put_u8(kind);
+    dst.put_u8(flags);
+    // Stream 1, which the request upgrading the connection is on.
+    dst.put_u32(1);
+    dst.put_slice(payload);
+}
+
+/// A connection that first yields bytes read from it before, e.g. by [`accept`].
+#[pin_project]
+pub(crate) struct Rewind<IO> {
+    #[pin]
+    inner: IO,
+    pending: Bytes,
+}
+
+impl<IO> Rewind<IO> {
+    /// A connection with nothing to put back.
+    pub(crate) fn new(inner: IO) -> Self {
+        Self {
+            inner,
+            pending: Bytes::new(),
+        }
+    }
+}
+
+impl<IO: AsyncRead> AsyncRead for Rewind<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        if !this.pending.is_empty() {
+            let n = this.pending.len().min(buf.remaining());
+            buf.put_slice(&this.pending[..n]);
+            this.pending.advance(n);
+            return Poll::Ready(Ok(()));
+        }
+        this.inner.poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for Rewind<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn accepts_prior_knowledge() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        client.write_all(PREFACE).await.unwrap();
+        client.write_all(b"settings").await.unwrap();
+
+        let mut io = accept(server).await.unwrap();
+        let mut read = vec![0; PREFACE.len() + 8];
+        io.read_exact(&mut read).await.unwrap();
+        assert_eq!(&read[..PREFACE.len()], PREFACE);
+        assert_eq!(&read[PREFACE.len()..], b"settings");
+    }
+
+    #[test]
+    fn encodes_integers_with_a_prefix() {
+        // The examples of RFC 7541, appendix C.1.
+        let mut block = BytesMut::new();
+        put_int(&mut block, 5, 10);
+        assert_eq!(&block[..], [10]);
+
+        let mut block = BytesMut::new();
+        put_int(&mut block, 5, 1337);
+        assert_eq!(&block[..], [31, 154, 10]);
+    }
+
+    #[tokio::test]
+    async fn rejects_http1() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        client
+            .write_all(b"POST /test.Test/UnaryCall HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+
+        assert!(accept(server).await.is_none());
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 505 HTTP Version Not Supported\r\n"));
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(response.contains(&format!("content-length: {}\r\n", body.len())));
+    }
+}
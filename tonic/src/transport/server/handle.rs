@@ -0,0 +1,69 @@
+use super::ShutdownReport;
+use crate::transport::Error;
+use std::{net::SocketAddr, panic};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// A server running on its own task, returned by [`Router::spawn`].
+///
+/// The handle lets an application supervise the server along with its other tasks: it tells
+/// the address the server is listening on, shuts it down gracefully and waits for it to stop.
+/// Dropping the handle detaches the server, which then runs until the runtime shuts down.
+///
+/// [`Router::spawn`]: super::Router::spawn
+#[derive(Debug)]
+pub struct ServerHandle {
+    local_addr: SocketAddr,
+    shutdown: CancellationToken,
+    task: JoinHandle<Result<ShutdownReport, Error>>,
+}
+
+impl ServerHandle {
+    pub(crate) fn new(
+        local_addr: SocketAddr,
+        shutdown: CancellationToken,
+        task: JoinHandle<Result<ShutdownReport, Error>>,
+    ) -> Self {
+        Self {
+            local_addr,
+            shutdown,
+            task,
+        }
+    }
+
+    /// The address the server is listening on, with the port picked by the OS if it was
+    /// spawned on port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Starts a graceful shutdown of the server, as the signal of
+    /// [`Router::serve_with_shutdown`] does.
+    ///
+    /// This returns right away, use [`ServerHandle::await_shutdown`] to wait until the server
+    /// stopped. Calling it again has no effect.
+    ///
+    /// [`Router::serve_with_shutdown`]: super::Router::serve_with_shutdown
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// Returns `true` if the server stopped, after a shutdown or because it failed.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Waits until the server stopped, returning the [`ShutdownReport`] of its graceful
+    /// shutdown, or the error it failed with.
+    ///
+    /// This does not start a shutdown by itself, so it only returns once
+    /// [`ServerHandle::shutdown`] was called or the server failed. A panic of the server task
+    /// is resumed.
+    pub async fn await_shutdown(self) -> Result<ShutdownReport, Error> {
+        match self.task.await {
+            Ok(result) => result,
+            Err(error) if error.is_panic() => panic::resume_unwind(error.into_panic()),
+            Err(error) => Err(Error::new_serve(error)),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+/// The largest window size HTTP/2 allows, see [RFC 9113, section 6.9.1].
+///
+/// [RFC 9113, section 6.9.1]: https://www.rfc-editor.org/rfc/rfc9113#section-6.9.1
+const MAX_WINDOW_SIZE: u32 = (1 << 31) - 1;
+const MIN_FRAME_SIZE: u32 = 1 << 14;
+const MAX_FRAME_SIZE: u32 = (1 << 24) - 1;
+
+/// The HTTP/2 settings a server sends to every client, see [`Server::http2_settings`].
+///
+/// Unlike the individual HTTP/2 options of [`Server`], which leave what they don't set to the
+/// defaults of tonic and `hyper`, these settings are fully specified: every value is advertised
+/// as set here. A new value starts out with the initial values of [RFC 9113, section 6.5.2],
+/// except for the maximum header list size, which HTTP/2 leaves unlimited and which starts out
+/// at 16 KiB, and no limit on concurrent streams.
+///
+/// The setters panic on values HTTP/2 doesn't allow, so invalid settings are never sent.
+///
+/// # Example
+///
+/// ```
+/// # use tonic::transport::server::Http2Settings;
+/// let settings = Http2Settings::new()
+///     .max_concurrent_streams(100)
+///     .initial_window_size(1024 * 1024)
+///     .max_frame_size(64 * 1024);
+/// ```
+///
+/// [`Server::http2_settings`]: super::Server::http2_settings
+/// [`Server`]: super::Server
+/// [RFC 9113, section 6.5.2]: https://www.rfc-editor.org/rfc/rfc9113#section-6.5.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Http2Settings {
+    pub(crate) header_table_size: u32,
+    pub(crate) max_concurrent_streams: Option<u32>,
+    pub(crate) initial_window_size: u32,
+    pub(crate) initial_connection_window_size: u32,
+    pub(crate) max_frame_size: u32,
+    pub(crate) max_header_list_size: u32,
+    pub(crate) enable_connect_protocol: bool,
+}
+
+impl Default for Http2Settings {
+    fn default() -> Self {
+        Self {
+            header_table_size: 4096,
+            max_concurrent_streams: None,
+            initial_window_size: 65_535,
+            initial_connection_window_size: 65_535,
+            max_frame_size: MIN_FRAME_SIZE,
+            max_header_list_size: 16 * 1024,
+            enable_connect_protocol: false,
+        }
+    }
+}
+
+impl Http2Settings {
+    /// Create settings with the initial values of HTTP/2.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `SETTINGS_HEADER_TABLE_SIZE`, the size of the HPACK dynamic table used to decode
+    /// request headers, in octets.
+    ///
+    /// Only applies to HTTP/2-only servers, it is ignored when
+    /// [`Server::accept_http1`](super::Server::accept_http1) is enabled.
+    #[must_use]
+    pub fn header_table_size(self, size: u32) -> Self {
+        Self {
+            header_table_size: size,
+            ..self
+        }
+    }
+
+    /// Sets `SETTINGS_MAX_CONCURRENT_STREAMS`, the number of streams a client may have open at
+    /// once. `None`, the default, doesn't send the setting, which leaves it unlimited.
+    #[must_use]
+    pub fn max_concurrent_streams(self, max: impl Into<Option<u32>>) -> Self {
+        Self {
+            max_concurrent_streams: max.into(),
+            ..self
+        }
+    }
+
+    /// Sets `SETTINGS_INITIAL_WINDOW_SIZE`, the flow control window of every stream, in octets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is larger than 2^31 - 1.
+    #[must_use]
+    pub fn initial_window_size(self, size: u32) -> Self {
+        assert!(
+            size <= MAX_WINDOW_SIZE,
+            "initial window size must be at most 2^31 - 1, got {size}"
+        );
+        Self {
+            initial_window_size: size,
+            ..self
+        }
+    }
+
+    /// Sets the flow control window of the connection, in octets.
+    ///
+    /// This isn't a setting, the window grows from its initial 65,535 octets through a
+    /// `WINDOW_UPDATE` frame sent along with the settings. Smaller windows are not advertised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is larger than 2^31 - 1.
+    #[must_use]
+    pub fn initial_connection_window_size(self, size: u32) -> Self {
+        assert!(
+            size <= MAX_WINDOW_SIZE,
+            "initial connection window size must be at most 2^31 - 1, got {size}"
+        );
+        Self {
+            initial_connection_window_size: size,
+            ..self
+        }
+    }
+
+    /// Sets `SETTINGS_MAX_FRAME_SIZE`, the largest frame payload the server accepts, in octets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is smaller than 16 KiB or larger than 16 MiB - 1.
+    #[must_use]
+    pub fn max_frame_size(self, size: u32) -> Self {
+        assert!(
+            (MIN_FRAME_SIZE..=MAX_FRAME_SIZE).contains(&size),
+            "max frame size must be between 2^14 and 2^24 - 1, got {size}"
+        );
+        Self {
+            max_frame_size: size,
+            ..self
+        }
+    }
+
+    /// Sets `SETTINGS_MAX_HEADER_LIST_SIZE`, the largest header list the server accepts, in
+    /// octets.
+    #[must_use]
+    pub fn max_header_list_size(self, size: u32) -> Self {
+        Self {
+            max_header_list_size: size,
+            ..self
+        }
+    }
+
+    /// Sets `SETTINGS_ENABLE_CONNECT_PROTOCOL`, which allows clients to bootstrap other
+    /// protocols over a stream with extended `CONNECT` requests, see [RFC 8441].
+    ///
+    /// [RFC 8441]: https://www.rfc-editor.org/rfc/rfc8441
+    #[must_use]
+    pub fn enable_connect_protocol(self, enabled: bool) -> Self {
+        Self {
+            enable_connect_protocol: enabled,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "max frame size must be between 2^14 and 2^24 - 1, got 1024")]
+    fn rejects_too_small_frames() {
+        let _ = Http2Settings::new().max_frame_size(1024);
+    }
+
+    #[test]
+    #[should_panic(expected = "initial window size must be at most 2^31 - 1")]
+    fn rejects_too_large_windows() {
+        let _ = Http2Settings::new().initial_window_size(1 << 31);
+    }
+}
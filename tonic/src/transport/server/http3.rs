@@ -0,0 +1,307 @@
+use super::{service::RecoverError, Router, TcpConnectInfo};
+use crate::{
+    body::Body,
+    service::{router, Routes},
+    transport::service::{Executor, GrpcTimeout, SharedExec},
+};
+use bytes::{Buf, Bytes};
+use h3::server::{RequestResolver, RequestStream};
+use http::{HeaderMap, Request, Response};
+use http_body::Frame;
+use http_body_util::BodyExt;
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+    task::{ready, Context, Poll},
+};
+use tokio::sync::watch;
+use tower::{Layer, Service, ServiceExt};
+use tracing::{debug, trace};
+
+type H3Connection = h3_quinn::Connection;
+
+impl<L> Router<L> {
+    /// Consume this [`Router`] and serve gRPC over HTTP/3 on the connections of a QUIC
+    /// `endpoint`.
+    ///
+    /// The endpoint is configured by the caller, including its TLS configuration, which must
+    /// advertise `h3` through ALPN. The `quinn` version must match the one of tonic.
+    ///
+    /// Requests go through the layers and routes of this router, [`Server::timeout`] and
+    /// [`Server::default_timeout`] apply to them, and connections and requests are spawned on
+    /// the [`Server::executor`]. The other options of the builder configure TCP and HTTP/2
+    /// connections and are ignored.
+    ///
+    /// [`Server::timeout`]: super::Server::timeout
+    /// [`Server::default_timeout`]: super::Server::default_timeout
+    /// [`Server::executor`]: super::Server::executor
+    pub async fn serve_http3<ResBody>(
+        self,
+        endpoint: quinn::Endpoint,
+    ) -> Result<(), super::super::Error>
+    where
+        L: Layer<Routes>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Error:
+            Into<crate::BoxError> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        self.serve_http3_with_shutdown(endpoint, std::future::pending())
+            .await
+    }
+
+    /// Consume this [`Router`] and serve gRPC over HTTP/3, like [`Router::serve_http3`], until
+    /// `signal` resolves.
+    ///
+    /// Once it does, the connections are sent a `GOAWAY` frame and the server waits for their
+    /// in-flight requests to complete.
+    pub async fn serve_http3_with_shutdown<F, ResBody>(
+        self,
+        endpoint: quinn::Endpoint,
+        signal: F,
+    ) -> Result<(), super::super::Error>
+    where
+        F: Future<Output = ()>,
+        L: Layer<Routes>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Error:
+            Into<crate::BoxError> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        let (server, routes) = self.into_parts();
+        let timeout = server.timeout;
+        let default_timeout = server.default_timeout;
+        let error_mappers = server.error_mappers;
+        let executor = server.executor;
+        let resolve_path = server.resolve_path;
+        let svc = server.service_builder.service(routes);
+        let svc = RecoverError::new(
+            GrpcTimeout::new(svc, timeout, default_timeout),
+            error_mappers,
+        )
+        .map_request(move |mut request: Request<Body>| {
+            if let Some(resolver) = &resolve_path {
+                router::resolve_path(resolver, request.uri_mut());
+            }
+            request
+        });
+
+        let (signal_tx, signal_rx) = watch::channel(());
+        let mut signal = pin!(signal);
+
+        loop {
+            let incoming = tokio::select! {
+                _ = &mut signal => {
+                    trace!("signal received, shutting down");
+                    break;
+                },
+                incoming = endpoint.accept() => incoming,
+            };
+            let Some(incoming) = incoming else {
+                break;
+            };
+
+            trace!("connection accepted");
+            executor.execute(serve_connection(
+                incoming,
+                svc.clone(),
+                signal_rx.clone(),
+                executor.clone(),
+            ));
+        }
+
+        // Stop taking new connections, then drain the ones that are open.
+        endpoint.set_server_config(None);
+        let _ = signal_tx.send(());
+        drop(signal_rx);
+        trace!(
+            "waiting for {} connections to close",
+            signal_tx.receiver_count()
+        );
+        signal_tx.closed().await;
+
+        Ok(())
+    }
+}
+
+async fn serve_connection<S, ResBody>(
+    incoming: quinn::Incoming,
+    svc: S,
+    mut signal: watch::Receiver<()>,
+    executor: SharedExec,
+) where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::BoxError> + Send,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<crate::BoxError>,
+{
+    let connection = match incoming.await {
+        Ok(connection) => connection,
+        Err(err) => {
+            debug!("failed to establish QUIC connection: {}", err);
+            return;
+        }
+    };
+    let connect_info = TcpConnectInfo {
+        local_addr: None,
+        remote_addr: Some(connection.remote_address()),
+    };
+
+    let mut connection =
+        match h3::server::Connection::<_, Bytes>::new(H3Connection::new(connection)).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                debug!("failed to establish HTTP/3 connection: {}", err);
+                return;
+            }
+        };
+
+    let mut shutting_down = false;
+    loop {
+        let accepted = tokio::select! {
+            accepted = connection.accept() => Some(accepted),
+            _ = signal.changed(), if !shutting_down => None,
+        };
+
+        match accepted {
+            // Tell the client to go away, `accept` returns `None` once its requests are done.
+            None => {
+                shutting_down = true;
+                if let Err(err) = connection.shutdown(0).await {
+                    debug!("failed to shut down HTTP/3 connection: {}", err);
+                    break;
+                }
+            }
+            Some(Ok(Some(resolver))) => {
+                executor.execute(serve_request(resolver, svc.clone(), connect_info.clone()));
+            }
+            Some(Ok(None)) => break,
+            Some(Err(err)) => {
+                debug!("HTTP/3 connection error: {}", err);
+                break;
+            }
+        }
+    }
+}
+
+async fn serve_request<S, ResBody>(
+    resolver: RequestResolver<H3Connection, Bytes>,
+    svc: S,
+    connect_info: TcpConnectInfo,
+) where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Send,
+    S::Future: Send,
+    S::Error: Into<crate::BoxError>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<crate::BoxError>,
+{
+    let (request, stream) = match resolver.resolve_request().await {
+        Ok(request) => request,
+        Err(err) => {
+            debug!("failed to receive HTTP/3 request: {}", err);
+            return;
+        }
+    };
+    let (mut send, recv) = stream.split();
+
+    let mut request = request.map(|()| Body::new(RecvBody::new(recv)));
+    request.extensions_mut().insert(connect_info);
+
+    let response = match svc.oneshot(request).await {
+        Ok(response) => response.map(Body::new),
+        Err(err) => {
+            debug!("failed to serve HTTP/3 request: {}", err.into());
+            send.stop_stream(h3::error::Code::H3_INTERNAL_ERROR);
+            return;
+        }
+    };
+
+    if let Err(err) = send_response(&mut send, response).await {
+        debug!("failed to send HTTP/3 response: {}", err);
+    }
+}
+
+async fn send_response(
+    send: &mut RequestStream<h3_quinn::SendStream<Bytes>, Bytes>,
+    response: Response<Body>,
+) -> Result<(), crate::BoxError> {
+    let (parts, body) = response.into_parts();
+    send.send_response(Response::from_parts(parts, ())).await?;
+
+    let mut body = pin!(body);
+    while let Some(frame) = body.frame().await {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                send.stop_stream(h3::error::Code::H3_INTERNAL_ERROR);
+                return Err(err.into());
+            }
+        };
+        match frame.into_data() {
+            Ok(data) => send.send_data(data).await?,
+            Err(frame) => {
+                if let Ok(trailers) = frame.into_trailers() {
+                    send.send_trailers(trailers).await?;
+                }
+            }
+        }
+    }
+
+    send.finish().await?;
+    Ok(())
+}
+
+/// The body of a request received over HTTP/3.
+struct RecvBody {
+    stream: RequestStream<h3_quinn::RecvStream, Bytes>,
+    data_done: bool,
+    done: bool,
+}
+
+impl RecvBody {
+    fn new(stream: RequestStream<h3_quinn::RecvStream, Bytes>) -> Self {
+        Self {
+            stream,
+            data_done: false,
+            done: false,
+        }
+    }
+}
+
+impl http_body::Body for RecvBody {
+    type Data = Bytes;
+    type Error = h3::error::StreamError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+
+        if !this.data_done {
+            match ready!(this.stream.poll_recv_data(cx))? {
+                Some(mut data) => {
+                    let data = data.copy_to_bytes(data.remaining());
+                    return Poll::Ready(Some(Ok(Frame::data(data))));
+                }
+                None => this.data_done = true,
+            }
+        }
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let trailers: Option<HeaderMap> = ready!(this.stream.poll_recv_trailers(cx))?;
+        this.done = true;
+        Poll::Ready(trailers.map(|trailers| Ok(Frame::trailers(trailers))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
@@ -0,0 +1,111 @@
+use tokio_rustls::rustls::pki_types::CertificateDer;
+use x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer};
+
+/// Identity of a TLS client, parsed from the certificate it presented.
+///
+/// This type will be accessible through [request extensions][ext] for TLS connections where
+/// the client sent a certificate, which requires client authentication to be configured through
+/// [`ServerTlsConfig::client_ca_root`].
+///
+/// The identity is inserted before the request reaches any layer added with [`Server::layer`],
+/// so interceptors can authorize requests based on the client certificate before the handler
+/// runs:
+///
+/// ```
+/// # use tonic::{transport::server::PeerIdentity, Request, Status};
+/// fn authorize(request: Request<()>) -> Result<Request<()>, Status> {
+///     match request.extensions().get::<PeerIdentity>() {
+///         Some(identity) if identity.common_name() == Some("billing") => Ok(request),
+///         Some(_) => Err(Status::permission_denied("client not allowed")),
+///         None => Err(Status::unauthenticated("no client certificate")),
+///     }
+/// }
+/// ```
+///
+/// [ext]: crate::Request::extensions
+/// [`ServerTlsConfig::client_ca_root`]: super::ServerTlsConfig::client_ca_root
+/// [`Server::layer`]: super::Server::layer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerIdentity {
+    common_name: Option<String>,
+    dns_names: Vec<String>,
+    uris: Vec<String>,
+}
+
+impl PeerIdentity {
+    /// Parse the identity out of a DER encoded certificate.
+    ///
+    /// Returns `None` if the certificate can't be parsed.
+    pub fn from_certificate(cert: &CertificateDer<'_>) -> Option<Self> {
+        let (_, cert) = X509Certificate::from_der(cert.as_ref()).ok()?;
+
+        let common_name = cert
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_owned);
+
+        let mut identity = PeerIdentity {
+            common_name,
+            ..Default::default()
+        };
+
+        if let Ok(Some(san)) = cert.subject_alternative_name() {
+            for name in &san.value.general_names {
+                match name {
+                    GeneralName::DNSName(name) => identity.dns_names.push((*name).to_owned()),
+                    GeneralName::URI(uri) => identity.uris.push((*uri).to_owned()),
+                    _ => {}
+                }
+            }
+        }
+
+        Some(identity)
+    }
+
+    /// The Common Name of the certificate's subject.
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// The DNS names in the certificate's Subject Alternative Names.
+    pub fn dns_names(&self) -> &[String] {
+        &self.dns_names
+    }
+
+    /// The URIs in the certificate's Subject Alternative Names, e.g. a SPIFFE ID like
+    /// `spiffe://example.org/service`.
+    pub fn uris(&self) -> &[String] {
+        &self.uris
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair, SanType};
+
+    #[test]
+    fn parses_common_name_and_sans() {
+        let mut params = CertificateParams::new(vec!["client.example.org".to_owned()]).unwrap();
+        params.subject_alt_names.push(SanType::URI(
+            "spiffe://example.org/service".try_into().unwrap(),
+        ));
+        let mut subject = DistinguishedName::new();
+        subject.push(DnType::CommonName, "client");
+        params.distinguished_name = subject;
+
+        let cert = params.self_signed(&KeyPair::generate().unwrap()).unwrap();
+        let identity = PeerIdentity::from_certificate(cert.der()).unwrap();
+
+        assert_eq!(identity.common_name(), Some("client"));
+        assert_eq!(identity.dns_names(), ["client.example.org"]);
+        assert_eq!(identity.uris(), ["spiffe://example.org/service"]);
+    }
+
+    #[test]
+    fn invalid_certificate() {
+        assert!(PeerIdentity::from_certificate(&CertificateDer::from(vec![1, 2, 3])).is_none());
+    }
+}
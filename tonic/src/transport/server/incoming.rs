@@ -1,24 +1,34 @@
 use std::{
+    fmt, io,
     net::{SocketAddr, TcpListener as StdTcpListener},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
 
-use socket2::TcpKeepalive;
-use tokio::net::{TcpListener, TcpStream};
-use tokio_stream::{wrappers::TcpListenerStream, Stream};
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task::JoinHandle,
+};
+use tokio_stream::{wrappers::TcpListenerStream, Stream, StreamExt as _};
 use tracing::warn;
 
+/// The listen backlog used when none is configured, consistent with `tokio::net::TcpListener::bind`.
+const DEFAULT_BACKLOG: i32 = 1024;
+
 /// Binds a socket address for a [Router](super::Router)
 ///
 /// An incoming stream, usable with [Router::serve_with_incoming](super::Router::serve_with_incoming),
 /// of `AsyncRead + AsyncWrite` that communicate with clients that connect to a socket address.
 #[derive(Debug)]
 pub struct TcpIncoming {
-    inner: TcpListenerStream,
+    inner: Listener,
     nodelay: Option<bool>,
     keepalive: Option<TcpKeepalive>,
+    configure_socket: Option<ConfigureSocket>,
 }
 
 impl TcpIncoming {
@@ -60,6 +70,121 @@ impl TcpIncoming {
         Ok(TcpListener::from_std(std_listener)?.into())
     }
 
+    /// Binds `workers` sockets to the specified socket address with `SO_REUSEPORT`, each accepting
+    /// connections on its own task, see [`Server::accept_workers`](super::Server::accept_workers).
+    ///
+    /// A single socket is bound with `SO_REUSEPORT` only if `reuse_port` is set, see
+    /// [`Server::reuse_port`](super::Server::reuse_port). `configure` is called to configure each
+    /// of the workers' `TcpIncoming` before its task is spawned.
+    pub(crate) fn bind_workers(
+        addr: SocketAddr,
+        workers: usize,
+        reuse_port: bool,
+        backlog: Option<u32>,
+        fastopen: Option<u32>,
+        configure_listener: Option<&ConfigureSocket>,
+        configure: impl Fn(Self) -> Self,
+    ) -> io::Result<Self> {
+        if workers <= 1 || !REUSE_PORT {
+            return Ok(configure(Self::bind_socket(
+                addr,
+                backlog,
+                fastopen,
+                configure_listener,
+                reuse_port,
+            )?));
+        }
+
+        let listeners =
+            Self::bind_reuse_port(addr, workers, backlog, fastopen, configure_listener)?;
+        let addr = listeners[0].local_addr()?;
+        Ok(Self::from_workers(
+            addr,
+            listeners.into_iter().map(configure),
+        ))
+    }
+
+    fn bind_reuse_port(
+        addr: SocketAddr,
+        workers: usize,
+        backlog: Option<u32>,
+        fastopen: Option<u32>,
+        configure: Option<&ConfigureSocket>,
+    ) -> io::Result<Vec<Self>> {
+        let first = Self::bind_socket(addr, backlog, fastopen, configure, true)?;
+        // The other sockets must share the port the first one got if `addr` has port 0.
+        let addr = first.local_addr()?;
+
+        let mut listeners = vec![first];
+        for _ in 1..workers {
+            listeners.push(Self::bind_socket(addr, backlog, fastopen, configure, true)?);
+        }
+        Ok(listeners)
+    }
+
+    fn from_workers(addr: SocketAddr, workers: impl Iterator<Item = Self>) -> Self {
+        let (tx, rx) = mpsc::channel(1);
+        let tasks = workers
+            .map(|mut incoming| {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    while let Some(accepted) = incoming.next().await {
+                        if tx.send(accepted).await.is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            inner: Listener::Workers(AcceptWorkers { addr, rx, tasks }),
+            nodelay: None,
+            keepalive: None,
+            configure_socket: None,
+        }
+    }
+
+    fn bind_socket(
+        addr: SocketAddr,
+        backlog: Option<u32>,
+        fastopen: Option<u32>,
+        configure: Option<&ConfigureSocket>,
+        reuse_port: bool,
+    ) -> io::Result<Self> {
+        let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+        // Consistent with `std::net::TcpListener::bind`.
+        #[cfg(unix)]
+        socket.set_reuse_address(true)?;
+        #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+        if reuse_port {
+            socket.set_reuse_port(true)?;
+        }
+        #[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))]
+        let _ = reuse_port;
+        if let Some(configure) = configure {
+            (configure.0)(&socket)?;
+        }
+        socket.bind(&addr.into())?;
+        if let Some(queue_len) = fastopen {
+            set_tcp_fastopen(&socket, queue_len)?;
+        }
+        let backlog = backlog.map_or(DEFAULT_BACKLOG, |backlog| {
+            i32::try_from(backlog).unwrap_or(i32::MAX)
+        });
+        socket.listen(backlog)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(TcpListener::from_std(socket.into())?.into())
+    }
+
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        match &self.inner {
+            Listener::Single(listener) => listener.as_ref().local_addr(),
+            Listener::Workers(workers) => Ok(workers.addr),
+        }
+    }
+
     /// Sets the `TCP_NODELAY` option on the accepted connection.
     pub fn with_nodelay(self, nodelay: Option<bool>) -> Self {
         Self { nodelay, ..self }
@@ -70,14 +195,23 @@ impl TcpIncoming {
         let keepalive = keepalive.map(|t| TcpKeepalive::new().with_time(t));
         Self { keepalive, ..self }
     }
+
+    /// Passes every accepted connection to `configure_socket`, closing it if that fails.
+    pub(crate) fn with_configure_socket(self, configure_socket: Option<ConfigureSocket>) -> Self {
+        Self {
+            configure_socket,
+            ..self
+        }
+    }
 }
 
 impl From<TcpListener> for TcpIncoming {
     fn from(listener: TcpListener) -> Self {
         Self {
-            inner: TcpListenerStream::new(listener),
+            inner: Listener::Single(TcpListenerStream::new(listener)),
             nodelay: None,
             keepalive: None,
+            configure_socket: None,
         }
     }
 }
@@ -86,13 +220,103 @@ impl Stream for TcpIncoming {
     type Item = std::io::Result<TcpStream>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let polled = Pin::new(&mut self.inner).poll_next(cx);
+        loop {
+            let polled = match &mut self.inner {
+                Listener::Single(listener) => Pin::new(listener).poll_next(cx),
+                // The workers already configured the connections they accepted.
+                Listener::Workers(workers) => return workers.rx.poll_recv(cx),
+            };
+
+            if let Poll::Ready(Some(Ok(stream))) = &polled {
+                set_accepted_socket_options(stream, self.nodelay, &self.keepalive);
+
+                if let Some(configure_socket) = &self.configure_socket {
+                    if let Err(e) = (configure_socket.0)(&SockRef::from(stream)) {
+                        warn!("error trying to configure accepted socket: {e}");
+                        continue;
+                    }
+                }
+            }
+
+            return polled;
+        }
+    }
+}
+
+/// Sets `TCP_FASTOPEN` on a listening socket, see
+/// [`Server::tcp_fastopen`](super::Server::tcp_fastopen).
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_tcp_fastopen(socket: &Socket, queue_len: u32) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let queue_len = libc::c_int::try_from(queue_len).unwrap_or(libc::c_int::MAX);
+    // SAFETY: the socket is open for as long as it's borrowed, and `queue_len` outlives the call.
+    let ret = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_FASTOPEN,
+            std::ptr::addr_of!(queue_len).cast(),
+            std::mem::size_of_val(&queue_len) as libc::socklen_t,
+        )
+    };
+    if ret == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// TCP Fast Open is ignored on the platforms that don't support it.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_tcp_fastopen(_socket: &Socket, _queue_len: u32) -> io::Result<()> {
+    Ok(())
+}
+
+/// Whether the platform supports `SO_REUSEPORT`, needed for more than one accept worker.
+const REUSE_PORT: bool = cfg!(all(
+    unix,
+    not(any(target_os = "solaris", target_os = "illumos"))
+));
 
-        if let Poll::Ready(Some(Ok(stream))) = &polled {
-            set_accepted_socket_options(stream, self.nodelay, &self.keepalive);
+#[derive(Debug)]
+enum Listener {
+    Single(TcpListenerStream),
+    Workers(AcceptWorkers),
+}
+
+/// Listeners sharing a port, each accepting connections on its own task.
+#[derive(Debug)]
+struct AcceptWorkers {
+    /// The address shared by the sockets of the workers.
+    addr: SocketAddr,
+    rx: mpsc::Receiver<io::Result<TcpStream>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for AcceptWorkers {
+    fn drop(&mut self) {
+        // The tasks own the listeners, which must be closed along with the server.
+        for task in &self.tasks {
+            task.abort();
         }
+    }
+}
+
+type ConfigureSocketFn = dyn Fn(&Socket) -> io::Result<()> + Send + Sync;
 
-        polled
+/// A user callback that configures a socket.
+#[derive(Clone)]
+pub(crate) struct ConfigureSocket(Arc<ConfigureSocketFn>);
+
+impl ConfigureSocket {
+    pub(crate) fn new(f: impl Fn(&Socket) -> io::Result<()> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for ConfigureSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigureSocket").finish()
     }
 }
 
@@ -119,6 +343,9 @@ fn set_accepted_socket_options(
 #[cfg(test)]
 mod tests {
     use crate::transport::server::TcpIncoming;
+    use std::time::Duration;
+    use tokio_stream::StreamExt;
+
     #[tokio::test]
     async fn one_tcpincoming_at_a_time() {
         let addr = "127.0.0.1:1322".parse().unwrap();
@@ -128,4 +355,58 @@ mod tests {
         }
         let _t3 = TcpIncoming::bind(addr).unwrap();
     }
+
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    #[tokio::test]
+    async fn accept_workers_share_the_port() {
+        let addr = "127.0.0.1:0".parse().unwrap();
+        let mut workers = TcpIncoming::bind_reuse_port(addr, 2, None, None, None).unwrap();
+        let addr = workers[0].local_addr().unwrap();
+        assert_eq!(workers[1].local_addr().unwrap(), addr);
+
+        // The kernel spreads connections over the sockets by their source port.
+        let clients = (0..32)
+            .map(|_| std::net::TcpStream::connect(addr).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut accepted = [0; 2];
+        for (worker, count) in workers.iter_mut().zip(&mut accepted) {
+            while let Ok(Some(Ok(_))) =
+                tokio::time::timeout(Duration::from_millis(50), worker.next()).await
+            {
+                *count += 1;
+            }
+        }
+
+        assert_eq!(accepted.iter().sum::<usize>(), clients.len());
+        assert!(accepted.iter().all(|count| *count > 0), "{accepted:?}");
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[tokio::test]
+    async fn binds_with_tcp_fastopen() {
+        use super::Listener;
+        use std::os::fd::AsRawFd;
+
+        let addr = "127.0.0.1:0".parse().unwrap();
+        let incoming = TcpIncoming::bind_socket(addr, None, Some(16), None, false).unwrap();
+        let Listener::Single(listener) = &incoming.inner else {
+            unreachable!("a single socket was bound");
+        };
+
+        let mut queue_len: libc::c_int = 0;
+        let mut len = std::mem::size_of_val(&queue_len) as libc::socklen_t;
+        // SAFETY: the listener is open, and both pointers are valid for the call.
+        let ret = unsafe {
+            libc::getsockopt(
+                listener.as_ref().as_raw_fd(),
+                libc::IPPROTO_TCP,
+                libc::TCP_FASTOPEN,
+                std::ptr::addr_of_mut!(queue_len).cast(),
+                &mut len,
+            )
+        };
+        assert_eq!(ret, 0, "{}", std::io::Error::last_os_error());
+        assert_eq!(queue_len, 16);
+    }
 }
@@ -1,5 +1,10 @@
 #[cfg(feature = "_tls-any")]
-use std::future::Future;
+use std::{
+    collections::{hash_map, HashMap},
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+};
 use std::{
     io,
     ops::ControlFlow,
@@ -15,12 +20,25 @@ use tokio_stream::Stream;
 #[cfg(feature = "_tls-any")]
 use tokio_stream::StreamExt as _;
 
+use super::conn::Connected;
 use super::service::ServerIo;
 #[cfg(feature = "_tls-any")]
-use super::service::TlsAcceptor;
+use super::{service::TlsAcceptor, HandshakeErrorHook};
 
+/// The TLS acceptor, the handshakes in progress, the limit of concurrent handshakes, the
+/// handshakes in progress per peer IP address and the hook for failed handshakes.
 #[cfg(feature = "_tls-any")]
-struct State<IO>(TlsAcceptor, JoinSet<Result<ServerIo<IO>, crate::BoxError>>);
+struct State<IO>(
+    TlsAcceptor,
+    JoinSet<HandshakeResult<IO>>,
+    Option<usize>,
+    Option<PendingHandshakes>,
+    Option<HandshakeErrorHook>,
+);
+
+/// The result of a TLS handshake, failing with the remote address of the connection.
+#[cfg(feature = "_tls-any")]
+type HandshakeResult<IO> = Result<ServerIo<IO>, (Option<SocketAddr>, crate::BoxError)>;
 
 #[pin_project]
 pub(crate) struct ServerIoStream<S, IO, IE>
@@ -37,11 +55,25 @@ impl<S, IO, IE> ServerIoStream<S, IO, IE>
 where
     S: Stream<Item = Result<IO, IE>>,
 {
-    pub(crate) fn new(incoming: S, #[cfg(feature = "_tls-any")] tls: Option<TlsAcceptor>) -> Self {
+    pub(crate) fn new(
+        incoming: S,
+        #[cfg(feature = "_tls-any")] tls: Option<TlsAcceptor>,
+        #[cfg(feature = "_tls-any")] max_concurrent_handshakes: Option<usize>,
+        #[cfg(feature = "_tls-any")] max_pending_handshakes_per_ip: Option<usize>,
+        #[cfg(feature = "_tls-any")] on_handshake_error: Option<HandshakeErrorHook>,
+    ) -> Self {
         Self {
             inner: incoming,
             #[cfg(feature = "_tls-any")]
-            state: tls.map(|tls| State(tls, JoinSet::new())),
+            state: tls.map(|tls| {
+                State(
+                    tls,
+                    JoinSet::new(),
+                    max_concurrent_handshakes,
+                    max_pending_handshakes_per_ip.map(PendingHandshakes::new),
+                    on_handshake_error,
+                )
+            }),
         }
     }
 
@@ -69,7 +101,7 @@ where
 impl<S, IO, IE> Stream for ServerIoStream<S, IO, IE>
 where
     S: Stream<Item = Result<IO, IE>>,
-    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
     IE: Into<crate::BoxError>,
 {
     type Item = Result<ServerIo<IO>, crate::BoxError>;
@@ -83,17 +115,40 @@ where
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let mut projected = self.as_mut().project();
 
-        let Some(State(tls, tasks)) = projected.state else {
+        let Some(State(tls, tasks, max_concurrent_handshakes, pending, on_handshake_error)) =
+            projected.state
+        else {
             return self.poll_next_without_tls(cx);
         };
 
-        let select_output = ready!(pin!(select(&mut projected.inner, tasks)).poll(cx));
+        let select_output = ready!(pin!(select(
+            &mut projected.inner,
+            tasks,
+            *max_concurrent_handshakes
+        ))
+        .poll(cx));
 
         match select_output {
             SelectOutput::Incoming(stream) => {
                 let tls = tls.clone();
+                let remote_addr = stream.peer_info().remote_addr();
+                let guard = match (pending, remote_addr) {
+                    (Some(pending), Some(addr)) => match pending.start(addr.ip()) {
+                        Some(guard) => Some(guard),
+                        None => {
+                            tracing::debug!(
+                                "too many handshakes in progress from {}, closing the new connection",
+                                addr.ip()
+                            );
+                            cx.waker().wake_by_ref();
+                            return Poll::Pending;
+                        }
+                    },
+                    _ => None,
+                };
                 tasks.spawn(async move {
-                    let io = tls.accept(stream).await?;
+                    let _guard = guard;
+                    let io = tls.accept(stream).await.map_err(|e| (remote_addr, e))?;
                     Ok(ServerIo::new_tls_io(io))
                 });
                 cx.waker().wake_by_ref();
@@ -110,8 +165,11 @@ where
                 ControlFlow::Break(e) => Poll::Ready(Some(Err(e))),
             },
 
-            SelectOutput::TlsErr(e) => {
+            SelectOutput::TlsErr(remote_addr, e) => {
                 tracing::debug!(error = %e, "tls accept error");
+                if let Some(hook) = on_handshake_error {
+                    hook(remote_addr, &crate::transport::Error::from_source(e));
+                }
                 cx.waker().wake_by_ref();
                 Poll::Pending
             }
@@ -121,6 +179,59 @@ where
     }
 }
 
+/// The TLS handshakes in progress per peer IP address, see
+/// [`Server::max_pending_handshakes_per_ip`](super::Server::max_pending_handshakes_per_ip).
+#[cfg(feature = "_tls-any")]
+struct PendingHandshakes {
+    max: usize,
+    per_ip: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+#[cfg(feature = "_tls-any")]
+impl PendingHandshakes {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            per_ip: Arc::default(),
+        }
+    }
+
+    /// Tracks a handshake from `ip` until the returned guard is dropped, or returns `None` if
+    /// `ip` already has the maximum number of handshakes in progress.
+    fn start(&self, ip: IpAddr) -> Option<PendingHandshake> {
+        let mut per_ip = self.per_ip.lock().unwrap_or_else(|e| e.into_inner());
+        let count = per_ip.entry(ip).or_insert(0);
+        if *count >= self.max {
+            return None;
+        }
+        *count += 1;
+        Some(PendingHandshake {
+            per_ip: self.per_ip.clone(),
+            ip,
+        })
+    }
+}
+
+/// A TLS handshake in progress, tracked by [`PendingHandshakes`].
+#[cfg(feature = "_tls-any")]
+struct PendingHandshake {
+    per_ip: Arc<Mutex<HashMap<IpAddr, usize>>>,
+    ip: IpAddr,
+}
+
+#[cfg(feature = "_tls-any")]
+impl Drop for PendingHandshake {
+    fn drop(&mut self) {
+        let mut per_ip = self.per_ip.lock().unwrap_or_else(|e| e.into_inner());
+        if let hash_map::Entry::Occupied(mut count) = per_ip.entry(self.ip) {
+            *count.get_mut() -= 1;
+            if *count.get() == 0 {
+                count.remove();
+            }
+        }
+    }
+}
+
 fn handle_tcp_accept_error(e: impl Into<crate::BoxError>) -> ControlFlow<crate::BoxError> {
     let e = e.into();
     tracing::debug!(error = %e, "accept loop error");
@@ -144,7 +255,8 @@ fn handle_tcp_accept_error(e: impl Into<crate::BoxError>) -> ControlFlow<crate::
 #[cfg(feature = "_tls-any")]
 async fn select<IO: 'static, IE>(
     incoming: &mut (impl Stream<Item = Result<IO, IE>> + Unpin),
-    tasks: &mut JoinSet<Result<ServerIo<IO>, crate::BoxError>>,
+    tasks: &mut JoinSet<HandshakeResult<IO>>,
+    max_concurrent_handshakes: Option<usize>,
 ) -> SelectOutput<IO>
 where
     IE: Into<crate::BoxError>,
@@ -161,15 +273,23 @@ where
         return incoming_stream_future.await;
     }
 
+    // Leave further connections in the listen queue until a handshake completes.
+    if max_concurrent_handshakes.is_some_and(|max| tasks.len() >= max) {
+        return join_next(tasks).await;
+    }
+
     tokio::select! {
         stream = incoming_stream_future => stream,
-        accept = tasks.join_next() => {
-            match accept.expect("JoinSet should never end") {
-                Ok(Ok(io)) => SelectOutput::Io(io),
-                Ok(Err(e)) => SelectOutput::TlsErr(e),
-                Err(e) => SelectOutput::TlsErr(e.into()),
-            }
-        }
+        accept = join_next(tasks) => accept,
+    }
+}
+
+#[cfg(feature = "_tls-any")]
+async fn join_next<IO: 'static>(tasks: &mut JoinSet<HandshakeResult<IO>>) -> SelectOutput<IO> {
+    match tasks.join_next().await.expect("JoinSet should never end") {
+        Ok(Ok(io)) => SelectOutput::Io(io),
+        Ok(Err((remote_addr, e))) => SelectOutput::TlsErr(remote_addr, e),
+        Err(e) => SelectOutput::TlsErr(None, e.into()),
     }
 }
 
@@ -178,6 +298,6 @@ enum SelectOutput<A> {
     Incoming(A),
     Io(ServerIo<A>),
     TcpErr(crate::BoxError),
-    TlsErr(crate::BoxError),
+    TlsErr(Option<SocketAddr>, crate::BoxError),
     Done,
 }
@@ -0,0 +1,238 @@
+use super::{Connected, PeerInfo};
+use pin_project::pin_project;
+use std::{
+    collections::{hash_map, HashMap},
+    io,
+    net::IpAddr,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_stream::Stream;
+
+/// The open connections per peer IP address, see
+/// [`Server::max_connections_per_ip`](super::Server::max_connections_per_ip).
+struct ConnectionsPerIp {
+    max: usize,
+    per_ip: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl ConnectionsPerIp {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            per_ip: Arc::default(),
+        }
+    }
+
+    /// Counts a connection from `ip` until the returned guard is dropped, or returns `None` if
+    /// `ip` already has the maximum number of open connections.
+    fn open(&self, ip: IpAddr) -> Option<IpGuard> {
+        let mut per_ip = self.per_ip.lock().unwrap_or_else(|e| e.into_inner());
+        let count = per_ip.entry(ip).or_default();
+        if *count >= self.max {
+            return None;
+        }
+        *count += 1;
+        Some(IpGuard {
+            per_ip: self.per_ip.clone(),
+            ip,
+        })
+    }
+}
+
+struct IpGuard {
+    per_ip: Arc<Mutex<HashMap<IpAddr, usize>>>,
+    ip: IpAddr,
+}
+
+impl Drop for IpGuard {
+    fn drop(&mut self) {
+        let mut per_ip = self.per_ip.lock().unwrap_or_else(|e| e.into_inner());
+        if let hash_map::Entry::Occupied(mut count) = per_ip.entry(self.ip) {
+            *count.get_mut() -= 1;
+            if *count.get() == 0 {
+                count.remove();
+            }
+        }
+    }
+}
+
+/// Closes the connections taken from `inner` whose peer IP address already has the maximum
+/// number of open connections, before any TLS handshake.
+#[pin_project]
+pub(crate) struct IpLimitedIncoming<S> {
+    #[pin]
+    inner: S,
+    limit: Option<ConnectionsPerIp>,
+}
+
+impl<S> IpLimitedIncoming<S> {
+    pub(crate) fn new(inner: S, max_per_ip: Option<usize>) -> Self {
+        Self {
+            inner,
+            limit: max_per_ip.map(ConnectionsPerIp::new),
+        }
+    }
+}
+
+impl<S, IO, IE> Stream for IpLimitedIncoming<S>
+where
+    S: Stream<Item = Result<IO, IE>>,
+    IO: Connected,
+{
+    type Item = Result<IpLimitedIo<IO>, IE>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let io = match ready!(this.inner.poll_next(cx)) {
+            Some(Ok(io)) => io,
+            Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+            None => return Poll::Ready(None),
+        };
+
+        let guard = match (&*this.limit, io.peer_info().remote_addr()) {
+            (Some(limit), Some(addr)) => match limit.open(addr.ip()) {
+                Some(guard) => Some(guard),
+                None => {
+                    tracing::debug!(
+                        "too many connections from {}, closing the new one",
+                        addr.ip()
+                    );
+                    drop(io);
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+            },
+            _ => None,
+        };
+        Poll::Ready(Some(Ok(IpLimitedIo {
+            inner: io,
+            _guard: guard,
+        })))
+    }
+}
+
+/// A connection counted against the limit of its peer IP address until it is closed.
+#[pin_project]
+pub(crate) struct IpLimitedIo<IO> {
+    #[pin]
+    inner: IO,
+    _guard: Option<IpGuard>,
+}
+
+impl<IO: AsyncRead> AsyncRead for IpLimitedIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for IpLimitedIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+impl<IO: Connected> Connected for IpLimitedIo<IO> {
+    type ConnectInfo = IO::ConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.inner.connect_info()
+    }
+
+    fn peer_info(&self) -> PeerInfo {
+        self.inner.peer_info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use tokio_stream::StreamExt;
+
+    struct Peer(Option<SocketAddr>);
+
+    impl Connected for Peer {
+        type ConnectInfo = ();
+
+        fn connect_info(&self) {}
+
+        fn peer_info(&self) -> PeerInfo {
+            PeerInfo::new(None, self.0)
+        }
+    }
+
+    fn peer(ip: &str) -> Peer {
+        Peer(Some(SocketAddr::new(ip.parse().unwrap(), 1000)))
+    }
+
+    #[test]
+    fn caps_connections_per_ip() {
+        let limit = ConnectionsPerIp::new(2);
+        let ip = |ip: &str| ip.parse().unwrap();
+
+        let first = limit.open(ip("10.0.0.1")).unwrap();
+        let _second = limit.open(ip("10.0.0.1")).unwrap();
+        assert!(limit.open(ip("10.0.0.1")).is_none());
+
+        // Other addresses are not affected.
+        let _other = limit.open(ip("10.0.0.2")).unwrap();
+
+        drop(first);
+        assert!(limit.open(ip("10.0.0.1")).is_some());
+    }
+
+    #[tokio::test]
+    async fn closes_connections_beyond_the_limit() {
+        let incoming = tokio_stream::iter([
+            peer("10.0.0.1"),
+            peer("10.0.0.1"),
+            peer("10.0.0.2"),
+            Peer(None),
+            peer("10.0.0.1"),
+        ])
+        .map(Ok::<_, io::Error>);
+
+        // The admitted connections stay open while the others are taken.
+        let admitted = IpLimitedIncoming::new(incoming, Some(1))
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        let addrs = admitted
+            .iter()
+            .map(|io| io.peer_info().remote_addr())
+            .collect::<Vec<_>>();
+        // Connections without a peer address, e.g. over unix domain sockets, are not limited.
+        assert_eq!(addrs, [peer("10.0.0.1").0, peer("10.0.0.2").0, None]);
+    }
+}
@@ -0,0 +1,261 @@
+use super::{Connected, Server, DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS};
+use crate::{body::Body, transport::service::GrpcTimeout};
+use bytes::Bytes;
+use http::{Request, Response};
+use hyper::{body::Incoming, server::conn::http2::Builder as Http2Builder};
+use hyper_util::{
+    rt::{TokioIo, TokioTimer},
+    service::TowerToHyperService,
+};
+use std::{
+    future::{pending, Future},
+    net::SocketAddr,
+    pin::pin,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::watch,
+    task::LocalSet,
+};
+use tokio_stream::{Stream, StreamExt as _};
+use tower::{layer::Layer, Service, ServiceExt};
+use tracing::{debug, trace};
+
+/// Spawns the tasks of the connections, and their streams, on the current thread.
+#[derive(Clone, Copy, Debug)]
+struct LocalExec;
+
+impl<F> hyper::rt::Executor<F> for LocalExec
+where
+    F: Future + 'static,
+{
+    fn execute(&self, fut: F) {
+        tokio::task::spawn_local(fut);
+    }
+}
+
+impl<L> Server<L> {
+    /// Serve `svc` on the current thread, for services that aren't `Send`.
+    ///
+    /// Services added to a [`Router`](super::Router) must be `Send`, as their requests are
+    /// spawned across the threads of the runtime. Services holding state that can't be sent
+    /// across threads, e.g. an `Rc` or a handle of a C library that isn't thread safe, can be
+    /// served with this method instead, which serves every connection and request on a
+    /// [`LocalSet`] driven by the returned future. The future isn't `Send` itself, so it must
+    /// be run on the thread it was created on, e.g. by awaiting it in `main` on a
+    /// `current_thread` runtime, or with [`LocalSet::run_until`]. All requests share that
+    /// thread, a handler blocking it stalls the whole server.
+    ///
+    /// `svc` is typically a [`tower::service_fn`] calling the methods of
+    /// [`server::Grpc`](crate::server::Grpc):
+    ///
+    /// ```no_run
+    /// # use std::{cell::Cell, rc::Rc};
+    /// # use tonic::{codec::ProstCodec, server::Grpc, transport::Server, Request, Response, Status};
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     // State that isn't `Send`.
+    ///     let calls = Rc::new(Cell::new(0));
+    ///
+    ///     let svc = tower::service_fn(move |req: http::Request<tonic::body::Body>| {
+    ///         let calls = calls.clone();
+    ///         async move {
+    ///             let count = tower::service_fn(move |_: Request<()>| {
+    ///                 calls.set(calls.get() + 1);
+    ///                 async { Ok::<_, Status>(Response::new(())) }
+    ///             });
+    ///             let mut grpc = Grpc::new(ProstCodec::<(), ()>::default());
+    ///             Ok::<_, std::convert::Infallible>(grpc.unary(count, req).await)
+    ///         }
+    ///     });
+    ///
+    ///     Server::builder()
+    ///         .serve_local("[::1]:50051".parse()?, svc)
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Unlike a [`Router`](super::Router), `svc` gets every request, whatever its path, and
+    /// only HTTP/2 without TLS is served. The layers, the timeouts, and the TCP and HTTP/2
+    /// settings of the server apply, the options enforced per connection or across connections,
+    /// like limits, statistics and hooks, don't.
+    pub async fn serve_local<S, ResBody>(
+        self,
+        addr: SocketAddr,
+        svc: S,
+    ) -> Result<(), crate::transport::Error>
+    where
+        L: Layer<S>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + 'static,
+        <L::Service as Service<Request<Body>>>::Future: 'static,
+        <L::Service as Service<Request<Body>>>::Error: Into<crate::BoxError>,
+        ResBody: http_body::Body<Data = Bytes> + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        let incoming = self.bind(addr)?;
+        self.serve_local_with_incoming_shutdown(svc, incoming, pending())
+            .await
+    }
+
+    /// Serve `svc` on the current thread on the connections of `incoming`, until `signal`
+    /// completes, see [`Server::serve_local`].
+    ///
+    /// Once `signal` completes, the server stops accepting connections and waits for the
+    /// requests in flight to complete, at most for the
+    /// [graceful shutdown timeout](Server::graceful_shutdown_timeout).
+    pub async fn serve_local_with_incoming_shutdown<S, I, IO, IE, F, ResBody>(
+        self,
+        svc: S,
+        incoming: I,
+        signal: F,
+    ) -> Result<(), crate::transport::Error>
+    where
+        L: Layer<S>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + 'static,
+        <L::Service as Service<Request<Body>>>::Future: 'static,
+        <L::Service as Service<Request<Body>>>::Error: Into<crate::BoxError>,
+        I: Stream<Item = Result<IO, IE>>,
+        IO: AsyncRead + AsyncWrite + Connected + Unpin + 'static,
+        IE: Into<crate::BoxError>,
+        F: Future<Output = ()>,
+        ResBody: http_body::Body<Data = Bytes> + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        self.check_max_frame_size()?;
+
+        // Dropping the set when the server stops aborts the connections still open.
+        LocalSet::new()
+            .run_until(self.serve_local_inner(svc, incoming, signal))
+            .await;
+        Ok(())
+    }
+
+    async fn serve_local_inner<S, I, IO, IE, F, ResBody>(self, svc: S, incoming: I, signal: F)
+    where
+        L: Layer<S>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + 'static,
+        <L::Service as Service<Request<Body>>>::Future: 'static,
+        <L::Service as Service<Request<Body>>>::Error: Into<crate::BoxError>,
+        I: Stream<Item = Result<IO, IE>>,
+        IO: AsyncRead + AsyncWrite + Connected + Unpin + 'static,
+        IE: Into<crate::BoxError>,
+        F: Future<Output = ()>,
+        ResBody: http_body::Body<Data = Bytes> + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        let builder = self.local_http2_builder();
+        let svc = GrpcTimeout::new(
+            self.service_builder.service(svc),
+            self.timeout,
+            self.default_timeout,
+        );
+
+        let (signal_tx, signal_rx) = watch::channel(());
+        let mut signal = pin!(signal);
+        // Boxed to close the listener as soon as the server stops accepting connections.
+        let mut incoming = Box::pin(incoming);
+
+        loop {
+            let io = tokio::select! {
+                _ = &mut signal => {
+                    trace!("signal received, shutting down");
+                    break;
+                },
+                io = incoming.next() => match io {
+                    Some(Ok(io)) => io,
+                    Some(Err(e)) => {
+                        trace!("error accepting connection: {:#}", e.into());
+                        continue;
+                    },
+                    None => break,
+                },
+            };
+
+            trace!("connection accepted");
+            let connect_info = io.connect_info();
+            let hyper_svc =
+                TowerToHyperService::new(svc.clone().map_request(move |req: Request<Incoming>| {
+                    let mut req = req.map(Body::new);
+                    req.extensions_mut().insert(connect_info.clone());
+                    req
+                }));
+
+            let conn = builder.serve_connection(TokioIo::new(io), hyper_svc);
+            let mut watcher = signal_rx.clone();
+            tokio::task::spawn_local(async move {
+                let mut conn = pin!(conn);
+                let result = tokio::select! {
+                    result = &mut conn => result,
+                    _ = watcher.changed() => {
+                        conn.as_mut().graceful_shutdown();
+                        conn.await
+                    },
+                };
+                if let Err(err) = result {
+                    debug!("failed serving connection: {:#}", err);
+                }
+                drop(watcher);
+            });
+        }
+
+        // Stop listening right away, then wait for the connections to drain.
+        drop(incoming);
+        let _ = signal_tx.send(());
+        drop(signal_rx);
+        let closed = signal_tx.closed();
+        match self.graceful_shutdown_timeout {
+            Some(timeout) => {
+                if tokio::time::timeout(timeout, closed).await.is_err() {
+                    debug!(
+                        "graceful shutdown timed out, closing {} connections",
+                        signal_tx.receiver_count()
+                    );
+                }
+            }
+            None => closed.await,
+        }
+    }
+
+    /// The HTTP/2 connection builder of [`Server::serve_local`], with the settings of the
+    /// server.
+    fn local_http2_builder(&self) -> Http2Builder<LocalExec> {
+        let mut builder = Http2Builder::new(LocalExec);
+        builder
+            .timer(TokioTimer::new())
+            .initial_connection_window_size(self.init_connection_window_size)
+            .initial_stream_window_size(self.init_stream_window_size)
+            .max_concurrent_streams(self.max_concurrent_streams)
+            .keep_alive_interval(self.http2_keepalive_interval)
+            .keep_alive_timeout(
+                self.http2_keepalive_timeout
+                    .unwrap_or_else(|| Duration::from_secs(DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS)),
+            )
+            .adaptive_window(self.http2_adaptive_window.unwrap_or_default())
+            .max_pending_accept_reset_streams(self.http2_max_pending_accept_reset_streams)
+            .max_frame_size(self.max_frame_size)
+            .header_table_size(self.http2_header_table_size)
+            .auto_date_header(self.date_header);
+
+        if let Some(max_header_list_size) = self.http2_max_header_list_size {
+            builder.max_header_list_size(max_header_list_size);
+        }
+
+        if let Some(settings) = self.http2_settings {
+            builder
+                .adaptive_window(false)
+                .initial_connection_window_size(settings.initial_connection_window_size)
+                .initial_stream_window_size(settings.initial_window_size)
+                .max_concurrent_streams(settings.max_concurrent_streams)
+                .max_frame_size(settings.max_frame_size)
+                .max_header_list_size(settings.max_header_list_size)
+                .header_table_size(settings.header_table_size);
+            if settings.enable_connect_protocol {
+                builder.enable_connect_protocol();
+            }
+        }
+
+        builder
+    }
+}
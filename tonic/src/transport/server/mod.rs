@@ -1,9 +1,37 @@
 //! Server implementation and builder.
 
+mod accept_rate;
+mod access_log;
+mod admission;
+#[cfg(feature = "audit")]
+mod audit;
+mod boxed_io;
+mod cancel;
 mod conn;
+mod connection_state;
+mod connections;
+mod flow_control_stall;
+mod frames;
+mod h2c;
+mod handle;
+mod http2_settings;
+#[cfg(feature = "http3")]
+mod http3;
+#[cfg(feature = "_tls-any")]
+mod identity;
 mod incoming;
 mod io_stream;
+mod ip_limit;
+mod local;
+#[cfg(feature = "otel-trace")]
+mod otel;
+mod ping;
+mod preface;
 mod service;
+mod shutdown;
+mod slow_request;
+mod stats;
+mod timing;
 #[cfg(feature = "_tls-any")]
 mod tls;
 #[cfg(unix)]
@@ -12,12 +40,16 @@ mod unix;
 use tokio_stream::StreamExt as _;
 use tracing::{debug, trace};
 
-use crate::service::Routes;
+use crate::service::{
+    router::{self, ResolvePath},
+    status_interceptor::{BoxObserver, StatusResponseFuture},
+    Routes, ServiceInfo,
+};
 
-pub use conn::{Connected, TcpConnectInfo};
+pub use conn::{Connected, PeerInfo, TcpConnectInfo};
 use hyper_util::{
-    rt::{TokioExecutor, TokioIo, TokioTimer},
-    server::conn::auto::{Builder as ConnectionBuilder, HttpServerConnExec},
+    rt::{TokioIo, TokioTimer},
+    server::conn::auto::{Builder as AutoBuilder, UpgradeableConnection as AutoConnection},
     service::TowerToHyperService,
 };
 #[cfg(feature = "_tls-any")]
@@ -27,52 +59,130 @@ pub use tls::ServerTlsConfig;
 pub use conn::TlsConnectInfo;
 
 #[cfg(feature = "_tls-any")]
-use self::service::TlsAcceptor;
+pub use identity::PeerIdentity;
+
+#[cfg(feature = "_tls-any")]
+pub use self::service::TlsAcceptor;
 
 #[cfg(unix)]
 pub use unix::UdsConnectInfo;
 
+pub use super::service::SharedExec;
+pub use access_log::{AccessLog, AccessLogBody, AccessLogFormat, AccessLogFuture, AccessLogLayer};
+pub use admission::StreamAdmission;
+#[cfg(feature = "audit")]
+pub use audit::{Audit, AuditBody, AuditEvent, AuditFuture, AuditIdentity, AuditLayer, AuditSink};
+pub use boxed_io::BoxedIo;
+pub use connection_state::ConnectionState;
+pub use connections::ConnectionControl;
+pub use flow_control_stall::{
+    FlowControlStall, FlowControlStallBody, FlowControlStallDetector, FlowControlStallFuture,
+    FlowControlStallLayer,
+};
+pub use handle::ServerHandle;
+pub use http2_settings::Http2Settings;
+use incoming::ConfigureSocket;
 pub use incoming::TcpIncoming;
+use ip_limit::IpLimitedIncoming;
+#[cfg(feature = "otel-trace")]
+pub use otel::{OtelTrace, OtelTraceBody, OtelTraceFuture, OtelTraceLayer};
+pub use ping::ConnectionPing;
+pub use shutdown::{ShutdownOutcome, ShutdownReport};
+pub use slow_request::{
+    SlowRequest, SlowRequestBody, SlowRequestDetector, SlowRequestFuture, SlowRequestLayer,
+};
+pub use stats::{ServerStats, StatsSnapshot};
+pub use timing::RequestTiming;
 
 #[cfg(feature = "_tls-any")]
 use crate::transport::Error;
 
-use self::service::{RecoverError, ServerIo};
-use super::service::GrpcTimeout;
+use self::accept_rate::{AcceptRate, RateLimitedIncoming};
+use self::cancel::CancelOnDrop;
+use self::connection_state::OnConnect;
+use self::connections::ConnectionHandle;
+use self::frames::{FramesIo, Watch};
+use self::h2c::{PendingUpgrade, Rewind, UpgradeH2c};
+use self::preface::PrefaceDone;
+use self::service::{
+    AdmissionGate, ConcurrencyLimit, CostFn, ErrorMappers, MetadataLimit, MetadataLimits,
+    MethodConcurrencyLimit, MethodLimits, ReadTimeoutBody, RecoverError, ServerIo,
+};
+use self::shutdown::Drain;
+use self::stats::ConnectionGuard;
+use self::timing::MarkHandlerStart;
+use super::go_away::{GoAway, GoAwayHook, GoAwayReason, GoAwayRewrite};
+use super::service::{Executor, GrpcTimeout};
 use crate::body::Body;
+use crate::codec::StreamingFlushMode;
+use crate::extensions::{
+    ClientUserAgent, MaxRequestMessages, MaxResponseMessages, PreviousRpcAttempts,
+};
+use crate::server::timing::{ServerTiming, SERVER_TIMING_REQUEST_HEADER};
 use crate::server::NamedService;
+use crate::Baggage;
 use bytes::Bytes;
-use http::{Request, Response};
+use http::{header, HeaderValue, Request, Response};
 use http_body_util::BodyExt;
-use hyper::{body::Incoming, service::Service as HyperService};
+use hyper::{
+    body::Incoming,
+    server::conn::http2::{Builder as Http2Builder, Connection as Http2Connection},
+    service::{HttpService, Service as HyperService},
+};
 use pin_project::pin_project;
 use std::future::pending;
 use std::{
+    collections::HashSet,
     convert::Infallible,
     fmt,
     future::{self, poll_fn, Future},
+    io,
     marker::PhantomData,
     net::SocketAddr,
     pin::{pin, Pin},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
     task::{ready, Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::Notify;
 use tokio::time::sleep;
 use tokio_stream::Stream;
+use tokio_util::{
+    either::Either,
+    sync::{CancellationToken, DropGuard},
+};
 use tower::{
     layer::util::{Identity, Stack},
-    layer::Layer,
-    limit::concurrency::ConcurrencyLimitLayer,
+    layer::{layer_fn, Layer},
     util::BoxCloneService,
     Service, ServiceBuilder, ServiceExt,
 };
 
 type BoxService = tower::util::BoxCloneService<Request<Body>, Response<Body>, crate::BoxError>;
 type TraceInterceptor = Arc<dyn Fn(&http::Request<()>) -> tracing::Span + Send + Sync + 'static>;
+type AcceptGate = Arc<dyn Fn() -> bool + Send + Sync + 'static>;
+type ConfigureHyper = Arc<dyn Fn(HyperBuilder<'_>) + Send + Sync + 'static>;
+pub(crate) type HandshakeErrorHook =
+    Arc<dyn Fn(Option<SocketAddr>, &super::Error) + Send + Sync + 'static>;
+type Http2GoAway = Arc<dyn Fn(GoAwayReason, GoAway) -> GoAway + Send + Sync + 'static>;
+type InterceptConnections =
+    Arc<dyn Fn(BoxedIo) -> Pin<Box<dyn Future<Output = Option<BoxedIo>> + Send>> + Send + Sync>;
 
 const DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS: u64 = 20;
+const DEFAULT_HTTP2_SETTINGS_TIMEOUT_SECS: u64 = 10;
+/// The number of streams a client can open on an HTTP/2 connection, as their IDs are the odd
+/// numbers below 2^31.
+const HTTP2_CLIENT_STREAM_IDS: u64 = 1 << 30;
+/// Leaves about a million streams for the client to open until it sees the GOAWAY.
+const DEFAULT_HTTP2_STREAM_ID_THRESHOLD: u64 = HTTP2_CLIENT_STREAM_IDS - (1 << 20);
+/// The range of `SETTINGS_MAX_FRAME_SIZE` values allowed by RFC 9113, section 6.5.2.
+const HTTP2_MAX_FRAME_SIZES: std::ops::RangeInclusive<u32> = (1 << 14)..=((1 << 24) - 1);
+/// How often a closed [`Server::accept_gate`] is evaluated again.
+const ACCEPT_GATE_INTERVAL: Duration = Duration::from_millis(50);
 
 /// A default batteries included `transport` server.
 ///
@@ -82,27 +192,94 @@ const DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS: u64 = 20;
 /// a very good out of the box http2 server for use with tonic but is also a
 /// reference implementation that should be a good starting point for anyone
 /// wanting to create a more complex and/or specific implementation.
+///
+/// # Cancellation
+///
+/// Every request carries a [`tokio_util::sync::CancellationToken`] in its
+/// [extensions](crate::Request::extensions). The token is cancelled once the
+/// call is over: when the response has been sent, the deadline set through
+/// `grpc-timeout` passed, or the client reset the stream or disconnected.
+/// Long running handlers can use it to abort work nobody is waiting for. Reading
+/// the token requires a direct dependency on `tokio-util` 0.7.
+///
+/// # Stream priority
+///
+/// HTTP/2 stream priorities are not supported. The `h2` implementation underlying
+/// the server ignores `PRIORITY` frames and the priority fields of `HEADERS`
+/// frames, and schedules the frames of all streams alike. RFC 9113 deprecated
+/// this prioritization scheme. To favour some methods over others under load,
+/// limit the concurrency of the others, see [`Server::method_concurrency`].
 #[derive(Clone)]
 pub struct Server<L = Identity> {
     trace_interceptor: Option<TraceInterceptor>,
     concurrency_limit: Option<usize>,
+    cost_fn: Option<CostFn>,
+    method_limits: MethodLimits,
     timeout: Option<Duration>,
+    default_timeout: Option<Duration>,
+    max_request_messages: Option<usize>,
+    max_response_messages: Option<usize>,
+    metadata_limits: MetadataLimits,
+    stream_read_timeout: Option<Duration>,
+    streaming_flush_mode: StreamingFlushMode,
     #[cfg(feature = "_tls-any")]
     tls: Option<TlsAcceptor>,
+    #[cfg(feature = "_tls-any")]
+    max_concurrent_handshakes: Option<usize>,
+    #[cfg(feature = "_tls-any")]
+    max_pending_handshakes_per_ip: Option<usize>,
     init_stream_window_size: Option<u32>,
     init_connection_window_size: Option<u32>,
     max_concurrent_streams: Option<u32>,
     tcp_keepalive: Option<Duration>,
     tcp_nodelay: bool,
+    tcp_backlog: Option<u32>,
+    tcp_fastopen: Option<u32>,
+    accept_workers: usize,
+    reuse_port: bool,
+    configure_listener: Option<ConfigureSocket>,
+    configure_socket: Option<ConfigureSocket>,
+    accept_gate: Option<AcceptGate>,
+    connection_rate_limit: Option<AcceptRate>,
+    max_connections_per_ip: Option<usize>,
     http2_keepalive_interval: Option<Duration>,
     http2_keepalive_timeout: Option<Duration>,
     http2_adaptive_window: Option<bool>,
     http2_max_pending_accept_reset_streams: Option<usize>,
     http2_max_header_list_size: Option<u32>,
+    http2_max_header_frames_per_stream: Option<u32>,
+    http2_connection_ping: bool,
+    http2_header_table_size: Option<u32>,
+    http2_settings: Option<Http2Settings>,
     max_frame_size: Option<u32>,
     accept_http1: bool,
+    h2c: bool,
+    configure_hyper: Option<ConfigureHyper>,
+    executor: SharedExec,
+    date_header: bool,
+    server_header: Option<Option<HeaderValue>>,
+    server_timing: bool,
     service_builder: ServiceBuilder<L>,
     max_connection_age: Option<Duration>,
+    on_goaway_sent: Option<GoAwayHook>,
+    http2_goaway: Option<Http2GoAway>,
+    on_handshake_error: Option<HandshakeErrorHook>,
+    on_connect: Option<OnConnect>,
+    intercept_connections: Option<InterceptConnections>,
+    /// The path resolver of the routes, applied before the layers, see [`Routes::resolve_path`].
+    resolve_path: Option<ResolvePath>,
+    /// The paths of the methods of the routes, counted by their path in the stats.
+    method_paths: Arc<HashSet<String>>,
+    http2_settings_timeout: Option<Duration>,
+    max_requests_per_connection: Option<u64>,
+    http2_stream_id_threshold: u64,
+    stats: ServerStats,
+    connections: ConnectionControl,
+    admission: StreamAdmission,
+    readiness: Arc<tokio::sync::watch::Sender<bool>>,
+    graceful_shutdown_timeout: Option<Duration>,
+    error_mappers: ErrorMappers,
+    shutdown: Arc<tokio::sync::watch::Sender<Option<ShutdownReport>>>,
 }
 
 impl Default for Server<Identity> {
@@ -110,29 +287,81 @@ impl Default for Server<Identity> {
         Self {
             trace_interceptor: None,
             concurrency_limit: None,
+            cost_fn: None,
+            method_limits: MethodLimits::default(),
             timeout: None,
+            default_timeout: None,
+            max_request_messages: None,
+            max_response_messages: None,
+            streaming_flush_mode: StreamingFlushMode::default(),
+            metadata_limits: MetadataLimits::default(),
+            stream_read_timeout: None,
             #[cfg(feature = "_tls-any")]
             tls: None,
+            #[cfg(feature = "_tls-any")]
+            max_concurrent_handshakes: None,
+            #[cfg(feature = "_tls-any")]
+            max_pending_handshakes_per_ip: None,
             init_stream_window_size: None,
             init_connection_window_size: None,
             max_concurrent_streams: None,
             tcp_keepalive: None,
             tcp_nodelay: false,
+            tcp_backlog: None,
+            tcp_fastopen: None,
+            accept_workers: 1,
+            reuse_port: false,
+            configure_listener: None,
+            configure_socket: None,
+            accept_gate: None,
+            configure_hyper: None,
+            connection_rate_limit: None,
+            max_connections_per_ip: None,
             http2_keepalive_interval: None,
             http2_keepalive_timeout: None,
             http2_adaptive_window: None,
             http2_max_pending_accept_reset_streams: None,
             http2_max_header_list_size: None,
+            http2_max_header_frames_per_stream: None,
+            http2_connection_ping: false,
+            http2_header_table_size: None,
+            http2_settings: None,
             max_frame_size: None,
             accept_http1: false,
+            h2c: false,
+            executor: SharedExec::tokio(),
+            date_header: true,
+            server_header: None,
+            server_timing: false,
             service_builder: Default::default(),
             max_connection_age: None,
+            on_goaway_sent: None,
+            http2_goaway: None,
+            on_handshake_error: None,
+            on_connect: None,
+            intercept_connections: None,
+            resolve_path: None,
+            method_paths: Arc::default(),
+            http2_settings_timeout: Some(Duration::from_secs(DEFAULT_HTTP2_SETTINGS_TIMEOUT_SECS)),
+            max_requests_per_connection: None,
+            http2_stream_id_threshold: DEFAULT_HTTP2_STREAM_ID_THRESHOLD,
+            stats: ServerStats::default(),
+            connections: ConnectionControl::default(),
+            admission: StreamAdmission::default(),
+            readiness: Arc::new(tokio::sync::watch::Sender::new(false)),
+            graceful_shutdown_timeout: None,
+            error_mappers: ErrorMappers::default(),
+            shutdown: Arc::new(tokio::sync::watch::Sender::new(None)),
         }
     }
 }
 
 /// A stack based [`Service`] router.
-#[derive(Debug)]
+///
+/// A router is [`Clone`] if its layers are, so one configured server can be served several
+/// times, for example on multiple listeners or from multiple runtimes. Clones share their
+/// [`ServerStats`].
+#[derive(Debug, Clone)]
 pub struct Router<L = Identity> {
     server: Server<L>,
     routes: Routes,
@@ -154,13 +383,95 @@ impl<L> Server<L> {
     #[cfg(feature = "_tls-any")]
     pub fn tls_config(self, tls_config: ServerTlsConfig) -> Result<Self, Error> {
         Ok(Server {
-            tls: Some(tls_config.tls_acceptor().map_err(Error::from_source)?),
+            tls: Some(tls_config.tls_acceptor()?),
             ..self
         })
     }
 
+    /// Configure TLS for this server with an acceptor that was already built.
+    ///
+    /// Unlike [`Server::tls_config`], this does not parse the certificates again, so an acceptor
+    /// built once with [`ServerTlsConfig::tls_acceptor`] can be reused for several servers.
+    ///
+    /// ```
+    /// # use tonic::transport::{Identity, Server, ServerTlsConfig};
+    /// # fn main() -> Result<(), tonic::transport::Error> {
+    /// # let (cert, key) = (Vec::<u8>::new(), Vec::<u8>::new());
+    /// # if false {
+    /// let acceptor = ServerTlsConfig::new()
+    ///     .identity(Identity::from_pem(cert, key))
+    ///     .tls_acceptor()?;
+    ///
+    /// let public = Server::builder().tls_acceptor(acceptor.clone());
+    /// let admin = Server::builder().tls_acceptor(acceptor);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "_tls-any")]
+    #[must_use]
+    pub fn tls_acceptor(self, acceptor: TlsAcceptor) -> Self {
+        Server {
+            tls: Some(acceptor),
+            ..self
+        }
+    }
+
+    /// Set the maximum number of TLS handshakes in progress at the same time.
+    ///
+    /// Handshakes are CPU intensive, so a burst of new connections can starve the requests on
+    /// established ones. Once the limit is reached, further connections are left in the listen
+    /// queue until a handshake completes, see [`Server::tcp_backlog`] for its size.
+    ///
+    /// Default is no limit (`None`).
+    #[cfg(feature = "_tls-any")]
+    #[must_use]
+    pub fn max_concurrent_handshakes(self, max: impl Into<Option<usize>>) -> Self {
+        Server {
+            max_concurrent_handshakes: max.into(),
+            ..self
+        }
+    }
+
+    /// Limit the number of TLS handshakes in progress from a single peer IP address to `max`.
+    ///
+    /// This blunts floods of handshakes that are never completed, which keep the server busy
+    /// without ever reaching a service. Once a peer address has `max` handshakes in progress,
+    /// further connections from it are closed right after they are accepted, until one of its
+    /// handshakes completes or fails, while other peers are unaffected. Unlike
+    /// [`Server::max_concurrent_handshakes`] alone, this keeps a single peer from taking all the
+    /// handshakes the server allows at once. Connections without a peer address, e.g. over unix
+    /// domain sockets, are not limited.
+    ///
+    /// Default is no limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.max_pending_handshakes_per_ip(4);
+    /// ```
+    #[cfg(feature = "_tls-any")]
+    #[must_use]
+    pub fn max_pending_handshakes_per_ip(self, max: usize) -> Self {
+        Server {
+            max_pending_handshakes_per_ip: Some(max),
+            ..self
+        }
+    }
+
     /// Set the concurrency limit applied to on requests inbound per connection.
     ///
+    /// This limits how many handlers run at the same time on a single connection, independently
+    /// of [`Server::max_concurrent_streams`]. Requests beyond the limit are queued until a running
+    /// handler returns its response, even if the client could still open more HTTP/2 streams.
+    ///
+    /// Queued requests are rejected with `DeadlineExceeded` once their deadline, from the
+    /// `grpc-timeout` header or [`Server::timeout`], expires. Requests arriving while the limit is
+    /// reached are rejected right away if their deadline is shorter than the average time
+    /// handlers take to respond on the connection.
+    ///
     /// # Example
     ///
     /// ```
@@ -177,6 +488,67 @@ impl<L> Server<L> {
         }
     }
 
+    /// Set a callback that assigns each request a cost against the
+    /// [concurrency limit](Server::concurrency_limit_per_connection).
+    ///
+    /// By default every request takes one slot of the limit. With a cost function, a request
+    /// takes as many slots as the function returns for it, so expensive methods, like batch
+    /// calls, leave less room for other requests than cheap ones. Costs above the limit are capped
+    /// at the limit, and a cost of 0 admits the request without taking a slot.
+    ///
+    /// The callback sees the request before its body is read. This has no effect unless
+    /// [`Server::concurrency_limit_per_connection`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder
+    ///     .concurrency_limit_per_connection(32)
+    ///     .cost_fn(|request| match request.uri().path() {
+    ///         "/store.Store/BatchGet" => 8,
+    ///         _ => 1,
+    ///     });
+    /// ```
+    #[must_use]
+    pub fn cost_fn(self, f: impl Fn(&Request<()>) -> u32 + Send + Sync + 'static) -> Self {
+        Server {
+            cost_fn: Some(CostFn::new(f)),
+            ..self
+        }
+    }
+
+    /// Limit how many requests to the method at `path` are in flight at the same time, across
+    /// all connections.
+    ///
+    /// Unlike [`Server::concurrency_limit_per_connection`], requests beyond the limit are not
+    /// queued but rejected right away with `ResourceExhausted`, so clients can back off, and
+    /// requests to other methods are not held up behind them. A request is in flight until its
+    /// response, including all messages of a streaming response, has been sent.
+    /// [`StatsSnapshot::active_requests_per_method`] tells how many requests each method has in
+    /// flight.
+    ///
+    /// Can be called once per method; methods without a limit are unlimited. Clones of the server
+    /// share the limits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.method_concurrency("/reports.Reports/ExpensiveReport", 10);
+    /// ```
+    #[must_use]
+    pub fn method_concurrency(self, path: impl Into<String>, limit: usize) -> Self {
+        let mut method_limits = self.method_limits;
+        method_limits.insert(path.into(), limit);
+        Server {
+            method_limits,
+            ..self
+        }
+    }
+
     /// Set a timeout on for all request handlers.
     ///
     /// # Example
@@ -196,196 +568,1410 @@ impl<L> Server<L> {
         }
     }
 
+    /// Set a timeout for requests that don't carry a `grpc-timeout` header.
+    ///
+    /// Clients that set a deadline keep it, so this only bounds requests of clients that didn't.
+    /// [`Server::timeout`] still caps both.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.default_timeout(Duration::from_secs(10));
+    /// ```
+    #[must_use]
+    pub fn default_timeout(self, timeout: Duration) -> Self {
+        Server {
+            default_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Limit how many messages a client may send on a single streaming request.
+    ///
+    /// Once a client-streaming or bidirectional-streaming request exceeds the limit, the request
+    /// stream yields a [`Code::ResourceExhausted`](crate::Code::ResourceExhausted) error instead
+    /// of the next message and ends. This protects handlers that buffer all of their input.
+    ///
+    /// Default is unlimited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # let builder = Server::builder();
+    /// builder.max_request_messages(1024);
+    /// ```
+    #[must_use]
+    pub fn max_request_messages(self, limit: usize) -> Self {
+        Server {
+            max_request_messages: Some(limit),
+            ..self
+        }
+    }
+
+    /// Limit how many messages a handler may send on a single streaming response.
+    ///
+    /// Once a server-streaming or bidirectional-streaming response exceeds the limit, it ends
+    /// with an [`Code::Internal`](crate::Code::Internal) error instead of the next message, and
+    /// the response stream is dropped. This catches handlers generating messages without end,
+    /// e.g. in development.
+    ///
+    /// Default is unlimited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.max_response_messages(10_000);
+    /// ```
+    #[must_use]
+    pub fn max_response_messages(self, limit: usize) -> Self {
+        Server {
+            max_response_messages: Some(limit),
+            ..self
+        }
+    }
+
+    /// Sets when the messages of streaming responses are handed to the connection to be written.
+    ///
+    /// By default, [`StreamingFlushMode::Buffered`], the messages a response stream has ready
+    /// are written together, which favours throughput. [`StreamingFlushMode::Immediate`] writes
+    /// every message on its own as soon as the stream yields it, which favours latency when
+    /// clients act on each message as it arrives.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::{codec::StreamingFlushMode, transport::Server};
+    /// # let builder = Server::builder();
+    /// builder.streaming_flush_mode(StreamingFlushMode::Immediate);
+    /// ```
+    #[must_use]
+    pub fn streaming_flush_mode(self, mode: StreamingFlushMode) -> Self {
+        Server {
+            streaming_flush_mode: mode,
+            ..self
+        }
+    }
+
+    /// Limit how many metadata entries a single request may carry.
+    ///
+    /// Requests with more entries are rejected with
+    /// [`Code::ResourceExhausted`](crate::Code::ResourceExhausted) before they reach the handler.
+    /// Unlike [`Server::http2_max_header_list_size`], which protects the connection, this
+    /// protects handlers and the services they forward metadata to. The headers reserved by
+    /// gRPC, like `content-type` and `te`, don't count.
+    ///
+    /// Default is unlimited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.max_metadata_entries(64).max_metadata_size(8 * 1024);
+    /// ```
+    #[must_use]
+    pub fn max_metadata_entries(self, limit: usize) -> Self {
+        Server {
+            metadata_limits: MetadataLimits {
+                max_entries: Some(limit),
+                ..self.metadata_limits
+            },
+            ..self
+        }
+    }
+
+    /// Limit the total size of the metadata of a single request, in bytes of keys and values.
+    ///
+    /// Requests with more metadata are rejected like with [`Server::max_metadata_entries`].
+    ///
+    /// Default is unlimited.
+    #[must_use]
+    pub fn max_metadata_size(self, limit: usize) -> Self {
+        Server {
+            metadata_limits: MetadataLimits {
+                max_size: Some(limit),
+                ..self.metadata_limits
+            },
+            ..self
+        }
+    }
+
+    /// Require every request to carry the metadata keys in `keys`, e.g. an `x-api-key`.
+    ///
+    /// Requests missing one of them are rejected with
+    /// [`Code::InvalidArgument`](crate::Code::InvalidArgument) before they reach the handler,
+    /// which saves writing an interceptor for this common check. The values are not checked, use
+    /// an interceptor to validate them. Keys are case-insensitive, and calling this again
+    /// replaces the keys required before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the keys is not a valid metadata key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.require_metadata(&["x-api-key", "x-tenant-id"]);
+    /// ```
+    #[must_use]
+    pub fn require_metadata(self, keys: &[&str]) -> Self {
+        let required = keys
+            .iter()
+            .map(|key| {
+                header::HeaderName::from_bytes(key.to_ascii_lowercase().as_bytes())
+                    .unwrap_or_else(|_| panic!("invalid metadata key `{key}`"))
+            })
+            .collect();
+        Server {
+            metadata_limits: MetadataLimits {
+                required,
+                ..self.metadata_limits
+            },
+            ..self
+        }
+    }
+
+    /// Set how long the server waits for the next part of a request before giving up.
+    ///
+    /// Unlike the overall deadline from the `grpc-timeout` header or [`Server::timeout`], this
+    /// limits the time between two messages. Once a client sends nothing for longer than the
+    /// timeout, the request stream yields a
+    /// [`Code::DeadlineExceeded`](crate::Code::DeadlineExceeded) error, which detects stalled
+    /// uploads on long-running client-streaming calls.
+    ///
+    /// Only the time a handler spends waiting for the next message counts, not the time it
+    /// spends processing the previous one.
+    ///
+    /// Default is no timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.stream_read_timeout(Duration::from_secs(30));
+    /// ```
+    #[must_use]
+    pub fn stream_read_timeout(self, timeout: Duration) -> Self {
+        Server {
+            stream_read_timeout: Some(timeout),
+            ..self
+        }
+    }
+
     /// Sets the [`SETTINGS_INITIAL_WINDOW_SIZE`][spec] option for HTTP2
     /// stream-level flow control.
     ///
-    /// Default is 65,535
+    /// Default is 65,535
+    ///
+    /// [spec]: https://httpwg.org/specs/rfc9113.html#InitialWindowSize
+    #[must_use]
+    pub fn initial_stream_window_size(self, sz: impl Into<Option<u32>>) -> Self {
+        Server {
+            init_stream_window_size: sz.into(),
+            ..self
+        }
+    }
+
+    /// Sets the max connection-level flow control for HTTP2
+    ///
+    /// Default is 65,535
+    #[must_use]
+    pub fn initial_connection_window_size(self, sz: impl Into<Option<u32>>) -> Self {
+        Server {
+            init_connection_window_size: sz.into(),
+            ..self
+        }
+    }
+
+    /// Sets the [`SETTINGS_MAX_CONCURRENT_STREAMS`][spec] option for HTTP2
+    /// connections.
+    ///
+    /// Default is no limit (`None`).
+    ///
+    /// [spec]: https://httpwg.org/specs/rfc9113.html#n-stream-concurrency
+    #[must_use]
+    pub fn max_concurrent_streams(self, max: impl Into<Option<u32>>) -> Self {
+        Server {
+            max_concurrent_streams: max.into(),
+            ..self
+        }
+    }
+
+    /// Sets the maximum time option in milliseconds that a connection may exist
+    ///
+    /// Default is no limit (`None`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.max_connection_age(Duration::from_secs(60));
+    /// ```
+    #[must_use]
+    pub fn max_connection_age(self, max_connection_age: Duration) -> Self {
+        Server {
+            max_connection_age: Some(max_connection_age),
+            ..self
+        }
+    }
+
+    /// Call `f` with every `GOAWAY` frame the server sends, announcing that it closes a
+    /// connection.
+    ///
+    /// This covers every reason for closing a connection, e.g. [`Server::max_connection_age`],
+    /// [`Server::max_requests_per_connection`], shutdown, or protocol errors, so operators can log
+    /// and alert on connections being recycled. Tell them apart by [`GoAway::code`]: graceful
+    /// closes send `NO_ERROR`. `f` is called on the task serving the connection, so it must not
+    /// block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.on_goaway_sent(|go_away| {
+    ///     tracing::info!(
+    ///         code = go_away.code(),
+    ///         last_stream_id = go_away.last_stream_id(),
+    ///         "closing connection",
+    ///     );
+    /// });
+    /// ```
+    #[must_use]
+    pub fn on_goaway_sent(self, f: impl Fn(&GoAway) + Send + Sync + 'static) -> Self {
+        Server {
+            on_goaway_sent: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Set the error code and debug data of the `GOAWAY` frames the server sends.
+    ///
+    /// `f` is called with why the connection is closed and each `GOAWAY` frame about to be sent,
+    /// and returns the frame to send instead, e.g. with [`GoAway::with_debug_data`] giving
+    /// clients and proxies a reason to log. The last stream ID is always kept. Frames sent on a
+    /// graceful close carry [`GoAway::NO_ERROR`], and a graceful close sends two of them. Other
+    /// error codes, like [`GoAway::ENHANCE_YOUR_CALM`], make clients fail the requests in
+    /// flight, so only send them for clients that misbehave. [`Server::on_goaway_sent`] sees the
+    /// frames as sent. `f` is called on the task serving the connection, so it must not block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::{GoAway, GoAwayReason, Server};
+    /// # let builder = Server::builder();
+    /// builder.http2_goaway(|reason, go_away| match reason {
+    ///     GoAwayReason::MaxConnectionAge => go_away.with_debug_data("max_age"),
+    ///     GoAwayReason::Shutdown => go_away.with_debug_data("shutting_down"),
+    ///     _ => go_away,
+    /// });
+    /// ```
+    #[must_use]
+    pub fn http2_goaway<F>(self, f: F) -> Self
+    where
+        F: Fn(GoAwayReason, GoAway) -> GoAway + Send + Sync + 'static,
+    {
+        Server {
+            http2_goaway: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Call `f` with the remote address and the error of every connection failing its handshake,
+    /// e.g. to alert on misconfigured clients or attacks.
+    ///
+    /// This covers failed TLS handshakes, like plaintext connections to a TLS port or rejected
+    /// client certificates, and HTTP/2 connections failing before the client completed its
+    /// connection preface and `SETTINGS` frame, including clients not sending them within
+    /// [`Server::http2_settings_timeout`]. These connections are closed either way, without
+    /// reaching a service. The address is `None` for connections without one, like Unix domain
+    /// sockets. `f` is called on the task accepting the connection, so it must not block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.on_handshake_error(|remote_addr, error| {
+    ///     tracing::warn!(?remote_addr, %error, "handshake failed");
+    /// });
+    /// ```
+    #[must_use]
+    pub fn on_handshake_error(
+        self,
+        f: impl Fn(Option<SocketAddr>, &super::Error) + Send + Sync + 'static,
+    ) -> Self {
+        Server {
+            on_handshake_error: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Call `f` for every accepted connection with its remote address, to create state shared by
+    /// all requests on the connection.
+    ///
+    /// The state is accessible through the [request extensions][ext] as a
+    /// [`ConnectionState<T>`], for protocols keeping a session or parameters negotiated per
+    /// connection. Every connection gets its own state, which is dropped once the connection and
+    /// all its requests are done. The address is `None` for connections without one, like Unix
+    /// domain sockets. `f` is called on the task accepting connections, so it must not block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::sync::Mutex;
+    /// # let builder = Server::builder();
+    /// #[derive(Default)]
+    /// struct Session {
+    ///     token: Mutex<Option<String>>,
+    /// }
+    ///
+    /// builder.on_connect(|_remote_addr| Session::default());
+    /// ```
+    ///
+    /// [ext]: crate::Request::extensions
+    #[must_use]
+    pub fn on_connect<T, F>(self, f: F) -> Self
+    where
+        F: Fn(Option<SocketAddr>) -> T + Send + Sync + 'static,
+        T: Send + Sync + 'static,
+    {
+        Server {
+            on_connect: Some(OnConnect::new(f)),
+            ..self
+        }
+    }
+
+    /// Pass every accepted connection to `f` before it is served, so `f` can serve connections
+    /// of another protocol itself, e.g. to serve gRPC and another byte protocol on one port.
+    ///
+    /// `f` gets the connection as a [`BoxedIo`], after its TLS handshake if any, and returns it
+    /// to serve it as usual, or `None` if it took it over. Sniffing the protocol of a connection
+    /// needs to read its first bytes, which must then still be read by whoever serves it: use
+    /// [`BoxedIo::peek`] to read them without consuming them. The client of a protocol where
+    /// the server speaks first, unlike HTTP/2, sends nothing to sniff.
+    ///
+    /// `f` runs on the task of the connection, so slow clients don't hold back other
+    /// connections, and a graceful shutdown waits for the future it returns. Connections it
+    /// takes over should be served on a task of their own, as they are no longer tracked by the
+    /// server.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # fn serve_legacy(_: tonic::transport::server::BoxedIo) {}
+    /// # let builder = Server::builder();
+    /// builder.intercept_connections(|mut io| async move {
+    ///     if io.peek(4).await.ok()? == b"LGCY" {
+    ///         tokio::spawn(async move { serve_legacy(io) });
+    ///         return None;
+    ///     }
+    ///     Some(io)
+    /// });
+    /// ```
+    #[must_use]
+    pub fn intercept_connections<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(BoxedIo) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<BoxedIo>> + Send + 'static,
+    {
+        Server {
+            intercept_connections: Some(Arc::new(move |io| Box::pin(f(io)))),
+            ..self
+        }
+    }
+
+    /// Sets how long a graceful shutdown, e.g. through [`Router::serve_with_shutdown`], waits
+    /// for the connections to drain.
+    ///
+    /// Once the timeout passes, the remaining connections are closed and their requests
+    /// aborted, and the shutdown completes as [`ShutdownOutcome::Forced`].
+    ///
+    /// Default is no limit (`None`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.graceful_shutdown_timeout(Duration::from_secs(30));
+    /// ```
+    #[must_use]
+    pub fn graceful_shutdown_timeout(self, timeout: impl Into<Option<Duration>>) -> Self {
+        Server {
+            graceful_shutdown_timeout: timeout.into(),
+            ..self
+        }
+    }
+
+    /// Sets the time a client has to send its HTTP/2 connection preface and `SETTINGS` frame
+    /// after connecting.
+    ///
+    /// Connections of clients that stall before completing their side of the handshake are
+    /// closed once the timeout elapses, so they don't hold on to server resources. For TLS
+    /// connections, the timeout starts after the TLS handshake. Connections that start with an
+    /// HTTP/1 request, see [`Server::accept_http1`], are done with the handshake once the first
+    /// bytes arrive.
+    ///
+    /// Pass `None` to disable the timeout.
+    ///
+    /// Default is 10 seconds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.http2_settings_timeout(Duration::from_secs(5));
+    /// ```
+    #[must_use]
+    pub fn http2_settings_timeout(self, timeout: impl Into<Option<Duration>>) -> Self {
+        Server {
+            http2_settings_timeout: timeout.into(),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of requests served on a single connection.
+    ///
+    /// Once a connection received this many requests, the server sends a GOAWAY frame and closes
+    /// the connection after the requests in flight completed. Clients then open a new connection,
+    /// which bounds the memory a long-lived connection holds, e.g. in its HPACK tables, and lets
+    /// load balancers that pin connections to a server rebalance them.
+    ///
+    /// Connections closed this way are counted in [`StatsSnapshot::recycled_connections`].
+    ///
+    /// Default is no limit (`None`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # let builder = Server::builder();
+    /// builder.max_requests_per_connection(10_000);
+    /// ```
+    #[must_use]
+    pub fn max_requests_per_connection(self, max_requests: u64) -> Self {
+        Server {
+            max_requests_per_connection: Some(max_requests),
+            ..self
+        }
+    }
+
+    /// Sets the number of requests after which a connection is recycled, before it runs out of
+    /// HTTP/2 stream IDs.
+    ///
+    /// Stream IDs are never reused on a connection, and the ones of the streams opened by the
+    /// client are the odd numbers below 2^31, so a connection carries at most 2^30 requests.
+    /// Clients that run out of stream IDs typically fail the requests they still try to send,
+    /// so the server sends a GOAWAY frame once a connection received this many requests, and
+    /// closes it after the requests in flight completed, like with
+    /// [`Server::max_requests_per_connection`]. Connections closed this way are counted in
+    /// [`StatsSnapshot::recycled_connections`] as well.
+    ///
+    /// The threshold is capped at 2^30. Default is `2^30 - 2^20`, leaving about a million streams
+    /// for requests the client sends until it sees the GOAWAY.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.http2_stream_id_threshold(1 << 29);
+    /// ```
+    #[must_use]
+    pub fn http2_stream_id_threshold(self, requests: u64) -> Self {
+        Server {
+            http2_stream_id_threshold: requests.min(HTTP2_CLIENT_STREAM_IDS),
+            ..self
+        }
+    }
+
+    /// Set whether HTTP2 Ping frames are enabled on accepted connections.
+    ///
+    /// If `None` is specified, HTTP2 keepalive is disabled, otherwise the duration
+    /// specified will be the time interval between HTTP2 Ping frames.
+    /// The timeout for receiving an acknowledgement of the keepalive ping
+    /// can be set with [`Server::http2_keepalive_timeout`].
+    ///
+    /// With [`Server::http2_connection_ping`] enabled, the round-trip times of these pings are
+    /// measured, and handlers can read the one of their connection through
+    /// [`ConnectionPing::last_rtt`].
+    ///
+    /// Pings sent by clients are answered independently of this setting, at any rate and also
+    /// on connections without requests in flight, so clients can keep idle connections open
+    /// through NATs and load balancers with `Endpoint::keep_alive_while_idle`.
+    /// There is no enforcement of a minimum ping interval like other gRPC implementations have.
+    ///
+    /// Default is no HTTP2 keepalive (`None`)
+    ///
+    #[must_use]
+    pub fn http2_keepalive_interval(self, http2_keepalive_interval: Option<Duration>) -> Self {
+        Server {
+            http2_keepalive_interval,
+            ..self
+        }
+    }
+
+    /// Sets a timeout for receiving an acknowledgement of the keepalive ping.
+    ///
+    /// If the ping is not acknowledged within the timeout, the connection will be closed.
+    /// Does nothing if http2_keep_alive_interval is disabled.
+    ///
+    /// Default is 20 seconds.
+    ///
+    #[must_use]
+    pub fn http2_keepalive_timeout(self, http2_keepalive_timeout: Option<Duration>) -> Self {
+        Server {
+            http2_keepalive_timeout,
+            ..self
+        }
+    }
+
+    /// Sets whether to use an adaptive flow control. Defaults to false.
+    /// Enabling this will override the limits set in http2_initial_stream_window_size and
+    /// http2_initial_connection_window_size.
+    #[must_use]
+    pub fn http2_adaptive_window(self, enabled: Option<bool>) -> Self {
+        Server {
+            http2_adaptive_window: enabled,
+            ..self
+        }
+    }
+
+    /// Configures the maximum number of pending reset streams allowed before a GOAWAY will be sent.
+    ///
+    /// This will default to whatever the default in h2 is. As of v0.3.17, it is 20.
+    ///
+    /// The GOAWAY carries the `ENHANCE_YOUR_CALM` error code and `too_many_resets` as debug
+    /// data. GOAWAY frames sent on shutdown or once [`Server::max_connection_age`] is reached
+    /// carry `NO_ERROR`. Both can be customized with [`Server::http2_goaway`].
+    ///
+    /// See <https://github.com/hyperium/hyper/issues/2877> for more information.
+    #[must_use]
+    pub fn http2_max_pending_accept_reset_streams(self, max: Option<usize>) -> Self {
+        Server {
+            http2_max_pending_accept_reset_streams: max,
+            ..self
+        }
+    }
+
+    /// Set whether TCP keepalive messages are enabled on accepted connections.
+    ///
+    /// If `None` is specified, keepalive is disabled, otherwise the duration
+    /// specified will be the time to remain idle before sending TCP keepalive
+    /// probes.
+    ///
+    /// Default is no keepalive (`None`)
+    ///
+    #[must_use]
+    pub fn tcp_keepalive(self, tcp_keepalive: Option<Duration>) -> Self {
+        Server {
+            tcp_keepalive,
+            ..self
+        }
+    }
+
+    /// Set the value of `TCP_NODELAY` option for accepted connections. Enabled by default.
+    #[must_use]
+    pub fn tcp_nodelay(self, enabled: bool) -> Self {
+        Server {
+            tcp_nodelay: enabled,
+            ..self
+        }
+    }
+
+    /// Set the maximum number of pending connections the operating system queues on the listening
+    /// socket before refusing new ones.
+    ///
+    /// A larger backlog reduces refused connections when many clients connect at once. The
+    /// operating system may clamp the value, e.g. to `net.core.somaxconn` on Linux.
+    ///
+    /// Like the other TCP options, this is ignored by [`Router::serve_with_incoming`].
+    ///
+    /// Default is 1024.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.tcp_backlog(4096);
+    /// ```
+    #[must_use]
+    pub fn tcp_backlog(self, backlog: u32) -> Self {
+        Server {
+            tcp_backlog: Some(backlog),
+            ..self
+        }
+    }
+
+    /// Enable TCP Fast Open on the listening socket, with a queue of at most `queue_len`
+    /// connections whose handshake hasn't completed yet.
+    ///
+    /// Clients reconnecting to the server, and that enabled Fast Open themselves, then send their
+    /// first request along with the handshake, saving a round trip on every new connection. This
+    /// helps latency-sensitive clients that open short-lived connections often.
+    ///
+    /// Only supported on Linux and Android, where it also needs Fast Open to be enabled for
+    /// servers by the `net.ipv4.tcp_fastopen` sysctl. On other platforms this is ignored. Like
+    /// the other TCP options, this is ignored by [`Router::serve_with_incoming`].
+    ///
+    /// Default is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.tcp_fastopen(256);
+    /// ```
+    #[must_use]
+    pub fn tcp_fastopen(self, queue_len: u32) -> Self {
+        Server {
+            tcp_fastopen: Some(queue_len),
+            ..self
+        }
+    }
+
+    /// Set the number of tasks accepting connections.
+    ///
+    /// Under very high connection rates, a single accept loop can become the bottleneck. With more
+    /// than one worker, [`Router::serve`] binds one socket per worker to the same address with
+    /// `SO_REUSEPORT`, and each worker accepts connections from its socket on its own task, so
+    /// accepts run in parallel on a multi-threaded runtime. The kernel spreads new connections
+    /// over the sockets.
+    ///
+    /// Workers may accept a connection each while the services are not ready, see
+    /// [`Server::layer`]. On platforms without `SO_REUSEPORT`, like Windows, a single socket is
+    /// used.
+    ///
+    /// Like the other TCP options, this is ignored by [`Router::serve_with_incoming`].
+    ///
+    /// Default is 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.accept_workers(4);
+    /// ```
+    #[must_use]
+    pub fn accept_workers(self, workers: usize) -> Self {
+        Server {
+            accept_workers: workers,
+            ..self
+        }
+    }
+
+    /// Bind the listening socket with `SO_REUSEPORT`, so another instance of the server can
+    /// listen on the same address, for graceful restarts.
+    ///
+    /// When several sockets listen on the same port, the kernel spreads new connections over them,
+    /// so a new instance of the server can take over from an old one without a moment in which
+    /// connections are refused. The handoff goes as follows:
+    ///
+    /// 1. The old instance serves on an address with `reuse_port(true)` and
+    ///    [`Router::serve_with_shutdown`].
+    /// 2. The new instance serves on the same address with `reuse_port(true)`. Both instances
+    ///    now accept connections. Binding fails if the old instance runs as another user.
+    /// 3. Once [`Server::ready`] of the new instance resolved, it tells the old one to shut down
+    ///    through some channel of the deployment, e.g. a signal sent by the supervisor.
+    /// 4. The old instance's shutdown signal completes. It closes its listening socket, so new
+    ///    connections only reach the new instance, sends its clients a `GOAWAY` frame making them
+    ///    reconnect, and [`Router::serve_with_shutdown`] returns once the requests in flight
+    ///    completed. [`Server::stats`] tells how many connections are left while it drains.
+    ///
+    /// Connections the kernel queued on the old socket but the old instance did not accept yet are
+    /// reset when it closes the socket. On Linux, setting the `net.ipv4.tcp_migrate_req` sysctl
+    /// moves them to the new instance's socket instead.
+    ///
+    /// This is implied by more than one [`Server::accept_workers`]. On platforms without
+    /// `SO_REUSEPORT`, like Windows, this is ignored. Like the other TCP options, this is ignored by
+    /// [`Router::serve_with_incoming`].
+    ///
+    /// Default is `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.reuse_port(true);
+    /// ```
+    #[must_use]
+    pub fn reuse_port(self, enabled: bool) -> Self {
+        Server {
+            reuse_port: enabled,
+            ..self
+        }
+    }
+
+    /// Configure the listening socket with a custom callback.
+    ///
+    /// The callback runs once per listening socket, see [`Server::accept_workers`], after the
+    /// socket has been created and before it is bound, so it can set options that have no
+    /// dedicated builder method. An error returned by the callback is returned by
+    /// [`Router::serve`].
+    ///
+    /// Like the other TCP options, this is ignored by [`Router::serve_with_incoming`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.configure_listener(|socket| socket.set_recv_buffer_size(1024 * 1024));
+    /// ```
+    #[must_use]
+    pub fn configure_listener(
+        self,
+        f: impl Fn(&socket2::Socket) -> io::Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        Server {
+            configure_listener: Some(ConfigureSocket::new(f)),
+            ..self
+        }
+    }
+
+    /// Configure every accepted connection with a custom callback.
+    ///
+    /// The callback runs for each accepted socket, after [`Server::tcp_nodelay`] and
+    /// [`Server::tcp_keepalive`] have been applied. Connections for which the callback returns
+    /// an error are closed right away; the server keeps accepting new ones.
+    ///
+    /// Like the other TCP options, this is ignored by [`Router::serve_with_incoming`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.configure_socket(|socket| socket.set_send_buffer_size(1024 * 1024));
+    /// ```
+    #[must_use]
+    pub fn configure_socket(
+        self,
+        f: impl Fn(&socket2::Socket) -> io::Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        Server {
+            configure_socket: Some(ConfigureSocket::new(f)),
+            ..self
+        }
+    }
+
+    /// Pause accepting connections while `gate` returns `false`.
+    ///
+    /// The gate is evaluated before each accept, and every 50ms while it is closed, so it must be
+    /// cheap, e.g. reading a gauge of the process memory kept up to date elsewhere. While
+    /// paused, new connections wait in the listen queue, see [`Server::tcp_backlog`], and the
+    /// connections already accepted are served as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::sync::atomic::{AtomicU64, Ordering};
+    /// static MEMORY_USAGE: AtomicU64 = AtomicU64::new(0);
+    ///
+    /// # let builder = Server::builder();
+    /// builder.accept_gate(|| MEMORY_USAGE.load(Ordering::Relaxed) < 4 << 30);
+    /// ```
+    #[must_use]
+    pub fn accept_gate(self, gate: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        Server {
+            accept_gate: Some(Arc::new(gate)),
+            ..self
+        }
+    }
+
+    /// Limit the rate at which new connections are accepted to `per_sec`, allowing bursts of up to
+    /// `burst` connections.
+    ///
+    /// This protects the server from connection floods, which are expensive for TLS servers as
+    /// every connection starts with a handshake. Connections beyond the rate wait in the listen
+    /// queue until they are allowed, or until the queue overflows, see [`Server::tcp_backlog`].
+    /// The limit applies to connections, use [`Server::concurrency_limit_per_connection`] and
+    /// layers to limit requests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `per_sec` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.connection_rate_limit(100, 20);
+    /// ```
+    #[must_use]
+    pub fn connection_rate_limit(self, per_sec: u32, burst: u32) -> Self {
+        Server {
+            connection_rate_limit: Some(AcceptRate::new(per_sec, burst)),
+            ..self
+        }
+    }
+
+    /// Limit the number of open connections from a single peer IP address to `max`.
+    ///
+    /// This keeps one client from exhausting the connections the server can hold. Once a peer
+    /// address has `max` open connections, further connections from it are closed as soon as
+    /// they are accepted, before any TLS handshake, until one of its connections closes.
+    /// Connections without a peer address, e.g. over unix domain sockets, are not limited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.max_connections_per_ip(16);
+    /// ```
+    #[must_use]
+    pub fn max_connections_per_ip(self, max: usize) -> Self {
+        Server {
+            max_connections_per_ip: Some(max),
+            ..self
+        }
+    }
+
+    /// Sets the max size of received header frames.
+    ///
+    /// This will default to whatever the default in hyper is. As of v1.4.1, it is 16 KiB.
+    ///
+    /// The limit applies to the whole header block of a request, across the CONTINUATION frames
+    /// it is split into, so it also bounds the memory a client can make the server buffer while
+    /// decoding headers. Requests over the limit are refused, and connections that keep sending
+    /// CONTINUATION frames beyond it are closed with a GOAWAY (`ENHANCE_YOUR_CALM`).
+    #[must_use]
+    pub fn http2_max_header_list_size(self, max: impl Into<Option<u32>>) -> Self {
+        Server {
+            http2_max_header_list_size: max.into(),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of `HEADERS` frames a client may send per stream, counting the
+    /// headers and the trailers of a request, but not the CONTINUATION frames of each.
+    ///
+    /// Streams sending more are reset with `PROTOCOL_ERROR`, without affecting the other streams
+    /// of the connection. A request has at most two header blocks, so `2` only rejects streams
+    /// sending further ones, which would otherwise fail the whole connection, and `1` rejects
+    /// requests with trailers, which gRPC clients don't send.
+    ///
+    /// Default is unlimited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.http2_max_header_frames_per_stream(2);
+    /// ```
+    #[must_use]
+    pub fn http2_max_header_frames_per_stream(self, max: impl Into<Option<u32>>) -> Self {
+        Server {
+            http2_max_header_frames_per_stream: max.into(),
+            ..self
+        }
+    }
+
+    /// Set whether handlers can ping the client of their connection, through the
+    /// [`ConnectionPing`] in the extensions of every request.
+    ///
+    /// This watches the frame headers of every connection for as long as it is open, to write
+    /// the `PING` frames in between the frames of the connection, and to find their acks,
+    /// which are removed before they reach `hyper`, as it doesn't expect them.
+    ///
+    /// Default is `false`, so requests carry no [`ConnectionPing`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.http2_connection_ping(true);
+    /// ```
+    #[must_use]
+    pub fn http2_connection_ping(self, enabled: bool) -> Self {
+        Server {
+            http2_connection_ping: enabled,
+            ..self
+        }
+    }
+
+    /// Sets the size of the HPACK dynamic table used to decode request headers, in octets.
+    ///
+    /// Clients reuse the table to avoid resending header fields, so a larger table saves
+    /// bandwidth for clients that send many repeated metadata entries, at a cost of up to this
+    /// many bytes of memory per connection. Memory-constrained servers may shrink it instead.
+    ///
+    /// This will default to whatever the default in h2 is. As of v0.4, it is 4 KiB.
+    ///
+    /// Only applies to HTTP/2-only servers, it is ignored when [`accept_http1`] is enabled.
+    ///
+    /// [`accept_http1`]: Self::accept_http1
+    #[must_use]
+    pub fn http2_header_table_size(self, size: impl Into<Option<u32>>) -> Self {
+        Server {
+            http2_header_table_size: size.into(),
+            ..self
+        }
+    }
+
+    /// Sets all the HTTP/2 settings the server sends to clients at once, see [`Http2Settings`].
+    ///
+    /// This is for clients or proxies that need exact settings. The settings override the ones
+    /// made through the individual HTTP/2 options, i.e. [`Server::initial_stream_window_size`],
+    /// [`Server::initial_connection_window_size`], [`Server::max_concurrent_streams`],
+    /// [`Server::max_frame_size`], [`Server::http2_max_header_list_size`] and
+    /// [`Server::http2_header_table_size`], and disable [`Server::http2_adaptive_window`], which
+    /// would change the window sizes. [`Server::configure_hyper`] still applies on top of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::{server::Http2Settings, Server};
+    /// # let builder = Server::builder();
+    /// builder.http2_settings(
+    ///     Http2Settings::new()
+    ///         .max_concurrent_streams(100)
+    ///         .initial_window_size(1024 * 1024),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn http2_settings(self, settings: Http2Settings) -> Self {
+        Server {
+            http2_settings: Some(settings),
+            ..self
+        }
+    }
+
+    /// Sets the maximum frame size to use for HTTP2.
+    ///
+    /// This is the `SETTINGS_MAX_FRAME_SIZE` the server advertises, i.e. the largest frame payload
+    /// it accepts. Larger frames reduce the per-frame overhead of big streaming messages, while
+    /// smaller frames interleave the streams of a connection more fairly. The value must be
+    /// between 16 KiB and 16 MiB - 1, otherwise serving fails with an error.
+    ///
+    /// Passing `None` will do nothing.
+    ///
+    /// If not set, will default from underlying transport, which is 16 KiB.
+    #[must_use]
+    pub fn max_frame_size(self, frame_size: impl Into<Option<u32>>) -> Self {
+        Server {
+            max_frame_size: frame_size.into(),
+            ..self
+        }
+    }
+
+    /// Allow this server to accept http1 requests.
+    ///
+    /// Accepting http1 requests is only useful when developing `grpc-web`
+    /// enabled services. If this setting is set to `true` but services are
+    /// not correctly configured to handle grpc-web requests, your server may
+    /// return confusing (but correct) protocol errors.
+    ///
+    /// HTTP/2 connections are still served alongside HTTP/1.1 ones: the protocol is detected
+    /// from the HTTP/2 connection preface. HTTP/1.1 connections can also be upgraded to HTTP/2
+    /// through `Upgrade: h2c`, in which case the request that carried the upgrade is answered on
+    /// stream 1 of the HTTP/2 connection. Its headers and body must each fit in 16 KiB, and its
+    /// body must not be chunked, otherwise it is served over HTTP/1.1 without upgrading.
+    ///
+    /// Default is `false`.
+    #[must_use]
+    pub fn accept_http1(self, accept_http1: bool) -> Self {
+        Server {
+            accept_http1,
+            ..self
+        }
+    }
+
+    /// Configure the `hyper` connection builder directly, for options tonic doesn't wrap.
+    ///
+    /// `f` is called with the builder for the connections of the server, after tonic applied its
+    /// own settings, so it can change any option `hyper` offers. Which builder it gets depends on
+    /// [`Server::accept_http1`], see [`HyperBuilder`].
+    ///
+    /// Settings made here override the ones made through `Server`, e.g.
+    /// [`Server::max_concurrent_streams`], and are not checked for making sense for gRPC. In
+    /// particular, the builder of a server accepting HTTP/1.1 must keep serving HTTP/2: gRPC
+    /// doesn't work over HTTP/1.1, and HTTP/2-only servers rely on hyper's HTTP/2 builder.
+    ///
+    /// # Example
     ///
-    /// [spec]: https://httpwg.org/specs/rfc9113.html#InitialWindowSize
+    /// ```
+    /// # use tonic::transport::{server::HyperBuilder, Server};
+    /// # let builder = Server::builder();
+    /// builder.configure_hyper(|builder| {
+    ///     if let HyperBuilder::Http2(http2) = builder {
+    ///         http2.max_send_buf_size(1024 * 1024);
+    ///     }
+    /// });
+    /// ```
     #[must_use]
-    pub fn initial_stream_window_size(self, sz: impl Into<Option<u32>>) -> Self {
+    pub fn configure_hyper<F>(self, f: F) -> Self
+    where
+        F: Fn(HyperBuilder<'_>) + Send + Sync + 'static,
+    {
         Server {
-            init_stream_window_size: sz.into(),
+            configure_hyper: Some(Arc::new(f)),
             ..self
         }
     }
 
-    /// Sets the max connection-level flow control for HTTP2
+    /// Serve cleartext HTTP/2 with prior knowledge (h2c) only.
     ///
-    /// Default is 65,535
+    /// HTTP/2-only servers, i.e. without [`Server::accept_http1`], already expect clients to
+    /// start with the HTTP/2 connection preface, but drop connections that don't without a word.
+    /// In h2c mode, clients that send anything else, like HTTP/1.1 requests, including ones asking
+    /// to upgrade to HTTP/2 through `Upgrade: h2c`, get a `505 HTTP Version Not Supported`
+    /// response telling them so before their connection is closed. There is no fallback to
+    /// HTTP/1.1, so this overrides [`Server::accept_http1`].
+    ///
+    /// This is meant for plaintext listeners, e.g. in a mesh whose sidecars terminate TLS. TLS
+    /// connections, see [`Server::tls_config`], go through the same check after their handshake.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.h2c();
+    /// ```
     #[must_use]
-    pub fn initial_connection_window_size(self, sz: impl Into<Option<u32>>) -> Self {
-        Server {
-            init_connection_window_size: sz.into(),
-            ..self
-        }
+    pub fn h2c(self) -> Self {
+        Server { h2c: true, ..self }
     }
 
-    /// Sets the [`SETTINGS_MAX_CONCURRENT_STREAMS`][spec] option for HTTP2
-    /// connections.
+    /// Set whether responses carry a `date` header.
     ///
-    /// Default is no limit (`None`).
+    /// Default is `true`, as recommended by [RFC 9110].
     ///
-    /// [spec]: https://httpwg.org/specs/rfc9113.html#n-stream-concurrency
+    /// [RFC 9110]: https://www.rfc-editor.org/rfc/rfc9110#section-6.6.1
     #[must_use]
-    pub fn max_concurrent_streams(self, max: impl Into<Option<u32>>) -> Self {
+    pub fn date_header(self, enabled: bool) -> Self {
         Server {
-            max_concurrent_streams: max.into(),
+            date_header: enabled,
             ..self
         }
     }
 
-    /// Sets the maximum time option in milliseconds that a connection may exist
+    /// Set the `server` header of every response.
     ///
-    /// Default is no limit (`None`).
+    /// With `Some`, responses carry the given value, replacing any `server` header set by a
+    /// handler or layer. With `None`, the `server` header is removed from all responses, so the
+    /// server does not reveal what it runs on.
+    ///
+    /// By default, responses only carry a `server` header if a handler or layer sets one.
     ///
     /// # Example
     ///
     /// ```
     /// # use tonic::transport::Server;
-    /// # use tower_service::Service;
-    /// # use std::time::Duration;
+    /// # use http::HeaderValue;
     /// # let builder = Server::builder();
-    /// builder.max_connection_age(Duration::from_secs(60));
+    /// builder.server_header(Some(HeaderValue::from_static("acme")));
     /// ```
     #[must_use]
-    pub fn max_connection_age(self, max_connection_age: Duration) -> Self {
+    pub fn server_header(self, value: Option<HeaderValue>) -> Self {
         Server {
-            max_connection_age: Some(max_connection_age),
+            server_header: Some(value),
             ..self
         }
     }
 
-    /// Set whether HTTP2 Ping frames are enabled on accepted connections.
+    /// Set whether clients can ask for the time their calls spent in each phase of the server.
     ///
-    /// If `None` is specified, HTTP2 keepalive is disabled, otherwise the duration
-    /// specified will be the time interval between HTTP2 Ping frames.
-    /// The timeout for receiving an acknowledgement of the keepalive ping
-    /// can be set with [`Server::http2_keepalive_timeout`].
+    /// Once enabled, the responses to requests carrying an `x-server-timing` header end with a
+    /// `server-timing` trailer, in the format of the HTTP [`Server-Timing`] header, e.g.
+    /// `queue;dur=0.012, decode;dur=0.034, handler;dur=12.500, encode;dur=0.021` in milliseconds:
     ///
-    /// Default is no HTTP2 keepalive (`None`)
+    /// - `queue`: until the handler started, e.g. waiting for the
+    ///   [concurrency limit](Server::concurrency_limit_per_connection).
+    /// - `decode`: decoding the request messages, including decompressing them.
+    /// - `handler`: until the handler returned the response. The messages of a streaming response
+    ///   are produced later, and are not part of it.
+    /// - `encode`: encoding the response messages, including compressing them.
+    ///
+    /// This is meant for developers to see where the time of a call went, without correlating
+    /// the logs of the server. Calls failing without a response, which end with the status in
+    /// their headers, don't carry the trailer.
+    ///
+    /// Default is `false`.
+    ///
+    /// [`Server-Timing`]: https://www.w3.org/TR/server-timing/
+    ///
+    /// # Example
     ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.server_timing(true);
+    /// ```
     #[must_use]
-    pub fn http2_keepalive_interval(self, http2_keepalive_interval: Option<Duration>) -> Self {
+    pub fn server_timing(self, enabled: bool) -> Self {
         Server {
-            http2_keepalive_interval,
+            server_timing: enabled,
             ..self
         }
     }
 
-    /// Sets a timeout for receiving an acknowledgement of the keepalive ping.
+    /// Sets the executor used to spawn the tasks serving connections.
     ///
-    /// If the ping is not acknowledged within the timeout, the connection will be closed.
-    /// Does nothing if http2_keep_alive_interval is disabled.
+    /// Every accepted connection is served on its own task, and HTTP/2 connections spawn a task
+    /// per stream as well. This lets them run on a dedicated runtime, e.g. separate from the one
+    /// running compute-heavy work. Accepting connections and TLS handshakes still run on the task
+    /// the server is awaited on.
     ///
-    /// Default is 20 seconds.
+    /// Uses `tokio::spawn` by default.
+    ///
+    /// # Example
     ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::future::Future;
+    /// # use tokio::runtime::Handle;
+    /// /// Spawns tasks on another runtime.
+    /// #[derive(Clone)]
+    /// struct Spawn(Handle);
+    ///
+    /// impl<F> hyper::rt::Executor<F> for Spawn
+    /// where
+    ///     F: Future + Send + 'static,
+    ///     F::Output: Send + 'static,
+    /// {
+    ///     fn execute(&self, fut: F) {
+    ///         self.0.spawn(fut);
+    ///     }
+    /// }
+    ///
+    /// # fn io_runtime() -> Handle { unimplemented!() }
+    /// # fn example(builder: Server) {
+    /// builder.executor(Spawn(io_runtime()));
+    /// # }
+    /// ```
     #[must_use]
-    pub fn http2_keepalive_timeout(self, http2_keepalive_timeout: Option<Duration>) -> Self {
+    pub fn executor<E>(self, executor: E) -> Self
+    where
+        E: Executor<Pin<Box<dyn Future<Output = ()> + Send>>> + Send + Sync + 'static,
+    {
         Server {
-            http2_keepalive_timeout,
+            executor: SharedExec::new(executor),
             ..self
         }
     }
 
-    /// Sets whether to use an adaptive flow control. Defaults to false.
-    /// Enabling this will override the limits set in http2_initial_stream_window_size and
-    /// http2_initial_connection_window_size.
+    /// Intercept inbound headers and add a [`tracing::Span`] to each response future.
     #[must_use]
-    pub fn http2_adaptive_window(self, enabled: Option<bool>) -> Self {
+    pub fn trace_fn<F>(self, f: F) -> Self
+    where
+        F: Fn(&http::Request<()>) -> tracing::Span + Send + Sync + 'static,
+    {
         Server {
-            http2_adaptive_window: enabled,
+            trace_interceptor: Some(Arc::new(f)),
             ..self
         }
     }
 
-    /// Configures the maximum number of pending reset streams allowed before a GOAWAY will be sent.
+    /// Register how errors of type `E` returned by the services, e.g. by [layers], are turned
+    /// into the [`Status`] of the response.
     ///
-    /// This will default to whatever the default in h2 is. As of v0.3.17, it is 20.
+    /// The errors are downcast to the registered types, following their
+    /// [source](std::error::Error::source) chain, so a library can register its error types once
+    /// for all methods of the server. Registering a type again replaces its mapper. Errors
+    /// without a mapper are turned into a status as before, see [`Status::from_error`].
     ///
-    /// See <https://github.com/hyperium/hyper/issues/2877> for more information.
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::{transport::Server, Status};
+    /// #[derive(Debug)]
+    /// struct ValidationError(String);
+    ///
+    /// impl std::fmt::Display for ValidationError {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         write!(f, "invalid request: {}", self.0)
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for ValidationError {}
+    ///
+    /// # let builder = Server::builder();
+    /// builder.register_error_mapper::<ValidationError, _>(|err| {
+    ///     Status::invalid_argument(err.to_string())
+    /// });
+    /// ```
+    ///
+    /// [layers]: Server::layer
+    /// [`Status`]: crate::Status
+    /// [`Status::from_error`]: crate::Status::from_error
     #[must_use]
-    pub fn http2_max_pending_accept_reset_streams(self, max: Option<usize>) -> Self {
+    pub fn register_error_mapper<E, F>(self, f: F) -> Self
+    where
+        E: std::error::Error + 'static,
+        F: Fn(&E) -> crate::Status + Send + Sync + 'static,
+    {
+        let mut error_mappers = self.error_mappers;
+        error_mappers.register(f);
         Server {
-            http2_max_pending_accept_reset_streams: max,
+            error_mappers,
             ..self
         }
     }
 
-    /// Set whether TCP keepalive messages are enabled on accepted connections.
+    /// Returns a handle to the statistics collected by this server.
     ///
-    /// If `None` is specified, keepalive is disabled, otherwise the duration
-    /// specified will be the time to remain idle before sending TCP keepalive
-    /// probes.
+    /// The handle is shared with every [`Router`] created from this builder, so
+    /// it can be retrieved before serving and polled while the server runs, for
+    /// example from an admin endpoint.
     ///
-    /// Default is no keepalive (`None`)
+    /// # Example
     ///
-    #[must_use]
-    pub fn tcp_keepalive(self, tcp_keepalive: Option<Duration>) -> Self {
-        Server {
-            tcp_keepalive,
-            ..self
-        }
+    /// ```
+    /// # use tonic::transport::Server;
+    /// let builder = Server::builder();
+    /// let stats = builder.stats();
+    ///
+    /// // ... serve from `builder` ...
+    ///
+    /// let snapshot = stats.snapshot();
+    /// println!("active connections: {}", snapshot.active_connections());
+    /// ```
+    pub fn stats(&self) -> ServerStats {
+        self.stats.clone()
     }
 
-    /// Set the value of `TCP_NODELAY` option for accepted connections. Enabled by default.
-    #[must_use]
-    pub fn tcp_nodelay(self, enabled: bool) -> Self {
-        Server {
-            tcp_nodelay: enabled,
-            ..self
-        }
+    /// Returns a handle to close the connections of this server, or pause accepting new ones, at
+    /// runtime.
+    ///
+    /// Like [`Server::stats`], the handle is shared with every [`Router`] created from this
+    /// builder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::net::IpAddr;
+    /// let builder = Server::builder();
+    /// let connections = builder.connections();
+    ///
+    /// // ... serve from `builder` ...
+    ///
+    /// let abuser: IpAddr = "192.0.2.1".parse().unwrap();
+    /// connections.close_connections(|peer| peer.ip() == abuser);
+    ///
+    /// // Keep the listener open, but leave new connections in its queue for a while.
+    /// connections.pause_accepting();
+    /// connections.resume_accepting();
+    /// ```
+    pub fn connections(&self) -> ConnectionControl {
+        self.connections.clone()
     }
 
-    /// Sets the max size of received header frames.
+    /// Returns a handle to refuse new streams at runtime, while keeping the connections open.
     ///
-    /// This will default to whatever the default in hyper is. As of v1.4.1, it is 16 KiB.
-    #[must_use]
-    pub fn http2_max_header_list_size(self, max: impl Into<Option<u32>>) -> Self {
-        Server {
-            http2_max_header_list_size: max.into(),
-            ..self
-        }
+    /// Refused streams are reset with `REFUSED_STREAM`, so clients can retry them elsewhere.
+    /// Like [`Server::stats`], the handle is shared with every [`Router`] created from this
+    /// builder. Requests over HTTP/1, see [`Server::accept_http1`], fail their connection
+    /// instead.
+    pub fn stream_admission(&self) -> StreamAdmission {
+        self.admission.clone()
     }
 
-    /// Sets the maximum frame size to use for HTTP2.
+    /// Returns a future that resolves once the server can serve requests.
     ///
-    /// Passing `None` will do nothing.
+    /// That is once its listener is bound and the service stack, including all layers, reported
+    /// readiness, so an orchestrator can gate traffic on it, e.g. with a readiness probe. Like
+    /// [`Server::stats`], this is shared with every [`Router`] created from this builder, so it
+    /// must be retrieved before serving. The server stops being ready once it stops accepting
+    /// connections, e.g. on shutdown, so the future doesn't resolve after that.
     ///
-    /// If not set, will default from underlying transport.
-    #[must_use]
-    pub fn max_frame_size(self, frame_size: impl Into<Option<u32>>) -> Self {
-        Server {
-            max_frame_size: frame_size.into(),
-            ..self
-        }
-    }
-
-    /// Allow this server to accept http1 requests.
+    /// # Example
     ///
-    /// Accepting http1 requests is only useful when developing `grpc-web`
-    /// enabled services. If this setting is set to `true` but services are
-    /// not correctly configured to handle grpc-web requests, your server may
-    /// return confusing (but correct) protocol errors.
+    /// ```no_run
+    /// # use tonic::transport::Server;
+    /// # use tonic::service::Routes;
+    /// # async {
+    /// let mut builder = Server::builder();
+    /// let ready = builder.ready();
     ///
-    /// Default is `false`.
-    #[must_use]
-    pub fn accept_http1(self, accept_http1: bool) -> Self {
-        Server {
-            accept_http1,
-            ..self
+    /// let router = builder.add_routes(Routes::default());
+    /// tokio::spawn(router.serve("[::1]:50051".parse().unwrap()));
+    ///
+    /// ready.await;
+    /// // ... report the server as ready ...
+    /// # };
+    /// ```
+    pub fn ready(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut readiness = self.readiness.subscribe();
+        async move {
+            if readiness.wait_for(|ready| *ready).await.is_err() {
+                pending().await
+            }
         }
     }
 
-    /// Intercept inbound headers and add a [`tracing::Span`] to each response future.
-    #[must_use]
-    pub fn trace_fn<F>(self, f: F) -> Self
-    where
-        F: Fn(&http::Request<()>) -> tracing::Span + Send + Sync + 'static,
-    {
-        Server {
-            trace_interceptor: Some(Arc::new(f)),
-            ..self
+    /// Returns a future that resolves with the [`ShutdownReport`] once a graceful shutdown of
+    /// the server completed, i.e. all its connections are closed.
+    ///
+    /// Like [`Server::ready`], this is shared with every [`Router`] created from this builder,
+    /// so it must be retrieved before serving. It resolves with the report of the first
+    /// shutdown, even if that completed before the future was polled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tonic::transport::Server;
+    /// # use tonic::service::Routes;
+    /// # async {
+    /// let mut builder = Server::builder();
+    /// let shutdown = builder.shutdown_complete();
+    ///
+    /// let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    /// let router = builder.add_routes(Routes::default());
+    /// tokio::spawn(router.serve_with_shutdown("[::1]:50051".parse().unwrap(), async {
+    ///     drop(rx.await);
+    /// }));
+    ///
+    /// // ... on a shutdown signal ...
+    /// tx.send(()).unwrap();
+    ///
+    /// let report = shutdown.await;
+    /// println!("drained {} requests in {:?}", report.drained(), report.elapsed());
+    /// # };
+    /// ```
+    pub fn shutdown_complete(&self) -> impl Future<Output = ShutdownReport> + Send + 'static {
+        let mut shutdown = self.shutdown.subscribe();
+        async move {
+            let report = match shutdown.wait_for(Option::is_some).await {
+                Ok(report) => report.clone(),
+                Err(_) => None,
+            };
+            match report {
+                Some(report) => report,
+                None => pending().await,
+            }
         }
     }
 
@@ -437,6 +2023,10 @@ impl<L> Server<L> {
     ///
     /// This enables using middleware from the [Tower ecosystem][eco].
     ///
+    /// The server waits for the layered service to report readiness before it accepts another
+    /// connection, so layers like a shared concurrency limit apply backpressure to new connections
+    /// while they are overloaded. Connections that were already accepted are served as usual.
+    ///
     /// # Example
     ///
     /// ```
@@ -499,31 +2089,121 @@ impl<L> Server<L> {
             service_builder: self.service_builder.layer(new_layer),
             trace_interceptor: self.trace_interceptor,
             concurrency_limit: self.concurrency_limit,
+            cost_fn: self.cost_fn,
+            method_limits: self.method_limits,
             timeout: self.timeout,
+            default_timeout: self.default_timeout,
+            max_request_messages: self.max_request_messages,
+            max_response_messages: self.max_response_messages,
+            streaming_flush_mode: self.streaming_flush_mode,
+            metadata_limits: self.metadata_limits,
+            stream_read_timeout: self.stream_read_timeout,
             #[cfg(feature = "_tls-any")]
             tls: self.tls,
+            #[cfg(feature = "_tls-any")]
+            max_concurrent_handshakes: self.max_concurrent_handshakes,
+            #[cfg(feature = "_tls-any")]
+            max_pending_handshakes_per_ip: self.max_pending_handshakes_per_ip,
             init_stream_window_size: self.init_stream_window_size,
             init_connection_window_size: self.init_connection_window_size,
             max_concurrent_streams: self.max_concurrent_streams,
             tcp_keepalive: self.tcp_keepalive,
             tcp_nodelay: self.tcp_nodelay,
+            tcp_backlog: self.tcp_backlog,
+            tcp_fastopen: self.tcp_fastopen,
+            accept_workers: self.accept_workers,
+            reuse_port: self.reuse_port,
+            configure_listener: self.configure_listener,
+            configure_socket: self.configure_socket,
+            accept_gate: self.accept_gate,
+            configure_hyper: self.configure_hyper,
+            connection_rate_limit: self.connection_rate_limit,
+            max_connections_per_ip: self.max_connections_per_ip,
             http2_keepalive_interval: self.http2_keepalive_interval,
             http2_keepalive_timeout: self.http2_keepalive_timeout,
             http2_adaptive_window: self.http2_adaptive_window,
             http2_max_pending_accept_reset_streams: self.http2_max_pending_accept_reset_streams,
             http2_max_header_list_size: self.http2_max_header_list_size,
+            http2_max_header_frames_per_stream: self.http2_max_header_frames_per_stream,
+            http2_connection_ping: self.http2_connection_ping,
+            http2_header_table_size: self.http2_header_table_size,
+            http2_settings: self.http2_settings,
             max_frame_size: self.max_frame_size,
             accept_http1: self.accept_http1,
+            h2c: self.h2c,
+            executor: self.executor,
+            date_header: self.date_header,
+            server_header: self.server_header,
+            server_timing: self.server_timing,
             max_connection_age: self.max_connection_age,
+            on_goaway_sent: self.on_goaway_sent,
+            http2_goaway: self.http2_goaway,
+            on_handshake_error: self.on_handshake_error,
+            on_connect: self.on_connect,
+            intercept_connections: self.intercept_connections,
+            resolve_path: self.resolve_path,
+            method_paths: self.method_paths,
+            http2_settings_timeout: self.http2_settings_timeout,
+            max_requests_per_connection: self.max_requests_per_connection,
+            http2_stream_id_threshold: self.http2_stream_id_threshold,
+            stats: self.stats,
+            connections: self.connections,
+            admission: self.admission,
+            readiness: self.readiness,
+            graceful_shutdown_timeout: self.graceful_shutdown_timeout,
+            error_mappers: self.error_mappers,
+            shutdown: self.shutdown,
+        }
+    }
+
+    /// Drops the TLS configuration, for connections that are already decrypted.
+    fn without_tls(self) -> Self {
+        Server {
+            #[cfg(feature = "_tls-any")]
+            tls: None,
+            ..self
+        }
+    }
+
+    fn check_max_frame_size(&self) -> Result<(), super::Error> {
+        match self
+            .max_frame_size
+            .filter(|size| !HTTP2_MAX_FRAME_SIZES.contains(size))
+        {
+            Some(size) => Err(super::Error::from_source(format!(
+                "invalid HTTP/2 max frame size {}, it must be between {} and {}",
+                size,
+                HTTP2_MAX_FRAME_SIZES.start(),
+                HTTP2_MAX_FRAME_SIZES.end()
+            ))),
+            None => Ok(()),
         }
     }
 
+    fn bind(&self, addr: SocketAddr) -> Result<TcpIncoming, super::Error> {
+        TcpIncoming::bind_workers(
+            addr,
+            self.accept_workers,
+            self.reuse_port,
+            self.tcp_backlog,
+            self.tcp_fastopen,
+            self.configure_listener.as_ref(),
+            |incoming| {
+                incoming
+                    .with_nodelay(Some(self.tcp_nodelay))
+                    .with_keepalive(self.tcp_keepalive)
+                    .with_configure_socket(self.configure_socket.clone())
+            },
+        )
+        .map_err(super::Error::new_bind)
+    }
+
     pub(crate) async fn serve_with_shutdown<S, I, F, IO, IE, ResBody>(
         self,
         svc: S,
         incoming: I,
         signal: Option<F>,
-    ) -> Result<(), super::Error>
+    ) -> Result<ShutdownReport, super::Error>
     where
         L: Layer<S>,
         L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
@@ -537,15 +2217,33 @@ impl<L> Server<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::BoxError>,
     {
+        self.check_max_frame_size()?;
+
         let trace_interceptor = self.trace_interceptor.clone();
         let concurrency_limit = self.concurrency_limit;
+        let cost_fn = self.cost_fn.clone();
+        let method_limits = self.method_limits.clone();
         let init_connection_window_size = self.init_connection_window_size;
         let init_stream_window_size = self.init_stream_window_size;
         let max_concurrent_streams = self.max_concurrent_streams;
         let timeout = self.timeout;
+        let default_timeout = self.default_timeout;
+        let max_request_messages = self.max_request_messages;
+        let max_response_messages = self.max_response_messages;
+        let streaming_flush_mode = self.streaming_flush_mode;
+        let metadata_limits = self.metadata_limits;
+        let stream_read_timeout = self.stream_read_timeout;
         let max_header_list_size = self.http2_max_header_list_size;
+        let header_table_size = self.http2_header_table_size;
+        let http2_settings = self.http2_settings;
         let max_frame_size = self.max_frame_size;
-        let http2_only = !self.accept_http1;
+        let h2c = self.h2c;
+        let configure_hyper = self.configure_hyper;
+        let executor = self.executor;
+        let http2_only = !self.accept_http1 || h2c;
+        let date_header = self.date_header;
+        let server_header = self.server_header.clone();
+        let server_timing = self.server_timing;
 
         let http2_keepalive_interval = self.http2_keepalive_interval;
         let http2_keepalive_timeout = self
@@ -554,61 +2252,199 @@ impl<L> Server<L> {
         let http2_adaptive_window = self.http2_adaptive_window;
         let http2_max_pending_accept_reset_streams = self.http2_max_pending_accept_reset_streams;
         let max_connection_age = self.max_connection_age;
+        let on_goaway_sent = self.on_goaway_sent;
+        let http2_goaway = self.http2_goaway;
+        let on_handshake_error = self.on_handshake_error;
+        let on_connect = self.on_connect;
+        let intercept_connections = self.intercept_connections;
+        let resolve_path = self.resolve_path;
+        let method_paths = self.method_paths;
+        let max_header_frames_per_stream = self.http2_max_header_frames_per_stream;
+        let connection_ping = self.http2_connection_ping;
+        let http2_settings_timeout = self.http2_settings_timeout;
+        let max_requests_per_connection = self.max_requests_per_connection;
+        let http2_stream_id_threshold = self.http2_stream_id_threshold;
+        let stats = self.stats;
+        let connections = self.connections;
+        let admission = self.admission;
+        let readiness = self.readiness;
+        let graceful_shutdown_timeout = self.graceful_shutdown_timeout;
+        let error_mappers = self.error_mappers;
+        let shutdown = self.shutdown;
+        let accept_gate = self.accept_gate;
 
         let svc = self.service_builder.service(svc);
 
         let incoming = io_stream::ServerIoStream::new(
-            incoming,
+            IpLimitedIncoming::new(
+                RateLimitedIncoming::new(incoming, self.connection_rate_limit),
+                self.max_connections_per_ip,
+            ),
             #[cfg(feature = "_tls-any")]
             self.tls,
+            #[cfg(feature = "_tls-any")]
+            self.max_concurrent_handshakes,
+            #[cfg(feature = "_tls-any")]
+            self.max_pending_handshakes_per_ip,
+            #[cfg(feature = "_tls-any")]
+            on_handshake_error.clone(),
         );
         let mut svc = MakeSvc {
             inner: svc,
             concurrency_limit,
+            cost_fn,
+            method_limits,
+            admission,
             timeout,
+            default_timeout,
+            max_request_messages,
+            max_response_messages,
+            streaming_flush_mode,
+            metadata_limits,
+            stream_read_timeout,
+            server_header,
+            server_timing,
             trace_interceptor,
+            error_mappers,
+            on_connect,
+            resolve_path,
+            method_paths,
+            probe: None,
+            stats: stats.clone(),
             _io: PhantomData,
         };
 
-        let server = {
-            let mut builder = ConnectionBuilder::new(TokioExecutor::new());
+        // Applies the HTTP/2 settings shared by both connection builders.
+        macro_rules! http2_settings {
+            ($builder:expr) => {{
+                let builder = $builder
+                    .timer(TokioTimer::new())
+                    .initial_connection_window_size(init_connection_window_size)
+                    .initial_stream_window_size(init_stream_window_size)
+                    .max_concurrent_streams(max_concurrent_streams)
+                    .keep_alive_interval(http2_keepalive_interval)
+                    .keep_alive_timeout(http2_keepalive_timeout)
+                    .adaptive_window(http2_adaptive_window.unwrap_or_default())
+                    .max_pending_accept_reset_streams(http2_max_pending_accept_reset_streams)
+                    .max_frame_size(max_frame_size)
+                    .auto_date_header(date_header);
+
+                if let Some(max_header_list_size) = max_header_list_size {
+                    builder.max_header_list_size(max_header_list_size);
+                }
+
+                if let Some(settings) = http2_settings {
+                    builder
+                        .adaptive_window(false)
+                        .initial_connection_window_size(settings.initial_connection_window_size)
+                        .initial_stream_window_size(settings.initial_window_size)
+                        .max_concurrent_streams(settings.max_concurrent_streams)
+                        .max_frame_size(settings.max_frame_size)
+                        .max_header_list_size(settings.max_header_list_size);
+                    if settings.enable_connect_protocol {
+                        builder.enable_connect_protocol();
+                    }
+                }
+            }};
+        }
 
-            if http2_only {
-                builder = builder.http2_only();
+        let server = if http2_only {
+            let mut builder = Http2Builder::new(executor.clone());
+            http2_settings!(builder);
+            builder.header_table_size(http2_settings.map_or(header_table_size, |settings| {
+                Some(settings.header_table_size)
+            }));
+            if let Some(configure_hyper) = &configure_hyper {
+                configure_hyper(HyperBuilder::Http2(&mut builder));
             }
 
-            builder
-                .http2()
-                .timer(TokioTimer::new())
-                .initial_connection_window_size(init_connection_window_size)
-                .initial_stream_window_size(init_stream_window_size)
-                .max_concurrent_streams(max_concurrent_streams)
-                .keep_alive_interval(http2_keepalive_interval)
-                .keep_alive_timeout(http2_keepalive_timeout)
-                .adaptive_window(http2_adaptive_window.unwrap_or_default())
-                .max_pending_accept_reset_streams(http2_max_pending_accept_reset_streams)
-                .max_frame_size(max_frame_size);
-
-            if let Some(max_header_list_size) = max_header_list_size {
-                builder.http2().max_header_list_size(max_header_list_size);
+            ConnectionBuilder::Http2(builder)
+        } else {
+            let mut builder = AutoBuilder::new(executor.clone());
+            builder.http1().auto_date_header(date_header);
+            let mut http2 = builder.http2();
+            http2_settings!(http2);
+            if let Some(configure_hyper) = &configure_hyper {
+                configure_hyper(HyperBuilder::Auto(&mut builder));
             }
 
-            builder
+            ConnectionBuilder::Auto(builder)
         };
 
         let (signal_tx, signal_rx) = tokio::sync::watch::channel(());
         let signal_tx = Arc::new(signal_tx);
+        let force_close = CancellationToken::new();
 
         let graceful = signal.is_some();
         let mut sig = pin!(Fuse { inner: signal });
-        let mut incoming = pin!(incoming);
+        let settings = ConnectionSettings {
+            builder: server,
+            executor,
+            force_close: force_close.clone(),
+            max_connection_age,
+            http2_settings_timeout,
+            h2c,
+            on_goaway_sent,
+            http2_goaway,
+            on_handshake_error,
+            max_header_frames_per_stream,
+            intercept_connections,
+        };
+
+        // Boxed to close the listener as soon as the server stops accepting connections.
+        let mut incoming = Box::pin(incoming);
+        let mut paused = connections.watch_paused();
+
+        'accept: loop {
+            // Only accept a connection once the services can take requests, so an overloaded
+            // server leaves new connections in the listen queue.
+            tokio::select! {
+                _ = &mut sig => {
+                    trace!("signal received, shutting down");
+                    break;
+                },
+                ready = poll_fn(|cx| svc.poll_ready(cx)) => {
+                    ready.map_err(super::Error::new_serve)?;
+                    readiness.send_if_modified(|ready| !std::mem::replace(ready, true));
+                },
+            }
+
+            if let Some(accept_gate) = &accept_gate {
+                if !accept_gate() {
+                    debug!("accept gate closed, pausing accepts");
+                    while !accept_gate() {
+                        tokio::select! {
+                            _ = &mut sig => {
+                                trace!("signal received, shutting down");
+                                break 'accept;
+                            },
+                            _ = sleep(ACCEPT_GATE_INTERVAL) => {},
+                        }
+                    }
+                    debug!("accept gate opened, resuming accepts");
+                }
+            }
+
+            let is_paused = *paused.borrow_and_update();
+            if is_paused {
+                debug!("accepts paused");
+                tokio::select! {
+                    _ = &mut sig => {
+                        trace!("signal received, shutting down");
+                        break;
+                    },
+                    _ = wait_for_paused(&mut paused, false) => {},
+                }
+                debug!("accepts resumed");
+            }
 
-        loop {
             tokio::select! {
                 _ = &mut sig => {
                     trace!("signal received, shutting down");
                     break;
                 },
+                // Stop waiting for a connection as soon as accepts are paused.
+                _ = wait_for_paused(&mut paused, true) => continue,
                 io = incoming.next() => {
                     let io = match io {
                         Some(Ok(io)) => io,
@@ -623,23 +2459,59 @@ impl<L> Server<L> {
 
                     trace!("connection accepted");
 
-                    poll_fn(|cx| svc.poll_ready(cx))
-                        .await
-                        .map_err(super::Error::from_source)?;
+                    let remote_addr = io.peer_info().remote_addr();
+
+                    let connection = connections.register(remote_addr);
 
                     let req_svc = svc
                         .call(&io)
                         .await
-                        .map_err(super::Error::from_source)?;
-
-                    let hyper_io = TokioIo::new(io);
-                    let hyper_svc = TowerToHyperService::new(req_svc.map_request(|req: Request<Incoming>| req.map(Body::new)));
+                        .map_err(super::Error::new_serve)?;
+
+                    let request_limit = RequestLimit::new(
+                        max_requests_per_connection
+                            .map_or(http2_stream_id_threshold, |max| max.min(http2_stream_id_threshold)),
+                    );
+
+                    let ping = connection_ping.then(ConnectionPing::default);
+                    let upgrade = (!http2_only).then(PendingUpgrade::default);
+                    let req_svc = req_svc.map_request({
+                        let request_limit = request_limit.clone();
+                        let ping = ping.clone();
+                        move |mut req: Request<Incoming>| {
+                            request_limit.record();
+                            if let Some(ping) = &ping {
+                                req.extensions_mut().insert(ping.clone());
+                            }
+                            req.map(Body::new)
+                        }
+                    });
+                    let hyper_svc = TowerToHyperService::new(UpgradeH2c::new(req_svc, upgrade.clone()));
+
+                    let accepted = AcceptedConnection {
+                        watcher: graceful.then(|| signal_rx.clone()),
+                        upgrade,
+                        request_limit,
+                        guard: stats.connection_opened(),
+                        handle: connection,
+                        ping,
+                        remote_addr,
+                    };
 
-                    serve_connection(hyper_io, hyper_svc, server.clone(), graceful.then(|| signal_rx.clone()), max_connection_age);
+                    serve_connection(io, hyper_svc, settings.clone(), accepted);
                 }
             }
         }
 
+        // Stop listening right away, so new connections reach the other instances sharing the
+        // port instead of waiting in the listen queue while the connections drain.
+        drop(incoming);
+        readiness.send_replace(false);
+
+        let drain = Drain::start(&stats);
+        let mut outcome = ShutdownOutcome::Drained;
+        let mut aborted = 0;
+
         if graceful {
             let _ = signal_tx.send(());
             drop(signal_rx);
@@ -649,66 +2521,429 @@ impl<L> Server<L> {
             );
 
             // Wait for all connections to close
-            signal_tx.closed().await;
+            let drained = match graceful_shutdown_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, signal_tx.closed())
+                    .await
+                    .is_ok(),
+                None => {
+                    signal_tx.closed().await;
+                    true
+                }
+            };
+
+            if !drained {
+                outcome = ShutdownOutcome::Forced;
+                aborted = stats.active_requests();
+                debug!(
+                    "graceful shutdown timed out, closing {} connections",
+                    signal_tx.receiver_count()
+                );
+                force_close.cancel();
+                signal_tx.closed().await;
+            }
         }
 
-        Ok(())
-    }
+        let report = drain.finish(outcome, aborted);
+        debug!(
+            "drained {} requests in {:?}, aborted {}",
+            report.drained(),
+            report.elapsed(),
+            report.aborted()
+        );
+        shutdown.send_if_modified(|shutdown| {
+            let first = shutdown.is_none();
+            if first {
+                *shutdown = Some(report.clone());
+            }
+            first
+        });
+
+        Ok(report)
+    }
+}
+
+/// The settings shared by all connections of a server.
+#[derive(Clone)]
+struct ConnectionSettings {
+    builder: ConnectionBuilder,
+    executor: SharedExec,
+    force_close: CancellationToken,
+    max_connection_age: Option<Duration>,
+    http2_settings_timeout: Option<Duration>,
+    h2c: bool,
+    on_goaway_sent: Option<GoAwayHook>,
+    http2_goaway: Option<Http2GoAway>,
+    on_handshake_error: Option<HandshakeErrorHook>,
+    max_header_frames_per_stream: Option<u32>,
+    intercept_connections: Option<InterceptConnections>,
+}
+
+/// The values specific to a single accepted connection.
+struct AcceptedConnection {
+    watcher: Option<tokio::sync::watch::Receiver<()>>,
+    upgrade: Option<PendingUpgrade>,
+    request_limit: RequestLimit,
+    guard: ConnectionGuard,
+    handle: ConnectionHandle,
+    ping: Option<ConnectionPing>,
+    remote_addr: Option<SocketAddr>,
 }
 
 // This is moved to its own function as a way to get around
 // https://github.com/rust-lang/rust/issues/102211
-fn serve_connection<B, IO, S, E>(
-    hyper_io: IO,
+fn serve_connection<B, IO, S>(
+    io: IO,
     hyper_svc: S,
-    builder: ConnectionBuilder<E>,
-    mut watcher: Option<tokio::sync::watch::Receiver<()>>,
-    max_connection_age: Option<Duration>,
+    settings: ConnectionSettings,
+    accepted: AcceptedConnection,
 ) where
     B: http_body::Body + Send + 'static,
     B::Data: Send,
     B::Error: Into<Box<dyn std::error::Error + Send + Sync>> + Send + Sync,
-    IO: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     S: HyperService<Request<Incoming>, Response = Response<B>> + Clone + Send + 'static,
     S::Future: Send + 'static,
     S::Error: Into<Box<dyn std::error::Error + Send + Sync>> + Send,
-    E: HttpServerConnExec<S::Future, B> + Send + Sync + 'static,
 {
-    tokio::spawn(async move {
+    let ConnectionSettings {
+        builder,
+        executor,
+        force_close,
+        max_connection_age,
+        http2_settings_timeout,
+        h2c,
+        on_goaway_sent,
+        http2_goaway,
+        on_handshake_error,
+        max_header_frames_per_stream,
+        intercept_connections,
+    } = settings;
+    let AcceptedConnection {
+        mut watcher,
+        upgrade,
+        request_limit,
+        guard: connection_guard,
+        handle: connection_handle,
+        ping,
+        remote_addr,
+    } = accepted;
+    let on_handshake_error = on_handshake_error.map(|hook| (hook, remote_addr));
+
+    executor.execute(async move {
+        let io = match intercept_connections {
+            Some(intercept) => {
+                let intercepted = tokio::select! {
+                    io = intercept(BoxedIo::new(io)) => io,
+                    _ = force_close.cancelled() => None,
+                };
+                match intercepted {
+                    Some(io) => Either::Right(io),
+                    None => {
+                        trace!("connection taken over by the interceptor");
+                        return;
+                    }
+                }
+            }
+            None => Either::Left(io),
+        };
+
+        let io = if h2c {
+            let accepted = tokio::select! {
+                io = h2c::accept(io) => io,
+                _ = sleep_or_pending(http2_settings_timeout) => {
+                    debug!("client did not send its HTTP/2 connection preface in time, closing");
+                    None
+                },
+                _ = async {
+                    match watcher.as_mut() {
+                        Some(watcher) => drop(watcher.changed().await),
+                        None => pending().await,
+                    }
+                } => None,
+                _ = force_close.cancelled() => None,
+            };
+            match accepted {
+                Some(io) => io,
+                None => return,
+            }
+        } else {
+            Rewind::new(io)
+        };
+
+        // The first reason for closing the connection gracefully, as the connection sends
+        // `GOAWAY` frames only for the first one.
+        let goaway_reason = Arc::new(OnceLock::new());
+        let rewrite_goaway = http2_goaway.map(|f| {
+            let goaway_reason = goaway_reason.clone();
+            Arc::new(move |go_away: GoAway| {
+                let reason = match goaway_reason.get() {
+                    Some(reason) if go_away.is_graceful() => *reason,
+                    _ => GoAwayReason::Error,
+                };
+                f(reason, go_away)
+            }) as GoAwayRewrite
+        });
+
+        let preface_done = (http2_settings_timeout.is_some() || on_handshake_error.is_some())
+            .then(PrefaceDone::default);
+        let io = FramesIo::wrap(
+            io,
+            Watch {
+                preface: preface_done.clone(),
+                max_header_frames_per_stream,
+                ping,
+                on_goaway_sent: on_goaway_sent.clone(),
+                rewrite_goaway: rewrite_goaway.clone(),
+            },
+        );
+        let preface = http2_settings_timeout.zip(preface_done.clone());
+        let handshake_failed = |err: crate::BoxError| {
+            if let Some((hook, remote_addr)) = &on_handshake_error {
+                if !preface_done.as_ref().is_some_and(PrefaceDone::established) {
+                    hook(*remote_addr, &super::Error::from_source(err));
+                }
+            }
+        };
+        let hyper_io = TokioIo::new(io);
+
         {
             let mut sig = pin!(Fuse {
                 inner: watcher.as_mut().map(|w| w.changed()),
             });
 
-            let mut conn = pin!(builder.serve_connection(hyper_io, hyper_svc));
+            let mut conn: Pin<Box<dyn ServeConnection + '_>> =
+                Box::pin(builder.serve_connection(hyper_io, hyper_svc.clone()));
+            let mut draining = false;
 
             let sleep = sleep_or_pending(max_connection_age);
             tokio::pin!(sleep);
 
+            let preface_deadline = sleep_or_pending(preface.as_ref().map(|(timeout, _)| *timeout));
+            tokio::pin!(preface_deadline);
+
+            let mut limit_reached = pin!(Fuse {
+                inner: Some(request_limit.reached.notified()),
+            });
+
             loop {
                 tokio::select! {
                     rv = &mut conn => {
                         if let Err(err) = rv {
                             debug!("failed serving connection: {:#}", err);
+                            handshake_failed(err);
+                            break;
                         }
-                        break;
+
+                        // The HTTP/1.1 side of a connection upgrading to h2c is done, so it
+                        // is served as HTTP/2 from now on.
+                        let Some(upgrade) = upgrade.as_ref().and_then(PendingUpgrade::take) else {
+                            break;
+                        };
+                        if draining {
+                            debug!("connection is closing, not upgrading it to h2c");
+                            break;
+                        }
+                        let upgraded = tokio::select! {
+                            io = h2c::upgraded(upgrade) => io,
+                            _ = sleep_or_pending(http2_settings_timeout) => {
+                                debug!("client did not send its HTTP/2 connection preface in time, closing");
+                                None
+                            },
+                            _ = force_close.cancelled() => None,
+                        };
+                        let Some(io) = upgraded else {
+                            break;
+                        };
+                        let io = FramesIo::wrap(
+                            io,
+                            Watch {
+                                max_header_frames_per_stream,
+                                on_goaway_sent: on_goaway_sent.clone(),
+                                rewrite_goaway: rewrite_goaway.clone(),
+                                ..Watch::default()
+                            },
+                        );
+                        conn = Box::pin(builder.serve_connection(TokioIo::new(io), hyper_svc.clone()));
                     },
                     _ = &mut sleep  => {
+                        let _ = goaway_reason.set(GoAwayReason::MaxConnectionAge);
+                        draining = true;
                         conn.as_mut().graceful_shutdown();
                         sleep.set(sleep_or_pending(None));
                     },
+                    _ = &mut preface_deadline => {
+                        if preface.as_ref().is_some_and(|(_, done)| !done.get()) {
+                            debug!("client did not send its HTTP/2 settings in time, closing");
+                            handshake_failed(crate::TimeoutExpired(()).into());
+                            break;
+                        }
+                        preface_deadline.set(sleep_or_pending(None));
+                    },
+                    _ = &mut limit_reached => {
+                        trace!("connection reached its request limit, closing");
+                        connection_guard.recycled();
+                        let _ = goaway_reason.set(GoAwayReason::RequestLimit);
+                        draining = true;
+                        conn.as_mut().graceful_shutdown();
+                    },
+                    _ = connection_handle.closed() => {
+                        debug!("connection closed through its control handle");
+                        let _ = goaway_reason.set(GoAwayReason::Closed);
+                        draining = true;
+                        conn.as_mut().graceful_shutdown();
+                    },
                     _ = &mut sig => {
+                        let _ = goaway_reason.set(GoAwayReason::Shutdown);
+                        draining = true;
                         conn.as_mut().graceful_shutdown();
+                    },
+                    _ = force_close.cancelled() => {
+                        debug!("connection did not drain in time, closing");
+                        break;
                     }
                 }
             }
         }
 
+        drop(connection_handle);
+        drop(connection_guard);
         drop(watcher);
         trace!("connection closed");
     });
 }
 
+/// Counts down the requests a connection may still receive, see
+/// [`Server::max_requests_per_connection`] and [`Server::http2_stream_id_threshold`].
+#[derive(Clone)]
+struct RequestLimit {
+    remaining: Arc<AtomicU64>,
+    reached: Arc<Notify>,
+}
+
+impl RequestLimit {
+    fn new(max_requests: u64) -> Self {
+        Self {
+            remaining: Arc::new(AtomicU64::new(max_requests.max(1))),
+            reached: Arc::new(Notify::new()),
+        }
+    }
+
+    fn record(&self) {
+        if self.remaining.fetch_sub(1, Ordering::Relaxed) == 1 {
+            // Stores a permit, in case the connection task is not waiting yet.
+            self.reached.notify_one();
+        }
+    }
+}
+
+/// Builds the connections accepted by the server.
+///
+/// Accepting http1 requires `hyper-util`'s auto builder, which doesn't expose every HTTP/2
+/// setting, so HTTP/2-only servers use `hyper`'s builder directly.
+#[derive(Clone)]
+enum ConnectionBuilder {
+    Auto(AutoBuilder<SharedExec>),
+    Http2(Http2Builder<SharedExec>),
+}
+
+/// The `hyper` connection builder passed to [`Server::configure_hyper`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HyperBuilder<'a> {
+    /// The builder of servers accepting both HTTP/1.1 and HTTP/2, see [`Server::accept_http1`].
+    Auto(&'a mut AutoBuilder<SharedExec>),
+    /// The builder of HTTP/2-only servers, the default.
+    Http2(&'a mut Http2Builder<SharedExec>),
+}
+
+impl ConnectionBuilder {
+    fn serve_connection<B, IO, S>(&self, io: IO, svc: S) -> Connection<'_, IO, S>
+    where
+        B: http_body::Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        IO: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+        S: HyperService<Request<Incoming>, Response = Response<B>>,
+        S::Future: Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        match self {
+            Self::Auto(builder) => {
+                Connection::Auto(builder.serve_connection_with_upgrades(io, svc))
+            }
+            Self::Http2(builder) => Connection::Http2(builder.serve_connection(io, svc)),
+        }
+    }
+}
+
+#[pin_project(project = ConnectionProj)]
+enum Connection<'a, IO, S>
+where
+    S: HttpService<Incoming>,
+{
+    Auto(#[pin] AutoConnection<'a, IO, S, SharedExec>),
+    Http2(#[pin] Http2Connection<IO, S, SharedExec>),
+}
+
+impl<B, IO, S> Connection<'_, IO, S>
+where
+    B: http_body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    IO: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+    S: HyperService<Request<Incoming>, Response = Response<B>>,
+    S::Future: Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn graceful_shutdown(self: Pin<&mut Self>) {
+        match self.project() {
+            ConnectionProj::Auto(conn) => conn.graceful_shutdown(),
+            ConnectionProj::Http2(conn) => conn.graceful_shutdown(),
+        }
+    }
+}
+
+/// A connection being served, which is replaced by an HTTP/2 one when it upgrades to h2c, see
+/// [`UpgradeH2c`].
+trait ServeConnection: Future<Output = Result<(), crate::BoxError>> + Send {
+    fn graceful_shutdown(self: Pin<&mut Self>);
+}
+
+impl<B, IO, S> ServeConnection for Connection<'_, IO, S>
+where
+    B: http_body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    IO: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+    S: HyperService<Request<Incoming>, Response = Response<B>> + Send,
+    S::Future: Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn graceful_shutdown(self: Pin<&mut Self>) {
+        Connection::graceful_shutdown(self)
+    }
+}
+
+impl<B, IO, S> Future for Connection<'_, IO, S>
+where
+    B: http_body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    IO: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+    S: HyperService<Request<Incoming>, Response = Response<B>>,
+    S::Future: Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Output = Result<(), crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ConnectionProj::Auto(conn) => conn.poll(cx),
+            ConnectionProj::Http2(conn) => conn.poll(cx).map_err(Into::into),
+        }
+    }
+}
+
 async fn sleep_or_pending(wait_for: Option<Duration>) {
     match wait_for {
         Some(wait) => sleep(wait).await,
@@ -716,10 +2951,34 @@ async fn sleep_or_pending(wait_for: Option<Duration>) {
     };
 }
 
+/// Resolves once accepting connections is paused, or resumed if `paused` is `false`, see
+/// [`ConnectionControl::pause_accepting`].
+async fn wait_for_paused(watch: &mut tokio::sync::watch::Receiver<bool>, paused: bool) {
+    // The sender is held by the server's `ConnectionControl`, so it isn't dropped while serving.
+    let _ = watch.wait_for(|value| *value == paused).await;
+}
+
 impl<L> Router<L> {
     pub(crate) fn new(server: Server<L>, routes: Routes) -> Self {
         Self { server, routes }
     }
+
+    /// Splits into the server and its prepared routes, moving the path resolver of the routes to
+    /// the server so that it applies before the layers, and handing it the methods to count in
+    /// its stats.
+    pub(crate) fn into_parts(self) -> (Server<L>, Routes) {
+        let Self { mut server, routes } = self;
+        let mut routes = routes.prepare();
+        server.resolve_path = routes.take_resolve_path();
+        server.method_paths = Arc::new(
+            routes
+                .services()
+                .iter()
+                .flat_map(ServiceInfo::method_paths)
+                .collect(),
+        );
+        (server, routes)
+    }
 }
 
 impl<L> Router<L> {
@@ -751,6 +3010,93 @@ impl<L> Router<L> {
         self
     }
 
+    /// Set the service that handles requests which don't match any added service.
+    ///
+    /// See [`Routes::fallback_service`] for more details.
+    pub fn fallback_service<S>(mut self, svc: S) -> Self
+    where
+        S: Service<Request<Body>, Error = Infallible> + Clone + Send + 'static,
+        S::Response: axum::response::IntoResponse,
+        S::Future: Send + 'static,
+    {
+        self.routes = self.routes.fallback_service(svc);
+        self
+    }
+
+    /// Forward requests which don't match any added service to `channel`, e.g. to build a
+    /// gRPC proxy.
+    ///
+    /// See [`Routes::proxy_all`] for more details.
+    #[cfg(feature = "channel")]
+    pub fn proxy_all(mut self, channel: crate::transport::Channel) -> Self {
+        self.routes = self.routes.proxy_all(channel);
+        self
+    }
+
+    /// Resolve the path of every request with `f` before routing it, e.g. to serve aliases of
+    /// methods.
+    ///
+    /// See [`Routes::resolve_path`] for more details.
+    pub fn resolve_path<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.routes = self.routes.resolve_path(f);
+        self
+    }
+
+    /// Answer requests which don't match any added service with the [`Status`] returned by `f`.
+    ///
+    /// See [`Routes::fallback_fn`] for more details.
+    ///
+    /// [`Status`]: crate::Status
+    pub fn fallback_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request<Body>) -> crate::Status + Clone + Send + 'static,
+    {
+        self.routes = self.routes.fallback_fn(f);
+        self
+    }
+
+    /// Answer requests for a method that an added service doesn't have with the [`Status`]
+    /// returned by `f`.
+    ///
+    /// See [`Routes::unknown_method_fn`] for more details.
+    ///
+    /// [`Status`]: crate::Status
+    pub fn unknown_method_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request<Body>) -> crate::Status + Send + Sync + 'static,
+    {
+        self.routes = self.routes.unknown_method_fn(f);
+        self
+    }
+
+    /// Answer the unary method at `path` with the synchronous function `f`, run on the blocking
+    /// thread pool.
+    ///
+    /// See [`Routes::blocking_unary`] for more details.
+    #[cfg(feature = "prost")]
+    pub fn blocking_unary<F, Req, Res>(mut self, path: &str, f: F) -> Self
+    where
+        F: Fn(crate::Request<Req>) -> Result<crate::Response<Res>, crate::Status>
+            + Send
+            + Sync
+            + 'static,
+        Req: prost::Message + Default + Send + 'static,
+        Res: prost::Message + Send + 'static,
+    {
+        self.routes = self.routes.blocking_unary(path, f);
+        self
+    }
+
+    /// The services added to this router, with the names of their methods.
+    ///
+    /// See [`Routes::services`] for more details.
+    pub fn services(&self) -> &[ServiceInfo] {
+        self.routes.services()
+    }
+
     /// Consume this [`Server`] creating a future that will execute the server
     /// on [tokio]'s default executor.
     ///
@@ -766,30 +3112,28 @@ impl<L> Router<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::BoxError>,
     {
-        let incoming = TcpIncoming::bind(addr)
-            .map_err(super::Error::from_source)?
-            .with_nodelay(Some(self.server.tcp_nodelay))
-            .with_keepalive(self.server.tcp_keepalive);
-        self.server
-            .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(
-                self.routes.prepare(),
-                incoming,
-                None,
-            )
+        let (server, routes) = self.into_parts();
+        let incoming = server.bind(addr)?;
+        server
+            .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(routes, incoming, None)
             .await
+            .map(drop)
     }
 
     /// Consume this [`Server`] creating a future that will execute the server
     /// on [tokio]'s default executor. And shutdown when the provided signal
     /// is received.
     ///
+    /// Once all connections closed, this returns a [`ShutdownReport`] telling how the shutdown
+    /// completed, see [`Server::graceful_shutdown_timeout`].
+    ///
     /// [`Server`]: struct.Server.html
     /// [tokio]: https://docs.rs/tokio
     pub async fn serve_with_shutdown<F: Future<Output = ()>, ResBody>(
         self,
         addr: SocketAddr,
         signal: F,
-    ) -> Result<(), super::Error>
+    ) -> Result<ShutdownReport, super::Error>
     where
         L: Layer<Routes>,
         L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
@@ -799,15 +3143,103 @@ impl<L> Router<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::BoxError>,
     {
-        let incoming = TcpIncoming::bind(addr)
-            .map_err(super::Error::from_source)?
-            .with_nodelay(Some(self.server.tcp_nodelay))
-            .with_keepalive(self.server.tcp_keepalive);
-        self.server
-            .serve_with_shutdown(self.routes.prepare(), incoming, Some(signal))
+        let (server, routes) = self.into_parts();
+        let incoming = server.bind(addr)?;
+        server
+            .serve_with_shutdown(routes, incoming, Some(signal))
             .await
     }
 
+    /// Consume this [`Server`] creating a future that will execute the server
+    /// on [tokio]'s default executor, and shut it down gracefully once the process is asked to
+    /// terminate.
+    ///
+    /// This is [`Router::serve_with_shutdown`] with the signals deployments use to stop a
+    /// process: `SIGTERM`, e.g. sent by Kubernetes or systemd, and `SIGINT`, i.e. Ctrl-C, on
+    /// Unix, and Ctrl-C on other platforms. The server then stops accepting connections and
+    /// drains the requests in flight, see [`Server::graceful_shutdown_timeout`].
+    ///
+    /// The signal handlers replace the default ones, which terminate the process, for the
+    /// lifetime of the process. Failing to install them returns an error before serving.
+    ///
+    /// ```no_run
+    /// # use tonic::{service::Routes, transport::Server};
+    /// # async fn run(routes: Routes) -> Result<(), tonic::transport::Error> {
+    /// let report = Server::builder()
+    ///     .add_routes(routes)
+    ///     .serve_with_default_signals("[::1]:50051".parse().unwrap())
+    ///     .await?;
+    /// println!("drained {} requests", report.drained());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [tokio]: https://docs.rs/tokio
+    pub async fn serve_with_default_signals<ResBody>(
+        self,
+        addr: SocketAddr,
+    ) -> Result<ShutdownReport, super::Error>
+    where
+        L: Layer<Routes>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Error:
+            Into<crate::BoxError> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        let signal = shutdown::default_signals().map_err(super::Error::new_serve)?;
+        self.serve_with_shutdown(addr, signal).await
+    }
+
+    /// Consume this [`Server`], spawning it on [tokio]'s default executor, and return a
+    /// [`ServerHandle`] to shut it down and wait for it to stop.
+    ///
+    /// Unlike [`Router::serve`], this returns right away, which makes it easier to run the
+    /// server along with the other tasks of a supervised application. The address is bound
+    /// before returning, so binding errors are returned here, and [`ServerHandle::local_addr`]
+    /// tells the port picked by the OS if `addr` has port 0.
+    ///
+    /// This must be called from within a [tokio] runtime.
+    ///
+    /// ```no_run
+    /// # use tonic::{service::Routes, transport::Server};
+    /// # async fn run(routes: Routes) -> Result<(), tonic::transport::Error> {
+    /// let server = Server::builder()
+    ///     .add_routes(routes)
+    ///     .spawn("[::1]:50051".parse().unwrap())?;
+    ///
+    /// // ... run the rest of the application ...
+    ///
+    /// server.shutdown();
+    /// let report = server.await_shutdown().await?;
+    /// println!("drained {} requests", report.drained());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [tokio]: https://docs.rs/tokio
+    pub fn spawn<ResBody>(self, addr: SocketAddr) -> Result<ServerHandle, super::Error>
+    where
+        L: Layer<Routes> + Send + 'static,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Error:
+            Into<crate::BoxError> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        let (server, routes) = self.into_parts();
+        let incoming = server.bind(addr)?;
+        let local_addr = incoming.local_addr().map_err(super::Error::new_bind)?;
+
+        let shutdown = CancellationToken::new();
+        let signal = shutdown.clone().cancelled_owned();
+        let task = tokio::spawn(server.serve_with_shutdown(routes, incoming, Some(signal)));
+
+        Ok(ServerHandle::new(local_addr, shutdown, task))
+    }
+
     /// Consume this [`Server`] creating a future that will execute the server
     /// on the provided incoming stream of `AsyncRead + AsyncWrite`.
     ///
@@ -831,19 +3263,17 @@ impl<L> Router<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::BoxError>,
     {
-        self.server
-            .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(
-                self.routes.prepare(),
-                incoming,
-                None,
-            )
+        let (server, routes) = self.into_parts();
+        server
+            .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(routes, incoming, None)
             .await
+            .map(drop)
     }
 
     /// Consume this [`Server`] creating a future that will execute the server
     /// on the provided incoming stream of `AsyncRead + AsyncWrite`. Similar to
     /// `serve_with_shutdown` this method will also take a signal future to
-    /// gracefully shutdown the server.
+    /// gracefully shutdown the server, and returns a [`ShutdownReport`] as well.
     ///
     /// This method discards any provided [`Server`] TCP configuration.
     ///
@@ -852,7 +3282,7 @@ impl<L> Router<L> {
         self,
         incoming: I,
         signal: F,
-    ) -> Result<(), super::Error>
+    ) -> Result<ShutdownReport, super::Error>
     where
         I: Stream<Item = Result<IO, IE>>,
         IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
@@ -866,8 +3296,86 @@ impl<L> Router<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::BoxError>,
     {
-        self.server
-            .serve_with_shutdown(self.routes.prepare(), incoming, Some(signal))
+        let (server, routes) = self.into_parts();
+        server
+            .serve_with_shutdown(routes, incoming, Some(signal))
+            .await
+    }
+
+    /// Consume this [`Server`] creating a future that will execute the server
+    /// on the provided stream of already accepted connections.
+    ///
+    /// This is lower level than [`Router::serve_with_incoming`], for tests, custom transports
+    /// and fuzzing the HTTP layer with crafted IO:
+    ///
+    /// * Each [`BoxedIo`] is served as one client connection, carrying the HTTP/2 bytes from
+    ///   the connection preface on, or HTTP/1 with [`Server::accept_http1`]. There is no TLS
+    ///   handshake, even if [`Server::tls_config`] was set, so connections that need TLS must
+    ///   be decrypted by the caller.
+    /// * Errors yielded by the stream are logged and skipped.
+    /// * The server runs until the stream ends, and then until all connections are closed.
+    ///
+    /// Like [`Router::serve_with_incoming`], this discards any provided [`Server`] TCP
+    /// configuration.
+    ///
+    /// ```
+    /// # use tonic::transport::{server::BoxedIo, Server};
+    /// # use tonic::service::Routes;
+    /// let (client, server) = tokio::io::duplex(64 * 1024);
+    /// let incoming = tokio_stream::once(Ok::<_, std::io::Error>(BoxedIo::new(server)));
+    ///
+    /// # async {
+    /// Server::builder()
+    ///     .add_routes(Routes::default())
+    ///     .serve_with_boxed_io(incoming)
+    ///     .await
+    /// # };
+    /// // `client` can now be used as the transport of a `Channel`.
+    /// ```
+    ///
+    /// [`Server::tls_config`]: Server::tls_config
+    pub async fn serve_with_boxed_io<I, IE, ResBody>(self, incoming: I) -> Result<(), super::Error>
+    where
+        I: Stream<Item = Result<BoxedIo, IE>>,
+        IE: Into<crate::BoxError>,
+        L: Layer<Routes>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Error:
+            Into<crate::BoxError> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        let (server, routes) = self.into_parts();
+        server
+            .without_tls()
+            .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(routes, incoming, None)
+            .await
+            .map(drop)
+    }
+
+    /// Like [`Router::serve_with_boxed_io`], but shuts down gracefully once `signal` completes.
+    pub async fn serve_with_boxed_io_shutdown<I, IE, F, ResBody>(
+        self,
+        incoming: I,
+        signal: F,
+    ) -> Result<ShutdownReport, super::Error>
+    where
+        I: Stream<Item = Result<BoxedIo, IE>>,
+        IE: Into<crate::BoxError>,
+        F: Future<Output = ()>,
+        L: Layer<Routes>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<Body>>>::Error:
+            Into<crate::BoxError> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        let (server, routes) = self.into_parts();
+        server
+            .without_tls()
+            .serve_with_shutdown(routes, incoming, Some(signal))
             .await
     }
 }
@@ -882,6 +3390,8 @@ impl<L> fmt::Debug for Server<L> {
 struct Svc<S> {
     inner: S,
     trace_interceptor: Option<TraceInterceptor>,
+    stats: ServerStats,
+    method_paths: Arc<HashSet<String>>,
 }
 
 impl<S, ResBody> Service<Request<Body>> for Svc<S>
@@ -914,9 +3424,22 @@ where
             tracing::Span::none()
         };
 
+        let path = req.uri().path();
+        let request_guard = self
+            .stats
+            .record_request(self.method_paths.contains(path).then_some(path));
+
+        let cancellation_token = CancellationToken::new();
+        req.extensions_mut().insert(cancellation_token.clone());
+
+        // Keeps the request active until its response ends, recording it as cancelled if it
+        // doesn't complete.
+        let observer: BoxObserver = Box::new(move |status| request_guard.complete(status.code()));
+
         SvcFuture {
-            inner: self.inner.call(req),
+            inner: StatusResponseFuture::new(self.inner.call(req), observer),
             span,
+            cancel_guard: Some(cancellation_token.drop_guard()),
         }
     }
 }
@@ -924,8 +3447,11 @@ where
 #[pin_project]
 struct SvcFuture<F> {
     #[pin]
-    inner: F,
+    inner: StatusResponseFuture<F, BoxObserver>,
     span: tracing::Span,
+    // Cancels the request's token if the future is dropped before completing, otherwise it
+    // is handed to the response body.
+    cancel_guard: Option<DropGuard>,
 }
 
 impl<F, E, ResBody> Future for SvcFuture<F>
@@ -941,8 +3467,10 @@ where
         let this = self.project();
         let _guard = this.span.enter();
 
-        let response: Response<ResBody> = ready!(this.inner.poll(cx)).map_err(Into::into)?;
-        let response = response.map(|body| Body::new(body.map_err(Into::into)));
+        let response = ready!(this.inner.poll(cx)).map_err(Into::into)?;
+        let cancel_guard = this.cancel_guard.take().expect("polled after completion");
+        let response = response
+            .map(|body| Body::new(CancelOnDrop::new(body, cancel_guard).map_err(Into::into)));
         Poll::Ready(Ok(response))
     }
 }
@@ -956,9 +3484,28 @@ impl<S> fmt::Debug for Svc<S> {
 #[derive(Clone)]
 struct MakeSvc<S, IO> {
     concurrency_limit: Option<usize>,
+    cost_fn: Option<CostFn>,
+    method_limits: MethodLimits,
+    admission: StreamAdmission,
     timeout: Option<Duration>,
+    default_timeout: Option<Duration>,
+    max_request_messages: Option<usize>,
+    max_response_messages: Option<usize>,
+    streaming_flush_mode: StreamingFlushMode,
+    metadata_limits: MetadataLimits,
+    stream_read_timeout: Option<Duration>,
+    server_header: Option<Option<HeaderValue>>,
+    server_timing: bool,
+    error_mappers: ErrorMappers,
+    on_connect: Option<OnConnect>,
+    resolve_path: Option<ResolvePath>,
+    method_paths: Arc<HashSet<String>>,
     inner: S,
+    // A clone of `inner` polled for readiness before accepting a connection. It is dropped once
+    // ready, so it does not hold on to whatever it reserved, like a concurrency limit permit.
+    probe: Option<S>,
     trace_interceptor: Option<TraceInterceptor>,
+    stats: ServerStats,
     _io: PhantomData<fn() -> IO>,
 }
 
@@ -975,27 +3522,126 @@ where
     type Error = crate::BoxError;
     type Future = future::Ready<Result<Self::Response, Self::Error>>;
 
-    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Ok(()).into()
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let probe = self.probe.get_or_insert_with(|| self.inner.clone());
+        let ready = ready!(probe.poll_ready(cx));
+        self.probe = None;
+
+        // Errors are left to the requests on the connection, as they were before readiness was
+        // checked here.
+        if let Err(err) = ready {
+            debug!("service not ready: {}", err.into());
+        }
+
+        Poll::Ready(Ok(()))
     }
 
     fn call(&mut self, io: &ServerIo<IO>) -> Self::Future {
+        let accepted_at = Instant::now();
         let conn_info = io.connect_info();
+        let peer_info = io.peer_info();
+        #[cfg(feature = "_tls-any")]
+        let peer_identity = peer_info
+            .peer_certs
+            .as_ref()
+            .and_then(|certs| certs.first())
+            .and_then(PeerIdentity::from_certificate);
 
         let svc = self.inner.clone();
         let concurrency_limit = self.concurrency_limit;
+        let cost_fn = self.cost_fn.clone();
+        let method_limits = self.method_limits.clone();
+        let admission = self.admission.clone();
         let timeout = self.timeout;
+        let default_timeout = self.default_timeout;
+        let max_request_messages = self.max_request_messages.map(MaxRequestMessages);
+        let max_response_messages = self.max_response_messages.map(MaxResponseMessages);
+        let streaming_flush_mode = self.streaming_flush_mode;
+        let metadata_limits = self.metadata_limits.clone();
+        let stream_read_timeout = self.stream_read_timeout;
+        let server_header = self.server_header.clone();
+        let server_timing = self.server_timing;
+        let error_mappers = self.error_mappers.clone();
         let trace_interceptor = self.trace_interceptor.clone();
+        let stats = self.stats.clone();
+        let resolve_path = self.resolve_path.clone();
+        let method_paths = self.method_paths.clone();
+        let insert_state = self
+            .on_connect
+            .as_ref()
+            .map(|on_connect| on_connect.connect(peer_info.remote_addr));
 
         let svc = ServiceBuilder::new()
-            .layer_fn(RecoverError::new)
-            .option_layer(concurrency_limit.map(ConcurrencyLimitLayer::new))
-            .layer_fn(|s| GrpcTimeout::new(s, timeout))
+            .layer_fn(|s| AdmissionGate::new(s, admission.clone()))
+            .layer_fn(|s| RecoverError::new(s, error_mappers.clone()))
+            .option_layer(
+                (!metadata_limits.is_unlimited())
+                    .then(|| layer_fn(move |s| MetadataLimit::new(s, metadata_limits.clone()))),
+            )
+            .layer_fn(|s| MethodConcurrencyLimit::new(s, method_limits.clone()))
+            .option_layer(concurrency_limit.map(|limit| {
+                let stats = stats.clone();
+                layer_fn(move |s| {
+                    ConcurrencyLimit::new(
+                        s,
+                        limit,
+                        timeout,
+                        default_timeout,
+                        cost_fn.clone(),
+                        stats.clone(),
+                    )
+                })
+            }))
+            .layer_fn(MarkHandlerStart::new)
+            .layer_fn(|s| GrpcTimeout::new(s, timeout, default_timeout))
             .service(svc);
 
         let svc = ServiceBuilder::new()
             .layer(BoxCloneService::layer())
             .map_request(move |mut request: Request<Body>| {
+                if let Some(resolver) = &resolve_path {
+                    router::resolve_path(resolver, request.uri_mut());
+                }
+                if let Some(stream_read_timeout) = stream_read_timeout {
+                    request = request
+                        .map(|body| Body::new(ReadTimeoutBody::new(body, stream_read_timeout)));
+                }
+
+                request.extensions_mut().insert(peer_info.clone());
+                request
+                    .extensions_mut()
+                    .insert(RequestTiming::new(accepted_at));
+                if server_timing && request.headers().contains_key(SERVER_TIMING_REQUEST_HEADER) {
+                    request.extensions_mut().insert(ServerTiming::new());
+                }
+                if let Some(insert_state) = &insert_state {
+                    insert_state(request.extensions_mut());
+                }
+                if let Some(user_agent) = request.headers().get(header::USER_AGENT) {
+                    let user_agent = ClientUserAgent::new(user_agent.clone());
+                    request.extensions_mut().insert(user_agent);
+                }
+                if let Some(attempts) = request
+                    .headers()
+                    .get(crate::metadata::GRPC_PREVIOUS_RPC_ATTEMPTS_HEADER)
+                    .and_then(PreviousRpcAttempts::from_header)
+                {
+                    request.extensions_mut().insert(attempts);
+                }
+                if let Some(baggage) = Baggage::from_headers(request.headers()) {
+                    request.extensions_mut().insert(baggage);
+                }
+
+                if let Some(max_request_messages) = max_request_messages {
+                    request.extensions_mut().insert(max_request_messages);
+                }
+                if let Some(max_response_messages) = max_response_messages {
+                    request.extensions_mut().insert(max_response_messages);
+                }
+                if streaming_flush_mode != StreamingFlushMode::default() {
+                    request.extensions_mut().insert(streaming_flush_mode);
+                }
+
                 match &conn_info {
                     tower::util::Either::Left(inner) => {
                         request.extensions_mut().insert(inner.clone());
@@ -1005,6 +3651,10 @@ where
                         {
                             request.extensions_mut().insert(inner.clone());
                             request.extensions_mut().insert(inner.get_ref().clone());
+
+                            if let Some(peer_identity) = &peer_identity {
+                                request.extensions_mut().insert(peer_identity.clone());
+                            }
                         }
 
                         #[cfg(not(feature = "_tls-any"))]
@@ -1018,9 +3668,24 @@ where
 
                 request
             })
+            .map_response(move |mut response: Response<Body>| {
+                match &server_header {
+                    Some(Some(value)) => {
+                        response.headers_mut().insert(header::SERVER, value.clone());
+                    }
+                    Some(None) => {
+                        response.headers_mut().remove(header::SERVER);
+                    }
+                    None => {}
+                }
+
+                response
+            })
             .service(Svc {
                 inner: svc,
                 trace_interceptor,
+                stats,
+                method_paths,
             });
 
         future::ready(Ok(svc))
@@ -0,0 +1,362 @@
+use super::PeerInfo;
+use crate::{
+    body::Body,
+    service::status_interceptor::{BoxObserver, StatusResponseBody, StatusResponseFuture},
+    Code,
+};
+use bytes::Bytes;
+use http::{HeaderMap, Request, Response};
+use http_body::{Frame, SizeHint};
+use opentelemetry::{
+    global::{self, BoxedTracer, ObjectSafeTracerProvider},
+    propagation::{Extractor, TextMapPropagator},
+    trace::{SpanKind, Status as SpanStatus, TraceContextExt, Tracer},
+    Context as OtelContext, InstrumentationScope, KeyValue,
+};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A layer creating one OpenTelemetry server span per RPC.
+///
+/// Spans are named after the method path, e.g. `helloworld.Greeter/SayHello`, and carry the
+/// attributes of the gRPC semantic conventions: `rpc.system`, `rpc.service`, `rpc.method`, the
+/// peer address as `network.peer.address` and `network.peer.port`, and, once the call completes,
+/// `rpc.grpc.status_code`. Calls failing with a status the conventions consider a server error,
+/// like [`Code::Internal`] or [`Code::Unavailable`], also set the span status to error.
+///
+/// The parent of each span is extracted from the request headers, e.g. the `traceparent` header,
+/// so spans join the trace of the client. The context holding the span is inserted into the
+/// request extensions and is the current context while the service runs, so spans created by
+/// handlers become its children.
+///
+/// Spans end once the response body completes, so streaming RPCs end their span when the stream
+/// terminates. Requests dropped before their response completes, e.g. because the client went
+/// away, end it with [`Code::Cancelled`].
+///
+/// By default, spans are created with the global tracer provider and the parent is extracted with
+/// the global propagator, see [`opentelemetry::global`].
+///
+/// ```
+/// # use tonic::transport::{server::OtelTraceLayer, Server};
+/// Server::builder().layer(OtelTraceLayer::new());
+/// ```
+#[derive(Clone, Default)]
+pub struct OtelTraceLayer {
+    tracer: Option<Arc<BoxedTracer>>,
+    propagator: Option<Arc<dyn TextMapPropagator + Send + Sync>>,
+}
+
+impl OtelTraceLayer {
+    /// Create a new OpenTelemetry tracing layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create spans with a tracer of `provider` instead of the global tracer provider.
+    pub fn tracer_provider<P>(self, provider: &P) -> Self
+    where
+        P: ObjectSafeTracerProvider,
+    {
+        let tracer = BoxedTracer::new(provider.boxed_tracer(scope()));
+        Self {
+            tracer: Some(Arc::new(tracer)),
+            ..self
+        }
+    }
+
+    /// Extract the parent of spans from the request headers with `propagator` instead of the
+    /// global propagator.
+    pub fn propagator<P>(self, propagator: P) -> Self
+    where
+        P: TextMapPropagator + Send + Sync + 'static,
+    {
+        Self {
+            propagator: Some(Arc::new(propagator)),
+            ..self
+        }
+    }
+
+    fn start(&self, req: &Request<Body>) -> OtelContext {
+        let headers = HeaderExtractor(req.headers());
+        let parent = match &self.propagator {
+            Some(propagator) => propagator.extract(&headers),
+            None => global::get_text_map_propagator(|propagator| propagator.extract(&headers)),
+        };
+
+        let path = req.uri().path().trim_start_matches('/');
+        let (service, method) = path.split_once('/').unwrap_or((path, ""));
+        let mut attributes = vec![
+            KeyValue::new("rpc.system", "grpc"),
+            KeyValue::new("rpc.service", service.to_owned()),
+            KeyValue::new("rpc.method", method.to_owned()),
+        ];
+        if let Some(peer) = req
+            .extensions()
+            .get::<PeerInfo>()
+            .and_then(|info| info.remote_addr)
+        {
+            attributes.push(KeyValue::new("network.peer.address", peer.ip().to_string()));
+            attributes.push(KeyValue::new("network.peer.port", i64::from(peer.port())));
+        }
+
+        let builder = |tracer: &BoxedTracer| {
+            tracer
+                .span_builder(path.to_owned())
+                .with_kind(SpanKind::Server)
+                .with_attributes(attributes)
+                .start_with_context(tracer, &parent)
+        };
+        let span = match &self.tracer {
+            Some(tracer) => builder(tracer),
+            None => builder(&global::tracer_with_scope(scope())),
+        };
+        parent.with_span(span)
+    }
+}
+
+impl fmt::Debug for OtelTraceLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtelTraceLayer").finish()
+    }
+}
+
+impl<S> Layer<S> for OtelTraceLayer {
+    type Service = OtelTrace<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        OtelTrace {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Middleware creating OpenTelemetry server spans, see [`OtelTraceLayer`].
+#[derive(Clone)]
+pub struct OtelTrace<S> {
+    inner: S,
+    layer: OtelTraceLayer,
+}
+
+impl<S> fmt::Debug for OtelTrace<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtelTrace").finish()
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for OtelTrace<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+{
+    type Response = Response<OtelTraceBody<ResBody>>;
+    type Error = S::Error;
+    type Future = OtelTraceFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let context = self.layer.start(&req);
+        req.extensions_mut().insert(context.clone());
+
+        let inner = {
+            let _guard = context.clone().attach();
+            self.inner.call(req)
+        };
+        let observer: BoxObserver = {
+            let context = context.clone();
+            Box::new(move |status| end(&context, status.code(), status.message()))
+        };
+
+        OtelTraceFuture {
+            inner: StatusResponseFuture::new(inner, observer),
+            context,
+        }
+    }
+}
+
+/// Response future for [`OtelTrace`].
+#[pin_project]
+pub struct OtelTraceFuture<F> {
+    #[pin]
+    inner: StatusResponseFuture<F, BoxObserver>,
+    context: OtelContext,
+}
+
+impl<F> fmt::Debug for OtelTraceFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtelTraceFuture").finish()
+    }
+}
+
+impl<F, ResBody, E> Future for OtelTraceFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<OtelTraceBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = {
+            let _guard = this.context.clone().attach();
+            ready!(this.inner.poll(cx))?
+        };
+        Poll::Ready(Ok(response.map(|inner| OtelTraceBody { inner })))
+    }
+}
+
+/// Response body for [`OtelTrace`], ending the span once it completes.
+#[pin_project]
+pub struct OtelTraceBody<B> {
+    #[pin]
+    inner: StatusResponseBody<B, BoxObserver>,
+}
+
+impl<B> fmt::Debug for OtelTraceBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtelTraceBody").finish()
+    }
+}
+
+impl<B> http_body::Body for OtelTraceBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+fn scope() -> InstrumentationScope {
+    InstrumentationScope::builder("tonic")
+        .with_version(env!("CARGO_PKG_VERSION"))
+        .build()
+}
+
+/// Records the final status of the call on the span of `context` and ends it.
+fn end(context: &OtelContext, code: Code, message: &str) {
+    let span = context.span();
+    span.set_attribute(KeyValue::new(
+        "rpc.grpc.status_code",
+        i64::from(code.to_i32()),
+    ));
+    // The codes the gRPC semantic conventions consider server errors.
+    if matches!(
+        code,
+        Code::Unknown
+            | Code::DeadlineExceeded
+            | Code::Unimplemented
+            | Code::Internal
+            | Code::Unavailable
+            | Code::DataLoss
+    ) {
+        span.set_status(SpanStatus::error(message.to_owned()));
+    }
+    span.end();
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+    use opentelemetry::{trace::TraceId, Value};
+    use opentelemetry_sdk::{
+        propagation::TraceContextPropagator,
+        trace::{InMemorySpanExporter, SdkTracerProvider},
+    };
+    use tower::ServiceExt;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const PARENT_ID: &str = "00f067aa0ba902b7";
+
+    #[tokio::test]
+    async fn creates_server_span_with_remote_parent() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let layer = OtelTraceLayer::new()
+            .tracer_provider(&provider)
+            .propagator(TraceContextPropagator::new());
+
+        let svc = tower::service_fn(|req: Request<Body>| async move {
+            // The span is current while the handler runs.
+            let current = OtelContext::current().span().span_context().trace_id();
+            assert_eq!(current, TraceId::from_hex(TRACE_ID).unwrap());
+            assert!(req.extensions().get::<OtelContext>().is_some());
+
+            let response = Response::builder()
+                .header("grpc-status", "13")
+                .header("grpc-message", "boom")
+                .body(Body::empty())
+                .unwrap();
+            Ok::<_, std::convert::Infallible>(response)
+        });
+        let request = Request::builder()
+            .uri("/test.Test/UnaryCall")
+            .header("traceparent", format!("00-{TRACE_ID}-{PARENT_ID}-01"))
+            .body(Body::empty())
+            .unwrap();
+        let response = layer.layer(svc).oneshot(request).await.unwrap();
+        response.into_body().collect().await.unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name, "test.Test/UnaryCall");
+        assert_eq!(span.span_kind, SpanKind::Server);
+        assert_eq!(
+            span.span_context.trace_id(),
+            TraceId::from_hex(TRACE_ID).unwrap()
+        );
+        assert_eq!(span.parent_span_id.to_string(), PARENT_ID);
+        assert!(span.parent_span_is_remote);
+        assert_eq!(span.status, SpanStatus::error("boom"));
+
+        let attribute = |key: &str| {
+            span.attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.clone())
+        };
+        assert_eq!(attribute("rpc.system"), Some(Value::from("grpc")));
+        assert_eq!(attribute("rpc.service"), Some(Value::from("test.Test")));
+        assert_eq!(attribute("rpc.method"), Some(Value::from("UnaryCall")));
+        assert_eq!(attribute("rpc.grpc.status_code"), Some(Value::I64(13)));
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    task::Waker,
+    time::{Duration, Instant},
+};
+use tokio::sync::oneshot;
+
+const FRAME_HEADER_LEN: usize = 9;
+const PING: u8 = 0x6;
+/// The opaque data of the pings sent through a [`ConnectionPing`], to tell their acks from the
+/// ones of the pings `hyper` sends itself.
+pub(crate) const PAYLOAD: [u8; 8] = *b"tonicpng";
+pub(crate) const PING_FRAME: [u8; FRAME_HEADER_LEN + PAYLOAD.len()] = {
+    let mut frame = [0; FRAME_HEADER_LEN + PAYLOAD.len()];
+    frame[2] = PAYLOAD.len() as u8;
+    frame[3] = PING;
+    let mut i = 0;
+    while i < PAYLOAD.len() {
+        frame[FRAME_HEADER_LEN + i] = PAYLOAD[i];
+        i += 1;
+    }
+    frame
+};
+
+/// A handle to send HTTP/2 `PING` frames on the connection a request arrived on.
+///
+/// This type is accessible through [request extensions][ext] of every request served by a
+/// [`Server`] with [`Server::http2_connection_ping`] enabled, for handlers and layers to actively
+/// probe whether the client is still there, e.g. before starting expensive work for a
+/// long-running call, or as part of custom health logic.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tonic::{Request, Status, transport::server::ConnectionPing};
+/// async fn check_client(request: &Request<()>) -> Result<(), Status> {
+///     let Some(ping) = request.extensions().get::<ConnectionPing>() else {
+///         return Ok(());
+///     };
+///     match tokio::time::timeout(Duration::from_secs(1), ping.ping()).await {
+///         Ok(Some(rtt)) => println!("client answered in {rtt:?}"),
+///         Ok(None) => return Err(Status::cancelled("connection closed")),
+///         Err(_) => return Err(Status::unavailable("client did not answer")),
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// # Constraints
+///
+/// - Only HTTP/2 connections can be pinged. On HTTP/1.1 connections, see
+///   [`Server::accept_http1`], including the ones upgraded to h2c, [`ConnectionPing::ping`]
+///   returns `None`.
+/// - The `PING` frame is sent in between the frames written by the connection, so a connection
+///   stuck writing a frame, e.g. because the client stopped reading, delays it.
+/// - Pings are not bounded in time, wrap them in a timeout. [`ConnectionPing::last_rtt`] tells
+///   the round-trip time of the last ping without sending a new one.
+/// - Concurrent pings of a connection share one `PING` frame.
+/// - Their acks are removed from the frames read from the connection, so `hyper`, which only
+///   expects acks of its own pings, never sees them.
+/// - Clients may count pings they didn't ask for against a budget, and close connections that
+///   exceed it, so ping sparingly.
+///
+/// [ext]: crate::Request::extensions
+/// [`Server`]: super::Server
+/// [`Server::accept_http1`]: super::Server::accept_http1
+/// [`Server::http2_connection_ping`]: super::Server::http2_connection_ping
+#[derive(Clone, Default)]
+pub struct ConnectionPing {
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    /// A ping waits to be written.
+    requested: bool,
+    /// When the pending ping was written.
+    sent_at: Option<Instant>,
+    waiters: Vec<oneshot::Sender<Duration>>,
+    /// The connection is closed or doesn't speak HTTP/2.
+    closed: bool,
+    /// The task reading the connection, which writes the ping if the connection is idle.
+    reader: Option<Waker>,
+    /// The opaque data of the last ping `hyper` sent itself, e.g. a keepalive ping, and when.
+    connection_ping: Option<([u8; 8], Instant)>,
+    /// The round-trip time of the last ping acked by the client.
+    rtt: Option<Duration>,
+}
+
+impl ConnectionPing {
+    /// Send a `PING` frame and wait for the client to acknowledge it, returning the round-trip
+    /// time.
+    ///
+    /// Returns `None` if the connection is closed before the ack arrives, or doesn't speak HTTP/2.
+    pub async fn ping(&self) -> Option<Duration> {
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut shared = self.lock();
+            if shared.closed {
+                return None;
+            }
+            shared.waiters.push(tx);
+            if !shared.requested && shared.sent_at.is_none() {
+                shared.requested = true;
+                if let Some(reader) = &shared.reader {
+                    reader.wake_by_ref();
+                }
+            }
+        }
+        rx.await.ok()
+    }
+
+    /// The round-trip time of the last ping of the connection acked by the client, or `None`
+    /// if none was yet.
+    ///
+    /// Besides the pings sent through [`ConnectionPing::ping`], this measures the keepalive
+    /// pings of [`Server::http2_keepalive_interval`] and the ones `hyper` sends for
+    /// [`Server::http2_adaptive_window`], so it tells the latency of a connection without
+    /// sending pings of its own.
+    ///
+    /// [`Server::http2_keepalive_interval`]: super::Server::http2_keepalive_interval
+    /// [`Server::http2_adaptive_window`]: super::Server::http2_adaptive_window
+    pub fn last_rtt(&self) -> Option<Duration> {
+        self.lock().rtt
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Shared> {
+        self.shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Registers the task reading the connection, to be woken when a ping is requested.
+    pub(crate) fn set_reader(&self, waker: &Waker) {
+        let mut shared = self.lock();
+        match &shared.reader {
+            Some(reader) if reader.will_wake(waker) => {}
+            _ => shared.reader = Some(waker.clone()),
+        }
+    }
+
+    /// Whether a ping waits to be written.
+    pub(crate) fn is_requested(&self) -> bool {
+        self.lock().requested
+    }
+
+    /// Takes the requested ping, to be written now.
+    pub(crate) fn take_request(&self) -> bool {
+        let mut shared = self.lock();
+        let requested = std::mem::take(&mut shared.requested);
+        if requested {
+            shared.sent_at = Some(Instant::now());
+        }
+        requested
+    }
+
+    pub(crate) fn acked(&self) {
+        let mut shared = self.lock();
+        if let Some(sent_at) = shared.sent_at.take() {
+            let rtt = sent_at.elapsed();
+            shared.rtt = Some(rtt);
+            for waiter in shared.waiters.drain(..) {
+                let _ = waiter.send(rtt);
+            }
+        }
+    }
+
+    /// Records a ping `hyper` sent itself.
+    pub(crate) fn connection_ping_sent(&self, payload: [u8; 8]) {
+        self.lock().connection_ping = Some((payload, Instant::now()));
+    }
+
+    /// Records the ack of a ping that wasn't sent through a [`ConnectionPing`].
+    pub(crate) fn connection_ping_acked(&self, payload: &[u8]) {
+        let mut shared = self.lock();
+        if let Some((sent, sent_at)) = shared.connection_ping {
+            if sent == payload {
+                shared.connection_ping = None;
+                shared.rtt = Some(sent_at.elapsed());
+            }
+        }
+    }
+
+    pub(crate) fn close(&self) {
+        let mut shared = self.lock();
+        shared.closed = true;
+        shared.waiters.clear();
+    }
+}
+
+impl fmt::Debug for ConnectionPing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionPing").finish()
+    }
+}
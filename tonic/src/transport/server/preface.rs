@@ -0,0 +1,41 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// The connection preface an HTTP/2 client starts with, followed by a `SETTINGS` frame.
+pub(crate) const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// Tells whether the client of a connection completed its side of the handshake, by sending its
+/// HTTP/2 connection preface and `SETTINGS` frame, see [`Server::http2_settings_timeout`].
+///
+/// Connections that start with anything else, like HTTP/1 requests, count as done as soon as
+/// that is detected. Set by a [`FramesIo`](super::frames::FramesIo).
+///
+/// [`Server::http2_settings_timeout`]: super::Server::http2_settings_timeout
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PrefaceDone(Arc<Flags>);
+
+#[derive(Debug, Default)]
+struct Flags {
+    done: AtomicBool,
+    established: AtomicBool,
+}
+
+impl PrefaceDone {
+    pub(crate) fn get(&self) -> bool {
+        self.0.done.load(Ordering::Relaxed)
+    }
+
+    /// Whether the client sent a valid HTTP/2 connection preface and `SETTINGS` frame, unlike
+    /// connections that are done because they turned out not to be HTTP/2.
+    pub(crate) fn established(&self) -> bool {
+        self.0.established.load(Ordering::Relaxed)
+    }
+
+    /// Records that the handshake is done, `established` or not.
+    pub(crate) fn finish(&self, established: bool) {
+        self.0.established.store(established, Ordering::Relaxed);
+        self.0.done.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::transport::server::StreamAdmission;
+use http::{Request, Response};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// Resets new streams with `REFUSED_STREAM` while its [`StreamAdmission`] refuses them.
+///
+/// The error is passed on to hyper, which resets the stream with the HTTP/2 error code found in
+/// its source chain, so this must be layered outside of [`RecoverError`].
+///
+/// [`RecoverError`]: super::RecoverError
+#[derive(Debug, Clone)]
+pub(crate) struct AdmissionGate<S> {
+    inner: S,
+    admission: StreamAdmission,
+}
+
+impl<S> AdmissionGate<S> {
+    pub(crate) fn new(inner: S, admission: StreamAdmission) -> Self {
+        Self { inner, admission }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for AdmissionGate<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<crate::BoxError>,
+{
+    type Response = S::Response;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        if self.admission.is_admitting() {
+            ResponseFuture::Inner {
+                future: self.inner.call(req),
+            }
+        } else {
+            ResponseFuture::Refused
+        }
+    }
+}
+
+#[pin_project(project = ResponseFutureProj)]
+pub(crate) enum ResponseFuture<F> {
+    Inner {
+        #[pin]
+        future: F,
+    },
+    Refused,
+}
+
+impl<F, E, ResBody> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+    E: Into<crate::BoxError>,
+{
+    type Output = Result<Response<ResBody>, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::Inner { future } => future.poll(cx).map_err(Into::into),
+            ResponseFutureProj::Refused => {
+                Poll::Ready(Err(h2::Error::from(h2::Reason::REFUSED_STREAM).into()))
+            }
+        }
+    }
+}
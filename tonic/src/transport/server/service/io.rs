@@ -1,4 +1,4 @@
-use crate::transport::server::Connected;
+use crate::transport::server::{Connected, PeerInfo};
 use std::io;
 use std::io::IoSlice;
 use std::pin::Pin;
@@ -42,6 +42,17 @@ impl<IO> ServerIo<IO> {
             Self::TlsIo(io) => Either::Right(io.connect_info()),
         }
     }
+
+    pub(in crate::transport) fn peer_info(&self) -> PeerInfo
+    where
+        IO: Connected,
+    {
+        match self {
+            Self::Io(io) => io.peer_info(),
+            #[cfg(feature = "_tls-any")]
+            Self::TlsIo(io) => io.peer_info(),
+        }
+    }
 }
 
 impl<IO> AsyncRead for ServerIo<IO>
@@ -0,0 +1,294 @@
+use crate::{
+    transport::{
+        server::ServerStats,
+        service::grpc_timeout::{request_timeout, try_parse_grpc_timeout},
+    },
+    Status,
+};
+use http::Request;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::ServiceExt;
+use tower_service::Service;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+type CostFnInner = dyn Fn(&Request<()>) -> u32 + Send + Sync;
+
+/// A user callback that weighs a request against the concurrency limit.
+#[derive(Clone)]
+pub(crate) struct CostFn(Arc<CostFnInner>);
+
+impl CostFn {
+    pub(crate) fn new(f: impl Fn(&Request<()>) -> u32 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Calls the callback with the head of `req`, without touching its body.
+    fn cost<B>(&self, req: Request<B>) -> (u32, Request<B>) {
+        let (parts, body) = req.into_parts();
+        let head = Request::from_parts(parts, ());
+        let cost = (self.0)(&head);
+        let (parts, ()) = head.into_parts();
+        (cost, Request::from_parts(parts, body))
+    }
+}
+
+impl fmt::Debug for CostFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CostFn").finish()
+    }
+}
+
+/// Limits the number of requests handled concurrently, taking their deadlines into account.
+///
+/// Requests beyond the limit are queued, but never past their deadline: a request is rejected
+/// with `DeadlineExceeded` as soon as its deadline expires while queued, or right away when its
+/// deadline is shorter than the average time requests take to complete, since it most likely
+/// expires before a slot opens.
+///
+/// Every request takes one slot, or as many as the [`CostFn`] assigns to it, capped at the limit.
+#[derive(Debug, Clone)]
+pub(crate) struct ConcurrencyLimit<S> {
+    inner: S,
+    state: Arc<State>,
+    server_timeout: Option<Duration>,
+    default_timeout: Option<Duration>,
+    cost_fn: Option<CostFn>,
+}
+
+#[derive(Debug)]
+struct State {
+    semaphore: Arc<Semaphore>,
+    limit: u32,
+    /// Exponentially weighted moving average of the handler latency in nanoseconds, or 0 until
+    /// the first request completed.
+    latency: AtomicU64,
+    stats: ServerStats,
+}
+
+impl State {
+    fn latency(&self) -> Option<Duration> {
+        match self.latency.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    fn record(&self, elapsed: Duration) {
+        let sample = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX).max(1);
+        let _ = self
+            .latency
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |latency| {
+                Some(match latency {
+                    0 => sample,
+                    latency => latency - latency / 8 + sample / 8,
+                })
+            });
+    }
+
+    async fn acquire(
+        &self,
+        cost: u32,
+        deadline: Option<Duration>,
+    ) -> Result<OwnedSemaphorePermit, Status> {
+        // A request costing more than the whole limit would never be admitted.
+        let cost = cost.min(self.limit);
+
+        if let Ok(permit) = self.semaphore.clone().try_acquire_many_owned(cost) {
+            return Ok(permit);
+        }
+
+        let _queued = self.stats.request_queued();
+        let permit = self.semaphore.clone().acquire_many_owned(cost);
+        let permit = match deadline {
+            Some(deadline) if self.latency().is_some_and(|latency| deadline < latency) => {
+                return Err(deadline_exceeded());
+            }
+            Some(deadline) => tokio::time::timeout(deadline, permit)
+                .await
+                .map_err(|_| deadline_exceeded())?,
+            None => permit.await,
+        };
+
+        Ok(permit.expect("semaphore is never closed"))
+    }
+}
+
+fn deadline_exceeded() -> Status {
+    Status::deadline_exceeded("deadline expires before the server can handle the request")
+}
+
+impl<S> ConcurrencyLimit<S> {
+    pub(crate) fn new(
+        inner: S,
+        limit: usize,
+        server_timeout: Option<Duration>,
+        default_timeout: Option<Duration>,
+        cost_fn: Option<CostFn>,
+        stats: ServerStats,
+    ) -> Self {
+        let limit = u32::try_from(limit).unwrap_or(u32::MAX);
+        Self {
+            inner,
+            state: Arc::new(State {
+                semaphore: Arc::new(Semaphore::new(limit as usize)),
+                limit,
+                latency: AtomicU64::new(0),
+                stats,
+            }),
+            server_timeout,
+            default_timeout,
+            cost_fn,
+        }
+    }
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for ConcurrencyLimit<S>
+where
+    S: Service<Request<ReqBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Into<crate::BoxError>,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = crate::BoxError;
+    type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Requests wait for a slot in the response future, where their deadline is known.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let client_timeout = try_parse_grpc_timeout(req.headers()).unwrap_or(None);
+        let deadline = request_timeout(client_timeout, self.server_timeout, self.default_timeout);
+        let (cost, req) = match &self.cost_fn {
+            Some(cost_fn) => cost_fn.cost(req),
+            None => (1, req),
+        };
+
+        let state = self.state.clone();
+        let inner = self.inner.clone();
+
+        Box::pin(async move {
+            let _permit = state.acquire(cost, deadline).await?;
+
+            let start = Instant::now();
+            let response = inner.oneshot(req).await.map_err(Into::into);
+            state.record(start.elapsed());
+
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_average() {
+        let limit = ConcurrencyLimit::new((), 1, None, None, None, ServerStats::default());
+        assert_eq!(limit.state.latency(), None);
+
+        limit.state.record(Duration::from_millis(80));
+        assert_eq!(limit.state.latency(), Some(Duration::from_millis(80)));
+
+        limit.state.record(Duration::from_millis(160));
+        assert_eq!(limit.state.latency(), Some(Duration::from_millis(90)));
+    }
+
+    #[tokio::test]
+    async fn rejects_deadline_shorter_than_latency() {
+        let limit = ConcurrencyLimit::new((), 1, None, None, None, ServerStats::default());
+        limit.state.record(Duration::from_secs(1));
+
+        let _busy = limit.state.acquire(1, None).await.unwrap();
+        let err = limit
+            .state
+            .acquire(1, Some(Duration::from_millis(500)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), crate::Code::DeadlineExceeded);
+    }
+
+    #[tokio::test]
+    async fn rejects_when_deadline_expires_while_queued() {
+        let limit = ConcurrencyLimit::new((), 1, None, None, None, ServerStats::default());
+
+        let _busy = limit.state.acquire(1, None).await.unwrap();
+        let err = limit
+            .state
+            .acquire(1, Some(Duration::from_millis(10)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), crate::Code::DeadlineExceeded);
+    }
+
+    #[tokio::test]
+    async fn counts_queued_requests() {
+        let stats = ServerStats::default();
+        let limit = ConcurrencyLimit::new((), 1, None, None, None, stats.clone());
+
+        let busy = limit.state.acquire(1, None).await.unwrap();
+        let waiting: Vec<_> = (0..2)
+            .map(|_| {
+                let state = limit.state.clone();
+                tokio::spawn(async move { drop(state.acquire(1, None).await.unwrap()) })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+        assert_eq!(stats.snapshot().queued_requests(), 2);
+
+        drop(busy);
+        for waiting in waiting {
+            waiting.await.unwrap();
+        }
+        assert_eq!(stats.snapshot().queued_requests(), 0);
+    }
+
+    #[tokio::test]
+    async fn cost_takes_multiple_slots() {
+        let limit = ConcurrencyLimit::new((), 4, None, None, None, ServerStats::default());
+
+        let expensive = limit.state.acquire(3, None).await.unwrap();
+        let _cheap = limit.state.acquire(1, None).await.unwrap();
+        assert_eq!(limit.state.semaphore.available_permits(), 0);
+
+        drop(expensive);
+        assert_eq!(limit.state.semaphore.available_permits(), 3);
+    }
+
+    #[tokio::test]
+    async fn cost_is_capped_at_the_limit() {
+        let limit = ConcurrencyLimit::new((), 4, None, None, None, ServerStats::default());
+
+        let _permit = limit.state.acquire(100, None).await.unwrap();
+        assert_eq!(limit.state.semaphore.available_permits(), 0);
+    }
+
+    #[test]
+    fn cost_fn_sees_the_request_head() {
+        let cost_fn = CostFn::new(|req| match req.uri().path() {
+            "/test.Test/Batch" => 10,
+            _ => 1,
+        });
+
+        let req = Request::post("/test.Test/Batch").body("body").unwrap();
+        let (cost, req) = cost_fn.cost(req);
+        assert_eq!(cost, 10);
+        assert_eq!(req.uri().path(), "/test.Test/Batch");
+        assert_eq!(*req.body(), "body");
+    }
+}
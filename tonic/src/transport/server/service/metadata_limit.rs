@@ -0,0 +1,174 @@
+use crate::{metadata::MetadataMap, Status};
+use http::{header::HeaderName, HeaderMap, Request, Response};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// The limits on the metadata of a single request, see [`Server::max_metadata_entries`],
+/// [`Server::max_metadata_size`] and [`Server::require_metadata`].
+///
+/// [`Server::max_metadata_entries`]: crate::transport::Server::max_metadata_entries
+/// [`Server::max_metadata_size`]: crate::transport::Server::max_metadata_size
+/// [`Server::require_metadata`]: crate::transport::Server::require_metadata
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MetadataLimits {
+    pub(crate) max_entries: Option<usize>,
+    pub(crate) max_size: Option<usize>,
+    pub(crate) required: Arc<[HeaderName]>,
+}
+
+impl MetadataLimits {
+    pub(crate) fn is_unlimited(&self) -> bool {
+        self.max_entries.is_none() && self.max_size.is_none() && self.required.is_empty()
+    }
+
+    /// Checks the custom metadata in `headers`, leaving out the headers reserved by gRPC.
+    fn check(&self, headers: &HeaderMap) -> Result<(), Status> {
+        let (entries, size) = headers
+            .iter()
+            .filter(|(name, _)| !MetadataMap::GRPC_RESERVED_HEADERS.contains(name))
+            .fold((0, 0), |(entries, size), (name, value)| {
+                (entries + 1, size + name.as_str().len() + value.len())
+            });
+
+        if let Some(max) = self.max_entries.filter(|max| entries > *max) {
+            return Err(Status::resource_exhausted(format!(
+                "request metadata has {} entries, more than the limit of {}",
+                entries, max
+            )));
+        }
+        if let Some(max) = self.max_size.filter(|max| size > *max) {
+            return Err(Status::resource_exhausted(format!(
+                "request metadata has {} bytes, more than the limit of {}",
+                size, max
+            )));
+        }
+        if let Some(missing) = self.required.iter().find(|key| !headers.contains_key(*key)) {
+            return Err(Status::invalid_argument(format!(
+                "request metadata is missing the required key `{}`",
+                missing
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Rejects requests whose metadata exceeds the [`MetadataLimits`] with `ResourceExhausted`, or
+/// lacks a required key with `InvalidArgument`, before they reach the handler.
+#[derive(Debug, Clone)]
+pub(crate) struct MetadataLimit<S> {
+    inner: S,
+    limits: MetadataLimits,
+}
+
+impl<S> MetadataLimit<S> {
+    pub(crate) fn new(inner: S, limits: MetadataLimits) -> Self {
+        Self { inner, limits }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for MetadataLimit<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<crate::BoxError>,
+{
+    type Response = S::Response;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        match self.limits.check(req.headers()) {
+            Ok(()) => ResponseFuture::Inner {
+                future: self.inner.call(req),
+            },
+            Err(status) => ResponseFuture::Rejected {
+                status: Some(status),
+            },
+        }
+    }
+}
+
+#[pin_project(project = ResponseFutureProj)]
+pub(crate) enum ResponseFuture<F> {
+    Inner {
+        #[pin]
+        future: F,
+    },
+    Rejected {
+        status: Option<Status>,
+    },
+}
+
+impl<F, E, ResBody> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+    E: Into<crate::BoxError>,
+{
+    type Output = Result<Response<ResBody>, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::Inner { future } => future.poll(cx).map_err(Into::into),
+            ResponseFutureProj::Rejected { status } => {
+                let status = status.take().expect("polled after completion");
+                Poll::Ready(Err(status.into()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_reserved_headers() {
+        let limits = MetadataLimits {
+            max_entries: Some(1),
+            max_size: Some(10),
+            ..Default::default()
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", "application/grpc".parse().unwrap());
+        headers.insert("te", "trailers".parse().unwrap());
+        headers.insert("x-a", "1".parse().unwrap());
+        assert!(limits.check(&headers).is_ok());
+
+        headers.insert("x-b", "1".parse().unwrap());
+        assert!(limits.check(&headers).is_err());
+
+        headers.remove("x-b");
+        headers.insert("x-a", "123456789".parse().unwrap());
+        assert!(limits.check(&headers).is_err());
+    }
+
+    #[test]
+    fn rejects_missing_required_keys() {
+        let limits = MetadataLimits {
+            required: Arc::from([
+                HeaderName::from_static("x-api-key"),
+                HeaderName::from_static("x-tenant-id"),
+            ]),
+            ..Default::default()
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "secret".parse().unwrap());
+        let status = limits.check(&headers).unwrap_err();
+        assert_eq!(status.code(), crate::Code::InvalidArgument);
+        assert!(status.message().contains("x-tenant-id"));
+
+        headers.insert("x-tenant-id", "acme".parse().unwrap());
+        assert!(limits.check(&headers).is_ok());
+    }
+}
@@ -0,0 +1,177 @@
+use crate::Status;
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body::{Body, Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower_service::Service;
+
+/// The limits on the requests in flight per method, shared by all connections of a server, see
+/// [`Server::method_concurrency`](crate::transport::Server::method_concurrency).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MethodLimits {
+    semaphores: Arc<HashMap<String, (usize, Arc<Semaphore>)>>,
+}
+
+impl MethodLimits {
+    pub(crate) fn insert(&mut self, path: String, limit: usize) {
+        Arc::make_mut(&mut self.semaphores).insert(path, (limit, Arc::new(Semaphore::new(limit))));
+    }
+
+    /// Takes a slot for a request to `path`, returning `None` for methods without a limit.
+    fn acquire(&self, path: &str) -> Result<Option<OwnedSemaphorePermit>, Status> {
+        let Some((limit, semaphore)) = self.semaphores.get(path) else {
+            return Ok(None);
+        };
+        match semaphore.clone().try_acquire_owned() {
+            Ok(permit) => Ok(Some(permit)),
+            Err(_) => Err(Status::resource_exhausted(format!(
+                "too many concurrent requests to {}, the limit is {}",
+                path, limit
+            ))),
+        }
+    }
+}
+
+/// Rejects requests with `ResourceExhausted` while their method has as many requests in flight
+/// as its [`MethodLimits`] allow.
+///
+/// A request is in flight until its response body ends, so streaming responses hold on to their
+/// slot while they stream.
+#[derive(Debug, Clone)]
+pub(crate) struct MethodConcurrencyLimit<S> {
+    inner: S,
+    limits: MethodLimits,
+}
+
+impl<S> MethodConcurrencyLimit<S> {
+    pub(crate) fn new(inner: S, limits: MethodLimits) -> Self {
+        Self { inner, limits }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for MethodConcurrencyLimit<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<crate::BoxError>,
+{
+    type Response = Response<PermitBody<ResBody>>;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        match self.limits.acquire(req.uri().path()) {
+            Ok(permit) => ResponseFuture::Inner {
+                future: self.inner.call(req),
+                permit,
+            },
+            Err(status) => ResponseFuture::Rejected {
+                status: Some(status),
+            },
+        }
+    }
+}
+
+#[pin_project(project = ResponseFutureProj)]
+pub(crate) enum ResponseFuture<F> {
+    Inner {
+        #[pin]
+        future: F,
+        permit: Option<OwnedSemaphorePermit>,
+    },
+    Rejected {
+        status: Option<Status>,
+    },
+}
+
+impl<F, E, ResBody> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+    E: Into<crate::BoxError>,
+{
+    type Output = Result<Response<PermitBody<ResBody>>, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::Inner { future, permit } => {
+                let response = ready!(future.poll(cx)).map_err(Into::into)?;
+                let permit = permit.take();
+                Poll::Ready(Ok(response.map(|inner| PermitBody { inner, permit })))
+            }
+            ResponseFutureProj::Rejected { status } => {
+                let status = status.take().expect("polled after completion");
+                Poll::Ready(Err(status.into()))
+            }
+        }
+    }
+}
+
+/// A response body holding on to its request's slot until it ends.
+#[pin_project]
+pub(crate) struct PermitBody<B> {
+    #[pin]
+    inner: B,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<B> Body for PermitBody<B>
+where
+    B: Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) if !frame.is_trailers() => {}
+            _ => {
+                this.permit.take();
+            }
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_only_configured_methods() {
+        let mut limits = MethodLimits::default();
+        limits.insert("/test.Test/Expensive".to_owned(), 1);
+
+        let permit = limits.acquire("/test.Test/Expensive").unwrap();
+        assert!(permit.is_some());
+        let err = limits.acquire("/test.Test/Expensive").unwrap_err();
+        assert_eq!(err.code(), crate::Code::ResourceExhausted);
+        assert!(limits.acquire("/test.Test/Cheap").unwrap().is_none());
+
+        drop(permit);
+        assert!(limits.acquire("/test.Test/Expensive").unwrap().is_some());
+    }
+}
@@ -1,10 +1,27 @@
+mod admission;
+pub(crate) use self::admission::AdmissionGate;
+
 mod io;
 pub(crate) use self::io::ServerIo;
 
+mod limit;
+pub(crate) use self::limit::{ConcurrencyLimit, CostFn};
+
+mod metadata_limit;
+pub(crate) use self::metadata_limit::{MetadataLimit, MetadataLimits};
+
+mod method_limit;
+pub(crate) use self::method_limit::{MethodConcurrencyLimit, MethodLimits};
+
+mod read_timeout;
+pub(crate) use self::read_timeout::ReadTimeoutBody;
+
 mod recover_error;
-pub(crate) use self::recover_error::RecoverError;
+pub(crate) use self::recover_error::{ErrorMappers, RecoverError};
 
 #[cfg(feature = "_tls-any")]
 mod tls;
 #[cfg(feature = "_tls-any")]
-pub(crate) use self::tls::TlsAcceptor;
+pub use self::tls::TlsAcceptor;
+#[cfg(feature = "_tls-any")]
+pub(crate) use self::tls::{ClientAuth, Protocols, SessionResumption, SniFilter};
@@ -0,0 +1,118 @@
+use crate::Status;
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::time::{Instant, Sleep};
+
+/// Fails a request body with `DeadlineExceeded` when no frame arrives within the timeout.
+///
+/// Only the time spent waiting for the client counts: the timer starts when the body is polled
+/// and stops as soon as a frame arrives, so slow handlers do not trip it.
+#[pin_project]
+pub(crate) struct ReadTimeoutBody<B> {
+    #[pin]
+    inner: B,
+    timeout: Duration,
+    #[pin]
+    sleep: Sleep,
+    armed: bool,
+}
+
+impl<B> ReadTimeoutBody<B> {
+    pub(crate) fn new(inner: B, timeout: Duration) -> Self {
+        Self {
+            inner,
+            timeout,
+            sleep: tokio::time::sleep(timeout),
+            armed: false,
+        }
+    }
+}
+
+impl<B> Body for ReadTimeoutBody<B>
+where
+    B: Body<Data = Bytes, Error = Status>,
+{
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+
+        if let Poll::Ready(frame) = this.inner.poll_frame(cx) {
+            *this.armed = false;
+            return Poll::Ready(frame);
+        }
+
+        if !*this.armed {
+            this.sleep.as_mut().reset(Instant::now() + *this.timeout);
+            *this.armed = true;
+        }
+
+        ready!(this.sleep.poll(cx));
+        Poll::Ready(Some(Err(Status::deadline_exceeded(format!(
+            "no message received from the client within {:?}",
+            this.timeout
+        )))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::{BodyExt, StreamBody};
+    use tokio_stream::StreamExt;
+
+    fn body(delays: &[u64]) -> impl Body<Data = Bytes, Error = Status> {
+        let frames = tokio_stream::iter(delays.to_vec()).then(|delay| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok(Frame::data(Bytes::from_static(b"message")))
+        });
+        StreamBody::new(Box::pin(frames))
+    }
+
+    #[tokio::test]
+    async fn frames_within_timeout() {
+        let body = ReadTimeoutBody::new(body(&[10, 10, 10]), Duration::from_millis(100));
+        let collected = body.collect().await.unwrap().to_bytes();
+        assert_eq!(collected.len(), 3 * b"message".len());
+    }
+
+    #[tokio::test]
+    async fn stalled_body_times_out() {
+        let body = ReadTimeoutBody::new(body(&[0, 500]), Duration::from_millis(50));
+        let mut body = std::pin::pin!(body);
+        assert!(body.frame().await.unwrap().is_ok());
+
+        let err = body.frame().await.unwrap().unwrap_err();
+        assert_eq!(err.code(), crate::Code::DeadlineExceeded);
+    }
+
+    #[tokio::test]
+    async fn time_between_polls_is_not_counted() {
+        let body = ReadTimeoutBody::new(body(&[0, 10]), Duration::from_millis(100));
+        let mut body = std::pin::pin!(body);
+        assert!(body.frame().await.unwrap().is_ok());
+
+        // The handler takes a while with the first message.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(body.frame().await.unwrap().is_ok());
+    }
+}
@@ -1,6 +1,10 @@
 use std::{
+    any::TypeId,
+    error::Error,
+    fmt,
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{ready, Context, Poll},
 };
 
@@ -10,16 +14,58 @@ use tower_service::Service;
 
 use crate::Status;
 
+type MapperFn = dyn Fn(&(dyn Error + 'static)) -> Option<Status> + Send + Sync;
+
+/// User callbacks turning errors of specific types into a `Status`, see
+/// [`Server::register_error_mapper`](crate::transport::Server::register_error_mapper).
+#[derive(Clone, Default)]
+pub(crate) struct ErrorMappers(Arc<Vec<(TypeId, Arc<MapperFn>)>>);
+
+impl ErrorMappers {
+    /// Registers `f` for errors of type `E`, replacing a previous mapper for the same type.
+    pub(crate) fn register<E, F>(&mut self, f: F)
+    where
+        E: Error + 'static,
+        F: Fn(&E) -> Status + Send + Sync + 'static,
+    {
+        let mapper: Arc<MapperFn> = Arc::new(move |err| err.downcast_ref::<E>().map(&f));
+        let mappers = Arc::make_mut(&mut self.0);
+        mappers.retain(|(type_id, _)| *type_id != TypeId::of::<E>());
+        mappers.push((TypeId::of::<E>(), mapper));
+    }
+
+    /// Maps the first error of the source chain of `err` that has a registered mapper.
+    fn map(&self, err: &(dyn Error + 'static)) -> Option<Status> {
+        let mut source = Some(err);
+        while let Some(err) = source {
+            if let Some(status) = self.0.iter().find_map(|(_, mapper)| mapper(err)) {
+                return Some(status);
+            }
+            source = err.source();
+        }
+        None
+    }
+}
+
+impl fmt::Debug for ErrorMappers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorMappers")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
 /// Middleware that attempts to recover from service errors by turning them into a response built
 /// from the `Status`.
 #[derive(Debug, Clone)]
 pub(crate) struct RecoverError<S> {
     inner: S,
+    mappers: ErrorMappers,
 }
 
 impl<S> RecoverError<S> {
-    pub(crate) fn new(inner: S) -> Self {
-        Self { inner }
+    pub(crate) fn new(inner: S, mappers: ErrorMappers) -> Self {
+        Self { inner, mappers }
     }
 }
 
@@ -39,6 +85,7 @@ where
     fn call(&mut self, req: Req) -> Self::Future {
         ResponseFuture {
             inner: self.inner.call(req),
+            mappers: self.mappers.clone(),
         }
     }
 }
@@ -47,6 +94,7 @@ where
 pub(crate) struct ResponseFuture<F> {
     #[pin]
     inner: F,
+    mappers: ErrorMappers,
 }
 
 impl<F, E, ResBody> Future for ResponseFuture<F>
@@ -57,19 +105,27 @@ where
     type Output = Result<Response<ResponseBody<ResBody>>, crate::BoxError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match ready!(self.project().inner.poll(cx)) {
+        let this = self.project();
+        match ready!(this.inner.poll(cx)) {
             Ok(response) => {
                 let response = response.map(ResponseBody::full);
                 Poll::Ready(Ok(response))
             }
-            Err(err) => match Status::try_from_error(err.into()) {
-                Ok(status) => {
-                    let (parts, ()) = status.into_http::<()>().into_parts();
-                    let res = Response::from_parts(parts, ResponseBody::empty());
-                    Poll::Ready(Ok(res))
+            Err(err) => {
+                let err = err.into();
+                let status = match this.mappers.map(&*err) {
+                    Some(status) => Ok(status),
+                    None => Status::try_from_error(err),
+                };
+                match status {
+                    Ok(status) => {
+                        let (parts, ()) = status.into_http::<()>().into_parts();
+                        let res = Response::from_parts(parts, ResponseBody::empty());
+                        Poll::Ready(Ok(res))
+                    }
+                    Err(err) => Poll::Ready(Err(err)),
                 }
-                Err(err) => Poll::Ready(Err(err)),
-            },
+            }
         }
     }
 }
@@ -121,3 +177,105 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Code;
+    use tower::ServiceExt;
+
+    #[derive(Debug)]
+    struct ValidationError;
+
+    impl fmt::Display for ValidationError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("validation failed")
+        }
+    }
+
+    impl Error for ValidationError {}
+
+    #[derive(Debug)]
+    struct QuotaError(Box<dyn Error + Send + Sync>);
+
+    impl fmt::Display for QuotaError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("quota exceeded")
+        }
+    }
+
+    impl Error for QuotaError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&*self.0)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Wrapped(ValidationError);
+
+    impl fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("wrapped")
+        }
+    }
+
+    impl Error for Wrapped {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    fn mappers() -> ErrorMappers {
+        let mut mappers = ErrorMappers::default();
+        mappers.register(|err: &ValidationError| Status::invalid_argument(err.to_string()));
+        mappers.register(|err: &QuotaError| Status::resource_exhausted(err.to_string()));
+        mappers
+    }
+
+    /// Runs a service failing with `err` through `RecoverError`, returning the status it
+    /// recovered, if any.
+    async fn status_of(err: crate::BoxError) -> Option<Status> {
+        let svc = tower::service_fn(|err: crate::BoxError| async { Err::<Response<()>, _>(err) });
+        let response = RecoverError::new(svc, mappers()).oneshot(err).await.ok()?;
+        Status::from_header_map(response.headers())
+    }
+
+    #[tokio::test]
+    async fn maps_registered_error_types() {
+        let status = status_of(Box::new(ValidationError)).await.unwrap();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "validation failed");
+
+        let status = status_of(Box::new(QuotaError("limit".into())))
+            .await
+            .unwrap();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.message(), "quota exceeded");
+    }
+
+    #[tokio::test]
+    async fn maps_errors_in_the_source_chain() {
+        let status = status_of(Box::new(Wrapped(ValidationError))).await.unwrap();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn keeps_unmapped_errors() {
+        let status = status_of(Box::new(Status::not_found("missing")))
+            .await
+            .unwrap();
+        assert_eq!(status.code(), Code::NotFound);
+
+        assert!(status_of("other error".into()).await.is_none());
+    }
+
+    #[test]
+    fn registering_a_type_again_replaces_its_mapper() {
+        let mut mappers = mappers();
+        mappers.register(|_: &ValidationError| Status::failed_precondition(""));
+
+        let status = mappers.map(&ValidationError).unwrap();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert_eq!(mappers.0.len(), 2);
+    }
+}
@@ -1,54 +1,145 @@
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+};
 
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_rustls::{
-    rustls::{server::WebPkiClientVerifier, RootCertStore, ServerConfig},
+    rustls::{
+        client::danger::HandshakeSignatureValid,
+        crypto::CryptoProvider,
+        pki_types::{pem::PemObject, CertificateDer, CertificateRevocationListDer, UnixTime},
+        server::{
+            danger::{ClientCertVerified, ClientCertVerifier},
+            ClientHello, NoServerSessionStorage, ProducesTickets, ResolvesServerCert,
+            ServerSessionMemoryCache, WebPkiClientVerifier,
+        },
+        sign::CertifiedKey,
+        CipherSuite, ConfigBuilder, DigitallySignedStruct, DistinguishedName, Error,
+        InconsistentKeys, ProtocolVersion, RootCertStore, ServerConfig, SignatureScheme,
+        WantsVerifier, ALL_VERSIONS, DEFAULT_VERSIONS,
+    },
     server::TlsStream,
     TlsAcceptor as RustlsAcceptor,
 };
+use x509_parser::{certificate::X509Certificate, prelude::FromDer};
 
 use crate::transport::{
-    service::tls::{convert_certificate_to_pki_types, convert_identity_to_pki_types, ALPN_H2},
+    service::tls::{
+        convert_certificate_to_pki_types, convert_identity_to_pki_types, TlsError, ALPN_H2,
+    },
     Certificate, Identity,
 };
 
+/// Terminates TLS for incoming connections.
+///
+/// An acceptor is built from a [`ServerTlsConfig`] with [`ServerTlsConfig::tls_acceptor`]. It is
+/// cheap to clone, so one acceptor can be shared between several servers, e.g. the public and an
+/// admin listener, by passing it to [`Server::tls_acceptor`] for each of them.
+///
+/// [`ServerTlsConfig`]: crate::transport::ServerTlsConfig
+/// [`ServerTlsConfig::tls_acceptor`]: crate::transport::ServerTlsConfig::tls_acceptor
+/// [`Server::tls_acceptor`]: crate::transport::Server::tls_acceptor
 #[derive(Clone)]
-pub(crate) struct TlsAcceptor {
+pub struct TlsAcceptor {
     inner: Arc<ServerConfig>,
+    cert: Arc<CertResolver>,
+    client_verifier: Option<Arc<ClientVerifier>>,
 }
 
 impl TlsAcceptor {
     pub(crate) fn new(
         identity: Identity,
-        client_ca_root: Option<Certificate>,
-        client_auth_optional: bool,
+        client_auth: Option<ClientAuth>,
+        ocsp_response: Option<Vec<u8>>,
+        sni_filter: Option<SniFilter>,
+        session_resumption: SessionResumption,
+        protocols: Protocols,
     ) -> Result<Self, crate::BoxError> {
-        let builder = ServerConfig::builder();
-
-        let builder = match client_ca_root {
-            None => builder.with_no_client_auth(),
-            Some(cert) => {
-                let mut roots = RootCertStore::empty();
-                roots.add_parsable_certificates(convert_certificate_to_pki_types(&cert)?);
-                let verifier = if client_auth_optional {
-                    WebPkiClientVerifier::builder(roots.into()).allow_unauthenticated()
-                } else {
-                    WebPkiClientVerifier::builder(roots.into())
-                }
-                .build()?;
-                builder.with_client_cert_verifier(verifier)
+        let builder = protocols.config_builder()?;
+
+        let (builder, client_verifier) = match client_auth {
+            None => (builder.with_no_client_auth(), None),
+            Some(client_auth) => {
+                let verifier = Arc::new(ClientVerifier::new(
+                    client_auth,
+                    builder.crypto_provider().clone(),
+                )?);
+                (
+                    builder.with_client_cert_verifier(verifier.clone()),
+                    Some(verifier),
+                )
             }
         };
 
         let (cert, key) = convert_identity_to_pki_types(&identity)?;
-        let mut config = builder.with_single_cert(cert, key)?;
+        let leaf = cert.first().ok_or(TlsError::CertificateMissing)?.clone();
+        let mut certified_key = CertifiedKey::from_der(cert, key, builder.crypto_provider())
+            .map_err(|err| match err {
+                Error::InconsistentKeys(InconsistentKeys::KeyMismatch) => {
+                    TlsError::KeyMismatch(subject(&leaf)).into()
+                }
+                err => crate::BoxError::from(err),
+            })?;
+        certified_key.ocsp = ocsp_response.filter(|response| !response.is_empty());
+
+        let cert = Arc::new(CertResolver {
+            key: RwLock::new(Arc::new(certified_key)),
+            sni_filter,
+        });
+        let mut config = builder.with_cert_resolver(cert.clone());
+
+        if let Some(size) = session_resumption.cache_size {
+            config.session_storage = if size == 0 {
+                Arc::new(NoServerSessionStorage {})
+            } else {
+                ServerSessionMemoryCache::new(size)
+            };
+        }
+        if session_resumption.tickets {
+            config.ticketer = ticketer()?;
+        }
 
         config.alpn_protocols.push(ALPN_H2.into());
         Ok(Self {
             inner: Arc::new(config),
+            cert,
+            client_verifier,
         })
     }
 
+    /// Replaces the OCSP response stapled to the certificate, or stops stapling one with `None`.
+    ///
+    /// The new response is used for all handshakes that start afterwards, on every server this
+    /// acceptor, or a clone of it, was passed to. Connections that are already established are
+    /// not affected.
+    pub fn set_ocsp_response(&self, response: Option<Vec<u8>>) {
+        let mut current = self.cert.key.write().unwrap();
+        let mut certified_key = CertifiedKey::clone(&current);
+        certified_key.ocsp = response.filter(|response| !response.is_empty());
+        *current = Arc::new(certified_key);
+    }
+
+    /// Replaces the PEM encoded certificate revocation lists client certificates are checked
+    /// against, see [`ServerTlsConfig::client_crls`]. Empty `pem` stops checking revocation.
+    ///
+    /// The new lists are used for all handshakes that start afterwards, on every server this
+    /// acceptor, or a clone of it, was passed to. Connections that are already established are
+    /// not affected, and the current lists are kept if the new ones can't be parsed.
+    ///
+    /// Returns an error if the acceptor doesn't verify client certificates, i.e. no
+    /// [`client_ca_root`](crate::transport::ServerTlsConfig::client_ca_root) was set, or the
+    /// lists can't be parsed.
+    ///
+    /// [`ServerTlsConfig::client_crls`]: crate::transport::ServerTlsConfig::client_crls
+    pub fn set_client_crls(&self, pem: impl AsRef<[u8]>) -> Result<(), crate::transport::Error> {
+        self.client_verifier
+            .as_ref()
+            .ok_or_else(|| crate::BoxError::from(TlsError::ClientCaRootMissing))
+            .and_then(|verifier| verifier.set_crls(pem.as_ref()))
+            .map_err(crate::transport::Error::new_tls)
+    }
+
     pub(crate) async fn accept<IO>(&self, io: IO) -> Result<TlsStream<IO>, crate::BoxError>
     where
         IO: AsyncRead + AsyncWrite + Unpin,
@@ -58,8 +149,730 @@ impl TlsAcceptor {
     }
 }
 
+/// How client certificates are verified, see [`ServerTlsConfig::client_ca_root`].
+///
+/// [`ServerTlsConfig::client_ca_root`]: crate::transport::ServerTlsConfig::client_ca_root
+#[derive(Clone, Debug)]
+pub(crate) struct ClientAuth {
+    pub(crate) root: Certificate,
+    pub(crate) optional: bool,
+    pub(crate) crls: Option<Vec<u8>>,
+}
+
+/// Verifies client certificates, checking them against revocation lists that can be swapped
+/// out while the acceptor is in use.
+#[derive(Debug)]
+struct ClientVerifier {
+    roots: Arc<RootCertStore>,
+    optional: bool,
+    provider: Arc<CryptoProvider>,
+    subjects: Vec<DistinguishedName>,
+    inner: RwLock<Arc<dyn ClientCertVerifier>>,
+}
+
+impl ClientVerifier {
+    fn new(
+        client_auth: ClientAuth,
+        provider: Arc<CryptoProvider>,
+    ) -> Result<Self, crate::BoxError> {
+        let mut roots = RootCertStore::empty();
+        roots.add_parsable_certificates(convert_certificate_to_pki_types(&client_auth.root)?);
+        let roots = Arc::new(roots);
+        let crls = client_auth.crls.as_deref().unwrap_or_default();
+        let inner = Self::build(&roots, &provider, client_auth.optional, crls)?;
+
+        Ok(Self {
+            subjects: roots.subjects(),
+            roots,
+            optional: client_auth.optional,
+            provider,
+            inner: RwLock::new(inner),
+        })
+    }
+
+    /// Builds a verifier checking client certificates against `roots` and the CRLs in `pem`.
+    fn build(
+        roots: &Arc<RootCertStore>,
+        provider: &Arc<CryptoProvider>,
+        optional: bool,
+        pem: &[u8],
+    ) -> Result<Arc<dyn ClientCertVerifier>, crate::BoxError> {
+        let crls = CertificateRevocationListDer::pem_slice_iter(pem)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| TlsError::CrlParseError)?;
+        // Don't silently stop checking revocation when given something other than CRLs.
+        if crls.is_empty() && !pem.trim_ascii().is_empty() {
+            return Err(TlsError::CrlParseError.into());
+        }
+        let builder = WebPkiClientVerifier::builder_with_provider(roots.clone(), provider.clone())
+            .with_crls(crls);
+        let builder = if optional {
+            builder.allow_unauthenticated()
+        } else {
+            builder
+        };
+        Ok(builder.build()?)
+    }
+
+    fn set_crls(&self, crls: &[u8]) -> Result<(), crate::BoxError> {
+        let verifier = Self::build(&self.roots, &self.provider, self.optional, crls)?;
+        *self.inner.write().unwrap() = verifier;
+        Ok(())
+    }
+
+    fn current(&self) -> Arc<dyn ClientCertVerifier> {
+        self.inner.read().unwrap().clone()
+    }
+}
+
+impl ClientCertVerifier for ClientVerifier {
+    fn offer_client_auth(&self) -> bool {
+        true
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        !self.optional
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &self.subjects
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, Error> {
+        self.current()
+            .verify_client_cert(end_entity, intermediates, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        self.current().verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        self.current().verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.current().supported_verify_schemes()
+    }
+}
+
+/// How clients may resume their sessions, see [`ServerTlsConfig::session_cache_size`] and
+/// [`ServerTlsConfig::session_tickets`].
+///
+/// [`ServerTlsConfig::session_cache_size`]: crate::transport::ServerTlsConfig::session_cache_size
+/// [`ServerTlsConfig::session_tickets`]: crate::transport::ServerTlsConfig::session_tickets
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct SessionResumption {
+    pub(crate) cache_size: Option<usize>,
+    pub(crate) tickets: bool,
+}
+
+/// The TLS protocol versions and cipher suites to offer, see
+/// [`ServerTlsConfig::protocol_versions`] and [`ServerTlsConfig::cipher_suites`].
+///
+/// [`ServerTlsConfig::protocol_versions`]: crate::transport::ServerTlsConfig::protocol_versions
+/// [`ServerTlsConfig::cipher_suites`]: crate::transport::ServerTlsConfig::cipher_suites
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Protocols {
+    pub(crate) versions: Option<Vec<ProtocolVersion>>,
+    pub(crate) cipher_suites: Option<Vec<CipherSuite>>,
+}
+
+impl Protocols {
+    /// Starts a config of the default crypto provider, restricted to these versions and suites.
+    fn config_builder(
+        &self,
+    ) -> Result<ConfigBuilder<ServerConfig, WantsVerifier>, crate::BoxError> {
+        let versions = match &self.versions {
+            None => DEFAULT_VERSIONS.to_vec(),
+            Some(enabled) => ALL_VERSIONS
+                .iter()
+                .copied()
+                .filter(|version| enabled.contains(&version.version))
+                .collect(),
+        };
+        if versions.is_empty() {
+            return Err(TlsError::NoProtocolVersion.into());
+        }
+
+        let mut provider = CryptoProvider::clone(ServerConfig::builder().crypto_provider());
+        if let Some(enabled) = &self.cipher_suites {
+            provider
+                .cipher_suites
+                .retain(|suite| enabled.contains(&suite.suite()));
+        }
+        if !provider
+            .cipher_suites
+            .iter()
+            .any(|suite| versions.contains(&suite.version()))
+        {
+            return Err(TlsError::NoCipherSuite.into());
+        }
+
+        Ok(ServerConfig::builder_with_provider(Arc::new(provider))
+            .with_protocol_versions(&versions)?)
+    }
+}
+
+/// The subject of a certificate for error messages, e.g. `CN=localhost`.
+fn subject(cert: &CertificateDer<'_>) -> String {
+    match X509Certificate::from_der(cert.as_ref()) {
+        Ok((_, cert)) => cert.subject().to_string(),
+        Err(_) => "<unparsable certificate>".to_owned(),
+    }
+}
+
+/// A ticketer of the enabled crypto provider, rotating its key every 6 hours.
+#[allow(unreachable_code)]
+fn ticketer() -> Result<Arc<dyn ProducesTickets>, crate::BoxError> {
+    #[cfg(feature = "tls-ring")]
+    return Ok(tokio_rustls::rustls::crypto::ring::Ticketer::new()?);
+    #[cfg(feature = "tls-aws-lc")]
+    return Ok(tokio_rustls::rustls::crypto::aws_lc_rs::Ticketer::new()?);
+    Err(TlsError::TicketerUnavailable.into())
+}
+
+/// Decides which server names clients may ask for, see [`ServerTlsConfig::sni_filter`].
+///
+/// [`ServerTlsConfig::sni_filter`]: crate::transport::ServerTlsConfig::sni_filter
+#[derive(Clone)]
+pub(crate) struct SniFilter(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl SniFilter {
+    pub(crate) fn new(f: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for SniFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SniFilter").finish()
+    }
+}
+
+/// Hands out the server certificate, which can be swapped out while the acceptor is in use.
+///
+/// Returning no certificate makes rustls abort the handshake with an `access_denied` alert.
+#[derive(Debug)]
+struct CertResolver {
+    key: RwLock<Arc<CertifiedKey>>,
+    sni_filter: Option<SniFilter>,
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        if let Some(filter) = &self.sni_filter {
+            if !client_hello
+                .server_name()
+                .is_some_and(|name| (filter.0)(name))
+            {
+                return None;
+            }
+        }
+        Some(self.key.read().unwrap().clone())
+    }
+}
+
 impl fmt::Debug for TlsAcceptor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("TlsAcceptor").finish()
     }
 }
+
+#[cfg(all(test, feature = "tls-ring"))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tokio_rustls::{
+        rustls::{
+            client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+            client::WebPkiServerVerifier,
+            pki_types::{CertificateDer, ServerName, UnixTime},
+            version::{TLS12, TLS13},
+            ClientConfig, DigitallySignedStruct, HandshakeKind, SignatureScheme,
+        },
+        TlsConnector,
+    };
+
+    /// Verifies the server certificate as usual and records the stapled OCSP response.
+    #[derive(Debug)]
+    struct RecordOcsp {
+        inner: Arc<WebPkiServerVerifier>,
+        stapled: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl ServerCertVerifier for RecordOcsp {
+        fn verify_server_cert(
+            &self,
+            end_entity: &CertificateDer<'_>,
+            intermediates: &[CertificateDer<'_>],
+            server_name: &ServerName<'_>,
+            ocsp_response: &[u8],
+            now: UnixTime,
+        ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+            self.stapled.lock().unwrap().push(ocsp_response.to_vec());
+            self.inner.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                ocsp_response,
+                now,
+            )
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+            self.inner.verify_tls12_signature(message, cert, dss)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+            self.inner.verify_tls13_signature(message, cert, dss)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.inner.supported_verify_schemes()
+        }
+    }
+
+    async fn handshake(acceptor: &TlsAcceptor, verifier: Arc<RecordOcsp>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let acceptor = acceptor.clone();
+        let server = tokio::spawn(async move {
+            let (io, _) = listener.accept().await.unwrap();
+            acceptor.accept(io).await.unwrap();
+        });
+
+        let config = ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
+            .with_no_client_auth();
+        let io = TcpStream::connect(addr).await.unwrap();
+        TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), io)
+            .await
+            .unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn staples_ocsp_response() {
+        let _ = tokio_rustls::rustls::crypto::ring::default_provider().install_default();
+
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let identity = Identity::from_pem(cert.pem(), key_pair.serialize_pem());
+        let acceptor = TlsAcceptor::new(
+            identity,
+            None,
+            Some(b"first response".to_vec()),
+            None,
+            SessionResumption::default(),
+            Protocols::default(),
+        )
+        .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.der().clone()).unwrap();
+        let verifier = Arc::new(RecordOcsp {
+            inner: WebPkiServerVerifier::builder(roots.into()).build().unwrap(),
+            stapled: Mutex::default(),
+        });
+
+        handshake(&acceptor, verifier.clone()).await;
+        acceptor.set_ocsp_response(Some(b"second response".to_vec()));
+        handshake(&acceptor, verifier.clone()).await;
+        acceptor.set_ocsp_response(None);
+        handshake(&acceptor, verifier.clone()).await;
+
+        assert_eq!(
+            *verifier.stapled.lock().unwrap(),
+            [
+                b"first response".to_vec(),
+                b"second response".to_vec(),
+                Vec::new()
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_filtered_server_names() {
+        let _ = tokio_rustls::rustls::crypto::ring::default_provider().install_default();
+
+        let names = vec!["allowed.example".to_owned(), "denied.example".to_owned()];
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(names).unwrap();
+        let identity = Identity::from_pem(cert.pem(), key_pair.serialize_pem());
+        let filter = SniFilter::new(|name| name == "allowed.example");
+        let acceptor = TlsAcceptor::new(
+            identity,
+            None,
+            None,
+            Some(filter),
+            SessionResumption::default(),
+            Protocols::default(),
+        )
+        .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.der().clone()).unwrap();
+        let config = Arc::new(
+            ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        );
+
+        for (name, allowed) in [("allowed.example", true), ("denied.example", false)] {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let acceptor = acceptor.clone();
+            let server = tokio::spawn(async move {
+                let (io, _) = listener.accept().await.unwrap();
+                acceptor.accept(io).await.map(drop)
+            });
+
+            let io = TcpStream::connect(addr).await.unwrap();
+            let client = TlsConnector::from(config.clone())
+                .connect(ServerName::try_from(name).unwrap(), io)
+                .await;
+
+            assert_eq!(client.is_ok(), allowed, "{name}");
+            assert_eq!(server.await.unwrap().is_ok(), allowed, "{name}");
+            if !allowed {
+                let err = client.unwrap_err().to_string();
+                assert!(err.contains("AccessDenied"), "{err}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_key_of_another_certificate() {
+        let _ = tokio_rustls::rustls::crypto::ring::default_provider().install_default();
+
+        let mut params = rcgen::CertificateParams::new(vec!["localhost".to_owned()]).unwrap();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "localhost");
+        let cert = params
+            .self_signed(&rcgen::KeyPair::generate().unwrap())
+            .unwrap();
+        let other_key = rcgen::KeyPair::generate().unwrap();
+
+        let identity = Identity::from_pem(cert.pem(), other_key.serialize_pem());
+        let err = TlsAcceptor::new(
+            identity,
+            None,
+            None,
+            None,
+            SessionResumption::default(),
+            Protocols::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.contains("does not match the certificate `CN=localhost`"),
+            "{err}"
+        );
+
+        let identity = Identity::from_pem("", other_key.serialize_pem());
+        let err = TlsAcceptor::new(
+            identity,
+            None,
+            None,
+            None,
+            SessionResumption::default(),
+            Protocols::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(err, "No certificate found in the server identity.");
+    }
+
+    #[tokio::test]
+    async fn rejects_disabled_protocol_versions() {
+        let _ = tokio_rustls::rustls::crypto::ring::default_provider().install_default();
+
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let identity = Identity::from_pem(cert.pem(), key_pair.serialize_pem());
+        let acceptor = TlsAcceptor::new(
+            identity,
+            None,
+            None,
+            None,
+            SessionResumption::default(),
+            Protocols {
+                versions: Some(vec![ProtocolVersion::TLSv1_3]),
+                cipher_suites: None,
+            },
+        )
+        .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.der().clone()).unwrap();
+        for (version, allowed) in [(&TLS12, false), (&TLS13, true)] {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let acceptor = acceptor.clone();
+            let server = tokio::spawn(async move {
+                let (io, _) = listener.accept().await.unwrap();
+                acceptor.accept(io).await.map(drop)
+            });
+
+            let config = ClientConfig::builder_with_protocol_versions(&[version])
+                .with_root_certificates(roots.clone())
+                .with_no_client_auth();
+            let io = TcpStream::connect(addr).await.unwrap();
+            let client = TlsConnector::from(Arc::new(config))
+                .connect(ServerName::try_from("localhost").unwrap(), io)
+                .await;
+
+            assert_eq!(client.is_ok(), allowed, "{:?}", version.version);
+            assert_eq!(
+                server.await.unwrap().is_ok(),
+                allowed,
+                "{:?}",
+                version.version
+            );
+            if !allowed {
+                let err = client.unwrap_err().to_string();
+                assert!(err.contains("ProtocolVersion"), "{err}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_configs_without_usable_cipher_suites() {
+        let _ = tokio_rustls::rustls::crypto::ring::default_provider().install_default();
+
+        let protocols = Protocols {
+            versions: Some(vec![ProtocolVersion::SSLv3]),
+            cipher_suites: None,
+        };
+        let err = protocols.config_builder().err().unwrap().to_string();
+        assert_eq!(
+            err,
+            "None of the configured TLS protocol versions is supported."
+        );
+
+        // Only a TLS 1.2 suite, but only TLS 1.3 enabled.
+        let protocols = Protocols {
+            versions: Some(vec![ProtocolVersion::TLSv1_3]),
+            cipher_suites: Some(vec![CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384]),
+        };
+        let err = protocols.config_builder().err().unwrap().to_string();
+        assert!(
+            err.starts_with("None of the configured TLS cipher suites"),
+            "{err}"
+        );
+
+        let protocols = Protocols {
+            versions: None,
+            cipher_suites: Some(vec![CipherSuite::TLS13_AES_256_GCM_SHA384]),
+        };
+        assert!(protocols.config_builder().is_ok());
+    }
+
+    /// Connects twice with the same client, returning the kind of each handshake.
+    async fn handshake_kinds(session_resumption: SessionResumption) -> Vec<HandshakeKind> {
+        let _ = tokio_rustls::rustls::crypto::ring::default_provider().install_default();
+
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let identity = Identity::from_pem(cert.pem(), key_pair.serialize_pem());
+        let acceptor = TlsAcceptor::new(
+            identity,
+            None,
+            None,
+            None,
+            session_resumption,
+            Protocols::default(),
+        )
+        .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.der().clone()).unwrap();
+        let config = Arc::new(
+            ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        );
+
+        let mut kinds = Vec::new();
+        for _ in 0..2 {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let acceptor = acceptor.clone();
+            let server = tokio::spawn(async move {
+                let (io, _) = listener.accept().await.unwrap();
+                let mut tls = acceptor.accept(io).await.unwrap();
+                tls.write_all(b"x").await.unwrap();
+                tls.shutdown().await.unwrap();
+            });
+
+            let io = TcpStream::connect(addr).await.unwrap();
+            let mut tls = TlsConnector::from(config.clone())
+                .connect(ServerName::try_from("localhost").unwrap(), io)
+                .await
+                .unwrap();
+            // Reading processes the TLS 1.3 tickets sent after the handshake.
+            let mut received = Vec::new();
+            tls.read_to_end(&mut received).await.unwrap();
+            kinds.push(tls.get_ref().1.handshake_kind().unwrap());
+            server.await.unwrap();
+        }
+        kinds
+    }
+
+    #[tokio::test]
+    async fn resumes_sessions() {
+        let cached = handshake_kinds(SessionResumption::default()).await;
+        assert_eq!(cached, [HandshakeKind::Full, HandshakeKind::Resumed]);
+
+        let tickets = handshake_kinds(SessionResumption {
+            cache_size: Some(0),
+            tickets: true,
+        })
+        .await;
+        assert_eq!(tickets, [HandshakeKind::Full, HandshakeKind::Resumed]);
+
+        let disabled = handshake_kinds(SessionResumption {
+            cache_size: Some(0),
+            tickets: false,
+        })
+        .await;
+        assert_eq!(disabled, [HandshakeKind::Full, HandshakeKind::Full]);
+    }
+
+    /// Completes a handshake with a client presenting `client`, returning the server's result.
+    async fn accept_client(
+        acceptor: &TlsAcceptor,
+        server_cert: &rcgen::Certificate,
+        client: &Identity,
+    ) -> Result<(), crate::BoxError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let acceptor = acceptor.clone();
+        let server = tokio::spawn(async move {
+            let (io, _) = listener.accept().await.unwrap();
+            let mut io = acceptor.accept(io).await?;
+            io.write_all(b"ok").await?;
+            Ok(())
+        });
+
+        let mut roots = RootCertStore::empty();
+        roots.add(server_cert.der().clone()).unwrap();
+        let (chain, key) = convert_identity_to_pki_types(client).unwrap();
+        let config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_client_auth_cert(chain, key)
+            .unwrap();
+        let io = TcpStream::connect(addr).await.unwrap();
+        // With TLS 1.3 the client finishes its handshake before the server checked its
+        // certificate, so wait for the server's result.
+        if let Ok(mut io) = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), io)
+            .await
+        {
+            let _ = io.read_to_end(&mut Vec::new()).await;
+        }
+        server.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn rejects_revoked_client_certificates() {
+        let _ = tokio_rustls::rustls::crypto::ring::default_provider().install_default();
+
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let identity = Identity::from_pem(cert.pem(), key_pair.serialize_pem());
+
+        let mut ca_params = rcgen::CertificateParams::default();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca_key = rcgen::KeyPair::generate().unwrap();
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+        let issue = |serial: u64| {
+            let mut params = rcgen::CertificateParams::default();
+            params.serial_number = Some(serial.into());
+            let key = rcgen::KeyPair::generate().unwrap();
+            let cert = params.signed_by(&key, &ca, &ca_key).unwrap();
+            Identity::from_pem(cert.pem(), key.serialize_pem())
+        };
+        let (revoked, valid) = (issue(1), issue(2));
+
+        let now = rcgen::date_time_ymd(2024, 1, 1);
+        let crl = rcgen::CertificateRevocationListParams {
+            this_update: now,
+            next_update: rcgen::date_time_ymd(2124, 1, 1),
+            crl_number: 1u64.into(),
+            issuing_distribution_point: None,
+            revoked_certs: vec![rcgen::RevokedCertParams {
+                serial_number: 1u64.into(),
+                revocation_time: now,
+                reason_code: Some(rcgen::RevocationReason::KeyCompromise),
+                invalidity_date: None,
+            }],
+            key_identifier_method: rcgen::KeyIdMethod::Sha256,
+        }
+        .signed_by(&ca, &ca_key)
+        .unwrap()
+        .pem()
+        .unwrap();
+
+        let acceptor = TlsAcceptor::new(
+            identity,
+            Some(ClientAuth {
+                root: Certificate::from_pem(ca.pem()),
+                optional: false,
+                crls: Some(crl.into_bytes()),
+            }),
+            None,
+            None,
+            SessionResumption::default(),
+            Protocols::default(),
+        )
+        .unwrap();
+
+        accept_client(&acceptor, &cert, &valid).await.unwrap();
+        let err = accept_client(&acceptor, &cert, &revoked)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Revoked"), "{err}");
+
+        assert!(acceptor.set_client_crls("not a CRL").is_err());
+        accept_client(&acceptor, &cert, &revoked).await.unwrap_err();
+
+        acceptor.set_client_crls("").unwrap();
+        accept_client(&acceptor, &cert, &revoked).await.unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+use super::ServerStats;
+use std::{
+    future::Future,
+    io,
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// How a graceful shutdown completed, see [`ShutdownReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShutdownOutcome {
+    /// All connections closed on their own, after completing their requests.
+    Drained,
+    /// The [graceful shutdown timeout] passed, so the remaining connections were closed and
+    /// their requests aborted.
+    ///
+    /// [graceful shutdown timeout]: super::Server::graceful_shutdown_timeout
+    Forced,
+}
+
+/// The result of a graceful shutdown, returned by [`Router::serve_with_shutdown`] and its
+/// variants, and by [`Server::shutdown_complete`].
+///
+/// Requests are counted from the [`ServerStats`] of the server, which are shared by every
+/// [`Router`] created from the same builder.
+///
+/// ```
+/// # use tonic::transport::server::ShutdownReport;
+/// fn log(report: &ShutdownReport) {
+///     println!(
+///         "{:?}: drained {} requests in {:?}, aborted {}",
+///         report.outcome(),
+///         report.drained(),
+///         report.elapsed(),
+///         report.aborted(),
+///     );
+/// }
+/// ```
+///
+/// [`Router::serve_with_shutdown`]: super::Router::serve_with_shutdown
+/// [`Server::shutdown_complete`]: super::Server::shutdown_complete
+/// [`Router`]: super::Router
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+    outcome: ShutdownOutcome,
+    drained: u64,
+    aborted: u64,
+    elapsed: Duration,
+}
+
+impl ShutdownReport {
+    /// How the shutdown completed.
+    pub fn outcome(&self) -> ShutdownOutcome {
+        self.outcome
+    }
+
+    /// The number of requests completed while draining.
+    pub fn drained(&self) -> u64 {
+        self.drained
+    }
+
+    /// The number of requests still in flight when the shutdown was forced.
+    pub fn aborted(&self) -> u64 {
+        self.aborted
+    }
+
+    /// The time from the shutdown signal until all connections were closed.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// Tracks the requests completed from the start of a drain.
+pub(crate) struct Drain {
+    stats: ServerStats,
+    started_at: Instant,
+    in_flight: u64,
+    total: u64,
+}
+
+impl Drain {
+    pub(crate) fn start(stats: &ServerStats) -> Self {
+        let snapshot = stats.snapshot();
+        Self {
+            stats: stats.clone(),
+            started_at: Instant::now(),
+            in_flight: snapshot.active_requests(),
+            total: snapshot.total_requests(),
+        }
+    }
+
+    pub(crate) fn finish(self, outcome: ShutdownOutcome, aborted: u64) -> ShutdownReport {
+        // Requests received while draining, e.g. on connections that didn't see the GOAWAY
+        // yet, count as well.
+        let received = self.stats.snapshot().total_requests() - self.total;
+        ShutdownReport {
+            outcome,
+            drained: (self.in_flight + received).saturating_sub(aborted),
+            aborted,
+            elapsed: self.started_at.elapsed(),
+        }
+    }
+}
+
+/// A future resolving once the process is asked to terminate, see
+/// [`Router::serve_with_default_signals`](super::Router::serve_with_default_signals).
+///
+/// The handlers are installed right away, so signals received before the future is polled
+/// count as well.
+pub(crate) fn default_signals() -> io::Result<impl Future<Output = ()>> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        Ok(async move {
+            tokio::select! {
+                _ = terminate.recv() => debug!("received SIGTERM, shutting down"),
+                _ = interrupt.recv() => debug!("received SIGINT, shutting down"),
+            }
+        })
+    }
+
+    #[cfg(not(unix))]
+    {
+        Ok(async {
+            match tokio::signal::ctrl_c().await {
+                Ok(()) => debug!("received Ctrl-C, shutting down"),
+                Err(err) => {
+                    // Keep serving rather than shutting down without being asked to.
+                    tracing::error!("failed to listen for Ctrl-C: {}", err);
+                    std::future::pending::<()>().await;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_requests_completed_while_draining() {
+        let stats = ServerStats::default();
+        let done = stats.record_request(Some("/test.Test/Done"));
+        let _pending = stats.record_request(Some("/test.Test/Pending"));
+
+        let drain = Drain::start(&stats);
+        drop(done);
+        drop(stats.record_request(Some("/test.Test/Late")));
+
+        let report = drain.finish(ShutdownOutcome::Forced, stats.active_requests());
+        assert_eq!(report.outcome(), ShutdownOutcome::Forced);
+        assert_eq!(report.drained(), 2);
+        assert_eq!(report.aborted(), 1);
+    }
+}
@@ -0,0 +1,311 @@
+use super::PeerInfo;
+use crate::{
+    body::Body,
+    service::status_interceptor::{BoxObserver, StatusResponseBody, StatusResponseFuture},
+};
+use bytes::Bytes;
+use http::{HeaderMap, Request, Response};
+use http_body::{Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+type OnSlowRequest = Arc<dyn Fn(&SlowRequest) + Send + Sync + 'static>;
+
+/// A layer reporting RPCs that take longer than a threshold.
+///
+/// A call is checked once its response body completes, so streaming RPCs are measured until the
+/// stream terminates. Calls exceeding the threshold emit a `WARN` event with the
+/// `tonic::slow_request` target, holding the method path, the peer address and the elapsed time.
+/// Calls that are dropped before completing are checked as well.
+///
+/// To capture more than the event, e.g. to feed a profiler, register a callback with
+/// [`on_slow_request`](Self::on_slow_request). It receives a [`SlowRequest`] sample including the
+/// request headers.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tonic::transport::{server::SlowRequestLayer, Server};
+/// Server::builder().layer(
+///     SlowRequestLayer::new(Duration::from_millis(500)).on_slow_request(|sample| {
+///         eprintln!("{} took {:?}", sample.method(), sample.elapsed());
+///     }),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct SlowRequestLayer {
+    threshold: Duration,
+    on_slow_request: Option<OnSlowRequest>,
+}
+
+impl SlowRequestLayer {
+    /// Create a layer reporting calls that take longer than `threshold`.
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            on_slow_request: None,
+        }
+    }
+
+    /// Call `f` with a sample of every slow call, in addition to emitting the event.
+    pub fn on_slow_request<F>(self, f: F) -> Self
+    where
+        F: Fn(&SlowRequest) + Send + Sync + 'static,
+    {
+        Self {
+            on_slow_request: Some(Arc::new(f)),
+            ..self
+        }
+    }
+}
+
+impl fmt::Debug for SlowRequestLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowRequestLayer")
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for SlowRequestLayer {
+    type Service = SlowRequestDetector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SlowRequestDetector {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// A sample of a slow call, passed to the callback of [`SlowRequestLayer::on_slow_request`].
+#[derive(Clone, Debug)]
+pub struct SlowRequest {
+    method: String,
+    peer: Option<SocketAddr>,
+    elapsed: Duration,
+    headers: HeaderMap,
+}
+
+impl SlowRequest {
+    /// The method path, e.g. `/helloworld.Greeter/SayHello`.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The address of the client, if known.
+    pub fn peer(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+
+    /// The time from receiving the request until its response completed.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The headers of the request.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+/// Middleware reporting slow calls, see [`SlowRequestLayer`].
+#[derive(Clone)]
+pub struct SlowRequestDetector<S> {
+    inner: S,
+    layer: SlowRequestLayer,
+}
+
+impl<S> fmt::Debug for SlowRequestDetector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowRequestDetector")
+            .field("threshold", &self.layer.threshold)
+            .finish()
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for SlowRequestDetector<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+{
+    type Response = Response<SlowRequestBody<ResBody>>;
+    type Error = S::Error;
+    type Future = SlowRequestFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let timer = Timer {
+            layer: self.layer.clone(),
+            start: Instant::now(),
+            method: req.uri().path().to_owned(),
+            peer: req
+                .extensions()
+                .get::<PeerInfo>()
+                .and_then(|info| info.remote_addr),
+            // Only kept around for the callback.
+            headers: self
+                .layer
+                .on_slow_request
+                .as_ref()
+                .map(|_| req.headers().clone()),
+        };
+
+        let observer: BoxObserver = Box::new(move |_| timer.check());
+
+        SlowRequestFuture {
+            inner: StatusResponseFuture::new(self.inner.call(req), observer),
+        }
+    }
+}
+
+/// Response future for [`SlowRequestDetector`].
+#[pin_project]
+pub struct SlowRequestFuture<F> {
+    #[pin]
+    inner: StatusResponseFuture<F, BoxObserver>,
+}
+
+impl<F> fmt::Debug for SlowRequestFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowRequestFuture").finish()
+    }
+}
+
+impl<F, ResBody, E> Future for SlowRequestFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<SlowRequestBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let response = ready!(self.project().inner.poll(cx))?;
+        Poll::Ready(Ok(response.map(|inner| SlowRequestBody { inner })))
+    }
+}
+
+/// Response body for [`SlowRequestDetector`], checking the elapsed time once it completes.
+#[pin_project]
+pub struct SlowRequestBody<B> {
+    #[pin]
+    inner: StatusResponseBody<B, BoxObserver>,
+}
+
+impl<B> fmt::Debug for SlowRequestBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowRequestBody").finish()
+    }
+}
+
+impl<B> http_body::Body for SlowRequestBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+struct Timer {
+    layer: SlowRequestLayer,
+    start: Instant,
+    method: String,
+    peer: Option<SocketAddr>,
+    headers: Option<HeaderMap>,
+}
+
+impl Timer {
+    fn check(self) {
+        let elapsed = self.start.elapsed();
+        if elapsed <= self.layer.threshold {
+            return;
+        }
+
+        tracing::warn!(
+            target: "tonic::slow_request",
+            method = %self.method,
+            peer = ?self.peer,
+            elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+            "slow request"
+        );
+
+        if let Some(on_slow_request) = &self.layer.on_slow_request {
+            on_slow_request(&SlowRequest {
+                method: self.method,
+                peer: self.peer,
+                elapsed,
+                headers: self.headers.unwrap_or_default(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+
+    async fn call(delay: Duration) -> Vec<SlowRequest> {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let layer = SlowRequestLayer::new(Duration::from_millis(50)).on_slow_request({
+            let samples = samples.clone();
+            move |sample| samples.lock().unwrap().push(sample.clone())
+        });
+
+        let svc = tower::service_fn(move |_: Request<Body>| async move {
+            tokio::time::sleep(delay).await;
+            Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+        });
+        let request = Request::builder()
+            .uri("/test.Test/UnaryCall")
+            .header("x-request-id", "1")
+            .body(Body::empty())
+            .unwrap();
+        let response = layer.layer(svc).oneshot(request).await.unwrap();
+        response.into_body().collect().await.unwrap();
+
+        let samples = samples.lock().unwrap().clone();
+        samples
+    }
+
+    #[tokio::test]
+    async fn reports_slow_calls() {
+        let samples = call(Duration::from_millis(100)).await;
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].method(), "/test.Test/UnaryCall");
+        assert_eq!(samples[0].headers()["x-request-id"], "1");
+        assert!(samples[0].elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn ignores_fast_calls() {
+        assert!(call(Duration::ZERO).await.is_empty());
+    }
+}
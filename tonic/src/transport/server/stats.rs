@@ -0,0 +1,336 @@
+use crate::Code;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+const CODES: usize = Code::Unauthenticated as usize + 1;
+
+/// A shared handle to the statistics collected by a [`Server`].
+///
+/// Obtained through [`Server::stats`]. All counters are updated with relaxed atomic operations,
+/// so a [`StatsSnapshot`] is not guaranteed to be consistent across fields while requests are
+/// in flight.
+///
+/// [`Server`]: super::Server
+/// [`Server::stats`]: super::Server::stats
+#[derive(Clone, Debug, Default)]
+pub struct ServerStats {
+    inner: Arc<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    active_connections: AtomicU64,
+    total_connections: AtomicU64,
+    recycled_connections: AtomicU64,
+    active_requests: AtomicU64,
+    total_requests: AtomicU64,
+    queued_requests: AtomicU64,
+    requests_per_method: RwLock<HashMap<String, Arc<MethodCounters>>>,
+    errors_by_code: [AtomicU64; CODES],
+}
+
+#[derive(Debug, Default)]
+struct MethodCounters {
+    active: AtomicU64,
+    total: AtomicU64,
+}
+
+impl ServerStats {
+    /// Take a point-in-time snapshot of the collected statistics.
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let counters = &self.inner;
+
+        let methods = counters
+            .requests_per_method
+            .read()
+            .unwrap_or_else(|e| e.into_inner());
+        let requests_per_method = methods
+            .iter()
+            .map(|(method, counters)| (method.clone(), counters.total.load(Ordering::Relaxed)))
+            .collect();
+        let active_requests_per_method = methods
+            .iter()
+            .map(|(method, counters)| (method.clone(), counters.active.load(Ordering::Relaxed)))
+            .collect();
+        drop(methods);
+
+        let errors_by_code = counters
+            .errors_by_code
+            .iter()
+            .enumerate()
+            .map(|(code, count)| (Code::from_i32(code as i32), count.load(Ordering::Relaxed)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+
+        StatsSnapshot {
+            active_connections: counters.active_connections.load(Ordering::Relaxed),
+            total_connections: counters.total_connections.load(Ordering::Relaxed),
+            recycled_connections: counters.recycled_connections.load(Ordering::Relaxed),
+            active_requests: counters.active_requests.load(Ordering::Relaxed),
+            total_requests: counters.total_requests.load(Ordering::Relaxed),
+            queued_requests: counters.queued_requests.load(Ordering::Relaxed),
+            requests_per_method,
+            active_requests_per_method,
+            errors_by_code,
+        }
+    }
+
+    /// The number of requests currently in flight.
+    ///
+    /// A request is in flight from the moment it is received until its response, including
+    /// all messages of a streaming response, has been sent or the call was cancelled. During a
+    /// graceful shutdown, e.g. through [`Router::serve_with_shutdown`], this reaches zero once
+    /// the server has drained.
+    ///
+    /// [`Router::serve_with_shutdown`]: super::Router::serve_with_shutdown
+    pub fn active_requests(&self) -> u64 {
+        self.inner.active_requests.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn connection_opened(&self) -> ConnectionGuard {
+        self.inner
+            .active_connections
+            .fetch_add(1, Ordering::Relaxed);
+        self.inner.total_connections.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard {
+            stats: self.clone(),
+        }
+    }
+
+    /// Record a received request, which stays active until the returned guard is dropped.
+    ///
+    /// Requests to paths that aren't known methods, i.e. `method` is `None`, are counted under
+    /// [`StatsSnapshot::UNKNOWN_METHOD`], so that clients can't grow the stats with random paths.
+    pub(crate) fn record_request(&self, method: Option<&str>) -> RequestGuard {
+        let method = method.unwrap_or(StatsSnapshot::UNKNOWN_METHOD);
+        let counters = &self.inner;
+        counters.total_requests.fetch_add(1, Ordering::Relaxed);
+        counters.active_requests.fetch_add(1, Ordering::Relaxed);
+
+        let found = counters
+            .requests_per_method
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(method)
+            .cloned();
+        let method = found.unwrap_or_else(|| {
+            counters
+                .requests_per_method
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(method.to_owned())
+                .or_default()
+                .clone()
+        });
+        method.total.fetch_add(1, Ordering::Relaxed);
+        method.active.fetch_add(1, Ordering::Relaxed);
+
+        RequestGuard {
+            stats: self.clone(),
+            method,
+            completed: false,
+        }
+    }
+
+    /// Record a request waiting for a slot of the concurrency limit, until the returned guard is
+    /// dropped.
+    pub(crate) fn request_queued(&self) -> QueuedGuard {
+        self.inner.queued_requests.fetch_add(1, Ordering::Relaxed);
+        QueuedGuard {
+            stats: self.clone(),
+        }
+    }
+
+    fn record_code(&self, code: Code) {
+        if code != Code::Ok {
+            self.inner.errors_by_code[code as usize].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A point-in-time view of the statistics collected by a [`Server`](super::Server).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    active_connections: u64,
+    total_connections: u64,
+    recycled_connections: u64,
+    active_requests: u64,
+    total_requests: u64,
+    queued_requests: u64,
+    requests_per_method: HashMap<String, u64>,
+    active_requests_per_method: HashMap<String, u64>,
+    errors_by_code: HashMap<Code, u64>,
+}
+
+impl StatsSnapshot {
+    /// The method counting the requests to paths that aren't methods of the services added to
+    /// the server, e.g. those answered by a fallback, see
+    /// [`requests_per_method`](Self::requests_per_method).
+    pub const UNKNOWN_METHOD: &'static str = "<unknown>";
+
+    /// The number of connections currently being served.
+    pub fn active_connections(&self) -> u64 {
+        self.active_connections
+    }
+
+    /// The total number of connections accepted.
+    ///
+    /// Together with [`total_requests`](Self::total_requests), this tells how well clients reuse
+    /// their connections.
+    pub fn total_connections(&self) -> u64 {
+        self.total_connections
+    }
+
+    /// The number of connections closed because they reached
+    /// [`Server::max_requests_per_connection`](super::Server::max_requests_per_connection).
+    pub fn recycled_connections(&self) -> u64 {
+        self.recycled_connections
+    }
+
+    /// The number of requests in flight, see [`ServerStats::active_requests`].
+    pub fn active_requests(&self) -> u64 {
+        self.active_requests
+    }
+
+    /// The total number of requests received.
+    pub fn total_requests(&self) -> u64 {
+        self.total_requests
+    }
+
+    /// The number of requests currently waiting for a slot of the
+    /// [concurrency limit](super::Server::concurrency_limit_per_connection), across all
+    /// connections.
+    ///
+    /// A queue that is rarely empty means the limit is too low for the load, or the handlers too
+    /// slow. Queued requests are also [active](Self::active_requests).
+    pub fn queued_requests(&self) -> u64 {
+        self.queued_requests
+    }
+
+    /// The number of requests received per method path, e.g. `/helloworld.Greeter/SayHello`.
+    ///
+    /// Only the methods of the services added to the server are counted by their path, see
+    /// [`Routes::services`]. Requests to any other path are counted together under
+    /// [`UNKNOWN_METHOD`](Self::UNKNOWN_METHOD).
+    ///
+    /// [`Routes::services`]: crate::service::Routes::services
+    pub fn requests_per_method(&self) -> &HashMap<String, u64> {
+        &self.requests_per_method
+    }
+
+    /// The number of requests in flight per method path, including methods without any, see
+    /// [`ServerStats::active_requests`] and [`Server::method_concurrency`].
+    ///
+    /// [`Server::method_concurrency`]: super::Server::method_concurrency
+    pub fn active_requests_per_method(&self) -> &HashMap<String, u64> {
+        &self.active_requests_per_method
+    }
+
+    /// The number of requests that completed with a non-`Ok` status, grouped by [`Code`].
+    ///
+    /// Requests dropped before completing, e.g. because the client cancelled them, count as
+    /// [`Code::Cancelled`].
+    pub fn errors_by_code(&self) -> &HashMap<Code, u64> {
+        &self.errors_by_code
+    }
+}
+
+/// Decrements the active connection count when dropped.
+#[derive(Debug)]
+pub(crate) struct ConnectionGuard {
+    stats: ServerStats,
+}
+
+impl ConnectionGuard {
+    /// Record that the connection is closed because it reached its request limit.
+    pub(crate) fn recycled(&self) {
+        self.stats
+            .inner
+            .recycled_connections
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.stats
+            .inner
+            .active_connections
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Decrements the queued request count when dropped.
+#[derive(Debug)]
+pub(crate) struct QueuedGuard {
+    stats: ServerStats,
+}
+
+impl Drop for QueuedGuard {
+    fn drop(&mut self) {
+        self.stats
+            .inner
+            .queued_requests
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Decrements the active request count when dropped.
+///
+/// Requests whose guard is dropped before [`RequestGuard::complete`] was called were cancelled,
+/// e.g. because the client reset the stream, and are recorded with `Cancelled`.
+#[derive(Debug)]
+pub(crate) struct RequestGuard {
+    stats: ServerStats,
+    method: Arc<MethodCounters>,
+    completed: bool,
+}
+
+impl RequestGuard {
+    /// Record the final status code of the request, which stops being active.
+    pub(crate) fn complete(mut self, code: Code) {
+        self.stats.record_code(code);
+        self.completed = true;
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.stats.record_code(Code::Cancelled);
+        }
+        self.stats
+            .inner
+            .active_requests
+            .fetch_sub(1, Ordering::Relaxed);
+        self.method.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_methods_share_one_entry() {
+        let stats = ServerStats::default();
+        drop(stats.record_request(Some("/test.Test/Known")));
+        for _ in 0..3 {
+            drop(stats.record_request(None));
+        }
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_requests(), 4);
+        assert_eq!(snapshot.requests_per_method().len(), 2);
+        assert_eq!(
+            snapshot.requests_per_method()[StatsSnapshot::UNKNOWN_METHOD],
+            3
+        );
+        assert_eq!(snapshot.requests_per_method()["/test.Test/Known"], 1);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::server::timing::ServerTiming;
+use http::Request;
+use std::{
+    sync::{Arc, OnceLock},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_service::Service;
+
+/// When a request was received and when its handler started.
+///
+/// This type is accessible through [request extensions][ext] of every request served by
+/// [`Server`], for handlers and layers to tell the time a request spent waiting for a slot of
+/// the [concurrency limit] from the time spent handling it. Requests queue, and the handler
+/// starts, before any layer added with [`Server::layer`] sees them.
+///
+/// ```
+/// # use tonic::{Request, transport::server::RequestTiming};
+/// fn log_timing(request: &Request<()>) {
+///     if let Some(timing) = request.extensions().get::<RequestTiming>() {
+///         println!("queued for {:?}", timing.queued());
+///     }
+/// }
+/// ```
+///
+/// [ext]: crate::Request::extensions
+/// [`Server`]: super::Server
+/// [`Server::layer`]: super::Server::layer
+/// [concurrency limit]: super::Server::concurrency_limit_per_connection
+#[derive(Debug, Clone)]
+pub struct RequestTiming {
+    connection_accepted_at: Instant,
+    received_at: Instant,
+    handler_started_at: Arc<OnceLock<Instant>>,
+}
+
+impl RequestTiming {
+    pub(crate) fn new(connection_accepted_at: Instant) -> Self {
+        Self {
+            connection_accepted_at,
+            received_at: Instant::now(),
+            handler_started_at: Arc::default(),
+        }
+    }
+
+    /// When the connection carrying the request was accepted.
+    pub fn connection_accepted_at(&self) -> Instant {
+        self.connection_accepted_at
+    }
+
+    /// When the server received the request headers.
+    pub fn received_at(&self) -> Instant {
+        self.received_at
+    }
+
+    /// When the request left the queue and its handler started, or `None` while it is queued.
+    pub fn handler_started_at(&self) -> Option<Instant> {
+        self.handler_started_at.get().copied()
+    }
+
+    /// How long the request waited before its handler started, so far if it is still queued.
+    pub fn queued(&self) -> Duration {
+        match self.handler_started_at() {
+            Some(started_at) => started_at - self.received_at,
+            None => self.received_at.elapsed(),
+        }
+    }
+
+    /// How long the handler has been running, zero while the request is queued.
+    pub fn in_handler(&self) -> Duration {
+        self.handler_started_at()
+            .map_or(Duration::ZERO, |started_at| started_at.elapsed())
+    }
+}
+
+/// Records the handler start in the [`RequestTiming`] of each request it passes on.
+#[derive(Debug, Clone)]
+pub(crate) struct MarkHandlerStart<S> {
+    inner: S,
+}
+
+impl<S> MarkHandlerStart<S> {
+    pub(crate) fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for MarkHandlerStart<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        if let Some(timing) = req.extensions().get::<RequestTiming>() {
+            let _ = timing.handler_started_at.set(Instant::now());
+        }
+        if let Some(timing) = req.extensions().get::<ServerTiming>() {
+            timing.dequeued();
+        }
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::server::service::ConcurrencyLimit;
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn saturated_limit_shows_as_queue_time() {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let handler = tower::service_fn({
+            let samples = samples.clone();
+            move |req: Request<()>| {
+                let timing = req.extensions().get::<RequestTiming>().unwrap();
+                samples
+                    .lock()
+                    .unwrap()
+                    .push((timing.queued(), timing.in_handler()));
+                async {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Ok::<_, crate::BoxError>(())
+                }
+            }
+        });
+        let svc = ConcurrencyLimit::new(
+            MarkHandlerStart::new(handler),
+            1,
+            None,
+            None,
+            None,
+            Default::default(),
+        );
+
+        let request = || {
+            let mut req = Request::new(());
+            req.extensions_mut()
+                .insert(RequestTiming::new(Instant::now()));
+            req
+        };
+        let (first, second) = tokio::join!(
+            svc.clone().oneshot(request()),
+            svc.clone().oneshot(request())
+        );
+        first.unwrap();
+        second.unwrap();
+
+        let samples = samples.lock().unwrap();
+        assert!(samples[0].0 < Duration::from_millis(50), "{samples:?}");
+        // The second request waits for the first one to complete.
+        assert!(samples[1].0 >= Duration::from_millis(100), "{samples:?}");
+        assert!(samples[1].1 < Duration::from_millis(50), "{samples:?}");
+    }
+
+    #[test]
+    fn queued_until_the_handler_starts() {
+        let timing = RequestTiming::new(Instant::now());
+        assert_eq!(timing.handler_started_at(), None);
+        assert_eq!(timing.in_handler(), Duration::ZERO);
+
+        let mut svc = MarkHandlerStart::new(tower::service_fn(|_: Request<()>| async {
+            Ok::<_, crate::BoxError>(())
+        }));
+        let mut req = Request::new(());
+        req.extensions_mut().insert(timing.clone());
+        drop(svc.call(req));
+
+        let started_at = timing.handler_started_at().unwrap();
+        assert_eq!(timing.queued(), started_at - timing.received_at());
+    }
+}
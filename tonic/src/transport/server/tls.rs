@@ -1,7 +1,13 @@
 use std::fmt;
 
-use super::service::TlsAcceptor;
-use crate::transport::tls::{Certificate, Identity};
+use tokio_rustls::rustls::{CipherSuite, ProtocolVersion};
+
+use super::service::{ClientAuth, Protocols, SessionResumption, SniFilter, TlsAcceptor};
+use crate::transport::{
+    service::tls::TlsError,
+    tls::{Certificate, Identity},
+    Error,
+};
 
 /// Configures TLS settings for servers.
 #[derive(Clone, Default)]
@@ -9,6 +15,11 @@ pub struct ServerTlsConfig {
     identity: Option<Identity>,
     client_ca_root: Option<Certificate>,
     client_auth_optional: bool,
+    client_crls: Option<Vec<u8>>,
+    ocsp_response: Option<Vec<u8>>,
+    sni_filter: Option<SniFilter>,
+    session_resumption: SessionResumption,
+    protocols: Protocols,
 }
 
 impl fmt::Debug for ServerTlsConfig {
@@ -24,6 +35,11 @@ impl ServerTlsConfig {
             identity: None,
             client_ca_root: None,
             client_auth_optional: false,
+            client_crls: None,
+            ocsp_response: None,
+            sni_filter: None,
+            session_resumption: SessionResumption::default(),
+            protocols: Protocols::default(),
         }
     }
 
@@ -56,11 +72,180 @@ impl ServerTlsConfig {
         }
     }
 
-    pub(crate) fn tls_acceptor(&self) -> Result<TlsAcceptor, crate::BoxError> {
+    /// Sets PEM encoded certificate revocation lists (CRLs) to check client certificates against.
+    ///
+    /// Client certificates revoked by one of the lists are rejected during the handshake, even if
+    /// they are otherwise valid. Once lists are set, the revocation status of every certificate
+    /// in the client's chain must be known, so certificates of an issuer without a list are
+    /// rejected too. CRLs are reissued periodically, so a long-running server should pass new
+    /// ones to [`TlsAcceptor::set_client_crls`].
+    ///
+    /// This option has effect only if CA certificate is set.
+    ///
+    /// ```
+    /// # use tonic::transport::{Certificate, ServerTlsConfig};
+    /// # let (ca, crl) = (String::new(), String::new());
+    /// let config = ServerTlsConfig::new()
+    ///     .client_ca_root(Certificate::from_pem(ca))
+    ///     .client_crls(crl);
+    /// ```
+    pub fn client_crls(self, pem: impl AsRef<[u8]>) -> Self {
+        ServerTlsConfig {
+            client_crls: Some(pem.as_ref().to_vec()),
+            ..self
+        }
+    }
+
+    /// Sets a DER encoded OCSP response to staple to the server certificate.
+    ///
+    /// Clients that ask for the certificate status receive the response during the handshake, so
+    /// they can check revocation without contacting the issuer. OCSP responses expire, so a
+    /// long-running server should fetch new ones and pass them to
+    /// [`TlsAcceptor::set_ocsp_response`]. An empty response is ignored.
+    pub fn ocsp_response(self, response: impl Into<Vec<u8>>) -> Self {
+        ServerTlsConfig {
+            ocsp_response: Some(response.into()),
+            ..self
+        }
+    }
+
+    /// Only complete handshakes with clients asking for a server name `filter` accepts.
+    ///
+    /// The filter is called with the server name indication (SNI) of each client hello. When it
+    /// returns `false`, the handshake is aborted with an `access_denied` alert, before the server
+    /// certificate is sent, so e.g. a multi-tenant server can turn away unknown tenants right
+    /// away. Clients that don't send a server name, like ones connecting to an IP address, are
+    /// rejected too.
+    ///
+    /// ```
+    /// # use tonic::transport::ServerTlsConfig;
+    /// let tenants = ["a.example.com", "b.example.com"];
+    /// let config = ServerTlsConfig::new().sni_filter(move |name| tenants.contains(&name));
+    /// ```
+    pub fn sni_filter(self, filter: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        ServerTlsConfig {
+            sni_filter: Some(SniFilter::new(filter)),
+            ..self
+        }
+    }
+
+    /// Sets how many sessions the server remembers for clients to resume.
+    ///
+    /// A client reconnecting with a remembered session, by its session ID with TLS 1.2 or a
+    /// ticket referring to it with TLS 1.3, resumes it with an abbreviated handshake instead of a
+    /// full one, which saves most of the handshake's CPU time. The oldest sessions are forgotten
+    /// once the cache is full. `0` disables this kind of resumption.
+    ///
+    /// # Default
+    /// By default, 256 sessions are remembered.
+    pub fn session_cache_size(self, size: usize) -> Self {
+        ServerTlsConfig {
+            session_resumption: SessionResumption {
+                cache_size: Some(size),
+                ..self.session_resumption
+            },
+            ..self
+        }
+    }
+
+    /// Sets whether to issue stateless session tickets.
+    ///
+    /// A ticket holds the whole session, encrypted with a key only the server knows, so clients
+    /// can resume any number of sessions without the server remembering them, see
+    /// [`session_cache_size`](Self::session_cache_size). The key is rotated every 6 hours and is
+    /// not shared between acceptors, so tickets can't be resumed on another acceptor or process.
+    ///
+    /// Building the acceptor fails if neither the `tls-ring` nor the `tls-aws-lc` feature is
+    /// enabled.
+    ///
+    /// # Default
+    /// By default, this option is set to `false`.
+    pub fn session_tickets(self, enabled: bool) -> Self {
+        ServerTlsConfig {
+            session_resumption: SessionResumption {
+                tickets: enabled,
+                ..self.session_resumption
+            },
+            ..self
+        }
+    }
+
+    /// Restricts the TLS protocol versions the server accepts.
+    ///
+    /// Clients only supporting other versions fail the handshake with a `protocol_version`
+    /// alert, e.g. `&[ProtocolVersion::TLSv1_3]` turns away TLS 1.2 clients. Versions the TLS
+    /// implementation doesn't support, i.e. anything but TLS 1.2 and 1.3, are ignored. Building the
+    /// acceptor fails if no supported version remains.
+    ///
+    /// ```
+    /// # use tonic::transport::{ProtocolVersion, ServerTlsConfig};
+    /// let config = ServerTlsConfig::new().protocol_versions(&[ProtocolVersion::TLSv1_3]);
+    /// ```
+    ///
+    /// # Default
+    /// By default, TLS 1.2 and 1.3 are accepted.
+    pub fn protocol_versions(self, versions: &[ProtocolVersion]) -> Self {
+        ServerTlsConfig {
+            protocols: Protocols {
+                versions: Some(versions.to_vec()),
+                ..self.protocols
+            },
+            ..self
+        }
+    }
+
+    /// Restricts the cipher suites the server negotiates to `suites`.
+    ///
+    /// Only the suites the crypto provider implements are used, in the provider's order of
+    /// preference. Building the acceptor fails if none of them remains for the enabled
+    /// [`protocol_versions`](Self::protocol_versions).
+    ///
+    /// ```
+    /// # use tonic::transport::{CipherSuite, ServerTlsConfig};
+    /// let config = ServerTlsConfig::new().cipher_suites(&[
+    ///     CipherSuite::TLS13_AES_256_GCM_SHA384,
+    ///     CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+    /// ]);
+    /// ```
+    ///
+    /// # Default
+    /// By default, all cipher suites of the crypto provider are used.
+    pub fn cipher_suites(self, suites: &[CipherSuite]) -> Self {
+        ServerTlsConfig {
+            protocols: Protocols {
+                cipher_suites: Some(suites.to_vec()),
+                ..self.protocols
+            },
+            ..self
+        }
+    }
+
+    /// Builds a [`TlsAcceptor`] from this configuration.
+    ///
+    /// The acceptor can be passed to [`Server::tls_acceptor`] for any number of servers, so the
+    /// certificates are only parsed once.
+    ///
+    /// Returns an error if no [`identity`](Self::identity) is set, the certificates, key or
+    /// revocation lists can't be parsed, or no protocol version or cipher suite is left enabled.
+    ///
+    /// [`Server::tls_acceptor`]: super::Server::tls_acceptor
+    pub fn tls_acceptor(&self) -> Result<TlsAcceptor, Error> {
+        let identity = self
+            .identity
+            .clone()
+            .ok_or_else(|| Error::new_tls(TlsError::IdentityMissing))?;
         TlsAcceptor::new(
-            self.identity.clone().unwrap(),
-            self.client_ca_root.clone(),
-            self.client_auth_optional,
+            identity,
+            self.client_ca_root.clone().map(|root| ClientAuth {
+                root,
+                optional: self.client_auth_optional,
+                crls: self.client_crls.clone(),
+            }),
+            self.ocsp_response.clone(),
+            self.sni_filter.clone(),
+            self.session_resumption,
+            self.protocols.clone(),
         )
+        .map_err(Error::new_tls)
     }
 }
@@ -1,11 +1,19 @@
-use crate::transport::channel::BoxFuture;
 use hyper_util::rt::TokioExecutor;
-use std::{future::Future, sync::Arc};
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
 
 pub(crate) use hyper::rt::Executor;
 
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The executor the tasks of a [`Server`] or [`Channel`] are spawned on.
+///
+/// This is the executor type of the `hyper` builders passed to [`Server::configure_hyper`].
+///
+/// [`Server`]: crate::transport::Server
+/// [`Server::configure_hyper`]: crate::transport::Server::configure_hyper
+/// [`Channel`]: crate::transport::Channel
 #[derive(Clone)]
-pub(crate) struct SharedExec {
+pub struct SharedExec {
     inner: Arc<dyn Executor<BoxFuture<'static, ()>> + Send + Sync + 'static>,
 }
 
@@ -24,6 +32,12 @@ impl SharedExec {
     }
 }
 
+impl fmt::Debug for SharedExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedExec").finish()
+    }
+}
+
 impl<F> Executor<F> for SharedExec
 where
     F: Future<Output = ()> + Send + 'static,
@@ -14,13 +14,19 @@ use tower_service::Service;
 pub(crate) struct GrpcTimeout<S> {
     inner: S,
     server_timeout: Option<Duration>,
+    default_timeout: Option<Duration>,
 }
 
 impl<S> GrpcTimeout<S> {
-    pub(crate) fn new(inner: S, server_timeout: Option<Duration>) -> Self {
+    pub(crate) fn new(
+        inner: S,
+        server_timeout: Option<Duration>,
+        default_timeout: Option<Duration>,
+    ) -> Self {
         Self {
             inner,
             server_timeout,
+            default_timeout,
         }
     }
 }
@@ -43,17 +49,8 @@ where
             tracing::trace!("Error parsing `grpc-timeout` header {:?}", e);
             None
         });
-
-        // Use the shorter of the two durations, if either are set
-        let timeout_duration = match (client_timeout, self.server_timeout) {
-            (None, None) => None,
-            (Some(dur), None) => Some(dur),
-            (None, Some(dur)) => Some(dur),
-            (Some(header), Some(server)) => {
-                let shorter_duration = std::cmp::min(header, server);
-                Some(shorter_duration)
-            }
-        };
+        let timeout_duration =
+            request_timeout(client_timeout, self.server_timeout, self.default_timeout);
 
         ResponseFuture {
             inner: self.inner.call(req),
@@ -93,6 +90,20 @@ where
     }
 }
 
+/// The timeout of a request: the `grpc-timeout` sent by the client, or `default_timeout` if it
+/// didn't send one, capped at `server_timeout`.
+pub(crate) fn request_timeout(
+    client_timeout: Option<Duration>,
+    server_timeout: Option<Duration>,
+    default_timeout: Option<Duration>,
+) -> Option<Duration> {
+    // Use the shorter of the two durations, if either are set
+    match (client_timeout.or(default_timeout), server_timeout) {
+        (Some(client), Some(server)) => Some(client.min(server)),
+        (client, server) => client.or(server),
+    }
+}
+
 const SECONDS_IN_HOUR: u64 = 60 * 60;
 const SECONDS_IN_MINUTE: u64 = 60;
 
@@ -100,7 +111,7 @@ const SECONDS_IN_MINUTE: u64 = 60;
 /// the value we attempted to parse.
 ///
 /// Follows the [gRPC over HTTP2 spec](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md).
-fn try_parse_grpc_timeout(
+pub(crate) fn try_parse_grpc_timeout(
     headers: &HeaderMap<HeaderValue>,
 ) -> Result<Option<Duration>, &HeaderValue> {
     let Some(val) = headers.get(GRPC_TIMEOUT_HEADER) else {
@@ -148,6 +159,7 @@ fn try_parse_grpc_timeout(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Status;
     use quickcheck::{Arbitrary, Gen};
     use quickcheck_macros::quickcheck;
 
@@ -225,6 +237,47 @@ mod tests {
         setup_map_try_parse(Some("oneH")).unwrap().unwrap();
     }
 
+    /// Runs a request with the given `grpc-timeout` through a handler that never completes,
+    /// returning how long it took to time out.
+    async fn time_out(header: Option<&str>, default_timeout: Duration) -> Duration {
+        let handler =
+            tower::service_fn(|_: Request<()>| std::future::pending::<Result<(), Status>>());
+        let mut svc = GrpcTimeout::new(handler, None, Some(default_timeout));
+
+        let mut req = Request::new(());
+        if let Some(header) = header {
+            req.headers_mut()
+                .insert(GRPC_TIMEOUT_HEADER, HeaderValue::from_str(header).unwrap());
+        }
+
+        let started = std::time::Instant::now();
+        let err = svc.call(req).await.unwrap_err();
+        assert!(err.is::<TimeoutExpired>());
+        started.elapsed()
+    }
+
+    #[tokio::test]
+    async fn default_timeout_bounds_requests_without_header() {
+        let elapsed = time_out(None, Duration::from_millis(50)).await;
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn shorter_client_timeout_wins_over_default() {
+        let elapsed = time_out(Some("20m"), Duration::from_secs(30)).await;
+        assert!(elapsed < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn server_timeout_caps_default_timeout() {
+        let (short, long) = (Some(Duration::from_secs(1)), Some(Duration::from_secs(2)));
+        assert_eq!(request_timeout(None, short, long), short);
+        assert_eq!(request_timeout(None, long, short), short);
+        assert_eq!(request_timeout(long, None, short), long);
+        assert_eq!(request_timeout(None, None, None), None);
+    }
+
     #[quickcheck]
     fn fuzz(header_value: HeaderValueGen) -> bool {
         let header_value = header_value.0;
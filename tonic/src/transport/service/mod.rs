@@ -1,5 +1,8 @@
+pub(crate) mod executor;
 pub(crate) mod grpc_timeout;
 #[cfg(feature = "_tls-any")]
 pub(crate) mod tls;
 
+pub(crate) use self::executor::Executor;
+pub use self::executor::SharedExec;
 pub(crate) use self::grpc_timeout::GrpcTimeout;
@@ -51,7 +51,9 @@ impl AsMut<[u8]> for Certificate {
 impl Identity {
     /// Parse a PEM encoded certificate and private key.
     ///
-    /// The provided cert must contain at least one PEM encoded certificate.
+    /// The provided cert must contain at least one PEM encoded certificate. The key may be a
+    /// PKCS#1 (`RSA PRIVATE KEY`), PKCS#8 (`PRIVATE KEY`) or SEC1 (`EC PRIVATE KEY`) key, which
+    /// is told apart by its PEM label. Encrypted keys are not supported.
     pub fn from_pem(cert: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> Self {
         let cert = Certificate::from_pem(cert);
         let key = key.as_ref().into();